@@ -0,0 +1,32 @@
+//! Feeds arbitrary bodies to every parser of the sign route. Each must either parse or fail
+//! cleanly, and whatever parses must get as far as its signing root without panicking.
+//!
+//! Run with `cargo fuzz run sign_request` from the repo root.
+
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use puffersecuresigner::eth2::eth_signing::BLSSignMsg;
+use puffersecuresigner::eth2::eth_types::{ForkSchedule, GENESIS_FORK_VERSION};
+use puffersecuresigner::eth2::ssz_request;
+
+fuzz_target!(|body: &[u8]| {
+    let parsed = [
+        serde_json::from_slice::<BLSSignMsg>(body).ok(),
+        BLSSignMsg::from_json_strict(body).ok(),
+        // The batch routes parse their items out of an already parsed JSON value
+        serde_json::from_slice::<serde_json::Value>(body)
+            .ok()
+            .and_then(|value| serde_json::from_value::<BLSSignMsg>(value).ok()),
+        ssz_request::from_ssz_bytes(body).ok(),
+    ];
+    let fork_schedule = ForkSchedule::for_genesis_fork_version(GENESIS_FORK_VERSION)
+        .expect("mainnet has a fork schedule");
+    for req in parsed.into_iter().flatten() {
+        let _ = req.type_name();
+        let _ = req.is_slashable_type();
+        let _ = req.subcommittee_index();
+        let _ = req.validate_fork_info(&fork_schedule);
+        let _ = req.to_signing_root(Some(GENESIS_FORK_VERSION));
+    }
+});
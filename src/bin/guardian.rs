@@ -3,7 +3,8 @@ use puffersecuresigner::{eth2::eth_types::Version, strip_0x_prefix};
 
 #[tokio::main]
 async fn main() {
-    tracing_subscriber::fmt::init();
+    // Truncate every validator pubkey in the logs
+    puffersecuresigner::enclave::shared::logging::init(std::env::var("REDACT_PUBKEYS").is_ok());
 
     let port = std::env::args()
         .nth(1)
@@ -18,9 +19,20 @@ async fn main() {
         &hex::decode(&genesis_fork_version_str).expect("Bad genesis_fork_version"),
     );
 
+    // Directory holding the keys and slashing protection databases
+    let data_dir = std::env::var("SECURE_SIGNER_DATA_DIR")
+        .unwrap_or(puffersecuresigner::constants::DEFAULT_DATA_DIR.to_string());
+    // Enclave identity keys are sealed to, "mrsigner" (the default) survives upgrades, "mrenclave" doesn't
+    let sealing_policy = std::env::var("SEALING_POLICY")
+        .map(|p| p.parse().expect("BAD SEALING_POLICY"))
+        .unwrap_or_default();
+    puffersecuresigner::io::config::Config::new(&data_dir)
+        .with_sealing_policy(sealing_policy)
+        .install();
+
     println!(
-        "Starting SGX Guardian: localhost:{}, using genesis_fork_version: {:?}",
-        port, genesis_fork_version
+        "Starting SGX Guardian: localhost:{}, using genesis_fork_version: {:?}, data_dir: {}",
+        port, genesis_fork_version, data_dir
     );
 
     let app = axum::Router::new()
@@ -29,6 +41,26 @@ async fn main() {
             "/upcheck",
             axum::routing::get(puffersecuresigner::enclave::shared::handlers::health::handler),
         )
+        // Endpoint to check that keys can be sealed and persisted
+        .route(
+            "/healthz",
+            axum::routing::get(puffersecuresigner::enclave::shared::handlers::healthz::handler),
+        )
+        // Endpoint to re-seal every stored key under the configured sealing policy after an upgrade
+        .route(
+            "/eth/v1/reseal",
+            axum::routing::post(puffersecuresigner::enclave::shared::handlers::reseal::handler),
+        )
+        // Endpoint to expose the build and enclave configuration
+        .route(
+            "/version",
+            axum::routing::get(puffersecuresigner::enclave::shared::handlers::version::handler),
+        )
+        // Endpoint to expose the running enclave's measurements
+        .route(
+            "/eth/v1/enclave-info",
+            axum::routing::get(puffersecuresigner::enclave::shared::handlers::enclave_info::handler),
+        )
         // Endpoint generated a new ETH key
         .route(
             "/eth/v1/keygen",
@@ -57,11 +89,36 @@ async fn main() {
                 puffersecuresigner::enclave::guardian::handlers::sign_exit::handler,
             ),
         )
-        ;
+        // Log every request in a span carrying its id, echoed in the X-Request-Id response header
+        .layer(axum::middleware::from_fn(
+            puffersecuresigner::enclave::shared::request_log::middleware,
+        ));
 
-    let addr = std::net::SocketAddr::from(([0, 0, 0, 0], port));
+    // Listen on LISTEN_ADDR if set, either host:port or unix:<path>, else on every interface
+    let listen_addr = std::env::var("LISTEN_ADDR")
+        .map(|a| a.parse().expect("BAD LISTEN_ADDR"))
+        .unwrap_or(
+            puffersecuresigner::enclave::shared::server::ListenAddr::Tcp(
+                std::net::SocketAddr::from(([0, 0, 0, 0], port)),
+            ),
+        );
+    let server = puffersecuresigner::enclave::shared::server::Server::bind(&listen_addr)
+        .expect("Failed to bind port");
 
-    _ = axum::Server::bind(&addr)
-        .serve(app.into_make_service())
-        .await;
+    // On SIGTERM stop accepting connections and give in-flight requests time to finish
+    let shutdown_grace = std::env::var("SHUTDOWN_GRACE_SECS")
+        .map(|s| s.parse::<u64>().expect("BAD SHUTDOWN_GRACE_SECS"))
+        .map(std::time::Duration::from_secs)
+        .unwrap_or(puffersecuresigner::enclave::shared::shutdown::DEFAULT_SHUTDOWN_GRACE);
+    if let Err(e) = server
+        .with_shutdown_grace(shutdown_grace)
+        .serve(
+            app,
+            Default::default(),
+            puffersecuresigner::enclave::shared::shutdown::signal(),
+        )
+        .await
+    {
+        log::error!("{:?}", e);
+    }
 }
@@ -1,9 +1,13 @@
 extern crate puffersecuresigner;
-use puffersecuresigner::{eth2::eth_types::Version, strip_0x_prefix};
+use puffersecuresigner::{
+    eth2::eth_types::{genesis_time_for_genesis_fork_version, ForkSchedule, Version},
+    strip_0x_prefix,
+};
 
 #[tokio::main]
 async fn main() {
-    tracing_subscriber::fmt::init();
+    // Truncate every validator pubkey in the logs
+    puffersecuresigner::enclave::shared::logging::init(std::env::var("REDACT_PUBKEYS").is_ok());
 
     let port = std::env::args()
         .nth(1)
@@ -18,21 +22,364 @@ async fn main() {
         &hex::decode(&genesis_fork_version_str).expect("Bad genesis_fork_version"),
     );
 
+    // Directory holding the keys and slashing protection databases
+    let data_dir = std::env::var("SECURE_SIGNER_DATA_DIR")
+        .unwrap_or(puffersecuresigner::constants::DEFAULT_DATA_DIR.to_string());
+    // Where slashing protection histories are kept, JSON files unless set to "sqlite"
+    let slashing_protection_backend = std::env::var("SLASHING_PROTECTION_BACKEND")
+        .map(|b| b.parse().expect("BAD SLASHING_PROTECTION_BACKEND"))
+        .unwrap_or_default();
+    // Enclave identity keys are sealed to, "mrsigner" (the default) survives upgrades, "mrenclave" doesn't
+    let sealing_policy = std::env::var("SEALING_POLICY")
+        .map(|p| p.parse().expect("BAD SEALING_POLICY"))
+        .unwrap_or_default();
+    // "memory" keeps all state in memory instead of under the data dir, lost on restart
+    let storage = std::env::var("STORAGE")
+        .map(|s| s.parse().expect("BAD STORAGE"))
+        .unwrap_or_default();
+    puffersecuresigner::io::config::Config::new(&data_dir)
+        .with_slashing_protection_backend(slashing_protection_backend)
+        .with_sealing_policy(sealing_policy)
+        .with_storage(storage)
+        .install();
+
     println!(
-        "Starting SGX Secure-Signer: localhost:{}, using genesis_fork_version: {:?}",
-        port, genesis_fork_version
+        "Starting SGX Secure-Signer: localhost:{}, using genesis_fork_version: {:?}, data_dir: {}",
+        port, genesis_fork_version, data_dir
+    );
+
+    // Reject signing requests whose fork_info disagrees with the network's fork schedule if
+    // ENFORCE_FORK_SCHEDULE=true
+    let enforce_fork_schedule = std::env::var("ENFORCE_FORK_SCHEDULE")
+        .map(|v| v.parse::<bool>().expect("BAD ENFORCE_FORK_SCHEDULE"))
+        .unwrap_or(false);
+    let fork_schedule = match enforce_fork_schedule {
+        true => ForkSchedule::for_genesis_fork_version(genesis_fork_version)
+            .expect("No known fork schedule for genesis_fork_version"),
+        false => ForkSchedule::default(),
+    };
+
+    // Only sign under these comma-separated fork versions and genesis_validators_roots, any if unset
+    let allowed_domains = puffersecuresigner::eth2::eth_types::AllowedDomains::parse(
+        &std::env::var("ALLOWED_FORK_VERSIONS").unwrap_or_default(),
+        &std::env::var("ALLOWED_GENESIS_VALIDATORS_ROOTS").unwrap_or_default(),
+    )
+    .expect("BAD ALLOWED_FORK_VERSIONS or ALLOWED_GENESIS_VALIDATORS_ROOTS");
+
+    // Require a distinct X-Request-Id on every sign request
+    let request_ids = match std::env::var("STRICT_REQUEST_ID") {
+        Ok(_) => Some(puffersecuresigner::enclave::shared::request_id::RequestIdCache::default()),
+        Err(_) => None,
+    };
+
+    // Answer exact retries within SIGN_RESPONSE_CACHE_SECS with the response already sent
+    let response_cache = std::env::var("SIGN_RESPONSE_CACHE_SECS").ok().map(|secs| {
+        let window = secs
+            .parse::<u64>()
+            .expect("BAD SIGN_RESPONSE_CACHE_SECS");
+        let capacity = std::env::var("SIGN_RESPONSE_CACHE_SIZE")
+            .map(|s| s.parse::<usize>().expect("BAD SIGN_RESPONSE_CACHE_SIZE"))
+            .unwrap_or(
+                puffersecuresigner::enclave::shared::response_cache::DEFAULT_SIGN_RESPONSE_CACHE_SIZE,
+            );
+        puffersecuresigner::enclave::shared::response_cache::ResponseCache::new(
+            std::time::Duration::from_secs(window),
+            capacity,
+        )
+    });
+
+    // Allow BLS secret keys to be exported out of the enclave
+    let allow_key_export = std::env::var("ALLOW_KEY_EXPORT").is_ok();
+
+    // Allow signing raw signing roots, which bypasses slashing protection
+    let allow_raw_signing = std::env::var("ALLOW_RAW_SIGNING").is_ok();
+
+    // Allow importing slashing protection interchanges from files under this dir, e.g. a mounted volume
+    let slash_protection_import_dir = std::env::var("SLASH_PROTECTION_IMPORT_DIR")
+        .ok()
+        .map(std::path::PathBuf::from);
+
+    // Allow importing keystores in bulk from a zip or tar archive
+    let allow_keystore_archive_import = std::env::var("ALLOW_KEYSTORE_ARCHIVE_IMPORT").is_ok();
+
+    // Only accept imported validator keys, the BLS keygen route answers 404. The ETH keygen route
+    // stays, imported keystore passwords are encrypted to the key it generates.
+    let import_only = std::env::var("IMPORT_ONLY").is_ok();
+
+    // Refuse to import a keystore unless an interchange carries its slashing protection history
+    let require_import_slashing_protection =
+        std::env::var("REQUIRE_IMPORT_SLASHING_PROTECTION").is_ok();
+
+    // Require sign requests to carry an HMAC under the secret provisioned when their key was imported
+    // or generated
+    let require_request_mac = std::env::var("REQUIRE_REQUEST_MAC").is_ok();
+
+    // Reject JSON sign requests with fields their type doesn't define instead of ignoring them
+    let deny_unknown_fields = std::env::var("DENY_UNKNOWN_FIELDS").is_ok();
+
+    // Refuse sync committee selection proofs and contributions for an out of range subcommittee
+    let validate_subcommittee_index = std::env::var("VALIDATE_SUBCOMMITTEE_INDEX").is_ok();
+
+    // Bind each key to the first genesis_validators_root it signs under
+    let bind_genesis_root = std::env::var("BIND_GENESIS_ROOT").is_ok();
+
+    // Refuse validator registrations that aren't newer than the last one signed for their key
+    let monotonic_registration_timestamps =
+        std::env::var("MONOTONIC_REGISTRATION_TIMESTAMPS").is_ok();
+
+    // Refuse validator registrations requesting a gas limit outside of these bounds
+    let gas_limit_bounds = {
+        let default = puffersecuresigner::eth2::eth_types::GasLimitBounds::default();
+        let bounds = puffersecuresigner::eth2::eth_types::GasLimitBounds {
+            min: std::env::var("MIN_GAS_LIMIT")
+                .map(|g| g.parse::<u64>().expect("BAD MIN_GAS_LIMIT"))
+                .unwrap_or(default.min),
+            max: std::env::var("MAX_GAS_LIMIT")
+                .map(|g| g.parse::<u64>().expect("BAD MAX_GAS_LIMIT"))
+                .unwrap_or(default.max),
+        };
+        if bounds.min > bounds.max {
+            panic!("MIN_GAS_LIMIT exceeds MAX_GAS_LIMIT");
+        }
+        bounds
+    };
+
+    // Reject voluntary exits for epochs closer than this to the current epoch
+    let min_exit_epoch_distance = std::env::var("MIN_EXIT_EPOCH_DISTANCE")
+        .ok()
+        .map(|d| d.parse::<u64>().expect("BAD MIN_EXIT_EPOCH_DISTANCE"));
+    let genesis_time = match std::env::var("GENESIS_TIME") {
+        Ok(t) => t.parse::<u64>().expect("BAD GENESIS_TIME"),
+        Err(_) => genesis_time_for_genesis_fork_version(genesis_fork_version).unwrap_or_default(),
+    };
+    if min_exit_epoch_distance.is_some() && genesis_time == 0 {
+        panic!("MIN_EXIT_EPOCH_DISTANCE requires GENESIS_TIME for this network");
+    }
+
+    // Escalate to an error log when the share of 5xx and 412 responses exceeds this
+    let error_rate = puffersecuresigner::enclave::shared::metrics::ErrorRateMonitor::new(
+        puffersecuresigner::enclave::shared::metrics::DEFAULT_ERROR_RATE_WINDOW,
+        std::env::var("ERROR_RATE_THRESHOLD")
+            .ok()
+            .map(|t| t.parse::<f64>().expect("BAD ERROR_RATE_THRESHOLD")),
+    );
+
+    // Queue signing requests past the concurrency limit instead of running them all at once
+    let signing_queue = std::env::var("SIGNING_QUEUE_DEPTH").ok().map(|depth| {
+        puffersecuresigner::enclave::shared::queue::SigningQueue::new(
+            std::env::var("SIGNING_CONCURRENCY")
+                .map(|c| c.parse::<usize>().expect("BAD SIGNING_CONCURRENCY"))
+                .unwrap_or_else(|_| std::thread::available_parallelism().map_or(1, |n| n.get())),
+            depth.parse::<usize>().expect("BAD SIGNING_QUEUE_DEPTH"),
+            std::env::var("SIGNING_QUEUE_MAX_WAIT_MS")
+                .map(|w| {
+                    std::time::Duration::from_millis(
+                        w.parse::<u64>().expect("BAD SIGNING_QUEUE_MAX_WAIT_MS"),
+                    )
+                })
+                .unwrap_or(
+                    puffersecuresigner::enclave::shared::queue::DEFAULT_SIGNING_QUEUE_MAX_WAIT,
+                ),
+        )
+    });
+
+    // Refuse to generate or import BLS keys beyond MAX_KEYS
+    let key_limit = std::env::var("MAX_KEYS").ok().map(|m| {
+        puffersecuresigner::enclave::shared::key_limit::KeyLimit::new(
+            m.parse::<usize>().expect("BAD MAX_KEYS"),
+        )
+    });
+
+    // Bound how long a signing request waits for another request on the same key to finish
+    let key_locks = puffersecuresigner::enclave::shared::key_lock::KeyLocks::new(
+        std::env::var("KEY_LOCK_TIMEOUT_MS")
+            .map(|t| {
+                std::time::Duration::from_millis(t.parse::<u64>().expect("BAD KEY_LOCK_TIMEOUT_MS"))
+            })
+            .unwrap_or(puffersecuresigner::enclave::shared::key_lock::DEFAULT_KEY_LOCK_TIMEOUT),
     );
 
+    // Let browser dashboards on CORS_ALLOWED_ORIGINS call the read-only routes, or CORS_ROUTES
+    let mut cors = puffersecuresigner::enclave::shared::cors::Cors::new(
+        &std::env::var("CORS_ALLOWED_ORIGINS").unwrap_or_default(),
+    )
+    .expect("BAD CORS_ALLOWED_ORIGINS");
+    if let Ok(methods) = std::env::var("CORS_ALLOWED_METHODS") {
+        cors = cors
+            .with_methods(&methods)
+            .expect("BAD CORS_ALLOWED_METHODS");
+    }
+    if let Ok(routes) = std::env::var("CORS_ROUTES") {
+        cors = cors.with_routes(&routes).expect("BAD CORS_ROUTES");
+    }
+
+    // Answer signing that takes longer than SIGN_TIMEOUT_MS with 504 so the client can still act
+    let sign_timeout = std::env::var("SIGN_TIMEOUT_MS")
+        .ok()
+        .map(|t| std::time::Duration::from_millis(t.parse::<u64>().expect("BAD SIGN_TIMEOUT_MS")));
+
+    // Let clients pin the genesis_validators_root through a handshake authenticated with this
+    // token, for SESSION_TTL_SECS with at most SESSION_MAX sessions open
+    let sessions = std::env::var("SESSION_TOKEN").ok().map(|token| {
+        let ttl = std::env::var("SESSION_TTL_SECS")
+            .map(|s| {
+                std::time::Duration::from_secs(s.parse::<u64>().expect("BAD SESSION_TTL_SECS"))
+            })
+            .unwrap_or(puffersecuresigner::enclave::shared::session::DEFAULT_SESSION_TTL);
+        let max_sessions = std::env::var("SESSION_MAX")
+            .map(|s| s.parse::<usize>().expect("BAD SESSION_MAX"))
+            .unwrap_or(puffersecuresigner::enclave::shared::session::DEFAULT_MAX_SESSIONS);
+        puffersecuresigner::enclave::shared::session::SessionStore::with_limits(
+            token,
+            ttl,
+            max_sessions,
+        )
+    });
+
+    // Serve the effective config to callers presenting this bearer token, disabled if unset
+    let config_token = std::env::var("CONFIG_TOKEN").ok();
+
+    // Comma separated labels keys may be tagged with to break down the signing metrics
+    let metrics_labels = puffersecuresigner::enclave::shared::metrics::MetricsLabels::parse(
+        &std::env::var("METRICS_LABELS").unwrap_or_default(),
+    )
+    .expect("BAD METRICS_LABELS");
+
+    // Safety margin added to the high-water marks in the slashing protection floors
+    let slash_protection_floor_margin = std::env::var("SLASH_PROTECTION_FLOOR_MARGIN")
+        .map(|m| m.parse::<u64>().expect("BAD SLASH_PROTECTION_FLOOR_MARGIN"))
+        .unwrap_or_default();
+
+    // Largest request bodies accepted by the signing and import routes, larger ones get a 413
+    let sign_body_limit = std::env::var("SIGN_BODY_LIMIT_BYTES")
+        .map(|l| l.parse::<usize>().expect("BAD SIGN_BODY_LIMIT_BYTES"))
+        .unwrap_or(puffersecuresigner::constants::DEFAULT_SIGN_BODY_LIMIT_BYTES);
+    let import_body_limit = std::env::var("IMPORT_BODY_LIMIT_BYTES")
+        .map(|l| l.parse::<usize>().expect("BAD IMPORT_BODY_LIMIT_BYTES"))
+        .unwrap_or(puffersecuresigner::constants::DEFAULT_IMPORT_BODY_LIMIT_BYTES);
+
     let app_state = puffersecuresigner::enclave::shared::handlers::AppState {
         genesis_fork_version,
+        fork_schedule,
+        allowed_domains,
+        request_ids,
+        response_cache,
+        gas_limit_bounds,
+        monotonic_registration_timestamps,
+        min_exit_epoch_distance,
+        genesis_time,
+        error_rate,
+        signing_queue,
+        sessions,
+        config_token,
+        metrics_labels,
+        slash_protection_floor_margin,
+        slash_protection_import_dir,
+        key_limit,
+        key_locks,
+        sign_timeout,
+        allow_key_export,
+        allow_raw_signing,
+        allow_keystore_archive_import,
+        import_only,
+        require_import_slashing_protection,
+        require_request_mac,
+        bind_genesis_root,
+        deny_unknown_fields,
+        validate_subcommittee_index,
+        empty_slash_history: Default::default(),
+        sign_stats: Default::default(),
+        in_flight: Default::default(),
+        readiness: Default::default(),
     };
 
+    let in_flight = app_state.in_flight.clone();
+
+    // Report ready to /readyz once keys can be listed, sealed and persisted
+    tokio::spawn(
+        app_state
+            .readiness
+            .clone()
+            .initialize(puffersecuresigner::enclave::shared::readiness::DEFAULT_READINESS_RETRY),
+    );
+
+    // Write buffered per-key signing stats to disk every SIGN_STATS_FLUSH_SECS
+    let sign_stats = app_state.sign_stats.clone();
+    let sign_stats_flush_interval = std::env::var("SIGN_STATS_FLUSH_SECS")
+        .map(|s| s.parse::<u64>().expect("BAD SIGN_STATS_FLUSH_SECS"))
+        .map(std::time::Duration::from_secs)
+        .unwrap_or(
+            puffersecuresigner::enclave::shared::sign_stats::DEFAULT_SIGN_STATS_FLUSH_INTERVAL,
+        );
+    tokio::spawn(sign_stats.clone().flush_every(sign_stats_flush_interval));
+
+    // Copy the slashing protection histories to this dir every SLASH_PROTECTION_BACKUP_SECS, and restore from it on startup
+    if let Ok(backup_dir) = std::env::var("SLASH_PROTECTION_BACKUP_DIR") {
+        let backup =
+            puffersecuresigner::eth2::slash_protection::SlashProtectionBackup::new(backup_dir);
+        for result in backup
+            .restore()
+            .expect("Failed to restore slash protection backup")
+        {
+            log::info!(
+                "Slash protection backup of {}: {:?} {}",
+                result.pubkey,
+                result.status,
+                result.detail
+            );
+        }
+        let backup_interval = std::env::var("SLASH_PROTECTION_BACKUP_SECS")
+            .map(|s| s.parse::<u64>().expect("BAD SLASH_PROTECTION_BACKUP_SECS"))
+            .map(std::time::Duration::from_secs)
+            .unwrap_or(
+                puffersecuresigner::eth2::slash_protection::DEFAULT_SLASH_PROTECTION_BACKUP_INTERVAL,
+            );
+        tokio::spawn(backup.backup_every(backup_interval));
+    }
+
     let app = axum::Router::new()
         // Endpoint to check health
         .route(
             "/upcheck",
             axum::routing::get(puffersecuresigner::enclave::shared::handlers::health::handler),
         )
+        // Liveness probe, 200 as long as the process is up
+        .route(
+            "/livez",
+            axum::routing::get(puffersecuresigner::enclave::shared::handlers::livez::handler),
+        )
+        // Readiness probe, 503 until startup completes
+        .route(
+            "/readyz",
+            axum::routing::get(puffersecuresigner::enclave::shared::handlers::readyz::handler),
+        )
+        // Endpoint to check that keys can be sealed and persisted
+        .route(
+            "/healthz",
+            axum::routing::get(puffersecuresigner::enclave::shared::handlers::healthz::handler),
+        )
+        // Endpoint to re-seal every stored key under the configured sealing policy after an upgrade
+        .route(
+            "/eth/v1/reseal",
+            axum::routing::post(puffersecuresigner::enclave::shared::handlers::reseal::handler),
+        )
+        // Endpoint to expose the build and enclave configuration
+        .route(
+            "/version",
+            axum::routing::get(puffersecuresigner::enclave::shared::handlers::version::handler),
+        )
+        // Endpoint to expose the running enclave's measurements
+        .route(
+            "/eth/v1/enclave-info",
+            axum::routing::get(
+                puffersecuresigner::enclave::shared::handlers::enclave_info::handler,
+            ),
+        )
+        // Endpoint to expose the signing error rate metrics
+        .route(
+            "/metrics",
+            axum::routing::get(puffersecuresigner::enclave::shared::handlers::metrics::handler),
+        )
         // Endpoint to securely generate and save an ETH sk
         .route(
             "/eth/v1/keygen/secp256k1",
@@ -59,6 +406,64 @@ async fn main() {
             "/eth/v1/keystores",
             axum::routing::get(
                 puffersecuresigner::enclave::shared::handlers::list_bls_keys::handler,
+            )
+            // Endpoint to import a keystore whose password is encrypted to an enclave ETH key
+            .post(puffersecuresigner::enclave::secure_signer::handlers::bls_import::handler)
+            .layer(axum::extract::DefaultBodyLimit::max(import_body_limit)),
+        )
+        // Endpoint to check whether a bls key is saved, without listing every key
+        .route(
+            "/eth/v1/keystores/:bls_pk_hex",
+            axum::routing::get(
+                puffersecuresigner::enclave::shared::handlers::keystore_exists::handler,
+            ),
+        )
+        // Web3Signer endpoint to list the pks that can be signed with
+        .route(
+            "/api/v1/eth2/publicKeys",
+            axum::routing::get(
+                puffersecuresigner::enclave::shared::handlers::list_public_keys::handler,
+            ),
+        )
+        // Keymanager endpoint to list remote keys, always empty since all keys are in the enclave
+        .route(
+            "/eth/v1/remotekeys",
+            axum::routing::get(
+                puffersecuresigner::enclave::shared::handlers::list_remote_keys::handler,
+            ),
+        )
+        // Endpoint to import keystores and their slashing protection from a zip or tar archive
+        .route(
+            "/eth/v1/keystores/archive",
+            axum::routing::post(
+                puffersecuresigner::enclave::secure_signer::handlers::keystore_archive_import::handler,
+            )
+            .layer(axum::extract::DefaultBodyLimit::max(
+                puffersecuresigner::constants::MAX_KEYSTORE_ARCHIVE_BYTES,
+            )),
+        )
+        // Endpoint to export a bls sk encrypted to a client's ETH pk
+        .route(
+            "/eth/v1/keystores/:bls_pk_hex/export",
+            axum::routing::post(
+                puffersecuresigner::enclave::secure_signer::handlers::bls_export::handler,
+            ),
+        )
+        // Endpoint to import an EIP-3076 slashing protection interchange, or export one in the
+        // complete or minimal format
+        .route(
+            "/eth/v1/slashing-protection",
+            axum::routing::post(
+                puffersecuresigner::enclave::shared::handlers::import_slash_protection::handler,
+            )
+            .layer(axum::extract::DefaultBodyLimit::max(import_body_limit))
+            .get(puffersecuresigner::enclave::shared::handlers::export_slash_protection::handler),
+        )
+        // Endpoint to import an EIP-3076 interchange from a file under SLASH_PROTECTION_IMPORT_DIR
+        .route(
+            "/eth/v1/slashing-protection/file",
+            axum::routing::post(
+                puffersecuresigner::enclave::shared::handlers::import_slash_protection_file::handler,
             ),
         )
         // Endpoint to sign DepositData message for registering validator on beacon chain
@@ -66,6 +471,57 @@ async fn main() {
             "/api/v1/eth2/deposit",
             axum::routing::post(
                 puffersecuresigner::enclave::secure_signer::handlers::validator_deposit::handler,
+            )
+            .layer(axum::extract::DefaultBodyLimit::max(sign_body_limit)),
+        )
+        // Endpoint to expose the effective config, without secrets
+        .route(
+            "/eth/v1/config",
+            axum::routing::get(puffersecuresigner::enclave::shared::handlers::config::handler),
+        )
+        // Endpoint to pin the genesis_validators_root for later sign requests
+        .route(
+            "/api/v1/eth2/session",
+            axum::routing::post(puffersecuresigner::enclave::shared::handlers::session::handler),
+        )
+        // Endpoint to fetch the latest slot and epochs a key signed
+        .route(
+            "/eth/v1/slashing/:bls_pk_hex",
+            axum::routing::get(
+                puffersecuresigner::enclave::shared::handlers::high_water_marks::handler,
+            ),
+        )
+        // Endpoint to fetch how many blocks and attestations a key signed and when it last signed
+        .route(
+            "/eth/v1/stats/:bls_pk_hex",
+            axum::routing::get(puffersecuresigner::enclave::shared::handlers::sign_stats::handler),
+        )
+        // Endpoint to raise the minimum slot and epochs a key will sign for
+        .route(
+            "/eth/v1/keystores/:bls_pk_hex/signing-floor",
+            axum::routing::post(
+                puffersecuresigner::enclave::shared::handlers::signing_floor::handler,
+            ),
+        )
+        // Endpoint to tag a key's signing metrics with one of the configured labels
+        .route(
+            "/eth/v1/keystores/:bls_pk_hex/metrics-label",
+            axum::routing::post(
+                puffersecuresigner::enclave::shared::handlers::metrics_label::handler,
+            ),
+        )
+        // Endpoint to fetch the slashing protection floors a client should resume from
+        .route(
+            "/eth/v1/slashing-protection/floor",
+            axum::routing::get(
+                puffersecuresigner::enclave::shared::handlers::slash_protection_floor::handler,
+            ),
+        )
+        // Endpoint to collapse every key's slashing protection history to its high-water marks
+        .route(
+            "/eth/v1/slashing-protection/prune",
+            axum::routing::post(
+                puffersecuresigner::enclave::shared::handlers::prune_slash_protection::handler,
             ),
         )
         // Endpoint to request a signature using BLS sk
@@ -73,13 +529,98 @@ async fn main() {
             "/api/v1/eth2/sign/:bls_pk_hex",
             axum::routing::post(
                 puffersecuresigner::enclave::shared::handlers::secure_sign_bls::handler,
+            )
+            .layer(axum::extract::DefaultBodyLimit::max(sign_body_limit)),
+        )
+        // Endpoint to sign a raw signing root, bypassing slashing protection
+        .route(
+            "/api/v1/eth2/sign/:bls_pk_hex/raw",
+            axum::routing::post(
+                puffersecuresigner::enclave::secure_signer::handlers::sign_raw::handler,
+            )
+            .layer(axum::extract::DefaultBodyLimit::max(sign_body_limit)),
+        )
+        // Endpoint to sign a batch of BLS to execution changes, each with its from_bls_pubkey's key
+        .route(
+            "/api/v1/eth2/sign/bls-to-execution-change/batch",
+            axum::routing::post(
+                puffersecuresigner::enclave::shared::handlers::sign_bls_to_execution_change_batch::handler,
+            )
+            .layer(axum::extract::DefaultBodyLimit::max(import_body_limit)),
+        )
+        // Endpoint to aggregate signatures over the same message
+        .route(
+            "/eth/v1/aggregate",
+            axum::routing::post(
+                puffersecuresigner::enclave::shared::handlers::aggregate_signatures::handler,
+            ),
+        )
+        // Endpoint to verify a signature
+        .route(
+            "/eth/v1/verify",
+            axum::routing::post(puffersecuresigner::enclave::shared::handlers::verify::handler),
+        )
+        // Endpoint to verify a batch of signatures
+        .route(
+            "/api/v1/eth2/verify/batch",
+            axum::routing::post(
+                puffersecuresigner::enclave::shared::handlers::verify_batch::handler,
             ),
         )
+        // Add CORS headers for allowed origins, never on the signing, deposit or export routes
+        .layer(axum::middleware::from_fn_with_state(
+            cors,
+            puffersecuresigner::enclave::shared::cors::middleware,
+        ))
+        // Log every request in a span carrying its id, echoed in the X-Request-Id response header
+        .layer(axum::middleware::from_fn(
+            puffersecuresigner::enclave::shared::request_log::middleware,
+        ))
         .with_state(app_state);
 
-    let addr = std::net::SocketAddr::from(([0, 0, 0, 0], port));
+    // Listen on LISTEN_ADDR if set, either host:port or unix:<path>, else on every interface
+    let listen_addr = std::env::var("LISTEN_ADDR")
+        .map(|a| a.parse().expect("BAD LISTEN_ADDR"))
+        .unwrap_or(
+            puffersecuresigner::enclave::shared::server::ListenAddr::Tcp(
+                std::net::SocketAddr::from(([0, 0, 0, 0], port)),
+            ),
+        );
+    let server = puffersecuresigner::enclave::shared::server::Server::bind(&listen_addr)
+        .expect("Failed to bind port");
 
-    _ = axum::Server::bind(&addr)
-        .serve(app.into_make_service())
-        .await;
+    // On SIGTERM stop accepting connections and give in-flight requests time to finish
+    let shutdown_grace = std::env::var("SHUTDOWN_GRACE_SECS")
+        .map(|s| s.parse::<u64>().expect("BAD SHUTDOWN_GRACE_SECS"))
+        .map(std::time::Duration::from_secs)
+        .unwrap_or(puffersecuresigner::enclave::shared::shutdown::DEFAULT_SHUTDOWN_GRACE);
+    // Serve at most MAX_CONNECTIONS connections at once, leaving the rest in the listen backlog
+    let max_connections = std::env::var("MAX_CONNECTIONS")
+        .ok()
+        .map(|m| m.parse::<usize>().expect("BAD MAX_CONNECTIONS"));
+    // Close keep-alive connections left waiting for a request this long
+    let keep_alive_timeout = std::env::var("KEEP_ALIVE_TIMEOUT_SECS")
+        .ok()
+        .map(|s| s.parse::<u64>().expect("BAD KEEP_ALIVE_TIMEOUT_SECS"))
+        .map(std::time::Duration::from_secs);
+    if let Err(e) = server
+        .with_shutdown_grace(shutdown_grace)
+        .with_connection_limits(
+            puffersecuresigner::enclave::shared::connections::ConnectionLimits {
+                max_connections,
+                keep_alive_timeout,
+            },
+        )
+        .serve(
+            app,
+            in_flight,
+            puffersecuresigner::enclave::shared::shutdown::signal(),
+        )
+        .await
+    {
+        log::error!("{:?}", e);
+    }
+    if let Err(e) = sign_stats.flush() {
+        log::error!("Failed to flush signing stats: {:?}", e);
+    }
 }
@@ -28,7 +28,9 @@ impl SecureSignerClient {
             .await?)
     }
 
-    pub async fn list_bls_keys(&self) -> anyhow::Result<crate::enclave::types::ListKeysResponse> {
+    pub async fn list_bls_keys(
+        &self,
+    ) -> anyhow::Result<crate::enclave::types::ListKeystoresResponse> {
         Ok(self
             .client
             .get(format!("{}/eth/v1/keystores", self.url))
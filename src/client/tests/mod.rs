@@ -79,6 +79,7 @@ async fn registration_flow_succeeds() {
         guardian_index: 0,
         validator_index: 0,
         fork_info: crate::eth2::eth_types::ForkInfo::default(),
+        genesis_fork_version: crate::eth2::eth_types::GENESIS_FORK_VERSION,
     };
     let resp4: crate::enclave::types::SignExitResponse =
         client.guardian.sign_exit(req).await.unwrap();
@@ -141,6 +142,7 @@ async fn test_cli_keygen_verified_by_guardians() {
         guardian_index: 0,
         validator_index: 0,
         fork_info: crate::eth2::eth_types::ForkInfo::default(),
+        genesis_fork_version: crate::eth2::eth_types::GENESIS_FORK_VERSION,
     };
     let _resp4: crate::enclave::types::SignExitResponse =
         client.guardian.sign_exit(req).await.unwrap();
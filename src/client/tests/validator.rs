@@ -136,7 +136,8 @@ async fn sign_voluntary_exit_message_with_success() {
         voluntary_exit,
     };
 
-    let root = crate::eth2::eth_signing::BLSSignMsg::VOLUNTARY_EXIT(vem_req).to_signing_root(None);
+    let root = crate::eth2::eth_signing::BLSSignMsg::VOLUNTARY_EXIT(vem_req)
+        .to_signing_root(Some(crate::eth2::eth_types::GENESIS_FORK_VERSION));
     assert!(resp
         .public_key_set()
         .unwrap()
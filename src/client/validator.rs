@@ -34,13 +34,15 @@ impl ValidatorClient {
             .await?)
     }
 
-    pub async fn list_bls_keys(&self) -> anyhow::Result<crate::enclave::types::ListKeysResponse> {
+    pub async fn list_bls_keys(
+        &self,
+    ) -> anyhow::Result<crate::enclave::types::ListKeystoresResponse> {
         Ok(self
             .client
             .get(format!("{}/eth/v1/keystores", self.url))
             .send()
             .await?
-            .json::<crate::enclave::types::ListKeysResponse>()
+            .json::<crate::enclave::types::ListKeystoresResponse>()
             .await?)
     }
 
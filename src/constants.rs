@@ -1,14 +1,42 @@
-pub const KEYS_DIR: &str = "./etc/keys/";
-pub const BLS_KEYS_DIR: &str = "./etc/keys/bls_keys/";
-pub const ETH_KEYS_DIR: &str = "./etc/keys/eth_keys/";
-pub const SLASHING_PROTECTION_DIR: &str = "./etc/slashing/";
+pub const DEFAULT_DATA_DIR: &str = "./etc";
+pub const KEYS_SUBDIR: &str = "keys";
+pub const BLS_KEYS_SUBDIR: &str = "bls_keys";
+pub const ETH_KEYS_SUBDIR: &str = "eth_keys";
+pub const REQUEST_MAC_SECRETS_SUBDIR: &str = "mac_secrets";
+pub const SLASHING_PROTECTION_SUBDIR: &str = "slashing";
+pub const SLASHING_PROTECTION_SQLITE_FILE: &str = "slashing.sqlite";
+pub const SIGNING_FLOOR_SUBDIR: &str = "signing_floors";
+pub const GENESIS_BINDING_SUBDIR: &str = "genesis_bindings";
+pub const FEE_RECIPIENT_ALLOWLIST_SUBDIR: &str = "fee_recipients";
+pub const REGISTRATION_TIMESTAMP_SUBDIR: &str = "registration_timestamps";
+pub const KEY_ORIGIN_SUBDIR: &str = "key_origins";
+pub const SIGN_TYPE_ALLOWLIST_SUBDIR: &str = "sign_types";
+pub const SIGN_STATS_SUBDIR: &str = "sign_stats";
+pub const PROPOSER_INDEX_SUBDIR: &str = "proposer_indices";
+pub const METRICS_LABEL_SUBDIR: &str = "metrics_labels";
+pub const MAX_KEYSTORE_ARCHIVE_BYTES: usize = 8 * 1024 * 1024;
+pub const MAX_KEYSTORE_ARCHIVE_ENTRIES: usize = 512;
+/// Default limit on the body of signing requests, larger bodies are refused with a 413
+pub const DEFAULT_SIGN_BODY_LIMIT_BYTES: usize = 1024 * 1024;
+/// Default limit on the body of key and interchange imports, which may carry the history of
+/// many keys
+pub const DEFAULT_IMPORT_BODY_LIMIT_BYTES: usize = 16 * 1024 * 1024;
 
 pub const BLS_SIG_BYTES: usize = 96;
 pub const BLS_PUB_KEY_BYTES: usize = 48;
 pub const BLS_PRIV_KEY_BYTES: usize = 32;
+/// Domain separation tag of Ethereum consensus BLS signatures (proof of possession scheme)
+pub const BLS_SIG_DST: &[u8] = b"BLS_SIG_BLS12381G2_XMD:SHA-256_SSWU_RO_POP_";
+/// Domain separation tag of BLS proofs of possession, so they can't pass as signatures
+pub const BLS_POP_DST: &[u8] = b"BLS_POP_BLS12381G2_XMD:SHA-256_SSWU_RO_POP_";
 pub const ETH_COMPRESSED_PK_BYTES: usize = 33;
 pub const ETH_UNCOMPRESSED_PK_BYTES: usize = 65;
 pub const ETH_SIGNATURE_BYTES: usize = 64;
+pub const EXECUTION_ADDRESS_BYTES: usize = 20;
+/// Default bounds on the gas limit of signed validator registrations, wide enough for the
+/// limits in use on mainnet while refusing obviously malformed values
+pub const DEFAULT_MIN_GAS_LIMIT: u64 = 10_000_000;
+pub const DEFAULT_MAX_GAS_LIMIT: u64 = 100_000_000;
 
 pub const ALLOW_GROWABLE_SLASH_PROTECTION_DB: bool = false;
 
@@ -1,11 +1,12 @@
-use crate::constants::BLS_PUB_KEY_BYTES;
+use crate::constants::{BLS_POP_DST, BLS_PUB_KEY_BYTES, BLS_SIG_DST};
 use crate::io::key_management::{
     read_bls_key, read_bls_keystore, write_bls_key, write_bls_keystore,
 };
 use crate::strip_0x_prefix;
 
 use blsttc::{
-    PublicKeySet, PublicKeyShare, SecretKeySet, SecretKeyShare, Signature, SignatureShare,
+    PublicKey, PublicKeySet, PublicKeyShare, SecretKeySet, SecretKeyShare, Signature,
+    SignatureShare,
 };
 
 use anyhow::{bail, Context, Result};
@@ -21,6 +22,38 @@ pub fn sanitize_bls_pk_hex(bls_pk_hex: &String) -> Result<String> {
     Ok(bls_pk)
 }
 
+/// Strictly validates a BLS public key hex string taken from a request: an optional `0x` prefix
+/// followed by the compressed (96 hex characters) or uncompressed (192 hex characters) encoding
+/// of a valid G1 point, in any casing. Returns the lowercase compressed hex without the prefix.
+pub fn validate_bls_pk_hex(bls_pk_hex: &str) -> Result<String> {
+    let bls_pk = bls_pk_hex
+        .strip_prefix("0x")
+        .or_else(|| bls_pk_hex.strip_prefix("0X"))
+        .unwrap_or(bls_pk_hex);
+    if !bls_pk.chars().all(|c| c.is_ascii_hexdigit()) {
+        bail!("bls_pk_hex must be hex")
+    }
+    let bls_pk = match bls_pk.len() {
+        len if len == 2 * BLS_PUB_KEY_BYTES => bls_pk.to_ascii_lowercase(),
+        len if len == 4 * BLS_PUB_KEY_BYTES => {
+            let bytes = hex::decode(bls_pk)?;
+            match blst::min_pk::PublicKey::key_validate(&bytes) {
+                Ok(pk) => hex::encode(pk.compress()),
+                Err(e) => bail!("bls_pk_hex is not a valid BLS public key: {:?}", e),
+            }
+        }
+        _ => bail!(
+            "bls_pk_hex must be {} or {} hex characters",
+            2 * BLS_PUB_KEY_BYTES,
+            4 * BLS_PUB_KEY_BYTES
+        ),
+    };
+    if let Err(e) = PublicKey::from_hex(&bls_pk) {
+        bail!("bls_pk_hex is not a valid BLS public key: {:?}", e)
+    }
+    Ok(bls_pk)
+}
+
 /// Generate a new BLS secret key
 pub fn new_bls_key(threshold: usize) -> SecretKeySet {
     let mut rng = rand::thread_rng();
@@ -54,7 +87,8 @@ pub fn save_bls_keystore(sk_set: &SecretKeySet, password: &String) -> Result<Str
     Ok(uuid)
 }
 
-/// Read the BLS secret key from a secure file using the hex encoded pk as filename
+/// Read the BLS secret key from a secure file using the hex encoded pk as filename.
+/// Keys are never cached, so key files added to the data dir out-of-band are signable at once.
 pub fn fetch_bls_sk(pk_hex: &String) -> Result<SecretKeySet> {
     let pk_hex: &str = strip_0x_prefix!(pk_hex);
     let sk_bytes = read_bls_key(pk_hex)?;
@@ -84,14 +118,22 @@ pub fn bls_agg_sign(secret_key_set: &SecretKeySet, msg: &[u8]) -> Signature {
 pub fn bls_agg_sign_from_saved_sk(pk_hex: &String, msg: &[u8]) -> Result<Signature> {
     // Fetch the secret key set from memory using the provided pk_hex
     let secret_key_set = fetch_bls_sk(pk_hex)?;
+    bls_agg_sign_checked(pk_hex, &secret_key_set, msg)
+}
 
+/// Like `bls_agg_sign`, but errors unless `secret_key_set` is the key of `pk_hex`
+pub fn bls_agg_sign_checked(
+    pk_hex: &String,
+    secret_key_set: &SecretKeySet,
+    msg: &[u8],
+) -> Result<Signature> {
     // Verify the supplied pk_hex matches the derived
     if pk_hex != &secret_key_set.public_keys().public_key().to_hex() {
         bail!("Mismatch with input and derived pk");
     }
 
     // Sign the message using the fetched secret key set
-    Ok(bls_agg_sign(&secret_key_set, msg))
+    Ok(bls_agg_sign(secret_key_set, msg))
 }
 
 /// Distributes `n` key shares from a given BLS `SecretKeySet`.
@@ -150,10 +192,176 @@ pub fn aggregate_signature_shares(
     Ok(aggregated_signature)
 }
 
+/// Outcome of verifying a batch of signatures
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BatchVerification {
+    /// Per-item verification result, in the order the items were given
+    pub valid: Vec<bool>,
+    /// True if every well-formed item was accepted by a single batch verification
+    pub batch_verified: bool,
+}
+
+/// Verifies `(pubkey, msg, signature)` triples using blst's batch verification.
+/// Items whose public key or signature fail to deserialize are invalid. If the batch
+/// check fails, the well-formed items are verified one by one to find the invalid ones.
+pub fn bls_batch_verify(items: &[(Vec<u8>, Vec<u8>, Vec<u8>)]) -> BatchVerification {
+    let mut valid = vec![false; items.len()];
+
+    // Deserialize with the subgroup checks so they can be skipped during verification
+    let parsed: Vec<(
+        usize,
+        blst::min_pk::PublicKey,
+        &[u8],
+        blst::min_pk::Signature,
+    )> = items
+        .iter()
+        .enumerate()
+        .filter_map(|(i, (pk, msg, sig))| {
+            let pk = blst::min_pk::PublicKey::key_validate(pk).ok()?;
+            let sig = blst::min_pk::Signature::sig_validate(sig, true).ok()?;
+            Some((i, pk, msg.as_slice(), sig))
+        })
+        .collect();
+
+    if parsed.is_empty() {
+        return BatchVerification {
+            valid,
+            batch_verified: false,
+        };
+    }
+
+    let msgs: Vec<&[u8]> = parsed.iter().map(|(_, _, msg, _)| *msg).collect();
+    let pks: Vec<&blst::min_pk::PublicKey> = parsed.iter().map(|(_, pk, _, _)| pk).collect();
+    let sigs: Vec<&blst::min_pk::Signature> = parsed.iter().map(|(_, _, _, sig)| sig).collect();
+
+    // Random 64 bit scalars stop invalid signatures from cancelling each other out
+    let rands: Vec<blst::blst_scalar> = parsed
+        .iter()
+        .map(|_| {
+            let mut b = [0_u8; 32];
+            b[..8].copy_from_slice(&rand::random::<u64>().max(1).to_le_bytes());
+            blst::blst_scalar { b }
+        })
+        .collect();
+
+    let batch_verified = blst::min_pk::Signature::verify_multiple_aggregate_signatures(
+        &msgs,
+        BLS_SIG_DST,
+        &pks,
+        false,
+        &sigs,
+        false,
+        &rands,
+        64,
+    ) == blst::BLST_ERROR::BLST_SUCCESS;
+
+    for (i, pk, msg, sig) in parsed.iter() {
+        valid[*i] = batch_verified
+            || sig.verify(false, msg, BLS_SIG_DST, &[], pk, false)
+                == blst::BLST_ERROR::BLST_SUCCESS;
+    }
+
+    BatchVerification {
+        valid,
+        batch_verified,
+    }
+}
+
+/// Verifies a compressed BLS signature over `msg` under the `BLS_SIG_DST` ciphersuite.
+/// Errors if the public key or signature fail to deserialize or their subgroup checks.
+pub fn bls_verify(pk: &[u8], msg: &[u8], sig: &[u8]) -> Result<bool> {
+    let pk = blst::min_pk::PublicKey::key_validate(pk)
+        .map_err(|e| anyhow::anyhow!("Bad public key: {:?}", e))?;
+    let sig = blst::min_pk::Signature::sig_validate(sig, true)
+        .map_err(|e| anyhow::anyhow!("Bad signature: {:?}", e))?;
+    Ok(sig.verify(false, msg, BLS_SIG_DST, &[], &pk, false) == blst::BLST_ERROR::BLST_SUCCESS)
+}
+
+/// Returns the proof of possession of `secret_key_set`'s key, its signature over its own
+/// compressed public key under the `BLS_POP_DST` ciphersuite.
+pub fn bls_proof_of_possession(secret_key_set: &SecretKeySet) -> Result<Vec<u8>> {
+    let sk = blst::min_pk::SecretKey::from_bytes(&secret_key_set.secret_key().to_bytes())
+        .map_err(|e| anyhow::anyhow!("Bad secret key: {:?}", e))?;
+    let pk = secret_key_set.public_keys().public_key().to_bytes();
+    Ok(sk.sign(&pk, BLS_POP_DST, &[]).to_bytes().to_vec())
+}
+
+/// Verifies a compressed proof of possession of the compressed public key `pk`.
+/// Errors if either fails to deserialize or their subgroup checks.
+pub fn bls_verify_proof_of_possession(pk: &[u8], pop: &[u8]) -> Result<bool> {
+    let parsed_pk = blst::min_pk::PublicKey::key_validate(pk)
+        .map_err(|e| anyhow::anyhow!("Bad public key: {:?}", e))?;
+    let pop = blst::min_pk::Signature::sig_validate(pop, true)
+        .map_err(|e| anyhow::anyhow!("Bad proof of possession: {:?}", e))?;
+    Ok(
+        pop.verify(false, pk, BLS_POP_DST, &[], &parsed_pk, false)
+            == blst::BLST_ERROR::BLST_SUCCESS,
+    )
+}
+
+/// Aggregates compressed BLS signatures into a single signature using blst. Each signature is
+/// subgroup checked, and the i-th one failing to deserialize is reported by its index.
+/// The aggregate only verifies against the aggregated public keys if every signature is over
+/// the same message.
+pub fn bls_aggregate_signatures(sigs: &[Vec<u8>]) -> Result<blst::min_pk::Signature> {
+    if sigs.is_empty() {
+        bail!("No signatures to aggregate")
+    }
+    let sigs: Vec<blst::min_pk::Signature> = sigs
+        .iter()
+        .enumerate()
+        .map(|(i, sig)| {
+            blst::min_pk::Signature::sig_validate(sig, true)
+                .map_err(|e| anyhow::anyhow!("Bad signature at index {i}: {:?}", e))
+        })
+        .collect::<Result<_>>()?;
+    let sigs: Vec<&blst::min_pk::Signature> = sigs.iter().collect();
+    let agg = blst::min_pk::AggregateSignature::aggregate(&sigs, false)
+        .map_err(|e| anyhow::anyhow!("Failed to aggregate signatures: {:?}", e))?;
+    Ok(agg.to_signature())
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
     use crate::io::key_management::{bls_key_exists, delete_bls_key};
+    #[test]
+    fn test_validate_bls_pk_hex() {
+        let pk_hex = new_bls_key(0).public_keys().public_key().to_hex();
+        assert_eq!(validate_bls_pk_hex(&format!("0x{pk_hex}")).unwrap(), pk_hex);
+        assert_eq!(
+            validate_bls_pk_hex(&format!("0x{}", pk_hex.to_uppercase())).unwrap(),
+            pk_hex
+        );
+
+        // The prefix is optional and casing is free
+        assert_eq!(validate_bls_pk_hex(&pk_hex).unwrap(), pk_hex);
+        assert_eq!(
+            validate_bls_pk_hex(&format!("0X{}", pk_hex.to_uppercase())).unwrap(),
+            pk_hex
+        );
+
+        // Uncompressed points are canonicalized to the compressed form
+        let uncompressed = blst::min_pk::PublicKey::from_bytes(&hex::decode(&pk_hex).unwrap())
+            .unwrap()
+            .serialize();
+        assert_eq!(
+            validate_bls_pk_hex(&format!("0x{}", hex::encode(uncompressed))).unwrap(),
+            pk_hex
+        );
+        let mut off_curve = uncompressed;
+        off_curve[95] ^= 1;
+        assert!(validate_bls_pk_hex(&hex::encode(off_curve)).is_err());
+
+        // Bad length, bad hex, not a point, and path traversal
+        assert!(validate_bls_pk_hex(&format!("0x{}", &pk_hex[2..])).is_err());
+        assert!(validate_bls_pk_hex(&format!("0x{pk_hex}00")).is_err());
+        assert!(validate_bls_pk_hex(&format!("0x{}", "g".repeat(96))).is_err());
+        assert!(validate_bls_pk_hex(&format!("0x{}", "ff".repeat(48))).is_err());
+        assert!(validate_bls_pk_hex("../../etc/passwd").is_err());
+        assert!(validate_bls_pk_hex(&format!("0x../../{}", &pk_hex[6..])).is_err());
+    }
+
     #[test]
     fn test_new_bls_key() {
         // Test for different threshold values
@@ -250,6 +458,103 @@ mod tests {
         bls_agg_sign_from_saved_sk(&pk_hex, msg).expect("Failed to sign the message");
     }
 
+    #[test]
+    fn test_bls_batch_verify() {
+        let msg = [7_u8; 32];
+        let items: Vec<(Vec<u8>, Vec<u8>, Vec<u8>)> = (0..4)
+            .map(|_| {
+                let sk_set = new_bls_key(0);
+                let sig = bls_agg_sign(&sk_set, &msg);
+                (
+                    sk_set.public_keys().public_key().to_bytes().to_vec(),
+                    msg.to_vec(),
+                    sig.to_bytes().to_vec(),
+                )
+            })
+            .collect();
+
+        // Every signature is accepted by the single batch check
+        let res = bls_batch_verify(&items);
+        assert_eq!(res.valid, vec![true; 4]);
+        assert!(res.batch_verified);
+
+        // Wrong message, swapped signature and malformed key
+        let mut mixed = items.clone();
+        mixed[1].1 = vec![8_u8; 32];
+        mixed[2].2 = items[3].2.clone();
+        mixed.push((vec![0_u8; 48], msg.to_vec(), items[0].2.clone()));
+        let res = bls_batch_verify(&mixed);
+        assert_eq!(res.valid, vec![true, false, false, true, false]);
+        assert!(!res.batch_verified);
+
+        assert_eq!(bls_batch_verify(&[]).valid, Vec::<bool>::new());
+    }
+
+    #[test]
+    fn test_bls_verify() {
+        let msg = [7_u8; 32];
+        let sk_set = new_bls_key(0);
+        let pk = sk_set.public_keys().public_key().to_bytes();
+        let sig = bls_agg_sign(&sk_set, &msg).to_bytes();
+
+        assert!(bls_verify(&pk, &msg, &sig).unwrap());
+        assert!(!bls_verify(&pk, &[8_u8; 32], &sig).unwrap());
+        assert!(bls_verify(&[0_u8; 48], &msg, &sig).is_err());
+        assert!(bls_verify(&pk, &msg, &sig[..95]).is_err());
+    }
+
+    #[test]
+    fn test_bls_proof_of_possession() {
+        let sk_set = new_bls_key(0);
+        let pk = sk_set.public_keys().public_key().to_bytes();
+        let pop = bls_proof_of_possession(&sk_set).unwrap();
+        assert!(bls_verify_proof_of_possession(&pk, &pop).unwrap());
+
+        // Neither a proof for another key nor a plain signature over the pubkey
+        let other_pk = new_bls_key(0).public_keys().public_key().to_bytes();
+        assert!(!bls_verify_proof_of_possession(&other_pk, &pop).unwrap());
+        assert!(!bls_verify(&pk, &pk, &pop).unwrap());
+        let sig = bls_agg_sign(&sk_set, &pk).to_bytes();
+        assert!(!bls_verify_proof_of_possession(&pk, &sig).unwrap());
+    }
+
+    #[test]
+    fn test_bls_aggregate_signatures() {
+        let msg = [7_u8; 32];
+        let sk_sets: Vec<SecretKeySet> = (0..3).map(|_| new_bls_key(0)).collect();
+        let sigs: Vec<Vec<u8>> = sk_sets
+            .iter()
+            .map(|sk_set| bls_agg_sign(sk_set, &msg).to_bytes().to_vec())
+            .collect();
+        let pks: Vec<blst::min_pk::PublicKey> = sk_sets
+            .iter()
+            .map(|sk_set| {
+                blst::min_pk::PublicKey::from_bytes(&sk_set.public_keys().public_key().to_bytes())
+                    .unwrap()
+            })
+            .collect();
+        let pks: Vec<&blst::min_pk::PublicKey> = pks.iter().collect();
+
+        let agg = bls_aggregate_signatures(&sigs).unwrap();
+        assert_eq!(
+            agg.fast_aggregate_verify(true, &msg, BLS_SIG_DST, &pks),
+            blst::BLST_ERROR::BLST_SUCCESS
+        );
+
+        // A subset doesn't verify against every key
+        let agg = bls_aggregate_signatures(&sigs[..2]).unwrap();
+        assert_ne!(
+            agg.fast_aggregate_verify(true, &msg, BLS_SIG_DST, &pks),
+            blst::BLST_ERROR::BLST_SUCCESS
+        );
+
+        let mut bad = sigs.clone();
+        bad[1] = vec![0_u8; 96];
+        let err = bls_aggregate_signatures(&bad).unwrap_err();
+        assert!(err.to_string().contains("index 1"), "{err}");
+        assert!(bls_aggregate_signatures(&[]).is_err());
+    }
+
     #[test]
     fn test_distribute_key_shares() {
         let threshold = 2;
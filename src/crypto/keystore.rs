@@ -1,9 +1,11 @@
 use crate::strip_0x_prefix;
 
 use super::eth_keys;
-use anyhow::{Context, Result};
+use anyhow::{bail, Context, Result};
+use blsttc::SecretKeySet;
 use ecies::SecretKey as EthSecretKey;
-use eth_keystore::decrypt_keystore;
+use serde::Deserialize;
+use unicode_normalization::UnicodeNormalization;
 
 pub fn import_keystore(
     keystore: &String,
@@ -15,14 +17,188 @@ pub fn import_keystore(
     let ct_password_bytes = hex::decode(ct_password_hex)?;
     let password_bytes = eth_keys::envelope_decrypt(envelope_sk, &ct_password_bytes)?;
     let password = String::from_utf8(password_bytes).with_context(|| "non-utf8 password")?;
-    decrypt_keystore(keystore, password).with_context(|| "Failed to decrypt keystore")
+    let sk_bytes = decrypt_keystore(keystore, &password)?;
+    verify_keystore_pubkey(keystore, &sk_bytes)?;
+    Ok(sk_bytes)
+}
+
+/// Derives the BLS key from decrypted keystore bytes and checks it against the keystore's
+/// `pubkey` field. The field is optional, but a keystore declaring a different key is corrupt
+/// and would otherwise be saved under a pubkey no one asks to sign with.
+pub fn verify_keystore_pubkey(keystore: &str, sk_bytes: &[u8]) -> Result<SecretKeySet> {
+    if sk_bytes.len() != crate::constants::BLS_PRIV_KEY_BYTES {
+        bail!("Keystore does not hold a BLS secret key")
+    }
+    let sk_set = match SecretKeySet::from_bytes(sk_bytes.to_vec()) {
+        Ok(sk) => sk,
+        Err(e) => bail!("Error deserializing bls sk bytes: {:?}", e),
+    };
+    let pk_hex = sk_set.public_keys().public_key().to_hex();
+
+    let json: serde_json::Value =
+        serde_json::from_str(keystore).with_context(|| "Keystore is not JSON")?;
+    if let Some(pubkey) = json.get("pubkey").and_then(|p| p.as_str()) {
+        let pubkey: &str = strip_0x_prefix!(pubkey);
+        if !pubkey.eq_ignore_ascii_case(&pk_hex) {
+            bail!(
+                "Keystore pubkey 0x{} does not match the decrypted key 0x{}",
+                pubkey,
+                pk_hex
+            )
+        }
+    }
+    Ok(sk_set)
+}
+
+#[derive(Deserialize)]
+struct KeystoreCrypto {
+    crypto: KeystoreModules,
+}
+
+#[derive(Deserialize)]
+struct KeystoreModules {
+    kdf: KeystoreModule,
+    checksum: KeystoreModule,
+    cipher: KeystoreModule,
+}
+
+#[derive(Deserialize)]
+struct KeystoreModule {
+    function: String,
+    params: serde_json::Value,
+    message: String,
+}
+
+/// Largest argon2id memory cost accepted from a keystore, in KiB (2 GiB)
+const MAX_ARGON2ID_MEMORY_KIB: u32 = 2 * 1024 * 1024;
+
+/// Largest argon2id number of passes accepted from a keystore
+const MAX_ARGON2ID_PASSES: u32 = 16;
+
+/// Largest argon2id degree of parallelism accepted from a keystore
+const MAX_ARGON2ID_PARALLELISM: u32 = 16;
+
+#[derive(Deserialize)]
+struct Argon2idParams {
+    dklen: u32,
+    /// Memory cost in KiB
+    m: u32,
+    /// Number of passes
+    t: u32,
+    /// Degree of parallelism
+    p: u32,
+    salt: String,
+}
+
+#[derive(Deserialize)]
+struct CipherParams {
+    iv: String,
+}
+
+/// Decrypts an EIP-2335 keystore, picking the KDF from its `kdf.function` field.
+/// scrypt and pbkdf2 keystores are handled by `eth_keystore`, argon2id ones here.
+pub fn decrypt_keystore(keystore: &str, password: &str) -> Result<Vec<u8>> {
+    let parsed: KeystoreCrypto =
+        serde_json::from_str(keystore).with_context(|| "Keystore is not EIP-2335 JSON")?;
+    match parsed.crypto.kdf.function.as_str() {
+        "scrypt" | "pbkdf2" => {
+            match eth_keystore::decrypt_keystore(keystore, password.to_string()) {
+                Ok(sk_bytes) => Ok(sk_bytes),
+                Err(eth_keystore::KeystoreError::MacMismatch) => {
+                    bail!("Failed to decrypt keystore, bad password")
+                }
+                Err(e) => Err(e).with_context(|| "Failed to decrypt keystore"),
+            }
+        }
+        "argon2id" => decrypt_argon2id(&parsed.crypto, password),
+        kdf => bail!("Unsupported keystore kdf {:?}", kdf),
+    }
+}
+
+fn decrypt_argon2id(crypto: &KeystoreModules, password: &str) -> Result<Vec<u8>> {
+    let params: Argon2idParams = serde_json::from_value(crypto.kdf.params.clone())
+        .with_context(|| "Bad argon2id kdf params")?;
+    if params.dklen != 32 {
+        bail!("Unsupported argon2id dklen {}", params.dklen)
+    }
+    // The params come from the client, so they can't be allowed to tie up the enclave
+    if params.m > MAX_ARGON2ID_MEMORY_KIB
+        || params.t > MAX_ARGON2ID_PASSES
+        || params.p > MAX_ARGON2ID_PARALLELISM
+    {
+        bail!(
+            "argon2id kdf params m={}, t={}, p={} exceed the maximum m={MAX_ARGON2ID_MEMORY_KIB}, t={MAX_ARGON2ID_PASSES}, p={MAX_ARGON2ID_PARALLELISM}",
+            params.m,
+            params.t,
+            params.p
+        )
+    }
+    let salt = decode_hex(&params.salt, "argon2id salt")?;
+    let argon2_params = argon2::Params::new(params.m, params.t, params.p, Some(32))
+        .map_err(|e| anyhow::anyhow!("Bad argon2id kdf params: {e}"))?;
+    let mut decryption_key = [0_u8; 32];
+    argon2::Argon2::new(
+        argon2::Algorithm::Argon2id,
+        argon2::Version::V0x13,
+        argon2_params,
+    )
+    .hash_password_into(
+        process_password(password).as_bytes(),
+        &salt,
+        &mut decryption_key,
+    )
+    .map_err(|e| anyhow::anyhow!("Failed to derive argon2id key: {e}"))?;
+
+    decrypt_with_key(crypto, &decryption_key)
+}
+
+/// Checks the keystore checksum and decrypts the secret with the KDF output
+fn decrypt_with_key(crypto: &KeystoreModules, decryption_key: &[u8; 32]) -> Result<Vec<u8>> {
+    if crypto.checksum.function != "sha256" {
+        bail!(
+            "Unsupported keystore checksum {:?}",
+            crypto.checksum.function
+        )
+    }
+    if crypto.cipher.function != "aes-128-ctr" {
+        bail!("Unsupported keystore cipher {:?}", crypto.cipher.function)
+    }
+    let cipher_message = decode_hex(&crypto.cipher.message, "Cipher message")?;
+    let checksum = decode_hex(&crypto.checksum.message, "Checksum message")?;
+
+    let mut pre_image = decryption_key[16..].to_vec();
+    pre_image.extend_from_slice(&cipher_message);
+    if openssl::sha::sha256(&pre_image).as_slice() != checksum.as_slice() {
+        bail!("Failed to decrypt keystore, bad password")
+    }
+
+    let cipher_params: CipherParams = serde_json::from_value(crypto.cipher.params.clone())
+        .with_context(|| "Bad cipher params")?;
+    let iv = decode_hex(&cipher_params.iv, "Cipher iv")?;
+    openssl::symm::decrypt(
+        openssl::symm::Cipher::aes_128_ctr(),
+        &decryption_key[..16],
+        Some(&iv),
+        &cipher_message,
+    )
+    .with_context(|| "Failed to decrypt keystore")
+}
+
+fn decode_hex(hex_str: &str, what: &str) -> Result<Vec<u8>> {
+    let hex_str: &str = strip_0x_prefix!(hex_str);
+    hex::decode(hex_str).with_context(|| format!("{what} is not hex"))
+}
+
+/// EIP-2335 password processing: NFKD normalize and strip control codes
+fn process_password(password: &str) -> String {
+    password.nfkd().filter(|c| !c.is_control()).collect()
 }
 
 #[cfg(test)]
 pub mod keystore_tests {
     use crate::crypto::eth_keys;
 
-    use super::import_keystore;
+    use super::{decrypt_keystore, import_keystore, verify_keystore_pubkey};
     use hex::FromHex;
 
     #[test]
@@ -94,4 +270,100 @@ pub mod keystore_tests {
         assert!(std::fs::remove_file(&keypath).is_ok());
         std::fs::remove_dir_all("./test_keys").ok();
     }
+
+    /// Encrypts `secret` into an EIP-2335 keystore using argon2id as the KDF
+    fn argon2id_keystore(secret: &[u8], password: &str, pubkey: &str) -> String {
+        let salt: [u8; 32] = rand::random();
+        let iv: [u8; 16] = rand::random();
+        let mut dk = [0_u8; 32];
+        argon2::Argon2::new(
+            argon2::Algorithm::Argon2id,
+            argon2::Version::V0x13,
+            argon2::Params::new(256, 2, 1, Some(32)).unwrap(),
+        )
+        .hash_password_into(password.as_bytes(), &salt, &mut dk)
+        .unwrap();
+        let ct = openssl::symm::encrypt(
+            openssl::symm::Cipher::aes_128_ctr(),
+            &dk[..16],
+            Some(&iv),
+            secret,
+        )
+        .unwrap();
+        let checksum = openssl::sha::sha256(&[&dk[16..], ct.as_slice()].concat());
+
+        serde_json::json!({
+            "crypto": {
+                "kdf": {
+                    "function": "argon2id",
+                    "params": { "dklen": 32, "m": 256, "t": 2, "p": 1, "salt": hex::encode(salt) },
+                    "message": ""
+                },
+                "checksum": { "function": "sha256", "params": {}, "message": hex::encode(checksum) },
+                "cipher": {
+                    "function": "aes-128-ctr",
+                    "params": { "iv": hex::encode(iv) },
+                    "message": hex::encode(ct)
+                }
+            },
+            "description": "This is a test keystore that uses argon2id to secure the secret.",
+            "pubkey": pubkey,
+            "path": "m/12381/60/0/0",
+            "uuid": "64625def-3331-4eea-ab6f-782f3ed16a83",
+            "version": 4
+        })
+        .to_string()
+    }
+
+    #[test]
+    fn test_import_argon2id_keystore() {
+        let secret =
+            Vec::from_hex("000000000019d6689c085ae165831e934ff763ae46a2a6c172b3f1b60a8ce26f")
+                .unwrap();
+        let pubkey = "9612d7a727c9d0a22e185a1c768478dfe919cada9266988cb32359c11f2b7b27f4ae4040902382ae2910c15e2b420d07";
+        let keystore = argon2id_keystore(&secret, "testpassword", pubkey);
+
+        let (eth_sk, eth_pk) = eth_keys::new_eth_key().unwrap();
+        let ct_pw = eth_keys::envelope_encrypt(&eth_pk, b"testpassword").unwrap();
+        let bls_sk_bytes = import_keystore(&keystore, &hex::encode(ct_pw), &eth_sk).unwrap();
+        assert_eq!(bls_sk_bytes, secret);
+
+        let sk_set = blsttc::SecretKeySet::from_bytes(bls_sk_bytes).unwrap();
+        assert_eq!(sk_set.public_keys().public_key().to_hex(), pubkey);
+
+        assert!(decrypt_keystore(&keystore, "notthepassword").is_err());
+    }
+
+    #[test]
+    fn test_decrypt_rejects_unknown_kdf() {
+        let keystore = r#"{"crypto": {
+            "kdf": {"function": "bcrypt", "params": {}, "message": ""},
+            "checksum": {"function": "sha256", "params": {}, "message": ""},
+            "cipher": {"function": "aes-128-ctr", "params": {"iv": ""}, "message": ""}
+        }}"#;
+        assert!(decrypt_keystore(keystore, "password").is_err());
+    }
+
+    #[test]
+    fn test_import_rejects_mismatched_pubkey() {
+        let secret =
+            Vec::from_hex("000000000019d6689c085ae165831e934ff763ae46a2a6c172b3f1b60a8ce26f")
+                .unwrap();
+        // A real pubkey, but not the one of `secret`
+        let other_pk_hex = crate::crypto::bls_keys::new_bls_key(0)
+            .public_keys()
+            .public_key()
+            .to_hex();
+        let keystore = argon2id_keystore(&secret, "testpassword", &other_pk_hex);
+
+        let (eth_sk, eth_pk) = eth_keys::new_eth_key().unwrap();
+        let ct_pw = eth_keys::envelope_encrypt(&eth_pk, b"testpassword").unwrap();
+        let err = import_keystore(&keystore, &hex::encode(ct_pw), &eth_sk).unwrap_err();
+        assert!(err.to_string().contains("does not match the decrypted key"));
+
+        // Without a pubkey field there is nothing to contradict
+        let mut json: serde_json::Value = serde_json::from_str(&keystore).unwrap();
+        json.as_object_mut().unwrap().remove("pubkey");
+        assert!(verify_keystore_pubkey(&json.to_string(), &secret).is_ok());
+    }
 }
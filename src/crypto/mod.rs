@@ -1,3 +1,4 @@
 pub mod bls_keys;
 pub mod eth_keys;
 pub mod keystore;
+pub mod sealing;
@@ -0,0 +1,252 @@
+use crate::io::config::{Config, SealingPolicy};
+use crate::strip_0x_prefix;
+
+use anyhow::{bail, Context, Result};
+use openssl::md::Md;
+use openssl::pkey::Id;
+use openssl::pkey_ctx::PkeyCtx;
+use openssl::symm::{decrypt_aead, encrypt_aead, Cipher};
+
+#[cfg(feature = "sgx")]
+#[link(name = "epid")]
+extern "C" {
+    /// The cpp function fetching the enclave's 128-bit sealing key under an SGX key policy,
+    /// defined in src/io/sealing.cpp
+    fn get_enclave_sealing_key(key_policy: u16, key: *mut u8) -> i32;
+}
+
+/// Size of the sealing key returned by EGETKEY
+const ENCLAVE_SEALING_KEY_BYTES: usize = 16;
+
+/// Size of the per-key AES-256-GCM subkey
+const SUBKEY_BYTES: usize = 32;
+
+const NONCE_BYTES: usize = 12;
+
+const TAG_BYTES: usize = 16;
+
+/// Domain separates the subkeys derived for at-rest key sealing
+const SEALING_HKDF_SALT: &[u8] = b"secure-signer/key-sealing/v1";
+
+/// Returned by `get_enclave_sealing_key` when the SGX device was momentarily unavailable
+#[cfg(feature = "sgx")]
+const TRANSIENT_SEALING_KEY_ERROR: i32 = -2;
+
+/// Seconds clients are told to wait before retrying after a transient sealing failure
+pub const SEALING_RETRY_AFTER_SECS: u64 = 1;
+
+/// The sealing backend failed in a way expected to clear up on its own, so the operation is worth
+/// retrying. Any other sealing error is permanent.
+#[derive(Debug)]
+pub struct TransientSealingError(pub String);
+
+impl std::fmt::Display for TransientSealingError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Transient sealing failure, {}", self.0)
+    }
+}
+
+impl std::error::Error for TransientSealingError {}
+
+/// Returns true if `e` was caused by a `TransientSealingError`
+pub fn is_transient(e: &anyhow::Error) -> bool {
+    e.chain().any(|cause| cause.is::<TransientSealingError>())
+}
+
+#[cfg(feature = "sgx")]
+fn enclave_sealing_key(policy: SealingPolicy) -> Result<[u8; ENCLAVE_SEALING_KEY_BYTES]> {
+    let mut key = [0_u8; ENCLAVE_SEALING_KEY_BYTES];
+    let ret = unsafe { get_enclave_sealing_key(policy.sgx_key_policy(), key.as_mut_ptr()) };
+    if ret == TRANSIENT_SEALING_KEY_ERROR {
+        bail!(TransientSealingError("the SGX device is busy".to_string()))
+    }
+    if ret != 0 {
+        bail!("Failed to get the enclave sealing key, error code {}", ret)
+    }
+    Ok(key)
+}
+
+#[cfg(not(feature = "sgx"))]
+thread_local! {
+    /// Sealing key fetches left to fail on this thread, see `inject_transient_failures`
+    static INJECTED_TRANSIENT_FAILURES: std::cell::Cell<usize> = std::cell::Cell::new(0);
+}
+
+/// Makes the next `n` seals or unseals on this thread fail transiently, to exercise how callers
+/// handle a busy sealing backend. Only available outside the enclave.
+#[cfg(not(feature = "sgx"))]
+pub fn inject_transient_failures(n: usize) {
+    INJECTED_TRANSIENT_FAILURES.with(|failures| failures.set(n));
+}
+
+#[cfg(not(feature = "sgx"))]
+// Use a fixed sealing key per policy for local development
+fn enclave_sealing_key(policy: SealingPolicy) -> Result<[u8; ENCLAVE_SEALING_KEY_BYTES]> {
+    let injected = INJECTED_TRANSIENT_FAILURES.with(|failures| {
+        let left = failures.get();
+        failures.set(left.saturating_sub(1));
+        left > 0
+    });
+    if injected {
+        bail!(TransientSealingError("injected failure".to_string()))
+    }
+    match policy {
+        SealingPolicy::MrEnclave => Ok(*b"dev-mrenclave-k!"),
+        SealingPolicy::MrSigner => Ok(*b"dev-sealing-key!"),
+    }
+}
+
+/// Derives the subkey sealing the secret key of `pk_hex` from the enclave sealing key,
+/// using HKDF-SHA256 with the public key as info. Every stored key gets its own subkey.
+fn derive_subkey(root_key: &[u8], pk_hex: &str) -> Result<[u8; SUBKEY_BYTES]> {
+    let pk_hex: &str = strip_0x_prefix!(pk_hex);
+    let pk_bytes = hex::decode(pk_hex).with_context(|| "Failed to hex-decode pk_hex")?;
+
+    let mut ctx = PkeyCtx::new_id(Id::HKDF)?;
+    ctx.derive_init()?;
+    ctx.set_hkdf_md(Md::sha256())?;
+    ctx.set_hkdf_salt(SEALING_HKDF_SALT)?;
+    ctx.set_hkdf_key(root_key)?;
+    ctx.add_hkdf_info(&pk_bytes)?;
+
+    let mut subkey = [0_u8; SUBKEY_BYTES];
+    ctx.derive(Some(&mut subkey))
+        .with_context(|| "Failed to derive sealing subkey")?;
+    Ok(subkey)
+}
+
+fn seal_with(root_key: &[u8], pk_hex: &str, sk: &[u8]) -> Result<Vec<u8>> {
+    let subkey = derive_subkey(root_key, pk_hex)?;
+    let nonce: [u8; NONCE_BYTES] = rand::random();
+    let mut tag = [0_u8; TAG_BYTES];
+    let ct = encrypt_aead(
+        Cipher::aes_256_gcm(),
+        &subkey,
+        Some(&nonce),
+        &[],
+        sk,
+        &mut tag,
+    )
+    .with_context(|| "Failed to seal secret key")?;
+
+    let mut sealed = Vec::with_capacity(NONCE_BYTES + ct.len() + TAG_BYTES);
+    sealed.extend_from_slice(&nonce);
+    sealed.extend_from_slice(&ct);
+    sealed.extend_from_slice(&tag);
+    Ok(sealed)
+}
+
+fn unseal_with(root_key: &[u8], pk_hex: &str, sealed: &[u8]) -> Result<Vec<u8>> {
+    if sealed.len() < NONCE_BYTES + TAG_BYTES {
+        bail!("Sealed secret key is too short")
+    }
+    let subkey = derive_subkey(root_key, pk_hex)?;
+    let (nonce, rest) = sealed.split_at(NONCE_BYTES);
+    let (ct, tag) = rest.split_at(rest.len() - TAG_BYTES);
+    decrypt_aead(Cipher::aes_256_gcm(), &subkey, Some(nonce), &[], ct, tag)
+        .with_context(|| "Failed to unseal secret key")
+}
+
+/// Encrypts the secret key of `pk_hex` under its per-key sealing subkey, derived from the
+/// sealing key of the configured policy. Returns `nonce || ciphertext || tag`.
+pub fn seal(pk_hex: &str, sk: &[u8]) -> Result<Vec<u8>> {
+    seal_with_policy(Config::current().sealing_policy, pk_hex, sk)
+}
+
+/// Decrypts a secret key produced by `seal` for the same `pk_hex` under the same policy.
+pub fn unseal(pk_hex: &str, sealed: &[u8]) -> Result<Vec<u8>> {
+    unseal_with_policy(Config::current().sealing_policy, pk_hex, sealed)
+}
+
+/// Like `seal`, under `policy` rather than the configured one
+pub fn seal_with_policy(policy: SealingPolicy, pk_hex: &str, sk: &[u8]) -> Result<Vec<u8>> {
+    seal_with(&enclave_sealing_key(policy)?, pk_hex, sk)
+}
+
+/// Like `unseal`, under `policy` rather than the configured one
+pub fn unseal_with_policy(policy: SealingPolicy, pk_hex: &str, sealed: &[u8]) -> Result<Vec<u8>> {
+    unseal_with(&enclave_sealing_key(policy)?, pk_hex, sealed)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_subkeys_differ_per_key() {
+        let root_key = enclave_sealing_key(SealingPolicy::default()).unwrap();
+        let subkey1 = derive_subkey(&root_key, "0x1234abcd").unwrap();
+        let subkey2 = derive_subkey(&root_key, "0x5678ef01").unwrap();
+        assert_ne!(subkey1, subkey2);
+
+        // Derivation is deterministic and ignores the 0x prefix
+        assert_eq!(subkey1, derive_subkey(&root_key, "1234abcd").unwrap());
+
+        // A different enclave sealing key yields different subkeys
+        let other_subkey = derive_subkey(b"another-root-key", "0x1234abcd").unwrap();
+        assert_ne!(subkey1, other_subkey);
+    }
+
+    #[test]
+    fn test_keys_round_trip_independently() {
+        let sk1 = hex::decode("abcdef123456").unwrap();
+        let sk2 = hex::decode("deadbeef2468").unwrap();
+        let sealed1 = seal("0x1234abcd", &sk1).unwrap();
+        let sealed2 = seal("0x5678ef01", &sk2).unwrap();
+
+        assert_eq!(unseal("0x1234abcd", &sealed1).unwrap(), sk1);
+        assert_eq!(unseal("0x5678ef01", &sealed2).unwrap(), sk2);
+
+        // A sealed key only opens under its own subkey
+        assert!(unseal("0x5678ef01", &sealed1).is_err());
+        assert!(unseal("0x1234abcd", &sealed2).is_err());
+    }
+
+    #[test]
+    fn test_tampered_sealed_key_is_rejected() {
+        let sk = hex::decode("abcdef123456").unwrap();
+        let mut sealed = seal("0x1234abcd", &sk).unwrap();
+        assert!(!sealed.windows(sk.len()).any(|w| w == sk.as_slice()));
+
+        sealed[NONCE_BYTES] ^= 1;
+        assert!(unseal("0x1234abcd", &sealed).is_err());
+        assert!(unseal("0x1234abcd", &sealed[..NONCE_BYTES]).is_err());
+    }
+
+    #[test]
+    fn test_injected_failures_are_transient() {
+        let sk = hex::decode("abcdef123456").unwrap();
+        let sealed = seal("0x1234abcd", &sk).unwrap();
+
+        inject_transient_failures(2);
+        let e = seal("0x1234abcd", &sk).unwrap_err();
+        assert!(is_transient(&e));
+        // Still recognized under the context callers add
+        let e = unseal("0x1234abcd", &sealed).with_context(|| "Unable to read secret key");
+        assert!(is_transient(&e.unwrap_err()));
+        assert_eq!(unseal("0x1234abcd", &sealed).unwrap(), sk);
+
+        // A sealed key that doesn't open is permanent
+        assert!(!is_transient(&unseal("0x5678ef01", &sealed).unwrap_err()));
+    }
+
+    #[test]
+    fn test_round_trip_under_each_policy() {
+        let sk = hex::decode("abcdef123456").unwrap();
+        let sealed_to = |policy: SealingPolicy| {
+            let _guard = Config::current().with_sealing_policy(policy).scoped();
+            let sealed = seal("0x1234abcd", &sk).unwrap();
+            assert_eq!(unseal("0x1234abcd", &sealed).unwrap(), sk);
+            sealed
+        };
+        let mrenclave_sealed = sealed_to(SealingPolicy::MrEnclave);
+        let mrsigner_sealed = sealed_to(SealingPolicy::MrSigner);
+
+        // A key sealed under one policy doesn't open under the other
+        let _guard = Config::current()
+            .with_sealing_policy(SealingPolicy::MrSigner)
+            .scoped();
+        assert!(unseal("0x1234abcd", &mrenclave_sealed).is_err());
+        assert_eq!(unseal("0x1234abcd", &mrsigner_sealed).unwrap(), sk);
+    }
+}
@@ -231,7 +231,13 @@ pub fn sign_voluntary_exit_message(
     let sk = crate::crypto::bls_keys::fetch_bls_sk(&pk_hex)?.secret_key();
 
     // Sign a VoluntaryExitMessage with Epoch 0
-    let (sig, _root) = sign_vem(sk, 0, req.validator_index, req.fork_info)?;
+    let (sig, _root) = sign_vem(
+        sk,
+        0,
+        req.validator_index,
+        req.fork_info,
+        req.genesis_fork_version,
+    )?;
 
     Ok(crate::enclave::types::SignExitResponse {
         signature: hex::encode(sig.as_ssz_bytes()),
@@ -243,6 +249,7 @@ fn sign_vem(
     epoch: crate::eth2::eth_types::Epoch,
     validator_index: crate::eth2::eth_types::ValidatorIndex,
     fork_info: crate::eth2::eth_types::ForkInfo,
+    genesis_fork_version: crate::eth2::eth_types::Version,
 ) -> Result<(BLSSignature, crate::eth2::eth_types::Root)> {
     let voluntary_exit = crate::eth2::eth_types::VoluntaryExit {
         epoch,
@@ -255,7 +262,8 @@ fn sign_vem(
         voluntary_exit,
     };
 
-    let root = crate::eth2::eth_signing::BLSSignMsg::VOLUNTARY_EXIT(vem_req).to_signing_root(None);
+    let root = crate::eth2::eth_signing::BLSSignMsg::VOLUNTARY_EXIT(vem_req)
+        .checked_signing_root(Some(genesis_fork_version))?;
 
     let sig: BLSSignature = BLSSignature::from(sk_share.sign(&root).to_bytes().to_vec());
 
@@ -475,10 +483,18 @@ mod tests {
                 guardian_index: i as u64,
                 validator_index: 0,
                 fork_info: crate::eth2::eth_types::ForkInfo::default(),
+                genesis_fork_version: crate::eth2::eth_types::GENESIS_FORK_VERSION,
             };
             let sk_share = verify_custody(&resp, &_g_sks[i]).unwrap();
             let sk = blsttc::SecretKey::from_bytes(sk_share.to_bytes()).unwrap();
-            let (sig, root) = sign_vem(sk, 0, req.validator_index, req.fork_info).unwrap();
+            let (sig, root) = sign_vem(
+                sk,
+                0,
+                req.validator_index,
+                req.fork_info,
+                req.genesis_fork_version,
+            )
+            .unwrap();
             msg_root = root;
 
             let mut sig_bytes: [u8; crate::constants::BLS_SIG_BYTES] =
@@ -0,0 +1,84 @@
+use axum::{
+    extract::{Path, State},
+    response::IntoResponse,
+    Json,
+};
+use log::{error, info};
+
+/// Exports the BLS secret key of `bls_pk_hex` ECIES-encrypted to the client's public key, with its
+/// slashing protection history, for migrating the key to another enclave. Only allowed if key
+/// export is enabled. The export runs under the key's signing lock, so the history is complete up
+/// to the moment the key is read. The key keeps signing here afterwards, delete it before the
+/// exported copy signs anywhere else.
+pub async fn handler(
+    Path(bls_pk_hex): Path<String>,
+    State(state): State<crate::enclave::shared::handlers::AppState>,
+    Json(req): Json<crate::enclave::types::BlsKeyExportRequest>,
+) -> axum::response::Response {
+    info!("bls_export()");
+
+    if !state.allow_key_export {
+        error!("Rejected export of {bls_pk_hex}, key export is disabled");
+        return (
+            axum::http::status::StatusCode::FORBIDDEN,
+            "Key export is disabled".to_string(),
+        )
+            .into_response();
+    }
+
+    let bls_pk_hex = match crate::crypto::bls_keys::validate_bls_pk_hex(&bls_pk_hex) {
+        Ok(pk) => pk,
+        Err(e) => {
+            error!("Bad BLS public key format: {bls_pk_hex}");
+            return (
+                axum::http::status::StatusCode::BAD_REQUEST,
+                format!("Bad bls_pk_hex, {:?}", e),
+            )
+                .into_response();
+        }
+    };
+
+    let client_pk = match crate::crypto::eth_keys::eth_pk_from_hex(&req.client_pubkey) {
+        Ok(pk) => pk,
+        Err(e) => {
+            error!("Bad client public key: {}", req.client_pubkey);
+            return (
+                axum::http::status::StatusCode::BAD_REQUEST,
+                format!("Bad client_pubkey, {:?}", e),
+            )
+                .into_response();
+        }
+    };
+
+    // A concurrent slashable signature would be missing from the exported history
+    let _guard = match state.key_locks.lock(&bls_pk_hex).await {
+        Ok(guard) => guard,
+        Err(e) => {
+            error!("Key 0x{bls_pk_hex} is busy: {e}");
+            return (
+                axum::http::status::StatusCode::SERVICE_UNAVAILABLE,
+                [(
+                    axum::http::header::RETRY_AFTER,
+                    crate::enclave::shared::key_lock::KEY_LOCK_RETRY_AFTER_SECS.to_string(),
+                )],
+                format!("Try again later, {e}"),
+            )
+                .into_response();
+        }
+    };
+
+    match crate::enclave::secure_signer::export_bls_key_with_slashing_protection(
+        &bls_pk_hex,
+        &client_pk,
+        req.genesis_validators_root,
+    ) {
+        Ok(resp) => {
+            info!("Exported BLS key {bls_pk_hex}");
+            (axum::http::status::StatusCode::OK, Json(resp)).into_response()
+        }
+        Err(e) => {
+            error!("bls_export() failed with: {:?}", e);
+            crate::enclave::shared::internal_error(&e, format!("Key export failed: {:?}", e))
+        }
+    }
+}
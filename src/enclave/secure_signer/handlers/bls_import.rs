@@ -0,0 +1,44 @@
+use axum::{extract::State, response::IntoResponse, Json};
+use log::{error, info};
+
+/// Imports an EIP-2335 keystore whose password is ECIES-encrypted to an enclave ETH key, along
+/// with an optional EIP-3076 interchange. Returns a `KeyImportResponse`.
+/// If slashing protection is required on import, the interchange must have history for the key.
+/// Answers 507 if the enclave already holds the maximum number of keys.
+pub async fn handler(
+    State(state): State<crate::enclave::shared::handlers::AppState>,
+    Json(req): Json<crate::enclave::types::KeyImportRequest>,
+) -> axum::response::Response {
+    info!("bls_import()");
+    let slot = match crate::enclave::shared::key_limit::reserve(state.key_limit.as_ref()) {
+        Ok(slot) => slot,
+        Err(response) => return response,
+    };
+    match crate::enclave::secure_signer::import_keystore(
+        &req,
+        state.require_import_slashing_protection,
+    ) {
+        Ok(resp) => {
+            if resp.data.iter().any(|outcome| {
+                outcome.status == crate::enclave::types::KeystoreImportStatus::Imported
+            }) {
+                if let Some(slot) = slot {
+                    slot.saved();
+                }
+            }
+            (axum::http::status::StatusCode::OK, Json(resp)).into_response()
+        }
+        Err(crate::enclave::secure_signer::KeyImportError::Internal(e)) => {
+            error!("bls_import() failed with: {:?}", e);
+            crate::enclave::shared::internal_error(&e, format!("Keystore import failed: {:?}", e))
+        }
+        Err(e) => {
+            error!("Bad keystore import: {e}");
+            (
+                axum::http::status::StatusCode::BAD_REQUEST,
+                format!("Bad keystore import, {e}"),
+            )
+                .into_response()
+        }
+    }
+}
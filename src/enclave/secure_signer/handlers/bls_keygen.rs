@@ -1,22 +1,88 @@
+use axum::extract::{Query, State};
 use axum::response::IntoResponse;
 use axum::Json;
 use log::{error, info};
 
-/// Generates, saves, and performs remote attestation on a new ETH key. Returns a `KeyGenResponse` on success.
-pub async fn handler() -> axum::response::Response {
+/// Generates, saves, and performs remote attestation on a new BLS key. Returns a `KeyGenResponse`
+/// carrying the key's proof of possession on success.
+/// `allowed_types` restricts which request types the key may sign and `proposer_index` the
+/// validator index it may propose blocks as. `ct_mac_secret_hex` provisions the secret
+/// authenticating its sign requests, ECIES-encrypted to the enclave ETH key `encrypting_pk_hex`.
+/// Not found in import-only mode,
+/// 507 if the enclave already holds the maximum number of keys.
+pub async fn handler(
+    State(state): State<crate::enclave::shared::handlers::AppState>,
+    Query(req): Query<crate::enclave::types::RemoteAttestationRequest>,
+) -> axum::response::Response {
     info!("eth_bls_gen_service()");
-    match crate::enclave::secure_signer::attest_new_bls_key() {
-        Ok((evidence, eth_pk)) => {
-            let resp = crate::enclave::types::KeyGenResponse::from_bls_key(eth_pk, evidence);
+    if state.import_only {
+        error!("Rejected BLS keygen request, the signer is import-only");
+        return axum::http::status::StatusCode::NOT_FOUND.into_response();
+    }
+    let nonce = match req.nonce() {
+        Ok(nonce) => nonce,
+        Err(e) => {
+            error!("bls_key_gen_service() got a bad nonce: {}", e);
+            return (
+                axum::http::status::StatusCode::BAD_REQUEST,
+                format!("Bad nonce, {e}"),
+            )
+                .into_response();
+        }
+    };
+    let allowed_types = match req.allowed_types() {
+        Ok(allowed_types) => allowed_types,
+        Err(e) => {
+            error!("bls_key_gen_service() got bad allowed types: {}", e);
+            return (
+                axum::http::status::StatusCode::BAD_REQUEST,
+                format!("Bad allowed types, {e}"),
+            )
+                .into_response();
+        }
+    };
+    let mac_secret = match req.mac_secret() {
+        Ok(mac_secret) => mac_secret,
+        Err(e) => {
+            error!(
+                "bls_key_gen_service() got a bad request MAC secret: {:#}",
+                e
+            );
+            return (
+                axum::http::status::StatusCode::BAD_REQUEST,
+                format!("Bad request MAC secret, {e:#}"),
+            )
+                .into_response();
+        }
+    };
+    let slot = match crate::enclave::shared::key_limit::reserve(state.key_limit.as_ref()) {
+        Ok(slot) => slot,
+        Err(response) => return response,
+    };
+    match crate::enclave::secure_signer::attest_new_bls_key(
+        nonce.as_ref(),
+        req.epid_signature_type,
+        allowed_types,
+        req.proposer_index,
+        mac_secret,
+    ) {
+        Ok((evidence, bls_pk, proof_of_possession)) => {
+            if let Some(slot) = slot {
+                slot.saved();
+            }
+            let resp = crate::enclave::types::KeyGenResponse::from_bls_key(
+                bls_pk,
+                evidence,
+                &proof_of_possession,
+            );
             (axum::http::status::StatusCode::CREATED, Json(resp)).into_response()
         }
         Err(e) => {
             error!("bls_key_gen_service() failed with: {}", e);
-            (
-                axum::http::status::StatusCode::INTERNAL_SERVER_ERROR,
+            crate::enclave::shared::internal_error(
+                &e,
                 format!("bls_key_gen_service failed: {:?}", e),
             )
-                .into_response()
         }
     }
 }
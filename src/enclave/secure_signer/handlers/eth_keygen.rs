@@ -1,22 +1,36 @@
+use axum::extract::Query;
 use axum::response::IntoResponse;
 use axum::Json;
 use log::{error, info};
 
 /// Generates, saves, and performs remote attestation on a new ETH key. Returns a `KeyGenResponse` on success.
-pub async fn handler() -> axum::response::Response {
+pub async fn handler(
+    Query(req): Query<crate::enclave::types::RemoteAttestationRequest>,
+) -> axum::response::Response {
     info!("eth_key_gen_service()");
-    match crate::enclave::secure_signer::attest_new_eth_key() {
+    let nonce = match req.nonce() {
+        Ok(nonce) => nonce,
+        Err(e) => {
+            error!("eth_key_gen_service() got a bad nonce: {}", e);
+            return (
+                axum::http::status::StatusCode::BAD_REQUEST,
+                format!("Bad nonce, {e}"),
+            )
+                .into_response();
+        }
+    };
+    match crate::enclave::secure_signer::attest_new_eth_key(nonce.as_ref(), req.epid_signature_type)
+    {
         Ok((evidence, eth_pk)) => {
             let resp = crate::enclave::types::KeyGenResponse::from_eth_key(eth_pk, evidence);
             (axum::http::status::StatusCode::CREATED, Json(resp)).into_response()
         }
         Err(e) => {
             error!("eth_key_gen_service() failed with: {}", e);
-            (
-                axum::http::status::StatusCode::INTERNAL_SERVER_ERROR,
+            crate::enclave::shared::internal_error(
+                &e,
                 format!("eth_key_gen_service failed: {:?}", e),
             )
-                .into_response()
         }
     }
 }
@@ -0,0 +1,101 @@
+use axum::{
+    body::Bytes,
+    extract::{Query, State},
+    response::IntoResponse,
+    Json,
+};
+use log::{error, info};
+
+/// Imports a zip or tar archive of EIP-2335 keystores, with a `passwords.json` mapping keystore
+/// file names to passwords, an optional `slashing_protection.json` EIP-3076 interchange, an
+/// optional `mac_secrets.json` mapping keystore file names to hex request MAC secrets, and an
+/// optional `fee_recipients.json` mapping keystore file names to their allowed fee recipients.
+/// Passwords and MAC secrets are hex ECIES ciphertexts encrypted to the enclave ETH key
+/// `encrypting_pk_hex`.
+/// Returns a `KeystoreArchiveImportResponse` with the outcome for every keystore.
+/// Only allowed if archive import is enabled.
+pub async fn handler(
+    State(state): State<crate::enclave::shared::handlers::AppState>,
+    Query(query): Query<crate::enclave::types::KeystoreArchiveImportQuery>,
+    body: Bytes,
+) -> axum::response::Response {
+    info!("keystore_archive_import()");
+
+    if !state.allow_keystore_archive_import {
+        error!("Rejected keystore archive, archive import is disabled");
+        return (
+            axum::http::status::StatusCode::FORBIDDEN,
+            "Keystore archive import is disabled".to_string(),
+        )
+            .into_response();
+    }
+
+    // Tell secrets encrypted to the wrong key apart from ones that are corrupt
+    if !crate::io::key_management::eth_key_exists(&query.encrypting_pk_hex) {
+        let e = crate::enclave::secure_signer::KeyImportError::UnknownEncryptingKey;
+        error!("Bad keystore archive: {e}");
+        return (
+            axum::http::status::StatusCode::BAD_REQUEST,
+            format!("Bad keystore archive, {e}"),
+        )
+            .into_response();
+    }
+    let envelope_sk = match crate::crypto::eth_keys::fetch_eth_key(&query.encrypting_pk_hex) {
+        Ok(sk) => sk,
+        Err(e) => {
+            error!("keystore_archive_import() failed with: {:?}", e);
+            return crate::enclave::shared::internal_error(
+                &e,
+                format!("Keystore archive import failed: {:?}", e),
+            );
+        }
+    };
+
+    let archive = match crate::io::keystore_archive::KeystoreArchive::read(&body) {
+        Ok(archive) => archive,
+        Err(e) => {
+            error!("Bad keystore archive: {:?}", e);
+            return (
+                axum::http::status::StatusCode::BAD_REQUEST,
+                format!("Bad keystore archive, {:?}", e),
+            )
+                .into_response();
+        }
+    };
+
+    // Reject a malformed interchange before importing any key without its history
+    let interchange = match archive
+        .interchange
+        .as_deref()
+        .map(crate::eth2::slash_protection::SlashingProtectionDB::from_str)
+        .transpose()
+    {
+        Ok(interchange) => interchange,
+        Err(e) => {
+            error!("Bad EIP-3076 interchange: {:?}", e);
+            return (
+                axum::http::status::StatusCode::BAD_REQUEST,
+                format!("Bad EIP-3076 interchange, {:?}", e),
+            )
+                .into_response();
+        }
+    };
+
+    match crate::enclave::secure_signer::import_keystore_archive(
+        &archive,
+        &envelope_sk,
+        interchange.as_ref(),
+        state.key_limit.as_ref(),
+        state.require_import_slashing_protection,
+    ) {
+        Ok(resp) => (axum::http::status::StatusCode::OK, Json(resp)).into_response(),
+        Err(e) => {
+            error!("keystore_archive_import() failed with: {:?}", e);
+            (
+                axum::http::status::StatusCode::INTERNAL_SERVER_ERROR,
+                format!("Keystore archive import failed: {:?}", e),
+            )
+                .into_response()
+        }
+    }
+}
@@ -1,3 +1,7 @@
+pub mod bls_export;
+pub mod bls_import;
 pub mod bls_keygen;
 pub mod eth_keygen;
+pub mod keystore_archive_import;
+pub mod sign_raw;
 pub mod validator_deposit;
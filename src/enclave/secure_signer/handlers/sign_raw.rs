@@ -0,0 +1,105 @@
+use axum::{
+    body::Bytes,
+    extract::{Path, State},
+    http::{HeaderMap, StatusCode},
+    response::IntoResponse,
+    Json,
+};
+use log::{error, info, warn};
+
+/// Signs a caller-computed 32 byte signing root with the BLS key of `bls_pk_hex`. Nothing about
+/// the message behind the root is known, so slashing protection can't apply and this is only
+/// allowed if raw signing is enabled, and for keys with a signing type allowlist only if it
+/// names `RAW`.
+pub async fn handler(
+    Path(bls_pk_hex): Path<String>,
+    State(state): State<crate::enclave::shared::handlers::AppState>,
+    headers: HeaderMap,
+    body: Bytes,
+) -> axum::response::Response {
+    info!("sign_raw()");
+    let _in_flight = state.in_flight.start();
+
+    if !state.allow_raw_signing {
+        error!("Rejected raw signing request for {bls_pk_hex}, raw signing is disabled");
+        return (StatusCode::FORBIDDEN, "Raw signing is disabled".to_string()).into_response();
+    }
+
+    if state.require_request_mac {
+        if let Err(e) = crate::enclave::shared::handlers::secure_sign_bls::verify_request_mac(
+            &bls_pk_hex,
+            &headers,
+            &body,
+        ) {
+            error!("Rejected unauthenticated raw signing request: {e}");
+            return (StatusCode::UNAUTHORIZED, e).into_response();
+        }
+    }
+
+    let req: crate::enclave::types::RawSignRequest = match serde_json::from_slice(&body) {
+        Ok(req) => req,
+        Err(e) => {
+            error!("Bad raw signing request: {e}");
+            return (StatusCode::BAD_REQUEST, format!("Bad signing request, {e}")).into_response();
+        }
+    };
+    let signing_root = match req.signing_root() {
+        Ok(root) => root,
+        Err(e) => {
+            error!("Bad signingRoot: {:?}", e);
+            return (StatusCode::BAD_REQUEST, format!("Bad signingRoot, {:?}", e)).into_response();
+        }
+    };
+
+    let bls_pk_hex = match crate::crypto::bls_keys::validate_bls_pk_hex(&bls_pk_hex) {
+        Ok(pk) => pk,
+        Err(e) => {
+            error!("Bad BLS public key format: {bls_pk_hex}");
+            return (StatusCode::BAD_REQUEST, format!("Bad bls_pk_hex, {:?}", e)).into_response();
+        }
+    };
+    if !crate::io::key_management::bls_key_exists(&bls_pk_hex) {
+        error!("Unknown BLS public key: {bls_pk_hex}");
+        return (
+            StatusCode::NOT_FOUND,
+            format!("No key found for bls_pk_hex 0x{bls_pk_hex}"),
+        )
+            .into_response();
+    }
+
+    // A key provisioned for some request types can't sign arbitrary roots behind their back
+    let raw = crate::io::key_management::RAW_SIGN_TYPE;
+    match crate::io::key_management::read_sign_type_allowlist(&bls_pk_hex) {
+        Ok(Some(allowed)) if !allowed.iter().any(|t| t == raw) => {
+            error!("{raw} requests are not allowed for {bls_pk_hex}");
+            return (
+                StatusCode::FORBIDDEN,
+                format!("{raw} requests are not allowed for this key"),
+            )
+                .into_response();
+        }
+        Ok(_) => {}
+        Err(e) => {
+            return crate::enclave::shared::internal_error(
+                &e,
+                format!("Signing operation failed: {:?}", e),
+            )
+        }
+    }
+
+    warn!(
+        "Signing raw root 0x{} with {bls_pk_hex} without slashing protection",
+        hex::encode(signing_root)
+    );
+    match crate::crypto::bls_keys::bls_agg_sign_from_saved_sk(&bls_pk_hex, &signing_root) {
+        Ok(sig) => {
+            let response = crate::enclave::types::SignatureResponse::new(&sig.to_bytes())
+                .with_signing_root(&signing_root);
+            (StatusCode::OK, Json(response)).into_response()
+        }
+        Err(e) => {
+            error!("Failed trying to sign");
+            crate::enclave::shared::internal_error(&e, format!("Signing operation failed: {:?}", e))
+        }
+    }
+}
@@ -28,7 +28,11 @@ pub async fn handler(
     }
 
     info!("Deposit request for validator pubkey: {bls_pk_hex}");
-    info!("Request:\n{:#?}", req);
+    // As JSON, so the pubkey is logged in hex where it can be redacted
+    info!(
+        "Request:\n{}",
+        serde_json::to_string_pretty(&req).unwrap_or_default()
+    );
 
     match crate::eth2::eth_signing::get_deposit_signature(
         bls_pk_hex,
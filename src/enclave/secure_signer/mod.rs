@@ -1,32 +1,465 @@
 pub mod handlers;
 use anyhow::{Context, Result};
 
-fn attest_new_eth_key() -> Result<(
+fn attest_new_eth_key(
+    nonce: Option<&[u8; crate::io::remote_attestation::ATTESTATION_NONCE_BYTES]>,
+    signature_type: Option<crate::io::remote_attestation::EpidSignatureType>,
+) -> Result<(
     crate::io::remote_attestation::AttestationEvidence,
     ecies::PublicKey,
 )> {
-    // Generate a fresh SECP256K1 ETH keypair (saving ETH private key)
-    let pk = crate::crypto::eth_keys::eth_key_gen()?;
+    // Generate a fresh SECP256K1 ETH keypair
+    let (sk, pk) = crate::crypto::eth_keys::new_eth_key()?;
 
-    // Commit to the payload
-    let proof =
-        crate::io::remote_attestation::AttestationEvidence::new(&pk.serialize_compressed())?;
+    // Commit to the payload before saving, so a failed attestation leaves no key behind
+    let proof = crate::io::remote_attestation::AttestationEvidence::new_with_signature_type(
+        &pk.serialize_compressed(),
+        nonce,
+        signature_type,
+    )?;
+    let pk = crate::crypto::eth_keys::save_eth_key(sk, pk)
+        .with_context(|| "Failed to save generated ETH key")?;
     Ok((proof, pk))
 }
 
-fn attest_new_bls_key() -> Result<(
+fn attest_new_bls_key(
+    nonce: Option<&[u8; crate::io::remote_attestation::ATTESTATION_NONCE_BYTES]>,
+    signature_type: Option<crate::io::remote_attestation::EpidSignatureType>,
+    allowed_types: Option<Vec<String>>,
+    proposer_index: Option<u64>,
+    mac_secret: Option<Vec<u8>>,
+) -> Result<(
     crate::io::remote_attestation::AttestationEvidence,
     blsttc::PublicKey,
+    Vec<u8>,
 )> {
-    // Generate a fresh BLS keypair (saving BLS private key)
+    // Generate a fresh BLS keypair
     let sk = crate::crypto::bls_keys::new_bls_key(0);
     let pk = sk.public_keys().public_key();
+
+    // Commit to the payload before saving, so a failed attestation leaves no key behind
+    let proof = crate::io::remote_attestation::AttestationEvidence::new_with_signature_type(
+        &pk.to_bytes(),
+        nonce,
+        signature_type,
+    )?;
+    let proof_of_possession = crate::crypto::bls_keys::bls_proof_of_possession(&sk)?;
+
     crate::crypto::bls_keys::save_bls_key(&sk).with_context(|| "Failed to save BLS key")?;
+    crate::io::key_management::write_key_origin(
+        &pk.to_hex(),
+        crate::io::key_management::KeyOrigin::Generated,
+    )?;
+    if let Some(allowed_types) = allowed_types {
+        crate::io::key_management::write_sign_type_allowlist(&pk.to_hex(), &allowed_types)
+            .with_context(|| "Failed to save signing type allowlist")?;
+    }
+    if let Some(proposer_index) = proposer_index {
+        crate::io::key_management::write_proposer_index(&pk.to_hex(), proposer_index)
+            .with_context(|| "Failed to save proposer index")?;
+    }
+    if let Some(secret) = mac_secret {
+        crate::io::key_management::write_request_mac_secret(&pk.to_hex(), &secret)
+            .with_context(|| "Failed to save request MAC secret")?;
+    }
 
     // Create a new slashing protection database
     crate::eth2::slash_protection::SlashingProtectionData::from_pk_hex(&pk.to_hex())?.write()?;
+    Ok((proof, pk, proof_of_possession))
+}
 
-    // Commit to the payload
-    let proof = crate::io::remote_attestation::AttestationEvidence::new(&pk.to_bytes())?;
-    Ok((proof, pk))
+/// Returns the BLS secret key of `bls_pk_hex` ECIES-encrypted to `client_pk`
+pub fn export_bls_key(bls_pk_hex: &String, client_pk: &ecies::PublicKey) -> Result<Vec<u8>> {
+    let sk_set = crate::crypto::bls_keys::fetch_bls_sk(bls_pk_hex)
+        .with_context(|| "Failed to fetch BLS key")?;
+    crate::crypto::eth_keys::envelope_encrypt(client_pk, &sk_set.secret_key().to_bytes())
+}
+
+/// Returns the BLS secret key of `bls_pk_hex` ECIES-encrypted to `client_pk`, along with the
+/// key's slashing protection history as an EIP-3076 interchange. Callers hold the key's signing
+/// lock so no slashable signature lands between reading the history and the key. The key is left
+/// in place and keeps signing, it must be deleted before the exported copy is used.
+pub fn export_bls_key_with_slashing_protection(
+    bls_pk_hex: &String,
+    client_pk: &ecies::PublicKey,
+    genesis_validators_root: crate::eth2::eth_types::Root,
+) -> Result<crate::enclave::types::BlsKeyExportResponse> {
+    // Read the history first so a key is never exported without it
+    let slashing_protection = crate::eth2::slash_protection::SlashingProtectionDB::for_key(
+        bls_pk_hex,
+        genesis_validators_root,
+    )
+    .with_context(|| "Failed to read slashing protection data")?;
+    let encrypted_sk = export_bls_key(bls_pk_hex, client_pk)?;
+    Ok(crate::enclave::types::BlsKeyExportResponse {
+        pk_hex: format!("0x{bls_pk_hex}"),
+        encrypted_sk: format!("0x{}", hex::encode(encrypted_sk)),
+        slashing_protection,
+    })
+}
+
+/// Imports every keystore of `archive` that `archive.passwords` holds a password for, decrypting
+/// the passwords and MAC secrets with the enclave ETH key `envelope_sk`, then merges
+/// `interchange` into the slashing protection of the imported keys. Keys already in the enclave
+/// are reported as duplicates and left untouched, keys beyond `key_limit` as errors. If
+/// `require_slashing_protection` is set, keys without history in `interchange` are reported as
+/// errors rather than imported with an empty history.
+pub fn import_keystore_archive(
+    archive: &crate::io::keystore_archive::KeystoreArchive,
+    envelope_sk: &ecies::SecretKey,
+    interchange: Option<&crate::eth2::slash_protection::SlashingProtectionDB>,
+    key_limit: Option<&crate::enclave::shared::key_limit::KeyLimit>,
+    require_slashing_protection: bool,
+) -> Result<crate::enclave::types::KeystoreArchiveImportResponse> {
+    let data: Vec<crate::enclave::types::KeystoreImportResult> = archive
+        .keystores
+        .iter()
+        .map(|(file, keystore)| {
+            // Keys past the limit are reported as errors, the others are still imported
+            let slot = match key_limit.map(|limit| limit.reserve()).transpose() {
+                Ok(Some(None)) => Err(anyhow::anyhow!(
+                    "The enclave already holds the maximum of {} keys",
+                    key_limit.map_or(0, |limit| limit.max_keys())
+                )),
+                slot => slot.map(Option::flatten),
+            };
+            match slot.and_then(|slot| {
+                let imported = import_archived_keystore(
+                    keystore,
+                    envelope_sk,
+                    archive.passwords.get(file),
+                    archive.mac_secrets.get(file),
+                    archive.fee_recipients.get(file),
+                    interchange,
+                    require_slashing_protection,
+                )?;
+                if imported.1 == crate::enclave::types::KeystoreImportStatus::Imported {
+                    if let Some(slot) = slot {
+                        slot.saved();
+                    }
+                }
+                Ok(imported)
+            }) {
+                Ok((pk_hex, status)) => crate::enclave::types::KeystoreImportResult {
+                    file: file.clone(),
+                    pubkey: Some(format!("0x{pk_hex}")),
+                    status,
+                    message: None,
+                },
+                Err(e) => crate::enclave::types::KeystoreImportResult {
+                    file: file.clone(),
+                    pubkey: None,
+                    status: crate::enclave::types::KeystoreImportStatus::Error,
+                    // The error and its causes on one line, e.g. why the keystore isn't JSON
+                    message: Some(format!("{:#}", e)),
+                },
+            }
+        })
+        .collect();
+
+    // Only after the keys are saved, since the interchange skips keys unknown to the enclave.
+    // Duplicates keep their history as it is.
+    let imported: Vec<String> = data
+        .iter()
+        .filter(|r| r.status == crate::enclave::types::KeystoreImportStatus::Imported)
+        .filter_map(|r| r.pubkey.as_deref())
+        .map(|pk| pk.strip_prefix("0x").unwrap_or(pk).to_string())
+        .collect();
+    let slashing_protection = match interchange {
+        Some(db) => db.import_only(&imported)?,
+        None => vec![],
+    };
+    Ok(crate::enclave::types::KeystoreArchiveImportResponse {
+        data,
+        slashing_protection,
+    })
+}
+
+/// Why a single keystore import was rejected
+#[derive(Debug)]
+pub enum KeyImportError {
+    /// No enclave ETH key matches `encrypting_pk_hex`
+    UnknownEncryptingKey,
+    /// `ct_password_hex` could not be decrypted with the enclave ETH key
+    DecryptionFailed,
+    /// Slashing protection is required on import, but the interchange has no history for the key
+    MissingSlashingProtection(String),
+    /// The keystore or interchange is malformed, or the password doesn't open the keystore
+    BadRequest(anyhow::Error),
+    Internal(anyhow::Error),
+}
+
+impl std::fmt::Display for KeyImportError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            KeyImportError::UnknownEncryptingKey => {
+                write!(f, "encryption public key does not match any enclave key")
+            }
+            KeyImportError::DecryptionFailed => write!(f, "decryption failed"),
+            KeyImportError::MissingSlashingProtection(pk_hex) => {
+                write!(f, "no slashing protection history for 0x{pk_hex}")
+            }
+            KeyImportError::BadRequest(e) => write!(f, "{:#}", e),
+            KeyImportError::Internal(e) => write!(f, "{:?}", e),
+        }
+    }
+}
+
+/// Imports an EIP-2335 keystore whose password is ECIES-encrypted to the enclave ETH key
+/// `encrypting_pk_hex`, then merges the request's interchange into the key's slashing protection.
+/// If `require_slashing_protection` is set, a key without history in the interchange is rejected
+/// rather than imported with an empty history. A key already in the enclave is reported as a
+/// duplicate, and neither it nor its slashing protection is touched.
+pub fn import_keystore(
+    req: &crate::enclave::types::KeyImportRequest,
+    require_slashing_protection: bool,
+) -> Result<crate::enclave::types::KeyImportResponse, KeyImportError> {
+    // Tell a password encrypted to the wrong key apart from one that is corrupt
+    if !crate::io::key_management::eth_key_exists(&req.encrypting_pk_hex) {
+        return Err(KeyImportError::UnknownEncryptingKey);
+    }
+    let envelope_sk = crate::crypto::eth_keys::fetch_eth_key(&req.encrypting_pk_hex)
+        .map_err(KeyImportError::Internal)?;
+    let ct_password_hex: &str = crate::strip_0x_prefix!(req.ct_password_hex);
+    let ct_password = hex::decode(ct_password_hex).map_err(|e| {
+        KeyImportError::BadRequest(anyhow::anyhow!("ct_password_hex is not hex: {e}"))
+    })?;
+    let password = crate::crypto::eth_keys::envelope_decrypt(&envelope_sk, &ct_password)
+        .ok()
+        .and_then(|password| String::from_utf8(password).ok())
+        .ok_or(KeyImportError::DecryptionFailed)?;
+    let mac_secret = req
+        .ct_mac_secret_hex
+        .as_ref()
+        .map(|ct_mac_secret_hex| decrypt_mac_secret(&envelope_sk, ct_mac_secret_hex))
+        .transpose()
+        .map_err(KeyImportError::BadRequest)?;
+
+    // Reject a malformed interchange before importing the key without its history
+    let interchange = req
+        .slashing_protection
+        .as_deref()
+        .map(crate::eth2::slash_protection::SlashingProtectionDB::from_str)
+        .transpose()
+        .map_err(KeyImportError::BadRequest)?;
+    let fee_recipients = req
+        .fee_recipients
+        .as_deref()
+        .map(crate::io::key_management::parse_fee_recipient_allowlist)
+        .transpose()
+        .map_err(KeyImportError::BadRequest)?;
+    let allowed_types = req
+        .allowed_types
+        .as_deref()
+        .map(crate::io::key_management::parse_sign_type_allowlist)
+        .transpose()
+        .map_err(KeyImportError::BadRequest)?;
+
+    let sk_bytes = crate::crypto::keystore::decrypt_keystore(&req.keystore, &password)
+        .map_err(KeyImportError::BadRequest)?;
+    let sk_set = crate::crypto::keystore::verify_keystore_pubkey(&req.keystore, &sk_bytes)
+        .map_err(KeyImportError::BadRequest)?;
+
+    // Re-importing a key already in the enclave changes nothing, least of all its slashing
+    // protection history, so provisioning can be re-run safely
+    let pk_hex = sk_set.public_keys().public_key().to_hex();
+    if crate::io::key_management::bls_key_exists(&pk_hex) {
+        return Ok(crate::enclave::types::KeyImportResponse {
+            data: vec![crate::enclave::types::KeystoreImportOutcome {
+                status: crate::enclave::types::KeystoreImportStatus::Duplicate,
+                message: None,
+            }],
+            pubkey: format!("0x{pk_hex}"),
+            slashing_protection: vec![],
+        });
+    }
+
+    // A key that signed elsewhere but arrives without its history could be made to double sign
+    if require_slashing_protection && !has_slashing_protection(interchange.as_ref(), &pk_hex) {
+        return Err(KeyImportError::MissingSlashingProtection(pk_hex));
+    }
+
+    let (pk_hex, status) = save_imported_key(
+        &sk_set,
+        mac_secret,
+        fee_recipients,
+        allowed_types,
+        req.proposer_index,
+    )
+    .map_err(KeyImportError::Internal)?;
+
+    // A concurrent import of the same key may have saved it first
+    let slashing_protection = match interchange {
+        Some(db) if status == crate::enclave::types::KeystoreImportStatus::Imported => {
+            db.import().map_err(KeyImportError::Internal)?
+        }
+        _ => vec![],
+    };
+    Ok(crate::enclave::types::KeyImportResponse {
+        data: vec![crate::enclave::types::KeystoreImportOutcome {
+            status,
+            message: None,
+        }],
+        pubkey: format!("0x{pk_hex}"),
+        slashing_protection,
+    })
+}
+
+/// Whether `interchange` holds slashing protection history for `pk_hex`
+fn has_slashing_protection(
+    interchange: Option<&crate::eth2::slash_protection::SlashingProtectionDB>,
+    pk_hex: &str,
+) -> bool {
+    interchange.map_or(false, |db| {
+        db.data.iter().any(|d| hex::encode(&d.pubkey[..]) == pk_hex)
+    })
+}
+
+/// Decrypts an EIP-2335 keystore and saves its key with a fresh slashing protection database,
+/// along with the secret authenticating its sign requests and its fee recipient allowlist if given.
+/// The password and secret are hex ECIES ciphertexts opened with `envelope_sk`.
+/// If `require_slashing_protection` is set, a new key without history in `interchange` is
+/// rejected. Returns the hex pubkey and whether it was imported or already known.
+fn import_archived_keystore(
+    keystore: &String,
+    envelope_sk: &ecies::SecretKey,
+    password: Option<&String>,
+    mac_secret: Option<&String>,
+    fee_recipients: Option<&Vec<String>>,
+    interchange: Option<&crate::eth2::slash_protection::SlashingProtectionDB>,
+    require_slashing_protection: bool,
+) -> Result<(String, crate::enclave::types::KeystoreImportStatus)> {
+    let mac_secret = mac_secret
+        .map(|ct_mac_secret_hex| decrypt_mac_secret(envelope_sk, ct_mac_secret_hex))
+        .transpose()?;
+    let fee_recipients = fee_recipients
+        .map(|f| crate::io::key_management::parse_fee_recipient_allowlist(f))
+        .transpose()?;
+
+    let Some(ct_password_hex) = password else {
+        anyhow::bail!(
+            "No password for this keystore in {}",
+            crate::io::keystore_archive::PASSWORDS_FILE
+        )
+    };
+    let password = envelope_decrypt_hex(envelope_sk, ct_password_hex)
+        .ok()
+        .and_then(|password| String::from_utf8(password).ok())
+        .ok_or_else(|| anyhow::anyhow!("Failed to decrypt keystore password"))?;
+    let sk_bytes = crate::crypto::keystore::decrypt_keystore(keystore, &password)?;
+    let sk_set = crate::crypto::keystore::verify_keystore_pubkey(keystore, &sk_bytes)?;
+
+    // Duplicates are left untouched, so only new keys need their history
+    let pk_hex = sk_set.public_keys().public_key().to_hex();
+    if require_slashing_protection
+        && !crate::io::key_management::bls_key_exists(&pk_hex)
+        && !has_slashing_protection(interchange, &pk_hex)
+    {
+        return Err(anyhow::anyhow!(KeyImportError::MissingSlashingProtection(
+            pk_hex
+        )));
+    }
+    save_imported_key(&sk_set, mac_secret, fee_recipients, None, None)
+}
+
+/// Decrypts a hex request MAC secret ECIES-encrypted to the enclave ETH key `envelope_sk`
+pub(crate) fn decrypt_mac_secret(
+    envelope_sk: &ecies::SecretKey,
+    ct_mac_secret_hex: &String,
+) -> Result<Vec<u8>> {
+    let secret = envelope_decrypt_hex(envelope_sk, ct_mac_secret_hex)
+        .with_context(|| "Failed to decrypt request MAC secret")?;
+    if secret.is_empty() {
+        anyhow::bail!("Request MAC secret is empty")
+    }
+    Ok(secret)
+}
+
+/// Decrypts a hex ECIES ciphertext with the enclave ETH key `envelope_sk`
+fn envelope_decrypt_hex(envelope_sk: &ecies::SecretKey, ct_hex: &String) -> Result<Vec<u8>> {
+    let ct_hex: &str = crate::strip_0x_prefix!(ct_hex);
+    let ct = hex::decode(ct_hex).with_context(|| "Ciphertext is not hex")?;
+    crate::crypto::eth_keys::envelope_decrypt(envelope_sk, &ct)
+}
+
+/// Saves an imported key with a fresh slashing protection database, along with the secret
+/// authenticating its sign requests, the fee recipients it may register with, the request types
+/// it may sign and the validator index it may propose blocks as if given. Keys already in the enclave are left untouched. Returns the hex
+/// pubkey and whether it was imported or already known.
+fn save_imported_key(
+    sk_set: &blsttc::SecretKeySet,
+    mac_secret: Option<Vec<u8>>,
+    fee_recipients: Option<Vec<String>>,
+    allowed_types: Option<Vec<String>>,
+    proposer_index: Option<u64>,
+) -> Result<(String, crate::enclave::types::KeystoreImportStatus)> {
+    let pk_hex = sk_set.public_keys().public_key().to_hex();
+
+    if crate::io::key_management::bls_key_exists(&pk_hex) {
+        return Ok((
+            pk_hex,
+            crate::enclave::types::KeystoreImportStatus::Duplicate,
+        ));
+    }
+    crate::crypto::bls_keys::save_bls_key(sk_set).with_context(|| "Failed to save BLS key")?;
+    crate::io::key_management::write_key_origin(
+        &pk_hex,
+        crate::io::key_management::KeyOrigin::Imported,
+    )?;
+    if let Some(secret) = mac_secret {
+        crate::io::key_management::write_request_mac_secret(&pk_hex, &secret)
+            .with_context(|| "Failed to save request MAC secret")?;
+    }
+    if let Some(fee_recipients) = fee_recipients {
+        crate::io::key_management::write_fee_recipient_allowlist(&pk_hex, &fee_recipients)
+            .with_context(|| "Failed to save fee recipient allowlist")?;
+    }
+    if let Some(allowed_types) = allowed_types {
+        crate::io::key_management::write_sign_type_allowlist(&pk_hex, &allowed_types)
+            .with_context(|| "Failed to save signing type allowlist")?;
+    }
+    if let Some(proposer_index) = proposer_index {
+        crate::io::key_management::write_proposer_index(&pk_hex, proposer_index)
+            .with_context(|| "Failed to save proposer index")?;
+    }
+
+    // Create a new slashing protection database, unless one outlived an earlier copy of the key
+    if crate::eth2::slash_protection::SlashingProtectionData::try_read(&pk_hex)?.is_none() {
+        crate::eth2::slash_protection::SlashingProtectionData::from_pk_hex(&pk_hex)?.write()?;
+    }
+    Ok((
+        pk_hex,
+        crate::enclave::types::KeystoreImportStatus::Imported,
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_export_bls_key_decrypts_on_client() {
+        let (_, bls_pk, _) = attest_new_bls_key(None, None, None, None, None).unwrap();
+        let (client_sk, client_pk) = crate::crypto::eth_keys::new_eth_key().unwrap();
+
+        let encrypted_sk = export_bls_key(&bls_pk.to_hex(), &client_pk).unwrap();
+
+        // Client side
+        let sk_bytes =
+            crate::crypto::eth_keys::envelope_decrypt(&client_sk, &encrypted_sk).unwrap();
+        let mut sk_arr = [0_u8; 32];
+        sk_arr.copy_from_slice(&sk_bytes);
+        let sk = blsttc::SecretKey::from_bytes(sk_arr).unwrap();
+        assert_eq!(sk.public_key(), bls_pk);
+    }
+
+    #[test]
+    fn test_export_bls_key_fails_for_unknown_key() {
+        let (_, client_pk) = crate::crypto::eth_keys::new_eth_key().unwrap();
+        let unknown_pk = crate::crypto::bls_keys::new_bls_key(0)
+            .public_keys()
+            .public_key()
+            .to_hex();
+        assert!(export_bls_key(&unknown_pk, &client_pk).is_err());
+    }
 }
@@ -0,0 +1,171 @@
+use std::future::Future;
+use std::io;
+use std::pin::Pin;
+use std::sync::Arc;
+use std::task::{Context, Poll};
+use std::time::Duration;
+use tokio::io::{AsyncRead, AsyncWrite, ReadBuf};
+use tokio::sync::{AcquireError, OwnedSemaphorePermit, Semaphore};
+
+/// Caps on the connections a server holds open, unlimited by default
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct ConnectionLimits {
+    /// Connections served at once. Further ones are left in the listen backlog until one closes.
+    pub max_connections: Option<usize>,
+    /// Time a keep-alive connection may wait for its next request before it is closed
+    pub keep_alive_timeout: Option<Duration>,
+}
+
+type Acquire = Pin<Box<dyn Future<Output = Result<OwnedSemaphorePermit, AcquireError>> + Send>>;
+
+/// Accepts connections from `inner` within `ConnectionLimits`
+pub(crate) struct LimitedAccept<A> {
+    inner: A,
+    permits: Option<Arc<Semaphore>>,
+    acquire: Option<Acquire>,
+    permit: Option<OwnedSemaphorePermit>,
+    keep_alive_timeout: Option<Duration>,
+}
+
+impl<A> LimitedAccept<A> {
+    pub(crate) fn new(inner: A, limits: ConnectionLimits) -> Self {
+        LimitedAccept {
+            inner,
+            permits: limits
+                .max_connections
+                .map(|max| Arc::new(Semaphore::new(max.max(1)))),
+            acquire: None,
+            permit: None,
+            keep_alive_timeout: limits.keep_alive_timeout,
+        }
+    }
+}
+
+impl<A> hyper::server::accept::Accept for LimitedAccept<A>
+where
+    A: hyper::server::accept::Accept + Unpin,
+    A::Conn: Unpin,
+{
+    type Conn = LimitedConn<A::Conn>;
+    type Error = A::Error;
+
+    fn poll_accept(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+    ) -> Poll<Option<Result<Self::Conn, Self::Error>>> {
+        let this = self.get_mut();
+        // Only accept once a connection slot is free, so excess clients wait in the backlog
+        if let Some(permits) = this.permits.as_ref().filter(|_| this.permit.is_none()) {
+            let acquire = this
+                .acquire
+                .get_or_insert_with(|| Box::pin(permits.clone().acquire_owned()));
+            match acquire.as_mut().poll(cx) {
+                Poll::Pending => return Poll::Pending,
+                Poll::Ready(permit) => {
+                    this.acquire = None;
+                    this.permit = Some(permit.expect("connection semaphore is never closed"));
+                }
+            }
+        }
+        Pin::new(&mut this.inner).poll_accept(cx).map(|conn| {
+            conn.map(|conn| {
+                conn.map(|conn| LimitedConn::new(conn, this.permit.take(), this.keep_alive_timeout))
+            })
+        })
+    }
+}
+
+/// A connection holding its slot until dropped, and closing itself once idle for longer than
+/// the keep-alive timeout
+pub(crate) struct LimitedConn<C> {
+    inner: C,
+    _permit: Option<OwnedSemaphorePermit>,
+    keep_alive_timeout: Option<Duration>,
+    /// Armed while waiting for a request: on accept and after each response is flushed
+    idle: Option<Pin<Box<tokio::time::Sleep>>>,
+}
+
+impl<C> LimitedConn<C> {
+    fn new(
+        inner: C,
+        permit: Option<OwnedSemaphorePermit>,
+        keep_alive_timeout: Option<Duration>,
+    ) -> Self {
+        let mut conn = LimitedConn {
+            inner,
+            _permit: permit,
+            keep_alive_timeout,
+            idle: None,
+        };
+        conn.arm_idle();
+        conn
+    }
+
+    fn arm_idle(&mut self) {
+        self.idle = self
+            .keep_alive_timeout
+            .map(|timeout| Box::pin(tokio::time::sleep(timeout)));
+    }
+}
+
+impl<C: AsyncRead + Unpin> AsyncRead for LimitedConn<C> {
+    fn poll_read(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut ReadBuf<'_>,
+    ) -> Poll<io::Result<()>> {
+        let this = self.get_mut();
+        let filled = buf.filled().len();
+        match Pin::new(&mut this.inner).poll_read(cx, buf) {
+            // A request is coming in, so the connection is busy until its response is flushed
+            Poll::Ready(Ok(())) if buf.filled().len() > filled => {
+                this.idle = None;
+                Poll::Ready(Ok(()))
+            }
+            Poll::Pending => match &mut this.idle {
+                // Reading nothing is EOF to hyper, which then closes the connection
+                Some(idle) if idle.as_mut().poll(cx).is_ready() => {
+                    log::debug!("Closing idle keep-alive connection");
+                    Poll::Ready(Ok(()))
+                }
+                _ => Poll::Pending,
+            },
+            res => res,
+        }
+    }
+}
+
+impl<C: AsyncWrite + Unpin> AsyncWrite for LimitedConn<C> {
+    fn poll_write(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &[u8],
+    ) -> Poll<io::Result<usize>> {
+        Pin::new(&mut self.get_mut().inner).poll_write(cx, buf)
+    }
+
+    fn poll_write_vectored(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        bufs: &[io::IoSlice<'_>],
+    ) -> Poll<io::Result<usize>> {
+        Pin::new(&mut self.get_mut().inner).poll_write_vectored(cx, bufs)
+    }
+
+    fn is_write_vectored(&self) -> bool {
+        self.inner.is_write_vectored()
+    }
+
+    fn poll_flush(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        let this = self.get_mut();
+        let res = Pin::new(&mut this.inner).poll_flush(cx);
+        if let Poll::Ready(Ok(())) = res {
+            this.arm_idle();
+        }
+        res
+    }
+
+    fn poll_shutdown(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        Pin::new(&mut self.get_mut().inner).poll_shutdown(cx)
+    }
+}
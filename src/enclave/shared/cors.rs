@@ -0,0 +1,253 @@
+use anyhow::{bail, Context, Result};
+use axum::{
+    extract::{MatchedPath, State},
+    http::{header, HeaderValue, Method, Request, StatusCode},
+    middleware::Next,
+    response::{IntoResponse, Response},
+};
+
+/// Routes browsers may call cross-origin unless configured otherwise. Some of them also serve
+/// POST, so on these routes only `READ_ONLY_METHODS` are allowed whatever methods are configured.
+pub const DEFAULT_CORS_ROUTES: &[&str] = &[
+    "/upcheck",
+    "/healthz",
+    "/livez",
+    "/readyz",
+    "/version",
+    "/metrics",
+    "/eth/v1/enclave-info",
+    "/eth/v1/keygen/secp256k1",
+    "/eth/v1/keystores",
+    "/eth/v1/remotekeys",
+    "/api/v1/eth2/publicKeys",
+    "/eth/v1/slashing/:bls_pk_hex",
+    "/eth/v1/stats/:bls_pk_hex",
+    "/eth/v1/slashing-protection/floor",
+];
+
+/// Methods allowed cross-origin on the `DEFAULT_CORS_ROUTES`
+pub const READ_ONLY_METHODS: &[Method] = &[Method::GET, Method::HEAD];
+
+/// Prefix of the signing routes, which never answer cross-origin requests
+pub const SIGN_ROUTE_PREFIX: &str = "/api/v1/eth2/sign";
+
+/// Routes that sign or hand out key material besides the signing routes, which never answer
+/// cross-origin requests either
+pub const NO_CORS_ROUTES: &[&str] = &[
+    "/api/v1/eth2/deposit",
+    "/eth/v1/keystores/:bls_pk_hex/export",
+];
+
+/// Returns true if CORS is never allowed on `route`
+fn never_cors(route: &str) -> bool {
+    route.starts_with(SIGN_ROUTE_PREFIX) || NO_CORS_ROUTES.contains(&route)
+}
+
+/// Seconds a browser may cache a preflight response
+pub const CORS_MAX_AGE_SECS: u64 = 600;
+
+/// Which origins may call which routes with which methods from a browser. Without any allowed
+/// origins no CORS headers are sent, and browsers keep blocking cross-origin calls.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Cors {
+    origins: Vec<String>,
+    methods: Vec<Method>,
+    routes: Vec<String>,
+}
+
+impl Default for Cors {
+    fn default() -> Self {
+        Cors {
+            origins: vec![],
+            methods: READ_ONLY_METHODS.to_vec(),
+            routes: DEFAULT_CORS_ROUTES.iter().map(|r| r.to_string()).collect(),
+        }
+    }
+}
+
+/// Splits a comma-separated list, dropping blanks
+fn split_list(list: &str) -> impl Iterator<Item = &str> {
+    list.split(',').map(str::trim).filter(|s| !s.is_empty())
+}
+
+impl Cors {
+    /// Allows the comma-separated `origins`, e.g. `https://dashboard.example.com`, or `*` for any
+    pub fn new(origins: &str) -> Result<Self> {
+        let origins: Vec<String> = split_list(origins)
+            .map(|o| o.trim_end_matches('/').to_string())
+            .collect();
+        for origin in &origins {
+            HeaderValue::from_str(origin).with_context(|| format!("Bad CORS origin {origin}"))?;
+        }
+        Ok(Cors {
+            origins,
+            ..Default::default()
+        })
+    }
+
+    /// Replaces the allowed methods with the comma-separated `methods`
+    pub fn with_methods(mut self, methods: &str) -> Result<Self> {
+        self.methods = split_list(methods)
+            .map(|m| {
+                Method::from_bytes(m.to_ascii_uppercase().as_bytes())
+                    .with_context(|| format!("Bad CORS method {m}"))
+            })
+            .collect::<Result<_>>()?;
+        if self.methods.is_empty() {
+            bail!("No CORS methods")
+        }
+        Ok(self)
+    }
+
+    /// Replaces the routes CORS applies to with the comma-separated route patterns `routes`. The
+    /// signing, deposit and export routes are refused.
+    pub fn with_routes(mut self, routes: &str) -> Result<Self> {
+        self.routes = split_list(routes).map(str::to_string).collect();
+        if let Some(route) = self.routes.iter().find(|r| never_cors(r)) {
+            bail!("CORS is never allowed on {route}, it signs or exports keys")
+        }
+        Ok(self)
+    }
+
+    /// The methods allowed cross-origin on `route`
+    fn methods_for(&self, route: &str) -> Vec<Method> {
+        if !DEFAULT_CORS_ROUTES.contains(&route) {
+            return self.methods.clone();
+        }
+        self.methods
+            .iter()
+            .filter(|m| READ_ONLY_METHODS.contains(m))
+            .cloned()
+            .collect()
+    }
+
+    /// The `Access-Control-Allow-Origin` value for `origin`, `None` if it isn't allowed
+    fn allow_origin(&self, origin: &HeaderValue) -> Option<HeaderValue> {
+        if self.origins.iter().any(|o| o == "*") {
+            return Some(HeaderValue::from_static("*"));
+        }
+        let origin = origin.to_str().ok()?;
+        if !self.origins.iter().any(|o| o == origin) {
+            return None;
+        }
+        HeaderValue::from_str(origin).ok()
+    }
+}
+
+fn allow_methods(methods: &[Method]) -> HeaderValue {
+    let methods: Vec<&str> = methods.iter().map(Method::as_str).collect();
+    HeaderValue::from_str(&methods.join(", ")).expect("methods are valid header values")
+}
+
+/// Adds CORS headers to responses of the configured routes for allowed origins and methods, and
+/// answers their preflight requests. Everything else passes through untouched.
+pub async fn middleware<B>(State(cors): State<Cors>, req: Request<B>, next: Next<B>) -> Response {
+    let methods = req
+        .extensions()
+        .get::<MatchedPath>()
+        .map(MatchedPath::as_str)
+        .filter(|route| !never_cors(route) && cors.routes.iter().any(|r| r == route))
+        .map(|route| cors.methods_for(route));
+    let Some((methods, allow_origin)) = methods.and_then(|methods| {
+        let origin = req.headers().get(header::ORIGIN)?;
+        Some((methods, cors.allow_origin(origin)?))
+    }) else {
+        return next.run(req).await;
+    };
+
+    if req.method() == Method::OPTIONS {
+        if let Some(requested) = req.headers().get(header::ACCESS_CONTROL_REQUEST_METHOD) {
+            let allowed =
+                Method::from_bytes(requested.as_bytes()).map_or(false, |m| methods.contains(&m));
+            if !allowed {
+                return StatusCode::FORBIDDEN.into_response();
+            }
+            return (
+                StatusCode::NO_CONTENT,
+                [
+                    (header::ACCESS_CONTROL_ALLOW_ORIGIN, allow_origin),
+                    (
+                        header::ACCESS_CONTROL_ALLOW_METHODS,
+                        allow_methods(&methods),
+                    ),
+                    (
+                        header::ACCESS_CONTROL_ALLOW_HEADERS,
+                        HeaderValue::from_static("content-type"),
+                    ),
+                    (
+                        header::ACCESS_CONTROL_MAX_AGE,
+                        HeaderValue::from(CORS_MAX_AGE_SECS),
+                    ),
+                    (header::VARY, HeaderValue::from_static("origin")),
+                ],
+            )
+                .into_response();
+        }
+    }
+
+    if !methods.contains(req.method()) {
+        return next.run(req).await;
+    }
+    let mut response = next.run(req).await;
+    let headers = response.headers_mut();
+    headers.insert(header::ACCESS_CONTROL_ALLOW_ORIGIN, allow_origin);
+    headers.append(header::VARY, HeaderValue::from_static("origin"));
+    response
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_cors() {
+        let cors = Cors::new("https://a.example, https://b.example/,")
+            .unwrap()
+            .with_methods("get,post")
+            .unwrap();
+        assert_eq!(cors.origins, vec!["https://a.example", "https://b.example"]);
+        assert_eq!(cors.methods, vec![Method::GET, Method::POST]);
+        assert_eq!(cors.routes.len(), DEFAULT_CORS_ROUTES.len());
+        assert!(cors
+            .allow_origin(&HeaderValue::from_static("https://b.example"))
+            .is_some());
+        assert!(cors
+            .allow_origin(&HeaderValue::from_static("https://c.example"))
+            .is_none());
+        assert_eq!(
+            Cors::new("*")
+                .unwrap()
+                .allow_origin(&HeaderValue::from_static("https://c.example")),
+            Some(HeaderValue::from_static("*"))
+        );
+
+        assert!(Cors::new("bad\norigin").is_err());
+        assert!(Cors::default().with_methods(" ,").is_err());
+        assert!(Cors::default()
+            .with_routes("/eth/v1/keystores, /api/v1/eth2/sign/:bls_pk_hex")
+            .is_err());
+        assert!(Cors::default()
+            .with_routes("/api/v1/eth2/sign/:bls_pk_hex/raw")
+            .is_err());
+        assert!(Cors::default().with_routes("/api/v1/eth2/deposit").is_err());
+        assert!(Cors::default()
+            .with_routes("/eth/v1/keystores/:bls_pk_hex/export")
+            .is_err());
+    }
+
+    #[test]
+    fn test_default_routes_stay_read_only() {
+        let cors = Cors::new("*").unwrap().with_methods("GET, POST").unwrap();
+        assert_eq!(cors.methods_for("/eth/v1/keystores"), vec![Method::GET]);
+        assert_eq!(
+            cors.methods_for("/eth/v1/keygen/secp256k1"),
+            vec![Method::GET]
+        );
+
+        let cors = cors.with_routes("/eth/v1/slashing-protection").unwrap();
+        assert_eq!(
+            cors.methods_for("/eth/v1/slashing-protection"),
+            vec![Method::GET, Method::POST]
+        );
+    }
+}
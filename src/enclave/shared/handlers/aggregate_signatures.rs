@@ -0,0 +1,37 @@
+use axum::{response::IntoResponse, Json};
+use log::{error, info};
+
+use crate::enclave::types::{AggregateSignaturesRequest, SignatureResponse};
+use crate::strip_0x_prefix;
+
+/// Aggregates BLS signatures over the same message, returning the aggregate as a
+/// `SignatureResponse`. Touches no key material.
+pub async fn handler(Json(req): Json<AggregateSignaturesRequest>) -> axum::response::Response {
+    info!("aggregate_signatures()");
+
+    let sigs: Result<Vec<Vec<u8>>, _> = req
+        .signatures
+        .iter()
+        .enumerate()
+        .map(|(i, sig)| {
+            let sig: String = strip_0x_prefix!(sig);
+            hex::decode(sig).map_err(|e| anyhow::anyhow!("Signature at index {i} is not hex: {e}"))
+        })
+        .collect();
+
+    match sigs.and_then(|sigs| crate::crypto::bls_keys::bls_aggregate_signatures(&sigs)) {
+        Ok(agg) => (
+            axum::http::status::StatusCode::OK,
+            Json(SignatureResponse::new(&agg.to_bytes())),
+        )
+            .into_response(),
+        Err(e) => {
+            error!("Bad signatures to aggregate: {:?}", e);
+            (
+                axum::http::status::StatusCode::BAD_REQUEST,
+                format!("Bad signatures, {:?}", e),
+            )
+                .into_response()
+        }
+    }
+}
@@ -0,0 +1,75 @@
+use axum::{
+    extract::State,
+    http::{header::AUTHORIZATION, HeaderMap, StatusCode},
+    response::IntoResponse,
+    Json,
+};
+use log::{error, info};
+
+/// Returns the signer's effective configuration, without secrets, so operators can check it
+/// matches what they provisioned. Requires `Authorization: Bearer <CONFIG_TOKEN>`, not found if
+/// no token is configured.
+pub async fn handler(
+    State(state): State<crate::enclave::shared::handlers::AppState>,
+    headers: HeaderMap,
+) -> axum::response::Response {
+    info!("config()");
+    let Some(config_token) = &state.config_token else {
+        return (StatusCode::NOT_FOUND, "Config endpoint is not enabled").into_response();
+    };
+
+    let token = headers
+        .get(AUTHORIZATION)
+        .and_then(|v| v.to_str().ok())
+        .and_then(|v| v.strip_prefix("Bearer "));
+    let authenticated = token.map_or(false, |t| {
+        t.len() == config_token.len() && openssl::memcmp::eq(t.as_bytes(), config_token.as_bytes())
+    });
+    if !authenticated {
+        error!("Unauthenticated config request");
+        return (StatusCode::UNAUTHORIZED, "Bad config token").into_response();
+    }
+
+    let resp = crate::enclave::types::ConfigResponse::new(&state);
+    (StatusCode::OK, Json(resp)).into_response()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::enclave::shared::handlers::AppState;
+    use axum::http::HeaderValue;
+
+    fn server(config_token: Option<&str>) -> axum_test::TestServer {
+        let app = axum::Router::new()
+            .route("/eth/v1/config", axum::routing::get(handler))
+            .with_state(AppState {
+                config_token: config_token.map(str::to_string),
+                ..Default::default()
+            })
+            .into_make_service();
+        axum_test::TestServer::new(app).unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_config_requires_the_token() {
+        let resp = server(None).get("/eth/v1/config").await;
+        assert_eq!(resp.status_code(), 404);
+
+        let server = server(Some("secret"));
+        let resp = server.get("/eth/v1/config").await;
+        assert_eq!(resp.status_code(), 401);
+        let resp = server
+            .get("/eth/v1/config")
+            .add_header(AUTHORIZATION, HeaderValue::from_static("Bearer secreT"))
+            .await;
+        assert_eq!(resp.status_code(), 401);
+
+        let resp = server
+            .get("/eth/v1/config")
+            .add_header(AUTHORIZATION, HeaderValue::from_static("Bearer secret"))
+            .await;
+        assert_eq!(resp.status_code(), 200);
+        assert!(!resp.text().contains("secret"));
+    }
+}
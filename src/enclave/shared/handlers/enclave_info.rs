@@ -0,0 +1,39 @@
+use axum::{response::IntoResponse, Json};
+use log::{error, info};
+
+/// Returns the running enclave's measurements so clients can check them against its quotes
+pub async fn handler() -> axum::response::Response {
+    info!("enclave_info()");
+    match crate::io::remote_attestation::EnclaveInfo::read() {
+        Ok(enclave_info) => {
+            (axum::http::status::StatusCode::OK, Json(enclave_info)).into_response()
+        }
+        Err(e) => {
+            error!("enclave_info() failed with: {:?}", e);
+            axum::http::status::StatusCode::INTERNAL_SERVER_ERROR.into_response()
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::io::remote_attestation::EnclaveInfo;
+
+    #[tokio::test]
+    async fn test_enclave_info_is_well_formed() {
+        let app = axum::Router::new()
+            .route("/eth/v1/enclave-info", axum::routing::get(handler))
+            .into_make_service();
+        let server = axum_test::TestServer::new(app).unwrap();
+        let resp = server.get("/eth/v1/enclave-info").await;
+        assert_eq!(resp.status_code(), 200);
+        let info: EnclaveInfo = resp.json();
+
+        for measurement in [&info.mrenclave, &info.mrsigner] {
+            assert_eq!(measurement.len(), 64);
+            assert!(hex::decode(measurement).is_ok());
+        }
+        assert!(["EPID", "NONE"].contains(&info.attestation_mode.as_str()));
+    }
+}
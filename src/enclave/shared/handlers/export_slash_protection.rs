@@ -0,0 +1,40 @@
+use axum::{extract::Query, response::IntoResponse, Json};
+use log::{error, info};
+use serde::Deserialize;
+
+#[derive(Deserialize, Debug, Default)]
+pub struct ExportSlashProtectionQuery {
+    /// Written to the interchange metadata so the target signer accepts it for its network
+    pub genesis_validators_root: Option<String>,
+    /// `complete` for the full history of every key, the default, or `minimal` for only the
+    /// high-water marks
+    #[serde(default)]
+    pub format: crate::eth2::slash_protection::InterchangeFormat,
+}
+
+/// Returns an EIP-3076 interchange with the slashing protection of every key, in the requested
+/// format, for migrating the keys to another signer.
+pub async fn handler(Query(query): Query<ExportSlashProtectionQuery>) -> axum::response::Response {
+    info!("export_slash_protection()");
+    let genesis_validators_root = match super::slash_protection_floor::parse_genesis_validators_root(
+        query.genesis_validators_root.as_deref(),
+    ) {
+        Ok(root) => root,
+        Err(response) => return response,
+    };
+
+    match crate::eth2::slash_protection::SlashingProtectionDB::export(
+        genesis_validators_root,
+        query.format,
+    ) {
+        Ok(db) => (axum::http::status::StatusCode::OK, Json(db)).into_response(),
+        Err(e) => {
+            error!("export_slash_protection() failed with: {:?}", e);
+            (
+                axum::http::status::StatusCode::INTERNAL_SERVER_ERROR,
+                format!("Failed to export slashing protection: {:?}", e),
+            )
+                .into_response()
+        }
+    }
+}
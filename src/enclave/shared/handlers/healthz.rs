@@ -0,0 +1,34 @@
+use axum::response::IntoResponse;
+use log::error;
+
+/// Deep health check: returns 200 only if keys can actually be sealed, persisted and read back
+pub async fn handler() -> axum::response::Response {
+    match crate::io::key_management::storage_health_check() {
+        Ok(()) => (axum::http::status::StatusCode::OK).into_response(),
+        Err(e) => {
+            error!("Storage health check failed: {:?}", e);
+            (
+                axum::http::status::StatusCode::SERVICE_UNAVAILABLE,
+                format!("Storage health check failed: {:?}", e),
+            )
+                .into_response()
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::io::config::Config;
+
+    #[tokio::test]
+    async fn test_healthz_unwritable_data_dir() {
+        let _data_dir = Config::scoped_temp().unwrap();
+        assert_eq!(handler().await.status(), 200);
+
+        let file = Config::current().data_dir.join("file");
+        std::fs::write(&file, "").unwrap();
+        let _unwritable = Config::new(file.join("data")).scoped();
+        assert_eq!(handler().await.status(), 503);
+    }
+}
@@ -0,0 +1,40 @@
+use axum::{extract::Path, response::IntoResponse, Json};
+use log::{error, info};
+
+/// Returns the `HighWaterMarks` of `bls_pk_hex`, i.e. the latest block slot and attestation
+/// epochs it signed. Returns 404 if no slashing protection data exists for the key.
+pub async fn handler(Path(bls_pk_hex): Path<String>) -> axum::response::Response {
+    info!("high_water_marks()");
+
+    let bls_pk_hex = match crate::crypto::bls_keys::validate_bls_pk_hex(&bls_pk_hex) {
+        Ok(pk) => pk,
+        Err(e) => {
+            error!("Bad BLS public key format: {bls_pk_hex}");
+            return (
+                axum::http::status::StatusCode::BAD_REQUEST,
+                format!("Bad bls_pk_hex, {:?}", e),
+            )
+                .into_response();
+        }
+    };
+
+    match crate::eth2::slash_protection::SlashingProtectionData::try_read(&bls_pk_hex) {
+        Ok(Some(data)) => {
+            let marks = crate::eth2::slash_protection::HighWaterMarks::from(&data);
+            (axum::http::status::StatusCode::OK, Json(marks)).into_response()
+        }
+        Ok(None) => (
+            axum::http::status::StatusCode::NOT_FOUND,
+            format!("No slashing protection data for bls_pk_hex 0x{bls_pk_hex}"),
+        )
+            .into_response(),
+        Err(e) => {
+            error!("Failed to read slashing protection data: {:?}", e);
+            (
+                axum::http::status::StatusCode::INTERNAL_SERVER_ERROR,
+                format!("Failed to read slashing protection data: {:?}", e),
+            )
+                .into_response()
+        }
+    }
+}
@@ -0,0 +1,64 @@
+use axum::{extract::State, response::IntoResponse, Json};
+use log::{error, info};
+
+/// Imports an EIP-3076 interchange, merging each entry into the saved slashing protection
+/// databases. Returns a `SlashProtectionImportResponse` with the outcome for every pubkey.
+pub async fn handler(
+    State(state): State<crate::enclave::shared::handlers::AppState>,
+    body: String,
+) -> axum::response::Response {
+    info!("import_slash_protection()");
+    import_interchange(&state.key_locks, &body).await
+}
+
+/// Parses and imports the EIP-3076 interchange `json`. Each key is merged under its signing lock,
+/// so a concurrent sign request can't write between the read and the write.
+pub(crate) async fn import_interchange(
+    key_locks: &crate::enclave::shared::key_lock::KeyLocks,
+    json: &str,
+) -> axum::response::Response {
+    use crate::eth2::slash_protection::{
+        SlashingProtectionImportResult, SlashingProtectionImportStatus,
+    };
+    let db = match crate::eth2::slash_protection::SlashingProtectionDB::from_str(json) {
+        Ok(db) => db,
+        Err(e) => {
+            error!("Bad EIP-3076 interchange: {:?}", e);
+            return (
+                axum::http::status::StatusCode::BAD_REQUEST,
+                format!("Bad EIP-3076 interchange, {:?}", e),
+            )
+                .into_response();
+        }
+    };
+
+    let mut data = vec![];
+    for entry in &db.data {
+        let pk_hex = hex::encode(&entry.pubkey[..]);
+        let _guard = match key_locks.lock(&pk_hex).await {
+            Ok(guard) => guard,
+            Err(e) => {
+                error!("Failed to import slash protection of 0x{pk_hex}: {e}");
+                data.push(SlashingProtectionImportResult::new(
+                    &pk_hex,
+                    SlashingProtectionImportStatus::Error,
+                    &e.to_string(),
+                ));
+                continue;
+            }
+        };
+        match entry.import() {
+            Ok(result) => data.push(result),
+            Err(e) => {
+                error!("Failed to open slashing protection store: {:?}", e);
+                return (
+                    axum::http::status::StatusCode::INTERNAL_SERVER_ERROR,
+                    format!("Failed to open slashing protection store, {:?}", e),
+                )
+                    .into_response();
+            }
+        }
+    }
+    let resp = crate::enclave::types::SlashProtectionImportResponse { data };
+    (axum::http::status::StatusCode::OK, Json(resp)).into_response()
+}
@@ -0,0 +1,40 @@
+use axum::{extract::State, response::IntoResponse, Json};
+use log::{error, info};
+
+/// Imports an EIP-3076 interchange from a file the enclave can read, for interchanges too large
+/// to send as a request body. The file must live under the configured import dir. Keys are
+/// merged under their signing locks, as for `import_slash_protection`.
+pub async fn handler(
+    State(state): State<crate::enclave::shared::handlers::AppState>,
+    Json(req): Json<crate::enclave::types::SlashProtectionImportFileRequest>,
+) -> axum::response::Response {
+    info!("import_slash_protection_file()");
+    let Some(dir) = state.slash_protection_import_dir.as_ref() else {
+        error!("Importing slashing protection from a file is disabled");
+        return (
+            axum::http::status::StatusCode::FORBIDDEN,
+            "Importing slashing protection from a file is disabled",
+        )
+            .into_response();
+    };
+
+    let json = match crate::io::key_management::resolve_under(dir, &req.path)
+        .and_then(|path| Ok(std::fs::read_to_string(path)?))
+    {
+        Ok(json) => json,
+        Err(e) => {
+            error!("Bad interchange file: {:?}", e);
+            return (
+                axum::http::status::StatusCode::BAD_REQUEST,
+                format!("Bad interchange file, {:?}", e),
+            )
+                .into_response();
+        }
+    };
+
+    crate::enclave::shared::handlers::import_slash_protection::import_interchange(
+        &state.key_locks,
+        &json,
+    )
+    .await
+}
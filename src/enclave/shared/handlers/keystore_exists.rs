@@ -0,0 +1,29 @@
+use axum::{extract::Path, response::IntoResponse};
+use log::{error, info};
+
+/// Answers 200 if a BLS key is saved for `bls_pk_hex` and 404 otherwise, from a single file
+/// existence check, so callers can skip re-importing a key without listing every key
+pub async fn handler(Path(bls_pk_hex): Path<String>) -> axum::response::Response {
+    info!("keystore_exists()");
+
+    let bls_pk_hex = match crate::crypto::bls_keys::validate_bls_pk_hex(&bls_pk_hex) {
+        Ok(pk) => pk,
+        Err(e) => {
+            error!("Bad BLS public key format: {bls_pk_hex}");
+            return (
+                axum::http::status::StatusCode::BAD_REQUEST,
+                format!("Bad bls_pk_hex, {:?}", e),
+            )
+                .into_response();
+        }
+    };
+
+    if !crate::io::key_management::bls_key_exists(&bls_pk_hex) {
+        return (
+            axum::http::status::StatusCode::NOT_FOUND,
+            format!("No key found for bls_pk_hex 0x{bls_pk_hex}"),
+        )
+            .into_response();
+    }
+    axum::http::status::StatusCode::OK.into_response()
+}
@@ -1,13 +1,21 @@
-use axum::{response::IntoResponse, Json};
+use axum::{extract::Query, response::IntoResponse, Json};
 use log::{error, info};
 
 use crate::io::key_management;
 
-pub async fn handler() -> axum::response::Response {
+/// Lists the saved keys ordered by pubkey as the keymanager API's `GET /eth/v1/keystores`, a page
+/// at a time if `limit` or `offset` are given, only those of the given `origin` if set
+pub async fn handler(
+    Query(query): Query<crate::enclave::types::ListKeysQuery>,
+) -> axum::response::Response {
     info!("list_bls_keys()");
-    match key_management::list_bls_keys() {
+    match key_management::list_bls_keys().and_then(|keys| match query.origin {
+        Some(origin) => filter_by_origin(keys, origin),
+        None => Ok(keys),
+    }) {
         Ok(list_res) => {
-            let resp = crate::enclave::types::ListKeysResponse::new(list_res);
+            let resp: crate::enclave::types::ListKeystoresResponse =
+                crate::enclave::types::ListKeysResponse::page(list_res, query).into();
             (axum::http::status::StatusCode::OK, Json(resp)).into_response()
         }
         Err(e) => {
@@ -16,3 +24,16 @@ pub async fn handler() -> axum::response::Response {
         }
     }
 }
+
+fn filter_by_origin(
+    keys: Vec<String>,
+    origin: key_management::KeyOrigin,
+) -> anyhow::Result<Vec<String>> {
+    let mut filtered = Vec::with_capacity(keys.len());
+    for pk_hex in keys {
+        if key_management::read_key_origin(&pk_hex)? == Some(origin) {
+            filtered.push(pk_hex);
+        }
+    }
+    Ok(filtered)
+}
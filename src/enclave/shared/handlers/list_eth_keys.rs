@@ -1,13 +1,16 @@
-use axum::{response::IntoResponse, Json};
+use axum::{extract::Query, response::IntoResponse, Json};
 use log::{error, info};
 
 use crate::io::key_management;
 
-pub async fn handler() -> axum::response::Response {
+/// Lists the saved keys ordered by pubkey, a page at a time if `limit` or `offset` are given
+pub async fn handler(
+    Query(query): Query<crate::enclave::types::ListKeysQuery>,
+) -> axum::response::Response {
     info!("list_eth_keys()");
     match key_management::list_eth_keys() {
         Ok(list_res) => {
-            let resp = crate::enclave::types::ListKeysResponse::new(list_res);
+            let resp = crate::enclave::types::ListKeysResponse::page(list_res, query);
             (axum::http::status::StatusCode::OK, Json(resp)).into_response()
         }
         Err(e) => {
@@ -0,0 +1,20 @@
+use axum::{response::IntoResponse, Json};
+use log::{error, info};
+
+use crate::io::key_management;
+
+/// Lists the pubkeys that can be signed with as a flat array, as web3signer's
+/// https://consensys.github.io/web3signer/web3signer-eth2.html#tag/Public-Key
+pub async fn handler() -> axum::response::Response {
+    info!("list_public_keys()");
+    match key_management::list_signable_bls_keys() {
+        Ok(keys) => {
+            let keys: Vec<String> = keys.iter().map(|pk| format!("0x{pk}")).collect();
+            (axum::http::status::StatusCode::OK, Json(keys)).into_response()
+        }
+        Err(e) => {
+            error!("list_public_keys() failed with: {:?}", e);
+            axum::http::status::StatusCode::INTERNAL_SERVER_ERROR.into_response()
+        }
+    }
+}
@@ -0,0 +1,14 @@
+use axum::{response::IntoResponse, Json};
+use log::info;
+
+/// Lists remote keys as the keymanager API's `GET /eth/v1/remotekeys`. This signer is itself the
+/// remote key holder and every key it lists under `/eth/v1/keystores` is held in the enclave, so
+/// there are never any.
+pub async fn handler() -> axum::response::Response {
+    info!("list_remote_keys()");
+    (
+        axum::http::status::StatusCode::OK,
+        Json(crate::enclave::types::ListRemoteKeysResponse { data: vec![] }),
+    )
+        .into_response()
+}
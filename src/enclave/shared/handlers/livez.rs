@@ -0,0 +1,6 @@
+use axum::response::IntoResponse;
+
+/// Liveness probe: answers 200 as long as the process serves requests, even while not yet ready
+pub async fn handler() -> axum::response::Response {
+    (axum::http::status::StatusCode::OK).into_response()
+}
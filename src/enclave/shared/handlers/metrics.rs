@@ -0,0 +1,16 @@
+use axum::extract::State;
+use axum::response::IntoResponse;
+
+/// Returns the signing endpoint's rolling error rates, queue depth, signing without slashing
+/// protection history and requests per metrics label in the Prometheus text format
+pub async fn handler(
+    State(state): State<crate::enclave::shared::handlers::AppState>,
+) -> axum::response::Response {
+    let mut metrics = state.error_rate.to_prometheus();
+    metrics.push_str(&state.empty_slash_history.to_prometheus());
+    if let Some(queue) = &state.signing_queue {
+        metrics.push_str(&queue.to_prometheus());
+    }
+    metrics.push_str(&state.metrics_labels.to_prometheus());
+    (axum::http::status::StatusCode::OK, metrics).into_response()
+}
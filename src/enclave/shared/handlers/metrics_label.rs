@@ -0,0 +1,76 @@
+use axum::{
+    extract::{Path, State},
+    response::IntoResponse,
+    Json,
+};
+use log::{error, info};
+
+/// Tags the signing metrics of `bls_pk_hex` with `label`, which must be one of the configured
+/// metrics labels. Returns 404 if metrics labels are disabled or the key is unknown.
+pub async fn handler(
+    State(state): State<crate::enclave::shared::handlers::AppState>,
+    Path(bls_pk_hex): Path<String>,
+    Json(req): Json<crate::enclave::types::MetricsLabelRequest>,
+) -> axum::response::Response {
+    info!("metrics_label()");
+
+    if state.metrics_labels.labels().is_empty() {
+        return (
+            axum::http::status::StatusCode::NOT_FOUND,
+            "Metrics labels are disabled",
+        )
+            .into_response();
+    }
+
+    let bls_pk_hex = match crate::crypto::bls_keys::validate_bls_pk_hex(&bls_pk_hex) {
+        Ok(pk) => pk,
+        Err(e) => {
+            error!("Bad BLS public key format: {bls_pk_hex}");
+            return (
+                axum::http::status::StatusCode::BAD_REQUEST,
+                format!("Bad bls_pk_hex, {:?}", e),
+            )
+                .into_response();
+        }
+    };
+
+    if !state.metrics_labels.is_allowed(&req.label) {
+        error!("Metrics label {:?} is not configured", req.label);
+        return (
+            axum::http::status::StatusCode::BAD_REQUEST,
+            format!("Metrics label {:?} is not configured", req.label),
+        )
+            .into_response();
+    }
+
+    if !crate::io::key_management::bls_key_exists(&bls_pk_hex) {
+        error!("Unknown BLS public key: {bls_pk_hex}");
+        return (
+            axum::http::status::StatusCode::NOT_FOUND,
+            format!("No key found for bls_pk_hex 0x{bls_pk_hex}"),
+        )
+            .into_response();
+    }
+
+    match crate::io::key_management::write_metrics_label(&bls_pk_hex, &req.label) {
+        Ok(()) => {
+            info!("Tagged the metrics of {bls_pk_hex} with {}", req.label);
+            (
+                axum::http::status::StatusCode::OK,
+                Json(crate::enclave::types::MetricsLabelResponse {
+                    pubkey: format!("0x{bls_pk_hex}"),
+                    label: req.label,
+                }),
+            )
+                .into_response()
+        }
+        Err(e) => {
+            error!("Failed to write metrics label: {:?}", e);
+            (
+                axum::http::status::StatusCode::INTERNAL_SERVER_ERROR,
+                format!("Failed to write metrics label: {:?}", e),
+            )
+                .into_response()
+        }
+    }
+}
@@ -1,9 +1,96 @@
+pub mod aggregate_signatures;
+pub mod config;
+pub mod enclave_info;
+pub mod export_slash_protection;
 pub mod health;
+pub mod healthz;
+pub mod high_water_marks;
+pub mod import_slash_protection;
+pub mod import_slash_protection_file;
+pub mod keystore_exists;
 pub mod list_bls_keys;
 pub mod list_eth_keys;
+pub mod list_public_keys;
+pub mod list_remote_keys;
+pub mod livez;
+pub mod metrics;
+pub mod metrics_label;
+pub mod prune_slash_protection;
+pub mod readyz;
+pub mod reseal;
 pub mod secure_sign_bls;
+pub mod session;
+pub mod sign_bls_to_execution_change_batch;
+pub mod sign_stats;
+pub mod signing_floor;
+pub mod slash_protection_floor;
+pub mod verify;
+pub mod verify_batch;
+pub mod version;
 
-#[derive(Clone)]
+#[derive(Clone, Default)]
 pub struct AppState {
     pub genesis_fork_version: crate::eth2::eth_types::Version,
+    /// Fork schedule that fork_info in signing requests is validated against. Empty disables the check.
+    pub fork_schedule: crate::eth2::eth_types::ForkSchedule,
+    /// Fork versions and genesis_validators_roots sign requests may compute their domain from.
+    pub allowed_domains: crate::eth2::eth_types::AllowedDomains,
+    /// When set, sign requests must carry an `X-Request-Id` not seen within the cache's TTL.
+    pub request_ids: Option<crate::enclave::shared::request_id::RequestIdCache>,
+    /// When set, exact retries of recently signed requests are answered with the cached response.
+    pub response_cache: Option<crate::enclave::shared::response_cache::ResponseCache>,
+    /// Allows secret keys to leave the enclave through the export endpoints.
+    pub allow_key_export: bool,
+    /// Allows signing caller-computed signing roots, which bypasses slashing protection.
+    pub allow_raw_signing: bool,
+    /// Allows importing keystores in bulk from an archive that carries their passwords.
+    pub allow_keystore_archive_import: bool,
+    /// Disables generating BLS keys in the enclave, so validator keys can only be imported.
+    pub import_only: bool,
+    /// Rejects importing a keystore without slashing protection history for its key.
+    pub require_import_slashing_protection: bool,
+    /// When set, sign requests must carry an HMAC of their body under the key's provisioned secret.
+    pub require_request_mac: bool,
+    /// Rejects JSON sign requests carrying fields their type doesn't define.
+    pub deny_unknown_fields: bool,
+    /// Rejects sync committee selection proofs and contributions for a nonexistent subcommittee.
+    pub validate_subcommittee_index: bool,
+    /// Binds each key to the genesis_validators_root it first signs under, refusing any other.
+    pub bind_genesis_root: bool,
+    /// When set, each key's validator registrations must have strictly increasing timestamps.
+    pub monotonic_registration_timestamps: bool,
+    /// Validator registrations must request a gas limit within these bounds.
+    pub gas_limit_bounds: crate::eth2::eth_types::GasLimitBounds,
+    /// Voluntary exits must be for an epoch at least this far past the current epoch.
+    pub min_exit_epoch_distance: Option<crate::eth2::eth_types::Epoch>,
+    /// Unix time of the chain's genesis, used to derive the current epoch.
+    pub genesis_time: u64,
+    /// Rolling error rate of the signing endpoint
+    pub error_rate: crate::enclave::shared::metrics::ErrorRateMonitor,
+    /// When set, bounds concurrent signing and queues the overflow instead of running it all at once.
+    pub signing_queue: Option<crate::enclave::shared::queue::SigningQueue>,
+    /// When set, clients may pin the genesis_validators_root once through a session handshake.
+    pub sessions: Option<crate::enclave::shared::session::SessionStore>,
+    /// Dir interchange files may be imported from by path. Unset disables importing by path.
+    pub slash_protection_import_dir: Option<std::path::PathBuf>,
+    /// Added to the slashing protection high-water marks when reporting floors to clients.
+    pub slash_protection_floor_margin: u64,
+    /// When set, signing that takes longer is answered with 504 instead of holding up the caller.
+    pub sign_timeout: Option<std::time::Duration>,
+    /// When set, BLS keys beyond the limit are neither generated nor imported.
+    pub key_limit: Option<crate::enclave::shared::key_limit::KeyLimit>,
+    /// Serializes signing of slashable messages per key, bounding how long a request waits its turn.
+    pub key_locks: crate::enclave::shared::key_lock::KeyLocks,
+    /// Warns about and counts slashable requests for keys without slashing protection history.
+    pub empty_slash_history: crate::enclave::shared::metrics::EmptySlashHistoryMonitor,
+    /// Per-key counts of signed and refused requests.
+    pub sign_stats: crate::enclave::shared::sign_stats::SignStats,
+    /// Signing requests being handled, waited on during graceful shutdown.
+    pub in_flight: crate::enclave::shared::shutdown::InFlight,
+    /// Set once startup checks pass, until then readiness probes answer 503.
+    pub readiness: crate::enclave::shared::readiness::Readiness,
+    /// When set, the effective config is served to callers presenting this bearer token.
+    pub config_token: Option<String>,
+    /// Labels keys may be tagged with to break down their signing metrics, none if empty.
+    pub metrics_labels: crate::enclave::shared::metrics::MetricsLabels,
 }
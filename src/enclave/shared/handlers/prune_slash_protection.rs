@@ -0,0 +1,55 @@
+use axum::extract::State;
+use axum::{response::IntoResponse, Json};
+use log::{error, info};
+
+/// Collapses every saved key's slashing protection history to its high-water marks, bounding the
+/// size of histories kept in the complete format. Each key is pruned under its signing lock, so
+/// a concurrent sign request can't write between the read and the write.
+pub async fn handler(
+    State(state): State<crate::enclave::shared::handlers::AppState>,
+) -> axum::response::Response {
+    info!("prune_slash_protection()");
+    let keys = match crate::io::key_management::list_bls_keys() {
+        Ok(keys) => keys,
+        Err(e) => {
+            error!("prune_slash_protection() failed to list keys: {:?}", e);
+            return axum::http::status::StatusCode::INTERNAL_SERVER_ERROR.into_response();
+        }
+    };
+    let mut resp = crate::enclave::types::SlashProtectionPruneResponse::default();
+    for pk_hex in keys {
+        let _guard = match state.key_locks.lock(&pk_hex).await {
+            Ok(guard) => guard,
+            Err(e) => {
+                error!("Failed to prune slash protection of 0x{pk_hex}: {e}");
+                resp.failed.push(format!("0x{pk_hex}"));
+                continue;
+            }
+        };
+        match prune(&pk_hex) {
+            Ok(0) => {}
+            Ok(removed) => {
+                resp.pruned.push(format!("0x{pk_hex}"));
+                resp.entries_removed += removed;
+            }
+            Err(e) => {
+                error!("Failed to prune slash protection of 0x{pk_hex}: {:?}", e);
+                resp.failed.push(format!("0x{pk_hex}"));
+            }
+        }
+    }
+    (axum::http::status::StatusCode::OK, Json(resp)).into_response()
+}
+
+/// Prunes the saved history of `pk_hex`, only writing it back if anything was dropped
+fn prune(pk_hex: &str) -> anyhow::Result<usize> {
+    use crate::eth2::slash_protection::SlashingProtectionData;
+    let Some(mut data) = SlashingProtectionData::try_read(pk_hex)? else {
+        return Ok(0);
+    };
+    let removed = data.prune();
+    if removed > 0 {
+        data.write()?;
+    }
+    Ok(removed)
+}
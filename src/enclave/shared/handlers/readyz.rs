@@ -0,0 +1,62 @@
+use axum::extract::State;
+use axum::response::IntoResponse;
+use log::error;
+
+/// Readiness probe: answers 503 until startup completes, then 200 only while keys can be listed,
+/// sealed and persisted, see `readiness::check`
+pub async fn handler(
+    State(state): State<crate::enclave::shared::handlers::AppState>,
+) -> axum::response::Response {
+    if !state.readiness.is_ready() {
+        return (
+            axum::http::status::StatusCode::SERVICE_UNAVAILABLE,
+            "Initializing",
+        )
+            .into_response();
+    }
+    match crate::enclave::shared::readiness::check() {
+        Ok(()) => (axum::http::status::StatusCode::OK).into_response(),
+        Err(e) => {
+            error!("Readiness check failed: {:?}", e);
+            (
+                axum::http::status::StatusCode::SERVICE_UNAVAILABLE,
+                format!("Readiness check failed: {:?}", e),
+            )
+                .into_response()
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::enclave::shared::handlers::AppState;
+    use crate::io::config::Config;
+
+    #[tokio::test]
+    async fn test_readyz_not_ready_until_initialized() {
+        let _data_dir = Config::scoped_temp().unwrap();
+        let state = AppState::default();
+        assert_eq!(handler(State(state.clone())).await.status(), 503);
+        // Alive all along
+        assert_eq!(
+            crate::enclave::shared::handlers::livez::handler()
+                .await
+                .status(),
+            200
+        );
+
+        state
+            .readiness
+            .clone()
+            .initialize(std::time::Duration::from_millis(10))
+            .await;
+        assert_eq!(handler(State(state.clone())).await.status(), 200);
+
+        // Ready signers stop being ready once keys can't be persisted
+        let file = Config::current().data_dir.join("file");
+        std::fs::write(&file, "").unwrap();
+        let _unwritable = Config::new(file.join("data")).scoped();
+        assert_eq!(handler(State(state)).await.status(), 503);
+    }
+}
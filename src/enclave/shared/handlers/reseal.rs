@@ -0,0 +1,14 @@
+use axum::{response::IntoResponse, Json};
+use log::{error, info};
+
+/// Re-seals every stored key under the configured sealing policy, see `key_management::reseal_keys`
+pub async fn handler() -> axum::response::Response {
+    info!("reseal()");
+    match crate::io::key_management::reseal_keys() {
+        Ok(resp) => (axum::http::status::StatusCode::OK, Json(resp)).into_response(),
+        Err(e) => {
+            error!("reseal() failed with: {:?}", e);
+            axum::http::status::StatusCode::INTERNAL_SERVER_ERROR.into_response()
+        }
+    }
+}
@@ -1,16 +1,376 @@
 use axum::{
-    extract::{Path, State},
+    body::Bytes,
+    extract::{Path, Query, State},
+    http::{HeaderMap, StatusCode},
+    response::IntoResponse,
     Json,
 };
-use log::info;
+use log::{error, info};
 
 /// Signs the specific type of request
 /// Maintains compatibility with https://consensys.github.io/web3signer/web3signer-eth2.html#tag/Signing
 pub async fn handler(
     Path(bls_pk_hex): Path<String>,
     State(state): State<crate::enclave::shared::handlers::AppState>,
-    Json(req): Json<crate::eth2::eth_signing::BLSSignMsg>,
+    Query(query): Query<crate::enclave::types::SignQuery>,
+    headers: HeaderMap,
+    body: Bytes,
 ) -> axum::response::Response {
     info!("secure_sign_bls()");
-    crate::enclave::shared::sign_validator_message(Path(bls_pk_hex), State(state), Json(req))
+    let in_flight = state.in_flight.start();
+    let error_rate = state.error_rate.clone();
+    let mut timings = crate::enclave::shared::sign_timings::SignTimings::new(query.timings);
+
+    // Canonicalize the pubkey so every lookup below finds the same key, `sign` rejects it if malformed
+    let bls_pk_hex = crate::crypto::bls_keys::validate_bls_pk_hex(&bls_pk_hex)
+        .map(|pk| format!("0x{pk}"))
+        .unwrap_or(bls_pk_hex);
+
+    // Authenticate the raw body before anything else when request MACs are required
+    if state.require_request_mac {
+        if let Err(e) = verify_request_mac(&bls_pk_hex, &headers, &body) {
+            error!("Rejected unauthenticated signing request: {e}");
+            let response = (StatusCode::UNAUTHORIZED, e).into_response();
+            error_rate.record(response.status());
+            return response;
+        }
+    }
+
+    // In strict mode every request must carry a request id that was not used recently, even one
+    // the response cache could answer. A dry run produces no signature to replay, so it doesn't
+    // use up its id.
+    let request_id = match state.request_ids.as_ref().filter(|_| !query.dry_run) {
+        Some(request_ids) => match reserve_request_id(request_ids, &headers) {
+            Ok(reservation) => Some(reservation),
+            Err(response) => {
+                error_rate.record(response.status());
+                return response;
+            }
+        },
+        None => None,
+    };
+
+    // An exact retry of a request signed within the cache window gets the same response back
+    // without signing again. Dry runs are neither served nor cached.
+    let cache_key = state
+        .response_cache
+        .clone()
+        .filter(|_| !query.dry_run)
+        .map(|cache| {
+            let session_id = headers
+                .get(crate::enclave::shared::session::SESSION_ID_HEADER)
+                .and_then(|v| v.to_str().ok());
+            let key = crate::enclave::shared::response_cache::ResponseCache::key(
+                &bls_pk_hex,
+                session_id,
+                &query,
+                &body,
+            );
+            (cache, key)
+        });
+    if let Some((cache, key)) = &cache_key {
+        if let Some(response) = cache.get(key) {
+            info!("Answering a retried signing request from the response cache");
+            error_rate.record(response.status());
+            if let Some(reservation) = request_id {
+                reservation.commit();
+            }
+            return response;
+        }
+    }
+
+    // Set once the request parsed, so only outcomes of signing attempts count towards the key's stats
+    let mut type_name = None;
+    let sign_stats = state.sign_stats.clone();
+    let metrics_labels = state.metrics_labels.clone();
+    // Requests in a session take the session's genesis_validators_root
+    let parsed = session_genesis_validators_root(&state, &headers).and_then(|session_root| {
+        parse_sign_request(
+            &headers,
+            &body,
+            session_root.as_ref(),
+            state.deny_unknown_fields,
+        )
+        .map(|req| (req, session_root))
+        // Malformed JSON or SSZ and unsupported signing types are both bad requests
+        .map_err(|e| {
+            error!("Bad signing request: {e}");
+            (StatusCode::BAD_REQUEST, format!("Bad signing request, {e}")).into_response()
+        })
+    });
+    let mut response = match parsed {
+        Ok((req, session_root)) => {
+            // Wait for a signing slot if signing is queued, shedding the request if it can't get one
+            let permit = match &state.signing_queue {
+                Some(queue) => match queue.acquire().await {
+                    Ok(permit) => Some(permit),
+                    Err(e) => {
+                        error!("Shedding signing request: {e}");
+                        let response = (
+                            StatusCode::SERVICE_UNAVAILABLE,
+                            format!("Try again later, {e}"),
+                        )
+                            .into_response();
+                        error_rate.record(response.status());
+                        return response;
+                    }
+                },
+                None => None,
+            };
+            timings.mark("queue");
+            // Slashable requests for the same key take turns through the slashing check and DB
+            // write, answering 503 rather than waiting indefinitely when the key stays busy.
+            // Tracked registration timestamps and the first binding of a key to a network are
+            // checked and recorded the same way.
+            let needs_key_lock = req.is_slashable_type()
+                || (state.monotonic_registration_timestamps && req.registration().is_some())
+                || (state.bind_genesis_root && req.genesis_validators_root().is_some());
+            let key_guard = match crate::crypto::bls_keys::validate_bls_pk_hex(&bls_pk_hex) {
+                Ok(pk) if needs_key_lock => match state.key_locks.lock(&pk).await {
+                    Ok(guard) => Some(guard),
+                    Err(e) => {
+                        error!("Key 0x{pk} is busy: {e}");
+                        let response = (
+                            StatusCode::SERVICE_UNAVAILABLE,
+                            [(
+                                axum::http::header::RETRY_AFTER,
+                                crate::enclave::shared::key_lock::KEY_LOCK_RETRY_AFTER_SECS
+                                    .to_string(),
+                            )],
+                            format!("Try again later, {e}"),
+                        )
+                            .into_response();
+                        error_rate.record(response.status());
+                        return response;
+                    }
+                },
+                // A malformed key is rejected by `sign`
+                _ => None,
+            };
+            timings.mark("key_lock");
+            type_name = Some(req.type_name());
+            // A signing past the timeout keeps its permit, key lock and in-flight mark until it
+            // finishes, so a retry can't race its slashing protection DB write
+            let sign_timeout = state.sign_timeout;
+            let pk = bls_pk_hex.clone();
+            let sign_query = query.clone();
+            let mut sign_timings = timings;
+            match crate::enclave::shared::sign_deadline::run(sign_timeout, move || {
+                let _guards = (in_flight, permit, key_guard);
+                let response = sign(pk, state, session_root, req, &sign_query, &mut sign_timings);
+                (response, sign_timings)
+            })
+            .await
+            {
+                Ok((response, sign_timings)) => {
+                    timings = sign_timings;
+                    response
+                }
+                Err(response) => {
+                    timings = crate::enclave::shared::sign_timings::SignTimings::new(false);
+                    response
+                }
+            }
+        }
+        Err(response) => response,
+    };
+    if let Some((cache, key)) = cache_key {
+        response = cache.insert(key, response).await;
+    }
+    // Only set if requested through `SignQuery::timings`
+    if let Some(value) = timings
+        .header_value()
+        .and_then(|v| axum::http::HeaderValue::from_str(&v).ok())
+    {
+        response.headers_mut().insert(
+            crate::enclave::shared::sign_timings::SIGN_TIMINGS_HEADER,
+            value,
+        );
+    }
+    error_rate.record(response.status());
+    // A request turned away before signing can be retried under the same id
+    if let Some(reservation) = request_id {
+        if !crate::enclave::shared::request_id::is_retryable(response.status()) {
+            reservation.commit();
+        }
+    }
+    // Unknown keys and dry runs don't count
+    if let (Some(type_name), Ok(pk)) = (
+        type_name,
+        crate::crypto::bls_keys::validate_bls_pk_hex(&bls_pk_hex),
+    ) {
+        if response.status() != StatusCode::NOT_FOUND && !query.dry_run {
+            sign_stats.record(&pk, type_name, response.status());
+            metrics_labels.record(&pk);
+        }
+    }
+    response
+}
+
+/// Returns the genesis_validators_root of the session the request belongs to, None outside a
+/// session. Answers 401 for an unknown or expired session.
+fn session_genesis_validators_root(
+    state: &crate::enclave::shared::handlers::AppState,
+    headers: &HeaderMap,
+) -> Result<Option<crate::eth2::eth_types::Root>, axum::response::Response> {
+    let Some(session_id) = headers
+        .get(crate::enclave::shared::session::SESSION_ID_HEADER)
+        .and_then(|v| v.to_str().ok())
+    else {
+        return Ok(None);
+    };
+    let session_root = match &state.sessions {
+        Some(sessions) => sessions.genesis_validators_root(session_id),
+        None => None,
+    };
+    match session_root {
+        Some(root) => Ok(Some(root)),
+        None => {
+            error!("Unknown session: {session_id}");
+            Err((
+                StatusCode::UNAUTHORIZED,
+                format!("Unknown session {session_id}"),
+            )
+                .into_response())
+        }
+    }
+}
+
+/// Parses the body as SSZ when sent as `application/octet-stream`, as JSON otherwise.
+/// With `deny_unknown_fields`, JSON carrying fields its request type doesn't define is refused.
+/// JSON in a session may omit the genesis_validators_root, which is taken from `session_root`.
+/// Outside a session it is required, so a missing root is never signed as zero.
+fn parse_sign_request(
+    headers: &HeaderMap,
+    body: &[u8],
+    session_root: Option<&crate::eth2::eth_types::Root>,
+    deny_unknown_fields: bool,
+) -> Result<crate::eth2::eth_signing::BLSSignMsg, String> {
+    let is_ssz = headers
+        .get(axum::http::header::CONTENT_TYPE)
+        .and_then(|v| v.to_str().ok())
+        .and_then(|v| v.split(';').next())
+        .map_or(false, |media_type| {
+            media_type
+                .trim()
+                .eq_ignore_ascii_case(crate::eth2::ssz_request::SSZ_CONTENT_TYPE)
+        });
+    if is_ssz {
+        return crate::eth2::ssz_request::from_ssz_bytes(body).map_err(|e| e.to_string());
+    }
+    let filled;
+    let body = match session_root {
+        Some(root) => {
+            filled = fill_genesis_validators_root(body, root)?;
+            &filled[..]
+        }
+        None => body,
+    };
+    if deny_unknown_fields {
+        crate::eth2::eth_signing::BLSSignMsg::from_json_strict(body).map_err(|e| e.to_string())
+    } else {
+        serde_json::from_slice(body).map_err(|e| e.to_string())
+    }
+}
+
+/// Sets the genesis_validators_root of a JSON request's fork_info to `root` if it is missing
+fn fill_genesis_validators_root(
+    body: &[u8],
+    root: &crate::eth2::eth_types::Root,
+) -> Result<Vec<u8>, String> {
+    let mut req: serde_json::Value = serde_json::from_slice(body).map_err(|e| e.to_string())?;
+    if let Some(fork_info) = req
+        .get_mut("fork_info")
+        .and_then(serde_json::Value::as_object_mut)
+    {
+        fork_info
+            .entry("genesis_validators_root")
+            .or_insert_with(|| format!("0x{}", hex::encode(root)).into());
+    }
+    serde_json::to_vec(&req).map_err(|e| e.to_string())
+}
+
+/// Reserves the request id the request carries, answering 400 if it has none and 409 if it was
+/// already used or is in use by a request in flight
+pub(crate) fn reserve_request_id(
+    request_ids: &crate::enclave::shared::request_id::RequestIdCache,
+    headers: &HeaderMap,
+) -> Result<crate::enclave::shared::request_id::RequestIdReservation, axum::response::Response> {
+    let request_id = match headers
+        .get(crate::enclave::shared::request_id::REQUEST_ID_HEADER)
+        .and_then(|v| v.to_str().ok())
+    {
+        Some(id) if !id.is_empty() => id,
+        _ => {
+            error!("Missing request id");
+            return Err((
+                StatusCode::BAD_REQUEST,
+                format!(
+                    "Missing {} header",
+                    crate::enclave::shared::request_id::REQUEST_ID_HEADER
+                ),
+            )
+                .into_response());
+        }
+    };
+
+    request_ids.reserve(request_id).ok_or_else(|| {
+        error!("Replayed request id: {request_id}");
+        (
+            StatusCode::CONFLICT,
+            format!("Request id {request_id} was already used"),
+        )
+            .into_response()
+    })
+}
+
+/// Checks the request carries a valid MAC over `body` under the secret provisioned for `bls_pk_hex`
+pub fn verify_request_mac(
+    bls_pk_hex: &str,
+    headers: &HeaderMap,
+    body: &[u8],
+) -> Result<(), String> {
+    let Some(mac_hex) = headers
+        .get(crate::enclave::shared::request_mac::REQUEST_MAC_HEADER)
+        .and_then(|v| v.to_str().ok())
+    else {
+        return Err(format!(
+            "Missing {} header",
+            crate::enclave::shared::request_mac::REQUEST_MAC_HEADER
+        ));
+    };
+    let Ok(secret) = crate::io::key_management::read_request_mac_secret(bls_pk_hex) else {
+        return Err(format!("No request MAC secret for {bls_pk_hex}"));
+    };
+    if !crate::enclave::shared::request_mac::verify(&secret, body, mac_hex) {
+        return Err("Bad request MAC".to_string());
+    }
+    Ok(())
+}
+
+fn sign(
+    bls_pk_hex: String,
+    state: crate::enclave::shared::handlers::AppState,
+    session_root: Option<crate::eth2::eth_types::Root>,
+    req: crate::eth2::eth_signing::BLSSignMsg,
+    query: &crate::enclave::types::SignQuery,
+    timings: &mut crate::enclave::shared::sign_timings::SignTimings,
+) -> axum::response::Response {
+    let mut req = req;
+    if let Some(session_root) = session_root {
+        if let Err(e) = req.apply_session_genesis_validators_root(&session_root) {
+            error!("Session mismatch: {:?}", e);
+            return (StatusCode::BAD_REQUEST, format!("Bad fork_info, {:?}", e)).into_response();
+        }
+    }
+
+    crate::enclave::shared::sign_validator_message(
+        Path(bls_pk_hex),
+        State(state),
+        Json(req),
+        query.include_signing_root,
+        query.signature_format,
+        query.dry_run,
+        timings,
+    )
 }
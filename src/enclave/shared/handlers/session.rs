@@ -0,0 +1,38 @@
+use axum::{
+    extract::State,
+    http::{header::AUTHORIZATION, HeaderMap, StatusCode},
+    response::IntoResponse,
+    Json,
+};
+use log::{error, info};
+
+/// Opens a session pinning the genesis_validators_root, so later sign requests carrying the
+/// session id may omit it until the session expires. Requires
+/// `Authorization: Bearer <SESSION_TOKEN>`.
+pub async fn handler(
+    State(state): State<crate::enclave::shared::handlers::AppState>,
+    headers: HeaderMap,
+    Json(req): Json<crate::enclave::types::SessionRequest>,
+) -> axum::response::Response {
+    info!("session()");
+    let Some(sessions) = &state.sessions else {
+        return (StatusCode::NOT_FOUND, "Sessions are not enabled").into_response();
+    };
+
+    let token = headers
+        .get(AUTHORIZATION)
+        .and_then(|v| v.to_str().ok())
+        .and_then(|v| v.strip_prefix("Bearer "));
+    if !token.map_or(false, |t| sessions.authenticate(t)) {
+        error!("Unauthenticated session handshake");
+        return (StatusCode::UNAUTHORIZED, "Bad session token").into_response();
+    }
+
+    let session_id = sessions.open(req.genesis_validators_root);
+    info!(
+        "Opened session for genesis_validators_root 0x{}",
+        hex::encode(req.genesis_validators_root)
+    );
+    let resp = crate::enclave::types::SessionResponse { session_id };
+    (StatusCode::OK, Json(resp)).into_response()
+}
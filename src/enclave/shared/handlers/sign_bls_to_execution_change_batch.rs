@@ -0,0 +1,140 @@
+use axum::{
+    extract::{Path, State},
+    http::{HeaderMap, StatusCode},
+    response::IntoResponse,
+    Json,
+};
+use log::{error, info};
+
+use crate::enclave::types::{BatchSignResponse, BatchSignResult, SignatureResponse};
+use crate::eth2::eth_signing::BLSSignMsg;
+use crate::eth2::eth_types::BlsToExecutionChangeRequest;
+
+/// Signs a batch of BLS to execution changes, each with the key of its `from_bls_pubkey`. Every
+/// item goes through the same checks as on the sign route and fails on its own, so one bad item
+/// doesn't hold up the rest of a validator set's withdrawal migration. The batch as a whole takes
+/// a signing slot and, in strict mode, a request id. Items carry no MAC of their own, so batches
+/// are refused while request MACs are required.
+pub async fn handler(
+    State(state): State<crate::enclave::shared::handlers::AppState>,
+    headers: HeaderMap,
+    Json(items): Json<Vec<serde_json::Value>>,
+) -> axum::response::Response {
+    info!("sign_bls_to_execution_change_batch()");
+    let _in_flight = state.in_flight.start();
+
+    if state.require_request_mac {
+        error!("Rejected batch of BLS to execution changes, request MACs are required");
+        return (
+            StatusCode::FORBIDDEN,
+            "Batches are not signed while request MACs are required".to_string(),
+        )
+            .into_response();
+    }
+
+    let request_id = match &state.request_ids {
+        Some(request_ids) => {
+            match crate::enclave::shared::handlers::secure_sign_bls::reserve_request_id(
+                request_ids,
+                &headers,
+            ) {
+                Ok(reservation) => Some(reservation),
+                Err(response) => return response,
+            }
+        }
+        None => None,
+    };
+
+    let _permit = match &state.signing_queue {
+        Some(queue) => match queue.acquire().await {
+            Ok(permit) => Some(permit),
+            Err(e) => {
+                error!("Shedding batch of BLS to execution changes: {e}");
+                return (
+                    StatusCode::SERVICE_UNAVAILABLE,
+                    format!("Try again later, {e}"),
+                )
+                    .into_response();
+            }
+        },
+        None => None,
+    };
+
+    let mut data = Vec::with_capacity(items.len());
+    for item in items {
+        data.push(sign_item(&state, item).await);
+    }
+    if let Some(reservation) = request_id {
+        reservation.commit();
+    }
+    (StatusCode::OK, Json(BatchSignResponse { data })).into_response()
+}
+
+async fn sign_item(
+    state: &crate::enclave::shared::handlers::AppState,
+    item: serde_json::Value,
+) -> BatchSignResult {
+    // Parsed one by one so a malformed item is reported against itself
+    let pubkey = item
+        .pointer("/bls_to_execution_change/from_bls_pubkey")
+        .and_then(|pk| pk.as_str())
+        .unwrap_or_default()
+        .to_string();
+    let req: BlsToExecutionChangeRequest = match serde_json::from_value(item) {
+        Ok(req) => req,
+        Err(e) => {
+            error!("Bad BLS to execution change: {e}");
+            return BatchSignResult {
+                pubkey,
+                status: StatusCode::BAD_REQUEST.as_u16(),
+                signature: None,
+                error: Some(format!("Bad BLS to execution change, {e}")),
+            };
+        }
+    };
+
+    let req = BLSSignMsg::BLS_TO_EXECUTION_CHANGE(req);
+    let type_name = req.type_name();
+    let response = crate::enclave::shared::sign_validator_message(
+        Path(pubkey.clone()),
+        State(state.clone()),
+        Json(req),
+        false,
+        Default::default(),
+        false,
+        &mut crate::enclave::shared::sign_timings::SignTimings::new(false),
+    );
+    let status = response.status();
+    // Counted like items signed one by one, unknown keys aside
+    if let Ok(pk) = crate::crypto::bls_keys::validate_bls_pk_hex(&pubkey) {
+        if status != StatusCode::NOT_FOUND {
+            state.sign_stats.record(&pk, type_name, status);
+            state.metrics_labels.record(&pk);
+        }
+    }
+    let body = hyper::body::to_bytes(response.into_body())
+        .await
+        .unwrap_or_default();
+    if status != StatusCode::OK {
+        return BatchSignResult {
+            pubkey,
+            status: status.as_u16(),
+            signature: None,
+            error: Some(String::from_utf8_lossy(&body).into_owned()),
+        };
+    }
+    match serde_json::from_slice::<SignatureResponse>(&body) {
+        Ok(resp) => BatchSignResult {
+            pubkey,
+            status: status.as_u16(),
+            signature: Some(resp.signature),
+            error: None,
+        },
+        Err(e) => BatchSignResult {
+            pubkey,
+            status: StatusCode::INTERNAL_SERVER_ERROR.as_u16(),
+            signature: None,
+            error: Some(format!("Signing operation failed: {e}")),
+        },
+    }
+}
@@ -0,0 +1,47 @@
+use axum::{
+    extract::{Path, State},
+    response::IntoResponse,
+    Json,
+};
+use log::{error, info};
+
+/// Returns the `KeyStats` of `bls_pk_hex`: how many blocks and attestations it signed, when it
+/// last signed and how many of its requests were refused. Returns 404 if the key doesn't exist.
+pub async fn handler(
+    Path(bls_pk_hex): Path<String>,
+    State(state): State<crate::enclave::shared::handlers::AppState>,
+) -> axum::response::Response {
+    info!("sign_stats()");
+
+    let bls_pk_hex = match crate::crypto::bls_keys::validate_bls_pk_hex(&bls_pk_hex) {
+        Ok(pk) => pk,
+        Err(e) => {
+            error!("Bad BLS public key format: {bls_pk_hex}");
+            return (
+                axum::http::status::StatusCode::BAD_REQUEST,
+                format!("Bad bls_pk_hex, {:?}", e),
+            )
+                .into_response();
+        }
+    };
+
+    if !crate::io::key_management::bls_key_exists(&bls_pk_hex) {
+        return (
+            axum::http::status::StatusCode::NOT_FOUND,
+            format!("Unknown bls_pk_hex 0x{bls_pk_hex}"),
+        )
+            .into_response();
+    }
+
+    match state.sign_stats.get(&bls_pk_hex) {
+        Ok(stats) => (axum::http::status::StatusCode::OK, Json(stats)).into_response(),
+        Err(e) => {
+            error!("Failed to read signing stats: {:?}", e);
+            (
+                axum::http::status::StatusCode::INTERNAL_SERVER_ERROR,
+                format!("Failed to read signing stats: {:?}", e),
+            )
+                .into_response()
+        }
+    }
+}
@@ -0,0 +1,60 @@
+use axum::{extract::Path, response::IntoResponse, Json};
+use log::{error, info};
+
+/// Raises the signing floor of `bls_pk_hex` to at least the requested minimum slot and epochs.
+/// A floor is never lowered, so each field keeps the higher of the saved and requested values.
+/// Returns the resulting `SigningFloor`.
+pub async fn handler(
+    Path(bls_pk_hex): Path<String>,
+    Json(req): Json<crate::eth2::slash_protection::SigningFloor>,
+) -> axum::response::Response {
+    info!("signing_floor()");
+
+    let bls_pk_hex = match crate::crypto::bls_keys::validate_bls_pk_hex(&bls_pk_hex) {
+        Ok(pk) => pk,
+        Err(e) => {
+            error!("Bad BLS public key format: {bls_pk_hex}");
+            return (
+                axum::http::status::StatusCode::BAD_REQUEST,
+                format!("Bad bls_pk_hex, {:?}", e),
+            )
+                .into_response();
+        }
+    };
+
+    if !crate::io::key_management::bls_key_exists(&bls_pk_hex) {
+        error!("Unknown BLS public key: {bls_pk_hex}");
+        return (
+            axum::http::status::StatusCode::NOT_FOUND,
+            format!("No key found for bls_pk_hex 0x{bls_pk_hex}"),
+        )
+            .into_response();
+    }
+
+    let floor = match crate::eth2::slash_protection::SigningFloor::read(&bls_pk_hex) {
+        Ok(saved) => saved.unwrap_or_default().raise(&req),
+        Err(e) => {
+            error!("Failed to read signing floor: {:?}", e);
+            return (
+                axum::http::status::StatusCode::INTERNAL_SERVER_ERROR,
+                format!("Failed to read signing floor: {:?}", e),
+            )
+                .into_response();
+        }
+    };
+
+    match floor.write(&bls_pk_hex) {
+        Ok(()) => {
+            info!("Signing floor of {bls_pk_hex} is now {:?}", floor);
+            (axum::http::status::StatusCode::OK, Json(floor)).into_response()
+        }
+        Err(e) => {
+            error!("Failed to write signing floor: {:?}", e);
+            (
+                axum::http::status::StatusCode::INTERNAL_SERVER_ERROR,
+                format!("Failed to write signing floor: {:?}", e),
+            )
+                .into_response()
+        }
+    }
+}
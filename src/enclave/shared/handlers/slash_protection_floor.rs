@@ -0,0 +1,69 @@
+use axum::{
+    extract::{Query, State},
+    response::IntoResponse,
+    Json,
+};
+use log::{error, info};
+use serde::Deserialize;
+
+use crate::strip_0x_prefix;
+
+/// Parses the genesis_validators_root to write to an interchange's metadata, zero if not given.
+/// Answers 400 if it isn't 32 bytes of hex.
+pub(crate) fn parse_genesis_validators_root(
+    root: Option<&str>,
+) -> Result<crate::eth2::eth_types::Root, axum::response::Response> {
+    let mut genesis_validators_root = crate::eth2::eth_types::Root::default();
+    if let Some(root) = root {
+        let root: String = strip_0x_prefix!(root);
+        match hex::decode(&root) {
+            Ok(root) if root.len() == genesis_validators_root.len() => {
+                genesis_validators_root.copy_from_slice(&root)
+            }
+            _ => {
+                error!("Bad genesis_validators_root: {root}");
+                return Err((
+                    axum::http::status::StatusCode::BAD_REQUEST,
+                    "Bad genesis_validators_root",
+                )
+                    .into_response());
+            }
+        }
+    }
+    Ok(genesis_validators_root)
+}
+
+#[derive(Deserialize, Debug, Default)]
+pub struct SlashProtectionFloorQuery {
+    /// Written to the interchange metadata so clients accept it for their network
+    pub genesis_validators_root: Option<String>,
+}
+
+/// Returns an EIP-3076 interchange with the recommended slashing protection floor of every key,
+/// i.e. the high-water marks plus the configured safety margin.
+pub async fn handler(
+    State(state): State<crate::enclave::shared::handlers::AppState>,
+    Query(query): Query<SlashProtectionFloorQuery>,
+) -> axum::response::Response {
+    info!("slash_protection_floor()");
+    let genesis_validators_root =
+        match parse_genesis_validators_root(query.genesis_validators_root.as_deref()) {
+            Ok(root) => root,
+            Err(response) => return response,
+        };
+
+    match crate::eth2::slash_protection::SlashingProtectionDB::floors(
+        genesis_validators_root,
+        state.slash_protection_floor_margin,
+    ) {
+        Ok(db) => (axum::http::status::StatusCode::OK, Json(db)).into_response(),
+        Err(e) => {
+            error!("slash_protection_floor() failed with: {:?}", e);
+            (
+                axum::http::status::StatusCode::INTERNAL_SERVER_ERROR,
+                format!("Failed to compute slashing protection floors: {:?}", e),
+            )
+                .into_response()
+        }
+    }
+}
@@ -0,0 +1,37 @@
+use axum::{response::IntoResponse, Json};
+use log::{error, info};
+
+use crate::enclave::types::{VerifyRequest, VerifyResponse};
+use crate::strip_0x_prefix;
+
+/// Verifies a single signature, returning a `VerifyResponse`. A well-formed signature that
+/// doesn't verify is a 200, only malformed hex, keys or signatures are a 400.
+pub async fn handler(Json(req): Json<VerifyRequest>) -> axum::response::Response {
+    info!("verify()");
+
+    let decode = |name: &str, s: &String| -> anyhow::Result<Vec<u8>> {
+        let s: String = strip_0x_prefix!(s);
+        hex::decode(s).map_err(|e| anyhow::anyhow!("{name} is not hex: {e}"))
+    };
+    let valid = decode("pubkey", &req.pubkey).and_then(|pk| {
+        let msg = decode("signing_root", &req.signing_root)?;
+        let sig = decode("signature", &req.signature)?;
+        crate::crypto::bls_keys::bls_verify(&pk, &msg, &sig)
+    });
+
+    match valid {
+        Ok(valid) => (
+            axum::http::status::StatusCode::OK,
+            Json(VerifyResponse { valid }),
+        )
+            .into_response(),
+        Err(e) => {
+            error!("Bad signature to verify: {:?}", e);
+            (
+                axum::http::status::StatusCode::BAD_REQUEST,
+                format!("Bad verify request, {:?}", e),
+            )
+                .into_response()
+        }
+    }
+}
@@ -0,0 +1,40 @@
+use axum::{response::IntoResponse, Json};
+use log::info;
+
+use crate::enclave::types::{BatchVerifyItem, BatchVerifyResponse, BatchVerifyResult};
+use crate::strip_0x_prefix;
+
+/// Verifies a batch of signatures, reporting which of them are valid
+pub async fn handler(Json(items): Json<Vec<BatchVerifyItem>>) -> axum::response::Response {
+    info!("verify_batch()");
+
+    // Malformed hex is treated as an invalid item rather than failing the batch
+    let decode = |s: &String| -> Vec<u8> {
+        let s: String = strip_0x_prefix!(s);
+        hex::decode(s).unwrap_or_default()
+    };
+    let triples: Vec<(Vec<u8>, Vec<u8>, Vec<u8>)> = items
+        .iter()
+        .map(|item| {
+            (
+                decode(&item.pubkey),
+                decode(&item.signing_root),
+                decode(&item.signature),
+            )
+        })
+        .collect();
+
+    let verification = crate::crypto::bls_keys::bls_batch_verify(&triples);
+    let resp = BatchVerifyResponse {
+        data: items
+            .into_iter()
+            .zip(verification.valid)
+            .map(|(item, valid)| BatchVerifyResult {
+                pubkey: item.pubkey,
+                valid,
+            })
+            .collect(),
+        batch_verified: verification.batch_verified,
+    };
+    (axum::http::status::StatusCode::OK, Json(resp)).into_response()
+}
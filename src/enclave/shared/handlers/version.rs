@@ -0,0 +1,43 @@
+use axum::{response::IntoResponse, Json};
+use log::info;
+
+/// Returns the signer's build and enclave configuration, for spotting stragglers in a rollout
+pub async fn handler() -> axum::response::Response {
+    info!("version()");
+    (
+        axum::http::status::StatusCode::OK,
+        Json(crate::enclave::types::VersionResponse::current()),
+    )
+        .into_response()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::enclave::types::VersionResponse;
+
+    #[tokio::test]
+    async fn test_version_matches_the_crate() {
+        let app = axum::Router::new()
+            .route("/version", axum::routing::get(handler))
+            .into_make_service();
+        let server = axum_test::TestServer::new(app).unwrap();
+        let resp = server.get("/version").await;
+        assert_eq!(resp.status_code(), 200);
+        let version: VersionResponse = resp.json();
+
+        assert_eq!(version.version, env!("CARGO_PKG_VERSION"));
+        assert!(!version.git_commit.is_empty());
+        assert_eq!(version.enclave_mode == "HW", cfg!(feature = "sgx"));
+        assert_eq!(
+            version.attestation_mode,
+            crate::io::remote_attestation::EnclaveInfo::read()
+                .unwrap()
+                .attestation_mode
+        );
+        assert_eq!(
+            version.features.contains(&"sqlite".to_string()),
+            cfg!(feature = "sqlite")
+        );
+    }
+}
@@ -0,0 +1,120 @@
+use anyhow::Result;
+use axum::http::StatusCode;
+use axum::response::{IntoResponse, Response};
+use log::error;
+use std::sync::{Arc, Mutex};
+
+/// Caps how many BLS keys the enclave holds. The saved keys are counted once, on the first
+/// reservation, and the count is kept up to date from then on instead of listing them every time.
+#[derive(Clone, Debug)]
+pub struct KeyLimit {
+    max_keys: usize,
+    count: Arc<Mutex<Option<usize>>>,
+}
+
+/// Room for one more key, given back when dropped unless the key was saved
+#[derive(Debug)]
+pub struct KeySlot {
+    count: Arc<Mutex<Option<usize>>>,
+    saved: bool,
+}
+
+impl KeyLimit {
+    pub fn new(max_keys: usize) -> Self {
+        KeyLimit {
+            max_keys,
+            count: Arc::new(Mutex::new(None)),
+        }
+    }
+
+    pub fn max_keys(&self) -> usize {
+        self.max_keys
+    }
+
+    /// Reserves room for one more key, `None` if the enclave already holds `max_keys`
+    pub fn reserve(&self) -> Result<Option<KeySlot>> {
+        let mut count = self.count.lock().expect("key count poisoned");
+        let saved = match *count {
+            Some(n) => n,
+            None => crate::io::key_management::list_bls_keys()?.len(),
+        };
+        if saved >= self.max_keys {
+            *count = Some(saved);
+            return Ok(None);
+        }
+        *count = Some(saved + 1);
+        Ok(Some(KeySlot {
+            count: self.count.clone(),
+            saved: false,
+        }))
+    }
+}
+
+impl KeySlot {
+    /// Keeps the slot taken, the key it was reserved for was saved
+    pub fn saved(mut self) {
+        self.saved = true;
+    }
+}
+
+impl Drop for KeySlot {
+    fn drop(&mut self) {
+        if !self.saved {
+            let mut count = self.count.lock().expect("key count poisoned");
+            *count = count.map(|n| n.saturating_sub(1));
+        }
+    }
+}
+
+/// Reserves room for one more key if `limit` is set, answering 507 if the enclave is full
+pub fn reserve(limit: Option<&KeyLimit>) -> Result<Option<KeySlot>, Response> {
+    let Some(limit) = limit else {
+        return Ok(None);
+    };
+    match limit.reserve() {
+        Ok(Some(slot)) => Ok(Some(slot)),
+        Ok(None) => {
+            error!(
+                "Rejected new key, the enclave holds the maximum of {} keys",
+                limit.max_keys
+            );
+            Err((
+                StatusCode::INSUFFICIENT_STORAGE,
+                format!(
+                    "The enclave already holds the maximum of {} keys",
+                    limit.max_keys
+                ),
+            )
+                .into_response())
+        }
+        Err(e) => {
+            error!("Failed to count the saved keys: {:?}", e);
+            Err(StatusCode::INTERNAL_SERVER_ERROR.into_response())
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::io::config::Config;
+
+    #[test]
+    fn test_key_limit() {
+        let _data_dir = Config::scoped_in_memory();
+        let sk = crate::crypto::bls_keys::new_bls_key(0);
+        crate::crypto::bls_keys::save_bls_key(&sk).unwrap();
+
+        // The saved key counts against the limit
+        let limit = KeyLimit::new(3);
+        let slot = limit.reserve().unwrap().unwrap();
+        slot.saved();
+        let slot = limit.reserve().unwrap().unwrap();
+        assert!(limit.reserve().unwrap().is_none());
+
+        // Given back if the key wasn't saved
+        drop(slot);
+        limit.reserve().unwrap().unwrap().saved();
+        assert!(limit.reserve().unwrap().is_none());
+    }
+}
@@ -0,0 +1,89 @@
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::sync::OwnedMutexGuard;
+
+/// Default time a signing request may wait for its key's lock before it is turned away
+pub const DEFAULT_KEY_LOCK_TIMEOUT: Duration = Duration::from_secs(1);
+
+/// Seconds a request turned away for a busy key is told to wait before retrying
+pub const KEY_LOCK_RETRY_AFTER_SECS: u64 = 1;
+
+/// Why a signing request could not take its key's lock
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct KeyLockTimeout(pub Duration);
+
+impl std::fmt::Display for KeyLockTimeout {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "key was busy for longer than {:?}", self.0)
+    }
+}
+
+/// Serializes signing per key, so one request's slashing check and DB write can't interleave with
+/// another's. Waiting for a key is bounded by `timeout` so a busy key fails fast instead of
+/// holding the caller past its duty deadline.
+#[derive(Clone, Debug)]
+pub struct KeyLocks {
+    locks: Arc<Mutex<HashMap<String, Arc<tokio::sync::Mutex<()>>>>>,
+    timeout: Duration,
+}
+
+impl Default for KeyLocks {
+    fn default() -> Self {
+        KeyLocks::new(DEFAULT_KEY_LOCK_TIMEOUT)
+    }
+}
+
+impl KeyLocks {
+    pub fn new(timeout: Duration) -> Self {
+        KeyLocks {
+            locks: Arc::new(Mutex::new(HashMap::new())),
+            timeout,
+        }
+    }
+
+    /// Waits for the lock of `bls_pk_hex`, which is held until the returned guard is dropped.
+    pub async fn lock(&self, bls_pk_hex: &str) -> Result<OwnedMutexGuard<()>, KeyLockTimeout> {
+        let lock = {
+            let mut locks = self.locks.lock().expect("key locks poisoned");
+            // Forget keys nobody holds or waits on, so the map only grows with concurrent keys
+            locks.retain(|_, lock| Arc::strong_count(lock) > 1);
+            locks.entry(bls_pk_hex.to_string()).or_default().clone()
+        };
+        tokio::time::timeout(self.timeout, lock.lock_owned())
+            .await
+            .map_err(|_| KeyLockTimeout(self.timeout))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_busy_key_times_out() {
+        let locks = KeyLocks::new(Duration::from_millis(10));
+        let held = locks.lock("aa").await.unwrap();
+        assert_eq!(
+            locks.lock("aa").await.err(),
+            Some(KeyLockTimeout(Duration::from_millis(10)))
+        );
+
+        // Other keys are not held up
+        locks.lock("bb").await.unwrap();
+
+        drop(held);
+        locks.lock("aa").await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_released_keys_are_forgotten() {
+        let locks = KeyLocks::default();
+        drop(locks.lock("aa").await.unwrap());
+        let _held = locks.lock("bb").await.unwrap();
+        assert_eq!(
+            locks.locks.lock().unwrap().keys().collect::<Vec<_>>(),
+            vec!["bb"]
+        );
+    }
+}
@@ -0,0 +1,101 @@
+use std::borrow::Cow;
+use std::io::Write;
+use tracing_subscriber::fmt::MakeWriter;
+
+/// Hex characters of a compressed BLS pubkey
+const PUBKEY_HEX_LEN: usize = 2 * crate::constants::BLS_PUB_KEY_BYTES;
+
+/// Leading hex characters of a pubkey kept when redacting, enough to tell keys apart
+pub const REDACTED_PUBKEY_PREFIX_LEN: usize = 8;
+
+/// Installs the global subscriber logging to stdout. If `redact_pubkeys` is set every BLS pubkey
+/// in the output is truncated, whichever handler or dependency logged it.
+pub fn init(redact_pubkeys: bool) {
+    let builder = tracing_subscriber::fmt();
+    match redact_pubkeys {
+        true => builder.with_writer(RedactPubkeys(std::io::stdout)).init(),
+        false => builder.init(),
+    }
+}
+
+/// Truncates every run of exactly 96 hex characters in `s`, i.e. every compressed BLS pubkey
+/// with or without its 0x prefix. Shorter roots and longer signatures are left alone.
+pub fn redact_pubkeys(s: &str) -> Cow<str> {
+    let bytes = s.as_bytes();
+    let mut out = String::new();
+    let mut copied = 0;
+    let mut i = 0;
+    while i < bytes.len() {
+        if !bytes[i].is_ascii_hexdigit() {
+            i += 1;
+            continue;
+        }
+        let start = i;
+        while i < bytes.len() && bytes[i].is_ascii_hexdigit() {
+            i += 1;
+        }
+        if i - start == PUBKEY_HEX_LEN {
+            out.push_str(&s[copied..start + REDACTED_PUBKEY_PREFIX_LEN]);
+            out.push('…');
+            copied = i;
+        }
+    }
+    if copied == 0 {
+        return Cow::Borrowed(s);
+    }
+    out.push_str(&s[copied..]);
+    Cow::Owned(out)
+}
+
+/// Makes writers that redact pubkeys before handing the output to the writers of `M`
+#[derive(Clone, Debug)]
+pub struct RedactPubkeys<M>(pub M);
+
+impl<'a, M: MakeWriter<'a>> MakeWriter<'a> for RedactPubkeys<M> {
+    type Writer = RedactingWriter<M::Writer>;
+
+    fn make_writer(&'a self) -> Self::Writer {
+        RedactingWriter(self.0.make_writer())
+    }
+}
+
+/// Redacts pubkeys from every write. The fmt subscriber writes each event whole, so a pubkey is
+/// never split across writes.
+#[derive(Debug)]
+pub struct RedactingWriter<W>(W);
+
+impl<W: Write> Write for RedactingWriter<W> {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        let s = String::from_utf8_lossy(buf);
+        self.0.write_all(redact_pubkeys(&s).as_bytes())?;
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        self.0.flush()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_redact_pubkeys() {
+        let pk = "8349434ad0700e79be65c0c7043945df426bd6d7e288c16671df69d822344f1b0ce8de80360a50550ad782b68035cb18";
+        assert_eq!(
+            redact_pubkeys(&format!("Request for validator pubkey: 0x{pk}")),
+            "Request for validator pubkey: 0x8349434a…"
+        );
+        assert_eq!(
+            redact_pubkeys(&format!("{pk},{}", pk.to_uppercase())),
+            "8349434a…,8349434A…"
+        );
+
+        // Signing roots and signatures are not pubkeys
+        let root = "ab".repeat(32);
+        let sig = "cd".repeat(96);
+        assert!(matches!(redact_pubkeys(&root), Cow::Borrowed(_)));
+        assert_eq!(redact_pubkeys(&sig), sig);
+    }
+}
@@ -0,0 +1,351 @@
+use axum::http::StatusCode;
+use log::{error, info, warn};
+use std::collections::{BTreeMap, VecDeque};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+
+/// Log target of the error rate escalation logs
+pub const ERROR_RATE_LOG_TARGET: &str = "secure_signer::error_rate";
+
+/// Log target of the warnings about keys signing without slashing protection history
+pub const EMPTY_SLASH_HISTORY_LOG_TARGET: &str = "secure_signer::empty_slash_history";
+
+/// Default number of most recent signing responses the error rate is computed over
+pub const DEFAULT_ERROR_RATE_WINDOW: usize = 100;
+
+/// Shares of the responses in the rolling window that were errors
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct ErrorRates {
+    /// Number of responses in the window
+    pub samples: usize,
+    /// Share of 5xx responses
+    pub server_error: f64,
+    /// Share of 412 responses, i.e. requests refused by slashing protection
+    pub precondition_failed: f64,
+}
+
+impl ErrorRates {
+    /// Combined share of 5xx and 412 responses
+    pub fn total(&self) -> f64 {
+        self.server_error + self.precondition_failed
+    }
+}
+
+#[derive(Debug, Default)]
+struct Window {
+    statuses: VecDeque<StatusCode>,
+    escalated: bool,
+}
+
+/// Tracks the error rate over the last `window` signing responses and escalates to an
+/// error log once the combined 5xx and 412 rate exceeds `threshold`.
+#[derive(Clone, Debug)]
+pub struct ErrorRateMonitor {
+    window: usize,
+    threshold: Option<f64>,
+    inner: Arc<Mutex<Window>>,
+}
+
+impl Default for ErrorRateMonitor {
+    fn default() -> Self {
+        ErrorRateMonitor::new(DEFAULT_ERROR_RATE_WINDOW, None)
+    }
+}
+
+impl ErrorRateMonitor {
+    pub fn new(window: usize, threshold: Option<f64>) -> Self {
+        ErrorRateMonitor {
+            window: window.max(1),
+            threshold,
+            inner: Arc::new(Mutex::new(Window::default())),
+        }
+    }
+
+    /// Records the status of a signing response and returns the updated error rates.
+    pub fn record(&self, status: StatusCode) -> ErrorRates {
+        let mut inner = self.inner.lock().expect("error rate lock poisoned");
+        inner.statuses.push_back(status);
+        while inner.statuses.len() > self.window {
+            inner.statuses.pop_front();
+        }
+        let rates = compute_rates(&inner.statuses);
+
+        // Only judge a full window so a single early failure does not escalate
+        if let Some(threshold) = self.threshold {
+            let exceeded = rates.samples == self.window && rates.total() > threshold;
+            if exceeded && !inner.escalated {
+                error!(
+                    target: ERROR_RATE_LOG_TARGET,
+                    "Error rate {:.2} exceeds threshold {:.2} over the last {} requests (5xx: {:.2}, 412: {:.2})",
+                    rates.total(),
+                    threshold,
+                    rates.samples,
+                    rates.server_error,
+                    rates.precondition_failed
+                );
+            } else if !exceeded && inner.escalated {
+                info!(
+                    target: ERROR_RATE_LOG_TARGET,
+                    "Error rate {:.2} is back under threshold {:.2}",
+                    rates.total(),
+                    threshold
+                );
+            }
+            inner.escalated = exceeded;
+        }
+        rates
+    }
+
+    /// Returns the error rates over the current window
+    pub fn rates(&self) -> ErrorRates {
+        let inner = self.inner.lock().expect("error rate lock poisoned");
+        compute_rates(&inner.statuses)
+    }
+
+    /// Renders the error rates in the Prometheus text format
+    pub fn to_prometheus(&self) -> String {
+        let rates = self.rates();
+        format!(
+            "# TYPE secure_signer_error_rate gauge\n\
+             secure_signer_error_rate{{kind=\"5xx\"}} {}\n\
+             secure_signer_error_rate{{kind=\"412\"}} {}\n\
+             # TYPE secure_signer_error_rate_samples gauge\n\
+             secure_signer_error_rate_samples {}\n",
+            rates.server_error, rates.precondition_failed, rates.samples
+        )
+    }
+}
+
+/// Warns about, and counts, slashable requests for keys with an empty slashing protection
+/// history. Usually the key was imported without its interchange, and nothing stops it from
+/// signing something it already signed elsewhere.
+#[derive(Clone, Debug, Default)]
+pub struct EmptySlashHistoryMonitor {
+    count: Arc<AtomicU64>,
+}
+
+impl EmptySlashHistoryMonitor {
+    pub fn record(&self, bls_pk_hex: &str) {
+        self.count.fetch_add(1, Ordering::SeqCst);
+        warn!(
+            target: EMPTY_SLASH_HISTORY_LOG_TARGET,
+            "Key {bls_pk_hex} has no slashing protection history, was it imported without its EIP-3076 interchange?"
+        );
+    }
+
+    pub fn count(&self) -> u64 {
+        self.count.load(Ordering::SeqCst)
+    }
+
+    /// Renders the count in the Prometheus text format
+    pub fn to_prometheus(&self) -> String {
+        format!(
+            "# TYPE secure_signer_empty_slash_history_total counter\n\
+             secure_signer_empty_slash_history_total {}\n",
+            self.count()
+        )
+    }
+}
+
+/// Counts signing requests per metrics label of their key. Only the configured labels can be
+/// provisioned, which bounds the number of series; none configured disables the labels.
+#[derive(Clone, Debug, Default)]
+pub struct MetricsLabels {
+    counts: Arc<Mutex<BTreeMap<String, u64>>>,
+}
+
+impl MetricsLabels {
+    /// Allows `labels`, which may only hold ASCII letters, digits, `-`, `_` and `.`
+    pub fn new(labels: impl IntoIterator<Item = String>) -> anyhow::Result<Self> {
+        let mut counts = BTreeMap::new();
+        for label in labels {
+            let valid = label
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.'));
+            if label.is_empty() || !valid {
+                anyhow::bail!("Bad metrics label {label:?}");
+            }
+            counts.insert(label, 0);
+        }
+        Ok(MetricsLabels {
+            counts: Arc::new(Mutex::new(counts)),
+        })
+    }
+
+    /// Parses comma separated labels, see `new`
+    pub fn parse(labels: &str) -> anyhow::Result<Self> {
+        MetricsLabels::new(
+            labels
+                .split(',')
+                .map(str::trim)
+                .filter(|l| !l.is_empty())
+                .map(str::to_string),
+        )
+    }
+
+    /// The configured labels, in order
+    pub fn labels(&self) -> Vec<String> {
+        let counts = self.counts.lock().expect("metrics labels lock poisoned");
+        counts.keys().cloned().collect()
+    }
+
+    pub fn is_allowed(&self, label: &str) -> bool {
+        let counts = self.counts.lock().expect("metrics labels lock poisoned");
+        counts.contains_key(label)
+    }
+
+    /// Counts a signing request for `bls_pk_hex` under its label, if it has a configured one
+    pub fn record(&self, bls_pk_hex: &str) {
+        if self.labels().is_empty() {
+            return;
+        }
+        match crate::io::key_management::read_metrics_label(bls_pk_hex) {
+            Ok(Some(label)) => {
+                let mut counts = self.counts.lock().expect("metrics labels lock poisoned");
+                if let Some(count) = counts.get_mut(&label) {
+                    *count += 1;
+                }
+            }
+            Ok(None) => {}
+            Err(e) => warn!("Failed to read metrics label of {bls_pk_hex}: {:?}", e),
+        }
+    }
+
+    /// Renders the per-label counts in the Prometheus text format, nothing if no labels are configured
+    pub fn to_prometheus(&self) -> String {
+        let counts = self.counts.lock().expect("metrics labels lock poisoned");
+        if counts.is_empty() {
+            return String::new();
+        }
+        let mut metrics = String::from("# TYPE secure_signer_requests_total counter\n");
+        for (label, count) in counts.iter() {
+            metrics.push_str(&format!(
+                "secure_signer_requests_total{{label=\"{label}\"}} {count}\n"
+            ));
+        }
+        metrics
+    }
+}
+
+fn compute_rates(statuses: &VecDeque<StatusCode>) -> ErrorRates {
+    if statuses.is_empty() {
+        return ErrorRates::default();
+    }
+    let samples = statuses.len();
+    let server_errors = statuses.iter().filter(|s| s.is_server_error()).count();
+    let precondition_failures = statuses
+        .iter()
+        .filter(|s| **s == StatusCode::PRECONDITION_FAILED)
+        .count();
+    ErrorRates {
+        samples,
+        server_error: server_errors as f64 / samples as f64,
+        precondition_failed: precondition_failures as f64 / samples as f64,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Captures the error rate logs so tests can assert on them
+    struct CaptureLogger;
+
+    static CAPTURED: Mutex<Vec<(log::Level, String)>> = Mutex::new(Vec::new());
+    static LOGGER: CaptureLogger = CaptureLogger;
+
+    impl log::Log for CaptureLogger {
+        fn enabled(&self, _: &log::Metadata) -> bool {
+            true
+        }
+
+        fn log(&self, record: &log::Record) {
+            if record.target() == ERROR_RATE_LOG_TARGET {
+                CAPTURED
+                    .lock()
+                    .unwrap()
+                    .push((record.level(), record.args().to_string()));
+            }
+        }
+
+        fn flush(&self) {}
+    }
+
+    #[test]
+    fn test_rates_over_rolling_window() {
+        let monitor = ErrorRateMonitor::new(4, None);
+        monitor.record(StatusCode::OK);
+        monitor.record(StatusCode::PRECONDITION_FAILED);
+        monitor.record(StatusCode::INTERNAL_SERVER_ERROR);
+        let rates = monitor.record(StatusCode::BAD_REQUEST);
+        assert_eq!(rates.samples, 4);
+        assert_eq!(rates.server_error, 0.25);
+        assert_eq!(rates.precondition_failed, 0.25);
+
+        // The oldest responses fall out of the window
+        monitor.record(StatusCode::OK);
+        let rates = monitor.record(StatusCode::OK);
+        assert_eq!(rates.samples, 4);
+        assert_eq!(rates.precondition_failed, 0.0);
+        assert_eq!(rates.server_error, 0.25);
+        assert!(monitor
+            .to_prometheus()
+            .contains("secure_signer_error_rate{kind=\"5xx\"} 0.25"));
+    }
+
+    #[test]
+    fn test_metrics_labels_must_be_configured() {
+        let _data_dir = crate::io::config::Config::scoped_temp().unwrap();
+        assert!(MetricsLabels::parse("pool-a,pool\"b").is_err());
+        let labels = MetricsLabels::parse("pool-a, pool-b").unwrap();
+        assert_eq!(labels.labels(), vec!["pool-a", "pool-b"]);
+
+        crate::io::key_management::write_metrics_label("0xaa", "pool-a").unwrap();
+        crate::io::key_management::write_metrics_label("0xbb", "pool-c").unwrap();
+        labels.record("0xaa");
+        labels.record("0xaa");
+        labels.record("0xbb");
+        labels.record("0xcc");
+        let metrics = labels.to_prometheus();
+        assert!(metrics.contains("secure_signer_requests_total{label=\"pool-a\"} 2\n"));
+        assert!(metrics.contains("secure_signer_requests_total{label=\"pool-b\"} 0\n"));
+        assert!(!metrics.contains("pool-c"));
+        assert_eq!(MetricsLabels::default().to_prometheus(), "");
+    }
+
+    #[test]
+    fn test_escalation_log_fires_past_threshold() {
+        let _ = log::set_logger(&LOGGER);
+        log::set_max_level(log::LevelFilter::Trace);
+
+        let monitor = ErrorRateMonitor::new(10, Some(0.5));
+        for _ in 0..5 {
+            monitor.record(StatusCode::OK);
+        }
+
+        // Drive 412s until just at the threshold, no escalation yet
+        for _ in 0..5 {
+            monitor.record(StatusCode::PRECONDITION_FAILED);
+        }
+        assert!(CAPTURED.lock().unwrap().is_empty());
+
+        // Past the threshold the error log fires once
+        for _ in 0..5 {
+            monitor.record(StatusCode::PRECONDITION_FAILED);
+        }
+        {
+            let captured = CAPTURED.lock().unwrap();
+            assert_eq!(captured.len(), 1);
+            assert_eq!(captured[0].0, log::Level::Error);
+            assert!(captured[0].1.contains("exceeds threshold"));
+        }
+
+        // Recovery is logged when the rate drops back under
+        for _ in 0..10 {
+            monitor.record(StatusCode::OK);
+        }
+        let captured = CAPTURED.lock().unwrap();
+        assert_eq!(captured.len(), 2);
+        assert_eq!(captured[1].0, log::Level::Info);
+    }
+}
@@ -1,4 +1,22 @@
+pub mod connections;
+pub mod cors;
 pub mod handlers;
+pub mod key_limit;
+pub mod key_lock;
+pub mod logging;
+pub mod metrics;
+pub mod queue;
+pub mod readiness;
+pub mod request_id;
+pub mod request_log;
+pub mod request_mac;
+pub mod response_cache;
+pub mod server;
+pub mod session;
+pub mod shutdown;
+pub mod sign_deadline;
+pub mod sign_stats;
+pub mod sign_timings;
 use anyhow::{bail, Result};
 use axum::{
     extract::{Path, State},
@@ -8,17 +26,21 @@ use axum::{
 use log::{error, info};
 use sha3::Digest;
 
-/// Signs the specific type of request
+/// Signs the specific type of request, or with `dry_run` only checks that it would be signed
 /// Maintains compatibility with https://consensys.github.io/web3signer/web3signer-eth2.html#tag/Signing
 pub fn sign_validator_message(
     Path(bls_pk_hex): Path<String>,
     State(state): State<crate::enclave::shared::handlers::AppState>,
     Json(req): Json<crate::eth2::eth_signing::BLSSignMsg>,
+    include_signing_root: bool,
+    signature_format: crate::enclave::types::SignatureFormat,
+    dry_run: bool,
+    timings: &mut crate::enclave::shared::sign_timings::SignTimings,
 ) -> axum::response::Response {
     info!("secure_sign_bls()");
 
-    // Sanitize the input bls_pk_hex
-    let bls_pk_hex = match crate::crypto::bls_keys::sanitize_bls_pk_hex(&bls_pk_hex) {
+    // Strictly validate the input bls_pk_hex before it is used to locate any files
+    let bls_pk_hex = match crate::crypto::bls_keys::validate_bls_pk_hex(&bls_pk_hex) {
         Ok(pk) => pk,
         Err(e) => {
             error!("Bad BLS public key format: {bls_pk_hex}");
@@ -30,37 +52,341 @@ pub fn sign_validator_message(
         }
     };
 
+    // Unknown keys are reported distinctly so callers can import them
+    if !crate::io::key_management::bls_key_exists(&bls_pk_hex) {
+        error!("Unknown BLS public key: {bls_pk_hex}");
+        return (
+            axum::http::status::StatusCode::NOT_FOUND,
+            format!("No key found for bls_pk_hex 0x{bls_pk_hex}"),
+        )
+            .into_response();
+    }
+
     info!("Request for validator pubkey: {bls_pk_hex}");
     info!("Request:\n{:#?}", serde_json::to_string_pretty(&req));
 
-    // Verify not a slashable msg
-    match crate::enclave::shared::is_slashable(&bls_pk_hex, &req) {
-        Ok(b) => match b {
-            true => {
+    // Verify the key was provisioned to sign this type of request
+    match crate::io::key_management::read_sign_type_allowlist(&bls_pk_hex) {
+        Ok(Some(allowed)) if !allowed.iter().any(|t| t == req.type_name()) => {
+            error!(
+                "{} requests are not allowed for {bls_pk_hex}",
+                req.type_name()
+            );
+            return (
+                axum::http::status::StatusCode::FORBIDDEN,
+                format!("{} requests are not allowed for this key", req.type_name()),
+            )
+                .into_response();
+        }
+        Ok(_) => {}
+        Err(e) => {
+            return (
+                axum::http::status::StatusCode::INTERNAL_SERVER_ERROR,
+                format!("Signing operation failed: {:?}", e),
+            )
+                .into_response()
+        }
+    }
+
+    // Verify a block is proposed as the validator index the key was bound to
+    if let Some(proposer_index) = req.proposer_index() {
+        match crate::io::key_management::read_proposer_index(&bls_pk_hex) {
+            Ok(Some(bound)) if bound != proposer_index => {
+                error!("Block for {bls_pk_hex} proposed as {proposer_index}, bound to {bound}");
                 return (
-                    axum::http::status::StatusCode::PRECONDITION_FAILED,
-                    format!("Signing operation failed due to slashing protection rules"),
+                    axum::http::status::StatusCode::FORBIDDEN,
+                    format!("proposer_index {proposer_index} is not the index bound to this key"),
+                )
+                    .into_response();
+            }
+            Ok(_) => {}
+            Err(e) => {
+                return (
+                    axum::http::status::StatusCode::INTERNAL_SERVER_ERROR,
+                    format!("Signing operation failed: {:?}", e),
+                )
+                    .into_response()
+            }
+        }
+    }
+
+    // Verify the fork_info is consistent with the fork schedule
+    if let Err(e) = req.validate_fork_info(&state.fork_schedule) {
+        error!("Inconsistent fork_info: {:?}", e);
+        return (
+            axum::http::status::StatusCode::BAD_REQUEST,
+            format!("Bad fork_info, {:?}", e),
+        )
+            .into_response();
+    }
+
+    // Verify the domain is one configured for this signer, so a mistyped fork_info fails loudly
+    if let Err(e) = req.validate_domain(&state.allowed_domains) {
+        error!("Disallowed signing domain: {:?}", e);
+        return (
+            axum::http::status::StatusCode::BAD_REQUEST,
+            format!("Bad signing domain, {e}"),
+        )
+            .into_response();
+    }
+
+    // Verify a sync committee selection proof or contribution is for an existing subcommittee
+    if state.validate_subcommittee_index {
+        use ssz_types::typenum::Unsigned;
+        let subnet_count = crate::eth2::eth_types::SYNC_COMMITTEE_SUBNET_COUNT::to_u64();
+        if let Some(index) = req.subcommittee_index().filter(|i| *i >= subnet_count) {
+            error!("Subcommittee index {index} out of range");
+            return (
+                axum::http::status::StatusCode::BAD_REQUEST,
+                format!("Bad subcommittee_index {index}, must be less than {subnet_count}"),
+            )
+                .into_response();
+        }
+    }
+
+    // An attestation can't vote for a target older than its source, whatever the key signed before
+    if let Some((source, target)) = req.attestation_epochs().filter(|(s, t)| s > t) {
+        error!("Attestation source epoch {source} is after its target epoch {target}");
+        return (
+            axum::http::status::StatusCode::BAD_REQUEST,
+            format!("Bad attestation, source epoch {source} is after target epoch {target}"),
+        )
+            .into_response();
+    }
+
+    // Verify the key isn't bound to another network's genesis_validators_root
+    let genesis_root = match state.bind_genesis_root {
+        true => req.genesis_validators_root(),
+        false => None,
+    };
+    let genesis_binding = match genesis_root {
+        Some(_) => match crate::eth2::slash_protection::GenesisBinding::read(&bls_pk_hex) {
+            Ok(binding) => binding,
+            Err(e) => {
+                return (
+                    axum::http::status::StatusCode::INTERNAL_SERVER_ERROR,
+                    format!("Signing operation failed: {:?}", e),
                 )
                     .into_response()
             }
-            false => {}
         },
-        Err(e) => {
+        None => None,
+    };
+    if let (Some(root), Some(binding)) = (genesis_root, genesis_binding) {
+        if binding.genesis_validators_root != root {
+            error!(
+                "Key {bls_pk_hex} is bound to genesis_validators_root 0x{}, not 0x{}",
+                hex::encode(binding.genesis_validators_root),
+                hex::encode(root)
+            );
             return (
-                axum::http::status::StatusCode::INTERNAL_SERVER_ERROR,
-                format!("Signing operation failed: {:?}", e),
+                axum::http::status::StatusCode::PRECONDITION_FAILED,
+                format!(
+                    "Signing operation failed, key is bound to genesis_validators_root 0x{}",
+                    hex::encode(binding.genesis_validators_root)
+                ),
             )
-                .into_response()
+                .into_response();
+        }
+    }
+
+    // Verify a voluntary exit is not too close to the current epoch
+    if let Some(min_distance) = state.min_exit_epoch_distance {
+        if let crate::eth2::eth_signing::BLSSignMsg::VOLUNTARY_EXIT(m)
+        | crate::eth2::eth_signing::BLSSignMsg::voluntary_exit(m) = &req
+        {
+            let current_epoch = crate::eth2::eth_signing::compute_current_epoch(state.genesis_time);
+            if m.voluntary_exit.epoch < current_epoch.saturating_add(min_distance) {
+                error!(
+                    "Exit epoch {} is less than {min_distance} epochs after current epoch {current_epoch}",
+                    m.voluntary_exit.epoch
+                );
+                return (
+                    axum::http::status::StatusCode::FORBIDDEN,
+                    format!(
+                        "Exit epoch must be at least {min_distance} epochs after current epoch {current_epoch}"
+                    ),
+                )
+                    .into_response();
+            }
+        }
+    }
+
+    // Verify a validator registration requests a sane gas limit, for a fee recipient the key was
+    // provisioned with
+    if let Some(registration) = req.registration() {
+        let gas_limit = registration.gas_limit;
+        let bounds = state.gas_limit_bounds;
+        if !bounds.contains(gas_limit) {
+            error!(
+                "Gas limit {gas_limit} outside of [{}, {}]",
+                bounds.min, bounds.max
+            );
+            return (
+                axum::http::status::StatusCode::BAD_REQUEST,
+                format!(
+                    "Bad gas_limit {gas_limit}, must be between {} and {}",
+                    bounds.min, bounds.max
+                ),
+            )
+                .into_response();
+        }
+
+        let fee_recipient = hex::encode(&registration.fee_recipient[..]);
+        match crate::io::key_management::read_fee_recipient_allowlist(&bls_pk_hex) {
+            Ok(Some(allowed)) if !allowed.contains(&fee_recipient) => {
+                error!("Fee recipient 0x{fee_recipient} is not allowed for {bls_pk_hex}");
+                return (
+                    axum::http::status::StatusCode::FORBIDDEN,
+                    format!("Fee recipient 0x{fee_recipient} is not allowed for this key"),
+                )
+                    .into_response();
+            }
+            Ok(_) => {}
+            Err(e) => {
+                return (
+                    axum::http::status::StatusCode::INTERNAL_SERVER_ERROR,
+                    format!("Signing operation failed: {:?}", e),
+                )
+                    .into_response()
+            }
         }
+    }
+
+    // Verify a validator registration is newer than the last one signed, so it can't be replayed
+    let registration_timestamp = match state.monotonic_registration_timestamps {
+        true => req.registration().map(|r| r.timestamp),
+        false => None,
+    };
+    let last_registration = match registration_timestamp {
+        Some(_) => match crate::eth2::slash_protection::LastRegistration::read(&bls_pk_hex) {
+            Ok(last) => last,
+            Err(e) => {
+                return (
+                    axum::http::status::StatusCode::INTERNAL_SERVER_ERROR,
+                    format!("Signing operation failed: {:?}", e),
+                )
+                    .into_response()
+            }
+        },
+        None => None,
     };
+    if let (Some(timestamp), Some(last)) = (registration_timestamp, last_registration) {
+        if timestamp <= last.timestamp {
+            error!(
+                "Registration timestamp {timestamp} is not after the last signed {}",
+                last.timestamp
+            );
+            return (
+                axum::http::status::StatusCode::PRECONDITION_FAILED,
+                format!(
+                    "Signing operation failed, registration timestamp must be after {}",
+                    last.timestamp
+                ),
+            )
+                .into_response();
+        }
+    }
 
-    // Compute the msg to be signed
+    timings.mark("validate");
+
+    // Only block proposals and attestations are checked against, and recorded in, the slash DB
+    let slashable_type = req.is_slashable_type();
+    if slashable_type {
+        // Verify not below the key's signing floor, which holds even if the slash DB was rolled back
+        match crate::enclave::shared::below_signing_floor(&bls_pk_hex, &req) {
+            Ok(Some(reason)) => {
+                error!("Refusing to sign below the signing floor: {reason}");
+                return (
+                    axum::http::status::StatusCode::PRECONDITION_FAILED,
+                    format!("Signing operation failed, below the signing floor: {reason}"),
+                )
+                    .into_response();
+            }
+            Ok(None) => {}
+            Err(e) => {
+                return (
+                    axum::http::status::StatusCode::INTERNAL_SERVER_ERROR,
+                    format!("Signing operation failed: {:?}", e),
+                )
+                    .into_response()
+            }
+        };
+
+        // Verify not a slashable msg. The slashing DB must exist
+        let db = match crate::eth2::slash_protection::SlashingProtectionData::read(&bls_pk_hex) {
+            Ok(db) => db,
+            Err(e) => {
+                return (
+                    axum::http::status::StatusCode::INTERNAL_SERVER_ERROR,
+                    format!("Signing operation failed: {:?}", e),
+                )
+                    .into_response()
+            }
+        };
+        // Not refused, the history may legitimately start here, but worth an operator's look
+        if db.is_empty() {
+            state.empty_slash_history.record(&bls_pk_hex);
+        }
+        match crate::enclave::shared::is_slashable(&db, &req) {
+            Ok(b) => match b {
+                true => {
+                    return (
+                        axum::http::status::StatusCode::PRECONDITION_FAILED,
+                        format!("Signing operation failed due to slashing protection rules"),
+                    )
+                        .into_response()
+                }
+                false => {}
+            },
+            Err(e) => {
+                return (
+                    axum::http::status::StatusCode::INTERNAL_SERVER_ERROR,
+                    format!("Signing operation failed: {:?}", e),
+                )
+                    .into_response()
+            }
+        };
+    }
+
+    timings.mark("slash_check");
+
+    // Compute the msg to be signed, rejecting a client signingRoot that doesn't match it and
+    // exits whose fork can't be told
     let signing_root: crate::eth2::eth_types::Root =
-        req.to_signing_root(Some(state.genesis_fork_version));
+        match req.checked_signing_root(Some(state.genesis_fork_version)) {
+            Ok(root) => root,
+            Err(e) => {
+                error!("Bad signing root: {:?}", e);
+                return (
+                    axum::http::status::StatusCode::BAD_REQUEST,
+                    format!("Bad signingRoot, {:?}", e),
+                )
+                    .into_response();
+            }
+        };
     info!("signing_root: {}", hex::encode(signing_root));
+    timings.mark("signing_root");
+
+    // A dry run stops once the request is known to be signable, before anything is recorded
+    if dry_run {
+        info!("Dry run passed for validator pubkey: {bls_pk_hex}");
+        return axum::http::status::StatusCode::OK.into_response();
+    }
+
+    // Unseal the key before anything is recorded, so a request failing on a transient sealing
+    // error can be retried without tripping over its own slashing protection entry
+    let secret_key_set = match crate::crypto::bls_keys::fetch_bls_sk(&bls_pk_hex) {
+        Ok(secret_key_set) => secret_key_set,
+        Err(e) => {
+            error!("Failed trying to unseal the key: {:?}", e);
+            return internal_error(&e, format!("Signing operation failed: {:?}", e));
+        }
+    };
+    timings.mark("unseal");
 
     // Update the slash protection DB if msg was a block or attestation
-    if req.can_be_slashed() {
+    if slashable_type {
         if let Err(e) = crate::enclave::shared::update_slash_protection_db(&bls_pk_hex, &req) {
             error!("Failed trying to update slash protection database");
             return (
@@ -71,12 +397,58 @@ pub fn sign_validator_message(
         }
     }
 
+    // Bind the key to the genesis_validators_root of its first signature
+    if let (Some(root), None) = (genesis_root, genesis_binding) {
+        let binding = crate::eth2::slash_protection::GenesisBinding {
+            genesis_validators_root: root,
+        };
+        if let Err(e) = binding.write(&bls_pk_hex) {
+            error!("Failed trying to bind key to genesis_validators_root");
+            return (
+                axum::http::status::StatusCode::INTERNAL_SERVER_ERROR,
+                format!("Signing operation failed: {:?}", e),
+            )
+                .into_response();
+        }
+    }
+
+    // Record the registration so older ones can't be signed after it
+    if let Some(timestamp) = registration_timestamp {
+        let last = crate::eth2::slash_protection::LastRegistration { timestamp };
+        if let Err(e) = last.write(&bls_pk_hex) {
+            error!("Failed trying to record registration timestamp");
+            return (
+                axum::http::status::StatusCode::INTERNAL_SERVER_ERROR,
+                format!("Signing operation failed: {:?}", e),
+            )
+                .into_response();
+        }
+    }
+
+    timings.mark("slash_db_write");
+
     // Sign the message
-    match crate::crypto::bls_keys::bls_agg_sign_from_saved_sk(&bls_pk_hex, &signing_root) {
+    let sig =
+        crate::crypto::bls_keys::bls_agg_sign_checked(&bls_pk_hex, &secret_key_set, &signing_root);
+    timings.mark("bls_sign");
+    match sig {
         Ok(sig) => {
             info!("signature: {:?}", hex::encode(sig.to_bytes()));
-            let response = crate::enclave::types::SignatureResponse::new(&sig.to_bytes());
-            (axum::http::status::StatusCode::OK, Json(response)).into_response()
+            let mut response = crate::enclave::types::SignatureResponse::new(&sig.to_bytes());
+            if include_signing_root {
+                response = response.with_signing_root(&signing_root);
+            }
+            match response.to_json(signature_format) {
+                Ok(json) => (axum::http::status::StatusCode::OK, Json(json)).into_response(),
+                Err(e) => {
+                    error!("Failed to encode signature: {e:?}");
+                    (
+                        axum::http::status::StatusCode::INTERNAL_SERVER_ERROR,
+                        format!("Failed to encode signature, {:?}", e),
+                    )
+                        .into_response()
+                }
+            }
         }
         Err(e) => {
             error!("Failed trying to sign");
@@ -89,15 +461,72 @@ pub fn sign_validator_message(
     }
 }
 
-/// Returns true if signing_data is a block proposal or attestation and is slashable
-fn is_slashable(
+/// Answers an operation that failed on `e` with a 500 carrying `message`, or with a 503 and a
+/// `Retry-After` if it failed on a transient sealing error, so clients retry instead of giving up
+pub fn internal_error(e: &anyhow::Error, message: String) -> axum::response::Response {
+    if crate::crypto::sealing::is_transient(e) {
+        return (
+            axum::http::status::StatusCode::SERVICE_UNAVAILABLE,
+            [(
+                axum::http::header::RETRY_AFTER,
+                crate::crypto::sealing::SEALING_RETRY_AFTER_SECS.to_string(),
+            )],
+            format!("Try again later, {:#}", e),
+        )
+            .into_response();
+    }
+    (
+        axum::http::status::StatusCode::INTERNAL_SERVER_ERROR,
+        message,
+    )
+        .into_response()
+}
+
+/// Returns why signing_data is below the signing floor of bls_pk_hex, or None if it is not
+/// or no floor was set
+fn below_signing_floor(
     bls_pk_hex: &String,
     signing_data: &crate::eth2::eth_signing::BLSSignMsg,
-) -> Result<bool> {
-    // The slashing DB must exist
-    let db: crate::eth2::slash_protection::SlashingProtectionData =
-        crate::eth2::slash_protection::SlashingProtectionData::read(bls_pk_hex.as_str())?;
+) -> Result<Option<String>> {
+    let Some(floor) = crate::eth2::slash_protection::SigningFloor::read(bls_pk_hex)? else {
+        return Ok(None);
+    };
+
+    let block_slot = match signing_data {
+        crate::eth2::eth_signing::BLSSignMsg::BLOCK(m)
+        | crate::eth2::eth_signing::BLSSignMsg::block(m) => Some(m.block.slot),
+        crate::eth2::eth_signing::BLSSignMsg::BLOCK_V2(m)
+        | crate::eth2::eth_signing::BLSSignMsg::block_v2(m) => {
+            Some(m.beacon_block.block_header.slot)
+        }
+        _ => None,
+    };
+    if let Some(slot) = block_slot {
+        if floor.is_below_block_slot(slot) {
+            return Ok(Some(format!(
+                "block slot {slot} is below the minimum slot {}",
+                floor.min_slot
+            )));
+        }
+    }
+
+    if let Some((src, tgt)) = signing_data.attestation_epochs() {
+        if floor.is_below_attestation_epochs(src, tgt) {
+            return Ok(Some(format!(
+                "attestation epochs (source: {src}, target: {tgt}) are below the minimum epochs (source: {}, target: {})",
+                floor.min_source_epoch, floor.min_target_epoch
+            )));
+        }
+    }
+    Ok(None)
+}
 
+/// Returns true if the block proposal or attestation signing_data is slashable against `db`.
+/// Errors for any other type, callers check `BLSSignMsg::is_slashable_type` first.
+fn is_slashable(
+    db: &crate::eth2::slash_protection::SlashingProtectionData,
+    signing_data: &crate::eth2::eth_signing::BLSSignMsg,
+) -> Result<bool> {
     match signing_data {
         crate::eth2::eth_signing::BLSSignMsg::BLOCK(m)
         | crate::eth2::eth_signing::BLSSignMsg::block(m) => {
@@ -107,17 +536,11 @@ fn is_slashable(
         | crate::eth2::eth_signing::BLSSignMsg::block_v2(m) => {
             Ok(db.is_slashable_block_slot(m.beacon_block.block_header.slot))
         }
-
-        crate::eth2::eth_signing::BLSSignMsg::ATTESTATION(m)
-        | crate::eth2::eth_signing::BLSSignMsg::attestation(m) => Ok(db
-            .is_slashable_attestation_epochs(
-                m.attestation.source.epoch,
-                m.attestation.target.epoch,
-            )),
-        _ => {
+        _ => match signing_data.attestation_epochs() {
+            Some((src, tgt)) => Ok(db.is_slashable_attestation_epochs(src, tgt)),
             // Only block proposals and attestations are slashable
-            Ok(false)
-        }
+            None => bail!("Should not check slash protection db for non blocks/attestations"),
+        },
     }
 }
 
@@ -126,8 +549,7 @@ fn update_slash_protection_db(
     signing_data: &crate::eth2::eth_signing::BLSSignMsg,
 ) -> Result<()> {
     info!("update_slash_protection_db()");
-    let mut db: crate::eth2::slash_protection::SlashingProtectionData =
-        crate::eth2::slash_protection::SlashingProtectionData::read(bls_pk_hex.as_str())?;
+    let store = crate::eth2::slash_protection::store()?;
     let signing_root = signing_data.to_signing_root(None);
     match signing_data {
         crate::eth2::eth_signing::BLSSignMsg::BLOCK(m)
@@ -136,8 +558,7 @@ fn update_slash_protection_db(
                 slot: m.block.slot,
                 signing_root: Some(signing_root),
             };
-            db.new_block(b, crate::constants::ALLOW_GROWABLE_SLASH_PROTECTION_DB)?;
-            db.write()
+            store.record_block(bls_pk_hex, b)
         }
         crate::eth2::eth_signing::BLSSignMsg::BLOCK_V2(m)
         | crate::eth2::eth_signing::BLSSignMsg::block_v2(m) => {
@@ -145,24 +566,23 @@ fn update_slash_protection_db(
                 slot: m.beacon_block.block_header.slot,
                 signing_root: Some(signing_root),
             };
-            db.new_block(b, crate::constants::ALLOW_GROWABLE_SLASH_PROTECTION_DB)?;
-            db.write()
-        }
-        crate::eth2::eth_signing::BLSSignMsg::ATTESTATION(m)
-        | crate::eth2::eth_signing::BLSSignMsg::attestation(m) => {
-            let a = crate::eth2::slash_protection::SignedAttestationEpochs {
-                source_epoch: m.attestation.source.epoch,
-                target_epoch: m.attestation.target.epoch,
-                signing_root: Some(signing_root),
-            };
-            db.new_attestation(a, crate::constants::ALLOW_GROWABLE_SLASH_PROTECTION_DB)?;
-            db.write()
-        }
-        _ => {
-            // Only block proposals and attestations are slashable
-            error!("Attempted to update slash protection db with non-slashable msg type");
-            bail!("Should not update slash protection db for non blocks/attestations")
+            store.record_block(bls_pk_hex, b)
         }
+        _ => match signing_data.attestation_epochs() {
+            Some((source_epoch, target_epoch)) => {
+                let a = crate::eth2::slash_protection::SignedAttestationEpochs {
+                    source_epoch,
+                    target_epoch,
+                    signing_root: Some(signing_root),
+                };
+                store.record_attestation(bls_pk_hex, a)
+            }
+            None => {
+                // Only block proposals and attestations are slashable
+                error!("Attempted to update slash protection db with non-slashable msg type");
+                bail!("Should not update slash protection db for non blocks/attestations")
+            }
+        },
     }
 }
 
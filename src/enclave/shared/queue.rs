@@ -0,0 +1,170 @@
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::{OwnedSemaphorePermit, Semaphore};
+
+/// Default time a signing request may wait in the queue before it is shed
+pub const DEFAULT_SIGNING_QUEUE_MAX_WAIT: Duration = Duration::from_secs(1);
+
+/// Why a signing request was shed instead of queued
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum QueueRejection {
+    /// The queue already holds `max_depth` waiting requests
+    Full,
+    /// No signing slot freed up within `max_wait`
+    TimedOut,
+}
+
+impl std::fmt::Display for QueueRejection {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            QueueRejection::Full => write!(f, "signing queue is full"),
+            QueueRejection::TimedOut => write!(f, "timed out waiting in the signing queue"),
+        }
+    }
+}
+
+/// Bounds the number of concurrent signing requests. Requests beyond that wait in a queue of
+/// at most `max_depth` for up to `max_wait`, and are only shed once either limit is hit.
+#[derive(Clone, Debug)]
+pub struct SigningQueue {
+    slots: Arc<Semaphore>,
+    concurrency: usize,
+    max_depth: usize,
+    max_wait: Duration,
+    waiting: Arc<AtomicUsize>,
+}
+
+/// Frees the queue position when dropped, including when the waiting request is cancelled
+struct Waiting(Arc<AtomicUsize>);
+
+impl Drop for Waiting {
+    fn drop(&mut self) {
+        self.0.fetch_sub(1, Ordering::SeqCst);
+    }
+}
+
+impl SigningQueue {
+    pub fn new(concurrency: usize, max_depth: usize, max_wait: Duration) -> Self {
+        SigningQueue {
+            slots: Arc::new(Semaphore::new(concurrency.max(1))),
+            concurrency: concurrency.max(1),
+            max_depth,
+            max_wait,
+            waiting: Arc::new(AtomicUsize::new(0)),
+        }
+    }
+
+    /// Waits for a signing slot, which is held until the returned permit is dropped.
+    pub async fn acquire(&self) -> Result<OwnedSemaphorePermit, QueueRejection> {
+        if let Ok(permit) = self.slots.clone().try_acquire_owned() {
+            return Ok(permit);
+        }
+
+        // Take a queue position if one is free
+        let max_depth = self.max_depth;
+        self.waiting
+            .fetch_update(Ordering::SeqCst, Ordering::SeqCst, |w| {
+                (w < max_depth).then_some(w + 1)
+            })
+            .map_err(|_| QueueRejection::Full)?;
+        let _waiting = Waiting(self.waiting.clone());
+
+        match tokio::time::timeout(self.max_wait, self.slots.clone().acquire_owned()).await {
+            Ok(Ok(permit)) => Ok(permit),
+            _ => Err(QueueRejection::TimedOut),
+        }
+    }
+
+    /// Number of requests currently waiting for a signing slot
+    pub fn depth(&self) -> usize {
+        self.waiting.load(Ordering::SeqCst)
+    }
+
+    /// Number of requests signed concurrently
+    pub fn concurrency(&self) -> usize {
+        self.concurrency
+    }
+
+    pub fn max_depth(&self) -> usize {
+        self.max_depth
+    }
+
+    pub fn max_wait(&self) -> Duration {
+        self.max_wait
+    }
+
+    /// Renders the queue depth in the Prometheus text format
+    pub fn to_prometheus(&self) -> String {
+        format!(
+            "# TYPE secure_signer_signing_queue_depth gauge\n\
+             secure_signer_signing_queue_depth {}\n",
+            self.depth()
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_burst_within_depth_is_queued() {
+        let queue = SigningQueue::new(1, 3, Duration::from_secs(5));
+        let busy = queue.acquire().await.unwrap();
+
+        let waiters: Vec<_> = (0..3)
+            .map(|_| {
+                let queue = queue.clone();
+                tokio::spawn(async move { queue.acquire().await.map(drop) })
+            })
+            .collect();
+        while queue.depth() < 3 {
+            tokio::task::yield_now().await;
+        }
+        assert!(queue
+            .to_prometheus()
+            .contains("secure_signer_signing_queue_depth 3"));
+
+        // Everyone gets through once the slot frees up
+        drop(busy);
+        for waiter in waiters {
+            assert_eq!(waiter.await.unwrap(), Ok(()));
+        }
+        assert_eq!(queue.depth(), 0);
+    }
+
+    #[tokio::test]
+    async fn test_burst_over_depth_is_shed() {
+        let queue = SigningQueue::new(1, 2, Duration::from_secs(5));
+        let busy = queue.acquire().await.unwrap();
+
+        let waiters: Vec<_> = (0..2)
+            .map(|_| {
+                let queue = queue.clone();
+                tokio::spawn(async move { queue.acquire().await.map(drop) })
+            })
+            .collect();
+        while queue.depth() < 2 {
+            tokio::task::yield_now().await;
+        }
+
+        // The excess is shed immediately
+        for _ in 0..3 {
+            assert_eq!(queue.acquire().await.err(), Some(QueueRejection::Full));
+        }
+
+        drop(busy);
+        for waiter in waiters {
+            assert_eq!(waiter.await.unwrap(), Ok(()));
+        }
+    }
+
+    #[tokio::test]
+    async fn test_wait_past_max_wait_is_shed() {
+        let queue = SigningQueue::new(1, 2, Duration::from_millis(10));
+        let _busy = queue.acquire().await.unwrap();
+        assert_eq!(queue.acquire().await.err(), Some(QueueRejection::TimedOut));
+        assert_eq!(queue.depth(), 0);
+    }
+}
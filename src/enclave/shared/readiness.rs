@@ -0,0 +1,61 @@
+use anyhow::{Context, Result};
+use log::{error, info};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+/// How long to wait before running the startup checks again after they failed
+pub const DEFAULT_READINESS_RETRY: Duration = Duration::from_secs(1);
+
+/// Whether the signer finished starting up and may be sent traffic. Starts out not ready, shared
+/// by every clone.
+#[derive(Clone, Debug, Default)]
+pub struct Readiness(Arc<AtomicBool>);
+
+impl Readiness {
+    pub fn is_ready(&self) -> bool {
+        self.0.load(Ordering::SeqCst)
+    }
+
+    pub fn mark_ready(&self) {
+        self.0.store(true, Ordering::SeqCst)
+    }
+
+    /// Runs the startup checks every `retry` until they pass, then marks the signer ready
+    pub async fn initialize(self, retry: Duration) {
+        loop {
+            match check() {
+                Ok(()) => break,
+                Err(e) => error!("Not ready yet: {:?}", e),
+            }
+            tokio::time::sleep(retry).await;
+        }
+        info!("Ready to serve");
+        self.mark_ready();
+    }
+}
+
+/// Checks the saved keys can be listed and that keys can be sealed to and read back from the
+/// data dir
+pub fn check() -> Result<()> {
+    crate::io::key_management::list_bls_keys().with_context(|| "Keys can't be listed")?;
+    crate::io::key_management::storage_health_check()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::io::config::Config;
+
+    #[tokio::test]
+    async fn test_initialize_marks_ready() {
+        let _data_dir = Config::scoped_in_memory();
+        let readiness = Readiness::default();
+        assert!(!readiness.is_ready());
+        readiness
+            .clone()
+            .initialize(Duration::from_millis(10))
+            .await;
+        assert!(readiness.is_ready());
+    }
+}
@@ -0,0 +1,183 @@
+use axum::http::StatusCode;
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+/// Header carrying the caller-chosen id of a signing request
+pub const REQUEST_ID_HEADER: &str = "X-Request-Id";
+
+/// Default window in which a request id may not be reused
+pub const DEFAULT_REQUEST_ID_TTL: Duration = Duration::from_secs(60 * 60);
+
+/// Default number of used request ids remembered at once. Past it the oldest ids are forgotten
+/// before their TTL, so a client sending distinct ids can't grow the cache without bound.
+pub const DEFAULT_MAX_REQUEST_IDS: usize = 100_000;
+
+/// Returns true if a request answered with `status` may be retried under the same id, as it was
+/// turned away before any outcome a retry wouldn't change
+pub fn is_retryable(status: StatusCode) -> bool {
+    matches!(
+        status,
+        StatusCode::SERVICE_UNAVAILABLE | StatusCode::GATEWAY_TIMEOUT
+    )
+}
+
+#[derive(Debug, Default)]
+struct Ids {
+    /// When each used id was recorded
+    used: HashMap<String, Instant>,
+    /// Used ids, oldest first
+    order: VecDeque<String>,
+    /// Ids of requests still in flight
+    reserved: HashSet<String>,
+}
+
+impl Ids {
+    fn forget_expired(&mut self, now: Instant, ttl: Duration) {
+        while let Some(oldest) = self.order.front() {
+            if now.duration_since(self.used[oldest]) < ttl {
+                break;
+            }
+            self.used.remove(oldest);
+            self.order.pop_front();
+        }
+    }
+}
+
+/// Remembers the request ids used within a TTL window so replayed signing requests can be rejected.
+#[derive(Clone, Debug)]
+pub struct RequestIdCache {
+    ttl: Duration,
+    max_ids: usize,
+    ids: Arc<Mutex<Ids>>,
+}
+
+impl RequestIdCache {
+    pub fn new(ttl: Duration) -> Self {
+        RequestIdCache::with_max_ids(ttl, DEFAULT_MAX_REQUEST_IDS)
+    }
+
+    /// Like `new`, remembering at most `max_ids` used ids
+    pub fn with_max_ids(ttl: Duration, max_ids: usize) -> Self {
+        RequestIdCache {
+            ttl,
+            max_ids: max_ids.max(1),
+            ids: Arc::new(Mutex::new(Ids::default())),
+        }
+    }
+
+    pub fn ttl(&self) -> Duration {
+        self.ttl
+    }
+
+    /// Reserves `request_id` for a request in flight, returning None if it was used within the TTL
+    /// window or is reserved by another request. The id only counts as used once the reservation
+    /// is committed, dropping it frees the id for a retry.
+    pub fn reserve(&self, request_id: &str) -> Option<RequestIdReservation> {
+        let mut ids = self.ids.lock().expect("request id cache poisoned");
+        ids.forget_expired(Instant::now(), self.ttl);
+        if ids.used.contains_key(request_id) || !ids.reserved.insert(request_id.to_string()) {
+            return None;
+        }
+        Some(RequestIdReservation {
+            cache: self.clone(),
+            request_id: request_id.to_string(),
+            used: false,
+        })
+    }
+
+    /// Records `request_id` as used, returning false if it was already used within the TTL window.
+    pub fn insert(&self, request_id: &str) -> bool {
+        self.reserve(request_id).map(|r| r.commit()).is_some()
+    }
+
+    fn release(&self, request_id: &str, used: bool) {
+        let mut ids = self.ids.lock().expect("request id cache poisoned");
+        ids.reserved.remove(request_id);
+        if !used {
+            return;
+        }
+        let now = Instant::now();
+        ids.forget_expired(now, self.ttl);
+        while ids.order.len() >= self.max_ids {
+            if let Some(oldest) = ids.order.pop_front() {
+                log::warn!("Forgetting request id {oldest} before its TTL, the cache is full");
+                ids.used.remove(&oldest);
+            }
+        }
+        ids.used.insert(request_id.to_string(), now);
+        ids.order.push_back(request_id.to_string());
+    }
+}
+
+impl Default for RequestIdCache {
+    fn default() -> Self {
+        RequestIdCache::new(DEFAULT_REQUEST_ID_TTL)
+    }
+}
+
+/// A request id held by a request in flight, see `RequestIdCache::reserve`
+#[derive(Debug)]
+pub struct RequestIdReservation {
+    cache: RequestIdCache,
+    request_id: String,
+    used: bool,
+}
+
+impl RequestIdReservation {
+    /// Uses up the id, for a request that reached an outcome a retry wouldn't change
+    pub fn commit(mut self) {
+        self.used = true;
+    }
+}
+
+impl Drop for RequestIdReservation {
+    fn drop(&mut self) {
+        self.cache.release(&self.request_id, self.used);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_duplicate_request_id_is_rejected() {
+        let cache = RequestIdCache::default();
+        assert!(cache.insert("abc"));
+        assert!(!cache.insert("abc"));
+        assert!(cache.insert("def"));
+    }
+
+    #[test]
+    fn test_request_id_can_be_reused_after_ttl() {
+        let cache = RequestIdCache::new(Duration::from_millis(10));
+        assert!(cache.insert("abc"));
+        std::thread::sleep(Duration::from_millis(20));
+        assert!(cache.insert("abc"));
+    }
+
+    #[test]
+    fn test_uncommitted_request_id_can_be_retried() {
+        let cache = RequestIdCache::default();
+        let reservation = cache.reserve("abc").unwrap();
+        // A concurrent request can't take the id while it is in flight
+        assert!(cache.reserve("abc").is_none());
+        drop(reservation);
+
+        cache.reserve("abc").unwrap().commit();
+        assert!(cache.reserve("abc").is_none());
+    }
+
+    #[test]
+    fn test_oldest_request_ids_are_forgotten_past_max_ids() {
+        let cache = RequestIdCache::with_max_ids(DEFAULT_REQUEST_ID_TTL, 2);
+        assert!(cache.insert("a"));
+        assert!(cache.insert("b"));
+        assert!(cache.insert("c"));
+        assert_eq!(cache.ids.lock().unwrap().used.len(), 2);
+        assert!(!cache.insert("b"));
+        assert!(!cache.insert("c"));
+        assert!(cache.insert("a"));
+    }
+}
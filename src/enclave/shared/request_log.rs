@@ -0,0 +1,145 @@
+use axum::{
+    extract::MatchedPath,
+    http::{HeaderValue, Request},
+    middleware::Next,
+    response::Response,
+};
+use tracing::Instrument;
+
+use crate::enclave::shared::request_id::REQUEST_ID_HEADER;
+
+/// Longest caller-chosen request id that is propagated, longer ones are replaced by a generated id
+pub const MAX_REQUEST_ID_LEN: usize = 64;
+
+/// Path parameter holding the pubkey of per-key routes
+const PUBKEY_PARAM: &str = ":bls_pk_hex";
+
+/// Leading characters of the pubkey kept in logs, enough to tell keys apart
+const PUBKEY_LOG_LEN: usize = 10;
+
+/// Id of the request being handled, available to handlers as an extension
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct RequestId(pub String);
+
+/// Runs every request in a span carrying its id, route, truncated pubkey and response status, so
+/// a validator client's log line can be matched to the signer's decision. The id is taken from
+/// the caller's `X-Request-Id` if it has one, else generated, and is echoed in the response.
+pub async fn middleware<B>(mut req: Request<B>, next: Next<B>) -> Response {
+    let request_id = req
+        .headers()
+        .get(REQUEST_ID_HEADER)
+        .and_then(|id| id.to_str().ok())
+        .filter(|id| is_loggable(id))
+        .map(str::to_string)
+        .unwrap_or_else(|| hex::encode(rand::random::<[u8; 8]>()));
+    let route = req
+        .extensions()
+        .get::<MatchedPath>()
+        .map(|route| route.as_str().to_string());
+    let pubkey = route
+        .as_deref()
+        .and_then(|route| truncated_pubkey(route, req.uri().path()));
+    let span = tracing::info_span!(
+        "request",
+        request_id = %request_id,
+        method = %req.method(),
+        route = route.as_deref().unwrap_or(req.uri().path()),
+        pubkey = pubkey.as_deref().unwrap_or_default(),
+        status = tracing::field::Empty,
+    );
+
+    // Left out of the request headers so a generated id never satisfies a required `X-Request-Id`
+    req.extensions_mut().insert(RequestId(request_id.clone()));
+
+    let start = std::time::Instant::now();
+    let mut response = next.run(req).instrument(span.clone()).await;
+    span.record("status", response.status().as_u16());
+    tracing::info!(
+        parent: &span,
+        elapsed_ms = start.elapsed().as_millis() as u64,
+        "request finished"
+    );
+
+    if let Ok(id) = HeaderValue::from_str(&request_id) {
+        response.headers_mut().insert(REQUEST_ID_HEADER, id);
+    }
+    response
+}
+
+/// Only short, printable ids are propagated, so callers can't bloat or forge log lines
+fn is_loggable(id: &str) -> bool {
+    !id.is_empty() && id.len() <= MAX_REQUEST_ID_LEN && id.chars().all(|c| c.is_ascii_graphic())
+}
+
+/// Returns the start of the pubkey in `path` if `route` has a pubkey parameter
+fn truncated_pubkey(route: &str, path: &str) -> Option<String> {
+    let i = route
+        .split('/')
+        .position(|segment| segment == PUBKEY_PARAM)?;
+    let pubkey = path.split('/').nth(i)?;
+    Some(pubkey.chars().take(PUBKEY_LOG_LEN).collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::{routing::get, Router};
+
+    fn test_server() -> axum_test::TestServer {
+        let app = Router::new()
+            .route(
+                "/upcheck",
+                get(crate::enclave::shared::handlers::health::handler),
+            )
+            .layer(axum::middleware::from_fn(middleware));
+        axum_test::TestServer::new(app.into_make_service()).unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_request_id_is_generated() {
+        let server = test_server();
+        let first = server.get("/upcheck").await;
+        let second = server.get("/upcheck").await;
+        let first = first.headers().get(REQUEST_ID_HEADER).unwrap();
+        let second = second.headers().get(REQUEST_ID_HEADER).unwrap();
+        assert_eq!(first.len(), 16);
+        assert_ne!(first, second);
+    }
+
+    #[tokio::test]
+    async fn test_request_id_is_propagated() {
+        let server = test_server();
+        let resp = server
+            .get("/upcheck")
+            .add_header(
+                axum::http::HeaderName::from_static("x-request-id"),
+                HeaderValue::from_static("vc-1234"),
+            )
+            .await;
+        assert_eq!(resp.headers().get(REQUEST_ID_HEADER).unwrap(), "vc-1234");
+
+        // An unloggable id is replaced
+        let long = "a".repeat(MAX_REQUEST_ID_LEN + 1);
+        let resp = server
+            .get("/upcheck")
+            .add_header(
+                axum::http::HeaderName::from_static("x-request-id"),
+                HeaderValue::from_str(&long).unwrap(),
+            )
+            .await;
+        assert_ne!(
+            resp.headers().get(REQUEST_ID_HEADER).unwrap(),
+            long.as_str()
+        );
+    }
+
+    #[test]
+    fn test_truncated_pubkey() {
+        let route = "/api/v1/eth2/sign/:bls_pk_hex";
+        assert_eq!(
+            truncated_pubkey(route, "/api/v1/eth2/sign/0x8349434ad0700e79be65"),
+            Some("0x8349434a".to_string())
+        );
+        assert_eq!(truncated_pubkey("/upcheck", "/upcheck"), None);
+    }
+}
@@ -0,0 +1,52 @@
+use anyhow::{Context, Result};
+use openssl::hash::MessageDigest;
+use openssl::pkey::PKey;
+use openssl::sign::Signer;
+
+/// Header carrying the hex HMAC-SHA256 of a sign request's body
+pub const REQUEST_MAC_HEADER: &str = "X-Request-Mac";
+
+/// Returns the HMAC-SHA256 of `body` under `secret`
+pub fn compute(secret: &[u8], body: &[u8]) -> Result<Vec<u8>> {
+    let key = PKey::hmac(secret).with_context(|| "Bad request MAC secret")?;
+    let mut signer = Signer::new(MessageDigest::sha256(), &key)?;
+    signer.update(body)?;
+    Ok(signer.sign_to_vec()?)
+}
+
+/// Returns true if `mac_hex` is the HMAC-SHA256 of `body` under `secret`, compared in constant time
+pub fn verify(secret: &[u8], body: &[u8], mac_hex: &str) -> bool {
+    let mac_hex: &str = crate::strip_0x_prefix!(mac_hex);
+    let (Ok(mac), Ok(expected)) = (hex::decode(mac_hex), compute(secret, body)) else {
+        return false;
+    };
+    mac.len() == expected.len() && openssl::memcmp::eq(&mac, &expected)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_valid_mac_verifies() {
+        let secret = b"shared-secret";
+        let body = br#"{"type":"RANDAO_REVEAL"}"#;
+        let mac = hex::encode(compute(secret, body).unwrap());
+        assert!(verify(secret, body, &mac));
+        assert!(verify(secret, body, &format!("0x{mac}")));
+    }
+
+    #[test]
+    fn test_tampered_mac_is_rejected() {
+        let secret = b"shared-secret";
+        let body = br#"{"type":"RANDAO_REVEAL"}"#;
+        let mac = hex::encode(compute(secret, body).unwrap());
+
+        // Tampered body, wrong secret, truncated or malformed MAC
+        assert!(!verify(secret, br#"{"type":"RANDAO_REVEAL "}"#, &mac));
+        assert!(!verify(b"another-secret", body, &mac));
+        assert!(!verify(secret, body, &mac[..mac.len() - 2]));
+        assert!(!verify(secret, body, "not hex"));
+        assert!(!verify(secret, body, ""));
+    }
+}
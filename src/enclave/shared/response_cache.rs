@@ -0,0 +1,197 @@
+use axum::{
+    body::Bytes,
+    http::{HeaderMap, StatusCode},
+    response::{IntoResponse, Response},
+};
+use std::collections::{HashMap, VecDeque};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+/// Default number of signing responses kept for replay to retries
+pub const DEFAULT_SIGN_RESPONSE_CACHE_SIZE: usize = 1024;
+
+/// A successful signing response, as sent
+struct CachedResponse {
+    cached_at: Instant,
+    headers: HeaderMap,
+    body: Bytes,
+}
+
+/// Entries in least to most recently used order
+#[derive(Default)]
+struct Entries {
+    responses: HashMap<[u8; 32], CachedResponse>,
+    order: VecDeque<[u8; 32]>,
+}
+
+impl Entries {
+    fn remove(&mut self, key: &[u8; 32]) {
+        self.responses.remove(key);
+        self.order.retain(|k| k != key);
+    }
+}
+
+/// Remembers successful signing responses for `window`, so an exact retry of a request gets the
+/// same response without being signed again. Holds at most `capacity` responses, evicting the
+/// least recently used.
+#[derive(Clone)]
+pub struct ResponseCache {
+    window: Duration,
+    capacity: usize,
+    entries: Arc<Mutex<Entries>>,
+}
+
+impl ResponseCache {
+    pub fn new(window: Duration, capacity: usize) -> Self {
+        ResponseCache {
+            window,
+            capacity: capacity.max(1),
+            entries: Arc::new(Mutex::new(Entries::default())),
+        }
+    }
+
+    pub fn window(&self) -> Duration {
+        self.window
+    }
+
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    /// SHA-256 of everything that shapes a signing response: the key, the session the request is
+    /// in, whether the signing root is returned, how the signature is encoded and the request body.
+    pub fn key(
+        bls_pk_hex: &str,
+        session_id: Option<&str>,
+        query: &crate::enclave::types::SignQuery,
+        body: &[u8],
+    ) -> [u8; 32] {
+        let session_id = session_id.unwrap_or_default();
+        let mut hasher = openssl::sha::Sha256::new();
+        for field in [bls_pk_hex.as_bytes(), session_id.as_bytes()] {
+            hasher.update(&(field.len() as u64).to_be_bytes());
+            hasher.update(field);
+        }
+        hasher.update(&[
+            query.include_signing_root as u8,
+            query.signature_format as u8,
+        ]);
+        hasher.update(body);
+        hasher.finish()
+    }
+
+    /// Returns the response cached under `key` if it is still within the window
+    pub fn get(&self, key: &[u8; 32]) -> Option<Response> {
+        let mut entries = self.entries.lock().expect("response cache poisoned");
+        let fresh = entries.responses.get(key)?.cached_at.elapsed() < self.window;
+        if !fresh {
+            entries.remove(key);
+            return None;
+        }
+        entries.order.retain(|k| k != key);
+        entries.order.push_back(*key);
+        let cached = &entries.responses[key];
+        Some((StatusCode::OK, cached.headers.clone(), cached.body.clone()).into_response())
+    }
+
+    /// Caches `response` under `key` if it succeeded, returning it to be sent
+    pub async fn insert(&self, key: [u8; 32], response: Response) -> Response {
+        if response.status() != StatusCode::OK {
+            return response;
+        }
+        let (parts, body) = response.into_parts();
+        let body = match hyper::body::to_bytes(body).await {
+            Ok(body) => body,
+            Err(e) => {
+                log::error!("Failed to buffer signing response: {e}");
+                return StatusCode::INTERNAL_SERVER_ERROR.into_response();
+            }
+        };
+
+        {
+            let mut entries = self.entries.lock().expect("response cache poisoned");
+            let window = self.window;
+            let Entries { responses, order } = &mut *entries;
+            responses.retain(|_, cached| cached.cached_at.elapsed() < window);
+            order.retain(|k| responses.contains_key(k) && k != &key);
+            while order.len() >= self.capacity {
+                if let Some(lru) = order.pop_front() {
+                    responses.remove(&lru);
+                }
+            }
+            responses.insert(
+                key,
+                CachedResponse {
+                    cached_at: Instant::now(),
+                    headers: parts.headers.clone(),
+                    body: body.clone(),
+                },
+            );
+            order.push_back(key);
+        }
+
+        Response::from_parts(parts, axum::body::boxed(axum::body::Full::from(body)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    async fn body_of(response: Response) -> Bytes {
+        hyper::body::to_bytes(response.into_body()).await.unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_least_recently_used_is_evicted() {
+        let cache = ResponseCache::new(Duration::from_secs(60), 2);
+        let (a, b, c) = ([1; 32], [2; 32], [3; 32]);
+        for (key, body) in [(a, "a"), (b, "b")] {
+            let response = cache.insert(key, body.into_response()).await;
+            assert_eq!(body_of(response).await, body);
+        }
+
+        // Using `a` leaves `b` as the least recently used
+        assert_eq!(body_of(cache.get(&a).unwrap()).await, "a");
+        cache.insert(c, "c".into_response()).await;
+        assert!(cache.get(&b).is_none());
+        assert_eq!(body_of(cache.get(&a).unwrap()).await, "a");
+        assert_eq!(body_of(cache.get(&c).unwrap()).await, "c");
+    }
+
+    #[tokio::test]
+    async fn test_expired_and_failed_responses_are_not_served() {
+        let cache = ResponseCache::new(Duration::from_millis(50), 2);
+        cache.insert([1; 32], "a".into_response()).await;
+        cache
+            .insert([2; 32], StatusCode::PRECONDITION_FAILED.into_response())
+            .await;
+        assert!(cache.get(&[1; 32]).is_some());
+        assert!(cache.get(&[2; 32]).is_none());
+
+        tokio::time::sleep(Duration::from_millis(60)).await;
+        assert!(cache.get(&[1; 32]).is_none());
+    }
+
+    #[test]
+    fn test_key_covers_pubkey_session_and_body() {
+        use crate::enclave::types::{SignQuery, SignatureFormat};
+        let query = SignQuery::default();
+        let key = ResponseCache::key("0xaa", None, &query, b"{}");
+        assert_eq!(key, ResponseCache::key("0xaa", None, &query, b"{}"));
+        assert_ne!(key, ResponseCache::key("0xbb", None, &query, b"{}"));
+        assert_ne!(key, ResponseCache::key("0xaa", Some("s"), &query, b"{}"));
+        assert_ne!(key, ResponseCache::key("0xaa", None, &query, b"{ }"));
+
+        let with_root = SignQuery {
+            include_signing_root: true,
+            ..Default::default()
+        };
+        assert_ne!(key, ResponseCache::key("0xaa", None, &with_root, b"{}"));
+        let base64 = SignQuery {
+            signature_format: SignatureFormat::Base64,
+            ..Default::default()
+        };
+        assert_ne!(key, ResponseCache::key("0xaa", None, &base64, b"{}"));
+    }
+}
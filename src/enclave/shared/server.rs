@@ -0,0 +1,358 @@
+use anyhow::{bail, Context, Result};
+use std::future::Future;
+use std::net::SocketAddr;
+use std::path::PathBuf;
+use std::str::FromStr;
+use std::time::Duration;
+
+use crate::enclave::shared::connections::ConnectionLimits;
+use crate::enclave::shared::shutdown::{InFlight, DEFAULT_SHUTDOWN_GRACE};
+
+/// Prefix marking a listen address as a unix domain socket path
+pub const UNIX_PREFIX: &str = "unix:";
+
+/// Permissions of a bound unix domain socket, read and write for the owner only
+#[cfg(unix)]
+pub const UNIX_SOCKET_MODE: u32 = 0o600;
+
+/// Where a server accepts connections
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ListenAddr {
+    Tcp(SocketAddr),
+    #[cfg(unix)]
+    Unix(PathBuf),
+}
+
+/// Parses `host:port`, or `unix:<path>` for a unix domain socket
+impl FromStr for ListenAddr {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        if let Some(path) = s.strip_prefix(UNIX_PREFIX) {
+            if path.is_empty() {
+                bail!("Missing unix socket path")
+            }
+            #[cfg(unix)]
+            return Ok(ListenAddr::Unix(PathBuf::from(path)));
+            #[cfg(not(unix))]
+            bail!("Unix sockets are not supported on this platform")
+        }
+        s.parse::<SocketAddr>()
+            .map(ListenAddr::Tcp)
+            .with_context(|| format!("Bad listen address {s}"))
+    }
+}
+
+impl std::fmt::Display for ListenAddr {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ListenAddr::Tcp(addr) => write!(f, "{addr}"),
+            #[cfg(unix)]
+            ListenAddr::Unix(path) => write!(f, "{UNIX_PREFIX}{}", path.display()),
+        }
+    }
+}
+
+/// A bound socket ready to be served
+#[derive(Debug)]
+pub enum Listener {
+    Tcp(std::net::TcpListener),
+    #[cfg(unix)]
+    Unix(std::os::unix::net::UnixListener),
+}
+
+impl From<std::net::TcpListener> for Listener {
+    fn from(listener: std::net::TcpListener) -> Self {
+        Listener::Tcp(listener)
+    }
+}
+
+#[cfg(unix)]
+impl From<std::os::unix::net::UnixListener> for Listener {
+    fn from(listener: std::os::unix::net::UnixListener) -> Self {
+        Listener::Unix(listener)
+    }
+}
+
+impl Listener {
+    pub fn bind(addr: &ListenAddr) -> Result<Self> {
+        match addr {
+            ListenAddr::Tcp(addr) => std::net::TcpListener::bind(addr)
+                .map(Listener::Tcp)
+                .with_context(|| format!("Failed to bind {addr}")),
+            #[cfg(unix)]
+            ListenAddr::Unix(path) => {
+                remove_stale_socket(path)?;
+                let listener = std::os::unix::net::UnixListener::bind(path)
+                    .with_context(|| format!("Failed to bind {}", path.display()))?;
+                // Only the owner may connect, so nothing else on the host can reach the signer
+                use std::os::unix::fs::PermissionsExt;
+                std::fs::set_permissions(path, std::fs::Permissions::from_mode(UNIX_SOCKET_MODE))
+                    .with_context(|| format!("Failed to restrict {}", path.display()))?;
+                listener.set_nonblocking(true)?;
+                Ok(Listener::Unix(listener))
+            }
+        }
+    }
+
+    /// The bound TCP address, useful when binding port 0
+    pub fn local_addr(&self) -> Option<SocketAddr> {
+        match self {
+            Listener::Tcp(listener) => listener.local_addr().ok(),
+            #[cfg(unix)]
+            Listener::Unix(_) => None,
+        }
+    }
+}
+
+/// A socket left behind by a previous run would make the bind fail, but anything that isn't a
+/// socket is left alone
+#[cfg(unix)]
+fn remove_stale_socket(path: &std::path::Path) -> Result<()> {
+    use std::os::unix::fs::FileTypeExt;
+    match std::fs::symlink_metadata(path) {
+        Ok(meta) if meta.file_type().is_socket() => std::fs::remove_file(path)
+            .with_context(|| format!("Failed to remove stale socket {}", path.display())),
+        Ok(_) => bail!("{} exists and is not a socket", path.display()),
+        Err(_) => Ok(()),
+    }
+}
+
+/// Accepts connections on a unix domain socket for hyper
+#[cfg(unix)]
+pub(crate) struct UnixAccept(pub(crate) tokio::net::UnixListener);
+
+#[cfg(unix)]
+impl hyper::server::accept::Accept for UnixAccept {
+    type Conn = tokio::net::UnixStream;
+    type Error = std::io::Error;
+
+    fn poll_accept(
+        self: std::pin::Pin<&mut Self>,
+        cx: &mut std::task::Context<'_>,
+    ) -> std::task::Poll<Option<Result<Self::Conn, Self::Error>>> {
+        self.0
+            .poll_accept(cx)
+            .map(|res| Some(res.map(|(stream, _)| stream)))
+    }
+}
+
+/// Serves a router on a TCP address or unix domain socket. The routes are unchanged either way,
+/// only how they're reached.
+pub struct Server {
+    listener: Listener,
+    shutdown_grace: Duration,
+    connection_limits: ConnectionLimits,
+}
+
+impl Server {
+    /// Binds `addr` straight away so bind errors surface before any serving starts
+    pub fn bind(addr: &ListenAddr) -> Result<Self> {
+        Ok(Server {
+            listener: Listener::bind(addr)?,
+            shutdown_grace: DEFAULT_SHUTDOWN_GRACE,
+            connection_limits: ConnectionLimits::default(),
+        })
+    }
+
+    pub fn with_shutdown_grace(mut self, grace: Duration) -> Self {
+        self.shutdown_grace = grace;
+        self
+    }
+
+    pub fn with_connection_limits(mut self, limits: ConnectionLimits) -> Self {
+        self.connection_limits = limits;
+        self
+    }
+
+    pub fn local_addr(&self) -> Option<SocketAddr> {
+        self.listener.local_addr()
+    }
+
+    /// Serves `app` until `shutdown` resolves, see `shutdown::serve`
+    pub async fn serve(
+        self,
+        app: axum::Router,
+        in_flight: InFlight,
+        shutdown: impl Future<Output = ()>,
+    ) -> Result<()> {
+        crate::enclave::shared::shutdown::serve(
+            self.listener,
+            app,
+            in_flight,
+            self.shutdown_grace,
+            self.connection_limits,
+            shutdown,
+        )
+        .await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::{routing::get, Router};
+
+    fn upcheck_app() -> Router {
+        Router::new().route(
+            "/upcheck",
+            get(crate::enclave::shared::handlers::health::handler),
+        )
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_parse_listen_addr() {
+        assert_eq!(
+            "127.0.0.1:3031".parse::<ListenAddr>().unwrap(),
+            ListenAddr::Tcp(SocketAddr::from(([127, 0, 0, 1], 3031)))
+        );
+        assert_eq!(
+            "unix:/run/secure-signer.sock"
+                .parse::<ListenAddr>()
+                .unwrap(),
+            ListenAddr::Unix(PathBuf::from("/run/secure-signer.sock"))
+        );
+        assert!("unix:".parse::<ListenAddr>().is_err());
+        assert!("localhost".parse::<ListenAddr>().is_err());
+    }
+
+    #[tokio::test]
+    async fn test_upcheck_on_ephemeral_port() {
+        let server = Server::bind(&"127.0.0.1:0".parse().unwrap()).unwrap();
+        let port = server.local_addr().unwrap().port();
+        assert_ne!(port, 0);
+
+        let (shutdown_tx, shutdown_rx) = tokio::sync::oneshot::channel::<()>();
+        let task = tokio::spawn(server.serve(upcheck_app(), InFlight::default(), async {
+            shutdown_rx.await.ok();
+        }));
+
+        let resp = reqwest::get(format!("http://127.0.0.1:{port}/upcheck"))
+            .await
+            .unwrap();
+        assert_eq!(resp.status(), 200);
+
+        shutdown_tx.send(()).unwrap();
+        task.await.unwrap().unwrap();
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_upcheck_on_unix_socket() {
+        let path = std::env::temp_dir().join(format!("secure-signer-{}.sock", std::process::id()));
+        let server = Server::bind(&ListenAddr::Unix(path.clone())).unwrap();
+        assert!(server.local_addr().is_none());
+        use std::os::unix::fs::PermissionsExt;
+        assert_eq!(
+            std::fs::metadata(&path).unwrap().permissions().mode() & 0o777,
+            UNIX_SOCKET_MODE
+        );
+
+        let (shutdown_tx, shutdown_rx) = tokio::sync::oneshot::channel::<()>();
+        let task = tokio::spawn(server.serve(upcheck_app(), InFlight::default(), async {
+            shutdown_rx.await.ok();
+        }));
+
+        // Speak HTTP/1.1 over the socket by hand, reqwest has no unix socket support
+        use tokio::io::{AsyncReadExt, AsyncWriteExt};
+        let mut stream = tokio::net::UnixStream::connect(&path).await.unwrap();
+        stream
+            .write_all(b"GET /upcheck HTTP/1.1\r\nHost: localhost\r\nConnection: close\r\n\r\n")
+            .await
+            .unwrap();
+        let mut resp = String::new();
+        stream.read_to_string(&mut resp).await.unwrap();
+        assert!(resp.starts_with("HTTP/1.1 200"), "{resp}");
+
+        shutdown_tx.send(()).unwrap();
+        task.await.unwrap().unwrap();
+
+        // A stale socket from the previous run doesn't block rebinding
+        Server::bind(&ListenAddr::Unix(path.clone())).unwrap();
+        std::fs::remove_file(path).unwrap();
+    }
+
+    /// Sends an upcheck on a kept alive `stream` and returns the start of the response
+    async fn upcheck_over(stream: &mut tokio::net::TcpStream) -> String {
+        use tokio::io::{AsyncReadExt, AsyncWriteExt};
+        stream
+            .write_all(b"GET /upcheck HTTP/1.1\r\nHost: localhost\r\n\r\n")
+            .await
+            .unwrap();
+        let mut buf = [0; 1024];
+        let n = stream.read(&mut buf).await.unwrap();
+        String::from_utf8_lossy(&buf[..n]).into_owned()
+    }
+
+    #[tokio::test]
+    async fn test_connections_beyond_the_cap_wait_for_a_free_slot() {
+        let server = Server::bind(&"127.0.0.1:0".parse().unwrap())
+            .unwrap()
+            .with_connection_limits(ConnectionLimits {
+                max_connections: Some(1),
+                ..Default::default()
+            });
+        let port = server.local_addr().unwrap().port();
+        let (shutdown_tx, shutdown_rx) = tokio::sync::oneshot::channel::<()>();
+        let task = tokio::spawn(server.serve(upcheck_app(), InFlight::default(), async {
+            shutdown_rx.await.ok();
+        }));
+
+        // The first connection is kept alive, holding the only slot
+        let mut first = tokio::net::TcpStream::connect(("127.0.0.1", port))
+            .await
+            .unwrap();
+        assert!(upcheck_over(&mut first).await.starts_with("HTTP/1.1 200"));
+
+        // The second waits in the backlog, unanswered, until the first closes
+        let mut second = tokio::net::TcpStream::connect(("127.0.0.1", port))
+            .await
+            .unwrap();
+        let pending = tokio::spawn(async move { upcheck_over(&mut second).await });
+        tokio::time::sleep(Duration::from_millis(200)).await;
+        assert!(!pending.is_finished());
+
+        drop(first);
+        let resp = tokio::time::timeout(Duration::from_secs(5), pending)
+            .await
+            .unwrap()
+            .unwrap();
+        assert!(resp.starts_with("HTTP/1.1 200"), "{resp}");
+
+        shutdown_tx.send(()).unwrap();
+        task.await.unwrap().unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_idle_keep_alive_connection_is_closed() {
+        let server = Server::bind(&"127.0.0.1:0".parse().unwrap())
+            .unwrap()
+            .with_connection_limits(ConnectionLimits {
+                keep_alive_timeout: Some(Duration::from_millis(100)),
+                ..Default::default()
+            });
+        let port = server.local_addr().unwrap().port();
+        let (shutdown_tx, shutdown_rx) = tokio::sync::oneshot::channel::<()>();
+        let task = tokio::spawn(server.serve(upcheck_app(), InFlight::default(), async {
+            shutdown_rx.await.ok();
+        }));
+
+        let mut stream = tokio::net::TcpStream::connect(("127.0.0.1", port))
+            .await
+            .unwrap();
+        assert!(upcheck_over(&mut stream).await.starts_with("HTTP/1.1 200"));
+
+        // Left idle, the server hangs up
+        use tokio::io::AsyncReadExt;
+        let mut buf = [0; 16];
+        let n = tokio::time::timeout(Duration::from_secs(5), stream.read(&mut buf))
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(n, 0);
+
+        shutdown_tx.send(()).unwrap();
+        task.await.unwrap().unwrap();
+    }
+}
@@ -0,0 +1,134 @@
+use crate::eth2::eth_types::Root;
+
+use std::collections::{HashMap, VecDeque};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+/// Header carrying the id of the session a sign request belongs to
+pub const SESSION_ID_HEADER: &str = "X-Session-Id";
+
+/// Default time a session stays open after its handshake
+pub const DEFAULT_SESSION_TTL: Duration = Duration::from_secs(24 * 60 * 60);
+
+/// Default number of sessions open at once. Past it the oldest sessions are closed, so repeated
+/// handshakes can't grow the store without bound.
+pub const DEFAULT_MAX_SESSIONS: usize = 1024;
+
+#[derive(Debug, Default)]
+struct Sessions {
+    /// The pinned genesis_validators_root of each session and when it was opened
+    by_id: HashMap<String, (Root, Instant)>,
+    /// Session ids, oldest first
+    order: VecDeque<String>,
+}
+
+impl Sessions {
+    fn close_expired(&mut self, now: Instant, ttl: Duration) {
+        while let Some(oldest) = self.order.front() {
+            if now.duration_since(self.by_id[oldest].1) < ttl {
+                break;
+            }
+            self.by_id.remove(oldest);
+            self.order.pop_front();
+        }
+    }
+}
+
+/// Sessions established through the handshake, each pinning a genesis_validators_root until it
+/// expires. Handshakes must present the configured bearer token.
+#[derive(Clone, Debug)]
+pub struct SessionStore {
+    token: String,
+    ttl: Duration,
+    max_sessions: usize,
+    sessions: Arc<Mutex<Sessions>>,
+}
+
+impl SessionStore {
+    pub fn new(token: String) -> Self {
+        SessionStore::with_limits(token, DEFAULT_SESSION_TTL, DEFAULT_MAX_SESSIONS)
+    }
+
+    /// Like `new`, closing sessions after `ttl` and keeping at most `max_sessions` open
+    pub fn with_limits(token: String, ttl: Duration, max_sessions: usize) -> Self {
+        SessionStore {
+            token,
+            ttl,
+            max_sessions: max_sessions.max(1),
+            sessions: Arc::new(Mutex::new(Sessions::default())),
+        }
+    }
+
+    /// Returns true if `token` matches the configured token, compared in constant time
+    pub fn authenticate(&self, token: &str) -> bool {
+        token.len() == self.token.len()
+            && openssl::memcmp::eq(token.as_bytes(), self.token.as_bytes())
+    }
+
+    /// Opens a session pinned to `genesis_validators_root` and returns its id, closing the oldest
+    /// session if the store is full
+    pub fn open(&self, genesis_validators_root: Root) -> String {
+        let session_id = hex::encode(rand::random::<[u8; 16]>());
+        let now = Instant::now();
+        let mut sessions = self.sessions.lock().expect("session store poisoned");
+        sessions.close_expired(now, self.ttl);
+        while sessions.order.len() >= self.max_sessions {
+            if let Some(oldest) = sessions.order.pop_front() {
+                log::warn!("Closing session {oldest} before its TTL, the session store is full");
+                sessions.by_id.remove(&oldest);
+            }
+        }
+        sessions
+            .by_id
+            .insert(session_id.clone(), (genesis_validators_root, now));
+        sessions.order.push_back(session_id.clone());
+        session_id
+    }
+
+    /// Returns the genesis_validators_root of the session, if it exists and hasn't expired
+    pub fn genesis_validators_root(&self, session_id: &str) -> Option<Root> {
+        let mut sessions = self.sessions.lock().expect("session store poisoned");
+        sessions.close_expired(Instant::now(), self.ttl);
+        sessions.by_id.get(session_id).map(|(root, _)| *root)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sessions() {
+        let store = SessionStore::new("secret".to_string());
+        assert!(store.authenticate("secret"));
+        assert!(!store.authenticate("secreT"));
+        assert!(!store.authenticate("secret2"));
+
+        let a = store.open([1; 32]);
+        let b = store.open([2; 32]);
+        assert_ne!(a, b);
+        assert_eq!(store.genesis_validators_root(&a), Some([1; 32]));
+        assert_eq!(store.genesis_validators_root(&b), Some([2; 32]));
+        assert_eq!(store.genesis_validators_root("unknown"), None);
+    }
+
+    #[test]
+    fn test_sessions_expire() {
+        let store = SessionStore::with_limits("secret".to_string(), Duration::from_millis(10), 16);
+        let a = store.open([1; 32]);
+        assert_eq!(store.genesis_validators_root(&a), Some([1; 32]));
+        std::thread::sleep(Duration::from_millis(20));
+        assert_eq!(store.genesis_validators_root(&a), None);
+    }
+
+    #[test]
+    fn test_oldest_sessions_are_closed_past_max_sessions() {
+        let store = SessionStore::with_limits("secret".to_string(), DEFAULT_SESSION_TTL, 2);
+        let a = store.open([1; 32]);
+        let b = store.open([2; 32]);
+        let c = store.open([3; 32]);
+        assert_eq!(store.genesis_validators_root(&a), None);
+        assert_eq!(store.genesis_validators_root(&b), Some([2; 32]));
+        assert_eq!(store.genesis_validators_root(&c), Some([3; 32]));
+    }
+}
@@ -0,0 +1,262 @@
+use crate::enclave::shared::connections::{ConnectionLimits, LimitedAccept};
+use anyhow::{Context, Result};
+use log::{info, warn};
+use std::future::Future;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::Notify;
+
+/// Default time in-flight requests get to finish once shutdown starts
+pub const DEFAULT_SHUTDOWN_GRACE: Duration = Duration::from_secs(30);
+
+/// Counts the signing requests currently being handled
+#[derive(Clone, Debug, Default)]
+pub struct InFlight {
+    count: Arc<AtomicUsize>,
+    idle: Arc<Notify>,
+}
+
+/// Marks a request as in flight until dropped
+pub struct InFlightGuard(InFlight);
+
+impl Drop for InFlightGuard {
+    fn drop(&mut self) {
+        if self.0.count.fetch_sub(1, Ordering::SeqCst) == 1 {
+            self.0.idle.notify_waiters();
+        }
+    }
+}
+
+impl InFlight {
+    pub fn start(&self) -> InFlightGuard {
+        self.count.fetch_add(1, Ordering::SeqCst);
+        InFlightGuard(self.clone())
+    }
+
+    pub fn count(&self) -> usize {
+        self.count.load(Ordering::SeqCst)
+    }
+
+    /// Resolves once no request is in flight
+    pub async fn wait_idle(&self) {
+        loop {
+            let notified = self.idle.notified();
+            tokio::pin!(notified);
+            // Register before checking so a guard dropped in between still wakes us
+            notified.as_mut().enable();
+            if self.count() == 0 {
+                return;
+            }
+            notified.await;
+        }
+    }
+}
+
+/// Resolves on SIGTERM or Ctrl-C
+pub async fn signal() {
+    let ctrl_c = async {
+        tokio::signal::ctrl_c()
+            .await
+            .expect("Failed to listen for Ctrl-C");
+    };
+
+    #[cfg(unix)]
+    let terminate = async {
+        tokio::signal::unix::signal(tokio::signal::unix::SignalKind::terminate())
+            .expect("Failed to listen for SIGTERM")
+            .recv()
+            .await;
+    };
+
+    #[cfg(not(unix))]
+    let terminate = std::future::pending::<()>();
+
+    tokio::select! {
+        _ = ctrl_c => {}
+        _ = terminate => {}
+    }
+}
+
+/// Serves `app` until `shutdown` resolves, then stops accepting connections and waits up to
+/// `grace` for the open ones to be answered. Signing runs without yielding from the slashing
+/// check through the slash DB write, so a request cut off by the grace period is never left
+/// with a half-written DB; it is dropped before its check or after its write. Open connections
+/// are kept within `limits`.
+pub async fn serve(
+    listener: impl Into<crate::enclave::shared::server::Listener>,
+    app: axum::Router,
+    in_flight: InFlight,
+    grace: Duration,
+    limits: ConnectionLimits,
+    shutdown: impl Future<Output = ()>,
+) -> Result<()> {
+    let (stop_tx, stop_rx) = tokio::sync::oneshot::channel::<()>();
+    let stop = async {
+        stop_rx.await.ok();
+    };
+    match listener.into() {
+        crate::enclave::shared::server::Listener::Tcp(listener) => {
+            listener.set_nonblocking(true)?;
+            let listener =
+                tokio::net::TcpListener::from_std(listener).with_context(|| "Failed to listen")?;
+            let incoming = hyper::server::conn::AddrIncoming::from_listener(listener)
+                .with_context(|| "Failed to listen")?;
+            let server = axum::Server::builder(LimitedAccept::new(incoming, limits))
+                .serve(app.into_make_service())
+                .with_graceful_shutdown(stop);
+            drain(server, stop_tx, in_flight, grace, shutdown).await
+        }
+        #[cfg(unix)]
+        crate::enclave::shared::server::Listener::Unix(listener) => {
+            let listener =
+                tokio::net::UnixListener::from_std(listener).with_context(|| "Failed to listen")?;
+            let server = axum::Server::builder(LimitedAccept::new(
+                crate::enclave::shared::server::UnixAccept(listener),
+                limits,
+            ))
+            .serve(app.into_make_service())
+            .with_graceful_shutdown(stop);
+            drain(server, stop_tx, in_flight, grace, shutdown).await
+        }
+    }
+}
+
+/// Runs `server` until `shutdown` resolves, then signals it through `stop_tx` to stop accepting
+/// and waits out the grace period
+async fn drain(
+    server: impl Future<Output = hyper::Result<()>>,
+    stop_tx: tokio::sync::oneshot::Sender<()>,
+    in_flight: InFlight,
+    grace: Duration,
+    shutdown: impl Future<Output = ()>,
+) -> Result<()> {
+    tokio::pin!(server);
+
+    tokio::select! {
+        res = &mut server => return res.with_context(|| "Server failed"),
+        _ = shutdown => {}
+    }
+
+    info!(
+        "Shutting down, waiting up to {:?} for {} in-flight signing requests",
+        grace,
+        in_flight.count()
+    );
+    stop_tx.send(()).ok();
+    let drained = tokio::time::timeout(grace, async {
+        let res = (&mut server).await;
+        in_flight.wait_idle().await;
+        res
+    })
+    .await;
+
+    match drained {
+        Ok(res) => {
+            info!("All in-flight requests finished");
+            res.with_context(|| "Server failed")
+        }
+        Err(_) => {
+            warn!(
+                "Shutdown grace period of {:?} elapsed with {} signing requests in flight",
+                grace,
+                in_flight.count()
+            );
+            Ok(())
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::{extract::State, routing::get, Router};
+
+    /// Serves a route that stays in flight for `delay`, returning the port, the server task
+    /// and the trigger to shut it down
+    fn slow_server(
+        in_flight: &InFlight,
+        delay: Duration,
+        grace: Duration,
+    ) -> (
+        u16,
+        tokio::task::JoinHandle<Result<()>>,
+        tokio::sync::oneshot::Sender<()>,
+    ) {
+        let app = Router::new()
+            .route(
+                "/slow",
+                get(|State(in_flight): State<InFlight>| async move {
+                    let _guard = in_flight.start();
+                    tokio::time::sleep(delay).await;
+                    "done"
+                }),
+            )
+            .with_state(in_flight.clone());
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let port = listener.local_addr().unwrap().port();
+        let (shutdown_tx, shutdown_rx) = tokio::sync::oneshot::channel::<()>();
+        let server = tokio::spawn(serve(
+            listener,
+            app,
+            in_flight.clone(),
+            grace,
+            Default::default(),
+            async {
+                shutdown_rx.await.ok();
+            },
+        ));
+        (port, server, shutdown_tx)
+    }
+
+    async fn wait_in_flight(in_flight: &InFlight) {
+        while in_flight.count() == 0 {
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+    }
+
+    #[tokio::test]
+    async fn test_in_flight_request_completes_during_shutdown() {
+        let in_flight = InFlight::default();
+        let (port, server, shutdown_tx) = slow_server(
+            &in_flight,
+            Duration::from_millis(300),
+            Duration::from_secs(5),
+        );
+        let req = tokio::spawn(reqwest::get(format!("http://127.0.0.1:{port}/slow")));
+
+        // Shut down while the request is being handled
+        wait_in_flight(&in_flight).await;
+        shutdown_tx.send(()).unwrap();
+
+        let resp = req.await.unwrap().unwrap();
+        assert_eq!(resp.status(), 200);
+        assert_eq!(resp.text().await.unwrap(), "done");
+        server.await.unwrap().unwrap();
+        assert_eq!(in_flight.count(), 0);
+
+        // No new connections are accepted
+        assert!(reqwest::get(format!("http://127.0.0.1:{port}/slow"))
+            .await
+            .is_err());
+    }
+
+    #[tokio::test]
+    async fn test_shutdown_is_bounded_by_grace_period() {
+        let in_flight = InFlight::default();
+        let (port, server, shutdown_tx) = slow_server(
+            &in_flight,
+            Duration::from_secs(60),
+            Duration::from_millis(100),
+        );
+        let _req = tokio::spawn(reqwest::get(format!("http://127.0.0.1:{port}/slow")));
+
+        wait_in_flight(&in_flight).await;
+        shutdown_tx.send(()).unwrap();
+        tokio::time::timeout(Duration::from_secs(5), server)
+            .await
+            .expect("shutdown should not wait for the stuck request")
+            .unwrap()
+            .unwrap();
+    }
+}
@@ -0,0 +1,99 @@
+use crate::io::config::Config;
+use axum::http::StatusCode;
+use axum::response::{IntoResponse, Response};
+use log::error;
+use std::time::Duration;
+
+#[cfg(not(feature = "sgx"))]
+thread_local! {
+    /// Delay before every timed signing started from this thread, see `inject_delay`
+    static INJECTED_DELAY: std::cell::Cell<Duration> = std::cell::Cell::new(Duration::ZERO);
+}
+
+/// Delays every timed signing started from this thread by `delay`, to exercise how callers
+/// handle a signing that outlives its timeout. Only available outside the enclave.
+#[cfg(not(feature = "sgx"))]
+pub fn inject_delay(delay: Duration) {
+    INJECTED_DELAY.with(|injected| injected.set(delay));
+}
+
+/// Runs `sign` on the blocking pool, answering 504 if it hasn't finished within `timeout` so the
+/// caller can still act before its duty deadline. A late `sign` is not cancelled, it runs to
+/// completion in the background holding whatever it captured, so its slashing protection DB
+/// write either completes or never starts. `sign` sees the caller's config even when it is scoped
+/// to the calling thread. Without a timeout `sign` runs inline.
+pub async fn run<T: Send + 'static>(
+    timeout: Option<Duration>,
+    sign: impl FnOnce() -> T + Send + 'static,
+) -> Result<T, Response> {
+    let Some(timeout) = timeout else {
+        return Ok(sign());
+    };
+    // The blocking pool doesn't see a config scoped to this thread, so carry it over
+    let config = Config::current();
+    #[cfg(not(feature = "sgx"))]
+    let delay = INJECTED_DELAY.with(|injected| injected.get());
+    #[cfg(feature = "sgx")]
+    let delay = Duration::ZERO;
+    let sign = move || {
+        let _config = config.scoped();
+        std::thread::sleep(delay);
+        sign()
+    };
+    match tokio::time::timeout(timeout, tokio::task::spawn_blocking(sign)).await {
+        Ok(Ok(out)) => Ok(out),
+        Ok(Err(e)) => {
+            error!("Signing task failed: {e}");
+            Err((StatusCode::INTERNAL_SERVER_ERROR, "Signing failed").into_response())
+        }
+        Err(_) => {
+            error!("Signing took longer than {timeout:?}");
+            Err((
+                StatusCode::GATEWAY_TIMEOUT,
+                format!("Signing took longer than {timeout:?}"),
+            )
+                .into_response())
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicBool, Ordering};
+    use std::sync::Arc;
+
+    #[tokio::test]
+    async fn test_slow_sign_times_out_but_completes() {
+        let done = Arc::new(AtomicBool::new(false));
+        let sign_done = done.clone();
+        let resp = run(Some(Duration::from_millis(10)), move || {
+            std::thread::sleep(Duration::from_millis(100));
+            sign_done.store(true, Ordering::SeqCst);
+        })
+        .await
+        .unwrap_err();
+        assert_eq!(resp.status(), StatusCode::GATEWAY_TIMEOUT);
+        assert!(!done.load(Ordering::SeqCst));
+
+        // The late sign isn't cut off halfway
+        tokio::time::sleep(Duration::from_millis(300)).await;
+        assert!(done.load(Ordering::SeqCst));
+    }
+
+    #[tokio::test]
+    async fn test_sign_sees_the_scoped_config() {
+        let _data_dir = Config::scoped_temp().unwrap();
+        let data_dir = Config::current().data_dir;
+        let seen = run(Some(Duration::from_secs(1)), || Config::current().data_dir)
+            .await
+            .unwrap();
+        assert_eq!(seen, data_dir);
+    }
+
+    #[tokio::test]
+    async fn test_fast_sign_passes_through() {
+        assert_eq!(run(Some(Duration::from_secs(1)), || 7).await.unwrap(), 7);
+        assert_eq!(run(None, || 7).await.unwrap(), 7);
+    }
+}
@@ -0,0 +1,155 @@
+use anyhow::{Context, Result};
+use axum::http::StatusCode;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use crate::io::config::{hex_file_path, Config};
+
+/// Default time between flushes of buffered signing stats to disk
+pub const DEFAULT_SIGN_STATS_FLUSH_INTERVAL: Duration = Duration::from_secs(10);
+
+/// Operational counters of a key, for spotting validators that stopped performing duties
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct KeyStats {
+    /// BLOCK and BLOCK_V2 requests signed
+    pub blocks_signed: u64,
+    /// ATTESTATION requests signed
+    pub attestations_signed: u64,
+    /// Unix time of the last request of any type signed
+    pub last_signed_at: Option<u64>,
+    /// Sign requests refused, by slashing protection or any other check
+    pub rejections: u64,
+}
+
+impl KeyStats {
+    fn merge(&mut self, other: &KeyStats) {
+        self.blocks_signed += other.blocks_signed;
+        self.attestations_signed += other.attestations_signed;
+        self.last_signed_at = self.last_signed_at.max(other.last_signed_at);
+        self.rejections += other.rejections;
+    }
+
+    /// Reads the persisted stats of `bls_pk_hex`, zeroed if none were flushed yet
+    fn read(bls_pk_hex: &str) -> Result<KeyStats> {
+        let path = hex_file_path(&Config::current().sign_stats_dir(), bls_pk_hex)?;
+        if !crate::io::key_management::file_exists(&path) {
+            return Ok(KeyStats::default());
+        }
+        let json = crate::io::key_management::read_file(&path)?;
+        serde_json::from_slice(&json).with_context(|| "Failed to read signing stats")
+    }
+
+    fn write(&self, bls_pk_hex: &str) -> Result<()> {
+        let path = hex_file_path(&Config::current().sign_stats_dir(), bls_pk_hex)?;
+        crate::io::key_management::write_atomic(&path, serde_json::to_string(self)?)
+            .with_context(|| "Failed to write signing stats")
+    }
+}
+
+/// Counts signing outcomes per key. Counts are buffered in memory so recording one costs a map
+/// update on the signing path, and are added to the per-key stats files by `flush`.
+#[derive(Clone, Debug, Default)]
+pub struct SignStats {
+    pending: Arc<Mutex<HashMap<String, KeyStats>>>,
+}
+
+impl SignStats {
+    /// Records the response to a `type_name` request for `bls_pk_hex`, given without 0x prefix
+    pub fn record(&self, bls_pk_hex: &str, type_name: &str, status: StatusCode) {
+        let mut pending = self.pending.lock().expect("sign stats lock poisoned");
+        let stats = pending.entry(bls_pk_hex.to_string()).or_default();
+        if !status.is_success() {
+            stats.rejections += 1;
+            return;
+        }
+        match type_name {
+            "BLOCK" | "BLOCK_V2" => stats.blocks_signed += 1,
+            "ATTESTATION" => stats.attestations_signed += 1,
+            _ => {}
+        }
+        stats.last_signed_at = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .ok()
+            .map(|t| t.as_secs());
+    }
+
+    /// Returns the stats of `bls_pk_hex`, including counts not flushed yet
+    pub fn get(&self, bls_pk_hex: &str) -> Result<KeyStats> {
+        let mut stats = KeyStats::read(bls_pk_hex)?;
+        let pending = self.pending.lock().expect("sign stats lock poisoned");
+        if let Some(pending) = pending.get(bls_pk_hex) {
+            stats.merge(pending);
+        }
+        Ok(stats)
+    }
+
+    /// Adds the buffered counts to the stats files. Counts that fail to be written are kept
+    /// for the next flush.
+    pub fn flush(&self) -> Result<()> {
+        let pending = std::mem::take(&mut *self.pending.lock().expect("sign stats lock poisoned"));
+        let mut failed = HashMap::new();
+        let mut first_error = None;
+        for (bls_pk_hex, delta) in pending {
+            let res = KeyStats::read(&bls_pk_hex).and_then(|mut stats| {
+                stats.merge(&delta);
+                stats.write(&bls_pk_hex)
+            });
+            if let Err(e) = res {
+                first_error.get_or_insert(e);
+                failed.insert(bls_pk_hex, delta);
+            }
+        }
+        if !failed.is_empty() {
+            let mut pending = self.pending.lock().expect("sign stats lock poisoned");
+            for (bls_pk_hex, delta) in failed {
+                pending.entry(bls_pk_hex).or_default().merge(&delta);
+            }
+        }
+        match first_error {
+            Some(e) => Err(e),
+            None => Ok(()),
+        }
+    }
+
+    /// Flushes every `interval` until the process exits
+    pub async fn flush_every(self, interval: Duration) {
+        let mut ticker = tokio::time::interval(interval);
+        loop {
+            ticker.tick().await;
+            if let Err(e) = self.flush() {
+                log::error!("Failed to flush signing stats: {:?}", e);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const PK: &str = "8349434ad0700e79be65c0c7043945df426bd6d7e288c16671df69d822344f1b0ce8de80360a50550ad782b68035cb18";
+
+    #[test]
+    fn test_flush_adds_to_persisted_stats() {
+        let _data_dir = Config::scoped_temp().unwrap();
+        let stats = SignStats::default();
+        stats.record(PK, "BLOCK_V2", StatusCode::OK);
+        stats.record(PK, "ATTESTATION", StatusCode::OK);
+        stats.record(PK, "BLOCK_V2", StatusCode::PRECONDITION_FAILED);
+        stats.flush().unwrap();
+        assert!(stats.pending.lock().unwrap().is_empty());
+
+        stats.record(PK, "BLOCK", StatusCode::OK);
+        let got = stats.get(PK).unwrap();
+        assert_eq!(got.blocks_signed, 2);
+        assert_eq!(got.attestations_signed, 1);
+        assert_eq!(got.rejections, 1);
+        assert!(got.last_signed_at.is_some());
+
+        // Survives a restart once flushed
+        stats.flush().unwrap();
+        assert_eq!(SignStats::default().get(PK).unwrap(), got);
+    }
+}
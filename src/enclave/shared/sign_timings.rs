@@ -0,0 +1,69 @@
+use std::time::{Duration, Instant};
+
+/// Response header carrying the per-phase durations of a signing request
+pub const SIGN_TIMINGS_HEADER: &str = "X-Sign-Timings";
+
+/// Records how long each phase of a signing request took. Does nothing unless enabled.
+#[derive(Debug)]
+pub struct SignTimings {
+    enabled: bool,
+    last: Instant,
+    phases: Vec<(&'static str, Duration)>,
+}
+
+impl SignTimings {
+    pub fn new(enabled: bool) -> Self {
+        SignTimings {
+            enabled,
+            last: Instant::now(),
+            phases: vec![],
+        }
+    }
+
+    /// Ends `phase`, which ran from the end of the previous phase until now
+    pub fn mark(&mut self, phase: &'static str) {
+        if !self.enabled {
+            return;
+        }
+        let now = Instant::now();
+        self.phases.push((phase, now - self.last));
+        self.last = now;
+    }
+
+    /// Formats the phases as `phase=<microseconds>us` pairs, `None` if disabled
+    pub fn header_value(&self) -> Option<String> {
+        if !self.enabled {
+            return None;
+        }
+        Some(
+            self.phases
+                .iter()
+                .map(|(phase, d)| format!("{phase}={}us", d.as_micros()))
+                .collect::<Vec<_>>()
+                .join(", "),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sign_timings() {
+        let mut timings = SignTimings::new(true);
+        timings.mark("slash_check");
+        timings.mark("bls_sign");
+        let value = timings.header_value().unwrap();
+        let phases: Vec<&str> = value
+            .split(", ")
+            .map(|p| p.split('=').next().unwrap())
+            .collect();
+        assert_eq!(phases, vec!["slash_check", "bls_sign"]);
+        assert!(value.ends_with("us"));
+
+        let mut timings = SignTimings::new(false);
+        timings.mark("bls_sign");
+        assert_eq!(timings.header_value(), None);
+    }
+}
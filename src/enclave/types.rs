@@ -1,4 +1,4 @@
-use crate::io::remote_attestation::AttestationEvidence;
+use crate::io::remote_attestation::{AttestationEvidence, Base64AttestationEvidence};
 use crate::{crypto::eth_keys, strip_0x_prefix};
 use anyhow::{bail, Result};
 use blsttc::{PublicKey as BlsPublicKey, PublicKeySet};
@@ -6,12 +6,19 @@ use ecies::{PublicKey as EthPublicKey, SecretKey as EthSecretKey};
 use ethers::types::TxHash;
 use serde::ser::SerializeSeq;
 use serde::{Deserialize, Serialize};
+use serde_hex::{SerHex, StrictPfx};
 use tree_hash::TreeHash;
 
 #[derive(Debug, Deserialize, Serialize, PartialEq, Eq)]
 pub struct KeyGenResponse {
     pub pk_hex: String,
     pub evidence: AttestationEvidence,
+    /// `evidence` with every field base64 encoded
+    #[serde(default)]
+    pub evidence_base64: Base64AttestationEvidence,
+    /// Hex signature of a BLS key over its own pubkey under `BLS_POP_DST`, unset for ETH keys
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub proof_of_possession: Option<String>,
 }
 
 impl KeyGenResponse {
@@ -19,14 +26,22 @@ impl KeyGenResponse {
         let pk: String = strip_0x_prefix!(hex::encode(pk.serialize())); // uncompressed
         KeyGenResponse {
             pk_hex: format!("0x{}", pk),
+            evidence_base64: evidence.to_base64(),
             evidence,
+            proof_of_possession: None,
         }
     }
 
-    pub fn from_bls_key(pk: BlsPublicKey, evidence: AttestationEvidence) -> Self {
+    pub fn from_bls_key(
+        pk: BlsPublicKey,
+        evidence: AttestationEvidence,
+        proof_of_possession: &[u8],
+    ) -> Self {
         KeyGenResponse {
             pk_hex: format!("0x{}", &pk.to_hex()),
+            evidence_base64: evidence.to_base64(),
             evidence,
+            proof_of_possession: Some(format!("0x{}", hex::encode(proof_of_possession))),
         }
     }
 
@@ -89,6 +104,75 @@ pub struct ListKeysResponseInner {
 #[derive(Debug, Deserialize, Serialize, PartialEq, Eq)]
 pub struct ListKeysResponse {
     pub data: Vec<ListKeysResponseInner>,
+    /// Number of keys across all pages, only set on paginated requests
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub total: Option<usize>,
+    /// Offset of the next page, unset on the last page
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub next_offset: Option<usize>,
+}
+
+/// A key as listed by the keymanager API's `GET /eth/v1/keystores`
+#[derive(Debug, Deserialize, Serialize, PartialEq, Eq)]
+pub struct KeystoreInfo {
+    pub validating_pubkey: String,
+    /// Keys enter the enclave without their derivation path, so it is never set
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub derivation_path: Option<String>,
+    /// Always set, no route modifies or deletes a key once it is in the enclave
+    pub readonly: bool,
+}
+
+#[derive(Debug, Deserialize, Serialize, PartialEq, Eq)]
+pub struct ListKeystoresResponse {
+    pub data: Vec<KeystoreInfo>,
+    /// Number of keys across all pages, only set on paginated requests
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub total: Option<usize>,
+    /// Offset of the next page, unset on the last page
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub next_offset: Option<usize>,
+}
+
+impl From<ListKeysResponse> for ListKeystoresResponse {
+    fn from(keys: ListKeysResponse) -> Self {
+        ListKeystoresResponse {
+            data: keys
+                .data
+                .into_iter()
+                .map(|k| KeystoreInfo {
+                    validating_pubkey: k.pubkey,
+                    derivation_path: None,
+                    readonly: true,
+                })
+                .collect(),
+            total: keys.total,
+            next_offset: keys.next_offset,
+        }
+    }
+}
+
+/// A key held by a remote signer, as listed by the keymanager API's `GET /eth/v1/remotekeys`
+#[derive(Debug, Deserialize, Serialize, PartialEq, Eq)]
+pub struct RemoteKeyInfo {
+    pub pubkey: String,
+    pub url: String,
+    pub readonly: bool,
+}
+
+#[derive(Debug, Deserialize, Serialize, PartialEq, Eq)]
+pub struct ListRemoteKeysResponse {
+    pub data: Vec<RemoteKeyInfo>,
+}
+
+/// Query parameters of the key listing endpoints. Keys are ordered by pubkey, and all keys are
+/// returned if neither `limit` nor `offset` is set.
+#[derive(Deserialize, Serialize, Debug, Default, Clone, Copy)]
+pub struct ListKeysQuery {
+    pub limit: Option<usize>,
+    pub offset: Option<usize>,
+    /// Only list BLS keys that entered the enclave this way, keys of unknown origin never match
+    pub origin: Option<crate::io::key_management::KeyOrigin>,
 }
 
 impl ListKeysResponse {
@@ -107,27 +191,561 @@ impl ListKeysResponse {
             })
             .collect();
 
-        ListKeysResponse { data: inners }
+        ListKeysResponse {
+            data: inners,
+            total: None,
+            next_offset: None,
+        }
+    }
+
+    /// Returns the page of `keys` selected by `query`, `keys` must already be sorted
+    pub fn page(keys: Vec<String>, query: ListKeysQuery) -> ListKeysResponse {
+        if query.limit.is_none() && query.offset.is_none() {
+            return ListKeysResponse::new(keys);
+        }
+        let total = keys.len();
+        let start = query.offset.unwrap_or(0).min(total);
+        let end = match query.limit {
+            Some(limit) => start.saturating_add(limit).min(total),
+            None => total,
+        };
+        let mut resp = ListKeysResponse::new(keys[start..end].to_vec());
+        resp.total = Some(total);
+        resp.next_offset = if end < total { Some(end) } else { None };
+        resp
+    }
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+pub struct SlashProtectionImportResponse {
+    pub data: Vec<crate::eth2::slash_protection::SlashingProtectionImportResult>,
+}
+
+/// Names an EIP-3076 interchange file, absolute or relative to the signer's import dir
+#[derive(Deserialize, Serialize, Debug)]
+pub struct SlashProtectionImportFileRequest {
+    pub path: String,
+}
+
+#[derive(Deserialize, Serialize, Debug)]
+pub struct BlsKeyExportRequest {
+    /// Compressed SECP256K1 public key the BLS secret key is ECIES-encrypted to
+    pub client_pubkey: String,
+    /// Written to the slashing protection interchange metadata, which the importing signer checks
+    /// against its own chain
+    #[serde(with = "SerHex::<StrictPfx>")]
+    pub genesis_validators_root: crate::eth2::eth_types::Root,
+}
+
+#[derive(Deserialize, Serialize, Debug)]
+pub struct BlsKeyExportResponse {
+    pub pk_hex: String,
+    pub encrypted_sk: String,
+    /// EIP-3076 interchange with the key's full signing history, to import alongside the key
+    pub slashing_protection: crate::eth2::slash_protection::SlashingProtectionDB,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum KeystoreImportStatus {
+    Imported,
+    /// The key is already saved in the enclave and was left untouched
+    Duplicate,
+    Error,
+}
+
+/// Outcome of importing one keystore, shaped as in the keymanager API's `POST /eth/v1/keystores`
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq, Eq)]
+pub struct KeystoreImportOutcome {
+    pub status: KeystoreImportStatus,
+    /// Why the import failed, unset otherwise
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub message: Option<String>,
+}
+
+#[derive(Deserialize, Serialize, Debug)]
+pub struct KeystoreImportResult {
+    /// Name of the keystore file inside the archive
+    pub file: String,
+    pub pubkey: Option<String>,
+    pub status: KeystoreImportStatus,
+    /// Why the import failed, unset otherwise
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub message: Option<String>,
+}
+
+#[derive(Deserialize, Serialize, Debug)]
+pub struct KeyImportRequest {
+    /// EIP-2335 keystore JSON
+    pub keystore: String,
+    /// Keystore password ECIES-encrypted to `encrypting_pk_hex`
+    pub ct_password_hex: String,
+    /// Compressed SECP256K1 public key of the enclave ETH key the password is encrypted to
+    pub encrypting_pk_hex: String,
+    /// Secret authenticating the key's sign requests, ECIES-encrypted to `encrypting_pk_hex`
+    #[serde(default)]
+    pub ct_mac_secret_hex: Option<String>,
+    /// EIP-3076 interchange to import alongside the key
+    #[serde(default)]
+    pub slashing_protection: Option<String>,
+    /// Fee recipients the key may sign validator registrations for, any if unset
+    #[serde(default)]
+    pub fee_recipients: Option<Vec<String>>,
+    /// Request types the key may sign, e.g. `["ATTESTATION"]`, or `RAW` for raw roots, any if
+    /// unset
+    #[serde(default)]
+    pub allowed_types: Option<Vec<String>>,
+    /// Validator index the key may propose blocks as, any if unset
+    #[serde(default)]
+    pub proposer_index: Option<u64>,
+}
+
+#[derive(Deserialize, Serialize, Debug)]
+pub struct KeyImportResponse {
+    /// The keystore's outcome as the keymanager API reports it, always a single entry
+    pub data: Vec<KeystoreImportOutcome>,
+    pub pubkey: String,
+    /// Outcome of the request's slashing protection interchange, empty if it had none
+    pub slashing_protection: Vec<crate::eth2::slash_protection::SlashingProtectionImportResult>,
+}
+
+/// Query parameters of the keystore archive import endpoint
+#[derive(Deserialize, Serialize, Debug)]
+pub struct KeystoreArchiveImportQuery {
+    /// Compressed SECP256K1 public key of the enclave ETH key the archive's passwords and MAC
+    /// secrets are encrypted to
+    pub encrypting_pk_hex: String,
+}
+
+#[derive(Deserialize, Serialize, Debug)]
+pub struct KeystoreArchiveImportResponse {
+    pub data: Vec<KeystoreImportResult>,
+    /// Outcome of the archive's slashing protection interchange, empty if it had none
+    pub slashing_protection: Vec<crate::eth2::slash_protection::SlashingProtectionImportResult>,
+}
+
+#[derive(Deserialize, Serialize, Debug)]
+pub struct MetricsLabelRequest {
+    pub label: String,
+}
+
+#[derive(Deserialize, Serialize, Debug)]
+pub struct MetricsLabelResponse {
+    pub pubkey: String,
+    pub label: String,
+}
+
+#[derive(Deserialize, Serialize, Debug)]
+pub struct SessionRequest {
+    #[serde(with = "SerHex::<StrictPfx>")]
+    pub genesis_validators_root: crate::eth2::eth_types::Root,
+}
+
+#[derive(Deserialize, Serialize, Debug)]
+pub struct SessionResponse {
+    pub session_id: String,
+}
+
+#[derive(Deserialize, Serialize, Debug, Clone)]
+pub struct BatchVerifyItem {
+    pub pubkey: String,
+    pub signing_root: String,
+    pub signature: String,
+}
+
+#[derive(Deserialize, Serialize, Debug)]
+pub struct BatchVerifyResult {
+    pub pubkey: String,
+    pub valid: bool,
+}
+
+#[derive(Deserialize, Serialize, Debug)]
+pub struct BatchVerifyResponse {
+    pub data: Vec<BatchVerifyResult>,
+    /// True if all items were accepted by a single batch verification
+    pub batch_verified: bool,
+}
+
+/// Outcome of one item of a signing batch, carrying the status it would have had on its own
+#[derive(Deserialize, Serialize, Debug)]
+pub struct BatchSignResult {
+    /// The key the item was signed with, as given in the item
+    pub pubkey: String,
+    pub status: u16,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub signature: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+#[derive(Deserialize, Serialize, Debug)]
+pub struct BatchSignResponse {
+    /// One result per item, in request order
+    pub data: Vec<BatchSignResult>,
+}
+
+#[derive(Deserialize, Serialize, Debug, Clone)]
+pub struct VerifyRequest {
+    pub pubkey: String,
+    pub signing_root: String,
+    pub signature: String,
+}
+
+#[derive(Deserialize, Serialize, Debug)]
+pub struct VerifyResponse {
+    pub valid: bool,
+}
+
+/// Signatures over the same message to be aggregated into one
+#[derive(Deserialize, Serialize, Debug, Clone)]
+pub struct AggregateSignaturesRequest {
+    pub signatures: Vec<String>,
+}
+
+/// Query parameters of the endpoints that attest to freshly generated keys
+#[derive(Deserialize, Serialize, Debug, Default, Clone)]
+pub struct RemoteAttestationRequest {
+    /// Required signature type of the EPID quote, any type is accepted if unset
+    #[serde(default)]
+    pub epid_signature_type: Option<crate::io::remote_attestation::EpidSignatureType>,
+    /// Hex encoded 32 byte challenge the quote's report data commits to, proving it is fresh
+    #[serde(default)]
+    pub nonce: Option<String>,
+    /// Comma separated request types a generated BLS key may sign, or `RAW` for raw roots, any if
+    /// unset
+    #[serde(default)]
+    pub allowed_types: Option<String>,
+    /// Validator index a generated BLS key may propose blocks as, any if unset
+    #[serde(default)]
+    pub proposer_index: Option<u64>,
+    /// Secret authenticating a generated BLS key's sign requests, ECIES-encrypted to
+    /// `encrypting_pk_hex`
+    #[serde(default)]
+    pub ct_mac_secret_hex: Option<String>,
+    /// Compressed SECP256K1 public key of the enclave ETH key `ct_mac_secret_hex` is encrypted to
+    #[serde(default)]
+    pub encrypting_pk_hex: Option<String>,
+}
+
+impl RemoteAttestationRequest {
+    /// Decrypts `ct_mac_secret_hex` with the enclave ETH key `encrypting_pk_hex`, if given
+    pub fn mac_secret(&self) -> Result<Option<Vec<u8>>> {
+        let Some(ct_mac_secret_hex) = &self.ct_mac_secret_hex else {
+            return Ok(None);
+        };
+        let Some(encrypting_pk_hex) = &self.encrypting_pk_hex else {
+            bail!("ct_mac_secret_hex is set without encrypting_pk_hex")
+        };
+        if !crate::io::key_management::eth_key_exists(encrypting_pk_hex) {
+            bail!("encryption public key does not match any enclave key")
+        }
+        let envelope_sk = crate::crypto::eth_keys::fetch_eth_key(encrypting_pk_hex)?;
+        crate::enclave::secure_signer::decrypt_mac_secret(&envelope_sk, ct_mac_secret_hex).map(Some)
+    }
+
+    pub fn nonce(
+        &self,
+    ) -> Result<Option<[u8; crate::io::remote_attestation::ATTESTATION_NONCE_BYTES]>> {
+        let Some(nonce) = &self.nonce else {
+            return Ok(None);
+        };
+        let nonce_hex: &str = strip_0x_prefix!(nonce);
+        let nonce = hex::decode(nonce_hex)?;
+        match nonce.try_into() {
+            Ok(nonce) => Ok(Some(nonce)),
+            Err(nonce) => bail!("Expected a 32 byte nonce, got {}", nonce.len()),
+        }
+    }
+
+    pub fn allowed_types(&self) -> Result<Option<Vec<String>>> {
+        self.allowed_types
+            .as_deref()
+            .map(|types| {
+                let types: Vec<String> = types.split(',').map(str::to_string).collect();
+                crate::io::key_management::parse_sign_type_allowlist(&types)
+            })
+            .transpose()
+    }
+}
+
+/// Body of the raw signing endpoint
+#[derive(Deserialize, Serialize, Debug)]
+pub struct RawSignRequest {
+    /// Hex encoded 32 byte root to sign as is
+    #[serde(rename = "signingRoot")]
+    pub signing_root: String,
+}
+
+impl RawSignRequest {
+    pub fn signing_root(&self) -> Result<crate::eth2::eth_types::Root> {
+        let root_hex: &str = strip_0x_prefix!(self.signing_root);
+        let root = hex::decode(root_hex)?;
+        match root.try_into() {
+            Ok(root) => Ok(root),
+            Err(root) => bail!("Expected 32 bytes, got {}", root.len()),
+        }
+    }
+}
+
+/// Query parameters of the sign endpoint
+#[derive(Deserialize, Serialize, Debug, Default, Clone)]
+pub struct SignQuery {
+    /// Also return the signing root the signature covers
+    #[serde(default)]
+    pub include_signing_root: bool,
+    /// Return the time spent in each signing phase in the `X-Sign-Timings` header
+    #[serde(default)]
+    pub timings: bool,
+    /// Only check whether the request would be signed, without signing or recording it
+    #[serde(default, rename = "dryRun")]
+    pub dry_run: bool,
+    /// Encoding of the returned signature
+    #[serde(default, rename = "signatureFormat")]
+    pub signature_format: SignatureFormat,
+}
+
+/// How signing responses encode the signature
+#[derive(Deserialize, Serialize, Debug, Default, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum SignatureFormat {
+    /// `0x` prefixed hex string
+    #[default]
+    Hex,
+    /// Padded standard base64 string
+    Base64,
+    /// JSON array of the signature's bytes
+    Bytes,
+}
+
+impl SignatureFormat {
+    pub fn encode(&self, bytes: &[u8]) -> serde_json::Value {
+        match self {
+            SignatureFormat::Hex => format!("0x{}", hex::encode(bytes)).into(),
+            SignatureFormat::Base64 => openssl::base64::encode_block(bytes).into(),
+            SignatureFormat::Bytes => bytes.to_vec().into(),
+        }
+    }
+
+    pub fn decode(&self, value: &serde_json::Value) -> Result<Vec<u8>> {
+        match (self, value) {
+            (SignatureFormat::Hex, serde_json::Value::String(s)) => {
+                let s: String = strip_0x_prefix!(s);
+                Ok(hex::decode(s)?)
+            }
+            (SignatureFormat::Base64, serde_json::Value::String(s)) => {
+                Ok(openssl::base64::decode_block(s)?)
+            }
+            (SignatureFormat::Bytes, value @ serde_json::Value::Array(_)) => {
+                Ok(serde_json::from_value(value.clone())?)
+            }
+            _ => bail!("Signature is not {self:?} encoded: {value}"),
+        }
+    }
+}
+
+/// Keys the reseal endpoint moved to the configured sealing policy, and those it couldn't unseal
+#[derive(Deserialize, Serialize, Debug, Default, Clone, PartialEq, Eq)]
+pub struct ResealResponse {
+    pub resealed: Vec<String>,
+    pub failed: Vec<String>,
+}
+
+/// Keys whose slashing protection history the prune endpoint collapsed to its high-water marks,
+/// and those it couldn't prune
+#[derive(Deserialize, Serialize, Debug, Default, Clone, PartialEq, Eq)]
+pub struct SlashProtectionPruneResponse {
+    pub pruned: Vec<String>,
+    pub failed: Vec<String>,
+    /// Signed blocks and attestations dropped across all keys
+    pub entries_removed: usize,
+}
+
+/// Build and configuration of a running signer, to tell instances apart during rollouts
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq, Eq)]
+pub struct VersionResponse {
+    pub version: String,
+    /// Commit the binary was built from, `unknown` if built outside a git checkout
+    pub git_commit: String,
+    /// `HW` in a hardware enclave, `SIM` otherwise
+    pub enclave_mode: String,
+    pub attestation_mode: String,
+    /// Cargo features the binary was built with
+    pub features: Vec<String>,
+}
+
+impl VersionResponse {
+    pub fn current() -> Self {
+        let features = [
+            ("sgx", cfg!(feature = "sgx")),
+            ("sqlite", cfg!(feature = "sqlite")),
+            ("clap", cfg!(feature = "clap")),
+        ];
+        VersionResponse {
+            version: env!("CARGO_PKG_VERSION").to_string(),
+            git_commit: env!("GIT_COMMIT").to_string(),
+            enclave_mode: crate::io::remote_attestation::ENCLAVE_MODE.to_string(),
+            attestation_mode: crate::io::remote_attestation::ATTESTATION_MODE.to_string(),
+            features: features
+                .into_iter()
+                .filter(|(_, enabled)| *enabled)
+                .map(|(name, _)| name.to_string())
+                .collect(),
+        }
+    }
+}
+
+/// Effective configuration of a running signer, without its secrets, to catch drift across a fleet
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq, Eq)]
+pub struct ConfigResponse {
+    pub version: VersionResponse,
+    pub data_dir: String,
+    pub storage: String,
+    pub slashing_protection_backend: String,
+    pub sealing_policy: String,
+    pub genesis_fork_version: String,
+    pub genesis_time: u64,
+    /// Fork versions and their activation epochs, empty if fork_info is not checked against them
+    pub fork_schedule: Vec<(String, u64)>,
+    pub allowed_fork_versions: Vec<String>,
+    pub allowed_genesis_validators_roots: Vec<String>,
+    pub allow_key_export: bool,
+    pub allow_raw_signing: bool,
+    pub allow_keystore_archive_import: bool,
+    pub import_only: bool,
+    pub require_import_slashing_protection: bool,
+    pub require_request_mac: bool,
+    pub deny_unknown_fields: bool,
+    pub validate_subcommittee_index: bool,
+    pub bind_genesis_root: bool,
+    pub monotonic_registration_timestamps: bool,
+    /// Window in which request ids may not be reused, unset if request ids aren't required
+    pub request_id_ttl_secs: Option<u64>,
+    /// Window in which exact retries are answered from the response cache, unset if disabled
+    pub sign_response_cache_secs: Option<u64>,
+    pub sign_response_cache_size: Option<usize>,
+    pub sessions_enabled: bool,
+    pub min_gas_limit: u64,
+    pub max_gas_limit: u64,
+    pub min_exit_epoch_distance: Option<u64>,
+    pub slash_protection_floor_margin: u64,
+    pub slash_protection_import_dir: Option<String>,
+    pub sign_timeout_ms: Option<u64>,
+    pub signing_concurrency: Option<usize>,
+    pub signing_queue_max_depth: Option<usize>,
+    pub signing_queue_max_wait_ms: Option<u64>,
+    pub max_keys: Option<usize>,
+    /// Labels keys may be tagged with, empty if metrics labels are disabled
+    pub metrics_labels: Vec<String>,
+}
+
+impl ConfigResponse {
+    pub fn new(state: &crate::enclave::shared::handlers::AppState) -> Self {
+        let config = crate::io::config::Config::current();
+        let storage = match config.storage.is_memory() {
+            true => "memory",
+            false => "disk",
+        };
+        let slashing_protection_backend = match config.slashing_protection_backend {
+            crate::io::config::SlashingProtectionBackend::Json => "json",
+            crate::io::config::SlashingProtectionBackend::Sqlite => "sqlite",
+        };
+        let sealing_policy = match config.sealing_policy {
+            crate::io::config::SealingPolicy::MrEnclave => "mrenclave",
+            crate::io::config::SealingPolicy::MrSigner => "mrsigner",
+        };
+        let queue = state.signing_queue.as_ref();
+        ConfigResponse {
+            version: VersionResponse::current(),
+            data_dir: config.data_dir.display().to_string(),
+            storage: storage.to_string(),
+            slashing_protection_backend: slashing_protection_backend.to_string(),
+            sealing_policy: sealing_policy.to_string(),
+            genesis_fork_version: format!("0x{}", hex::encode(state.genesis_fork_version)),
+            genesis_time: state.genesis_time,
+            fork_schedule: state
+                .fork_schedule
+                .forks
+                .iter()
+                .map(|(version, epoch)| (format!("0x{}", hex::encode(version)), *epoch))
+                .collect(),
+            allowed_fork_versions: state
+                .allowed_domains
+                .fork_versions
+                .iter()
+                .map(|v| format!("0x{}", hex::encode(v)))
+                .collect(),
+            allowed_genesis_validators_roots: state
+                .allowed_domains
+                .genesis_validators_roots
+                .iter()
+                .map(|r| format!("0x{}", hex::encode(r)))
+                .collect(),
+            allow_key_export: state.allow_key_export,
+            allow_raw_signing: state.allow_raw_signing,
+            allow_keystore_archive_import: state.allow_keystore_archive_import,
+            import_only: state.import_only,
+            require_import_slashing_protection: state.require_import_slashing_protection,
+            require_request_mac: state.require_request_mac,
+            deny_unknown_fields: state.deny_unknown_fields,
+            validate_subcommittee_index: state.validate_subcommittee_index,
+            bind_genesis_root: state.bind_genesis_root,
+            monotonic_registration_timestamps: state.monotonic_registration_timestamps,
+            request_id_ttl_secs: state.request_ids.as_ref().map(|c| c.ttl().as_secs()),
+            sign_response_cache_secs: state.response_cache.as_ref().map(|c| c.window().as_secs()),
+            sign_response_cache_size: state.response_cache.as_ref().map(|c| c.capacity()),
+            sessions_enabled: state.sessions.is_some(),
+            min_gas_limit: state.gas_limit_bounds.min,
+            max_gas_limit: state.gas_limit_bounds.max,
+            min_exit_epoch_distance: state.min_exit_epoch_distance,
+            slash_protection_floor_margin: state.slash_protection_floor_margin,
+            slash_protection_import_dir: state
+                .slash_protection_import_dir
+                .as_ref()
+                .map(|d| d.display().to_string()),
+            sign_timeout_ms: state.sign_timeout.map(|t| t.as_millis() as u64),
+            signing_concurrency: queue.map(|q| q.concurrency()),
+            signing_queue_max_depth: queue.map(|q| q.max_depth()),
+            signing_queue_max_wait_ms: queue.map(|q| q.max_wait().as_millis() as u64),
+            max_keys: state.key_limit.as_ref().map(|l| l.max_keys()),
+            metrics_labels: state.metrics_labels.labels(),
+        }
     }
 }
 
 #[derive(Deserialize, Serialize, Debug)]
 pub struct SignatureResponse {
     pub signature: String,
+    /// Only set if requested through `SignQuery::include_signing_root`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub signing_root: Option<String>,
 }
 
 impl SignatureResponse {
     pub fn new(sig: &[u8]) -> Self {
         SignatureResponse {
             signature: format!("0x{}", hex::encode(sig)),
+            signing_root: None,
         }
     }
 
+    pub fn with_signing_root(mut self, signing_root: &crate::eth2::eth_types::Root) -> Self {
+        self.signing_root = Some(format!("0x{}", hex::encode(signing_root)));
+        self
+    }
+
     pub fn to_ssz_bytes(&self) -> Result<crate::eth2::eth_types::BLSSignature> {
         let sig_stripped: String = strip_0x_prefix!(self.signature.clone());
         let sig_bytes = hex::decode(sig_stripped)?;
         Ok(crate::eth2::eth_types::BLSSignature::from(sig_bytes))
     }
+
+    /// The response as JSON with its signature encoded in `format`
+    pub fn to_json(&self, format: SignatureFormat) -> Result<serde_json::Value> {
+        let mut json = serde_json::to_value(self)?;
+        let sig = SignatureFormat::Hex.decode(&json["signature"])?;
+        json["signature"] = format.encode(&sig);
+        Ok(json)
+    }
 }
 
 #[derive(serde::Serialize, serde::Deserialize, Debug)]
@@ -279,6 +897,8 @@ pub struct SignExitRequest {
     pub guardian_index: u64,
     pub validator_index: u64,
     pub fork_info: crate::eth2::eth_types::ForkInfo,
+    /// Genesis fork version of the exit's network, whose fork schedule picks the exit's domain
+    pub genesis_fork_version: crate::eth2::eth_types::Version,
 }
 
 impl SignExitRequest {
@@ -1,10 +1,15 @@
-use axum::{response::IntoResponse, Json};
+use axum::{extract::State, response::IntoResponse, Json};
 use log::{error, info};
 
 pub async fn handler(
+    State(state): State<crate::enclave::shared::handlers::AppState>,
     Json(keygen_payload): Json<crate::enclave::types::AttestFreshBlsKeyPayload>,
 ) -> axum::response::Response {
     info!("attest_fresh_bls_key()");
+    let slot = match crate::enclave::shared::key_limit::reserve(state.key_limit.as_ref()) {
+        Ok(slot) => slot,
+        Err(response) => return response,
+    };
     match crate::enclave::validator::attest_fresh_bls_key(
         keygen_payload.withdrawal_credentials,
         keygen_payload.guardian_pubkeys,
@@ -13,11 +18,14 @@ pub async fn handler(
         keygen_payload.do_remote_attestation,
     ) {
         Ok(keygen_result) => {
+            if let Some(slot) = slot {
+                slot.saved();
+            }
             (axum::http::status::StatusCode::CREATED, Json(keygen_result)).into_response()
         }
         Err(e) => {
             error!("attest_fresh_bls_key() failed with: {:?}", e);
-            axum::http::status::StatusCode::INTERNAL_SERVER_ERROR.into_response()
+            crate::enclave::shared::internal_error(&e, String::new())
         }
     }
 }
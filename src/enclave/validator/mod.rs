@@ -80,6 +80,10 @@ pub fn attest_fresh_bls_key(
 
     // save validator private key to enclave
     save_bls_key(&secret_key_set)?;
+    crate::io::key_management::write_key_origin(
+        &validator_pubkey.to_hex(),
+        crate::io::key_management::KeyOrigin::Generated,
+    )?;
 
     // Create a new slashing protection database
     crate::eth2::slash_protection::SlashingProtectionData::from_pk_hex(&validator_pubkey.to_hex())?
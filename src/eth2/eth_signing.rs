@@ -1,7 +1,7 @@
 use super::eth_types::*;
 use crate::crypto::bls_keys;
 
-use anyhow::Result;
+use anyhow::{bail, Result};
 use blsttc::SecretKeySet;
 use log::info;
 use serde::{Deserialize, Serialize};
@@ -34,6 +34,15 @@ pub fn compute_epoch_at_slot(slot: Slot) -> Epoch {
     slot / SLOTS_PER_EPOCH
 }
 
+/// Return the epoch of the wall clock time, for a chain that started at ``genesis_time`` (unix seconds).
+pub fn compute_current_epoch(genesis_time: u64) -> Epoch {
+    let now = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or_default();
+    compute_epoch_at_slot(now.saturating_sub(genesis_time) / SECONDS_PER_SLOT)
+}
+
 /// Return the signature domain (fork version concatenated with domain type) of a message.
 /// https://github.com/ethereum/consensus-specs/blob/dev/specs/phase0/beacon-chain.md#get_domain
 /// Modified to adhere to https://consensys.github.io/web3signer/web3signer-eth2.html#tag/Signing
@@ -43,11 +52,7 @@ pub fn get_domain(fork_info: ForkInfo, domain_type: DomainType, epoch: Option<Ep
         None => fork_info.fork.epoch,
     };
 
-    let fork_version = if epoch < fork_info.fork.epoch {
-        fork_info.fork.previous_version
-    } else {
-        fork_info.fork.current_version
-    };
+    let fork_version = fork_version_at(&fork_info, epoch);
     compute_domain(
         domain_type,
         Some(fork_version),
@@ -55,15 +60,24 @@ pub fn get_domain(fork_info: ForkInfo, domain_type: DomainType, epoch: Option<Ep
     )
 }
 
-/// Return the domain for the ``domain_type`` and ``fork_version``.
+/// The version of the fork `fork_info` is in at `epoch`, the one its domain is computed with
+fn fork_version_at(fork_info: &ForkInfo, epoch: Epoch) -> Version {
+    if epoch < fork_info.fork.epoch {
+        fork_info.fork.previous_version
+    } else {
+        fork_info.fork.current_version
+    }
+}
+
+/// Return the domain for the ``domain_type`` and ``fork_version``. Defaults to the genesis fork
+/// version and `ZERO_GENESIS_VALIDATORS_ROOT`, as the spec does.
 pub fn compute_domain(
     domain_type: DomainType,
     fork_version: Option<Version>,
     genesis_validators_root: Option<Root>,
 ) -> Domain {
     let fv = fork_version.unwrap_or(GENESIS_FORK_VERSION);
-    // let gvr = genesis_validators_root.unwrap_or([0_u8; 32]);
-    let gvr = genesis_validators_root.unwrap_or(Root::default());
+    let gvr = genesis_validators_root.unwrap_or(ZERO_GENESIS_VALIDATORS_ROOT);
     let fork_data_root = compute_fork_data_root(fv, gvr);
     let mut d = [0_u8; 32]; // domain_type + fork_data_root[:28]
     domain_type.iter().enumerate().for_each(|(i, v)| d[i] = *v);
@@ -156,6 +170,7 @@ pub enum BLSSignMsg {
     ATTESTATION(AttestationRequest),
     RANDAO_REVEAL(RandaoRevealRequest),
     AGGREGATE_AND_PROOF(AggregateAndProofRequest),
+    AGGREGATE_AND_PROOF_V2(AggregateAndProofV2Request),
     AGGREGATION_SLOT(AggregationSlotRequest),
     DEPOSIT(DepositRequest),
     VOLUNTARY_EXIT(VoluntaryExitRequest),
@@ -163,6 +178,7 @@ pub enum BLSSignMsg {
     SYNC_COMMITTEE_SELECTION_PROOF(SyncCommitteeSelectionProofRequest),
     SYNC_COMMITTEE_CONTRIBUTION_AND_PROOF(SyncCommitteeContributionAndProofRequest),
     VALIDATOR_REGISTRATION(ValidatorRegistrationRequest),
+    BLS_TO_EXECUTION_CHANGE(BlsToExecutionChangeRequest),
 
     // lower case
     block(BlockRequest),
@@ -170,6 +186,7 @@ pub enum BLSSignMsg {
     attestation(AttestationRequest),
     randao_reveal(RandaoRevealRequest),
     aggregate_and_proof(AggregateAndProofRequest),
+    aggregate_and_proof_v2(AggregateAndProofV2Request),
     aggregation_slot(AggregationSlotRequest),
     deposit(DepositRequest),
     voluntary_exit(VoluntaryExitRequest),
@@ -177,10 +194,125 @@ pub enum BLSSignMsg {
     sync_committee_selection_proof(SyncCommitteeSelectionProofRequest),
     sync_committee_contribution_and_proof(SyncCommitteeContributionAndProofRequest),
     validator_registration(ValidatorRegistrationRequest),
+    bls_to_execution_change(BlsToExecutionChangeRequest),
+}
+
+/// Returns the paths of fields in `input` that have no counterpart in `known`. Null and empty
+/// fields are treated as absent, as an empty `signingRoot` is.
+fn unknown_fields(input: &serde_json::Value, known: &serde_json::Value, path: &str) -> Vec<String> {
+    use serde_json::Value;
+    match (input, known) {
+        (Value::Object(input), Value::Object(known)) => input
+            .iter()
+            .flat_map(|(k, v)| {
+                let path = format!("{path}.{k}");
+                match known.get(k) {
+                    Some(known) => unknown_fields(v, known, &path),
+                    None if v.is_null() || v == "" => vec![],
+                    None => vec![path],
+                }
+            })
+            .collect(),
+        (Value::Array(input), Value::Array(known)) => input
+            .iter()
+            .zip(known)
+            .enumerate()
+            .flat_map(|(i, (v, known))| unknown_fields(v, known, &format!("{path}[{i}]")))
+            .collect(),
+        _ => vec![],
+    }
 }
 
 impl BLSSignMsg {
-    pub fn can_be_slashed(&self) -> bool {
+    /// Parses a JSON request like `serde_json::from_slice`, but refuses fields its type doesn't
+    /// define instead of ignoring them. Serde's `deny_unknown_fields` can't be toggled at runtime,
+    /// so the request's own fields are found by serializing it back.
+    pub fn from_json_strict(body: &[u8]) -> Result<BLSSignMsg> {
+        let input: serde_json::Value = serde_json::from_slice(body)?;
+        let req: BLSSignMsg = serde_json::from_slice(body)?;
+        let mut known = serde_json::to_value(&req)?;
+
+        // A full BLOCK_V2 block is reduced to its header, so check it against its own schema
+        if let (Some(block), Some(version)) = (
+            input.pointer("/beacon_block/block"),
+            input
+                .pointer("/beacon_block/version")
+                .and_then(|v| v.as_str()),
+        ) {
+            let block = match version.to_uppercase().as_str() {
+                "CAPELLA" => serde_json::to_value(serde_json::from_value::<BeaconBlockCapella>(
+                    block.clone(),
+                )?)?,
+                "DENEB" => serde_json::to_value(serde_json::from_value::<BeaconBlockDeneb>(
+                    block.clone(),
+                )?)?,
+                v => bail!("Full block not supported for version {v}"),
+            };
+            known["beacon_block"]["block"] = block;
+        }
+
+        let unknown = unknown_fields(&input, &known, "");
+        if !unknown.is_empty() {
+            bail!("Unknown fields {}", unknown.join(", "))
+        }
+        Ok(req)
+    }
+
+    /// The request types, as named in the Web3Signer API
+    pub const TYPE_NAMES: [&'static str; 14] = [
+        "BLOCK",
+        "BLOCK_V2",
+        "ATTESTATION",
+        "RANDAO_REVEAL",
+        "AGGREGATE_AND_PROOF",
+        "AGGREGATE_AND_PROOF_V2",
+        "AGGREGATION_SLOT",
+        "DEPOSIT",
+        "VOLUNTARY_EXIT",
+        "SYNC_COMMITTEE_MESSAGE",
+        "SYNC_COMMITTEE_SELECTION_PROOF",
+        "SYNC_COMMITTEE_CONTRIBUTION_AND_PROOF",
+        "VALIDATOR_REGISTRATION",
+        "BLS_TO_EXECUTION_CHANGE",
+    ];
+
+    /// Returns the request's type as named in the Web3Signer API, whichever case it was sent in
+    pub fn type_name(&self) -> &'static str {
+        match self {
+            BLSSignMsg::BLOCK(_) | BLSSignMsg::block(_) => "BLOCK",
+            BLSSignMsg::BLOCK_V2(_) | BLSSignMsg::block_v2(_) => "BLOCK_V2",
+            BLSSignMsg::ATTESTATION(_) | BLSSignMsg::attestation(_) => "ATTESTATION",
+            BLSSignMsg::RANDAO_REVEAL(_) | BLSSignMsg::randao_reveal(_) => "RANDAO_REVEAL",
+            BLSSignMsg::AGGREGATE_AND_PROOF(_) | BLSSignMsg::aggregate_and_proof(_) => {
+                "AGGREGATE_AND_PROOF"
+            }
+            BLSSignMsg::AGGREGATE_AND_PROOF_V2(_) | BLSSignMsg::aggregate_and_proof_v2(_) => {
+                "AGGREGATE_AND_PROOF_V2"
+            }
+            BLSSignMsg::AGGREGATION_SLOT(_) | BLSSignMsg::aggregation_slot(_) => "AGGREGATION_SLOT",
+            BLSSignMsg::DEPOSIT(_) | BLSSignMsg::deposit(_) => "DEPOSIT",
+            BLSSignMsg::VOLUNTARY_EXIT(_) | BLSSignMsg::voluntary_exit(_) => "VOLUNTARY_EXIT",
+            BLSSignMsg::SYNC_COMMITTEE_MESSAGE(_) | BLSSignMsg::sync_committee_message(_) => {
+                "SYNC_COMMITTEE_MESSAGE"
+            }
+            BLSSignMsg::SYNC_COMMITTEE_SELECTION_PROOF(_)
+            | BLSSignMsg::sync_committee_selection_proof(_) => "SYNC_COMMITTEE_SELECTION_PROOF",
+            BLSSignMsg::SYNC_COMMITTEE_CONTRIBUTION_AND_PROOF(_)
+            | BLSSignMsg::sync_committee_contribution_and_proof(_) => {
+                "SYNC_COMMITTEE_CONTRIBUTION_AND_PROOF"
+            }
+            BLSSignMsg::VALIDATOR_REGISTRATION(_) | BLSSignMsg::validator_registration(_) => {
+                "VALIDATOR_REGISTRATION"
+            }
+            BLSSignMsg::BLS_TO_EXECUTION_CHANGE(_) | BLSSignMsg::bls_to_execution_change(_) => {
+                "BLS_TO_EXECUTION_CHANGE"
+            }
+        }
+    }
+
+    /// Returns true for the block proposal and attestation types, the only ones that are checked
+    /// against and recorded in the slashing protection DB
+    pub fn is_slashable_type(&self) -> bool {
         if let BLSSignMsg::BLOCK(_)
         | BLSSignMsg::block(_)
         | BLSSignMsg::BLOCK_V2(_)
@@ -194,6 +326,282 @@ impl BLSSignMsg {
         }
     }
 
+    /// Returns the request's fork_info and the epoch its domain is computed at.
+    /// Returns None for types whose domain does not depend on fork_info.
+    pub fn fork_info_and_epoch(&self) -> Option<(&ForkInfo, Epoch)> {
+        match self {
+            BLSSignMsg::BLOCK(m) | BLSSignMsg::block(m) => {
+                Some((&m.fork_info, compute_epoch_at_slot(m.block.slot)))
+            }
+            BLSSignMsg::BLOCK_V2(m) | BLSSignMsg::block_v2(m) => Some((
+                &m.fork_info,
+                compute_epoch_at_slot(m.beacon_block.block_header.slot),
+            )),
+            BLSSignMsg::ATTESTATION(m) | BLSSignMsg::attestation(m) => {
+                Some((&m.fork_info, m.attestation.target.epoch))
+            }
+            BLSSignMsg::RANDAO_REVEAL(m) | BLSSignMsg::randao_reveal(m) => {
+                Some((&m.fork_info, m.randao_reveal.epoch))
+            }
+            BLSSignMsg::AGGREGATE_AND_PROOF(m) | BLSSignMsg::aggregate_and_proof(m) => Some((
+                &m.fork_info,
+                compute_epoch_at_slot(m.aggregate_and_proof.aggregate.data.slot),
+            )),
+            BLSSignMsg::AGGREGATE_AND_PROOF_V2(m) | BLSSignMsg::aggregate_and_proof_v2(m) => {
+                Some((
+                    &m.fork_info,
+                    compute_epoch_at_slot(m.aggregate_and_proof.data.attestation_data().slot),
+                ))
+            }
+            BLSSignMsg::AGGREGATION_SLOT(m) | BLSSignMsg::aggregation_slot(m) => {
+                Some((&m.fork_info, compute_epoch_at_slot(m.aggregation_slot.slot)))
+            }
+            BLSSignMsg::VOLUNTARY_EXIT(m) | BLSSignMsg::voluntary_exit(m) => {
+                Some((&m.fork_info, m.voluntary_exit.epoch))
+            }
+            BLSSignMsg::SYNC_COMMITTEE_MESSAGE(m) | BLSSignMsg::sync_committee_message(m) => {
+                Some((
+                    &m.fork_info,
+                    compute_epoch_at_slot(m.sync_committee_message.slot),
+                ))
+            }
+            BLSSignMsg::SYNC_COMMITTEE_SELECTION_PROOF(m)
+            | BLSSignMsg::sync_committee_selection_proof(m) => Some((
+                &m.fork_info,
+                compute_epoch_at_slot(m.sync_aggregator_selection_data.slot),
+            )),
+            BLSSignMsg::SYNC_COMMITTEE_CONTRIBUTION_AND_PROOF(m)
+            | BLSSignMsg::sync_committee_contribution_and_proof(m) => Some((
+                &m.fork_info,
+                compute_epoch_at_slot(m.contribution_and_proof.contribution.slot),
+            )),
+            BLSSignMsg::DEPOSIT(_)
+            | BLSSignMsg::deposit(_)
+            | BLSSignMsg::VALIDATOR_REGISTRATION(_)
+            | BLSSignMsg::validator_registration(_)
+            | BLSSignMsg::BLS_TO_EXECUTION_CHANGE(_)
+            | BLSSignMsg::bls_to_execution_change(_) => None,
+        }
+    }
+
+    /// Returns the (source, target) epochs of an attestation, or None for other types.
+    /// Attestations sign `AttestationData` both before and after Electra, which moved the
+    /// committee index into the aggregate's `committee_bits` but left the checkpoints as is.
+    pub fn attestation_epochs(&self) -> Option<(Epoch, Epoch)> {
+        match self {
+            BLSSignMsg::ATTESTATION(m) | BLSSignMsg::attestation(m) => {
+                Some((m.attestation.source.epoch, m.attestation.target.epoch))
+            }
+            _ => None,
+        }
+    }
+
+    /// Returns the proposer index of a block proposal, or None for any other type.
+    pub fn proposer_index(&self) -> Option<ValidatorIndex> {
+        match self {
+            BLSSignMsg::BLOCK(m) | BLSSignMsg::block(m) => Some(m.block.proposer_index),
+            BLSSignMsg::BLOCK_V2(m) | BLSSignMsg::block_v2(m) => {
+                Some(m.beacon_block.block_header.proposer_index)
+            }
+            _ => None,
+        }
+    }
+
+    /// Returns the registration of a validator registration request, or None for any other type.
+    pub fn registration(&self) -> Option<&ValidatorRegistration> {
+        match self {
+            BLSSignMsg::VALIDATOR_REGISTRATION(m) | BLSSignMsg::validator_registration(m) => {
+                Some(&m.validator_registration)
+            }
+            _ => None,
+        }
+    }
+
+    /// Returns the sync subcommittee a selection proof or contribution is for, or None for any
+    /// other type.
+    pub fn subcommittee_index(&self) -> Option<u64> {
+        match self {
+            BLSSignMsg::SYNC_COMMITTEE_SELECTION_PROOF(m)
+            | BLSSignMsg::sync_committee_selection_proof(m) => {
+                Some(m.sync_aggregator_selection_data.subcommittee_index)
+            }
+            BLSSignMsg::SYNC_COMMITTEE_CONTRIBUTION_AND_PROOF(m)
+            | BLSSignMsg::sync_committee_contribution_and_proof(m) => {
+                Some(m.contribution_and_proof.contribution.subcommittee_index)
+            }
+            _ => None,
+        }
+    }
+
+    /// Returns the genesis_validators_root the request's domain is computed with, or None for
+    /// types whose domain is the same on every network sharing a genesis fork version.
+    pub fn genesis_validators_root(&self) -> Option<Root> {
+        self.fork_info_and_epoch()
+            .map(|(fork_info, _)| fork_info.genesis_validators_root)
+    }
+
+    /// Returns the request's fork_info, or None for types without one.
+    pub fn fork_info_mut(&mut self) -> Option<&mut ForkInfo> {
+        match self {
+            BLSSignMsg::BLOCK(m) | BLSSignMsg::block(m) => Some(&mut m.fork_info),
+            BLSSignMsg::BLOCK_V2(m) | BLSSignMsg::block_v2(m) => Some(&mut m.fork_info),
+            BLSSignMsg::ATTESTATION(m) | BLSSignMsg::attestation(m) => Some(&mut m.fork_info),
+            BLSSignMsg::RANDAO_REVEAL(m) | BLSSignMsg::randao_reveal(m) => Some(&mut m.fork_info),
+            BLSSignMsg::AGGREGATE_AND_PROOF(m) | BLSSignMsg::aggregate_and_proof(m) => {
+                Some(&mut m.fork_info)
+            }
+            BLSSignMsg::AGGREGATE_AND_PROOF_V2(m) | BLSSignMsg::aggregate_and_proof_v2(m) => {
+                Some(&mut m.fork_info)
+            }
+            BLSSignMsg::AGGREGATION_SLOT(m) | BLSSignMsg::aggregation_slot(m) => {
+                Some(&mut m.fork_info)
+            }
+            BLSSignMsg::VOLUNTARY_EXIT(m) | BLSSignMsg::voluntary_exit(m) => Some(&mut m.fork_info),
+            BLSSignMsg::SYNC_COMMITTEE_MESSAGE(m) | BLSSignMsg::sync_committee_message(m) => {
+                Some(&mut m.fork_info)
+            }
+            BLSSignMsg::SYNC_COMMITTEE_SELECTION_PROOF(m)
+            | BLSSignMsg::sync_committee_selection_proof(m) => Some(&mut m.fork_info),
+            BLSSignMsg::SYNC_COMMITTEE_CONTRIBUTION_AND_PROOF(m)
+            | BLSSignMsg::sync_committee_contribution_and_proof(m) => Some(&mut m.fork_info),
+            BLSSignMsg::BLS_TO_EXECUTION_CHANGE(m) | BLSSignMsg::bls_to_execution_change(m) => {
+                Some(&mut m.fork_info)
+            }
+            BLSSignMsg::DEPOSIT(_)
+            | BLSSignMsg::deposit(_)
+            | BLSSignMsg::VALIDATOR_REGISTRATION(_)
+            | BLSSignMsg::validator_registration(_) => None,
+        }
+    }
+
+    /// Fills in the genesis_validators_root established by a session if the request left it as
+    /// `ZERO_GENESIS_VALIDATORS_ROOT`. Errors if the request carries a different
+    /// genesis_validators_root than the session. Deposits and builder registrations
+    /// are left alone, their domains always use the zero root.
+    pub fn apply_session_genesis_validators_root(&mut self, session_root: &Root) -> Result<()> {
+        let Some(fork_info) = self.fork_info_mut() else {
+            return Ok(());
+        };
+        if fork_info.genesis_validators_root == ZERO_GENESIS_VALIDATORS_ROOT {
+            fork_info.genesis_validators_root = *session_root;
+        } else if fork_info.genesis_validators_root != *session_root {
+            bail!(
+                "genesis_validators_root 0x{} does not match the session's 0x{}",
+                hex::encode(fork_info.genesis_validators_root),
+                hex::encode(session_root)
+            )
+        }
+        Ok(())
+    }
+
+    /// Verifies the fork version that `get_domain` would select from the request's fork_info
+    /// is the one the fork schedule says is active at the request's epoch. An empty schedule
+    /// disables the check.
+    pub fn validate_fork_info(&self, fork_schedule: &ForkSchedule) -> Result<()> {
+        if fork_schedule.forks.is_empty() {
+            return Ok(());
+        }
+        let Some((fork_info, epoch)) = self.fork_info_and_epoch() else {
+            return Ok(());
+        };
+
+        let claimed = fork_version_at(fork_info, epoch);
+
+        match fork_schedule.version_at_epoch(epoch) {
+            Some(expected) if expected == claimed => Ok(()),
+            Some(expected) => bail!(
+                "fork_info selects fork version 0x{} for epoch {epoch}, but the fork schedule expects 0x{}",
+                hex::encode(claimed),
+                hex::encode(expected)
+            ),
+            None => bail!("No fork version is scheduled for epoch {epoch}"),
+        }
+    }
+
+    /// Checks the fork version and genesis_validators_root the request's domain is computed from
+    /// are allowed. Only values taken from the request are checked, the signer's own genesis fork
+    /// version is trusted.
+    pub fn validate_domain(&self, allowed: &AllowedDomains) -> Result<()> {
+        let (fork_version, genesis_validators_root) = match self {
+            BLSSignMsg::DEPOSIT(m) | BLSSignMsg::deposit(m) => (Some(m.genesis_fork_version), None),
+            BLSSignMsg::BLS_TO_EXECUTION_CHANGE(m) | BLSSignMsg::bls_to_execution_change(m) => {
+                (None, Some(m.fork_info.genesis_validators_root))
+            }
+            _ => match self.fork_info_and_epoch() {
+                Some((fork_info, epoch)) => (
+                    Some(fork_version_at(fork_info, epoch)),
+                    Some(fork_info.genesis_validators_root),
+                ),
+                None => (None, None),
+            },
+        };
+        if let Some(v) = fork_version.filter(|v| !allowed.allows_fork_version(v)) {
+            bail!(
+                "fork version 0x{} is not one of the allowed fork versions",
+                hex::encode(v)
+            );
+        }
+        if let Some(root) =
+            genesis_validators_root.filter(|r| !allowed.allows_genesis_validators_root(r))
+        {
+            bail!(
+                "genesis_validators_root 0x{} is not one of the allowed roots",
+                hex::encode(root)
+            );
+        }
+        Ok(())
+    }
+
+    /// Returns the signingRoot the client sent along with the request, if any
+    pub fn provided_signing_root(&self) -> Option<Root> {
+        match self {
+            BLSSignMsg::BLOCK(m) | BLSSignMsg::block(m) => m.signingRoot,
+            BLSSignMsg::BLOCK_V2(m) | BLSSignMsg::block_v2(m) => m.signingRoot,
+            BLSSignMsg::ATTESTATION(m) | BLSSignMsg::attestation(m) => m.signingRoot,
+            BLSSignMsg::RANDAO_REVEAL(m) | BLSSignMsg::randao_reveal(m) => m.signingRoot,
+            BLSSignMsg::AGGREGATE_AND_PROOF(m) | BLSSignMsg::aggregate_and_proof(m) => {
+                m.signingRoot
+            }
+            BLSSignMsg::AGGREGATE_AND_PROOF_V2(m) | BLSSignMsg::aggregate_and_proof_v2(m) => {
+                m.signingRoot
+            }
+            BLSSignMsg::AGGREGATION_SLOT(m) | BLSSignMsg::aggregation_slot(m) => m.signingRoot,
+            BLSSignMsg::DEPOSIT(m) | BLSSignMsg::deposit(m) => m.signingRoot,
+            BLSSignMsg::VOLUNTARY_EXIT(m) | BLSSignMsg::voluntary_exit(m) => m.signingRoot,
+            BLSSignMsg::SYNC_COMMITTEE_MESSAGE(m) | BLSSignMsg::sync_committee_message(m) => {
+                m.signingRoot
+            }
+            BLSSignMsg::SYNC_COMMITTEE_SELECTION_PROOF(m)
+            | BLSSignMsg::sync_committee_selection_proof(m) => m.signingRoot,
+            BLSSignMsg::SYNC_COMMITTEE_CONTRIBUTION_AND_PROOF(m)
+            | BLSSignMsg::sync_committee_contribution_and_proof(m) => m.signingRoot,
+            BLSSignMsg::VALIDATOR_REGISTRATION(m) | BLSSignMsg::validator_registration(m) => {
+                m.signingRoot
+            }
+            BLSSignMsg::BLS_TO_EXECUTION_CHANGE(m) | BLSSignMsg::bls_to_execution_change(m) => {
+                m.signingRoot
+            }
+        }
+    }
+
+    /// Computes the signing root from the request's fields. If the client also sent a
+    /// signingRoot it must be the same, so the signed root always matches the structure.
+    /// A voluntary exit is refused if the fork its domain is computed with can't be told.
+    pub fn checked_signing_root(&self, genesis_fork_version: Option<Version>) -> Result<Root> {
+        if let BLSSignMsg::VOLUNTARY_EXIT(m) | BLSSignMsg::voluntary_exit(m) = self {
+            voluntary_exit_capella_fork_version(genesis_fork_version, &m.fork_info)?;
+        }
+        let root = self.to_signing_root(genesis_fork_version);
+        match self.provided_signing_root() {
+            Some(provided) if provided != root => bail!(
+                "signingRoot 0x{} does not match the computed signing root 0x{}",
+                hex::encode(provided),
+                hex::encode(root)
+            ),
+            _ => Ok(root),
+        }
+    }
+
     pub fn to_signing_root(&self, _genesis_fork_version: Option<Version>) -> Root {
         match self {
             // https://github.com/ethereum/consensus-specs/blob/dev/specs/phase0/validator.md#signature
@@ -243,6 +651,22 @@ impl BLSSignMsg {
                     get_domain(m.fork_info.clone(), DOMAIN_AGGREGATE_AND_PROOF, Some(epoch));
                 compute_signing_root(m.aggregate_and_proof.clone(), domain)
             }
+            // https://github.com/ethereum/consensus-specs/blob/dev/specs/electra/validator.md#construct-aggregate
+            // Same domain as before Electra, but the aggregate is hashed with its fork's container
+            BLSSignMsg::AGGREGATE_AND_PROOF_V2(m) | BLSSignMsg::aggregate_and_proof_v2(m) => {
+                let data = &m.aggregate_and_proof.data;
+                let epoch = compute_epoch_at_slot(data.attestation_data().slot);
+                let domain =
+                    get_domain(m.fork_info.clone(), DOMAIN_AGGREGATE_AND_PROOF, Some(epoch));
+                match data {
+                    VersionedAggregateAndProof::Phase0(a) => {
+                        compute_signing_root(a.clone(), domain)
+                    }
+                    VersionedAggregateAndProof::Electra(a) => {
+                        compute_signing_root(a.clone(), domain)
+                    }
+                }
+            }
             // https://github.com/ethereum/consensus-specs/blob/dev/specs/phase0/validator.md#aggregation-selection
             BLSSignMsg::AGGREGATION_SLOT(m) | BLSSignMsg::aggregation_slot(m) => {
                 let epoch = compute_epoch_at_slot(m.aggregation_slot.slot.clone());
@@ -251,17 +675,34 @@ impl BLSSignMsg {
             }
             // https://github.com/ethereum/consensus-specs/blob/dev/specs/phase0/validator.md#submit-deposit
             BLSSignMsg::DEPOSIT(m) | BLSSignMsg::deposit(m) => {
-                let domain =
-                    compute_domain(DOMAIN_DEPOSIT, Some(m.genesis_fork_version.clone()), None);
+                let domain = compute_domain(
+                    DOMAIN_DEPOSIT,
+                    Some(m.genesis_fork_version.clone()),
+                    Some(ZERO_GENESIS_VALIDATORS_ROOT),
+                );
                 compute_signing_root(m.deposit.clone(), domain)
             }
             // https://github.com/ethereum/consensus-specs/blob/dev/specs/phase0/beacon-chain.md#voluntary-exits
+            // From Deneb on the domain is always computed with the Capella fork version, see
+            // https://github.com/ethereum/consensus-specs/blob/dev/specs/deneb/beacon-chain.md#modified-process_voluntary_exit
+            // Only `checked_signing_root` refuses exits on networks without a known fork schedule.
             BLSSignMsg::VOLUNTARY_EXIT(m) | BLSSignMsg::voluntary_exit(m) => {
-                let domain = get_domain(
-                    m.fork_info.clone(),
-                    DOMAIN_VOLUNTARY_EXIT,
-                    Some(m.voluntary_exit.epoch.clone()),
-                );
+                let capella_fork_version =
+                    voluntary_exit_capella_fork_version(_genesis_fork_version, &m.fork_info)
+                        .ok()
+                        .flatten();
+                let domain = match capella_fork_version {
+                    Some(fork_version) => compute_domain(
+                        DOMAIN_VOLUNTARY_EXIT,
+                        Some(fork_version),
+                        Some(m.fork_info.genesis_validators_root),
+                    ),
+                    None => get_domain(
+                        m.fork_info.clone(),
+                        DOMAIN_VOLUNTARY_EXIT,
+                        Some(m.voluntary_exit.epoch.clone()),
+                    ),
+                };
                 compute_signing_root(m.voluntary_exit.clone(), domain)
             }
             // https://github.com/ethereum/consensus-specs/blob/dev/specs/altair/validator.md#sync-committee-messages
@@ -295,10 +736,930 @@ impl BLSSignMsg {
             }
             // https://github.com/ethereum/builder-specs/blob/main/specs/bellatrix/builder.md#signing
             BLSSignMsg::VALIDATOR_REGISTRATION(m) | BLSSignMsg::validator_registration(m) => {
-                let domain =
-                    compute_domain(DOMAIN_APPLICATION_BUILDER, _genesis_fork_version, None);
+                let domain = compute_domain(
+                    DOMAIN_APPLICATION_BUILDER,
+                    _genesis_fork_version,
+                    Some(ZERO_GENESIS_VALIDATORS_ROOT),
+                );
                 compute_signing_root(m.validator_registration.clone(), domain)
             }
+            // https://github.com/ethereum/consensus-specs/blob/dev/specs/capella/beacon-chain.md#new-process_bls_to_execution_change
+            // The domain is always computed with the genesis fork version, independent of fork_info's fork
+            BLSSignMsg::BLS_TO_EXECUTION_CHANGE(m) | BLSSignMsg::bls_to_execution_change(m) => {
+                let domain = compute_domain(
+                    DOMAIN_BLS_TO_EXECUTION_CHANGE,
+                    _genesis_fork_version,
+                    Some(m.fork_info.genesis_validators_root),
+                );
+                compute_signing_root(m.bls_to_execution_change.clone(), domain)
+            }
+        }
+    }
+}
+
+/// Returns the Capella fork version a voluntary exit under `fork_info` is signed with if its
+/// current fork is Deneb or later (EIP-7044), None before Deneb. Errors if the network's fork
+/// schedule is unknown or the current fork can't be placed in it, as guessing either way can
+/// yield an invalid exit.
+fn voluntary_exit_capella_fork_version(
+    genesis_fork_version: Option<Version>,
+    fork_info: &ForkInfo,
+) -> Result<Option<Version>> {
+    let Some(genesis_fork_version) = genesis_fork_version else {
+        bail!("Signing a voluntary exit needs the genesis fork version of its network")
+    };
+    let Some(schedule) = ForkSchedule::for_genesis_fork_version(genesis_fork_version) else {
+        bail!(
+            "No known fork schedule for genesis fork version 0x{}, can't tell which fork a voluntary exit is signed under",
+            hex::encode(genesis_fork_version)
+        )
+    };
+    schedule.voluntary_exit_fork_version(&fork_info.fork)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_from_json_strict_rejects_unknown_fields() {
+        let req = r#"{
+            "type": "RANDAO_REVEAL",
+            "fork_info": {
+                "fork": {"previous_version": "0x00000000", "current_version": "0x00000000", "epoch": "0"},
+                "genesis_validators_root": "0x0000000000000000000000000000000000000000000000000000000000000000"
+            },
+            "signingRoot": "",
+            "randao_reveal": {"epoch": "3"}
+        }"#;
+        assert!(BLSSignMsg::from_json_strict(req.as_bytes()).is_ok());
+
+        let extra = req.replace(r#""epoch": "3""#, r#""epoch": "3", "note": "hi""#);
+        assert!(serde_json::from_str::<BLSSignMsg>(&extra).is_ok());
+        let err = BLSSignMsg::from_json_strict(extra.as_bytes()).unwrap_err();
+        assert_eq!(err.to_string(), "Unknown fields .randao_reveal.note");
+    }
+
+    fn mainnet_block_v2_request(
+        previous_version: &str,
+        current_version: &str,
+        fork_epoch: u64,
+        slot: u64,
+    ) -> BLSSignMsg {
+        let req = format!(
+            r#"
+            {{
+                "fork_info":{{
+                    "fork":{{
+                        "previous_version":"{previous_version}",
+                        "current_version":"{current_version}",
+                        "epoch":"{fork_epoch}"
+                    }},
+                    "genesis_validators_root":"0x4b363db94e286120d76eb905340fdd4e54bfe9f06bf33ff6cf5ad27f511bfe95"
+                }},
+                "beacon_block": {{
+                    "version": "BELLATRIX",
+                    "block_header": {{
+                        "slot": "{slot}",
+                        "proposer_index": "0",
+                        "parent_root":"0x0000000000000000000000000000000000000000000000000000000000000000",
+                        "state_root":"0x0000000000000000000000000000000000000000000000000000000000000000",
+                        "body_root":"0xcd7c49966ebe72b1214e6d4733adf6bf06935c5fbc3b3ad08e84e3085428b82f"
+                    }}
+                }}
+            }}"#
+        );
+        BLSSignMsg::BLOCK_V2(serde_json::from_str(&req).unwrap())
+    }
+
+    #[test]
+    fn test_bellatrix_block_with_bellatrix_fork_info_is_accepted() {
+        let schedule = ForkSchedule::for_genesis_fork_version([0, 0, 0, 0]).unwrap();
+        // Slot in epoch 146875, after the bellatrix fork at epoch 144896
+        let req = mainnet_block_v2_request("0x01000000", "0x02000000", 144896, 4700000);
+        req.validate_fork_info(&schedule).unwrap();
+    }
+
+    #[test]
+    fn test_bellatrix_block_claiming_phase0_fork_version_is_rejected() {
+        let schedule = ForkSchedule::for_genesis_fork_version([0, 0, 0, 0]).unwrap();
+        let req = mainnet_block_v2_request("0x00000000", "0x00000000", 0, 4700000);
+        let err = req.validate_fork_info(&schedule).unwrap_err();
+        assert!(err.to_string().contains("0x02000000"));
+    }
+
+    #[test]
+    fn test_fulu_block_with_fulu_fork_info_is_accepted() {
+        let schedule = ForkSchedule::for_genesis_fork_version([0, 0, 0, 0]).unwrap();
+        // Slot in epoch 411400, after the fulu fork at epoch 411392
+        let req = mainnet_block_v2_request("0x05000000", "0x06000000", 411392, 13164800);
+        req.validate_fork_info(&schedule).unwrap();
+
+        // Electra is over by then
+        let req = mainnet_block_v2_request("0x04000000", "0x05000000", 364032, 13164800);
+        let err = req.validate_fork_info(&schedule).unwrap_err();
+        assert!(err.to_string().contains("0x06000000"));
+    }
+
+    #[test]
+    fn test_fork_info_selecting_previous_version_is_checked() {
+        let schedule = ForkSchedule::for_genesis_fork_version([0, 0, 0, 0]).unwrap();
+        // The fork epoch is in the future so get_domain would pick the phase0 previous_version
+        let req = mainnet_block_v2_request("0x00000000", "0x02000000", 200000, 4700000);
+        assert!(req.validate_fork_info(&schedule).is_err());
+    }
+
+    #[test]
+    fn test_electra_attestation_epochs() {
+        let req = r#"{
+            "type": "ATTESTATION",
+            "fork_info": {
+                "fork": {
+                    "previous_version": "0x04000000",
+                    "current_version": "0x05000000",
+                    "epoch": "364032"
+                },
+                "genesis_validators_root": "0x4b363db94e286120d76eb905340fdd4e54bfe9f06bf33ff6cf5ad27f511bfe95"
+            },
+            "attestation": {
+                "slot": "11651232",
+                "index": "0",
+                "beacon_block_root": "0x1111111111111111111111111111111111111111111111111111111111111111",
+                "source": {
+                    "epoch": "364100",
+                    "root": "0x2222222222222222222222222222222222222222222222222222222222222222"
+                },
+                "target": {
+                    "epoch": "364101",
+                    "root": "0x3333333333333333333333333333333333333333333333333333333333333333"
+                }
+            }
+        }"#;
+        let req: BLSSignMsg = serde_json::from_str(req).unwrap();
+        assert_eq!(req.attestation_epochs(), Some((364100, 364101)));
+        let schedule = ForkSchedule::for_genesis_fork_version([0, 0, 0, 0]).unwrap();
+        req.validate_fork_info(&schedule).unwrap();
+
+        // Other types carry no attestation epochs
+        let req = mainnet_block_v2_request("0x01000000", "0x02000000", 144896, 4700000);
+        assert_eq!(req.attestation_epochs(), None);
+    }
+
+    #[test]
+    fn test_empty_fork_schedule_skips_validation() {
+        let req = mainnet_block_v2_request("0x00000000", "0x00000000", 0, 4700000);
+        req.validate_fork_info(&ForkSchedule::default()).unwrap();
+    }
+
+    #[test]
+    fn test_provided_signing_root_must_match_computed_root() {
+        let mut req = mainnet_block_v2_request("0x01000000", "0x02000000", 144896, 4700000);
+        let root = req.to_signing_root(None);
+
+        // No signingRoot is fine, the computed root is signed
+        assert_eq!(req.checked_signing_root(None).unwrap(), root);
+
+        if let BLSSignMsg::BLOCK_V2(m) = &mut req {
+            m.signingRoot = Some(root);
+        }
+        assert_eq!(req.checked_signing_root(None).unwrap(), root);
+
+        // A tampered root is rejected rather than signed
+        let mut tampered = root;
+        tampered[0] ^= 1;
+        if let BLSSignMsg::BLOCK_V2(m) = &mut req {
+            m.signingRoot = Some(tampered);
+        }
+        let err = req.checked_signing_root(None).unwrap_err();
+        assert!(err.to_string().contains(&hex::encode(tampered)));
+    }
+
+    fn full_block_v2_request(version: &str, fork_info: &str, slot: u64) -> BLSSignMsg {
+        let (payload_extra, body_extra) = match version {
+            "DENEB" => (
+                r#",
+                            "blob_gas_used": "131072",
+                            "excess_blob_gas": "0""#
+                    .to_string(),
+                format!(
+                    r#",
+                        "blob_kzg_commitments": ["0x{}"]"#,
+                    "a1".repeat(48)
+                ),
+            ),
+            _ => (String::new(), String::new()),
+        };
+        let req = format!(
+            r#"
+            {{
+                "fork_info": {fork_info},
+                "beacon_block": {{
+                    "version": "{version}",
+                    "block": {{
+                        "slot": "{slot}",
+                        "proposer_index": "42",
+                        "parent_root": "0x{dcc}",
+                        "state_root": "0x{ddd}",
+                        "body": {{
+                        "randao_reveal": "0x{aa}",
+                        "eth1_data": {{
+                            "deposit_root": "0x{d11}",
+                            "deposit_count": "100",
+                            "block_hash": "0x{d22}"
+                        }},
+                        "graffiti": "0x{d33}",
+                        "proposer_slashings": [],
+                        "attester_slashings": [],
+                        "attestations": [],
+                        "deposits": [],
+                        "voluntary_exits": [],
+                        "sync_aggregate": {{
+                            "sync_committee_bits": "0x{ff}",
+                            "sync_committee_signature": "0x{bb}"
+                        }},
+                        "execution_payload": {{
+                            "parent_hash": "0x{d44}",
+                            "fee_recipient": "0x{d55}",
+                            "state_root": "0x{d66}",
+                            "receipts_root": "0x{d77}",
+                            "logs_bloom": "0x{zero256}",
+                            "prev_randao": "0x{d88}",
+                            "block_number": "17000000",
+                            "gas_limit": "30000000",
+                            "gas_used": "15000000",
+                            "timestamp": "1700000000",
+                            "extra_data": "0x6265617665",
+                            "base_fee_per_gas": "7",
+                            "block_hash": "0x{d99}",
+                            "transactions": ["0x02f87001"],
+                            "withdrawals": [
+                                {{
+                                    "index": "5",
+                                    "validator_index": "7",
+                                    "address": "0x{d55}",
+                                    "amount": "1000"
+                                }}
+                            ]{payload_extra}
+                        }},
+                        "bls_to_execution_changes": []{body_extra}
+                        }}
+                    }}
+                }}
+            }}"#,
+            aa = "aa".repeat(96),
+            bb = "bb".repeat(96),
+            ff = "ff".repeat(64),
+            d11 = "11".repeat(32),
+            d22 = "22".repeat(32),
+            d33 = "33".repeat(32),
+            d44 = "44".repeat(32),
+            d55 = "55".repeat(20),
+            d66 = "66".repeat(32),
+            d77 = "77".repeat(32),
+            d88 = "88".repeat(32),
+            d99 = "99".repeat(32),
+            dcc = "cc".repeat(32),
+            ddd = "dd".repeat(32),
+            zero256 = "00".repeat(256),
+        );
+        BLSSignMsg::BLOCK_V2(serde_json::from_str(&req).unwrap())
+    }
+
+    #[test]
+    fn test_capella_block_v2_signing_root() {
+        let fork_info = r#"{
+            "fork": {
+                "previous_version": "0x02000000",
+                "current_version": "0x03000000",
+                "epoch": "194048"
+            },
+            "genesis_validators_root": "0x4b363db94e286120d76eb905340fdd4e54bfe9f06bf33ff6cf5ad27f511bfe95"
+        }"#;
+        let req = full_block_v2_request("CAPELLA", fork_info, 6300000);
+        let BLSSignMsg::BLOCK_V2(ref m) = req else {
+            panic!("expected BLOCK_V2")
+        };
+        assert_eq!(
+            hex::encode(m.beacon_block.block_header.body_root),
+            "9037e21db36850ed9f0c3a68a51d760a7d9f8494371d30f5cf9c9c92f0c093dc"
+        );
+        assert_eq!(
+            hex::encode(req.to_signing_root(None)),
+            "fc2bbc9d3a6fce045eb8cae62d97dbb33849b8289b9551a1e0ba95c4617c7755"
+        );
+    }
+
+    #[test]
+    fn test_deneb_block_v2_signing_root() {
+        let fork_info = r#"{
+            "fork": {
+                "previous_version": "0x03000000",
+                "current_version": "0x04000000",
+                "epoch": "269568"
+            },
+            "genesis_validators_root": "0x4b363db94e286120d76eb905340fdd4e54bfe9f06bf33ff6cf5ad27f511bfe95"
+        }"#;
+        let req = full_block_v2_request("DENEB", fork_info, 8700000);
+        let BLSSignMsg::BLOCK_V2(ref m) = req else {
+            panic!("expected BLOCK_V2")
+        };
+        assert_eq!(
+            hex::encode(m.beacon_block.block_header.body_root),
+            "65d02d70aead89bf892b088266b25fb9106c2d4884a1b013ec7c21762e405cc9"
+        );
+        assert_eq!(
+            hex::encode(req.to_signing_root(None)),
+            "e7dab8d050982359f0ccb22854b449a4cd5f50a5982a6340d6e5bfe12db90349"
+        );
+    }
+
+    fn bls_to_execution_change_request(current_version: &str) -> BLSSignMsg {
+        let req = format!(
+            r#"
+            {{
+                "type": "BLS_TO_EXECUTION_CHANGE",
+                "fork_info": {{
+                    "fork": {{
+                        "previous_version": "0x02000000",
+                        "current_version": "{current_version}",
+                        "epoch": "194048"
+                    }},
+                    "genesis_validators_root": "0x4b363db94e286120d76eb905340fdd4e54bfe9f06bf33ff6cf5ad27f511bfe95"
+                }},
+                "bls_to_execution_change": {{
+                    "validator_index": "123",
+                    "from_bls_pubkey": "0x8349434ad0700e79be65c0c7043945df426bd6d7e288c16671df69d822344f1b0ce8de80360a50550ad782b68035cb18",
+                    "to_execution_address": "0x2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a"
+                }}
+            }}"#
+        );
+        serde_json::from_str(&req).unwrap()
+    }
+
+    #[test]
+    fn test_bls_to_execution_change_uses_genesis_fork_version() {
+        let genesis_fork_version = Some([0, 0, 0, 0]);
+        // Domain computed with the genesis fork version 0x00000000, not the capella 0x03000000
+        let exp = "80b509fdfe672c11d800f21028f2171dc42831a8f4f82ffe810e015e0b77a072";
+
+        let req = bls_to_execution_change_request("0x03000000");
+        assert_eq!(hex::encode(req.to_signing_root(genesis_fork_version)), exp);
+
+        // The current fork in fork_info has no effect
+        let req = bls_to_execution_change_request("0x04000000");
+        assert_eq!(hex::encode(req.to_signing_root(genesis_fork_version)), exp);
+    }
+
+    fn voluntary_exit_request(previous_version: &str, current_version: &str) -> BLSSignMsg {
+        voluntary_exit_request_at(previous_version, current_version, 269568)
+    }
+
+    fn voluntary_exit_request_at(
+        previous_version: &str,
+        current_version: &str,
+        fork_epoch: u64,
+    ) -> BLSSignMsg {
+        let req = format!(
+            r#"{{
+                "type": "VOLUNTARY_EXIT",
+                "fork_info": {{
+                    "fork": {{
+                        "previous_version": "{previous_version}",
+                        "current_version": "{current_version}",
+                        "epoch": "{fork_epoch}"
+                    }},
+                    "genesis_validators_root": "0x4b363db94e286120d76eb905340fdd4e54bfe9f06bf33ff6cf5ad27f511bfe95"
+                }},
+                "voluntary_exit": {{
+                    "epoch": "270000",
+                    "validator_index": "123"
+                }}
+            }}"#
+        );
+        serde_json::from_str(&req).unwrap()
+    }
+
+    #[test]
+    fn test_voluntary_exit_uses_capella_fork_version_from_deneb() {
+        let genesis_fork_version = Some([0, 0, 0, 0]);
+        let BLSSignMsg::VOLUNTARY_EXIT(deneb) = voluntary_exit_request("0x03000000", "0x04000000")
+        else {
+            unreachable!()
+        };
+        let capella_domain = compute_domain(
+            DOMAIN_VOLUNTARY_EXIT,
+            Some([3, 0, 0, 0]),
+            Some(deneb.fork_info.genesis_validators_root),
+        );
+        let deneb_domain = compute_domain(
+            DOMAIN_VOLUNTARY_EXIT,
+            Some([4, 0, 0, 0]),
+            Some(deneb.fork_info.genesis_validators_root),
+        );
+        let exp = compute_signing_root(deneb.voluntary_exit.clone(), capella_domain);
+        assert_ne!(
+            exp,
+            compute_signing_root(deneb.voluntary_exit.clone(), deneb_domain)
+        );
+
+        // Deneb and every later fork sign exits under the Capella domain
+        for (previous_version, current_version) in
+            [("0x03000000", "0x04000000"), ("0x04000000", "0x05000000")]
+        {
+            let req = voluntary_exit_request(previous_version, current_version);
+            assert_eq!(req.to_signing_root(genesis_fork_version), exp);
+        }
+
+        // Before Deneb the current fork's domain is still used
+        let req = voluntary_exit_request("0x02000000", "0x03000000");
+        assert_eq!(req.to_signing_root(genesis_fork_version), exp);
+        let req = voluntary_exit_request("0x01000000", "0x02000000");
+        let root = req.to_signing_root(genesis_fork_version);
+        let BLSSignMsg::VOLUNTARY_EXIT(bellatrix) = req else {
+            unreachable!()
+        };
+        let bellatrix_domain = compute_domain(
+            DOMAIN_VOLUNTARY_EXIT,
+            Some([2, 0, 0, 0]),
+            Some(bellatrix.fork_info.genesis_validators_root),
+        );
+        assert_eq!(
+            root,
+            compute_signing_root(bellatrix.voluntary_exit, bellatrix_domain)
+        );
+    }
+
+    #[test]
+    fn test_voluntary_exit_needs_known_fork_schedule() {
+        let req = voluntary_exit_request("0x03000000", "0x04000000");
+        assert_eq!(
+            req.checked_signing_root(Some([0, 0, 0, 0])).unwrap(),
+            req.to_signing_root(Some([0, 0, 0, 0]))
+        );
+        // A devnet's fork schedule is unknown, so it can't be told whether Deneb is active
+        assert!(req.checked_signing_root(Some([0x10, 0, 0, 0x38])).is_err());
+        assert!(req.checked_signing_root(None).is_err());
+        // Nor can it for a fork the schedule doesn't have that activated before Deneb
+        let req = voluntary_exit_request_at("0x02000000", "0x03abcdef", 194048);
+        assert!(req.checked_signing_root(Some([0, 0, 0, 0])).is_err());
+    }
+
+    #[test]
+    fn test_voluntary_exit_after_deneb_uses_capella_fork_version() {
+        let genesis_fork_version = Some([0, 0, 0, 0]);
+        let exp = voluntary_exit_request("0x03000000", "0x04000000")
+            .checked_signing_root(genesis_fork_version)
+            .unwrap();
+
+        // Fulu, and a fork past it that the schedule doesn't list yet
+        for (previous_version, current_version, fork_epoch) in [
+            ("0x05000000", "0x06000000", 411392),
+            ("0x06000000", "0x07000000", 500000),
+        ] {
+            let req = voluntary_exit_request_at(previous_version, current_version, fork_epoch);
+            assert_eq!(req.checked_signing_root(genesis_fork_version).unwrap(), exp);
+        }
+    }
+
+    #[test]
+    fn test_full_block_v2_requires_known_version() {
+        let req = r#"{ "version": "BELLATRIX", "block": {} }"#;
+        let err = serde_json::from_str::<BlockV2RequestWrapper>(req).unwrap_err();
+        assert!(err.to_string().contains("BELLATRIX"));
+
+        let req = r#"{ "version": "CAPELLA" }"#;
+        assert!(serde_json::from_str::<BlockV2RequestWrapper>(req).is_err());
+    }
+
+    const MAINNET_GENESIS_VALIDATORS_ROOT: &str =
+        "4b363db94e286120d76eb905340fdd4e54bfe9f06bf33ff6cf5ad27f511bfe95";
+
+    fn root(hex_str: &str) -> Root {
+        hex::decode(hex_str).unwrap().try_into().unwrap()
+    }
+
+    #[test]
+    fn test_fork_data_root_matches_mainnet_fork_digests() {
+        let gvr = root(MAINNET_GENESIS_VALIDATORS_ROOT);
+        // The fork digest is the first 4 bytes of the fork data root
+        for (version, digest) in [
+            ([0, 0, 0, 0], "b5303f2a"), // phase0
+            ([1, 0, 0, 0], "afcaaba0"), // altair
+            ([2, 0, 0, 0], "4a26c58b"), // bellatrix
+            ([3, 0, 0, 0], "bba4da96"), // capella
+        ] {
+            assert_eq!(
+                hex::encode(&compute_fork_data_root(version, gvr)[..4]),
+                digest
+            );
+        }
+    }
+
+    #[test]
+    fn test_compute_domain_mainnet_vectors() {
+        let gvr = Some(root(MAINNET_GENESIS_VALIDATORS_ROOT));
+        let phase0 = Some([0, 0, 0, 0]);
+        let altair = Some([1, 0, 0, 0]);
+        let bellatrix = Some([2, 0, 0, 0]);
+        let capella = Some([3, 0, 0, 0]);
+        for (domain_type, fork_version, gvr, exp) in [
+            (
+                DOMAIN_BEACON_PROPOSER,
+                phase0,
+                gvr,
+                "00000000b5303f2ad2010d699a76c8e62350947421a3e4a979779642cfdb0f66",
+            ),
+            (
+                DOMAIN_BEACON_ATTESTER,
+                phase0,
+                gvr,
+                "01000000b5303f2ad2010d699a76c8e62350947421a3e4a979779642cfdb0f66",
+            ),
+            (
+                DOMAIN_RANDAO,
+                phase0,
+                gvr,
+                "02000000b5303f2ad2010d699a76c8e62350947421a3e4a979779642cfdb0f66",
+            ),
+            (
+                DOMAIN_VOLUNTARY_EXIT,
+                phase0,
+                gvr,
+                "04000000b5303f2ad2010d699a76c8e62350947421a3e4a979779642cfdb0f66",
+            ),
+            (
+                DOMAIN_SELECTION_PROOF,
+                phase0,
+                gvr,
+                "05000000b5303f2ad2010d699a76c8e62350947421a3e4a979779642cfdb0f66",
+            ),
+            (
+                DOMAIN_AGGREGATE_AND_PROOF,
+                phase0,
+                gvr,
+                "06000000b5303f2ad2010d699a76c8e62350947421a3e4a979779642cfdb0f66",
+            ),
+            (
+                DOMAIN_SYNC_COMMITTEE,
+                altair,
+                gvr,
+                "07000000afcaaba0efab1ca832a15152469bb09bb84641c405171dfa2d3fb45f",
+            ),
+            (
+                DOMAIN_SYNC_COMMITTEE_SELECTION_PROOF,
+                altair,
+                gvr,
+                "08000000afcaaba0efab1ca832a15152469bb09bb84641c405171dfa2d3fb45f",
+            ),
+            (
+                DOMAIN_CONTRIBUTION_AND_PROOF,
+                altair,
+                gvr,
+                "09000000afcaaba0efab1ca832a15152469bb09bb84641c405171dfa2d3fb45f",
+            ),
+            (
+                DOMAIN_BEACON_PROPOSER,
+                bellatrix,
+                gvr,
+                "000000004a26c58b08add8089b75caa540848881a8d4f0af0be83417a85c0f45",
+            ),
+            (
+                DOMAIN_BEACON_ATTESTER,
+                bellatrix,
+                gvr,
+                "010000004a26c58b08add8089b75caa540848881a8d4f0af0be83417a85c0f45",
+            ),
+            (
+                DOMAIN_BEACON_PROPOSER,
+                capella,
+                gvr,
+                "00000000bba4da96354c9f25476cf1bc69bf583a7f9e0af049305b62de676640",
+            ),
+            (
+                DOMAIN_VOLUNTARY_EXIT,
+                capella,
+                gvr,
+                "04000000bba4da96354c9f25476cf1bc69bf583a7f9e0af049305b62de676640",
+            ),
+            // Capella signs BLS to execution changes with the genesis fork version
+            (
+                DOMAIN_BLS_TO_EXECUTION_CHANGE,
+                phase0,
+                gvr,
+                "0a000000b5303f2ad2010d699a76c8e62350947421a3e4a979779642cfdb0f66",
+            ),
+            // Deposits and builder registrations use a zero genesis_validators_root
+            (
+                DOMAIN_DEPOSIT,
+                None,
+                None,
+                "03000000f5a5fd42d16a20302798ef6ed309979b43003d2320d9f0e8ea9831a9",
+            ),
+            (
+                DOMAIN_APPLICATION_BUILDER,
+                None,
+                None,
+                "00000001f5a5fd42d16a20302798ef6ed309979b43003d2320d9f0e8ea9831a9",
+            ),
+        ] {
+            assert_eq!(
+                hex::encode(compute_domain(domain_type, fork_version, gvr)),
+                exp
+            );
+        }
+    }
+
+    #[test]
+    fn test_get_domain_selects_fork_version_by_epoch() {
+        // Mainnet altair fork
+        let fork_info = ForkInfo {
+            fork: Fork {
+                previous_version: [0, 0, 0, 0],
+                current_version: [1, 0, 0, 0],
+                epoch: 74240,
+            },
+            genesis_validators_root: root(MAINNET_GENESIS_VALIDATORS_ROOT),
+        };
+        let phase0 = "02000000b5303f2ad2010d699a76c8e62350947421a3e4a979779642cfdb0f66";
+        let altair = "02000000afcaaba0efab1ca832a15152469bb09bb84641c405171dfa2d3fb45f";
+        let domain = |epoch| hex::encode(get_domain(fork_info.clone(), DOMAIN_RANDAO, epoch));
+        assert_eq!(domain(Some(74239)), phase0);
+        assert_eq!(domain(Some(74240)), altair);
+        // Without an epoch the fork epoch is used
+        assert_eq!(domain(None), altair);
+    }
+
+    #[test]
+    fn test_compute_signing_root_vectors() {
+        // RANDAO reveal for the first altair epoch on mainnet
+        let domain = compute_domain(
+            DOMAIN_RANDAO,
+            Some([1, 0, 0, 0]),
+            Some(root(MAINNET_GENESIS_VALIDATORS_ROOT)),
+        );
+        assert_eq!(
+            hex::encode(compute_signing_root(74240_u64, domain)),
+            "89f5e8b7403e2187648ee4dc2c62ce0c06a291db5960a2f74ab238ee39255b6b"
+        );
+
+        // Builder registration from the builder-specs and Web3Signer examples
+        let registration: ValidatorRegistration = serde_json::from_str(
+            r#"{
+                "fee_recipient": "0x2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a",
+                "gas_limit": "30000000",
+                "timestamp": "100",
+                "pubkey": "0x8349434ad0700e79be65c0c7043945df426bd6d7e288c16671df69d822344f1b0ce8de80360a50550ad782b68035cb18"
+            }"#,
+        )
+        .unwrap();
+        let domain = compute_domain(DOMAIN_APPLICATION_BUILDER, None, None);
+        assert_eq!(
+            hex::encode(compute_signing_root(registration, domain)),
+            "139d59dbb1770fdc582ff75193720352ccc76131e37ac69d0c10e7416f3f3050"
+        );
+    }
+
+    fn randao_reveal_request(genesis_validators_root: &Root) -> BLSSignMsg {
+        serde_json::from_str(&format!(
+            r#"{{
+                "type": "RANDAO_REVEAL",
+                "fork_info": {{
+                    "fork": {{"previous_version": "0x00000000", "current_version": "0x00000000", "epoch": "0"}},
+                    "genesis_validators_root": "0x{}"
+                }},
+                "randao_reveal": {{"epoch": "10"}}
+            }}"#,
+            hex::encode(genesis_validators_root)
+        ))
+        .unwrap()
+    }
+
+    fn bls_to_execution_change_request_under(genesis_validators_root: &Root) -> BLSSignMsg {
+        serde_json::from_str(&format!(
+            r#"{{
+                "type": "BLS_TO_EXECUTION_CHANGE",
+                "fork_info": {{
+                    "fork": {{"previous_version": "0x02000000", "current_version": "0x03000000", "epoch": "194048"}},
+                    "genesis_validators_root": "0x{}"
+                }},
+                "bls_to_execution_change": {{
+                    "validator_index": "123",
+                    "from_bls_pubkey": "0x8349434ad0700e79be65c0c7043945df426bd6d7e288c16671df69d822344f1b0ce8de80360a50550ad782b68035cb18",
+                    "to_execution_address": "0x2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a"
+                }}
+            }}"#,
+            hex::encode(genesis_validators_root)
+        ))
+        .unwrap()
+    }
+
+    fn deposit_request() -> BLSSignMsg {
+        serde_json::from_str(
+            r#"{
+                "type": "DEPOSIT",
+                "deposit": {
+                    "pubkey": "0x8349434ad0700e79be65c0c7043945df426bd6d7e288c16671df69d822344f1b0ce8de80360a50550ad782b68035cb18",
+                    "withdrawal_credentials": "0x0000000000000000000000000000000000000000000000000000000000000000",
+                    "amount": "32000000000"
+                },
+                "genesis_fork_version": "0x00000000"
+            }"#,
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn test_deposit_domain_always_uses_zero_root() {
+        let req = deposit_request();
+        // Not bound to a network, so sessions and genesis bindings don't apply
+        assert!(req.genesis_validators_root().is_none());
+        let mut in_session = deposit_request();
+        in_session
+            .apply_session_genesis_validators_root(&[42; 32])
+            .unwrap();
+        assert_eq!(in_session.to_signing_root(None), req.to_signing_root(None));
+
+        let BLSSignMsg::DEPOSIT(m) = &req else {
+            unreachable!()
+        };
+        let domain = compute_domain(
+            DOMAIN_DEPOSIT,
+            Some(GENESIS_FORK_VERSION),
+            Some(ZERO_GENESIS_VALIDATORS_ROOT),
+        );
+        assert_eq!(
+            req.to_signing_root(None),
+            compute_signing_root(m.deposit.clone(), domain)
+        );
+    }
+
+    #[test]
+    fn test_network_domains_use_fork_info_root_even_if_zero() {
+        for req in [randao_reveal_request, bls_to_execution_change_request_under] {
+            let zero = req(&ZERO_GENESIS_VALIDATORS_ROOT);
+            let network = req(&[42; 32]);
+            // An explicit zero root is signed with as is, not rejected or replaced
+            assert_eq!(
+                zero.genesis_validators_root(),
+                Some(ZERO_GENESIS_VALIDATORS_ROOT)
+            );
+            assert_eq!(network.genesis_validators_root(), Some([42; 32]));
+            assert_ne!(zero.to_signing_root(None), network.to_signing_root(None));
+        }
+
+        let domain = compute_domain(
+            DOMAIN_RANDAO,
+            Some(GENESIS_FORK_VERSION),
+            Some(ZERO_GENESIS_VALIDATORS_ROOT),
+        );
+        assert_eq!(
+            randao_reveal_request(&ZERO_GENESIS_VALIDATORS_ROOT).to_signing_root(None),
+            compute_signing_root(10_u64, domain)
+        );
+    }
+
+    #[test]
+    fn test_validate_domain() {
+        let allowed = AllowedDomains {
+            fork_versions: vec![GENESIS_FORK_VERSION],
+            genesis_validators_roots: vec![[42; 32]],
+        };
+        assert!(randao_reveal_request(&[42; 32])
+            .validate_domain(&allowed)
+            .is_ok());
+        let err = randao_reveal_request(&[43; 32])
+            .validate_domain(&allowed)
+            .unwrap_err();
+        assert!(err.to_string().contains(&hex::encode([43; 32])));
+        assert!(deposit_request().validate_domain(&allowed).is_ok());
+
+        let capella = bls_to_execution_change_request_under(&[42; 32]);
+        assert!(capella.validate_domain(&allowed).is_ok());
+        let allowed = AllowedDomains {
+            fork_versions: vec![[3, 0, 0, 0]],
+            ..Default::default()
+        };
+        assert!(randao_reveal_request(&[43; 32])
+            .validate_domain(&allowed)
+            .is_err());
+        assert!(deposit_request().validate_domain(&allowed).is_err());
+        // Signed with the signer's own genesis fork version, only its root comes from the request
+        assert!(capella.validate_domain(&allowed).is_ok());
+
+        assert!(randao_reveal_request(&[43; 32])
+            .validate_domain(&AllowedDomains::default())
+            .is_ok());
+    }
+
+    fn electra_aggregate_and_proof_request() -> BLSSignMsg {
+        aggregate_and_proof_v2_request("ELECTRA")
+    }
+
+    fn aggregate_and_proof_v2_request(version: &str) -> BLSSignMsg {
+        let req = format!(
+            r#"
+            {{
+                "fork_info": {{
+                    "fork": {{
+                        "previous_version": "0x04000000",
+                        "current_version": "0x05000000",
+                        "epoch": "364032"
+                    }},
+                    "genesis_validators_root": "0x{MAINNET_GENESIS_VALIDATORS_ROOT}"
+                }},
+                "aggregate_and_proof": {{
+                    "version": "{version}",
+                    "data": {{
+                        "aggregator_index": "5",
+                        "aggregate": {{
+                            "aggregation_bits": "0x0f",
+                            "data": {{
+                                "slot": "11649029",
+                                "index": "0",
+                                "beacon_block_root": "0x{d11}",
+                                "source": {{ "epoch": "364030", "root": "0x{d22}" }},
+                                "target": {{ "epoch": "364032", "root": "0x{d33}" }}
+                            }},
+                            "signature": "0x{aa}",
+                            "committee_bits": "0x0100000000000000"
+                        }},
+                        "selection_proof": "0x{bb}"
+                    }}
+                }}
+            }}"#,
+            d11 = "11".repeat(32),
+            d22 = "22".repeat(32),
+            d33 = "33".repeat(32),
+            aa = "aa".repeat(96),
+            bb = "bb".repeat(96),
+        );
+        BLSSignMsg::AGGREGATE_AND_PROOF_V2(serde_json::from_str(&req).unwrap())
+    }
+
+    #[test]
+    fn test_electra_aggregate_and_proof_signing_root() {
+        let req = electra_aggregate_and_proof_request();
+        assert_eq!(
+            hex::encode(req.to_signing_root(None)),
+            "3e19aeedeedb609b35aa6b76c20f33b5e539fed0fabc7858b34337e1c3f03a0c"
+        );
+    }
+
+    #[test]
+    fn test_pre_electra_aggregate_and_proof_v2_matches_v1() {
+        let aggregate = format!(
+            r#"{{
+                "aggregator_index": "5",
+                "aggregate": {{
+                    "aggregation_bits": "0x1234",
+                    "data": {{
+                        "slot": "750",
+                        "index": "1",
+                        "beacon_block_root": "0x{d11}",
+                        "source": {{ "epoch": "10", "root": "0x{d11}" }},
+                        "target": {{ "epoch": "12", "root": "0x{d11}" }}
+                    }},
+                    "signature": "0x{aa}"
+                }},
+                "selection_proof": "0x{aa}"
+            }}"#,
+            d11 = "11".repeat(32),
+            aa = "aa".repeat(96),
+        );
+        let fork_info = format!(
+            r#"{{
+                "fork": {{
+                    "previous_version": "0x03000000",
+                    "current_version": "0x04000000",
+                    "epoch": "0"
+                }},
+                "genesis_validators_root": "0x{MAINNET_GENESIS_VALIDATORS_ROOT}"
+            }}"#
+        );
+        let v1 = format!(r#"{{ "fork_info": {fork_info}, "aggregate_and_proof": {aggregate} }}"#);
+        let v1 = BLSSignMsg::AGGREGATE_AND_PROOF(serde_json::from_str(&v1).unwrap());
+        let v2 = format!(
+            r#"{{ "fork_info": {fork_info}, "aggregate_and_proof": {{ "version": "DENEB", "data": {aggregate} }} }}"#
+        );
+        let v2 = BLSSignMsg::AGGREGATE_AND_PROOF_V2(serde_json::from_str(&v2).unwrap());
+        assert_eq!(v1.to_signing_root(None), v2.to_signing_root(None));
+
+        // The Electra container does not accept a pre-Electra aggregate
+        let electra = format!(r#"{{ "version": "ELECTRA", "data": {aggregate} }}"#);
+        assert!(serde_json::from_str::<AggregateAndProofV2>(&electra).is_err());
+    }
+
+    #[test]
+    fn test_fulu_aggregate_and_proof_uses_electra_container() {
+        let fulu = aggregate_and_proof_v2_request("FULU");
+        match &fulu {
+            BLSSignMsg::AGGREGATE_AND_PROOF_V2(req) => assert!(matches!(
+                req.aggregate_and_proof.data,
+                VersionedAggregateAndProof::Electra(_)
+            )),
+            _ => unreachable!(),
         }
+        let electra = electra_aggregate_and_proof_request();
+        assert_eq!(fulu.to_signing_root(None), electra.to_signing_root(None));
     }
 }
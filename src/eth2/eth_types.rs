@@ -1,9 +1,9 @@
+use crate::eth2::quoted_u64;
 use num_bigint::BigUint;
 use serde::de::{self, Deserializer};
 use serde::ser::{self, Serializer};
 use serde::{Deserialize, Serialize};
 use serde_hex::{SerHex, StrictPfx};
-use serde_utils::quoted_u64;
 use ssz::{Decode, Encode};
 use ssz_derive::{Decode, Encode};
 use ssz_types::{typenum, BitList, BitVector, FixedVector, VariableList};
@@ -60,11 +60,17 @@ pub const DOMAIN_AGGREGATE_AND_PROOF: DomainType = [6_u8, 0_u8, 0_u8, 0_u8]; //
 pub const DOMAIN_SYNC_COMMITTEE: DomainType = [7_u8, 0_u8, 0_u8, 0_u8]; // '0x07000000'
 pub const DOMAIN_SYNC_COMMITTEE_SELECTION_PROOF: DomainType = [8_u8, 0_u8, 0_u8, 0_u8]; // '0x08000000'
 pub const DOMAIN_CONTRIBUTION_AND_PROOF: DomainType = [9_u8, 0_u8, 0_u8, 0_u8]; // '0x09000000'
+pub const DOMAIN_BLS_TO_EXECUTION_CHANGE: DomainType = [10_u8, 0_u8, 0_u8, 0_u8]; // '0x0A000000'
 pub const DOMAIN_APPLICATION_MASK: DomainType = [0_u8, 0_u8, 0_u8, 1_u8]; // '0x00000001'
 pub const DOMAIN_APPLICATION_BUILDER: DomainType = [0_u8, 0_u8, 0_u8, 1_u8]; // '0x00000001'
 
 pub const GENESIS_FORK_VERSION: Version = [0_u8, 0_u8, 0_u8, 0_u8]; // '0x00000000'
+/// The genesis_validators_root of the deposit and builder domains, which must verify before
+/// genesis and on every network sharing a genesis fork version. Network specific domains take
+/// the root from the request's fork_info, where zero is a valid root like any other.
+pub const ZERO_GENESIS_VALIDATORS_ROOT: Root = [0_u8; 32];
 pub const SLOTS_PER_EPOCH: u64 = 32;
+pub const SECONDS_PER_SLOT: u64 = 12;
 
 // altair
 #[allow(non_camel_case_types)]
@@ -92,19 +98,180 @@ pub type MAX_BLS_TO_EXECUTION_CHANGES = typenum::U16;
 #[allow(non_camel_case_types)]
 pub type MAX_WITHDRAWALS_PER_PAYLOAD = typenum::U16;
 
+// deneb
+pub type KZGCommitment = Bytes48;
+#[allow(non_camel_case_types)]
+pub type MAX_BLOB_COMMITMENTS_PER_BLOCK = typenum::U4096;
+
+// electra
+#[allow(non_camel_case_types)]
+pub type MAX_COMMITTEES_PER_SLOT = typenum::U64;
+#[allow(non_camel_case_types)]
+pub type MAX_VALIDATORS_PER_SLOT = typenum::U131072; // MAX_VALIDATORS_PER_COMMITTEE * MAX_COMMITTEES_PER_SLOT
+
+/// Position of the Capella fork in every known fork schedule
+const CAPELLA_FORK_INDEX: usize = 3;
+/// Position of the Deneb fork in every known fork schedule
+const DENEB_FORK_INDEX: usize = 4;
+
+/// Fork versions paired with their activation epochs, ordered by activation epoch
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ForkSchedule {
+    pub forks: Vec<(Version, Epoch)>,
+}
+
+impl ForkSchedule {
+    /// Returns the fork schedule of a known network identified by its genesis fork version.
+    pub fn for_genesis_fork_version(genesis_fork_version: Version) -> Option<Self> {
+        let forks = match genesis_fork_version {
+            // mainnet
+            [0x00, 0x00, 0x00, 0x00] => vec![
+                ([0x00, 0x00, 0x00, 0x00], 0),
+                ([0x01, 0x00, 0x00, 0x00], 74240),
+                ([0x02, 0x00, 0x00, 0x00], 144896),
+                ([0x03, 0x00, 0x00, 0x00], 194048),
+                ([0x04, 0x00, 0x00, 0x00], 269568),
+                ([0x05, 0x00, 0x00, 0x00], 364032),
+                ([0x06, 0x00, 0x00, 0x00], 411392),
+            ],
+            // goerli
+            [0x00, 0x00, 0x10, 0x20] => vec![
+                ([0x00, 0x00, 0x10, 0x20], 0),
+                ([0x01, 0x00, 0x10, 0x20], 36660),
+                ([0x02, 0x00, 0x10, 0x20], 112260),
+                ([0x03, 0x00, 0x10, 0x20], 162304),
+                ([0x04, 0x00, 0x10, 0x20], 231680),
+            ],
+            // sepolia
+            [0x90, 0x00, 0x00, 0x69] => vec![
+                ([0x90, 0x00, 0x00, 0x69], 0),
+                ([0x90, 0x00, 0x00, 0x70], 50),
+                ([0x90, 0x00, 0x00, 0x71], 100),
+                ([0x90, 0x00, 0x00, 0x72], 56832),
+                ([0x90, 0x00, 0x00, 0x73], 132608),
+                ([0x90, 0x00, 0x00, 0x74], 222464),
+                ([0x90, 0x00, 0x00, 0x75], 272640),
+            ],
+            // holesky
+            [0x01, 0x01, 0x70, 0x00] => vec![
+                ([0x01, 0x01, 0x70, 0x00], 0),
+                ([0x02, 0x01, 0x70, 0x00], 0),
+                ([0x03, 0x01, 0x70, 0x00], 0),
+                ([0x04, 0x01, 0x70, 0x00], 256),
+                ([0x05, 0x01, 0x70, 0x00], 29696),
+                ([0x06, 0x01, 0x70, 0x00], 115968),
+                ([0x07, 0x01, 0x70, 0x00], 165120),
+            ],
+            // hoodi
+            [0x10, 0x00, 0x09, 0x10] => vec![
+                ([0x10, 0x00, 0x09, 0x10], 0),
+                ([0x20, 0x00, 0x09, 0x10], 0),
+                ([0x30, 0x00, 0x09, 0x10], 0),
+                ([0x40, 0x00, 0x09, 0x10], 0),
+                ([0x50, 0x00, 0x09, 0x10], 0),
+                ([0x60, 0x00, 0x09, 0x10], 2048),
+                ([0x70, 0x00, 0x09, 0x10], 50688),
+            ],
+            _ => return None,
+        };
+        Some(ForkSchedule { forks })
+    }
+
+    /// Returns the fork version that is active at `epoch`
+    pub fn version_at_epoch(&self, epoch: Epoch) -> Option<Version> {
+        self.forks
+            .iter()
+            .filter(|(_, activation_epoch)| *activation_epoch <= epoch)
+            .last()
+            .map(|(version, _)| *version)
+    }
+
+    /// Returns the Capella fork version if `fork` is Deneb or a later fork, which voluntary exits
+    /// are signed with from then on (EIP-7044), None before Deneb. A current version the schedule
+    /// doesn't list counts as a later fork if it activated at or after Deneb, so exits keep
+    /// working through forks not added here yet. Errors if `fork` can't be placed.
+    pub fn voluntary_exit_fork_version(&self, fork: &Fork) -> anyhow::Result<Option<Version>> {
+        let (Some((capella_version, _)), Some((_, deneb_epoch))) = (
+            self.forks.get(CAPELLA_FORK_INDEX),
+            self.forks.get(DENEB_FORK_INDEX),
+        ) else {
+            anyhow::bail!("The fork schedule has no Deneb fork")
+        };
+        let deneb_or_later = match self
+            .forks
+            .iter()
+            .position(|(v, _)| *v == fork.current_version)
+        {
+            Some(position) => position >= DENEB_FORK_INDEX,
+            None if fork.epoch >= *deneb_epoch => true,
+            None => anyhow::bail!(
+                "Fork version 0x{} activated at epoch {} is not in the fork schedule, and predates Deneb",
+                hex::encode(fork.current_version),
+                fork.epoch
+            ),
+        };
+        Ok(deneb_or_later.then_some(*capella_version))
+    }
+}
+
+/// Returns the genesis time (unix seconds) of a known network identified by its genesis fork version.
+pub fn genesis_time_for_genesis_fork_version(genesis_fork_version: Version) -> Option<u64> {
+    match genesis_fork_version {
+        [0x00, 0x00, 0x00, 0x00] => Some(1606824023), // mainnet
+        [0x00, 0x00, 0x10, 0x20] => Some(1616508000), // goerli
+        [0x90, 0x00, 0x00, 0x69] => Some(1655733600), // sepolia
+        [0x01, 0x01, 0x70, 0x00] => Some(1695902400), // holesky
+        [0x10, 0x00, 0x09, 0x10] => Some(1742213400), // hoodi
+        _ => None,
+    }
+}
+
 // Custom deserializers
+
+/// Byte containers decoded from hex. Unlike `From<Vec<u8>>`, which pads or truncates, bytes that
+/// don't fit the container are refused.
+pub trait FromHexBytes: Sized {
+    fn from_hex_bytes(bytes: Vec<u8>) -> Result<Self, String>;
+}
+
+impl<N: typenum::Unsigned> FromHexBytes for FixedVector<u8, N> {
+    fn from_hex_bytes(bytes: Vec<u8>) -> Result<Self, String> {
+        if bytes.len() != N::to_usize() {
+            return Err(format!(
+                "Expected {} bytes, got {}",
+                N::to_usize(),
+                bytes.len()
+            ));
+        }
+        Ok(FixedVector::from(bytes))
+    }
+}
+
+impl<N: typenum::Unsigned> FromHexBytes for VariableList<u8, N> {
+    fn from_hex_bytes(bytes: Vec<u8>) -> Result<Self, String> {
+        let len = bytes.len();
+        VariableList::new(bytes)
+            .map_err(|_| format!("Expected at most {} bytes, got {len}", N::to_usize()))
+    }
+}
+
+/// Decodes a hex string, with or without its 0x prefix. The string is owned since JSON strings
+/// with escapes, or read back from a `serde_json::Value`, can't be borrowed.
+fn decode_hex_string<'de, D>(deserializer: D) -> Result<Vec<u8>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    let hex_string = String::deserialize(deserializer)?;
+    let hex_str: &str = strip_0x_prefix!(hex_string);
+    hex::decode(hex_str).map_err(|e| de::Error::custom(format!("Not valid hex: {:?}", e)))
+}
+
 pub fn from_hex_to_ssz_type<'de, D, T>(deserializer: D) -> Result<T, D::Error>
 where
     D: Deserializer<'de>,
-    T: From<Vec<u8>>,
+    T: FromHexBytes,
 {
-    let hex_str: &str = Deserialize::deserialize(deserializer)?;
-    let hex_str: &str = strip_0x_prefix!(hex_str);
-    let bytes = match hex::decode(hex_str) {
-        Ok(bs) => bs,
-        Err(e) => return Err(de::Error::custom(format!("Not valid hex: {:?}", e))),
-    };
-    Ok(T::from(bytes))
+    T::from_hex_bytes(decode_hex_string(deserializer)?).map_err(de::Error::custom)
 }
 
 pub fn to_hex_from_ssz_type<S, T>(data: &T, serializer: S) -> Result<S::Ok, S::Error>
@@ -121,12 +288,7 @@ where
     D: Deserializer<'de>,
     T: Decode,
 {
-    let hex_str: &str = Deserialize::deserialize(deserializer)?;
-    let hex_str: &str = strip_0x_prefix!(hex_str);
-    let bytes = match hex::decode(hex_str) {
-        Ok(bs) => bs,
-        Err(e) => return Err(de::Error::custom(format!("Not valid hex: {:?}", e))),
-    };
+    let bytes = decode_hex_string(deserializer)?;
     match T::from_ssz_bytes(&bytes.as_ssz_bytes()) {
         Ok(out) => Ok(out),
         Err(e) => {
@@ -145,16 +307,18 @@ where
     let decimal_str = String::deserialize(deserializer)?;
     let big_uint = BigUint::parse_bytes(decimal_str.as_bytes(), 10);
 
-    let bytes = match big_uint {
+    let mut bytes = match big_uint {
         Some(u) => u.to_u64_digits(),
         None => return Err(de::Error::custom("Invalid decimal string for U256")),
     };
 
-    if bytes.len() != 4 {
+    if bytes.len() > 4 {
         return Err(de::Error::custom(
             "Decimal string doesn't match the required length for U256",
         ));
     }
+    // to_u64_digits() drops the high zero limbs
+    bytes.resize(4, 0);
 
     let out: U256 = FixedVector::from(bytes);
     Ok(out)
@@ -168,11 +332,48 @@ where
     serializer.serialize_str(&out.to_str_radix(10))
 }
 
+pub fn from_hex_list_to_ssz_type<'de, D, T, N>(
+    deserializer: D,
+) -> Result<VariableList<T, N>, D::Error>
+where
+    D: Deserializer<'de>,
+    T: FromHexBytes,
+    N: typenum::Unsigned,
+{
+    let hex_strs: Vec<String> = Deserialize::deserialize(deserializer)?;
+    let mut out = Vec::with_capacity(hex_strs.len());
+    for hex_str in hex_strs.iter() {
+        let hex_str: &str = strip_0x_prefix!(hex_str);
+        match hex::decode(hex_str) {
+            Ok(bs) => out.push(T::from_hex_bytes(bs).map_err(de::Error::custom)?),
+            Err(e) => return Err(de::Error::custom(format!("Not valid hex: {:?}", e))),
+        }
+    }
+    VariableList::new(out)
+        .map_err(|e| de::Error::custom(format!("Too many list elements: {:?}", e)))
+}
+
+pub fn to_hex_list_from_ssz_type<S, T, N>(
+    data: &VariableList<T, N>,
+    serializer: S,
+) -> Result<S::Ok, S::Error>
+where
+    S: Serializer,
+    T: Encode,
+    N: typenum::Unsigned,
+{
+    let hex_strs: Vec<String> = data
+        .iter()
+        .map(|item| "0x".to_string() + &hex::encode(item.as_ssz_bytes()))
+        .collect();
+    hex_strs.serialize(serializer)
+}
+
 pub fn de_signing_root<'de, D>(deserializer: D) -> Result<Option<Root>, D::Error>
 where
     D: Deserializer<'de>,
 {
-    let hex_string: &str = Deserialize::deserialize(deserializer)?;
+    let hex_string = String::deserialize(deserializer)?;
     if hex_string.is_empty() {
         return Ok(None);
     }
@@ -230,6 +431,7 @@ pub struct ForkData {
 #[derive(Debug, Deserialize, Serialize, Encode, Decode, TreeHash, Clone, Default)]
 pub struct ForkInfo {
     pub fork: Fork,
+    /// Required, requests in a session that omit it have the session's filled in before parsing
     #[serde(with = "SerHex::<StrictPfx>")]
     pub genesis_validators_root: Root,
 }
@@ -385,6 +587,72 @@ pub struct SignedVoluntaryExit {
     pub signature: BLSSignature,
 }
 
+/// Fork versions and genesis_validators_roots signing requests may compute their domain from, so
+/// a mistyped fork_info for a custom network is refused instead of signed under the wrong domain.
+/// An empty list allows any value.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct AllowedDomains {
+    pub fork_versions: Vec<Version>,
+    pub genesis_validators_roots: Vec<Root>,
+}
+
+impl AllowedDomains {
+    /// Parses comma-separated lists of hex fork versions and genesis_validators_roots
+    pub fn parse(fork_versions: &str, genesis_validators_roots: &str) -> anyhow::Result<Self> {
+        Ok(AllowedDomains {
+            fork_versions: parse_hex_list(fork_versions)?,
+            genesis_validators_roots: parse_hex_list(genesis_validators_roots)?,
+        })
+    }
+
+    pub fn allows_fork_version(&self, fork_version: &Version) -> bool {
+        self.fork_versions.is_empty() || self.fork_versions.contains(fork_version)
+    }
+
+    pub fn allows_genesis_validators_root(&self, genesis_validators_root: &Root) -> bool {
+        self.genesis_validators_roots.is_empty()
+            || self
+                .genesis_validators_roots
+                .contains(genesis_validators_root)
+    }
+}
+
+/// Parses a comma-separated list of fixed-size hex values, with or without their 0x prefix
+fn parse_hex_list<const N: usize>(list: &str) -> anyhow::Result<Vec<[u8; N]>> {
+    list.split(',')
+        .map(str::trim)
+        .filter(|s| !s.is_empty())
+        .map(|s| {
+            let hex_str: &str = strip_0x_prefix!(s);
+            let bytes = hex::decode(hex_str).map_err(|e| anyhow::anyhow!("{s} is not hex: {e}"))?;
+            <[u8; N]>::try_from(bytes)
+                .map_err(|b| anyhow::anyhow!("{s} is {} bytes, expected {N}", b.len()))
+        })
+        .collect()
+}
+
+/// Inclusive bounds on the gas limit a validator registration may request
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct GasLimitBounds {
+    pub min: u64,
+    pub max: u64,
+}
+
+impl Default for GasLimitBounds {
+    fn default() -> Self {
+        GasLimitBounds {
+            min: crate::constants::DEFAULT_MIN_GAS_LIMIT,
+            max: crate::constants::DEFAULT_MAX_GAS_LIMIT,
+        }
+    }
+}
+
+impl GasLimitBounds {
+    pub fn contains(&self, gas_limit: u64) -> bool {
+        (self.min..=self.max).contains(&gas_limit)
+    }
+}
+
 #[derive(Debug, Deserialize, Serialize, Encode, Decode, TreeHash, Clone)]
 /// https://github.com/ethereum/builder-specs/blob/main/specs/bellatrix/builder.md#validatorregistrationv1
 /// used by Web3Signer type = "VALIDATOR_REGISTRATION"
@@ -418,6 +686,27 @@ pub struct Attestation {
     pub signature: BLSSignature,
 }
 
+#[derive(Debug, Deserialize, Serialize, Encode, Decode, TreeHash, Clone)]
+/// https://github.com/ethereum/consensus-specs/blob/dev/specs/electra/beacon-chain.md#attestation
+pub struct AttestationElectra {
+    #[serde(
+        deserialize_with = "from_hex_to_ssz_bits_type",
+        serialize_with = "to_hex_from_ssz_type"
+    )]
+    pub aggregation_bits: BitList<MAX_VALIDATORS_PER_SLOT>, // [Modified in Electra]
+    pub data: AttestationData,
+    #[serde(
+        deserialize_with = "from_hex_to_ssz_type",
+        serialize_with = "to_hex_from_ssz_type"
+    )]
+    pub signature: BLSSignature,
+    #[serde(
+        deserialize_with = "from_hex_to_ssz_bits_type",
+        serialize_with = "to_hex_from_ssz_type"
+    )]
+    pub committee_bits: BitVector<MAX_COMMITTEES_PER_SLOT>, // [New in Electra]
+}
+
 #[derive(Debug, Deserialize, Serialize, Encode, Decode, TreeHash, Clone)]
 pub struct SyncAggregate {
     #[serde(
@@ -500,6 +789,10 @@ pub struct ExecutionPayload {
     // Extra payload fields,
     #[serde(with = "SerHex::<StrictPfx>")]
     pub block_hash: Root, // Hash of execution block
+    #[serde(
+        deserialize_with = "from_hex_list_to_ssz_type",
+        serialize_with = "to_hex_list_from_ssz_type"
+    )]
     pub transactions: VariableList<Transaction, MAX_TRANSACTIONS_PER_PAYLOAD>,
     pub withdrawals: VariableList<Withdrawal, MAX_WITHDRAWALS_PER_PAYLOAD>, // [New in Capella]
 }
@@ -561,6 +854,131 @@ pub struct Withdrawal {
     pub amount: Gwei,
 }
 
+/// `BeaconBlockBody` and `ExecutionPayload` follow the Capella schema.
+/// https://github.com/ethereum/consensus-specs/blob/dev/specs/capella/beacon-chain.md#beaconblockbody
+pub type BeaconBlockCapella = BeaconBlock;
+
+impl BeaconBlock {
+    /// Returns the header committing to this block, used as the signing object for BLOCK_V2
+    pub fn to_block_header(&self) -> BeaconBlockHeader {
+        BeaconBlockHeader {
+            slot: self.slot,
+            proposer_index: self.proposer_index,
+            parent_root: self.parent_root,
+            state_root: self.state_root,
+            body_root: tree_hash::TreeHash::tree_hash_root(&self.body).to_fixed_bytes(),
+        }
+    }
+}
+
+#[derive(Debug, Deserialize, Serialize, Encode, Decode, TreeHash, Clone)]
+/// https://github.com/ethereum/consensus-specs/blob/dev/specs/deneb/beacon-chain.md#executionpayload
+pub struct ExecutionPayloadDeneb {
+    #[serde(with = "SerHex::<StrictPfx>")]
+    pub parent_hash: Root,
+    #[serde(
+        deserialize_with = "from_hex_to_ssz_type",
+        serialize_with = "to_hex_from_ssz_type"
+    )]
+    pub fee_recipient: ExecutionAddress,
+    #[serde(with = "SerHex::<StrictPfx>")]
+    pub state_root: Root,
+    #[serde(with = "SerHex::<StrictPfx>")]
+    pub receipts_root: Root,
+    #[serde(
+        deserialize_with = "from_hex_to_ssz_type",
+        serialize_with = "to_hex_from_ssz_type"
+    )]
+    pub logs_bloom: FixedVector<u8, BYTES_PER_LOGS_BLOOM>,
+    #[serde(with = "SerHex::<StrictPfx>")]
+    pub prev_randao: Root,
+    #[serde(with = "quoted_u64")]
+    pub block_number: u64,
+    #[serde(with = "quoted_u64")]
+    pub gas_limit: u64,
+    #[serde(with = "quoted_u64")]
+    pub gas_used: u64,
+    #[serde(with = "quoted_u64")]
+    pub timestamp: u64,
+    #[serde(
+        deserialize_with = "from_hex_to_ssz_type",
+        serialize_with = "to_hex_from_ssz_type"
+    )]
+    pub extra_data: VariableList<u8, MAX_EXTRA_DATA_BYTES>,
+    #[serde(
+        deserialize_with = "from_u256_string",
+        serialize_with = "to_u256_string"
+    )]
+    pub base_fee_per_gas: U256,
+    #[serde(with = "SerHex::<StrictPfx>")]
+    pub block_hash: Root,
+    #[serde(
+        deserialize_with = "from_hex_list_to_ssz_type",
+        serialize_with = "to_hex_list_from_ssz_type"
+    )]
+    pub transactions: VariableList<Transaction, MAX_TRANSACTIONS_PER_PAYLOAD>,
+    pub withdrawals: VariableList<Withdrawal, MAX_WITHDRAWALS_PER_PAYLOAD>,
+    #[serde(with = "quoted_u64")]
+    pub blob_gas_used: u64, // [New in Deneb]
+    #[serde(with = "quoted_u64")]
+    pub excess_blob_gas: u64, // [New in Deneb]
+}
+
+#[derive(Debug, Deserialize, Serialize, Encode, Decode, TreeHash, Clone)]
+/// https://github.com/ethereum/consensus-specs/blob/dev/specs/deneb/beacon-chain.md#beaconblockbody
+pub struct BeaconBlockBodyDeneb {
+    #[serde(
+        deserialize_with = "from_hex_to_ssz_type",
+        serialize_with = "to_hex_from_ssz_type"
+    )]
+    pub randao_reveal: BLSSignature,
+    pub eth1_data: Eth1Data,
+    #[serde(with = "SerHex::<StrictPfx>")]
+    pub graffiti: Bytes32,
+    pub proposer_slashings: VariableList<ProposerSlashing, MAX_PROPOSER_SLASHINGS>,
+    pub attester_slashings: VariableList<AttesterSlashing, MAX_ATTESTER_SLASHINGS>,
+    pub attestations: VariableList<Attestation, MAX_ATTESTATIONS>,
+    pub deposits: VariableList<Deposit, MAX_DEPOSITS>,
+    pub voluntary_exits: VariableList<SignedVoluntaryExit, MAX_VOLUNTARY_EXITS>,
+    pub sync_aggregate: SyncAggregate,
+    pub execution_payload: ExecutionPayloadDeneb, // [Modified in Deneb]
+    pub bls_to_execution_changes:
+        VariableList<SignedBLSToExecutionChange, MAX_BLS_TO_EXECUTION_CHANGES>,
+    #[serde(
+        deserialize_with = "from_hex_list_to_ssz_type",
+        serialize_with = "to_hex_list_from_ssz_type"
+    )]
+    pub blob_kzg_commitments: VariableList<KZGCommitment, MAX_BLOB_COMMITMENTS_PER_BLOCK>, // [New in Deneb]
+}
+
+#[derive(Debug, Deserialize, Serialize, Encode, Decode, TreeHash, Clone)]
+/// https://github.com/ethereum/consensus-specs/blob/dev/specs/phase0/beacon-chain.md#beaconblock
+/// with the Deneb block body.
+pub struct BeaconBlockDeneb {
+    #[serde(with = "quoted_u64")]
+    pub slot: Slot,
+    #[serde(with = "quoted_u64")]
+    pub proposer_index: ValidatorIndex,
+    #[serde(with = "SerHex::<StrictPfx>")]
+    pub parent_root: Root,
+    #[serde(with = "SerHex::<StrictPfx>")]
+    pub state_root: Root,
+    pub body: BeaconBlockBodyDeneb,
+}
+
+impl BeaconBlockDeneb {
+    /// Returns the header committing to this block, used as the signing object for BLOCK_V2
+    pub fn to_block_header(&self) -> BeaconBlockHeader {
+        BeaconBlockHeader {
+            slot: self.slot,
+            proposer_index: self.proposer_index,
+            parent_root: self.parent_root,
+            state_root: self.state_root,
+            body_root: tree_hash::TreeHash::tree_hash_root(&self.body).to_fixed_bytes(),
+        }
+    }
+}
+
 #[derive(Debug, Deserialize, Serialize, Encode, Decode, TreeHash, Clone)]
 /// https://github.com/ethereum/consensus-specs/blob/dev/specs/phase0/validator.md#aggregateandproof
 /// used by Web3Signer type = "AGGREGATE_AND_PROOF"
@@ -575,6 +993,73 @@ pub struct AggregateAndProof {
     pub selection_proof: BLSSignature,
 }
 
+#[derive(Debug, Deserialize, Serialize, Encode, Decode, TreeHash, Clone)]
+/// https://github.com/ethereum/consensus-specs/blob/dev/specs/electra/validator.md#aggregateandproof
+pub struct AggregateAndProofElectra {
+    #[serde(with = "quoted_u64")]
+    pub aggregator_index: ValidatorIndex,
+    pub aggregate: AttestationElectra, // [Modified in Electra]
+    #[serde(
+        deserialize_with = "from_hex_to_ssz_type",
+        serialize_with = "to_hex_from_ssz_type"
+    )]
+    pub selection_proof: BLSSignature,
+}
+
+/// The aggregate of a Web3Signer "AGGREGATE_AND_PROOF_V2" request, whose SSZ container
+/// depends on the fork named by `version`
+#[derive(Deserialize, Serialize, Debug)]
+#[serde(try_from = "AggregateAndProofV2Raw")]
+pub struct AggregateAndProofV2 {
+    pub version: String,
+    pub data: VersionedAggregateAndProof,
+}
+
+#[derive(Serialize, Debug)]
+#[serde(untagged)]
+pub enum VersionedAggregateAndProof {
+    /// Phase0 through Deneb
+    Phase0(AggregateAndProof),
+    Electra(AggregateAndProofElectra),
+}
+
+impl VersionedAggregateAndProof {
+    pub fn attestation_data(&self) -> &AttestationData {
+        match self {
+            VersionedAggregateAndProof::Phase0(a) => &a.aggregate.data,
+            VersionedAggregateAndProof::Electra(a) => &a.aggregate.data,
+        }
+    }
+}
+
+#[derive(Deserialize)]
+struct AggregateAndProofV2Raw {
+    version: String,
+    data: serde_json::Value,
+}
+
+impl TryFrom<AggregateAndProofV2Raw> for AggregateAndProofV2 {
+    type Error = String;
+
+    fn try_from(raw: AggregateAndProofV2Raw) -> Result<Self, Self::Error> {
+        let data = match raw.version.to_uppercase().as_str() {
+            "PHASE0" | "ALTAIR" | "BELLATRIX" | "CAPELLA" | "DENEB" => {
+                serde_json::from_value(raw.data).map(VersionedAggregateAndProof::Phase0)
+            }
+            // Fulu kept the Electra attestation container
+            "ELECTRA" | "FULU" => {
+                serde_json::from_value(raw.data).map(VersionedAggregateAndProof::Electra)
+            }
+            v => return Err(format!("Aggregate not supported for version {v}")),
+        }
+        .map_err(|e| format!("Invalid {} aggregate: {e}", raw.version))?;
+        Ok(AggregateAndProofV2 {
+            version: raw.version,
+            data,
+        })
+    }
+}
+
 #[derive(Debug, Deserialize, Serialize, Encode, Decode, TreeHash, Clone)]
 /// https://github.com/ethereum/consensus-specs/blob/dev/specs/altair/validator.md#synccommitteemessage
 /// used by Web3Signer type = "SYNC_COMMITTEE_MESSAGE"
@@ -679,12 +1164,61 @@ pub struct BlockV2Request {
     pub beacon_block: BlockV2RequestWrapper,
 }
 
+/// Web3Signer sends either the `block_header` or, for Capella and later, the full `block`.
+/// A full block is reduced to its header by hashing the body with the SSZ schema of `version`.
 #[derive(Deserialize, Serialize, Debug)]
+#[serde(try_from = "BlockV2RequestWrapperRaw")]
 pub struct BlockV2RequestWrapper {
     pub version: String,
     pub block_header: BeaconBlockHeader,
 }
 
+#[derive(Deserialize)]
+struct BlockV2RequestWrapperRaw {
+    version: String,
+    block_header: Option<BeaconBlockHeader>,
+    block: Option<serde_json::Value>,
+}
+
+impl TryFrom<BlockV2RequestWrapperRaw> for BlockV2RequestWrapper {
+    type Error = String;
+
+    fn try_from(raw: BlockV2RequestWrapperRaw) -> Result<Self, Self::Error> {
+        let from_block = match raw.block {
+            None => None,
+            Some(block) => {
+                let header = match raw.version.to_uppercase().as_str() {
+                    "CAPELLA" => serde_json::from_value::<BeaconBlockCapella>(block)
+                        .map(|b| b.to_block_header()),
+                    "DENEB" => serde_json::from_value::<BeaconBlockDeneb>(block)
+                        .map(|b| b.to_block_header()),
+                    v => return Err(format!("Full block not supported for version {v}")),
+                }
+                .map_err(|e| format!("Invalid {} block: {e}", raw.version))?;
+                Some(header)
+            }
+        };
+
+        let block_header = match (raw.block_header, from_block) {
+            (Some(h), None) | (None, Some(h)) => h,
+            (Some(h), Some(b)) => {
+                if tree_hash::TreeHash::tree_hash_root(&h)
+                    != tree_hash::TreeHash::tree_hash_root(&b)
+                {
+                    return Err("block_header does not match block".to_string());
+                }
+                h
+            }
+            (None, None) => return Err("Expected block_header or block".to_string()),
+        };
+
+        Ok(BlockV2RequestWrapper {
+            version: raw.version,
+            block_header,
+        })
+    }
+}
+
 #[derive(Deserialize, Serialize, Debug)]
 #[allow(non_snake_case)]
 pub struct AttestationRequest {
@@ -721,6 +1255,18 @@ pub struct AggregateAndProofRequest {
     pub aggregate_and_proof: AggregateAndProof,
 }
 
+#[derive(Deserialize, Serialize, Debug)]
+#[allow(non_snake_case)]
+pub struct AggregateAndProofV2Request {
+    pub fork_info: ForkInfo,
+    #[serde(default)]
+    #[serde(deserialize_with = "de_signing_root")]
+    #[serde(serialize_with = "se_signing_root")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub signingRoot: Option<Root>,
+    pub aggregate_and_proof: AggregateAndProofV2,
+}
+
 #[derive(Deserialize, Serialize, Debug)]
 #[allow(non_snake_case)]
 pub struct AggregationSlotRequest {
@@ -820,6 +1366,18 @@ pub struct SyncCommitteeContributionAndProofRequest {
     pub contribution_and_proof: ContributionAndProof,
 }
 
+#[derive(Deserialize, Serialize, Debug)]
+#[allow(non_snake_case)]
+pub struct BlsToExecutionChangeRequest {
+    pub fork_info: ForkInfo,
+    #[serde(default)]
+    #[serde(deserialize_with = "de_signing_root")]
+    #[serde(serialize_with = "se_signing_root")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub signingRoot: Option<Root>,
+    pub bls_to_execution_change: BLSToExecutionChange,
+}
+
 #[derive(Deserialize, Serialize, Debug)]
 #[allow(non_snake_case)]
 pub struct ValidatorRegistrationRequest {
@@ -942,4 +1500,103 @@ mod serialization_tests {
         );
         Ok(())
     }
+
+    #[test]
+    fn test_allowed_domains() {
+        let allowed =
+            AllowedDomains::parse("0x10000038, 10000039", &format!("0x{}", "2a".repeat(32)))
+                .unwrap();
+        assert_eq!(
+            allowed.fork_versions,
+            vec![[0x10, 0, 0, 0x38], [0x10, 0, 0, 0x39]]
+        );
+        assert!(allowed.allows_fork_version(&[0x10, 0, 0, 0x39]));
+        assert!(!allowed.allows_fork_version(&[0x10, 0, 0, 0x83]));
+        assert!(allowed.allows_genesis_validators_root(&[0x2a; 32]));
+        assert!(!allowed.allows_genesis_validators_root(&[0x2b; 32]));
+
+        // Unset lists allow anything
+        let any = AllowedDomains::parse("", " ").unwrap();
+        assert_eq!(any, AllowedDomains::default());
+        assert!(any.allows_fork_version(&[0x10, 0, 0, 0x83]));
+        assert!(any.allows_genesis_validators_root(&[0x2b; 32]));
+
+        assert!(AllowedDomains::parse("0x100000", "").is_err());
+        assert!(AllowedDomains::parse("0x1000003g", "").is_err());
+        assert!(AllowedDomains::parse("", "0x2a2a").is_err());
+    }
+
+    const PUBKEY: &str = "0x8349434ad0700e79be65c0c7043945df426bd6d7e288c16671df69d822344f1b0ce8de80360a50550ad782b68035cb18";
+
+    fn deposit_message(pubkey: &str, withdrawal_credentials: &str, amount: &str) -> String {
+        format!(
+            r#"{{"pubkey":"{pubkey}","withdrawal_credentials":"{withdrawal_credentials}","amount":{amount}}}"#
+        )
+    }
+
+    #[test]
+    fn test_malformed_hex_is_refused() {
+        let root = format!("0x{}", "04".repeat(32));
+        assert!(
+            serde_json::from_str::<DepositMessage>(&deposit_message(PUBKEY, &root, "1")).is_ok()
+        );
+
+        let malformed = [
+            // Truncated hex
+            deposit_message(&PUBKEY[..PUBKEY.len() - 1], &root, "1"),
+            deposit_message(PUBKEY, &root[..root.len() - 1], "1"),
+            // A byte short or over, previously padded or cut to fit
+            deposit_message(&PUBKEY[..PUBKEY.len() - 2], &root, "1"),
+            deposit_message(&format!("{PUBKEY}18"), &root, "1"),
+            deposit_message(PUBKEY, &root[..root.len() - 2], "1"),
+            deposit_message(PUBKEY, &format!("{root}73"), "1"),
+            deposit_message("0x", &root, "1"),
+            deposit_message("0xzz", &root, "1"),
+            // Out of range integers
+            deposit_message(PUBKEY, &root, r#""18446744073709551616""#),
+            deposit_message(PUBKEY, &root, "18446744073709551616"),
+            deposit_message(PUBKEY, &root, "1e30"),
+            deposit_message(PUBKEY, &root, "-1"),
+            deposit_message(PUBKEY, &root, r#""""#),
+        ];
+        for req in malformed {
+            assert!(
+                serde_json::from_str::<DepositMessage>(&req).is_err(),
+                "{req}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_hex_bytes_fit_their_container() {
+        assert!(BLSPubkey::from_hex_bytes(vec![0; 48]).is_ok());
+        assert!(BLSPubkey::from_hex_bytes(vec![0; 47]).is_err());
+        assert!(BLSPubkey::from_hex_bytes(vec![0; 49]).is_err());
+        assert!(VariableList::<u8, MAX_EXTRA_DATA_BYTES>::from_hex_bytes(vec![0; 32]).is_ok());
+        assert!(VariableList::<u8, MAX_EXTRA_DATA_BYTES>::from_hex_bytes(vec![0; 33]).is_err());
+    }
+
+    #[test]
+    fn test_hex_fields_need_not_be_borrowed() -> Result<()> {
+        // Escaped strings and `serde_json::Value`s can't be borrowed from
+        let escaped = PUBKEY.replacen("0x", "\\u0030x", 1);
+        let root = format!("0x{}", "04".repeat(32));
+        let v: DepositMessage = serde_json::from_str(&deposit_message(&escaped, &root, "1"))?;
+        assert_eq!(hex::encode(&v.pubkey[..]), PUBKEY[2..]);
+
+        let value: serde_json::Value = serde_json::from_str(&deposit_message(PUBKEY, &root, "1"))?;
+        let v: DepositMessage = serde_json::from_value(value)?;
+        assert_eq!(hex::encode(&v.pubkey[..]), PUBKEY[2..]);
+
+        let v: RandaoRevealRequest = serde_json::from_value(serde_json::json!({
+            "fork_info": {
+                "fork": {"previous_version": "0x00000000", "current_version": "0x00000000", "epoch": "0"},
+                "genesis_validators_root": root
+            },
+            "signingRoot": root,
+            "randao_reveal": {"epoch": "1"}
+        }))?;
+        assert_eq!(v.signingRoot, Some([4; 32]));
+        Ok(())
+    }
 }
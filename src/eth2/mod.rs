@@ -1,3 +1,5 @@
 pub mod eth_signing;
 pub mod eth_types;
+pub mod quoted_u64;
 pub mod slash_protection;
+pub mod ssz_request;
@@ -0,0 +1,113 @@
+//! Serde for the Beacon API's quoted integers. They're always written as JSON strings (`"10"`),
+//! but read from either a string or a number since not every client quotes them.
+
+use serde::{de, Deserializer, Serializer};
+use std::fmt;
+
+pub fn serialize<S>(value: &u64, serializer: S) -> Result<S::Ok, S::Error>
+where
+    S: Serializer,
+{
+    serializer.serialize_str(&value.to_string())
+}
+
+pub fn deserialize<'de, D>(deserializer: D) -> Result<u64, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    deserializer.deserialize_any(QuotedU64Visitor)
+}
+
+struct QuotedU64Visitor;
+
+impl<'de> de::Visitor<'de> for QuotedU64Visitor {
+    type Value = u64;
+
+    fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("an unsigned integer, quoted or not")
+    }
+
+    fn visit_str<E: de::Error>(self, s: &str) -> Result<u64, E> {
+        s.parse()
+            .map_err(|_| E::invalid_value(de::Unexpected::Str(s), &self))
+    }
+
+    fn visit_u64<E: de::Error>(self, v: u64) -> Result<u64, E> {
+        Ok(v)
+    }
+
+    fn visit_i64<E: de::Error>(self, v: i64) -> Result<u64, E> {
+        u64::try_from(v).map_err(|_| E::invalid_value(de::Unexpected::Signed(v), &self))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::eth2::eth_signing::BLSSignMsg;
+    use crate::eth2::eth_types::{AggregationSlot, RandaoReveal, ValidatorRegistration};
+
+    const PUBKEY: &str = "0x8349434ad0700e79be65c0c7043945df426bd6d7e288c16671df69d822344f1b0ce8de80360a50550ad782b68035cb18";
+    const FEE_RECIPIENT: &str = "0x2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a";
+
+    fn registration(gas_limit: &str, timestamp: &str) -> ValidatorRegistration {
+        serde_json::from_str(&format!(
+            r#"{{"fee_recipient":"{FEE_RECIPIENT}","gas_limit":{gas_limit},"timestamp":{timestamp},"pubkey":"{PUBKEY}"}}"#
+        ))
+        .unwrap()
+    }
+
+    #[test]
+    fn test_slot_and_epoch_quoted_or_not() {
+        for slot in [r#"{"slot":"10"}"#, r#"{"slot":10}"#] {
+            let got: AggregationSlot = serde_json::from_str(slot).unwrap();
+            assert_eq!(got.slot, 10);
+        }
+        for epoch in [r#"{"epoch":"3"}"#, r#"{"epoch":3}"#] {
+            let got: RandaoReveal = serde_json::from_str(epoch).unwrap();
+            assert_eq!(got.epoch, 3);
+        }
+    }
+
+    #[test]
+    fn test_gas_limit_and_timestamp_quoted_or_not() {
+        for (gas_limit, timestamp) in [
+            (r#""30000000""#, r#""1700000000""#),
+            ("30000000", "1700000000"),
+        ] {
+            let got = registration(gas_limit, timestamp);
+            assert_eq!(got.gas_limit, 30_000_000);
+            assert_eq!(got.timestamp, 1_700_000_000);
+        }
+    }
+
+    #[test]
+    fn test_always_serialized_quoted() {
+        let got = serde_json::to_value(registration("30000000", "1700000000")).unwrap();
+        assert_eq!(got["gas_limit"], "30000000");
+        assert_eq!(got["timestamp"], "1700000000");
+    }
+
+    #[test]
+    fn test_unquoted_ints_in_tagged_sign_request() {
+        // The request enum is internally tagged, so fields are buffered before they're visited
+        let req = r#"{
+            "type": "AGGREGATION_SLOT",
+            "fork_info": {
+                "fork": {"previous_version": "0x00000000", "current_version": "0x00000000", "epoch": 0},
+                "genesis_validators_root": "0x0000000000000000000000000000000000000000000000000000000000000000"
+            },
+            "aggregation_slot": {"slot": 119}
+        }"#;
+        match serde_json::from_str::<BLSSignMsg>(req).unwrap() {
+            BLSSignMsg::AGGREGATION_SLOT(req) => assert_eq!(req.aggregation_slot.slot, 119),
+            _ => panic!("Expected an AGGREGATION_SLOT request"),
+        }
+    }
+
+    #[test]
+    fn test_rejects_negative_and_garbage() {
+        assert!(serde_json::from_str::<AggregationSlot>(r#"{"slot":-1}"#).is_err());
+        assert!(serde_json::from_str::<AggregationSlot>(r#"{"slot":"ten"}"#).is_err());
+        assert!(serde_json::from_str::<AggregationSlot>(r#"{"slot":1.5}"#).is_err());
+    }
+}
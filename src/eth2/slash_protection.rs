@@ -4,18 +4,22 @@ use super::eth_types::{
     de_signing_root, from_hex_to_ssz_type, se_signing_root, to_hex_from_ssz_type, BLSPubkey, Epoch,
     Root, Slot,
 };
-use crate::constants::SLASHING_PROTECTION_DIR;
+use crate::io::config::{hex_file_path, Config, SlashingProtectionBackend};
 
+use crate::eth2::quoted_u64;
 use anyhow::{bail, Context, Result};
 use hex;
 use log::{debug, error};
 use serde::{Deserialize, Serialize};
 use serde_hex::{SerHex, StrictPfx};
-use serde_utils::quoted_u64;
 use ssz::Encode;
 use ssz_types::FixedVector;
-use std::fs;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::Duration;
+
+#[cfg(feature = "sqlite")]
+pub mod sqlite;
 
 #[derive(Serialize, Deserialize, Debug)]
 pub struct SlashingProtectionMetaData {
@@ -51,6 +55,11 @@ impl SlashingProtectionData {
         Ok(SlashingProtectionData::new(pk_bytes))
     }
 
+    /// True if nothing was ever signed or imported for the key
+    pub fn is_empty(&self) -> bool {
+        self.signed_blocks.is_empty() && self.signed_attestations.is_empty()
+    }
+
     pub fn get_latest_signed_block_slot(&self) -> Slot {
         match self.signed_blocks.iter().max_by_key(|s| s.slot) {
             None => 0,
@@ -127,26 +136,409 @@ impl SlashingProtectionData {
         Ok(())
     }
 
+    /// Saves this data to the configured slashing protection store
     pub fn write(&self) -> Result<()> {
-        let fname = hex::encode(self.pubkey.as_ssz_bytes());
-        let file_path: PathBuf = [SLASHING_PROTECTION_DIR, &fname].iter().collect();
-        if let Some(p) = file_path.parent() {
-            fs::create_dir_all(p).with_context(|| "Failed to create slashing dir")?
-        };
-        let json = serde_json::to_string(&self)?;
-        debug!("Writing Slash Protection DB:\n{json}");
-        fs::write(&file_path, json).with_context(|| "failed to write protection data")
+        store()?.put(self)
     }
 
+    /// Loads the data of `pk_hex` from the configured slashing protection store
     pub fn read(pk_hex: &str) -> Result<Self> {
+        store()?.get(pk_hex)
+    }
+
+    /// Merges this interchange entry into the configured slashing protection store, see
+    /// `SlashingProtectionDB::import`. Callers serving signing requests must hold the key's lock.
+    pub fn import(&self) -> Result<SlashingProtectionImportResult> {
+        Ok(self.import_into(store()?.as_ref()))
+    }
+
+    /// Loads the data of `pk_hex` from the configured slashing protection store, or None if the
+    /// store holds no data for it
+    pub fn try_read(pk_hex: &str) -> Result<Option<Self>> {
+        let store = store()?;
+        if !store.contains(pk_hex)? {
+            return Ok(None);
+        }
+        store.get(pk_hex).map(Some)
+    }
+}
+
+/// Persistence of the per-key slashing protection histories
+pub trait SlashingProtectionStore {
+    /// Loads the data of `pk_hex`, erroring if none was saved
+    fn get(&self, pk_hex: &str) -> Result<SlashingProtectionData>;
+
+    /// Returns true if data was saved for `pk_hex`
+    fn contains(&self, pk_hex: &str) -> Result<bool>;
+
+    /// Saves `data`, replacing whatever was saved for the same pubkey
+    fn put(&self, data: &SlashingProtectionData) -> Result<()>;
+
+    /// Appends a signed block to the history of `pk_hex`, erroring if it is slashable
+    fn record_block(&self, pk_hex: &str, block: SignedBlockSlot) -> Result<()> {
+        let mut data = self.get(pk_hex)?;
+        data.new_block(block, crate::constants::ALLOW_GROWABLE_SLASH_PROTECTION_DB)?;
+        self.put(&data)
+    }
+
+    /// Appends a signed attestation to the history of `pk_hex`, erroring if it is slashable
+    fn record_attestation(&self, pk_hex: &str, attest: SignedAttestationEpochs) -> Result<()> {
+        let mut data = self.get(pk_hex)?;
+        data.new_attestation(attest, crate::constants::ALLOW_GROWABLE_SLASH_PROTECTION_DB)?;
+        self.put(&data)
+    }
+
+    /// Merges every entry of an EIP-3076 interchange, see `SlashingProtectionDB::import`
+    fn import_interchange(&self, db: &SlashingProtectionDB) -> Vec<SlashingProtectionImportResult> {
+        db.data
+            .iter()
+            .map(|entry| entry.import_into(self))
+            .collect()
+    }
+}
+
+/// Keeps every key's history in its own JSON file in the configured storage, named after the hex
+/// pubkey
+pub struct JsonFileStore {
+    dir: PathBuf,
+}
+
+impl JsonFileStore {
+    pub fn new(dir: impl Into<PathBuf>) -> Self {
+        JsonFileStore { dir: dir.into() }
+    }
+}
+
+impl SlashingProtectionStore for JsonFileStore {
+    fn get(&self, pk_hex: &str) -> Result<SlashingProtectionData> {
         let pk_hex: String = strip_0x_prefix!(pk_hex);
-        let file_path: PathBuf = [SLASHING_PROTECTION_DIR, &pk_hex].iter().collect();
-        let json_vec = fs::read(file_path)?;
+        let file_path: PathBuf = hex_file_path(&self.dir, &pk_hex)?;
+        let json_vec = crate::io::key_management::read_file(&file_path)?;
         let json =
             serde_json::from_slice(&json_vec).with_context(|| "failed to read protection data")?;
-        debug!("Reading Slash Protection DB:\n{:#?}", json);
+        debug!(
+            "Reading Slash Protection DB:\n{}",
+            String::from_utf8_lossy(&json_vec)
+        );
         Ok(json)
     }
+
+    fn contains(&self, pk_hex: &str) -> Result<bool> {
+        let pk_hex: String = strip_0x_prefix!(pk_hex);
+        Ok(crate::io::key_management::file_exists(&hex_file_path(
+            &self.dir, &pk_hex,
+        )?))
+    }
+
+    fn put(&self, data: &SlashingProtectionData) -> Result<()> {
+        let fname = hex::encode(data.pubkey.as_ssz_bytes());
+        let file_path: PathBuf = hex_file_path(&self.dir, &fname)?;
+        let json = serde_json::to_string(data)?;
+        debug!("Writing Slash Protection DB:\n{json}");
+        crate::io::key_management::write_atomic(&file_path, json)
+            .with_context(|| "failed to write protection data")
+    }
+}
+
+/// SQLite databases opened so far, by path. Each is opened once and shared by every caller, rather
+/// than reconnecting and re-running the schema on each signing.
+#[cfg(feature = "sqlite")]
+static SQLITE_STORES: std::sync::Mutex<Vec<(PathBuf, Arc<sqlite::SqliteStore>)>> =
+    std::sync::Mutex::new(Vec::new());
+
+/// Returns the SQLite database at `path`, opening it on first use
+#[cfg(feature = "sqlite")]
+fn sqlite_store(path: PathBuf) -> Result<Arc<sqlite::SqliteStore>> {
+    let mut stores = SQLITE_STORES
+        .lock()
+        .expect("slashing database list poisoned");
+    if let Some((_, store)) = stores.iter().find(|(p, _)| *p == path) {
+        return Ok(store.clone());
+    }
+    let store = Arc::new(sqlite::SqliteStore::open(&path)?);
+    stores.push((path, store.clone()));
+    Ok(store)
+}
+
+/// Returns the slashing protection store selected by the current config
+pub fn store() -> Result<Arc<dyn SlashingProtectionStore>> {
+    let config = Config::current();
+    match config.slashing_protection_backend {
+        SlashingProtectionBackend::Json => Ok(Arc::new(JsonFileStore::new(
+            config.slashing_protection_dir(),
+        ))),
+        SlashingProtectionBackend::Sqlite if config.storage.is_memory() => {
+            bail!("The SQLite slashing protection backend can't be kept in memory")
+        }
+        #[cfg(feature = "sqlite")]
+        SlashingProtectionBackend::Sqlite => {
+            Ok(sqlite_store(config.slashing_protection_sqlite_path())?)
+        }
+        #[cfg(not(feature = "sqlite"))]
+        SlashingProtectionBackend::Sqlite => {
+            bail!("The SQLite slashing protection backend requires the sqlite feature")
+        }
+    }
+}
+
+/// Default time between two backups of the slashing protection histories
+pub const DEFAULT_SLASH_PROTECTION_BACKUP_INTERVAL: Duration = Duration::from_secs(60);
+
+/// Keeps a copy of every key's slashing protection history in a secondary dir, one JSON file per
+/// key, to restore from if the primary store is lost or corrupted
+#[derive(Clone, Debug)]
+pub struct SlashProtectionBackup {
+    dir: PathBuf,
+}
+
+impl SlashProtectionBackup {
+    pub fn new(dir: impl Into<PathBuf>) -> Self {
+        SlashProtectionBackup { dir: dir.into() }
+    }
+
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    /// Atomically copies the history of every saved key to the backup dir. A backup is never
+    /// replaced by a history with lower high-water marks, so rolling back the primary doesn't roll
+    /// back the backup with it. Returns the number of keys backed up.
+    pub fn backup(&self) -> Result<usize> {
+        let store = store()?;
+        let backup = JsonFileStore::new(&self.dir);
+        let mut backed_up = 0;
+        for pk_hex in crate::io::key_management::list_bls_keys()? {
+            // Keys that never signed have nothing to back up
+            if !store.contains(&pk_hex)? {
+                continue;
+            }
+            let data = match store.get(&pk_hex) {
+                Ok(data) => data,
+                Err(e) => {
+                    error!(
+                        "Not backing up unreadable slash protection of {pk_hex}: {:?}",
+                        e
+                    );
+                    continue;
+                }
+            };
+            if let Ok(saved) = backup.get(&pk_hex) {
+                if !HighWaterMarks::from(&data).covers(&HighWaterMarks::from(&saved)) {
+                    error!("Not backing up slash protection of {pk_hex} behind its backup");
+                    continue;
+                }
+            }
+            backup.put(&data)?;
+            backed_up += 1;
+        }
+        Ok(backed_up)
+    }
+
+    /// Backs up every `interval` until the process exits
+    pub async fn backup_every(self, interval: Duration) {
+        let mut ticker = tokio::time::interval(interval);
+        loop {
+            ticker.tick().await;
+            if let Err(e) = self.backup() {
+                error!("Failed to back up slash protection: {:?}", e);
+            }
+        }
+    }
+
+    /// Restores the history of every backed up key whose primary history is missing or
+    /// unreadable. A readable primary is only advanced to the backup's high-water marks where
+    /// they are higher, as an interchange import would, so the safer marks always win.
+    pub fn restore(&self) -> Result<Vec<SlashingProtectionImportResult>> {
+        let store = store()?;
+        let backup = JsonFileStore::new(&self.dir);
+        let mut results = vec![];
+        for pk_hex in Config::current().storage.list(&self.dir)? {
+            let data = match backup.get(&pk_hex) {
+                Ok(data) => data,
+                Err(e) => {
+                    error!("Unreadable slash protection backup of {pk_hex}: {:?}", e);
+                    results.push(SlashingProtectionImportResult::new(
+                        &pk_hex,
+                        SlashingProtectionImportStatus::Error,
+                        &format!("Unreadable backup, {:?}", e),
+                    ));
+                    continue;
+                }
+            };
+            let primary_readable = store.contains(&pk_hex)? && store.get(&pk_hex).is_ok();
+            if primary_readable {
+                results.push(data.import_into(&*store));
+                continue;
+            }
+            results.push(match store.put(&data) {
+                Ok(()) => SlashingProtectionImportResult::new(
+                    &pk_hex,
+                    SlashingProtectionImportStatus::Imported,
+                    "Restored from backup",
+                ),
+                Err(e) => SlashingProtectionImportResult::new(
+                    &pk_hex,
+                    SlashingProtectionImportStatus::Error,
+                    &format!("{:?}", e),
+                ),
+            });
+        }
+        Ok(results)
+    }
+}
+
+/// The latest block slot and attestation epochs a key has signed
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct HighWaterMarks {
+    #[serde(with = "quoted_u64")]
+    pub last_signed_block_slot: Slot,
+    #[serde(with = "quoted_u64")]
+    pub last_source_epoch: Epoch,
+    #[serde(with = "quoted_u64")]
+    pub last_target_epoch: Epoch,
+}
+
+impl HighWaterMarks {
+    /// True if each of these marks is at least as high as the same mark of `other`
+    pub fn covers(&self, other: &HighWaterMarks) -> bool {
+        self.last_signed_block_slot >= other.last_signed_block_slot
+            && self.last_source_epoch >= other.last_source_epoch
+            && self.last_target_epoch >= other.last_target_epoch
+    }
+}
+
+impl From<&SlashingProtectionData> for HighWaterMarks {
+    fn from(data: &SlashingProtectionData) -> Self {
+        let (last_signed_block_slot, last_source_epoch, last_target_epoch) =
+            data.high_water_marks();
+        HighWaterMarks {
+            last_signed_block_slot,
+            last_source_epoch,
+            last_target_epoch,
+        }
+    }
+}
+
+/// Per-key minimum block slot and attestation epochs, below which nothing is signed regardless
+/// of the slashing protection history. Kept apart from that history so it still holds if the
+/// history is rolled back, e.g. after restoring from a backup. See EIP-3076 minimal protection.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct SigningFloor {
+    #[serde(with = "quoted_u64")]
+    pub min_slot: Slot,
+    #[serde(with = "quoted_u64")]
+    pub min_source_epoch: Epoch,
+    #[serde(with = "quoted_u64")]
+    pub min_target_epoch: Epoch,
+}
+
+impl SigningFloor {
+    /// Returns the field-wise maximum of both floors, so a floor can only ever be raised
+    pub fn raise(&self, other: &SigningFloor) -> SigningFloor {
+        SigningFloor {
+            min_slot: std::cmp::max(self.min_slot, other.min_slot),
+            min_source_epoch: std::cmp::max(self.min_source_epoch, other.min_source_epoch),
+            min_target_epoch: std::cmp::max(self.min_target_epoch, other.min_target_epoch),
+        }
+    }
+
+    pub fn is_below_block_slot(&self, slot: Slot) -> bool {
+        slot < self.min_slot
+    }
+
+    pub fn is_below_attestation_epochs(&self, src: Epoch, tgt: Epoch) -> bool {
+        src < self.min_source_epoch || tgt < self.min_target_epoch
+    }
+
+    /// Saves the floor of `pk_hex`
+    pub fn write(&self, pk_hex: &str) -> Result<()> {
+        let pk_hex: String = strip_0x_prefix!(pk_hex);
+        let file_path: PathBuf = hex_file_path(&Config::current().signing_floor_dir(), &pk_hex)?;
+        let json = serde_json::to_string(&self)?;
+        crate::io::key_management::write_atomic(&file_path, json)
+            .with_context(|| "failed to write signing floor")
+    }
+
+    /// Loads the floor of `pk_hex`, or `None` if none was set
+    pub fn read(pk_hex: &str) -> Result<Option<Self>> {
+        let pk_hex: String = strip_0x_prefix!(pk_hex);
+        let file_path: PathBuf = hex_file_path(&Config::current().signing_floor_dir(), &pk_hex)?;
+        if !crate::io::key_management::file_exists(&file_path) {
+            return Ok(None);
+        }
+        let json_vec = crate::io::key_management::read_file(&file_path)?;
+        let floor =
+            serde_json::from_slice(&json_vec).with_context(|| "failed to read signing floor")?;
+        Ok(Some(floor))
+    }
+}
+
+/// The genesis_validators_root a key first signed under. The slashing protection history is kept
+/// per pubkey alone, so a key bound to one network refuses to sign for another rather than build
+/// a history mixing both.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub struct GenesisBinding {
+    #[serde(with = "SerHex::<StrictPfx>")]
+    pub genesis_validators_root: Root,
+}
+
+impl GenesisBinding {
+    /// Saves the binding of `pk_hex`
+    pub fn write(&self, pk_hex: &str) -> Result<()> {
+        let pk_hex: String = strip_0x_prefix!(pk_hex);
+        let file_path: PathBuf = hex_file_path(&Config::current().genesis_binding_dir(), &pk_hex)?;
+        let json = serde_json::to_string(&self)?;
+        crate::io::key_management::write_atomic(&file_path, json)
+            .with_context(|| "failed to write genesis binding")
+    }
+
+    /// Loads the binding of `pk_hex`, or `None` if it never signed while bindings were enforced
+    pub fn read(pk_hex: &str) -> Result<Option<Self>> {
+        let pk_hex: String = strip_0x_prefix!(pk_hex);
+        let file_path: PathBuf = hex_file_path(&Config::current().genesis_binding_dir(), &pk_hex)?;
+        if !crate::io::key_management::file_exists(&file_path) {
+            return Ok(None);
+        }
+        let json_vec = crate::io::key_management::read_file(&file_path)?;
+        let binding =
+            serde_json::from_slice(&json_vec).with_context(|| "failed to read genesis binding")?;
+        Ok(Some(binding))
+    }
+}
+
+/// Timestamp of the last validator registration a key signed. Like the slashing protection
+/// high-water marks it only moves forward, so a replayed older registration can't be signed.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub struct LastRegistration {
+    #[serde(with = "quoted_u64")]
+    pub timestamp: u64,
+}
+
+impl LastRegistration {
+    /// Saves the last registration of `pk_hex`
+    pub fn write(&self, pk_hex: &str) -> Result<()> {
+        let pk_hex: String = strip_0x_prefix!(pk_hex);
+        let file_path: PathBuf =
+            hex_file_path(&Config::current().registration_timestamp_dir(), &pk_hex)?;
+        let json = serde_json::to_string(&self)?;
+        crate::io::key_management::write_atomic(&file_path, json)
+            .with_context(|| "failed to write last registration")
+    }
+
+    /// Loads the last registration of `pk_hex`, or `None` if it never signed one while
+    /// registration timestamps were tracked
+    pub fn read(pk_hex: &str) -> Result<Option<Self>> {
+        let pk_hex: String = strip_0x_prefix!(pk_hex);
+        let file_path: PathBuf =
+            hex_file_path(&Config::current().registration_timestamp_dir(), &pk_hex)?;
+        if !crate::io::key_management::file_exists(&file_path) {
+            return Ok(None);
+        }
+        let json_vec = crate::io::key_management::read_file(&file_path)?;
+        let last = serde_json::from_slice(&json_vec)
+            .with_context(|| "failed to read last registration")?;
+        Ok(Some(last))
+    }
 }
 
 #[derive(Serialize, Deserialize, Debug)]
@@ -206,10 +598,284 @@ impl SlashingProtectionDB {
         Ok(db)
     }
 
-    pub fn read(&self) -> Result<()> {
-        // TODO combine all saved SlashingProtectionData into
-        // a SlashingProtectionDB to return via GET endpoint.
-        Ok(())
+    /// Builds an interchange holding the slashing protection of every saved key, for moving the
+    /// keys to another signer. See `InterchangeFormat` for how much history is kept.
+    pub fn export(genesis_validators_root: Root, format: InterchangeFormat) -> Result<Self> {
+        let mut db = SlashingProtectionDB::new();
+        db.metadata.genesis_validators_root = genesis_validators_root;
+        for pk_hex in crate::io::key_management::list_bls_keys()? {
+            // Keys that never signed have nothing to protect
+            if let Some(data) = SlashingProtectionData::try_read(&pk_hex)? {
+                db.data.push(match format {
+                    InterchangeFormat::Complete => data,
+                    InterchangeFormat::Minimal => data.minimal(),
+                });
+            }
+        }
+        Ok(db)
+    }
+
+    /// Builds an interchange holding the floor of every saved key, for a client to import
+    /// before it resumes signing. See `SlashingProtectionData::floor`.
+    pub fn floors(genesis_validators_root: Root, margin: u64) -> Result<Self> {
+        let mut db = SlashingProtectionDB::new();
+        db.metadata.genesis_validators_root = genesis_validators_root;
+        for pk_hex in crate::io::key_management::list_bls_keys()? {
+            // Keys that never signed have no database and need no floor
+            if let Ok(data) = SlashingProtectionData::read(&pk_hex) {
+                db.data.push(data.floor(margin));
+            }
+        }
+        Ok(db)
+    }
+
+    /// Builds an interchange holding the full history of `pk_hex`, for moving the key to
+    /// another signer without losing its slashing protection
+    pub fn for_key(pk_hex: &str, genesis_validators_root: Root) -> Result<Self> {
+        let mut db = SlashingProtectionDB::new();
+        db.metadata.genesis_validators_root = genesis_validators_root;
+        db.data.push(SlashingProtectionData::read(pk_hex)?);
+        Ok(db)
+    }
+
+    /// Merges every entry of the interchange into the configured slashing protection store.
+    /// Each entry is handled independently so one bad entry does not abort the others, and
+    /// the outcome for every pubkey is reported back.
+    pub fn import(&self) -> Result<Vec<SlashingProtectionImportResult>> {
+        Ok(store()?.import_interchange(self))
+    }
+
+    /// Like `import`, but only merges the entries of the hex pubkeys `pk_hexes`, the others are
+    /// neither merged nor reported
+    pub fn import_only(&self, pk_hexes: &[String]) -> Result<Vec<SlashingProtectionImportResult>> {
+        let store = store()?;
+        Ok(self
+            .data
+            .iter()
+            .filter(|entry| pk_hexes.contains(&hex::encode(&entry.pubkey[..])))
+            .map(|entry| entry.import_into(store.as_ref()))
+            .collect())
+    }
+}
+
+/// How much of each key's history an exported interchange carries, the two forms EIP-3076 allows
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum InterchangeFormat {
+    /// Every signed block and attestation saved
+    #[default]
+    Complete,
+    /// Only the high-water marks, as a single block and a single attestation
+    Minimal,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum SlashingProtectionImportStatus {
+    /// The entry raised the local high-water marks and was saved
+    Imported,
+    /// The entry carried nothing newer than what is already saved
+    Skipped,
+    /// The entry is below the local high-water marks and was not applied
+    Conflict,
+    /// The entry could not be applied, e.g. the key is unknown to the enclave
+    Error,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct SlashingProtectionImportResult {
+    pub pubkey: String,
+    pub status: SlashingProtectionImportStatus,
+    pub detail: String,
+}
+
+impl SlashingProtectionImportResult {
+    pub(crate) fn new(pubkey: &str, status: SlashingProtectionImportStatus, detail: &str) -> Self {
+        SlashingProtectionImportResult {
+            pubkey: format!("0x{}", pubkey),
+            status,
+            detail: detail.to_string(),
+        }
+    }
+}
+
+impl SlashingProtectionData {
+    /// Returns the (block slot, source epoch, target epoch) high-water marks
+    pub fn high_water_marks(&self) -> (Slot, Epoch, Epoch) {
+        let (src, tgt) = self.get_latest_signed_attestation_epochs();
+        (self.get_latest_signed_block_slot(), src, tgt)
+    }
+
+    /// Returns the minimal EIP-3076 form of this history: a block at the highest slot signed and
+    /// an attestation at the highest source and target epochs signed, if there are any
+    pub fn minimal(&self) -> SlashingProtectionData {
+        let (src, tgt) = self.get_latest_signed_attestation_epochs();
+        SlashingProtectionData {
+            pubkey: self.pubkey.clone(),
+            signed_blocks: self
+                .signed_blocks
+                .iter()
+                .max_by_key(|b| b.slot)
+                .cloned()
+                .into_iter()
+                .collect(),
+            signed_attestations: self
+                .signed_attestations
+                .iter()
+                .max_by_key(|a| a.target_epoch)
+                .map(|a| SignedAttestationEpochs {
+                    source_epoch: src,
+                    target_epoch: tgt,
+                    // The highest source and target may come from different attestations
+                    signing_root: a.signing_root.filter(|_| a.source_epoch == src),
+                })
+                .into_iter()
+                .collect(),
+        }
+    }
+
+    /// Collapses the history into its minimal form, see `minimal`, which rejects exactly the
+    /// same blocks and attestations. Returns the number of signed blocks and attestations dropped.
+    pub fn prune(&mut self) -> usize {
+        let before = self.signed_blocks.len() + self.signed_attestations.len();
+        *self = self.minimal();
+        before - self.signed_blocks.len() - self.signed_attestations.len()
+    }
+
+    /// Returns the recommended floor for a client resuming after a crash: the high-water marks
+    /// with `margin` added to the block slot and attestation target epoch. The source epoch is
+    /// kept as is, since raising it would stop the client attesting to the justified checkpoint.
+    pub fn floor(&self, margin: u64) -> SlashingProtectionData {
+        let (slot, src, tgt) = self.high_water_marks();
+        SlashingProtectionData {
+            pubkey: self.pubkey.clone(),
+            signed_blocks: vec![SignedBlockSlot {
+                slot: slot.saturating_add(margin),
+                signing_root: None,
+            }],
+            signed_attestations: vec![SignedAttestationEpochs {
+                source_epoch: src,
+                target_epoch: tgt.saturating_add(margin),
+                signing_root: None,
+            }],
+        }
+    }
+
+    /// Advances `local` to this entry's latest block and/or attestation, then saves it.
+    fn merge_into<S: SlashingProtectionStore + ?Sized>(
+        &self,
+        store: &S,
+        local: &mut SlashingProtectionData,
+        new_block: bool,
+        new_attestation: bool,
+    ) -> Result<()> {
+        let (_, local_src, _) = local.high_water_marks();
+        if new_block {
+            if let Some(b) = self.signed_blocks.iter().max_by_key(|b| b.slot) {
+                local.new_block(
+                    b.clone(),
+                    crate::constants::ALLOW_GROWABLE_SLASH_PROTECTION_DB,
+                )?;
+            }
+        }
+        if new_attestation {
+            if let Some(a) = self
+                .signed_attestations
+                .iter()
+                .max_by_key(|a| a.target_epoch)
+            {
+                let (src, _) = self.get_latest_signed_attestation_epochs();
+                let a = SignedAttestationEpochs {
+                    source_epoch: std::cmp::max(src, local_src),
+                    target_epoch: a.target_epoch,
+                    signing_root: a.signing_root,
+                };
+                local.new_attestation(a, crate::constants::ALLOW_GROWABLE_SLASH_PROTECTION_DB)?;
+            }
+        }
+        store.put(local)
+    }
+
+    /// Merges this interchange entry into the protection data `store` holds for the same pubkey.
+    fn import_into<S: SlashingProtectionStore + ?Sized>(
+        &self,
+        store: &S,
+    ) -> SlashingProtectionImportResult {
+        let pk_hex = hex::encode(self.pubkey.as_ssz_bytes());
+
+        if !crate::io::key_management::bls_key_exists(&pk_hex) {
+            return SlashingProtectionImportResult::new(
+                &pk_hex,
+                SlashingProtectionImportStatus::Error,
+                "Validator key is not known to this enclave",
+            );
+        }
+
+        if self.signed_blocks.is_empty() && self.signed_attestations.is_empty() {
+            return SlashingProtectionImportResult::new(
+                &pk_hex,
+                SlashingProtectionImportStatus::Skipped,
+                "No signed blocks or attestations to import",
+            );
+        }
+
+        // Only a key without history starts from scratch, an unreadable one is never overwritten
+        let saved = match store.contains(&pk_hex) {
+            Ok(true) => store.get(&pk_hex).map(Some),
+            Ok(false) => Ok(None),
+            Err(e) => Err(e),
+        };
+        let mut local = match saved {
+            Ok(Some(db)) => db,
+            Ok(None) => SlashingProtectionData::new(self.pubkey.clone()),
+            Err(e) => {
+                error!("Unreadable slash protection of {pk_hex}: {:?}", e);
+                return SlashingProtectionImportResult::new(
+                    &pk_hex,
+                    SlashingProtectionImportStatus::Error,
+                    &format!("Unreadable local slashing protection, {:?}", e),
+                );
+            }
+        };
+
+        let (local_slot, local_src, local_tgt) = local.high_water_marks();
+        let (slot, src, tgt) = self.high_water_marks();
+
+        if (slot, src, tgt) == (local_slot, local_src, local_tgt) {
+            return SlashingProtectionImportResult::new(
+                &pk_hex,
+                SlashingProtectionImportStatus::Skipped,
+                "Already up to date",
+            );
+        }
+
+        let new_block = slot > local_slot;
+        let new_attestation = tgt > local_tgt;
+        if !new_block && !new_attestation {
+            return SlashingProtectionImportResult::new(
+                &pk_hex,
+                SlashingProtectionImportStatus::Conflict,
+                &format!(
+                    "Below local high-water marks (slot: {local_slot}, source: {local_src}, target: {local_tgt})"
+                ),
+            );
+        }
+
+        match self.merge_into(store, &mut local, new_block, new_attestation) {
+            Ok(()) => SlashingProtectionImportResult::new(
+                &pk_hex,
+                SlashingProtectionImportStatus::Imported,
+                "",
+            ),
+            Err(e) => {
+                error!("Failed to import slash protection for {pk_hex}: {:?}", e);
+                SlashingProtectionImportResult::new(
+                    &pk_hex,
+                    SlashingProtectionImportStatus::Error,
+                    &format!("{:?}", e),
+                )
+            }
+        }
     }
 }
 
@@ -551,4 +1217,441 @@ pub mod test_slash_protection {
 
         Ok(())
     }
+
+    #[test]
+    fn test_import_reports_per_key_status() -> Result<()> {
+        // New key with no local history
+        let new_sk = crate::crypto::bls_keys::new_bls_key(0);
+        crate::crypto::bls_keys::save_bls_key(&new_sk)?;
+        let new_pk = new_sk.public_keys().public_key().to_hex();
+
+        // Key whose local history is ahead of the interchange
+        let conflict_sk = crate::crypto::bls_keys::new_bls_key(0);
+        crate::crypto::bls_keys::save_bls_key(&conflict_sk)?;
+        let conflict_pk = conflict_sk.public_keys().public_key().to_hex();
+        let mut local = SlashingProtectionData::from_pk_hex(&conflict_pk)?;
+        local.new_block(
+            SignedBlockSlot {
+                slot: 100,
+                signing_root: None,
+            },
+            false,
+        )?;
+        local.new_attestation(
+            SignedAttestationEpochs {
+                source_epoch: 10,
+                target_epoch: 20,
+                signing_root: None,
+            },
+            false,
+        )?;
+        local.write()?;
+
+        // Key that was never generated or imported
+        let unknown_pk = crate::crypto::bls_keys::new_bls_key(0)
+            .public_keys()
+            .public_key()
+            .to_hex();
+
+        let entry = |pk: &String, slot: u64, src: u64, tgt: u64| {
+            format!(
+                r#"{{
+                    "pubkey": "0x{pk}",
+                    "signed_blocks": [{{ "slot": "{slot}" }}],
+                    "signed_attestations": [{{ "source_epoch": "{src}", "target_epoch": "{tgt}" }}]
+                }}"#
+            )
+        };
+        let raw = format!(
+            r#"{{
+                "metadata": {{
+                    "interchange_format_version": "5",
+                    "genesis_validators_root": "0x04700007fabc8282644aed6d1c7c9e21d38a03a0c4ba193f3afe428824b3a673"
+                }},
+                "data": [{}, {}, {}]
+            }}"#,
+            entry(&new_pk, 50, 5, 6),
+            entry(&conflict_pk, 50, 5, 6),
+            entry(&unknown_pk, 50, 5, 6)
+        );
+
+        let db = SlashingProtectionDB::from_str(&raw)?;
+        let results = db.import()?;
+        assert_eq!(results.len(), 3);
+
+        assert_eq!(results[0].pubkey, format!("0x{new_pk}"));
+        assert_eq!(results[0].status, SlashingProtectionImportStatus::Imported);
+        let saved = SlashingProtectionData::read(&new_pk)?;
+        assert_eq!(saved.high_water_marks(), (50, 5, 6));
+
+        assert_eq!(results[1].pubkey, format!("0x{conflict_pk}"));
+        assert_eq!(results[1].status, SlashingProtectionImportStatus::Conflict);
+        let saved = SlashingProtectionData::read(&conflict_pk)?;
+        assert_eq!(saved.high_water_marks(), (100, 10, 20));
+
+        assert_eq!(results[2].pubkey, format!("0x{unknown_pk}"));
+        assert_eq!(results[2].status, SlashingProtectionImportStatus::Error);
+
+        // Importing the same interchange again is a no-op for the new key
+        let results = db.import()?;
+        assert_eq!(results[0].status, SlashingProtectionImportStatus::Skipped);
+        Ok(())
+    }
+
+    #[test]
+    fn test_import_does_not_overwrite_unreadable_history() -> Result<()> {
+        let _data_dir = crate::io::config::Config::scoped_temp()?;
+        let sk = crate::crypto::bls_keys::new_bls_key(0);
+        crate::crypto::bls_keys::save_bls_key(&sk)?;
+        let pk = sk.public_keys().public_key().to_hex();
+        let file_path = hex_file_path(
+            &crate::io::config::Config::current().slashing_protection_dir(),
+            &pk,
+        )?;
+        crate::io::key_management::write_atomic(&file_path, "not json")?;
+
+        let raw = format!(
+            r#"{{
+                "metadata": {{
+                    "interchange_format_version": "5",
+                    "genesis_validators_root": "0x04700007fabc8282644aed6d1c7c9e21d38a03a0c4ba193f3afe428824b3a673"
+                }},
+                "data": [{{
+                    "pubkey": "0x{pk}",
+                    "signed_blocks": [{{ "slot": "50" }}],
+                    "signed_attestations": []
+                }}]
+            }}"#
+        );
+        let results = SlashingProtectionDB::from_str(&raw)?.import()?;
+        assert_eq!(results[0].status, SlashingProtectionImportStatus::Error);
+        assert_eq!(
+            crate::io::key_management::read_file(&file_path)?,
+            b"not json"
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_floors_add_margin_to_high_water_marks() -> Result<()> {
+        let _data_dir = crate::io::config::Config::scoped_temp()?;
+
+        // Key that signed a block and an attestation
+        let sk = crate::crypto::bls_keys::new_bls_key(0);
+        crate::crypto::bls_keys::save_bls_key(&sk)?;
+        let pk = sk.public_keys().public_key().to_hex();
+        let mut local = SlashingProtectionData::from_pk_hex(&pk)?;
+        local.new_block(
+            SignedBlockSlot {
+                slot: 100,
+                signing_root: None,
+            },
+            false,
+        )?;
+        local.new_attestation(
+            SignedAttestationEpochs {
+                source_epoch: 10,
+                target_epoch: 20,
+                signing_root: None,
+            },
+            false,
+        )?;
+        local.write()?;
+
+        // Key without a slashing protection database
+        crate::crypto::bls_keys::save_bls_key(&crate::crypto::bls_keys::new_bls_key(0))?;
+
+        let db = SlashingProtectionDB::floors([4; 32], 5)?;
+        assert_eq!(db.metadata.genesis_validators_root, [4; 32]);
+        assert_eq!(db.data.len(), 1);
+        assert_eq!(hex::encode(db.data[0].pubkey.as_ssz_bytes()), pk);
+        assert_eq!(db.data[0].high_water_marks(), (105, 10, 25));
+
+        // The floor round-trips through the interchange format
+        let json = serde_json::to_string(&db)?;
+        let db = SlashingProtectionDB::from_str(&json)?;
+        assert_eq!(db.data[0].high_water_marks(), (105, 10, 25));
+        Ok(())
+    }
+
+    #[test]
+    fn test_minimal_keeps_only_high_water_marks() -> Result<()> {
+        let mut data = SlashingProtectionData::from_pk_hex(&"aa".repeat(48))?;
+        for slot in [1, 5] {
+            data.new_block(
+                SignedBlockSlot {
+                    slot,
+                    signing_root: Some([slot as u8; 32]),
+                },
+                true,
+            )?;
+        }
+        for (source_epoch, target_epoch) in [(3, 4), (2, 6)] {
+            data.signed_attestations.push(SignedAttestationEpochs {
+                source_epoch,
+                target_epoch,
+                signing_root: Some([target_epoch as u8; 32]),
+            });
+        }
+
+        let minimal = data.minimal();
+        assert_eq!(minimal.signed_blocks.len(), 1);
+        assert_eq!(minimal.signed_blocks[0].signing_root, Some([5; 32]));
+        assert_eq!(minimal.signed_attestations.len(), 1);
+        assert_eq!(minimal.high_water_marks(), (5, 3, 6));
+        // No attestation was signed at (3, 6), so there's no root to carry over
+        assert_eq!(minimal.signed_attestations[0].signing_root, None);
+
+        // Nothing signed, nothing to keep
+        let empty = SlashingProtectionData::from_pk_hex(&"aa".repeat(48))?.minimal();
+        assert!(empty.signed_blocks.is_empty() && empty.signed_attestations.is_empty());
+        Ok(())
+    }
+
+    #[test]
+    fn test_prune_keeps_high_water_marks() -> Result<()> {
+        let mut data = SlashingProtectionData::from_pk_hex(&"aa".repeat(48))?;
+        for i in 1..=1000 {
+            data.new_block(
+                SignedBlockSlot {
+                    slot: i * 2,
+                    signing_root: None,
+                },
+                true,
+            )?;
+            data.new_attestation(
+                SignedAttestationEpochs {
+                    source_epoch: i,
+                    target_epoch: i + 1,
+                    signing_root: None,
+                },
+                true,
+            )?;
+        }
+        let marks = data.high_water_marks();
+
+        assert_eq!(data.prune(), 1998);
+        assert_eq!(data.signed_blocks.len(), 1);
+        assert_eq!(data.signed_attestations.len(), 1);
+        assert_eq!(data.high_water_marks(), marks);
+        assert!(data.is_slashable_block_slot(2000));
+        assert!(data.is_slashable_block_slot(1000));
+        assert!(!data.is_slashable_block_slot(2001));
+        assert!(data.is_slashable_attestation_epochs(999, 1002));
+        assert!(data.is_slashable_attestation_epochs(1000, 1001));
+        assert!(!data.is_slashable_attestation_epochs(1000, 1002));
+
+        // Already minimal
+        assert_eq!(data.prune(), 0);
+        assert_eq!(data.high_water_marks(), marks);
+        Ok(())
+    }
+
+    /// Exercises a slashing protection store, run against every backend
+    fn check_store(store: &dyn SlashingProtectionStore) -> Result<()> {
+        let sk = crate::crypto::bls_keys::new_bls_key(0);
+        crate::crypto::bls_keys::save_bls_key(&sk)?;
+        let pk = sk.public_keys().public_key().to_hex();
+
+        // Nothing is saved until the key's data is put
+        assert!(store.get(&pk).is_err());
+        store.put(&SlashingProtectionData::from_pk_hex(&pk)?)?;
+        assert_eq!(store.get(&pk)?.high_water_marks(), (0, 0, 0));
+
+        // Blocks and attestations only move forward
+        store.record_block(
+            &pk,
+            SignedBlockSlot {
+                slot: 10,
+                signing_root: Some([1; 32]),
+            },
+        )?;
+        assert!(store
+            .record_block(
+                &pk,
+                SignedBlockSlot {
+                    slot: 10,
+                    signing_root: None,
+                },
+            )
+            .is_err());
+        store.record_attestation(
+            &pk,
+            SignedAttestationEpochs {
+                source_epoch: 2,
+                target_epoch: 3,
+                signing_root: None,
+            },
+        )?;
+        assert!(store
+            .record_attestation(
+                &pk,
+                SignedAttestationEpochs {
+                    source_epoch: 1,
+                    target_epoch: 4,
+                    signing_root: None,
+                },
+            )
+            .is_err());
+
+        // A 0x prefix addresses the same key
+        let saved = store.get(&format!("0x{pk}"))?;
+        assert_eq!(saved.high_water_marks(), (10, 2, 3));
+        assert_eq!(saved.signed_blocks[0].signing_root, Some([1; 32]));
+
+        // The interchange raises the marks, importing it twice is a no-op
+        let raw = format!(
+            r#"{{
+                "metadata": {{
+                    "interchange_format_version": "5",
+                    "genesis_validators_root": "0x04700007fabc8282644aed6d1c7c9e21d38a03a0c4ba193f3afe428824b3a673"
+                }},
+                "data": [{{
+                    "pubkey": "0x{pk}",
+                    "signed_blocks": [{{ "slot": "50" }}],
+                    "signed_attestations": [{{ "source_epoch": "5", "target_epoch": "6" }}]
+                }}]
+            }}"#
+        );
+        let db = SlashingProtectionDB::from_str(&raw)?;
+        let results = store.import_interchange(&db);
+        assert_eq!(results[0].status, SlashingProtectionImportStatus::Imported);
+        assert_eq!(store.get(&pk)?.high_water_marks(), (50, 5, 6));
+        let results = store.import_interchange(&db);
+        assert_eq!(results[0].status, SlashingProtectionImportStatus::Skipped);
+        Ok(())
+    }
+
+    #[test]
+    fn test_json_file_store() -> Result<()> {
+        let _data_dir = crate::io::config::Config::scoped_temp()?;
+        let store = JsonFileStore::new(Config::current().slashing_protection_dir());
+        check_store(&store)
+    }
+
+    #[cfg(feature = "sqlite")]
+    #[test]
+    fn test_sqlite_store() -> Result<()> {
+        let _data_dir = crate::io::config::Config::scoped_temp()?;
+        let store = sqlite::SqliteStore::open(Config::current().slashing_protection_sqlite_path())?;
+        check_store(&store)?;
+
+        // The history survives reopening the database
+        let reopened =
+            sqlite::SqliteStore::open(Config::current().slashing_protection_sqlite_path())?;
+        for pk_hex in crate::io::key_management::list_bls_keys()? {
+            assert_eq!(reopened.get(&pk_hex)?.high_water_marks(), (50, 5, 6));
+        }
+        Ok(())
+    }
+
+    #[cfg(feature = "sqlite")]
+    #[test]
+    fn test_sqlite_store_is_opened_once() -> Result<()> {
+        let _data_dir = crate::io::config::Config::scoped_temp()?;
+        let path = Config::current().slashing_protection_sqlite_path();
+        assert!(Arc::ptr_eq(
+            &sqlite_store(path.clone())?,
+            &sqlite_store(path)?
+        ));
+        Ok(())
+    }
+
+    #[test]
+    fn test_signing_floor_only_rises() -> Result<()> {
+        let _data_dir = crate::io::config::Config::scoped_temp()?;
+        let pk = crate::crypto::bls_keys::new_bls_key(0)
+            .public_keys()
+            .public_key()
+            .to_hex();
+        assert!(SigningFloor::read(&pk)?.is_none());
+
+        let floor = SigningFloor {
+            min_slot: 100,
+            min_source_epoch: 10,
+            min_target_epoch: 20,
+        };
+        floor.write(&pk)?;
+        let saved = SigningFloor::read(&format!("0x{pk}"))?.unwrap();
+        assert_eq!(saved, floor);
+
+        assert!(saved.is_below_block_slot(99));
+        assert!(!saved.is_below_block_slot(100));
+        assert!(saved.is_below_attestation_epochs(9, 30));
+        assert!(saved.is_below_attestation_epochs(10, 19));
+        assert!(!saved.is_below_attestation_epochs(10, 20));
+
+        let lower = SigningFloor {
+            min_slot: 50,
+            min_source_epoch: 15,
+            min_target_epoch: 5,
+        };
+        assert_eq!(
+            saved.raise(&lower),
+            SigningFloor {
+                min_slot: 100,
+                min_source_epoch: 15,
+                min_target_epoch: 20,
+            }
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_restore_from_backup_keeps_the_higher_marks() -> Result<()> {
+        let _data_dir = crate::io::config::Config::scoped_temp()?;
+        let backup = SlashProtectionBackup::new(Config::current().data_dir.join("backup"));
+        let store = store()?;
+        let new_key_signed_at = |slot: Slot| -> Result<String> {
+            let sk = crate::crypto::bls_keys::new_bls_key(0);
+            crate::crypto::bls_keys::save_bls_key(&sk)?;
+            let pk = sk.public_keys().public_key().to_hex();
+            store.put(&SlashingProtectionData::from_pk_hex(&pk)?)?;
+            store.record_block(
+                &pk,
+                SignedBlockSlot {
+                    slot,
+                    signing_root: None,
+                },
+            )?;
+            Ok(pk)
+        };
+        let (ahead, corrupted, rolled_back) = (
+            new_key_signed_at(10)?,
+            new_key_signed_at(10)?,
+            new_key_signed_at(10)?,
+        );
+        assert_eq!(backup.backup()?, 3);
+
+        // One primary moves ahead of its backup, one is corrupted and one is rolled back
+        store.record_block(
+            &ahead,
+            SignedBlockSlot {
+                slot: 20,
+                signing_root: None,
+            },
+        )?;
+        crate::io::key_management::write_atomic(
+            &hex_file_path(&Config::current().slashing_protection_dir(), &corrupted)?,
+            "{\"pubkey\": \"0x",
+        )?;
+        let mut lower = SlashingProtectionData::from_pk_hex(&rolled_back)?;
+        lower.new_block(
+            SignedBlockSlot {
+                slot: 5,
+                signing_root: None,
+            },
+            true,
+        )?;
+        store.put(&lower)?;
+
+        // Neither the corrupted nor the rolled back primary replaces its backup
+        assert_eq!(backup.backup()?, 1);
+
+        backup.restore()?;
+        assert_eq!(store.get(&ahead)?.high_water_marks(), (20, 0, 0));
+        assert_eq!(store.get(&corrupted)?.high_water_marks(), (10, 0, 0));
+        assert_eq!(store.get(&rolled_back)?.high_water_marks(), (10, 0, 0));
+        Ok(())
+    }
 }
@@ -0,0 +1,221 @@
+use super::{
+    SignedAttestationEpochs, SignedBlockSlot, SlashingProtectionData, SlashingProtectionStore,
+};
+use crate::eth2::eth_types::{BLSPubkey, Root};
+use crate::strip_0x_prefix;
+
+use anyhow::{bail, Context, Result};
+use log::debug;
+use rusqlite::{params, Connection, OptionalExtension, Transaction, TransactionBehavior};
+use ssz::Encode;
+use ssz_types::FixedVector;
+use std::path::Path;
+use std::sync::Mutex;
+use std::time::Duration;
+
+/// How long to wait on a database locked by another connection before failing
+const BUSY_TIMEOUT: Duration = Duration::from_secs(5);
+
+const SCHEMA: &str = "
+    CREATE TABLE IF NOT EXISTS validators (
+        id INTEGER PRIMARY KEY,
+        pubkey BLOB NOT NULL UNIQUE
+    );
+    CREATE TABLE IF NOT EXISTS signed_blocks (
+        validator_id INTEGER NOT NULL REFERENCES validators(id),
+        slot INTEGER NOT NULL,
+        signing_root BLOB
+    );
+    CREATE TABLE IF NOT EXISTS signed_attestations (
+        validator_id INTEGER NOT NULL REFERENCES validators(id),
+        source_epoch INTEGER NOT NULL,
+        target_epoch INTEGER NOT NULL,
+        signing_root BLOB
+    );
+    CREATE INDEX IF NOT EXISTS signed_blocks_validator ON signed_blocks(validator_id);
+    CREATE INDEX IF NOT EXISTS signed_attestations_validator ON signed_attestations(validator_id);
+";
+
+/// Keeps the histories of all keys in a single SQLite database. Every update runs in its own
+/// transaction, so a crash never leaves a half-written history behind.
+pub struct SqliteStore {
+    conn: Mutex<Connection>,
+}
+
+impl SqliteStore {
+    /// Opens the database at `path`, creating it and its tables if needed
+    pub fn open(path: impl AsRef<Path>) -> Result<Self> {
+        if let Some(p) = path.as_ref().parent() {
+            std::fs::create_dir_all(p).with_context(|| "Failed to create slashing dir")?
+        };
+        let conn = Connection::open(path).with_context(|| "Failed to open slashing database")?;
+        conn.busy_timeout(BUSY_TIMEOUT)?;
+        conn.execute_batch(SCHEMA)
+            .with_context(|| "Failed to create slashing database tables")?;
+        Ok(SqliteStore {
+            conn: Mutex::new(conn),
+        })
+    }
+
+    /// Runs `f` on the saved data of `pk_hex` and saves the result, all in one transaction
+    fn update(
+        &self,
+        pk_hex: &str,
+        f: impl FnOnce(&mut SlashingProtectionData) -> Result<()>,
+    ) -> Result<()> {
+        let mut conn = self.conn.lock().expect("slashing database lock poisoned");
+        // Take the write lock up front so concurrent signers can't both pass the check
+        let tx = conn.transaction_with_behavior(TransactionBehavior::Immediate)?;
+        let mut data = load(&tx, &pubkey_bytes(pk_hex)?)?;
+        f(&mut data)?;
+        save(&tx, &data)?;
+        tx.commit()
+            .with_context(|| "failed to write protection data")
+    }
+}
+
+impl SlashingProtectionStore for SqliteStore {
+    fn get(&self, pk_hex: &str) -> Result<SlashingProtectionData> {
+        let mut conn = self.conn.lock().expect("slashing database lock poisoned");
+        let tx = conn.transaction()?;
+        let data = load(&tx, &pubkey_bytes(pk_hex)?)?;
+        // As JSON, so the pubkey is logged in hex where it can be redacted
+        debug!(
+            "Reading Slash Protection DB:\n{}",
+            serde_json::to_string(&data).unwrap_or_default()
+        );
+        Ok(data)
+    }
+
+    fn contains(&self, pk_hex: &str) -> Result<bool> {
+        let conn = self.conn.lock().expect("slashing database lock poisoned");
+        let id: Option<i64> = conn
+            .query_row(
+                "SELECT id FROM validators WHERE pubkey = ?1",
+                params![pubkey_bytes(pk_hex)?],
+                |row| row.get(0),
+            )
+            .optional()?;
+        Ok(id.is_some())
+    }
+
+    fn put(&self, data: &SlashingProtectionData) -> Result<()> {
+        let mut conn = self.conn.lock().expect("slashing database lock poisoned");
+        let tx = conn.transaction_with_behavior(TransactionBehavior::Immediate)?;
+        save(&tx, data)?;
+        tx.commit()
+            .with_context(|| "failed to write protection data")
+    }
+
+    fn record_block(&self, pk_hex: &str, block: SignedBlockSlot) -> Result<()> {
+        self.update(pk_hex, |data| {
+            data.new_block(block, crate::constants::ALLOW_GROWABLE_SLASH_PROTECTION_DB)
+        })
+    }
+
+    fn record_attestation(&self, pk_hex: &str, attest: SignedAttestationEpochs) -> Result<()> {
+        self.update(pk_hex, |data| {
+            data.new_attestation(attest, crate::constants::ALLOW_GROWABLE_SLASH_PROTECTION_DB)
+        })
+    }
+}
+
+fn pubkey_bytes(pk_hex: &str) -> Result<Vec<u8>> {
+    let pk_hex: &str = strip_0x_prefix!(pk_hex);
+    hex::decode(pk_hex).with_context(|| "Failed to hex-decode pk_hex")
+}
+
+fn to_root(signing_root: Option<Vec<u8>>) -> Result<Option<Root>> {
+    match signing_root {
+        None => Ok(None),
+        Some(root) => match Root::try_from(root.as_slice()) {
+            Ok(root) => Ok(Some(root)),
+            Err(_) => bail!("Saved signing root is not 32 bytes"),
+        },
+    }
+}
+
+fn load(tx: &Transaction, pubkey: &[u8]) -> Result<SlashingProtectionData> {
+    let id: i64 = match tx
+        .query_row(
+            "SELECT id FROM validators WHERE pubkey = ?1",
+            params![pubkey],
+            |row| row.get(0),
+        )
+        .optional()?
+    {
+        Some(id) => id,
+        None => bail!("No slashing protection data for 0x{}", hex::encode(pubkey)),
+    };
+
+    let pubkey: BLSPubkey = FixedVector::from(pubkey.to_vec());
+    let mut data = SlashingProtectionData::new(pubkey);
+
+    let mut stmt = tx.prepare(
+        "SELECT slot, signing_root FROM signed_blocks WHERE validator_id = ?1 ORDER BY rowid",
+    )?;
+    let mut rows = stmt.query(params![id])?;
+    while let Some(row) = rows.next()? {
+        data.signed_blocks.push(SignedBlockSlot {
+            slot: row.get(0)?,
+            signing_root: to_root(row.get(1)?)?,
+        });
+    }
+
+    let mut stmt = tx.prepare(
+        "SELECT source_epoch, target_epoch, signing_root FROM signed_attestations \
+         WHERE validator_id = ?1 ORDER BY rowid",
+    )?;
+    let mut rows = stmt.query(params![id])?;
+    while let Some(row) = rows.next()? {
+        data.signed_attestations.push(SignedAttestationEpochs {
+            source_epoch: row.get(0)?,
+            target_epoch: row.get(1)?,
+            signing_root: to_root(row.get(2)?)?,
+        });
+    }
+    Ok(data)
+}
+
+fn save(tx: &Transaction, data: &SlashingProtectionData) -> Result<()> {
+    let pubkey = data.pubkey.as_ssz_bytes();
+    tx.execute(
+        "INSERT OR IGNORE INTO validators (pubkey) VALUES (?1)",
+        params![pubkey],
+    )?;
+    let id: i64 = tx.query_row(
+        "SELECT id FROM validators WHERE pubkey = ?1",
+        params![pubkey],
+        |row| row.get(0),
+    )?;
+
+    tx.execute(
+        "DELETE FROM signed_blocks WHERE validator_id = ?1",
+        params![id],
+    )?;
+    for b in data.signed_blocks.iter() {
+        tx.execute(
+            "INSERT INTO signed_blocks (validator_id, slot, signing_root) VALUES (?1, ?2, ?3)",
+            params![id, b.slot, b.signing_root.map(|r| r.to_vec())],
+        )?;
+    }
+
+    tx.execute(
+        "DELETE FROM signed_attestations WHERE validator_id = ?1",
+        params![id],
+    )?;
+    for a in data.signed_attestations.iter() {
+        tx.execute(
+            "INSERT INTO signed_attestations (validator_id, source_epoch, target_epoch, signing_root) \
+             VALUES (?1, ?2, ?3, ?4)",
+            params![
+                id,
+                a.source_epoch,
+                a.target_epoch,
+                a.signing_root.map(|r| r.to_vec())
+            ],
+        )?;
+    }
+    debug!("Writing Slash Protection DB for 0x{}", hex::encode(&pubkey));
+    Ok(())
+}
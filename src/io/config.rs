@@ -0,0 +1,302 @@
+use crate::constants::{
+    BLS_KEYS_SUBDIR, DEFAULT_DATA_DIR, ETH_KEYS_SUBDIR, FEE_RECIPIENT_ALLOWLIST_SUBDIR,
+    GENESIS_BINDING_SUBDIR, KEYS_SUBDIR, KEY_ORIGIN_SUBDIR, METRICS_LABEL_SUBDIR,
+    PROPOSER_INDEX_SUBDIR, REGISTRATION_TIMESTAMP_SUBDIR, REQUEST_MAC_SECRETS_SUBDIR,
+    SIGNING_FLOOR_SUBDIR, SIGN_STATS_SUBDIR, SIGN_TYPE_ALLOWLIST_SUBDIR,
+    SLASHING_PROTECTION_SQLITE_FILE, SLASHING_PROTECTION_SUBDIR,
+};
+use crate::io::storage::Storage;
+use anyhow::{bail, Context, Result};
+
+use std::cell::RefCell;
+use std::path::{Path, PathBuf};
+use std::sync::RwLock;
+
+/// Where slashing protection histories are kept
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum SlashingProtectionBackend {
+    /// One JSON file per validator key
+    #[default]
+    Json,
+    /// A single SQLite database for all keys, requires the `sqlite` feature
+    Sqlite,
+}
+
+impl std::str::FromStr for SlashingProtectionBackend {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s.to_ascii_lowercase().as_str() {
+            "json" => Ok(SlashingProtectionBackend::Json),
+            "sqlite" => Ok(SlashingProtectionBackend::Sqlite),
+            _ => bail!("Unknown slashing protection backend {:?}", s),
+        }
+    }
+}
+
+/// Enclave identity the at-rest sealing key is bound to
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum SealingPolicy {
+    /// Only this exact enclave build can unseal, keys must be migrated across upgrades
+    MrEnclave,
+    /// Any enclave signed by the same authority can unseal, so keys survive upgrades
+    #[default]
+    MrSigner,
+}
+
+impl SealingPolicy {
+    pub const ALL: [SealingPolicy; 2] = [SealingPolicy::MrEnclave, SealingPolicy::MrSigner];
+
+    /// The `key_policy` EGETKEY is called with
+    pub fn sgx_key_policy(&self) -> u16 {
+        match self {
+            SealingPolicy::MrEnclave => 0x0001,
+            SealingPolicy::MrSigner => 0x0002,
+        }
+    }
+}
+
+impl std::str::FromStr for SealingPolicy {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s.to_ascii_lowercase().as_str() {
+            "mrenclave" => Ok(SealingPolicy::MrEnclave),
+            "mrsigner" => Ok(SealingPolicy::MrSigner),
+            _ => bail!("Unknown sealing policy {:?}", s),
+        }
+    }
+}
+
+/// Location of the enclave's persistent state (keys and slashing protection databases)
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Config {
+    pub data_dir: PathBuf,
+    pub slashing_protection_backend: SlashingProtectionBackend,
+    /// Changing it makes keys sealed under the previous policy unreadable
+    pub sealing_policy: SealingPolicy,
+    /// What backs the files under `data_dir`
+    pub storage: Storage,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Config::new(DEFAULT_DATA_DIR)
+    }
+}
+
+/// Config installed at startup, `None` until `Config::install` is called
+static INSTALLED: RwLock<Option<Config>> = RwLock::new(None);
+
+thread_local! {
+    /// Per-thread override, used to isolate tests from each other
+    static SCOPED: RefCell<Option<Config>> = RefCell::new(None);
+}
+
+impl Config {
+    pub fn new(data_dir: impl Into<PathBuf>) -> Self {
+        Config {
+            data_dir: data_dir.into(),
+            slashing_protection_backend: SlashingProtectionBackend::default(),
+            sealing_policy: SealingPolicy::default(),
+            storage: Storage::default(),
+        }
+    }
+
+    pub fn with_slashing_protection_backend(mut self, backend: SlashingProtectionBackend) -> Self {
+        self.slashing_protection_backend = backend;
+        self
+    }
+
+    pub fn with_sealing_policy(mut self, policy: SealingPolicy) -> Self {
+        self.sealing_policy = policy;
+        self
+    }
+
+    pub fn with_storage(mut self, storage: Storage) -> Self {
+        self.storage = storage;
+        self
+    }
+
+    pub fn keys_dir(&self) -> PathBuf {
+        self.data_dir.join(KEYS_SUBDIR)
+    }
+
+    pub fn bls_keys_dir(&self) -> PathBuf {
+        self.keys_dir().join(BLS_KEYS_SUBDIR)
+    }
+
+    pub fn eth_keys_dir(&self) -> PathBuf {
+        self.keys_dir().join(ETH_KEYS_SUBDIR)
+    }
+
+    pub fn request_mac_secrets_dir(&self) -> PathBuf {
+        self.keys_dir().join(REQUEST_MAC_SECRETS_SUBDIR)
+    }
+
+    pub fn slashing_protection_dir(&self) -> PathBuf {
+        self.data_dir.join(SLASHING_PROTECTION_SUBDIR)
+    }
+
+    pub fn signing_floor_dir(&self) -> PathBuf {
+        self.data_dir.join(SIGNING_FLOOR_SUBDIR)
+    }
+
+    pub fn genesis_binding_dir(&self) -> PathBuf {
+        self.data_dir.join(GENESIS_BINDING_SUBDIR)
+    }
+
+    pub fn fee_recipient_allowlist_dir(&self) -> PathBuf {
+        self.data_dir.join(FEE_RECIPIENT_ALLOWLIST_SUBDIR)
+    }
+
+    pub fn registration_timestamp_dir(&self) -> PathBuf {
+        self.data_dir.join(REGISTRATION_TIMESTAMP_SUBDIR)
+    }
+
+    pub fn sign_type_allowlist_dir(&self) -> PathBuf {
+        self.data_dir.join(SIGN_TYPE_ALLOWLIST_SUBDIR)
+    }
+
+    pub fn sign_stats_dir(&self) -> PathBuf {
+        self.data_dir.join(SIGN_STATS_SUBDIR)
+    }
+
+    pub fn key_origin_dir(&self) -> PathBuf {
+        self.data_dir.join(KEY_ORIGIN_SUBDIR)
+    }
+
+    pub fn proposer_index_dir(&self) -> PathBuf {
+        self.data_dir.join(PROPOSER_INDEX_SUBDIR)
+    }
+
+    pub fn metrics_label_dir(&self) -> PathBuf {
+        self.data_dir.join(METRICS_LABEL_SUBDIR)
+    }
+
+    pub fn slashing_protection_sqlite_path(&self) -> PathBuf {
+        self.data_dir.join(SLASHING_PROTECTION_SQLITE_FILE)
+    }
+
+    /// Makes this the config for the whole process. Called once at startup.
+    pub fn install(self) {
+        *INSTALLED.write().expect("config lock poisoned") = Some(self);
+    }
+
+    /// Returns the config of the current thread, falling back to the installed config.
+    pub fn current() -> Config {
+        if let Some(config) = SCOPED.with(|c| c.borrow().clone()) {
+            return config;
+        }
+        INSTALLED
+            .read()
+            .expect("config lock poisoned")
+            .clone()
+            .unwrap_or_default()
+    }
+
+    /// Overrides the config on the current thread until the returned guard is dropped.
+    pub fn scoped(self) -> ScopedConfig {
+        let previous = SCOPED.with(|c| c.replace(Some(self)));
+        ScopedConfig {
+            previous,
+            remove_on_drop: None,
+        }
+    }
+
+    /// Overrides the config on the current thread with a fresh temporary data directory,
+    /// which is deleted when the returned guard is dropped.
+    pub fn scoped_temp() -> Result<ScopedConfig> {
+        let data_dir = std::env::temp_dir().join(format!(
+            "secure-signer-{}",
+            hex::encode(rand::random::<[u8; 16]>())
+        ));
+        std::fs::create_dir_all(&data_dir).with_context(|| "Failed to create temp data dir")?;
+        let mut guard = Config::new(&data_dir).scoped();
+        guard.remove_on_drop = Some(data_dir);
+        Ok(guard)
+    }
+
+    /// Overrides the config on the current thread with fresh in-memory storage, so nothing is
+    /// written to disk and everything is gone once the returned guard is dropped.
+    pub fn scoped_in_memory() -> ScopedConfig {
+        Config::default().with_storage(Storage::memory()).scoped()
+    }
+}
+
+/// Restores the previous thread config when dropped
+pub struct ScopedConfig {
+    previous: Option<Config>,
+    remove_on_drop: Option<PathBuf>,
+}
+
+impl Drop for ScopedConfig {
+    fn drop(&mut self) {
+        let previous = self.previous.take();
+        SCOPED.with(|c| *c.borrow_mut() = previous);
+        if let Some(dir) = &self.remove_on_drop {
+            std::fs::remove_dir_all(dir).ok();
+        }
+    }
+}
+
+/// Joins `fname` to `dir`, erroring if `fname` is anything other than a non-empty hex string.
+/// File names are derived from user supplied public keys, so this keeps them inside `dir`.
+pub fn hex_file_path(dir: &Path, fname: &str) -> Result<PathBuf> {
+    if fname.is_empty() || !fname.chars().all(|c| c.is_ascii_hexdigit()) {
+        bail!("Invalid file name {:?}, expected a hex string", fname)
+    }
+    Ok(dir.join(fname))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_scoped_config_is_restored() {
+        let outer = Config::current();
+        {
+            let _guard = Config::new("/tmp/signer-a").scoped();
+            assert_eq!(Config::current().data_dir, PathBuf::from("/tmp/signer-a"));
+            assert_eq!(
+                Config::current().bls_keys_dir(),
+                PathBuf::from("/tmp/signer-a/keys/bls_keys")
+            );
+        }
+        assert_eq!(Config::current(), outer);
+    }
+
+    #[test]
+    fn test_scoped_temp_dir_is_removed() {
+        let guard = Config::scoped_temp().unwrap();
+        let data_dir = Config::current().data_dir;
+        assert!(data_dir.exists());
+        drop(guard);
+        assert!(!data_dir.exists());
+    }
+
+    #[test]
+    fn test_parse_sealing_policy() {
+        assert_eq!(
+            "MRENCLAVE".parse::<SealingPolicy>().unwrap(),
+            SealingPolicy::MrEnclave
+        );
+        assert_eq!(
+            "mrsigner".parse::<SealingPolicy>().unwrap(),
+            SealingPolicy::MrSigner
+        );
+        assert!("mrwhatever".parse::<SealingPolicy>().is_err());
+    }
+
+    #[test]
+    fn test_hex_file_path_rejects_traversal() {
+        let dir = Path::new("/data/keys");
+        assert!(hex_file_path(dir, "abcd1234").is_ok());
+        assert!(hex_file_path(dir, "").is_err());
+        assert!(hex_file_path(dir, "../../etc/passwd").is_err());
+        assert!(hex_file_path(dir, "..").is_err());
+        assert!(hex_file_path(dir, "/etc/passwd").is_err());
+        assert!(hex_file_path(dir, "abcd/../ef").is_err());
+    }
+}
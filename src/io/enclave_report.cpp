@@ -0,0 +1,42 @@
+#include <fcntl.h>
+#include <stdio.h>
+#include <string.h>
+#include <sys/ioctl.h>
+#include <unistd.h>
+
+#include "sgx_report.h"
+
+// Occlum exposes EREPORT through the /dev/sgx device
+typedef struct {
+  const sgx_target_info_t* target_info;  // Input (optional)
+  const sgx_report_data_t* report_data;  // Input (optional)
+  sgx_report_t* report;                  // Output
+} sgxioc_create_report_arg_t;
+
+#define SGXIOC_CREATE_REPORT _IOWR('s', 4, sgxioc_create_report_arg_t)
+
+extern "C"
+int get_enclave_identity(uint8_t mrenclave[32], uint8_t mrsigner[32], uint16_t* isv_prod_id,
+                         uint16_t* isv_svn) {
+  int sgx_fd = open("/dev/sgx", O_RDONLY);
+  if (sgx_fd < 0) {
+    printf("Fail to open /dev/sgx!\n");
+    return -1;
+  }
+
+  // A report targeting ourselves carries the running enclave's identity
+  sgx_report_t report = {0};
+  sgxioc_create_report_arg_t arg = {NULL, NULL, &report};
+  int ret = ioctl(sgx_fd, SGXIOC_CREATE_REPORT, &arg);
+  close(sgx_fd);
+  if (ret < 0) {
+    printf("Fail to create the enclave report!\n");
+    return -1;
+  }
+
+  memcpy(mrenclave, report.body.mr_enclave.m, 32);
+  memcpy(mrsigner, report.body.mr_signer.m, 32);
+  *isv_prod_id = report.body.isv_prod_id;
+  *isv_svn = report.body.isv_svn;
+  return 0;
+}
@@ -1,16 +1,63 @@
-use crate::constants::{BLS_KEYS_DIR, ETH_KEYS_DIR};
+use crate::constants::EXECUTION_ADDRESS_BYTES;
+use crate::crypto::sealing::{seal, unseal, unseal_with_policy};
+use crate::io::config::{hex_file_path, Config, SealingPolicy};
 use crate::strip_0x_prefix;
 use anyhow::{bail, Context, Result};
+use serde::{Deserialize, Serialize};
 
 use std::fs;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
+
+/// Creates `dir` and its parents if missing. Safe to call from concurrent requests: losing the
+/// race to create a dir to another caller is not an error as long as the dir ends up existing.
+pub fn ensure_dir(dir: &Path) -> Result<()> {
+    match fs::create_dir_all(dir) {
+        Ok(()) => Ok(()),
+        Err(_) if dir.is_dir() => Ok(()),
+        Err(e) => Err(e).with_context(|| format!("Failed to create dir {:?}", dir.as_os_str())),
+    }
+}
 
-// Writes the sk_hex string to the specified path
-fn write_key(file_path: PathBuf, sk_hex: &str) -> Result<()> {
-    if let Some(p) = file_path.parent() {
-        fs::create_dir_all(p).with_context(|| "Failed to create keys dir")?
-    };
-    fs::write(&file_path, sk_hex).with_context(|| "failed to write sk")
+/// Writes `contents` to `file_path` in the configured storage. On disk concurrent readers and
+/// listings never see a partially written file, see `Storage::write`.
+pub fn write_atomic(file_path: &Path, contents: impl AsRef<[u8]>) -> Result<()> {
+    Config::current().storage.write(file_path, contents)
+}
+
+/// Reads `file_path` from the configured storage
+pub fn read_file(file_path: &Path) -> Result<Vec<u8>> {
+    Config::current().storage.read(file_path)
+}
+
+/// Returns true if `file_path` exists in the configured storage
+pub fn file_exists(file_path: &Path) -> bool {
+    Config::current().storage.exists(file_path)
+}
+
+/// Resolves `path`, relative to `dir` unless absolute, to an existing file inside `dir`. Symlinks
+/// and `..` are resolved first, so neither can reach outside of `dir`.
+pub fn resolve_under(dir: &Path, path: &str) -> Result<PathBuf> {
+    let dir = dir
+        .canonicalize()
+        .with_context(|| format!("Failed to resolve dir {:?}", dir.as_os_str()))?;
+    let resolved = dir
+        .join(path)
+        .canonicalize()
+        .with_context(|| format!("Failed to resolve {path}"))?;
+    if !resolved.starts_with(&dir) {
+        bail!("{path} is outside of {:?}", dir.as_os_str())
+    }
+    if !resolved.is_file() {
+        bail!("{path} is not a file")
+    }
+    Ok(resolved)
+}
+
+// Seals the sk_hex string to the subkey of pk_hex and writes it hex-encoded to the specified path
+fn write_key(file_path: PathBuf, pk_hex: &str, sk_hex: &str) -> Result<()> {
+    let sk_bytes = hex::decode(sk_hex).with_context(|| "Unable to hex-decode secret key")?;
+    let sealed = seal(pk_hex, &sk_bytes)?;
+    write_atomic(&file_path, hex::encode(sealed)).with_context(|| "failed to write sk")
 }
 
 /// Writes the hex-encoded ETH secret key to a file named from `fname`
@@ -18,8 +65,8 @@ pub fn write_eth_key(pk_hex: &String, sk_hex: &String) -> Result<()> {
     // Sanitize inputs
     let pk_hex: &str = strip_0x_prefix!(pk_hex);
     let sk_hex: &str = strip_0x_prefix!(sk_hex);
-    let file_path: PathBuf = [ETH_KEYS_DIR, pk_hex].iter().collect();
-    write_key(file_path, sk_hex)
+    let file_path = hex_file_path(&Config::current().eth_keys_dir(), pk_hex)?;
+    write_key(file_path, pk_hex, sk_hex)
 }
 
 /// Writes the hex-encoded BLS secret key to a file named from `fname`
@@ -27,125 +74,399 @@ pub fn write_bls_key(pk_hex: &String, sk_hex: &String) -> Result<()> {
     // Sanitize inputs
     let pk_hex: &str = strip_0x_prefix!(pk_hex);
     let sk_hex: &str = strip_0x_prefix!(sk_hex);
-    let file_path: PathBuf = [BLS_KEYS_DIR, pk_hex].iter().collect();
-    write_key(file_path, sk_hex)
+    let file_path = hex_file_path(&Config::current().bls_keys_dir(), pk_hex)?;
+    write_key(file_path, pk_hex, sk_hex)
 }
 
 /// Writes the BLS secret key to a keystore file
 pub fn write_bls_keystore(pk_hex: &String, sk: &[u8], password: &String) -> Result<String> {
+    if Config::current().storage.is_memory() {
+        bail!("Keystore files are only supported on disk")
+    }
+
     // Create the keys dir if it does not exist
-    fs::create_dir_all(BLS_KEYS_DIR).with_context(|| "Failed to create keys dir")?;
+    let bls_keys_dir = Config::current().bls_keys_dir();
+    ensure_dir(&bls_keys_dir).with_context(|| "Failed to create keys dir")?;
 
     // Sanitize inputs
     let pk_hex: &str = strip_0x_prefix!(pk_hex);
+    hex_file_path(&bls_keys_dir, pk_hex)?;
     let mut rng = rand::thread_rng();
 
     // Create encrypted keystore
-    let uuid = eth_keystore::encrypt_key(BLS_KEYS_DIR, &mut rng, sk, password, Some(pk_hex))?;
+    let uuid = eth_keystore::encrypt_key(&bls_keys_dir, &mut rng, sk, password, Some(pk_hex))?;
     Ok(uuid)
 }
 
-/// Reads hex-encoded secret key from the specified path and returns the hex-decoded bytes
-fn read_key(file_path: PathBuf) -> Result<Vec<u8>> {
-    let sk_rec_bytes = fs::read(&file_path).with_context(|| "Unable to read secret key")?;
-    hex::decode(sk_rec_bytes).with_context(|| "Unable to hex-decode secret key")
+/// Seals the secret authenticating sign requests for the BLS key `pk_hex` and saves it
+pub fn write_request_mac_secret(pk_hex: &str, secret: &[u8]) -> Result<()> {
+    let pk_hex: &str = strip_0x_prefix!(pk_hex);
+    let file_path = hex_file_path(&Config::current().request_mac_secrets_dir(), pk_hex)?;
+    write_key(file_path, pk_hex, &hex::encode(secret))
+}
+
+/// Reads the secret authenticating sign requests for the BLS key `pk_hex`
+pub fn read_request_mac_secret(pk_hex: &str) -> Result<Vec<u8>> {
+    let pk_hex: &str = strip_0x_prefix!(pk_hex);
+    let file_path = hex_file_path(&Config::current().request_mac_secrets_dir(), pk_hex)?;
+    read_key(file_path, pk_hex)
+}
+
+/// How a BLS key entered the enclave
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum KeyOrigin {
+    /// Generated inside the enclave
+    Generated,
+    /// Imported from a keystore
+    Imported,
+}
+
+/// Records how the BLS key `pk_hex` entered the enclave
+pub fn write_key_origin(pk_hex: &str, origin: KeyOrigin) -> Result<()> {
+    let pk_hex: &str = strip_0x_prefix!(pk_hex);
+    let file_path = hex_file_path(&Config::current().key_origin_dir(), pk_hex)?;
+    write_atomic(&file_path, serde_json::to_string(&origin)?)
+        .with_context(|| "Failed to write key origin")
+}
+
+/// Reads how the BLS key `pk_hex` entered the enclave, `None` for keys saved before origins were
+/// recorded
+pub fn read_key_origin(pk_hex: &str) -> Result<Option<KeyOrigin>> {
+    let pk_hex: &str = strip_0x_prefix!(pk_hex);
+    let file_path = hex_file_path(&Config::current().key_origin_dir(), pk_hex)?;
+    if !file_exists(&file_path) {
+        return Ok(None);
+    }
+    let json = read_file(&file_path)?;
+    let origin = serde_json::from_slice(&json).with_context(|| "Failed to read key origin")?;
+    Ok(Some(origin))
+}
+
+/// Parses the fee recipients a key may register with, returning them as lowercase hex without the
+/// 0x prefix. Errors if the list is empty or any of them isn't a 20 byte address.
+pub fn parse_fee_recipient_allowlist(fee_recipients: &[String]) -> Result<Vec<String>> {
+    if fee_recipients.is_empty() {
+        bail!("Empty fee recipient allowlist")
+    }
+    fee_recipients
+        .iter()
+        .map(|address| {
+            let address: &str = strip_0x_prefix!(address);
+            match hex::decode(address) {
+                Ok(bytes) if bytes.len() == EXECUTION_ADDRESS_BYTES => Ok(hex::encode(bytes)),
+                _ => bail!("Bad fee recipient {address}, expected a 20 byte hex address"),
+            }
+        })
+        .collect()
+}
+
+/// Saves the fee recipients the BLS key `pk_hex` may register with
+pub fn write_fee_recipient_allowlist(pk_hex: &str, fee_recipients: &[String]) -> Result<()> {
+    let pk_hex: &str = strip_0x_prefix!(pk_hex);
+    let fee_recipients = parse_fee_recipient_allowlist(fee_recipients)?;
+    let file_path = hex_file_path(&Config::current().fee_recipient_allowlist_dir(), pk_hex)?;
+    write_atomic(&file_path, serde_json::to_string(&fee_recipients)?)
+        .with_context(|| "Failed to write fee recipient allowlist")
+}
+
+/// Reads the fee recipients the BLS key `pk_hex` may register with, as lowercase hex without the
+/// 0x prefix. `None` if none were provisioned, in which case any fee recipient is allowed.
+pub fn read_fee_recipient_allowlist(pk_hex: &str) -> Result<Option<Vec<String>>> {
+    let pk_hex: &str = strip_0x_prefix!(pk_hex);
+    let file_path = hex_file_path(&Config::current().fee_recipient_allowlist_dir(), pk_hex)?;
+    if !file_exists(&file_path) {
+        return Ok(None);
+    }
+    let json = read_file(&file_path)?;
+    let fee_recipients =
+        serde_json::from_slice(&json).with_context(|| "Failed to read fee recipient allowlist")?;
+    Ok(Some(fee_recipients))
+}
+
+/// Allowlist entry letting a key sign caller-computed roots on the raw signing route
+pub const RAW_SIGN_TYPE: &str = "RAW";
+
+/// Parses the request types a key may sign, returning them as named in the Web3Signer API, or
+/// `RAW_SIGN_TYPE`. Errors if the list is empty or names an unknown type.
+pub fn parse_sign_type_allowlist(types: &[String]) -> Result<Vec<String>> {
+    if types.is_empty() {
+        bail!("Empty signing type allowlist")
+    }
+    types
+        .iter()
+        .map(|t| {
+            let t = t.trim().to_ascii_uppercase();
+            match t == RAW_SIGN_TYPE
+                || crate::eth2::eth_signing::BLSSignMsg::TYPE_NAMES.contains(&t.as_str())
+            {
+                true => Ok(t),
+                false => bail!("Unknown signing type {t}"),
+            }
+        })
+        .collect()
+}
+
+/// Saves the request types the BLS key `pk_hex` may sign
+pub fn write_sign_type_allowlist(pk_hex: &str, types: &[String]) -> Result<()> {
+    let pk_hex: &str = strip_0x_prefix!(pk_hex);
+    let types = parse_sign_type_allowlist(types)?;
+    let file_path = hex_file_path(&Config::current().sign_type_allowlist_dir(), pk_hex)?;
+    write_atomic(&file_path, serde_json::to_string(&types)?)
+        .with_context(|| "Failed to write signing type allowlist")
+}
+
+/// Reads the request types the BLS key `pk_hex` may sign. `None` if none were provisioned, in
+/// which case any type is allowed.
+pub fn read_sign_type_allowlist(pk_hex: &str) -> Result<Option<Vec<String>>> {
+    let pk_hex: &str = strip_0x_prefix!(pk_hex);
+    let file_path = hex_file_path(&Config::current().sign_type_allowlist_dir(), pk_hex)?;
+    if !file_exists(&file_path) {
+        return Ok(None);
+    }
+    let json = read_file(&file_path)?;
+    let types =
+        serde_json::from_slice(&json).with_context(|| "Failed to read signing type allowlist")?;
+    Ok(Some(types))
+}
+
+/// Binds the BLS key `pk_hex` to the validator index it may propose blocks as
+pub fn write_proposer_index(pk_hex: &str, proposer_index: u64) -> Result<()> {
+    let pk_hex: &str = strip_0x_prefix!(pk_hex);
+    let file_path = hex_file_path(&Config::current().proposer_index_dir(), pk_hex)?;
+    write_atomic(&file_path, serde_json::to_string(&proposer_index)?)
+        .with_context(|| "Failed to write proposer index")
+}
+
+/// Reads the validator index the BLS key `pk_hex` may propose blocks as. `None` if the key was
+/// never bound, in which case any index is allowed.
+pub fn read_proposer_index(pk_hex: &str) -> Result<Option<u64>> {
+    let pk_hex: &str = strip_0x_prefix!(pk_hex);
+    let file_path = hex_file_path(&Config::current().proposer_index_dir(), pk_hex)?;
+    if !file_exists(&file_path) {
+        return Ok(None);
+    }
+    let json = read_file(&file_path)?;
+    let proposer_index =
+        serde_json::from_slice(&json).with_context(|| "Failed to read proposer index")?;
+    Ok(Some(proposer_index))
+}
+
+/// Tags the metrics of the BLS key `pk_hex` with `label`
+pub fn write_metrics_label(pk_hex: &str, label: &str) -> Result<()> {
+    let pk_hex: &str = strip_0x_prefix!(pk_hex);
+    let file_path = hex_file_path(&Config::current().metrics_label_dir(), pk_hex)?;
+    write_atomic(&file_path, serde_json::to_string(label)?)
+        .with_context(|| "Failed to write metrics label")
+}
+
+/// Reads the label the metrics of the BLS key `pk_hex` are tagged with, `None` if it has none
+pub fn read_metrics_label(pk_hex: &str) -> Result<Option<String>> {
+    let pk_hex: &str = strip_0x_prefix!(pk_hex);
+    let file_path = hex_file_path(&Config::current().metrics_label_dir(), pk_hex)?;
+    if !file_exists(&file_path) {
+        return Ok(None);
+    }
+    let json = read_file(&file_path)?;
+    let label = serde_json::from_slice(&json).with_context(|| "Failed to read metrics label")?;
+    Ok(Some(label))
+}
+
+/// Size of the key files written before keys were sealed, which held the raw secret key. A sealed
+/// key of the same size carries a nonce and tag on top, so it is never this short.
+const LEGACY_SECRET_KEY_BYTES: usize = 32;
+
+/// Reads the sealed secret key from the specified path. A plaintext key left by a version that
+/// didn't seal keys is sealed in place first. Returns whether the key had to be migrated.
+fn read_sealed_key(file_path: &Path, pk_hex: &str) -> Result<(Vec<u8>, bool)> {
+    let stored_hex = read_file(file_path).with_context(|| "Unable to read secret key")?;
+    let stored = hex::decode(stored_hex).with_context(|| "Unable to hex-decode secret key")?;
+    if stored.len() != LEGACY_SECRET_KEY_BYTES {
+        return Ok((stored, false));
+    }
+    log::warn!("Sealing the plaintext secret key of 0x{pk_hex}");
+    let sealed = seal(pk_hex, &stored)?;
+    write_atomic(file_path, hex::encode(&sealed)).with_context(|| "failed to write sk")?;
+    Ok((sealed, true))
+}
+
+/// Reads the sealed secret key from the specified path and returns it unsealed with the subkey of pk_hex
+fn read_key(file_path: PathBuf, pk_hex: &str) -> Result<Vec<u8>> {
+    let (sealed, _) = read_sealed_key(&file_path, pk_hex)?;
+    unseal(pk_hex, &sealed)
 }
 
 /// Reads hex-encoded ETH secret key from a file named from `pk_hex` and returns the bytes
 pub fn read_eth_key(pk_hex: &str) -> Result<Vec<u8>> {
     let pk_hex: &str = strip_0x_prefix!(pk_hex);
-    let file_path: PathBuf = [ETH_KEYS_DIR, pk_hex].iter().collect();
-    read_key(file_path)
+    let file_path = hex_file_path(&Config::current().eth_keys_dir(), pk_hex)?;
+    read_key(file_path, pk_hex)
 }
 
 /// Reads hex-encoded BLS secret key from a file named from `pk_hex` and returns the bytes
 pub fn read_bls_key(pk_hex: &str) -> Result<Vec<u8>> {
     let pk_hex: &str = strip_0x_prefix!(pk_hex);
-    let file_path: PathBuf = [BLS_KEYS_DIR, pk_hex].iter().collect();
-    read_key(file_path)
+    let file_path = hex_file_path(&Config::current().bls_keys_dir(), pk_hex)?;
+    read_key(file_path, pk_hex)
 }
 
 /// Reads BLS secret key from encrypted keystore
 pub fn read_bls_keystore(pk_hex: &String, password: &String) -> Result<Vec<u8>> {
+    if Config::current().storage.is_memory() {
+        bail!("Keystore files are only supported on disk")
+    }
     // Sanitize inputs
     let pk_hex: &str = strip_0x_prefix!(pk_hex);
-    let file_path: PathBuf = [BLS_KEYS_DIR, pk_hex].iter().collect();
+    let file_path = hex_file_path(&Config::current().bls_keys_dir(), pk_hex)?;
     let sk_bytes = eth_keystore::decrypt_key(file_path, password)?;
     Ok(sk_bytes)
 }
 
 /// Deletes the secret key saved at the specified path
 fn delete_key(file_path: PathBuf) -> Result<()> {
-    fs::remove_file(&file_path)
+    Config::current()
+        .storage
+        .remove(&file_path)
         .with_context(|| format!("failed to delete key at: {:?}", file_path.as_os_str()))
 }
 
 /// Deletes the ETH secret key saved at the specified path
 pub fn delete_eth_key(pk_hex: &str) -> Result<()> {
     let pk_hex: &str = strip_0x_prefix!(pk_hex);
-    let file_path: PathBuf = [ETH_KEYS_DIR, pk_hex].iter().collect();
+    let file_path = hex_file_path(&Config::current().eth_keys_dir(), pk_hex)?;
     delete_key(file_path)
 }
 
 /// Deletes the BLS secret key saved at the specified path
 pub fn delete_bls_key(pk_hex: &str) -> Result<()> {
     let pk_hex: &str = strip_0x_prefix!(pk_hex);
-    let file_path: PathBuf = [BLS_KEYS_DIR, pk_hex].iter().collect();
+    let file_path = hex_file_path(&Config::current().bls_keys_dir(), pk_hex)?;
     delete_key(file_path)
 }
 
 /// Return true if the key at the specified path exists
 fn key_exists(file_path: &PathBuf) -> bool {
-    file_path.exists()
+    file_exists(file_path)
 }
 
 /// Return true if the ETH key at the specified path exists
 pub fn eth_key_exists(pk_hex: &str) -> bool {
     let pk_hex: &str = strip_0x_prefix!(pk_hex);
-    let file_path: PathBuf = [ETH_KEYS_DIR, pk_hex].iter().collect();
-    key_exists(&file_path)
+    match hex_file_path(&Config::current().eth_keys_dir(), pk_hex) {
+        Ok(file_path) => key_exists(&file_path),
+        Err(_) => false,
+    }
 }
 
 /// Return true if the BLS key at the specified path exists
 pub fn bls_key_exists(pk_hex: &str) -> bool {
     let pk_hex: &str = strip_0x_prefix!(pk_hex);
-    let file_path: PathBuf = [BLS_KEYS_DIR, pk_hex].iter().collect();
-    key_exists(&file_path)
+    match hex_file_path(&Config::current().bls_keys_dir(), pk_hex) {
+        Ok(file_path) => key_exists(&file_path),
+        Err(_) => false,
+    }
 }
 
-/// Return the file names in the specified directory
-fn list_fnames(path_to_dir: &str) -> Result<Vec<String>> {
-    let paths = fs::read_dir(path_to_dir).with_context(|| "No keys saved in dir")?;
-
-    let mut keys: Vec<String> = Vec::new();
-    for path in paths {
-        // Get the paths to each file in this dir
-        let p = match path.as_ref().err() {
-            Some(e) => bail!("failed to find path: {}", e),
-            _ => path.unwrap(),
-        };
-
-        // remove path prefix, to grab just the file name
-        let fname = p.file_name();
-
-        match fname.to_os_string().into_string() {
-            Ok(s) => keys.push(s),
-            Err(e) => bail!("Error, bad file name in list_keys(): {:?}", e),
-        }
-    }
-    Ok(keys)
+/// Return the file names in the specified directory in lexicographic order
+fn list_fnames(path_to_dir: &Path) -> Result<Vec<String>> {
+    Config::current().storage.list(path_to_dir)
 }
 
 /// Returns the file names of each of the saved bls secret keys, where each fname
 /// is assumed to be the compressed public key in hex without the `0x` prefix.
 pub fn list_bls_keys() -> Result<Vec<String>> {
-    list_fnames(BLS_KEYS_DIR)
+    list_fnames(&Config::current().bls_keys_dir())
+}
+
+/// Returns the saved bls keys that signing requests are accepted for, i.e. those whose file name
+/// is a valid compressed public key, in hex without the `0x` prefix.
+pub fn list_signable_bls_keys() -> Result<Vec<String>> {
+    Ok(list_bls_keys()?
+        .into_iter()
+        .filter(|pk_hex| {
+            crate::crypto::bls_keys::validate_bls_pk_hex(&format!("0x{pk_hex}")).is_ok()
+        })
+        .collect())
 }
 
 /// Returns the file names of each of the saved eth secret keys, where each fname
 /// is assumed to be the eth wallet address derived from the eth public key in hex without the `0x` prefix.
 pub fn list_eth_keys() -> Result<Vec<String>> {
-    list_fnames(ETH_KEYS_DIR)
+    list_fnames(&Config::current().eth_keys_dir())
+}
+
+/// Re-seals the key at `file_path` under the configured policy if it was sealed under another one
+/// or not sealed at all. Returns whether it had to be re-sealed.
+fn reseal_key(file_path: PathBuf, pk_hex: &str) -> Result<bool> {
+    let (sealed, migrated) = read_sealed_key(&file_path, pk_hex)?;
+    let current = Config::current().sealing_policy;
+    if unseal_with_policy(current, pk_hex, &sealed).is_ok() {
+        return Ok(migrated);
+    }
+    let Some(sk) = SealingPolicy::ALL
+        .into_iter()
+        .filter(|policy| *policy != current)
+        .find_map(|policy| unseal_with_policy(policy, pk_hex, &sealed).ok())
+    else {
+        bail!("Not sealed under any policy this enclave can unseal")
+    };
+    write_key(file_path, pk_hex, &hex::encode(sk))?;
+    Ok(true)
+}
+
+/// Re-seals every stored secret (BLS and ETH keys, request MAC secrets) that was sealed under
+/// another policy than the configured one or stored in plaintext by an older version, so it stays
+/// readable after an upgrade. Only keys this
+/// enclave can unseal under some policy can be migrated, e.g. MRSIGNER-sealed keys after a new
+/// build, never MRENCLAVE-sealed keys of an older build.
+pub fn reseal_keys() -> Result<crate::enclave::types::ResealResponse> {
+    let config = Config::current();
+    let mut resp = crate::enclave::types::ResealResponse::default();
+    for dir in [
+        config.bls_keys_dir(),
+        config.eth_keys_dir(),
+        config.request_mac_secrets_dir(),
+    ] {
+        for fname in list_fnames(&dir)? {
+            match hex_file_path(&dir, &fname).and_then(|path| reseal_key(path, &fname)) {
+                Ok(true) => resp.resealed.push(format!("0x{fname}")),
+                Ok(false) => {}
+                Err(e) => {
+                    log::error!("Failed to reseal {:?}: {:?}", dir.join(&fname), e);
+                    resp.failed.push(format!("0x{fname}"));
+                }
+            }
+        }
+    }
+    Ok(resp)
+}
+
+/// Name of the file the storage health check round-trips through the data dir
+const HEALTH_CHECK_FNAME: &str = ".healthz";
+
+/// Confirms the data dir is writable and that sealing works by sealing a dummy value,
+/// writing it to the data dir, then reading it back and unsealing it.
+pub fn storage_health_check() -> Result<()> {
+    let config = Config::current();
+    let file_path = config.data_dir.join(HEALTH_CHECK_FNAME);
+
+    let dummy: [u8; 32] = rand::random();
+    let dummy_id = hex::encode(&dummy[..8]);
+    let sealed = seal(&dummy_id, &dummy)?;
+    config
+        .storage
+        .write(&file_path, &sealed)
+        .with_context(|| "Data dir is not writable")?;
+
+    let read_back = config
+        .storage
+        .read(&file_path)
+        .with_context(|| "Data dir is not readable");
+    config.storage.remove(&file_path).ok();
+    if unseal(&dummy_id, &read_back?)? != dummy {
+        bail!("Unsealed value does not match the sealed value")
+    }
+    Ok(())
 }
 
 #[cfg(test)]
@@ -153,58 +474,112 @@ mod test_key_management {
     use hex::FromHex;
 
     use super::*;
-    use crate::constants::KEYS_DIR;
-    use std::path::Path;
 
     // Helper function to read the content of a file
     fn read_file(file_path: &Path) -> Result<String> {
         fs::read_to_string(file_path).with_context(|| "failed to read")
     }
 
+    #[test]
+    fn test_resolve_under() {
+        let _data_dir = Config::scoped_temp().unwrap();
+        let dir = Config::current().data_dir.join("imports");
+        ensure_dir(&dir.join("nested")).unwrap();
+        fs::write(dir.join("nested").join("interchange.json"), "{}").unwrap();
+        fs::write(Config::current().data_dir.join("outside.json"), "{}").unwrap();
+
+        let expected = dir
+            .join("nested")
+            .join("interchange.json")
+            .canonicalize()
+            .unwrap();
+        assert_eq!(
+            resolve_under(&dir, "nested/interchange.json").unwrap(),
+            expected
+        );
+        assert_eq!(
+            resolve_under(&dir, expected.to_str().unwrap()).unwrap(),
+            expected
+        );
+        assert_eq!(
+            resolve_under(&dir, "nested/../nested/interchange.json").unwrap(),
+            expected
+        );
+
+        // Nothing outside of the dir, however it's spelled
+        assert!(resolve_under(&dir, "../outside.json").is_err());
+        assert!(resolve_under(&dir, "nested/../../outside.json").is_err());
+        let outside = Config::current().data_dir.join("outside.json");
+        assert!(resolve_under(&dir, outside.to_str().unwrap()).is_err());
+        #[cfg(unix)]
+        {
+            std::os::unix::fs::symlink(&outside, dir.join("link.json")).unwrap();
+            assert!(resolve_under(&dir, "link.json").is_err());
+        }
+
+        // Only existing files
+        assert!(resolve_under(&dir, "missing.json").is_err());
+        assert!(resolve_under(&dir, "nested").is_err());
+    }
+
     #[test]
     fn test_write_key() {
-        let file_path: PathBuf = [KEYS_DIR, "test"].iter().collect();
+        let _data_dir = Config::scoped_temp().unwrap();
+        let file_path: PathBuf = Config::current().keys_dir().join("test");
 
+        let pk_hex = "1234abcd";
         let sk_hex = "abcdef123456";
 
-        write_key(file_path.clone(), sk_hex).unwrap();
+        write_key(file_path.clone(), pk_hex, sk_hex).unwrap();
 
+        // The key is sealed at rest
         let written_content = read_file(&file_path).unwrap();
-        assert_eq!(written_content, sk_hex);
-        fs::remove_dir_all("./etc").ok();
+        assert!(!written_content.contains(sk_hex));
+        assert_eq!(
+            read_key(file_path, pk_hex).unwrap(),
+            hex::decode(sk_hex).unwrap()
+        );
     }
 
     #[test]
     fn test_write_eth_key() {
-        fs::remove_dir_all("./etc").ok();
+        let _data_dir = Config::scoped_temp().unwrap();
         let pk_hex = "0x1234abcd";
         let sk_hex = "0xabcdef123456";
 
         write_eth_key(&pk_hex.to_string(), &sk_hex.to_string()).unwrap();
 
-        let file_path: PathBuf = [ETH_KEYS_DIR, "1234abcd"].iter().collect();
+        let file_path: PathBuf = Config::current().eth_keys_dir().join("1234abcd");
         let written_content = read_file(&file_path).unwrap();
-        assert_eq!(written_content, "abcdef123456");
-        fs::remove_dir_all("./etc").ok();
+        assert!(!written_content.contains("abcdef123456"));
+        let sealed = hex::decode(written_content).unwrap();
+        assert_eq!(
+            unseal(pk_hex, &sealed).unwrap(),
+            hex::decode("abcdef123456").unwrap()
+        );
     }
 
     #[test]
     fn test_write_bls_key() {
-        fs::remove_dir_all("./etc").ok();
+        let _data_dir = Config::scoped_temp().unwrap();
         let pk_hex = "0x1234abcd";
         let sk_hex = "0xabcdef123456";
 
         write_bls_key(&pk_hex.to_string(), &sk_hex.to_string()).unwrap();
 
-        let file_path: PathBuf = [BLS_KEYS_DIR, "1234abcd"].iter().collect();
+        let file_path: PathBuf = Config::current().bls_keys_dir().join("1234abcd");
         let written_content = read_file(&file_path).unwrap();
-        assert_eq!(written_content, "abcdef123456");
-        fs::remove_dir_all("./etc").ok();
+        assert!(!written_content.contains("abcdef123456"));
+        let sealed = hex::decode(written_content).unwrap();
+        assert_eq!(
+            unseal(pk_hex, &sealed).unwrap(),
+            hex::decode("abcdef123456").unwrap()
+        );
     }
 
     #[test]
     fn test_write_read_delete_eth_key() {
-        fs::remove_dir_all("./etc").ok();
+        let _data_dir = Config::scoped_temp().unwrap();
         let pk_hex = "0x1234abcd";
         let sk_hex = "0xabcdef123456";
 
@@ -224,7 +599,7 @@ mod test_key_management {
 
     #[test]
     fn test_write_read_delete_bls_key() {
-        fs::remove_dir_all("./etc").ok();
+        let _data_dir = Config::scoped_temp().unwrap();
         let pk_hex = "0x1234abcd";
         let sk_hex = "0xabcdef123456";
 
@@ -244,7 +619,7 @@ mod test_key_management {
 
     #[test]
     fn test_write_read_delete_bls_keystore() {
-        fs::remove_dir_all("./etc").ok();
+        let _data_dir = Config::scoped_temp().unwrap();
         let pk_hex = "a8a1580a80406ccb0a89e1115c92ec1a09994e2ac6341cfddcad5daf75f587244aa6d722b3449a17b0b0b482c1d13215";
         let sk_hex = "4c627588f8040116b75f14fdb55b552612a46a2cd91e65b516defe39d81fc08f";
         let sk_bytes_in = hex::decode(sk_hex).unwrap();
@@ -267,7 +642,7 @@ mod test_key_management {
 
     #[test]
     fn test_list_eth_keys() {
-        fs::remove_dir_all("./etc").ok();
+        let _data_dir = Config::scoped_temp().unwrap();
         let pk_hex1 = "0x1234abcd";
         let sk_hex1 = "0xabcdef123456";
         let pk_hex2 = "0x5678ef01";
@@ -297,7 +672,7 @@ mod test_key_management {
 
     #[test]
     fn test_list_bls_keys() {
-        fs::remove_dir_all("./etc").ok();
+        let _data_dir = Config::scoped_temp().unwrap();
         let pk_hex1 = "0x1234abcd";
         let sk_hex1 = "0xabcdef123456";
         let pk_hex2 = "0x5678ef01";
@@ -324,4 +699,126 @@ mod test_key_management {
         let bls_keys = list_bls_keys().unwrap();
         assert_eq!(bls_keys.len(), 0);
     }
+
+    #[test]
+    fn test_keys_are_isolated_per_data_dir() {
+        let pk_hex = "0x1234abcd";
+        let sk_hex = "0xabcdef123456";
+        {
+            let _data_dir = Config::scoped_temp().unwrap();
+            write_bls_key(&pk_hex.to_string(), &sk_hex.to_string()).unwrap();
+            assert!(bls_key_exists(pk_hex));
+        }
+        let _data_dir = Config::scoped_temp().unwrap();
+        assert!(!bls_key_exists(pk_hex));
+    }
+
+    #[test]
+    fn test_storage_health_check() {
+        let _data_dir = Config::scoped_temp().unwrap();
+        storage_health_check().unwrap();
+        assert!(!Config::current().data_dir.join(HEALTH_CHECK_FNAME).exists());
+
+        // A data dir nested under a regular file can never be created, even as root
+        let file = Config::current().data_dir.join("file");
+        fs::write(&file, "").unwrap();
+        let _unwritable = Config::new(file.join("data")).scoped();
+        assert!(storage_health_check().is_err());
+    }
+
+    #[test]
+    fn test_write_read_fee_recipient_allowlist() {
+        let _data_dir = Config::scoped_temp().unwrap();
+        let pk_hex = "0x1234abcd";
+        assert_eq!(read_fee_recipient_allowlist(pk_hex).unwrap(), None);
+
+        let allowed = format!("0x{}", "AB".repeat(20));
+        write_fee_recipient_allowlist(pk_hex, &[allowed]).unwrap();
+        assert_eq!(
+            read_fee_recipient_allowlist(pk_hex).unwrap(),
+            Some(vec!["ab".repeat(20)])
+        );
+
+        assert!(write_fee_recipient_allowlist(pk_hex, &[]).is_err());
+        assert!(write_fee_recipient_allowlist(pk_hex, &["0x2a2a".to_string()]).is_err());
+    }
+
+    #[test]
+    fn test_write_read_proposer_index() {
+        let _data_dir = Config::scoped_temp().unwrap();
+        let pk_hex = "0x1234abcd";
+        assert_eq!(read_proposer_index(pk_hex).unwrap(), None);
+
+        write_proposer_index(pk_hex, 42).unwrap();
+        assert_eq!(read_proposer_index("1234abcd").unwrap(), Some(42));
+    }
+
+    #[test]
+    fn test_write_read_metrics_label() {
+        let _data_dir = Config::scoped_temp().unwrap();
+        let pk_hex = "0x1234abcd";
+        assert_eq!(read_metrics_label(pk_hex).unwrap(), None);
+
+        write_metrics_label(pk_hex, "pool-a").unwrap();
+        assert_eq!(
+            read_metrics_label("1234abcd").unwrap().as_deref(),
+            Some("pool-a")
+        );
+    }
+
+    #[test]
+    fn test_path_traversal_is_rejected() {
+        let _data_dir = Config::scoped_temp().unwrap();
+        let sk_hex = "0xabcdef123456".to_string();
+        for pk_hex in [
+            "../../etc/passwd",
+            "../slashing/1234",
+            "/tmp/abcd",
+            "",
+            "0x",
+        ] {
+            assert!(write_bls_key(&pk_hex.to_string(), &sk_hex).is_err());
+            assert!(write_eth_key(&pk_hex.to_string(), &sk_hex).is_err());
+            assert!(read_bls_key(pk_hex).is_err());
+            assert!(delete_bls_key(pk_hex).is_err());
+            assert!(!bls_key_exists(pk_hex));
+        }
+    }
+
+    #[test]
+    fn test_concurrent_keygen_on_fresh_data_dir() {
+        // Nothing under the data dir exists yet, so every thread races to create the key dirs
+        let _data_dir = Config::scoped_temp().unwrap();
+        let config = Config::current();
+
+        let handles: Vec<_> = (0..16)
+            .map(|i| {
+                let config = config.clone();
+                std::thread::spawn(move || -> Result<String> {
+                    let _config = config.scoped();
+                    if i % 2 == 0 {
+                        let sk_set = crate::crypto::bls_keys::new_bls_key(0);
+                        crate::crypto::bls_keys::save_bls_key(&sk_set)?;
+                        crate::eth2::slash_protection::SlashingProtectionData::from_pk_hex(
+                            &sk_set.public_keys().public_key().to_hex(),
+                        )?
+                        .write()?;
+                        Ok(sk_set.public_keys().public_key().to_hex())
+                    } else {
+                        let pk = crate::crypto::eth_keys::eth_key_gen()?;
+                        Ok(crate::crypto::eth_keys::eth_pk_to_hex(&pk))
+                    }
+                })
+            })
+            .collect();
+        let pks: Vec<String> = handles
+            .into_iter()
+            .map(|h| h.join().unwrap().unwrap())
+            .collect();
+
+        let distinct: std::collections::HashSet<&String> = pks.iter().collect();
+        assert_eq!(distinct.len(), pks.len());
+        assert_eq!(list_bls_keys().unwrap().len(), 8);
+        assert_eq!(list_eth_keys().unwrap().len(), 8);
+    }
 }
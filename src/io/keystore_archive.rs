@@ -0,0 +1,217 @@
+use crate::constants::{MAX_KEYSTORE_ARCHIVE_BYTES, MAX_KEYSTORE_ARCHIVE_ENTRIES};
+
+use anyhow::{bail, Context, Result};
+use std::collections::HashMap;
+use std::io::{Cursor, Read};
+
+/// Name of the archive entry mapping keystore file names to their hex passwords ECIES-encrypted
+/// to the enclave ETH key
+pub const PASSWORDS_FILE: &str = "passwords.json";
+
+/// Name of the optional archive entry mapping keystore file names to hex request MAC secrets
+/// ECIES-encrypted to the enclave ETH key
+pub const MAC_SECRETS_FILE: &str = "mac_secrets.json";
+
+/// Name of the optional archive entry mapping keystore file names to their allowed fee recipients
+pub const FEE_RECIPIENTS_FILE: &str = "fee_recipients.json";
+
+/// Name of the archive entry holding the EIP-3076 slashing protection interchange
+pub const INTERCHANGE_FILE: &str = "slashing_protection.json";
+
+const ZIP_MAGIC: &[u8] = b"PK\x03\x04";
+
+/// Contents of a keystore archive, keyed by file name. Directories inside the archive are
+/// flattened, so `validators/keystore-0.json` is read as `keystore-0.json`.
+#[derive(Debug, Default)]
+pub struct KeystoreArchive {
+    /// (file name, keystore JSON) in archive order
+    pub keystores: Vec<(String, String)>,
+    /// Keystore file name to its hex password ECIES-encrypted to the enclave ETH key
+    pub passwords: HashMap<String, String>,
+    /// Keystore file name to the hex secret authenticating its sign requests, ECIES-encrypted to
+    /// the enclave ETH key
+    pub mac_secrets: HashMap<String, String>,
+    /// Keystore file name to the fee recipients its validator registrations may use
+    pub fee_recipients: HashMap<String, Vec<String>>,
+    /// The slashing protection interchange, if the archive carries one
+    pub interchange: Option<String>,
+}
+
+impl KeystoreArchive {
+    /// Reads a zip or tar archive. Errors if it holds no keystores, more than
+    /// `MAX_KEYSTORE_ARCHIVE_ENTRIES` files, or unpacks to more than `MAX_KEYSTORE_ARCHIVE_BYTES`.
+    pub fn read(bytes: &[u8]) -> Result<Self> {
+        if bytes.len() > MAX_KEYSTORE_ARCHIVE_BYTES {
+            bail!("Archive exceeds {} bytes", MAX_KEYSTORE_ARCHIVE_BYTES)
+        }
+        let files = if bytes.starts_with(ZIP_MAGIC) {
+            read_zip(bytes)?
+        } else {
+            read_tar(bytes)?
+        };
+
+        let mut archive = KeystoreArchive::default();
+        for (name, contents) in files {
+            match name.as_str() {
+                PASSWORDS_FILE => {
+                    archive.passwords = serde_json::from_str(&contents)
+                        .with_context(|| format!("Bad {PASSWORDS_FILE}"))?
+                }
+                MAC_SECRETS_FILE => {
+                    archive.mac_secrets = serde_json::from_str(&contents)
+                        .with_context(|| format!("Bad {MAC_SECRETS_FILE}"))?
+                }
+                FEE_RECIPIENTS_FILE => {
+                    archive.fee_recipients = serde_json::from_str(&contents)
+                        .with_context(|| format!("Bad {FEE_RECIPIENTS_FILE}"))?
+                }
+                INTERCHANGE_FILE => archive.interchange = Some(contents),
+                _ if name.ends_with(".json") => archive.keystores.push((name, contents)),
+                // Anything else, e.g. a README or deposit data, is not ours to import
+                _ => {}
+            }
+        }
+        if archive.keystores.is_empty() {
+            bail!("Archive holds no keystores")
+        }
+        Ok(archive)
+    }
+}
+
+/// Keeps track of the entry count and unpacked size while reading an archive
+#[derive(Default)]
+struct Budget {
+    entries: usize,
+    bytes: usize,
+}
+
+impl Budget {
+    fn read(&mut self, name: &str, entry: impl Read) -> Result<String> {
+        self.entries += 1;
+        if self.entries > MAX_KEYSTORE_ARCHIVE_ENTRIES {
+            bail!(
+                "Archive has more than {} files",
+                MAX_KEYSTORE_ARCHIVE_ENTRIES
+            )
+        }
+        // Read one byte past the remaining budget to tell a full budget from an overrun
+        let remaining = MAX_KEYSTORE_ARCHIVE_BYTES - self.bytes;
+        let mut contents = String::new();
+        entry
+            .take(remaining as u64 + 1)
+            .read_to_string(&mut contents)
+            .with_context(|| format!("Failed to read {name}"))?;
+        if contents.len() > remaining {
+            bail!(
+                "Archive unpacks to more than {} bytes",
+                MAX_KEYSTORE_ARCHIVE_BYTES
+            )
+        }
+        self.bytes += contents.len();
+        Ok(contents)
+    }
+}
+
+/// Returns the last path component, or `None` for entries that aren't ours to read
+fn file_name(path: &str) -> Option<String> {
+    let name = path.rsplit('/').next()?;
+    // Skip hidden files and the resource forks macOS adds to zips
+    if name.is_empty() || name.starts_with('.') || path.starts_with("__MACOSX/") {
+        return None;
+    }
+    Some(name.to_string())
+}
+
+fn read_zip(bytes: &[u8]) -> Result<Vec<(String, String)>> {
+    let mut zip = zip::ZipArchive::new(Cursor::new(bytes)).with_context(|| "Bad zip archive")?;
+    if zip.len() > MAX_KEYSTORE_ARCHIVE_ENTRIES {
+        bail!(
+            "Archive has more than {} files",
+            MAX_KEYSTORE_ARCHIVE_ENTRIES
+        )
+    }
+    let mut budget = Budget::default();
+    let mut files = vec![];
+    for i in 0..zip.len() {
+        let entry = zip.by_index(i).with_context(|| "Bad zip archive")?;
+        if entry.is_dir() {
+            continue;
+        }
+        let Some(name) = file_name(entry.name()) else {
+            continue;
+        };
+        let contents = budget.read(&name, entry)?;
+        files.push((name, contents));
+    }
+    Ok(files)
+}
+
+fn read_tar(bytes: &[u8]) -> Result<Vec<(String, String)>> {
+    let mut tar = tar::Archive::new(Cursor::new(bytes));
+    let mut budget = Budget::default();
+    let mut files = vec![];
+    for entry in tar.entries().with_context(|| "Bad tar archive")? {
+        let entry = entry.with_context(|| "Bad tar archive")?;
+        if !entry.header().entry_type().is_file() {
+            continue;
+        }
+        let path = entry.path()?.to_string_lossy().to_string();
+        let Some(name) = file_name(&path) else {
+            continue;
+        };
+        let contents = budget.read(&name, entry)?;
+        files.push((name, contents));
+    }
+    Ok(files)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn tar_of(files: &[(&str, &str)]) -> Vec<u8> {
+        let mut builder = tar::Builder::new(Vec::new());
+        for (path, contents) in files {
+            let mut header = tar::Header::new_gnu();
+            header.set_size(contents.len() as u64);
+            header.set_mode(0o644);
+            header.set_cksum();
+            builder
+                .append_data(&mut header, path, contents.as_bytes())
+                .unwrap();
+        }
+        builder.into_inner().unwrap()
+    }
+
+    #[test]
+    fn test_read_tar_sorts_entries() {
+        let bytes = tar_of(&[
+            ("validators/keystore-0.json", "{}"),
+            ("passwords.json", r#"{"keystore-0.json": "hunter2"}"#),
+            ("slashing_protection.json", "{}"),
+            ("mac_secrets.json", r#"{"keystore-0.json": "0xabcd"}"#),
+            ("README.md", "not a keystore"),
+            ("validators/.hidden.json", "{}"),
+        ]);
+        let archive = KeystoreArchive::read(&bytes).unwrap();
+        assert_eq!(
+            archive.keystores,
+            vec![("keystore-0.json".to_string(), "{}".to_string())]
+        );
+        assert_eq!(archive.passwords["keystore-0.json"], "hunter2");
+        assert_eq!(archive.interchange.as_deref(), Some("{}"));
+        assert_eq!(archive.mac_secrets["keystore-0.json"], "0xabcd");
+    }
+
+    #[test]
+    fn test_read_rejects_oversized_archives() {
+        let names: Vec<String> = (0..=MAX_KEYSTORE_ARCHIVE_ENTRIES)
+            .map(|i| format!("keystore-{i}.json"))
+            .collect();
+        let files: Vec<(&str, &str)> = names.iter().map(|n| (n.as_str(), "{}")).collect();
+        assert!(KeystoreArchive::read(&tar_of(&files)).is_err());
+
+        let big = vec![0_u8; MAX_KEYSTORE_ARCHIVE_BYTES + 1];
+        assert!(KeystoreArchive::read(&big).is_err());
+    }
+}
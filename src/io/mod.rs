@@ -1,2 +1,5 @@
+pub mod config;
 pub mod key_management;
+pub mod keystore_archive;
 pub mod remote_attestation;
+pub mod storage;
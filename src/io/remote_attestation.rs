@@ -10,6 +10,9 @@ use serde_derive::Serialize;
 
 use std::ffi::CString;
 use std::os::raw::c_char;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
 
 #[cfg(feature = "sgx")]
 #[link(name = "epid")]
@@ -33,6 +36,90 @@ pub fn do_epid_ra(
 ) {
 }
 
+#[cfg(feature = "sgx")]
+#[link(name = "epid")]
+extern "C" {
+    /// The cpp function reading the running enclave's identity from its own report, defined in src/io/enclave_report.cpp
+    fn get_enclave_identity(
+        mrenclave: *mut u8,
+        mrsigner: *mut u8,
+        isv_prod_id: *mut u16,
+        isv_svn: *mut u16,
+    ) -> i32;
+}
+
+/// Whether the signer runs in a hardware enclave or simulates one
+#[cfg(feature = "sgx")]
+pub const ENCLAVE_MODE: &str = "HW";
+#[cfg(not(feature = "sgx"))]
+pub const ENCLAVE_MODE: &str = "SIM";
+
+/// Remote attestation scheme quotes are produced with
+#[cfg(feature = "sgx")]
+pub const ATTESTATION_MODE: &str = "EPID";
+#[cfg(not(feature = "sgx"))]
+pub const ATTESTATION_MODE: &str = "NONE";
+
+/// Identity of the running enclave, for clients to compare against attestation quotes
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct EnclaveInfo {
+    pub mrenclave: String,
+    pub mrsigner: String,
+    pub isv_prod_id: u16,
+    pub isv_svn: u16,
+    /// Remote attestation scheme the enclave's quotes are verified with
+    pub attestation_mode: String,
+}
+
+impl EnclaveInfo {
+    #[cfg(feature = "sgx")]
+    pub fn read() -> Result<Self> {
+        let mut mrenclave = [0_u8; 32];
+        let mut mrsigner = [0_u8; 32];
+        let mut isv_prod_id = 0_u16;
+        let mut isv_svn = 0_u16;
+        let ret = unsafe {
+            get_enclave_identity(
+                mrenclave.as_mut_ptr(),
+                mrsigner.as_mut_ptr(),
+                &mut isv_prod_id,
+                &mut isv_svn,
+            )
+        };
+        if ret != 0 {
+            bail!("Failed to read the enclave identity, error code {}", ret)
+        }
+        Ok(EnclaveInfo {
+            mrenclave: hex::encode(mrenclave),
+            mrsigner: hex::encode(mrsigner),
+            isv_prod_id,
+            isv_svn,
+            attestation_mode: ATTESTATION_MODE.to_string(),
+        })
+    }
+
+    #[cfg(not(feature = "sgx"))]
+    // Outside of an enclave there is no identity to report
+    pub fn read() -> Result<Self> {
+        Ok(EnclaveInfo {
+            mrenclave: hex::encode([0_u8; 32]),
+            mrsigner: hex::encode([0_u8; 32]),
+            isv_prod_id: 0,
+            isv_svn: 0,
+            attestation_mode: ATTESTATION_MODE.to_string(),
+        })
+    }
+}
+
+/// Signature type of an EPID quote. Linkable quotes from the same platform share a pseudonym
+/// under a given SPID, unlinkable ones can't be correlated.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum EpidSignatureType {
+    Unlinkable,
+    Linkable,
+}
+
 #[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq, Eq)]
 pub struct AttestationEvidence {
     pub raw_report: String,
@@ -40,21 +127,79 @@ pub struct AttestationEvidence {
     pub signing_cert: String,
 }
 
+/// `AttestationEvidence` with each field base64 encoded, for verifiers that take the IAS report
+/// as base64 rather than as is. Every field decodes to exactly the bytes of the same field in
+/// `AttestationEvidence`, the signature included even though IAS already sends it as base64.
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq, Eq)]
+pub struct Base64AttestationEvidence {
+    pub raw_report: String,
+    pub signed_report: String,
+    pub signing_cert: String,
+}
+
+impl Base64AttestationEvidence {
+    /// The evidence as IAS returned it
+    pub fn decode(&self) -> Result<AttestationEvidence> {
+        let decode = |field: &str, name: &str| -> Result<String> {
+            let bytes = openssl::base64::decode_block(field)
+                .with_context(|| format!("{name} is not base64"))?;
+            String::from_utf8(bytes).with_context(|| format!("{name} is not utf8"))
+        };
+        Ok(AttestationEvidence {
+            raw_report: decode(&self.raw_report, "raw_report")?,
+            signed_report: decode(&self.signed_report, "signed_report")?,
+            signing_cert: decode(&self.signing_cert, "signing_cert")?,
+        })
+    }
+}
+
+/// Size of the client-supplied challenge an attestation can be bound to
+pub const ATTESTATION_NONCE_BYTES: usize = 32;
+
+/// Bytes of `report_data` left after the payload that a nonce commitment needs at least
+const MIN_NONCE_COMMITMENT_BYTES: usize = 16;
+
+/// Lays out the 64 byte `report_data` a quote commits to: `payload` first, so verifiers find the
+/// attested public key where they always have, then zero padding. With a `nonce`, the padding is
+/// replaced by `sha256(payload || nonce)` truncated to fit, tying the quote to that challenge.
+pub fn report_data(
+    payload: &[u8],
+    nonce: Option<&[u8; ATTESTATION_NONCE_BYTES]>,
+) -> Result<[u8; 64]> {
+    if payload.len() > 64 {
+        bail!("remote attestation report data exceed 64B limit!")
+    }
+    let mut report_data = [0_u8; 64];
+    report_data[..payload.len()].copy_from_slice(payload);
+    if let Some(nonce) = nonce {
+        let free = 64 - payload.len();
+        if free < MIN_NONCE_COMMITMENT_BYTES {
+            bail!("No room left in the report data to commit to a nonce")
+        }
+        let commitment = openssl::sha::sha256(&[payload, &nonce[..]].concat());
+        let n = free.min(commitment.len());
+        report_data[payload.len()..payload.len() + n].copy_from_slice(&commitment[..n]);
+    }
+    Ok(report_data)
+}
+
 impl AttestationEvidence {
     pub fn new(data: &[u8]) -> Result<Self> {
-        info!("Attempting Remote Attestation");
-        if data.len() > 64 {
-            bail!("remote attestation report data exceed 64B limit!")
-        }
+        AttestationEvidence::from_report_data(&report_data(data, None)?)
+    }
 
-        let mut sized_data: Vec<u8> = Vec::with_capacity(64);
-        // add the data
-        sized_data.extend_from_slice(data);
-        // pad any remaining bytes with 0
-        sized_data.extend_from_slice(&vec![0; 64 - data.len()]);
+    /// The same evidence with every field base64 encoded
+    pub fn to_base64(&self) -> Base64AttestationEvidence {
+        Base64AttestationEvidence {
+            raw_report: openssl::base64::encode_block(self.raw_report.as_bytes()),
+            signed_report: openssl::base64::encode_block(self.signed_report.as_bytes()),
+            signing_cert: openssl::base64::encode_block(self.signing_cert.as_bytes()),
+        }
+    }
 
-        let mut report_data = [0_u8; 64];
-        report_data.clone_from_slice(&sized_data[..]);
+    /// Attests to `report_data` as is
+    fn from_report_data(report_data: &[u8; 64]) -> Result<Self> {
+        info!("Attempting Remote Attestation");
 
         // sufficient sized buffers
         //TODO: vec with capacity; Currently using vec with capacity leads to failure in attestation
@@ -77,7 +222,7 @@ impl AttestationEvidence {
 
         unsafe {
             // call cpp EPID remote attestation lib
-            do_epid_ra(&report_data as *const u8, raw_rpt, raw_sig, raw_cert);
+            do_epid_ra(report_data.as_ptr(), raw_rpt, raw_sig, raw_cert);
             _rpt = CString::from_raw(raw_rpt);
             _sig = CString::from_raw(raw_sig);
             _cert = CString::from_raw(raw_cert);
@@ -97,85 +242,53 @@ impl AttestationEvidence {
         })
     }
 
-    /// Verifies attestation evidence IAS signatures. During remote attestation
-    /// IAS returns their signing certificate and root CA as concatenated PEMs.
-    /// This function verifies that the signing certificate is rooted in Intel's root CA.
-    pub fn verify_intel_signing_certificate(&self) -> Result<()> {
-        debug!("Verifying certificate {}", self.signing_cert);
-        let x509s = X509::stack_from_pem(&self.signing_cert.as_bytes())?;
-
-        // Extract intel's signing certificate
-        let signing_x509 = match x509s.get(0) {
-            Some(x) => x.to_owned(),
-            None => bail!("Couldn't extract signing certificate pem!"),
-        };
-
-        // Extract intel's root ca
-        let root_x509 = match x509s.get(1) {
-            Some(x) => x.to_owned(),
-            None => bail!("Couldn't extract intel root CA pem!"),
-        };
-
-        // Verify the common name is valid
-        match signing_x509
-            .subject_name()
-            .entries_by_nid(openssl::nid::Nid::COMMONNAME)
-            .into_iter()
-            .last()
-        {
-            Some(name) => {
-                let n = name
-                    .data()
-                    .as_utf8()
-                    .with_context(|| "Couldn't convert x509 name data to string")?
-                    .to_string();
-                if n != "Intel SGX Attestation Report Signing".to_string() {
-                    bail!("The x509 certificate has an invalid common name: {}", n)
-                }
-            }
-            None => bail!("Couldn't extract COMMONNAME from intel x509 cert"),
+    /// Attests to `data` like `new`, bound to the client's `nonce` if given, erroring if the quote
+    /// is not of `signature_type`. The quote type is set by the platform's quoting configuration,
+    /// so a mismatch is reported instead of handing a verifier evidence it would reject.
+    pub fn new_with_signature_type(
+        data: &[u8],
+        nonce: Option<&[u8; ATTESTATION_NONCE_BYTES]>,
+        signature_type: Option<EpidSignatureType>,
+    ) -> Result<Self> {
+        let evidence = AttestationEvidence::from_report_data(&report_data(data, nonce)?)?;
+        // Outside of an enclave there is no quote to check
+        #[cfg(feature = "sgx")]
+        if let Some(signature_type) = signature_type {
+            evidence.ensure_signature_type(signature_type)?;
         }
+        #[cfg(not(feature = "sgx"))]
+        let _ = signature_type;
+        Ok(evidence)
+    }
 
-        // Verify the common name is valid
-        match root_x509
-            .subject_name()
-            .entries_by_nid(openssl::nid::Nid::COMMONNAME)
-            .into_iter()
-            .last()
-        {
-            Some(name) => {
-                let n = name
-                    .data()
-                    .as_utf8()
-                    .with_context(|| "Couldn't convert x509 name data to string")?
-                    .to_string();
-                if n != "Intel SGX Attestation Report Signing CA".to_string() {
-                    bail!("The x509 certificate has an invalid common name: {}", n)
-                }
-            }
-            None => bail!("Couldn't extract COMMONNAME from intel x509 cert"),
+    /// Errors unless the quote in this evidence has the `expected` signature type
+    pub fn ensure_signature_type(&self, expected: EpidSignatureType) -> Result<()> {
+        let got = self.get_signature_type()?;
+        if got != expected {
+            bail!(
+                "Requested a {:?} EPID quote but the platform produced a {:?} one",
+                expected,
+                got
+            )
         }
+        Ok(())
+    }
 
-        let mut builder = X509StoreBuilder::new()?;
-        let _ = builder.add_cert(root_x509.clone());
-        let trust = builder.build();
-
-        let mut cert_chain: Stack<X509> = Stack::new()?;
-        cert_chain
-            .push(root_x509)
-            .with_context(|| "could not push to cert chain")?;
-
-        // Verify the signing_x509 is valid
-        let mut store = X509StoreContext::new()?;
-        match store.init(
-            trust.as_ref(),
-            signing_x509.as_ref(),
-            cert_chain.as_ref(),
-            |c| c.verify_cert(),
-        ) {
-            Ok(true) => Ok(()),
-            _ => bail!("Failed to verify the intel signing certificate"),
-        }
+    pub fn get_signature_type(&self) -> Result<EpidSignatureType> {
+        let report: AttestationReport = serde_json::from_slice(self.raw_report.as_bytes())
+            .with_context(|| {
+                "Couldn't get AttestationReport from AttestationEvidence.raw_report"
+            })?;
+        let body = report.deserialize_quote_body()?;
+        Ok(body.signature_type())
+    }
+
+    /// Verifies attestation evidence IAS signatures. During remote attestation
+    /// IAS returns their signing certificate and root CA as concatenated PEMs.
+    /// This function verifies that the signing certificate is rooted in Intel's root CA.
+    /// Verified chains are cached for `IAS_CERT_CACHE_TTL`.
+    pub fn verify_intel_signing_certificate(&self) -> Result<()> {
+        CERT_CHAIN_CACHE.verify(&self.signing_cert)
     }
 
     pub fn get_report_data(&self) -> Result<[u8; 64]> {
@@ -189,6 +302,20 @@ impl AttestationEvidence {
         Ok(report_bytes)
     }
 
+    /// Whether the quote commits to `nonce` after its first `payload_len` bytes, proving it was
+    /// produced in response to the verifier's challenge rather than replayed
+    pub fn commits_to_nonce(
+        &self,
+        payload_len: usize,
+        nonce: &[u8; ATTESTATION_NONCE_BYTES],
+    ) -> Result<bool> {
+        let got = self.get_report_data()?;
+        if payload_len > got.len() {
+            bail!("Payload is longer than the report data")
+        }
+        Ok(report_data(&got[..payload_len], Some(nonce))? == got)
+    }
+
     /// Expets the BLS public key to be in the first 48 bytes of report body
     pub fn get_bls_pk(&self) -> Result<PublicKey> {
         let report: AttestationReport = serde_json::from_slice(self.raw_report.as_bytes())
@@ -234,6 +361,142 @@ impl AttestationEvidence {
     }
 }
 
+/// Intel's root CA of IAS report signing certificates, valid until 2049
+const INTEL_SGX_ATTESTATION_CA: &str = include_str!("intel_sgx_attestation_ca.pem");
+
+/// How long a verified IAS signing certificate chain is trusted before being verified again
+pub const IAS_CERT_CACHE_TTL: Duration = Duration::from_secs(60 * 60);
+
+static CERT_CHAIN_CACHE: CertChainCache = CertChainCache::new(IAS_CERT_CACHE_TTL);
+
+/// Remembers recently verified IAS signing certificate chains, keyed by their PEM digest
+pub struct CertChainCache {
+    ttl: Duration,
+    verified: Mutex<Vec<([u8; 32], Instant)>>,
+    verifications: AtomicUsize,
+}
+
+impl CertChainCache {
+    pub const fn new(ttl: Duration) -> Self {
+        CertChainCache {
+            ttl,
+            verified: Mutex::new(Vec::new()),
+            verifications: AtomicUsize::new(0),
+        }
+    }
+
+    /// Verifies the chain in `signing_cert` unless it was verified within the TTL
+    pub fn verify(&self, signing_cert: &str) -> Result<()> {
+        let digest = openssl::sha::sha256(signing_cert.as_bytes());
+        {
+            let mut verified = self.verified.lock().expect("cert cache lock poisoned");
+            verified.retain(|(_, at)| at.elapsed() < self.ttl);
+            if verified.iter().any(|(d, _)| *d == digest) {
+                return Ok(());
+            }
+        }
+
+        self.verifications.fetch_add(1, Ordering::SeqCst);
+        verify_cert_chain(signing_cert)?;
+        self.verified
+            .lock()
+            .expect("cert cache lock poisoned")
+            .push((digest, Instant::now()));
+        Ok(())
+    }
+
+    /// Number of times a chain was actually verified rather than found in the cache
+    pub fn verifications(&self) -> usize {
+        self.verifications.load(Ordering::SeqCst)
+    }
+}
+
+/// Verifies the IAS signing certificate in `signing_cert` is rooted in Intel's root CA. The root
+/// CA IAS sends along must be the bundled one, which is used on its own if IAS sent none.
+fn verify_cert_chain(signing_cert: &str) -> Result<()> {
+    debug!("Verifying certificate {}", signing_cert);
+    let x509s = X509::stack_from_pem(signing_cert.as_bytes())?;
+
+    // Extract intel's signing certificate
+    let signing_x509 = match x509s.get(0) {
+        Some(x) => x.to_owned(),
+        None => bail!("Couldn't extract signing certificate pem!"),
+    };
+
+    // Extract intel's root ca, falling back to the bundled one
+    let bundled_root = X509::from_pem(INTEL_SGX_ATTESTATION_CA.as_bytes())?;
+    let root_x509 = match x509s.get(1) {
+        Some(x) => {
+            if x.to_der()? != bundled_root.to_der()? {
+                bail!("The intel root CA pem is not the bundled root CA")
+            }
+            x.to_owned()
+        }
+        None => bundled_root,
+    };
+
+    // Verify the common name is valid
+    match signing_x509
+        .subject_name()
+        .entries_by_nid(openssl::nid::Nid::COMMONNAME)
+        .into_iter()
+        .last()
+    {
+        Some(name) => {
+            let n = name
+                .data()
+                .as_utf8()
+                .with_context(|| "Couldn't convert x509 name data to string")?
+                .to_string();
+            if n != "Intel SGX Attestation Report Signing".to_string() {
+                bail!("The x509 certificate has an invalid common name: {}", n)
+            }
+        }
+        None => bail!("Couldn't extract COMMONNAME from intel x509 cert"),
+    }
+
+    // Verify the common name is valid
+    match root_x509
+        .subject_name()
+        .entries_by_nid(openssl::nid::Nid::COMMONNAME)
+        .into_iter()
+        .last()
+    {
+        Some(name) => {
+            let n = name
+                .data()
+                .as_utf8()
+                .with_context(|| "Couldn't convert x509 name data to string")?
+                .to_string();
+            if n != "Intel SGX Attestation Report Signing CA".to_string() {
+                bail!("The x509 certificate has an invalid common name: {}", n)
+            }
+        }
+        None => bail!("Couldn't extract COMMONNAME from intel x509 cert"),
+    }
+
+    let mut builder = X509StoreBuilder::new()?;
+    let _ = builder.add_cert(root_x509.clone());
+    let trust = builder.build();
+
+    let mut cert_chain: Stack<X509> = Stack::new()?;
+    cert_chain
+        .push(root_x509)
+        .with_context(|| "could not push to cert chain")?;
+
+    // Verify the signing_x509 is valid
+    let mut store = X509StoreContext::new()?;
+    match store.init(
+        trust.as_ref(),
+        signing_x509.as_ref(),
+        cert_chain.as_ref(),
+        |c| c.verify_cert(),
+    ) {
+        Ok(true) => Ok(()),
+        _ => bail!("Failed to verify the intel signing certificate"),
+    }
+}
+
 #[allow(non_snake_case)]
 #[derive(Serialize, Deserialize, Debug, Default)]
 pub struct AttestationReport {
@@ -266,6 +529,15 @@ pub struct QuoteBody {
     pub REPORTDATA: Vec<u8>,
 }
 
+impl QuoteBody {
+    pub fn signature_type(&self) -> EpidSignatureType {
+        match self.SIGNATURE_TYPE {
+            0 => EpidSignatureType::Unlinkable,
+            _ => EpidSignatureType::Linkable,
+        }
+    }
+}
+
 impl AttestationReport {
     /// Follows the API to decode https://api.trustedservices.intel.com/documents/sgx-attestation-api-spec.pdf
     pub fn deserialize_quote_body(&self) -> Result<QuoteBody> {
@@ -361,4 +633,131 @@ mod tests {
         assert_eq!(exp_eth_pk, got_pk);
         Ok(())
     }
+
+    #[test]
+    fn test_base64_evidence_decodes_to_the_same_bytes() -> Result<()> {
+        for evidence in [fetch_dummy_bls_evidence(), fetch_dummy_eth_evidence()] {
+            let encoded = evidence.to_base64();
+            assert_eq!(
+                openssl::base64::decode_block(&encoded.raw_report)?,
+                evidence.raw_report.as_bytes()
+            );
+            assert_eq!(
+                openssl::base64::decode_block(&encoded.signed_report)?,
+                evidence.signed_report.as_bytes()
+            );
+            assert_eq!(
+                openssl::base64::decode_block(&encoded.signing_cert)?,
+                evidence.signing_cert.as_bytes()
+            );
+            assert_eq!(encoded.decode()?, evidence);
+        }
+        assert_eq!(
+            AttestationEvidence::default().to_base64().decode()?,
+            AttestationEvidence::default()
+        );
+        assert!(Base64AttestationEvidence {
+            raw_report: "not base64!".into(),
+            ..Default::default()
+        }
+        .decode()
+        .is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_quote_signature_type() -> Result<()> {
+        // The recorded quotes were generated as linkable
+        let evidence = fetch_dummy_bls_evidence();
+        assert_eq!(evidence.get_signature_type()?, EpidSignatureType::Linkable);
+        assert!(evidence
+            .ensure_signature_type(EpidSignatureType::Linkable)
+            .is_ok());
+        assert!(evidence
+            .ensure_signature_type(EpidSignatureType::Unlinkable)
+            .is_err());
+
+        // Flip the sign type bit of the quote to read it back as unlinkable
+        let mut report: AttestationReport = serde_json::from_slice(evidence.raw_report.as_bytes())?;
+        let mut body = openssl::base64::decode_block(&report.isvEnclaveQuoteBody)?;
+        body[2] = 0;
+        report.isvEnclaveQuoteBody = openssl::base64::encode_block(&body);
+        assert_eq!(
+            report.deserialize_quote_body()?.signature_type(),
+            EpidSignatureType::Unlinkable
+        );
+
+        assert_eq!(
+            serde_json::from_str::<EpidSignatureType>(r#""unlinkable""#)?,
+            EpidSignatureType::Unlinkable
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_cert_chain_is_verified_once_within_ttl() {
+        let cache = CertChainCache::new(Duration::from_secs(60));
+        let bls_evidence = fetch_dummy_bls_evidence();
+        let eth_evidence = fetch_dummy_eth_evidence();
+
+        // Both attestations were signed with the same IAS certificate chain
+        cache.verify(&bls_evidence.signing_cert).unwrap();
+        cache.verify(&eth_evidence.signing_cert).unwrap();
+        assert_eq!(cache.verifications(), 1);
+
+        // Entries expire after the TTL
+        let cache = CertChainCache::new(Duration::ZERO);
+        cache.verify(&bls_evidence.signing_cert).unwrap();
+        cache.verify(&bls_evidence.signing_cert).unwrap();
+        assert_eq!(cache.verifications(), 2);
+
+        // A chain that fails verification is not cached
+        let cache = CertChainCache::new(Duration::from_secs(60));
+        assert!(cache.verify("not a pem").is_err());
+        assert!(cache.verify("not a pem").is_err());
+        assert_eq!(cache.verifications(), 2);
+    }
+
+    #[test]
+    fn test_cert_chain_falls_back_to_bundled_root() {
+        let evidence = fetch_dummy_bls_evidence();
+        let end = "-----END CERTIFICATE-----\n";
+        let signing_cert_only =
+            &evidence.signing_cert[..evidence.signing_cert.find(end).unwrap() + end.len()];
+        verify_cert_chain(signing_cert_only).unwrap();
+
+        // A chain rooted in any other CA is rejected
+        let signing_cert_twice = format!("{signing_cert_only}{signing_cert_only}");
+        assert!(verify_cert_chain(&signing_cert_twice).is_err());
+    }
+
+    #[test]
+    fn test_nonce_is_committed_in_report_data() {
+        let bls_pk = crate::crypto::bls_keys::new_bls_key(0)
+            .public_keys()
+            .public_key()
+            .to_bytes();
+        let without_nonce = report_data(&bls_pk, None).unwrap();
+        let with_nonce_a = report_data(&bls_pk, Some(&[0xaa; ATTESTATION_NONCE_BYTES])).unwrap();
+        let with_nonce_b = report_data(&bls_pk, Some(&[0xbb; ATTESTATION_NONCE_BYTES])).unwrap();
+
+        // Different challenges yield different report data, with the public key left in place
+        assert_ne!(with_nonce_a, with_nonce_b);
+        assert_ne!(with_nonce_a, without_nonce);
+        for data in [without_nonce, with_nonce_a, with_nonce_b] {
+            assert_eq!(data[..48], bls_pk[..]);
+        }
+        assert_eq!(without_nonce[48..], [0_u8; 16]);
+
+        // The ETH pk leaves more room for the commitment
+        let (_, eth_pk) = eth_keys::new_eth_key().unwrap();
+        let eth_pk = eth_pk.serialize_compressed();
+        let data = report_data(&eth_pk, Some(&[0xaa; ATTESTATION_NONCE_BYTES])).unwrap();
+        assert_eq!(data[..33], eth_pk[..]);
+        assert_ne!(data[33..], [0_u8; 31]);
+
+        // No room for a commitment after a full payload
+        assert!(report_data(&[1_u8; 64], Some(&[0xaa; ATTESTATION_NONCE_BYTES])).is_err());
+        assert!(report_data(&[1_u8; 65], None).is_err());
+    }
 }
@@ -0,0 +1,61 @@
+#include <errno.h>
+#include <fcntl.h>
+#include <stdio.h>
+#include <string.h>
+#include <sys/ioctl.h>
+#include <unistd.h>
+
+#include "sgx_key.h"
+
+// Occlum exposes EGETKEY through the /dev/sgx device
+typedef struct {
+  const sgx_key_request_t* key_request;  // Input
+  sgx_key_128bit_t* key;                 // Output
+} sgxioc_get_key_arg_t;
+
+#define SGXIOC_GET_KEY _IOWR('s', 11, sgxioc_get_key_arg_t)
+
+// Same masks as the SGX SDK's sgx_seal_data
+#define SEAL_FLAGS_MASK 0xFF0000000000000BULL
+#define SEAL_MISC_MASK 0xF0000000
+
+// -2 for errors worth retrying, the device or the kernel was momentarily busy, -1 otherwise
+static int failure_code(int err) {
+  return (err == EAGAIN || err == EBUSY || err == EINTR || err == ENOMEM) ? -2 : -1;
+}
+
+extern "C"
+int get_enclave_sealing_key(uint16_t key_policy, uint8_t key[16]) {
+  int sgx_fd = open("/dev/sgx", O_RDONLY);
+  if (sgx_fd < 0) {
+    int err = errno;
+    printf("Fail to open /dev/sgx!\n");
+    return failure_code(err);
+  }
+
+  // SGX_KEYPOLICY_MRSIGNER survives enclave upgrades, SGX_KEYPOLICY_MRENCLAVE binds to this build
+  if (key_policy != SGX_KEYPOLICY_MRENCLAVE && key_policy != SGX_KEYPOLICY_MRSIGNER) {
+    printf("Unknown sealing key policy %u!\n", key_policy);
+    return -1;
+  }
+  sgx_key_request_t key_request = {0};
+  key_request.key_name = SGX_KEYSELECT_SEAL;
+  key_request.key_policy = key_policy;
+  key_request.attribute_mask.flags = SEAL_FLAGS_MASK;
+  key_request.attribute_mask.xfrm = 0x0;
+  key_request.misc_mask = SEAL_MISC_MASK;
+
+  sgx_key_128bit_t sealing_key = {0};
+  sgxioc_get_key_arg_t get_key_arg = {&key_request, &sealing_key};
+  int ret = ioctl(sgx_fd, SGXIOC_GET_KEY, &get_key_arg);
+  int err = errno;
+  close(sgx_fd);
+  if (ret < 0) {
+    printf("Fail to get the enclave sealing key!\n");
+    return failure_code(err);
+  }
+
+  memcpy(key, sealing_key, sizeof(sgx_key_128bit_t));
+  memset(sealing_key, 0, sizeof(sgx_key_128bit_t));
+  return 0;
+}
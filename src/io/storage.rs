@@ -0,0 +1,201 @@
+use anyhow::{bail, Context, Result};
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+
+/// Where the enclave's persistent state is kept. Paths are laid out under the data dir either
+/// way, only what backs them differs.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub enum Storage {
+    /// Files under the data dir
+    #[default]
+    Disk,
+    /// Contents kept in memory for as long as the process runs, nothing is written to disk.
+    /// Meant for tests and throwaway signers, everything is lost on restart.
+    Memory(MemoryFiles),
+}
+
+/// In-memory files by path, shared by every clone
+#[derive(Debug, Clone, Default)]
+pub struct MemoryFiles(Arc<Mutex<BTreeMap<PathBuf, Vec<u8>>>>);
+
+/// Two handles are equal if they share the same files
+impl PartialEq for MemoryFiles {
+    fn eq(&self, other: &Self) -> bool {
+        Arc::ptr_eq(&self.0, &other.0)
+    }
+}
+
+impl Eq for MemoryFiles {}
+
+impl MemoryFiles {
+    fn files(&self) -> std::sync::MutexGuard<'_, BTreeMap<PathBuf, Vec<u8>>> {
+        self.0.lock().expect("memory storage poisoned")
+    }
+}
+
+impl std::str::FromStr for Storage {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s.to_ascii_lowercase().as_str() {
+            "disk" => Ok(Storage::Disk),
+            "memory" => Ok(Storage::memory()),
+            _ => bail!("Unknown storage {:?}", s),
+        }
+    }
+}
+
+impl Storage {
+    /// Fresh, empty in-memory storage
+    pub fn memory() -> Self {
+        Storage::Memory(MemoryFiles::default())
+    }
+
+    pub fn is_memory(&self) -> bool {
+        matches!(self, Storage::Memory(_))
+    }
+
+    /// Replaces the contents of `file_path`. On disk the contents are written to a hidden temp
+    /// file next to `file_path` and renamed into place, so concurrent readers and listings never
+    /// see a partially written file.
+    pub fn write(&self, file_path: &Path, contents: impl AsRef<[u8]>) -> Result<()> {
+        let Storage::Memory(memory) = self else {
+            return write_atomic_to_disk(file_path, contents);
+        };
+        if file_path.file_name().is_none() {
+            bail!("No file name to write to")
+        }
+        memory
+            .files()
+            .insert(file_path.to_path_buf(), contents.as_ref().to_vec());
+        Ok(())
+    }
+
+    pub fn read(&self, file_path: &Path) -> Result<Vec<u8>> {
+        match self {
+            Storage::Disk => Ok(fs::read(file_path)?),
+            Storage::Memory(memory) => memory
+                .files()
+                .get(file_path)
+                .cloned()
+                .with_context(|| format!("No such file {:?}", file_path.as_os_str())),
+        }
+    }
+
+    pub fn exists(&self, file_path: &Path) -> bool {
+        match self {
+            Storage::Disk => file_path.exists(),
+            Storage::Memory(memory) => memory.files().contains_key(file_path),
+        }
+    }
+
+    pub fn remove(&self, file_path: &Path) -> Result<()> {
+        match self {
+            Storage::Disk => Ok(fs::remove_file(file_path)?),
+            Storage::Memory(memory) => match memory.files().remove(file_path) {
+                Some(_) => Ok(()),
+                None => bail!("No such file {:?}", file_path.as_os_str()),
+            },
+        }
+    }
+
+    /// Returns the names of the files directly in `dir` in lexicographic order, skipping hidden
+    /// files which on disk are writes still in progress. Empty if `dir` doesn't exist.
+    pub fn list(&self, dir: &Path) -> Result<Vec<String>> {
+        let mut fnames = match self {
+            Storage::Disk => list_disk_dir(dir)?,
+            Storage::Memory(memory) => memory
+                .files()
+                .keys()
+                .filter(|path| path.parent() == Some(dir))
+                .filter_map(|path| path.file_name())
+                .map(|fname| fname.to_string_lossy().into_owned())
+                .collect(),
+        };
+        fnames.retain(|fname| !fname.starts_with('.'));
+        fnames.sort();
+        Ok(fnames)
+    }
+}
+
+fn write_atomic_to_disk(file_path: &Path, contents: impl AsRef<[u8]>) -> Result<()> {
+    let Some(dir) = file_path.parent() else {
+        bail!("No parent dir for {:?}", file_path.as_os_str())
+    };
+    crate::io::key_management::ensure_dir(dir)?;
+    let fname = file_path
+        .file_name()
+        .with_context(|| "No file name to write to")?
+        .to_string_lossy();
+    let tmp_path = dir.join(format!(
+        ".{}.{}.tmp",
+        fname,
+        hex::encode(rand::random::<[u8; 8]>())
+    ));
+    fs::write(&tmp_path, contents)?;
+    fs::rename(&tmp_path, file_path).map_err(|e| {
+        fs::remove_file(&tmp_path).ok();
+        e
+    })?;
+    Ok(())
+}
+
+fn list_disk_dir(dir: &Path) -> Result<Vec<String>> {
+    // Nothing was ever saved on a fresh data dir
+    if !dir.exists() {
+        return Ok(vec![]);
+    }
+    let mut fnames = Vec::new();
+    for entry in fs::read_dir(dir).with_context(|| "No keys saved in dir")? {
+        let entry = entry.with_context(|| "failed to find path")?;
+        match entry.file_name().into_string() {
+            Ok(fname) => fnames.push(fname),
+            Err(e) => bail!("Error, bad file name in list_keys(): {:?}", e),
+        }
+    }
+    Ok(fnames)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_memory_storage() {
+        let storage = Storage::memory();
+        let dir = PathBuf::from("/nonexistent/keys");
+        assert_eq!(storage.list(&dir).unwrap(), Vec::<String>::new());
+
+        storage.write(&dir.join("bb"), "2").unwrap();
+        storage.write(&dir.join("aa"), "1").unwrap();
+        storage.write(&dir.join("nested").join("cc"), "3").unwrap();
+        assert!(storage.exists(&dir.join("aa")));
+        assert_eq!(storage.read(&dir.join("aa")).unwrap(), b"1");
+        // Only the files directly in the dir
+        assert_eq!(storage.list(&dir).unwrap(), vec!["aa", "bb"]);
+
+        // Clones share the files
+        let clone = storage.clone();
+        assert_eq!(clone, storage);
+        clone.write(&dir.join("aa"), "4").unwrap();
+        assert_eq!(storage.read(&dir.join("aa")).unwrap(), b"4");
+        assert_ne!(Storage::memory(), storage);
+
+        storage.remove(&dir.join("aa")).unwrap();
+        assert!(!storage.exists(&dir.join("aa")));
+        assert!(storage.read(&dir.join("aa")).is_err());
+        assert!(storage.remove(&dir.join("aa")).is_err());
+
+        // Nothing touched the disk
+        assert!(!dir.exists());
+    }
+
+    #[test]
+    fn test_parse_storage() {
+        assert_eq!("disk".parse::<Storage>().unwrap(), Storage::Disk);
+        assert!("MEMORY".parse::<Storage>().unwrap().is_memory());
+        assert!("tmpfs".parse::<Storage>().is_err());
+    }
+}
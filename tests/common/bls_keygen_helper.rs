@@ -15,6 +15,7 @@ pub async fn mock_bls_keygen_route() -> Result<axum_test::TestResponse> {
                 puffersecuresigner::enclave::secure_signer::handlers::bls_keygen::handler,
             ),
         )
+        .with_state(puffersecuresigner::enclave::shared::handlers::AppState::default())
         .into_make_service();
 
     let server = axum_test::TestServer::new(test_app)?;
@@ -77,6 +78,27 @@ async fn test_register_new_bls_key() {
     let _ = register_new_bls_key(port).await;
 }
 
+#[tokio::test]
+async fn test_keygen_evidence_in_both_encodings() {
+    let resp = register_new_bls_key(None).await;
+    assert_eq!(resp.evidence_base64, resp.evidence.to_base64());
+    assert_eq!(resp.evidence_base64.decode().unwrap(), resp.evidence);
+}
+
+#[tokio::test]
+async fn test_keygen_returns_proof_of_possession() {
+    let port = read_secure_signer_port();
+    let resp = register_new_bls_key(port).await;
+    let pk_hex: String = strip_0x_prefix!(&resp.pk_hex);
+    let pop_hex = resp.proof_of_possession.unwrap();
+    let pop_hex: String = strip_0x_prefix!(pop_hex);
+    let pk = hex::decode(pk_hex).unwrap();
+    let pop = hex::decode(pop_hex).unwrap();
+    assert!(
+        puffersecuresigner::crypto::bls_keys::bls_verify_proof_of_possession(&pk, &pop).unwrap()
+    );
+}
+
 #[tokio::test]
 async fn test_bls_key_in_remote_attestation_evidence() {
     if env::var("SECURE_SIGNER_PORT").is_ok() {
@@ -75,6 +75,7 @@ async fn test_register_new_eth_key() {
     let port = read_secure_signer_port();
     let resp = register_new_eth_key(port).await;
     let _pk = eth_keys::eth_pk_from_hex(&resp.pk_hex).unwrap();
+    assert!(resp.proof_of_possession.is_none());
     dbg!(resp.pk_hex);
 }
 
@@ -1,7 +1,7 @@
 use crate::common::{
     bls_keygen_helper::register_new_bls_key, eth_keygen_helper::register_new_eth_key,
 };
-use puffersecuresigner::enclave::types::ListKeysResponse;
+use puffersecuresigner::enclave::types::{ListKeysResponse, ListKeystoresResponse};
 
 use super::read_secure_signer_port;
 
@@ -55,6 +55,17 @@ pub async fn request_list_bls_keys_route(port: u16) -> Result<Response, reqwest:
 
     response
 }
+/// Reduces the keymanager listing of BLS keys to their pubkeys
+fn into_list_keys(keystores: ListKeystoresResponse) -> ListKeysResponse {
+    ListKeysResponse::new(
+        keystores
+            .data
+            .into_iter()
+            .map(|k| k.validating_pubkey)
+            .collect(),
+    )
+}
+
 pub enum ListRequestKind {
     BLS,
     ETH,
@@ -73,11 +84,11 @@ pub async fn make_list_request(
                     Err(_) => panic!("Failed request_list_bls_keys_route"),
                 };
                 let status = resp.status();
-                let keys: ListKeysResponse = resp
+                let keys: ListKeystoresResponse = resp
                     .json()
                     .await
-                    .with_context(|| format!("Failed to parse to ListKeysResponse"))?;
-                Ok((keys, status))
+                    .with_context(|| format!("Failed to parse to ListKeystoresResponse"))?;
+                Ok((into_list_keys(keys), status))
             }
             ListRequestKind::ETH => {
                 let resp = match request_list_eth_keys_route(p).await {
@@ -97,9 +108,9 @@ pub async fn make_list_request(
         None => match t {
             ListRequestKind::BLS => {
                 let resp = mock_list_bls_keys_route().await?;
-                let keys: ListKeysResponse = serde_json::from_slice(resp.as_bytes())
-                    .with_context(|| "Failed to parse to ListKeysResponse")?;
-                Ok((keys, resp.status_code()))
+                let keys: ListKeystoresResponse = serde_json::from_slice(resp.as_bytes())
+                    .with_context(|| "Failed to parse to ListKeystoresResponse")?;
+                Ok((into_list_keys(keys), resp.status_code()))
             }
             ListRequestKind::ETH => {
                 let resp = mock_list_eth_keys_route().await?;
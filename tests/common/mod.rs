@@ -43,5 +43,5 @@ pub fn setup_dummy_keypair() -> String {
     let db = SlashingProtectionData::from_pk_hex(&pk_hex).unwrap();
     db.write().unwrap();
 
-    pk_hex
+    format!("0x{pk_hex}")
 }
@@ -10,6 +10,18 @@ use serde_json;
 pub async fn mock_secure_sign_route(
     bls_pk: &String,
     signing_data: BLSSignMsg,
+) -> Result<axum_test::TestResponse> {
+    let state = puffersecuresigner::enclave::shared::handlers::AppState {
+        genesis_fork_version: GENESIS_FORK_VERSION,
+        ..Default::default()
+    };
+    mock_secure_sign_route_with_state(bls_pk, signing_data, state).await
+}
+
+pub async fn mock_secure_sign_route_with_state(
+    bls_pk: &String,
+    signing_data: BLSSignMsg,
+    state: puffersecuresigner::enclave::shared::handlers::AppState,
 ) -> Result<axum_test::TestResponse> {
     let uri = format!("/api/v1/eth2/sign/{}", bls_pk);
     let test_app = axum::Router::new()
@@ -19,9 +31,7 @@ pub async fn mock_secure_sign_route(
                 puffersecuresigner::enclave::shared::handlers::secure_sign_bls::handler,
             ),
         )
-        .with_state(puffersecuresigner::enclave::shared::handlers::AppState {
-            genesis_fork_version: GENESIS_FORK_VERSION,
-        })
+        .with_state(state)
         .into_make_service();
 
     let server = axum_test::TestServer::new(test_app)?;
@@ -102,7 +112,6 @@ async fn test_sign_route() {
                 }},
                 "genesis_validators_root":"0x270d43e74ce340de4bca2b1936beca0f4f5408d9e78aec4850920baf659d5b69"
             }},
-            "signingRoot": "0x270d43e74ce340de4bca2b1936beca0f4f5408d9e78aec4850920baf659d5b69",
             "attestation": {{
                 "slot": "255",
                 "index": "65535",
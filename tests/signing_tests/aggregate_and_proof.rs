@@ -26,7 +26,6 @@ pub fn mock_aggregate_and_proof_request() -> String {
                   }},
                   "genesis_validators_root":"0x270d43e74ce340de4bca2b1936beca0f4f5408d9e78aec4850920baf659d5b69"
                }},
-               "signingRoot": "0x270d43e74ce340de4bca2b1936beca0f4f5408d9e78aec4850920baf659d5b69",
                "aggregate_and_proof":{{
                     "aggregator_index": "5",
                     "aggregate": {{
@@ -110,3 +109,30 @@ async fn test_aggregate_and_proof_eth2_specs() {
         assert_eq!(status, 200);
     }
 }
+
+/// The mock aggregate wrapped in an "AGGREGATE_AND_PROOF_V2" request for `version`
+fn aggregate_and_proof_v2_request(version: &str) -> BLSSignMsg {
+    let mut req: serde_json::Value =
+        serde_json::from_str(&mock_aggregate_and_proof_request()).unwrap();
+    req["type"] = "AGGREGATE_AND_PROOF_V2".into();
+    req["aggregate_and_proof"] = serde_json::json!({
+        "version": version,
+        "data": req["aggregate_and_proof"].take(),
+    });
+    serde_json::from_value(req).unwrap()
+}
+
+#[tokio::test]
+pub async fn test_aggregate_and_proof_v2_pre_electra_matches_v1_test_vec() {
+    let port = None;
+    let exp_sig = "81e56af6c3b9f0ce1c7fd3545a3d689fc2edd2c9dd5451ea5f345cc57d74de76ed940e373fdccc76150e643edc57bdb0145ad3770d9207164484f86f746fb26f889833106e3e17cd49572eb7938a9e4502bba99c3234f32695f73ef3ed18bb51";
+    let req = aggregate_and_proof_v2_request("DENEB");
+    let bls_pk_hex = common::setup_dummy_keypair();
+    let (resp, status) = make_signing_route_request(req, &bls_pk_hex, port)
+        .await
+        .unwrap();
+    assert_eq!(status, 200);
+    let sig = resp.unwrap().signature;
+    let got_sig: String = strip_0x_prefix!(sig);
+    assert_eq!(exp_sig, got_sig);
+}
@@ -0,0 +1,74 @@
+use puffersecuresigner::constants::BLS_SIG_DST;
+use puffersecuresigner::crypto::bls_keys::{bls_agg_sign, new_bls_key};
+use puffersecuresigner::enclave::types::{AggregateSignaturesRequest, SignatureResponse};
+use puffersecuresigner::strip_0x_prefix;
+
+fn aggregate_server() -> axum_test::TestServer {
+    let test_app = axum::Router::new()
+        .route(
+            "/eth/v1/aggregate",
+            axum::routing::post(
+                puffersecuresigner::enclave::shared::handlers::aggregate_signatures::handler,
+            ),
+        )
+        .into_make_service();
+    axum_test::TestServer::new(test_app).unwrap()
+}
+
+#[tokio::test]
+async fn test_aggregate_two_signatures() {
+    let msg = [9_u8; 32];
+    let sk_sets = [new_bls_key(0), new_bls_key(0)];
+    let req = AggregateSignaturesRequest {
+        signatures: sk_sets
+            .iter()
+            .map(|sk_set| format!("0x{}", hex::encode(bls_agg_sign(sk_set, &msg).to_bytes())))
+            .collect(),
+    };
+
+    let resp = aggregate_server()
+        .post("/eth/v1/aggregate")
+        .json(&req)
+        .await;
+    assert_eq!(resp.status_code(), 200);
+    let resp: SignatureResponse = resp.json();
+    let agg_hex: String = strip_0x_prefix!(resp.signature);
+    let agg = blst::min_pk::Signature::from_bytes(&hex::decode(agg_hex).unwrap()).unwrap();
+
+    // Verifies against the combined pubkeys
+    let pks: Vec<blst::min_pk::PublicKey> = sk_sets
+        .iter()
+        .map(|sk_set| {
+            blst::min_pk::PublicKey::from_bytes(&sk_set.public_keys().public_key().to_bytes())
+                .unwrap()
+        })
+        .collect();
+    let agg_pk = blst::min_pk::AggregatePublicKey::aggregate(&pks.iter().collect::<Vec<_>>(), true)
+        .unwrap()
+        .to_public_key();
+    assert_eq!(
+        agg.verify(true, &msg, BLS_SIG_DST, &[], &agg_pk, true),
+        blst::BLST_ERROR::BLST_SUCCESS
+    );
+}
+
+#[tokio::test]
+async fn test_aggregate_rejects_bad_signatures() {
+    let server = aggregate_server();
+    let sig = bls_agg_sign(&new_bls_key(0), &[9_u8; 32]);
+
+    for signatures in [
+        vec![],
+        vec![
+            format!("0x{}", hex::encode(sig.to_bytes())),
+            "0xdeadbeef".into(),
+        ],
+        vec!["not hex".into()],
+    ] {
+        let resp = server
+            .post("/eth/v1/aggregate")
+            .json(&AggregateSignaturesRequest { signatures })
+            .await;
+        assert_eq!(resp.status_code(), 400);
+    }
+}
@@ -26,7 +26,7 @@ pub fn mock_aggregation_slot_request() -> String {
                 }},
                 "genesis_validators_root":"0x2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a"
             }},
-            "signingRoot": "0x2ebfc2d70944cc2fbff6d67c6d9cbb043d7fbe0a660d248b6e666ce110af418a",
+            "signingRoot": "0xfae6059beffb4b31d35df953a9e129f7da1108525f466f93f334c7d552a75cfb",
             "aggregation_slot": {{
                 "slot": "123123"
             }}
@@ -0,0 +1,83 @@
+use crate::common::bls_keygen_helper::register_new_bls_key;
+use crate::common::signing_helper::mock_secure_sign_route_with_state;
+use puffersecuresigner::enclave::shared::handlers::AppState;
+use puffersecuresigner::eth2::eth_signing::BLSSignMsg;
+use puffersecuresigner::eth2::eth_types::{AllowedDomains, RandaoRevealRequest};
+
+/// A custom network's fork version
+const CUSTOM_FORK_VERSION: &str = "0x10000038";
+const CUSTOM_GENESIS_VALIDATORS_ROOT: &str =
+    "0x2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a";
+
+fn randao_reveal_request(fork_version: &str, genesis_validators_root: &str) -> BLSSignMsg {
+    let req = format!(
+        r#"
+        {{
+           "type":"randao_reveal",
+           "fork_info":{{
+              "fork":{{
+                 "previous_version":"{fork_version}",
+                 "current_version":"{fork_version}",
+                 "epoch":"0"
+              }},
+              "genesis_validators_root":"{genesis_validators_root}"
+           }},
+           "randao_reveal":{{
+                "epoch": "10"
+           }}
+        }}"#
+    );
+    BLSSignMsg::RANDAO_REVEAL(serde_json::from_str::<RandaoRevealRequest>(&req).unwrap())
+}
+
+fn state_allowing_custom_network() -> AppState {
+    AppState {
+        allowed_domains: AllowedDomains::parse(CUSTOM_FORK_VERSION, CUSTOM_GENESIS_VALIDATORS_ROOT)
+            .unwrap(),
+        ..Default::default()
+    }
+}
+
+#[tokio::test]
+async fn test_allowed_domain_signs() {
+    let bls_pk_hex = register_new_bls_key(None).await.pk_hex;
+    let req = randao_reveal_request(CUSTOM_FORK_VERSION, CUSTOM_GENESIS_VALIDATORS_ROOT);
+    let resp = mock_secure_sign_route_with_state(&bls_pk_hex, req, state_allowing_custom_network())
+        .await
+        .unwrap();
+    assert_eq!(resp.status_code(), 200);
+}
+
+#[tokio::test]
+async fn test_disallowed_fork_version_is_refused() {
+    let bls_pk_hex = register_new_bls_key(None).await.pk_hex;
+    let req = randao_reveal_request("0x10000039", CUSTOM_GENESIS_VALIDATORS_ROOT);
+    let resp = mock_secure_sign_route_with_state(&bls_pk_hex, req, state_allowing_custom_network())
+        .await
+        .unwrap();
+    assert_eq!(resp.status_code(), 400);
+    assert!(resp.text().contains("Bad signing domain"));
+}
+
+#[tokio::test]
+async fn test_disallowed_genesis_validators_root_is_refused() {
+    let bls_pk_hex = register_new_bls_key(None).await.pk_hex;
+    let req = randao_reveal_request(
+        CUSTOM_FORK_VERSION,
+        "0x2b2b2b2b2b2b2b2b2b2b2b2b2b2b2b2b2b2b2b2b2b2b2b2b2b2b2b2b2b2b2b2b",
+    );
+    let resp = mock_secure_sign_route_with_state(&bls_pk_hex, req, state_allowing_custom_network())
+        .await
+        .unwrap();
+    assert_eq!(resp.status_code(), 400);
+}
+
+#[tokio::test]
+async fn test_any_domain_signs_by_default() {
+    let bls_pk_hex = register_new_bls_key(None).await.pk_hex;
+    let req = randao_reveal_request("0x10000039", CUSTOM_GENESIS_VALIDATORS_ROOT);
+    let resp = mock_secure_sign_route_with_state(&bls_pk_hex, req, AppState::default())
+        .await
+        .unwrap();
+    assert_eq!(resp.status_code(), 200);
+}
@@ -9,7 +9,7 @@ use std::path::PathBuf;
 const START_SRC_EPOCH: u64 = 1234;
 const START_TGT_EPOCH: u64 = 1235;
 
-fn attestation_req(src_epoch: u64, tgt_epoch: u64) -> BLSSignMsg {
+pub fn attestation_req(src_epoch: u64, tgt_epoch: u64) -> BLSSignMsg {
     // Create AttestationRequest
     let req = mock_attestation_request(src_epoch, tgt_epoch);
     let signing_data: AttestationRequest = serde_json::from_str(&req).unwrap();
@@ -29,7 +29,6 @@ fn mock_attestation_request(src_epoch: u64, tgt_epoch: u64) -> String {
                 }},
                 "genesis_validators_root":"0x270d43e74ce340de4bca2b1936beca0f4f5408d9e78aec4850920baf659d5b69"
             }},
-            "signingRoot": "0x270d43e74ce340de4bca2b1936beca0f4f5408d9e78aec4850920baf659d5b69",
             "attestation": {{
                 "slot": "255",
                 "index": "65535",
@@ -48,6 +47,43 @@ fn mock_attestation_request(src_epoch: u64, tgt_epoch: u64) -> String {
     req
 }
 
+/// First epochs after mainnet's Electra fork at epoch 364032
+const ELECTRA_SRC_EPOCH: u64 = 364100;
+const ELECTRA_TGT_EPOCH: u64 = 364101;
+
+/// An attestation signed under Electra, whose committee index is always 0
+fn electra_attestation_req(src_epoch: u64, tgt_epoch: u64) -> BLSSignMsg {
+    let req = format!(
+        r#"
+        {{
+            "type": "ATTESTATION",
+            "fork_info":{{
+                "fork":{{
+                   "previous_version":"0x04000000",
+                   "current_version":"0x05000000",
+                   "epoch":"364032"
+                }},
+                "genesis_validators_root":"0x4b363db94e286120d76eb905340fdd4e54bfe9f06bf33ff6cf5ad27f511bfe95"
+            }},
+            "attestation": {{
+                "slot": "{slot}",
+                "index": "0",
+                "beacon_block_root": "0x270d43e74ce340de4bca2b1936beca0f4f5408d9e78aec4850920baf659d5b69",
+                "source": {{
+                    "epoch": "{src_epoch}",
+                    "root": "0x270d43e74ce340de4bca2b1936beca0f4f5408d9e78aec4850920baf659d5b69"
+                }},
+                "target": {{
+                    "epoch": "{tgt_epoch}",
+                    "root": "0x270d43e74ce340de4bca2b1936beca0f4f5408d9e78aec4850920baf659d5b69"
+                }}
+            }}
+        }}"#,
+        slot = tgt_epoch * 32,
+    );
+    serde_json::from_str(&req).unwrap()
+}
+
 #[tokio::test]
 pub async fn test_aggregate_route_fails_from_invalid_pk_hex() {
     let port = common::read_secure_signer_port();
@@ -158,6 +194,96 @@ pub async fn test_slash_protection_prevents_decreasing_target() {
     assert_eq!(status, 412);
 }
 
+#[tokio::test]
+pub async fn test_inverted_source_and_target_is_rejected() {
+    let port = common::read_secure_signer_port();
+    let bls_pk_hex = register_new_bls_key(port).await.pk_hex;
+
+    // Refused as malformed even though the key has never signed anything
+    let req = attestation_req(START_TGT_EPOCH, START_SRC_EPOCH);
+    let (_resp, status) = make_signing_route_request(req, &bls_pk_hex, port)
+        .await
+        .unwrap();
+    assert_eq!(status, 400);
+
+    // Nothing was recorded, so the well-formed pair still signs
+    let req = attestation_req(START_SRC_EPOCH, START_TGT_EPOCH);
+    let (_resp, status) = make_signing_route_request(req, &bls_pk_hex, port)
+        .await
+        .unwrap();
+    assert_eq!(status, 200);
+}
+
+#[tokio::test]
+pub async fn test_tampered_attestation_signing_root_is_rejected() {
+    let port = common::read_secure_signer_port();
+    let bls_pk_hex = register_new_bls_key(port).await.pk_hex;
+
+    // A benign looking attestation carrying the root of a different one
+    let sneaked = attestation_req(START_SRC_EPOCH + 10, START_TGT_EPOCH + 10)
+        .to_signing_root(Some(GENESIS_FORK_VERSION));
+    let BLSSignMsg::ATTESTATION(mut tampered) = attestation_req(START_SRC_EPOCH, START_TGT_EPOCH)
+    else {
+        unreachable!()
+    };
+    tampered.signingRoot = Some(sneaked);
+    let (_resp, status) =
+        make_signing_route_request(BLSSignMsg::ATTESTATION(tampered), &bls_pk_hex, port)
+            .await
+            .unwrap();
+    assert_eq!(status, 400);
+
+    // Nothing was signed or recorded, so the displayed attestation still signs
+    let req = attestation_req(START_SRC_EPOCH, START_TGT_EPOCH);
+    let (_resp, status) = make_signing_route_request(req, &bls_pk_hex, port)
+        .await
+        .unwrap();
+    assert_eq!(status, 200);
+}
+
+#[tokio::test]
+pub async fn test_electra_slash_protection_allows_non_slashable_attestation() {
+    let port = common::read_secure_signer_port();
+    let req = electra_attestation_req(ELECTRA_SRC_EPOCH, ELECTRA_TGT_EPOCH);
+    let bls_pk_hex = register_new_bls_key(port).await.pk_hex;
+    let (_resp, status) = make_signing_route_request(req, &bls_pk_hex, port)
+        .await
+        .unwrap();
+    assert_eq!(status, 200);
+
+    let req = electra_attestation_req(ELECTRA_SRC_EPOCH + 1, ELECTRA_TGT_EPOCH + 1);
+    let (_resp, status) = make_signing_route_request(req, &bls_pk_hex, port)
+        .await
+        .unwrap();
+    assert_eq!(status, 200);
+}
+
+#[tokio::test]
+pub async fn test_electra_slash_protection_prevents_slashable_attestations() {
+    let port = common::read_secure_signer_port();
+    let req = electra_attestation_req(ELECTRA_SRC_EPOCH, ELECTRA_TGT_EPOCH);
+    let bls_pk_hex = register_new_bls_key(port).await.pk_hex;
+    let (_resp, status) = make_signing_route_request(req, &bls_pk_hex, port)
+        .await
+        .unwrap();
+    assert_eq!(status, 200);
+
+    for (src, tgt) in [
+        // decreasing source
+        (ELECTRA_SRC_EPOCH - 1, ELECTRA_TGT_EPOCH + 1),
+        // same target
+        (ELECTRA_SRC_EPOCH, ELECTRA_TGT_EPOCH),
+        // decreasing target
+        (ELECTRA_SRC_EPOCH, ELECTRA_TGT_EPOCH - 1),
+    ] {
+        let req = electra_attestation_req(src, tgt);
+        let (_resp, status) = make_signing_route_request(req, &bls_pk_hex, port)
+            .await
+            .unwrap();
+        assert_eq!(status, 412, "source {src}, target {tgt}");
+    }
+}
+
 async fn perf_test(n: u64, bls_pk_hex: &String, port: Option<u16>) {
     for i in 1..n {
         let req = attestation_req(i, i);
@@ -8,7 +8,7 @@ use std::path::PathBuf;
 
 const START_SLOT: u64 = 1234;
 
-fn block_proposal_request(slot: u64) -> BLSSignMsg {
+pub fn block_proposal_request(slot: u64) -> BLSSignMsg {
     // Create a BlockRequest
     let req = mock_propose_block_request(slot);
     let signing_data: BlockRequest = serde_json::from_str(&req).unwrap();
@@ -69,8 +69,7 @@ fn mock_propose_block_request(slot: u64) -> String {
                      }},
                      "bls_to_execution_changes": []
                   }}
-               }},
-               "signingRoot": "0x270d43e74ce340de4bca2b1936beca0f4f5408d9e78aec4850920baf659d5b69"
+               }}
             }}"#
     );
     req
@@ -167,6 +166,47 @@ pub async fn test_slash_protection_prevents_decreasing_slot() {
     assert_eq!(status, 412);
 }
 
+#[tokio::test]
+pub async fn test_sign_timings_header_on_request() {
+    let bls_pk_hex = register_new_bls_key(None).await.pk_hex;
+    let test_app = axum::Router::new()
+        .route(
+            "/api/v1/eth2/sign/:bls_pk_hex",
+            axum::routing::post(
+                puffersecuresigner::enclave::shared::handlers::secure_sign_bls::handler,
+            ),
+        )
+        .with_state(puffersecuresigner::enclave::shared::handlers::AppState::default())
+        .into_make_service();
+    let server = axum_test::TestServer::new(test_app).unwrap();
+    let header = puffersecuresigner::enclave::shared::sign_timings::SIGN_TIMINGS_HEADER;
+
+    let resp = server
+        .post(&format!("/api/v1/eth2/sign/{bls_pk_hex}?timings=true"))
+        .json(&block_proposal_request(START_SLOT))
+        .await;
+    assert_eq!(resp.status_code(), 200);
+    let timings = resp.headers()[header].to_str().unwrap().to_string();
+    for phase in [
+        "queue",
+        "validate",
+        "slash_check",
+        "signing_root",
+        "slash_db_write",
+        "bls_sign",
+    ] {
+        assert!(timings.contains(&format!("{phase}=")), "{timings}");
+    }
+
+    // Off by default
+    let resp = server
+        .post(&format!("/api/v1/eth2/sign/{bls_pk_hex}"))
+        .json(&block_proposal_request(START_SLOT + 1))
+        .await;
+    assert_eq!(resp.status_code(), 200);
+    assert!(resp.headers().get(header).is_none());
+}
+
 async fn perf_test(n: u64, bls_pk_hex: &String, port: Option<u16>) {
     for i in 1..n {
         let req = block_proposal_request(i);
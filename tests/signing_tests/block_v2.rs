@@ -28,7 +28,6 @@ pub fn mock_propose_block_v2_request(slot: u64) -> String {
                 }},
                 "genesis_validators_root":"0x2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a"
              }},
-             "signingRoot": "0x2ebfc2d70944cc2fbff6d67c6d9cbb043d7fbe0a660d248b6e666ce110af418a",
             "beacon_block": {{
                 "version": "BELLATRIX",
                 "block_header": {{
@@ -0,0 +1,119 @@
+use crate::common;
+use puffersecuresigner::enclave::types::BlsKeyExportResponse;
+use puffersecuresigner::strip_0x_prefix;
+
+fn export_server(allow_key_export: bool) -> axum_test::TestServer {
+    export_server_with(puffersecuresigner::enclave::shared::handlers::AppState {
+        allow_key_export,
+        ..Default::default()
+    })
+}
+
+fn export_server_with(
+    state: puffersecuresigner::enclave::shared::handlers::AppState,
+) -> axum_test::TestServer {
+    let test_app = axum::Router::new()
+        .route(
+            "/eth/v1/keystores/:bls_pk_hex/export",
+            axum::routing::post(
+                puffersecuresigner::enclave::secure_signer::handlers::bls_export::handler,
+            ),
+        )
+        .with_state(state)
+        .into_make_service();
+    axum_test::TestServer::new(test_app).unwrap()
+}
+
+#[tokio::test]
+async fn test_export_round_trip() {
+    let bls_pk_hex = common::setup_dummy_keypair();
+    let (client_sk, client_pk) = puffersecuresigner::crypto::eth_keys::new_eth_key().unwrap();
+    let server = export_server(true);
+
+    let resp = server
+        .post(&format!("/eth/v1/keystores/{bls_pk_hex}/export"))
+        .json(&serde_json::json!({
+            "client_pubkey": hex::encode(client_pk.serialize_compressed()),
+            "genesis_validators_root": format!("0x{}", "2a".repeat(32)),
+        }))
+        .await;
+    assert_eq!(resp.status_code(), 200);
+    let resp: BlsKeyExportResponse = resp.json();
+    assert_eq!(resp.pk_hex, bls_pk_hex);
+
+    // Client side, the secret decrypts to the key that was saved
+    let encrypted_sk: &str = strip_0x_prefix!(resp.encrypted_sk);
+    let sk = puffersecuresigner::crypto::eth_keys::envelope_decrypt(
+        &client_sk,
+        &hex::decode(encrypted_sk).unwrap(),
+    )
+    .unwrap();
+    assert_eq!(
+        hex::encode(sk),
+        "5528f51154c1ea9b18eab53aabc1d1a478930aaebde47730b51375df02f0076c"
+    );
+
+    // The slashing protection history comes along
+    let db = resp.slashing_protection;
+    assert_eq!(db.metadata.genesis_validators_root, [0x2a; 32]);
+    assert_eq!(db.data.len(), 1);
+    let pk: &str = strip_0x_prefix!(bls_pk_hex);
+    assert_eq!(hex::encode(&db.data[0].pubkey[..]), pk);
+}
+
+#[tokio::test]
+async fn test_export_is_rejected_when_disabled() {
+    let bls_pk_hex = common::setup_dummy_keypair();
+    let (_, client_pk) = puffersecuresigner::crypto::eth_keys::new_eth_key().unwrap();
+    let resp = export_server(false)
+        .post(&format!("/eth/v1/keystores/{bls_pk_hex}/export"))
+        .json(&serde_json::json!({
+            "client_pubkey": hex::encode(client_pk.serialize_compressed()),
+            "genesis_validators_root": format!("0x{}", "2a".repeat(32)),
+        }))
+        .await;
+    assert_eq!(resp.status_code(), 403);
+}
+
+#[tokio::test]
+async fn test_export_requires_genesis_validators_root() {
+    let bls_pk_hex = common::setup_dummy_keypair();
+    let (_, client_pk) = puffersecuresigner::crypto::eth_keys::new_eth_key().unwrap();
+    let resp = export_server(true)
+        .post(&format!("/eth/v1/keystores/{bls_pk_hex}/export"))
+        .json(&serde_json::json!({
+            "client_pubkey": hex::encode(client_pk.serialize_compressed()),
+        }))
+        .await;
+    assert_eq!(resp.status_code(), 422);
+}
+
+#[tokio::test]
+async fn test_export_waits_for_the_key_lock() {
+    let bls_pk_hex = common::setup_dummy_keypair();
+    let (_, client_pk) = puffersecuresigner::crypto::eth_keys::new_eth_key().unwrap();
+    let state = puffersecuresigner::enclave::shared::handlers::AppState {
+        allow_key_export: true,
+        key_locks: puffersecuresigner::enclave::shared::key_lock::KeyLocks::new(
+            std::time::Duration::from_millis(10),
+        ),
+        ..Default::default()
+    };
+
+    // A sign request holding the key's lock could still add to the history being exported
+    let pk: &str = strip_0x_prefix!(bls_pk_hex);
+    let held = state.key_locks.lock(pk).await.unwrap();
+    let server = export_server_with(state);
+    let export = || {
+        server
+            .post(&format!("/eth/v1/keystores/{bls_pk_hex}/export"))
+            .json(&serde_json::json!({
+                "client_pubkey": hex::encode(client_pk.serialize_compressed()),
+                "genesis_validators_root": format!("0x{}", "2a".repeat(32)),
+            }))
+    };
+    assert_eq!(export().await.status_code(), 503);
+
+    drop(held);
+    assert_eq!(export().await.status_code(), 200);
+}
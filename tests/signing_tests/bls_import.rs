@@ -0,0 +1,336 @@
+use crate::signing_tests::keystore_archive::{interchange_entry, new_keystore};
+use puffersecuresigner::crypto::eth_keys;
+use puffersecuresigner::enclave::types::{
+    KeyImportRequest, KeyImportResponse, KeystoreImportStatus,
+};
+use puffersecuresigner::io::config::Config;
+
+pub fn import_server_with(require_import_slashing_protection: bool) -> axum_test::TestServer {
+    let test_app = axum::Router::new()
+        .route(
+            "/eth/v1/keystores",
+            axum::routing::post(
+                puffersecuresigner::enclave::secure_signer::handlers::bls_import::handler,
+            ),
+        )
+        .with_state(puffersecuresigner::enclave::shared::handlers::AppState {
+            require_import_slashing_protection,
+            ..Default::default()
+        })
+        .into_make_service();
+    axum_test::TestServer::new(test_app).unwrap()
+}
+
+fn import_server() -> axum_test::TestServer {
+    import_server_with(false)
+}
+
+/// Saves a fresh enclave ETH key, returning its pubkey in hex and parsed
+pub fn new_enclave_eth_key() -> (String, ecies::PublicKey) {
+    let (sk, pk) = eth_keys::new_eth_key().unwrap();
+    eth_keys::save_eth_key(sk, pk).unwrap();
+    (eth_keys::eth_pk_to_hex(&pk), pk)
+}
+
+pub fn import_request(
+    password: &str,
+    encrypt_to: &ecies::PublicKey,
+    encrypting_pk_hex: String,
+) -> (String, KeyImportRequest) {
+    let (pk_hex, keystore) = new_keystore(password);
+    let ct_password = eth_keys::envelope_encrypt(encrypt_to, password.as_bytes()).unwrap();
+    (
+        pk_hex,
+        KeyImportRequest {
+            keystore,
+            ct_password_hex: hex::encode(ct_password),
+            encrypting_pk_hex,
+            ct_mac_secret_hex: None,
+            slashing_protection: None,
+            fee_recipients: None,
+            allowed_types: None,
+            proposer_index: None,
+        },
+    )
+}
+
+#[tokio::test]
+async fn test_import_keystore_with_encrypted_password() {
+    let _data_dir = Config::scoped_temp().unwrap();
+    let (eth_pk_hex, eth_pk) = new_enclave_eth_key();
+    let (bls_pk_hex, req) = import_request("hunter2", &eth_pk, eth_pk_hex);
+
+    let resp = import_server().post("/eth/v1/keystores").json(&req).await;
+    assert_eq!(resp.status_code(), 200);
+    let resp: KeyImportResponse = resp.json();
+    assert_eq!(resp.pubkey, format!("0x{bls_pk_hex}"));
+    assert_eq!(resp.data[0].status, KeystoreImportStatus::Imported);
+    assert!(puffersecuresigner::io::key_management::bls_key_exists(
+        &bls_pk_hex
+    ));
+}
+
+#[tokio::test]
+async fn test_import_response_matches_keymanager_api() {
+    let _data_dir = Config::scoped_temp().unwrap();
+    let (eth_pk_hex, eth_pk) = new_enclave_eth_key();
+    let (_, req) = import_request("hunter2", &eth_pk, eth_pk_hex);
+    let server = import_server();
+
+    // As the keymanager OpenAPI examples, with no message on success
+    let resp = server.post("/eth/v1/keystores").json(&req).await;
+    let resp: serde_json::Value = resp.json();
+    assert_eq!(resp["data"], serde_json::json!([{ "status": "imported" }]));
+
+    let resp = server.post("/eth/v1/keystores").json(&req).await;
+    assert_eq!(resp.status_code(), 200);
+    let resp: serde_json::Value = resp.json();
+    assert_eq!(resp["data"], serde_json::json!([{ "status": "duplicate" }]));
+}
+
+#[tokio::test]
+async fn test_import_rejects_unknown_encrypting_key() {
+    let _data_dir = Config::scoped_temp().unwrap();
+    // Encrypted to a key that never made it into the enclave
+    let (_, pk) = eth_keys::new_eth_key().unwrap();
+    let (_, req) = import_request("hunter2", &pk, eth_keys::eth_pk_to_hex(&pk));
+
+    let resp = import_server().post("/eth/v1/keystores").json(&req).await;
+    assert_eq!(resp.status_code(), 400);
+    assert!(resp
+        .text()
+        .contains("encryption public key does not match any enclave key"));
+}
+
+#[tokio::test]
+async fn test_import_rejects_password_encrypted_to_another_key() {
+    let _data_dir = Config::scoped_temp().unwrap();
+    let (eth_pk_hex, _) = new_enclave_eth_key();
+    let (_, other_pk) = eth_keys::new_eth_key().unwrap();
+    let (bls_pk_hex, req) = import_request("hunter2", &other_pk, eth_pk_hex);
+
+    let resp = import_server().post("/eth/v1/keystores").json(&req).await;
+    assert_eq!(resp.status_code(), 400);
+    assert!(resp.text().contains("decryption failed"));
+    assert!(!puffersecuresigner::io::key_management::bls_key_exists(
+        &bls_pk_hex
+    ));
+}
+
+#[tokio::test]
+async fn test_import_rejects_oversized_argon2id_params() {
+    let _data_dir = Config::scoped_temp().unwrap();
+    let (eth_pk_hex, eth_pk) = new_enclave_eth_key();
+    let (_, mut req) = import_request("hunter2", &eth_pk, eth_pk_hex);
+
+    for (m, t, p) in [(4 * 1024 * 1024, 2, 1), (256, 1_000_000, 1), (256, 2, 255)] {
+        req.keystore = serde_json::json!({
+            "crypto": {
+                "kdf": {
+                    "function": "argon2id",
+                    "params": { "dklen": 32, "m": m, "t": t, "p": p, "salt": "00" },
+                    "message": ""
+                },
+                "checksum": { "function": "sha256", "params": {}, "message": "" },
+                "cipher": { "function": "aes-128-ctr", "params": { "iv": "" }, "message": "" }
+            },
+            "pubkey": "",
+            "version": 4
+        })
+        .to_string();
+        // Refused before any key derivation is attempted
+        let resp = import_server().post("/eth/v1/keystores").json(&req).await;
+        assert_eq!(resp.status_code(), 400);
+        assert!(resp.text().contains("exceed the maximum"));
+    }
+}
+
+fn interchange_of(entries: &[String]) -> String {
+    format!(
+        r#"{{
+            "metadata": {{
+                "interchange_format_version": "5",
+                "genesis_validators_root": "0x04700007fabc8282644aed6d1c7c9e21d38a03a0c4ba193f3afe428824b3a673"
+            }},
+            "data": [{}]
+        }}"#,
+        entries.join(",")
+    )
+}
+
+#[tokio::test]
+async fn test_strict_import_rejects_key_without_slashing_protection() {
+    let _data_dir = Config::scoped_temp().unwrap();
+    let server = import_server_with(true);
+    let (eth_pk_hex, eth_pk) = new_enclave_eth_key();
+
+    // No interchange at all
+    let (bls_pk_hex, req) = import_request("hunter2", &eth_pk, eth_pk_hex.clone());
+    let resp = server.post("/eth/v1/keystores").json(&req).await;
+    assert_eq!(resp.status_code(), 400);
+    assert!(resp.text().contains(&format!(
+        "no slashing protection history for 0x{bls_pk_hex}"
+    )));
+    assert!(!puffersecuresigner::io::key_management::bls_key_exists(
+        &bls_pk_hex
+    ));
+
+    // An interchange with history for some other key only
+    let (other_pk_hex, _) = new_keystore("other");
+    let (bls_pk_hex, mut req) = import_request("hunter2", &eth_pk, eth_pk_hex);
+    req.slashing_protection = Some(interchange_of(&[interchange_entry(&other_pk_hex, 1, 1, 2)]));
+    let resp = server.post("/eth/v1/keystores").json(&req).await;
+    assert_eq!(resp.status_code(), 400);
+    assert!(!puffersecuresigner::io::key_management::bls_key_exists(
+        &bls_pk_hex
+    ));
+}
+
+#[tokio::test]
+async fn test_strict_import_accepts_key_with_slashing_protection() {
+    let _data_dir = Config::scoped_temp().unwrap();
+    let (eth_pk_hex, eth_pk) = new_enclave_eth_key();
+    let (bls_pk_hex, mut req) = import_request("hunter2", &eth_pk, eth_pk_hex);
+    req.slashing_protection = Some(interchange_of(&[interchange_entry(
+        &bls_pk_hex,
+        100,
+        10,
+        20,
+    )]));
+
+    let resp = import_server_with(true)
+        .post("/eth/v1/keystores")
+        .json(&req)
+        .await;
+    assert_eq!(resp.status_code(), 200);
+    let resp: KeyImportResponse = resp.json();
+    assert_eq!(resp.data[0].status, KeystoreImportStatus::Imported);
+    assert_eq!(resp.slashing_protection.len(), 1);
+}
+
+#[tokio::test]
+async fn test_lenient_import_accepts_key_without_slashing_protection() {
+    let _data_dir = Config::scoped_temp().unwrap();
+    let (eth_pk_hex, eth_pk) = new_enclave_eth_key();
+    let (bls_pk_hex, req) = import_request("hunter2", &eth_pk, eth_pk_hex);
+
+    let resp = import_server_with(false)
+        .post("/eth/v1/keystores")
+        .json(&req)
+        .await;
+    assert_eq!(resp.status_code(), 200);
+
+    // Imported with an empty history
+    let data =
+        puffersecuresigner::eth2::slash_protection::SlashingProtectionData::read(&bls_pk_hex)
+            .unwrap();
+    assert!(data.signed_blocks.is_empty());
+    assert!(data.signed_attestations.is_empty());
+}
+
+#[tokio::test]
+async fn test_import_rejects_bad_fee_recipients() {
+    let _data_dir = Config::scoped_temp().unwrap();
+    let (eth_pk_hex, eth_pk) = new_enclave_eth_key();
+    let (bls_pk_hex, mut req) = import_request("hunter2", &eth_pk, eth_pk_hex);
+    req.fee_recipients = Some(vec!["0x2a2a".to_string()]);
+
+    let resp = import_server().post("/eth/v1/keystores").json(&req).await;
+    assert_eq!(resp.status_code(), 400);
+    assert!(!puffersecuresigner::io::key_management::bls_key_exists(
+        &bls_pk_hex
+    ));
+}
+
+#[tokio::test]
+async fn test_import_only_disables_bls_keygen() {
+    let _data_dir = Config::scoped_temp().unwrap();
+    let test_app = axum::Router::new()
+        .route(
+            "/eth/v1/keygen/bls",
+            axum::routing::post(
+                puffersecuresigner::enclave::secure_signer::handlers::bls_keygen::handler,
+            ),
+        )
+        .route(
+            "/eth/v1/keystores",
+            axum::routing::post(
+                puffersecuresigner::enclave::secure_signer::handlers::bls_import::handler,
+            ),
+        )
+        .with_state(puffersecuresigner::enclave::shared::handlers::AppState {
+            import_only: true,
+            ..Default::default()
+        })
+        .into_make_service();
+    let server = axum_test::TestServer::new(test_app).unwrap();
+
+    let resp = server.post("/eth/v1/keygen/bls").await;
+    assert_eq!(resp.status_code(), 404);
+    assert!(puffersecuresigner::io::key_management::list_bls_keys()
+        .unwrap()
+        .is_empty());
+
+    // Importing still works
+    let (eth_pk_hex, eth_pk) = new_enclave_eth_key();
+    let (bls_pk_hex, req) = import_request("hunter2", &eth_pk, eth_pk_hex);
+    let resp = server.post("/eth/v1/keystores").json(&req).await;
+    assert_eq!(resp.status_code(), 200);
+    assert!(puffersecuresigner::io::key_management::bls_key_exists(
+        &bls_pk_hex
+    ));
+}
+
+#[tokio::test]
+async fn test_reimport_is_duplicate_and_keeps_slashing_protection() {
+    let _data_dir = Config::scoped_temp().unwrap();
+    let (eth_pk_hex, eth_pk) = new_enclave_eth_key();
+    let (bls_pk_hex, mut req) = import_request("hunter2", &eth_pk, eth_pk_hex);
+    req.slashing_protection = Some(interchange_of(&[interchange_entry(
+        &bls_pk_hex,
+        100,
+        10,
+        20,
+    )]));
+    let server = import_server_with(true);
+    let resp: KeyImportResponse = server.post("/eth/v1/keystores").json(&req).await.json();
+    assert_eq!(resp.data[0].status, KeystoreImportStatus::Imported);
+    let marks = || {
+        puffersecuresigner::eth2::slash_protection::SlashingProtectionData::read(&bls_pk_hex)
+            .unwrap()
+            .high_water_marks()
+    };
+    assert_eq!(marks(), (100, 10, 20));
+
+    // Neither a newer interchange nor none at all touches the saved history
+    req.slashing_protection = Some(interchange_of(&[interchange_entry(
+        &bls_pk_hex,
+        200,
+        30,
+        40,
+    )]));
+    let resp = server.post("/eth/v1/keystores").json(&req).await;
+    assert_eq!(resp.status_code(), 200);
+    let resp: KeyImportResponse = resp.json();
+    assert_eq!(resp.data[0].status, KeystoreImportStatus::Duplicate);
+    assert_eq!(resp.pubkey, format!("0x{bls_pk_hex}"));
+    assert!(resp.slashing_protection.is_empty());
+    assert_eq!(marks(), (100, 10, 20));
+
+    req.slashing_protection = None;
+    let resp = server.post("/eth/v1/keystores").json(&req).await;
+    assert_eq!(resp.status_code(), 200);
+    let resp: KeyImportResponse = resp.json();
+    assert_eq!(resp.data[0].status, KeystoreImportStatus::Duplicate);
+    assert_eq!(marks(), (100, 10, 20));
+
+    // A history that outlived its key isn't reset when the key comes back
+    puffersecuresigner::io::key_management::delete_bls_key(&bls_pk_hex).unwrap();
+    let resp: KeyImportResponse = import_server()
+        .post("/eth/v1/keystores")
+        .json(&req)
+        .await
+        .json();
+    assert_eq!(resp.data[0].status, KeystoreImportStatus::Imported);
+    assert_eq!(marks(), (100, 10, 20));
+}
@@ -0,0 +1,175 @@
+use crate::common;
+use crate::common::bls_keygen_helper::register_new_bls_key;
+use crate::common::signing_helper::*;
+use puffersecuresigner::eth2::eth_signing::*;
+use puffersecuresigner::eth2::eth_types::*;
+use puffersecuresigner::strip_0x_prefix;
+
+fn bls_to_execution_change_request() -> BLSSignMsg {
+    // Create a BlsToExecutionChangeRequest
+    let req = mock_bls_to_execution_change_request();
+    let signing_data: BlsToExecutionChangeRequest =
+        serde_json::from_str(&req).expect("Failed to serialize mock BlsToExecutionChangeRequest");
+    BLSSignMsg::BLS_TO_EXECUTION_CHANGE(signing_data)
+}
+
+pub fn mock_bls_to_execution_change_request() -> String {
+    let req = format!(
+        r#"
+        {{
+            "type": "BLS_TO_EXECUTION_CHANGE",
+            "fork_info":{{
+                "fork":{{
+                    "previous_version":"0x02000000",
+                    "current_version":"0x03000000",
+                    "epoch":"194048"
+                }},
+                "genesis_validators_root":"0x4b363db94e286120d76eb905340fdd4e54bfe9f06bf33ff6cf5ad27f511bfe95"
+            }},
+            "bls_to_execution_change": {{
+                "validator_index": "123",
+                "from_bls_pubkey": "0x8349434ad0700e79be65c0c7043945df426bd6d7e288c16671df69d822344f1b0ce8de80360a50550ad782b68035cb18",
+                "to_execution_address": "0x2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a"
+            }}
+        }}"#
+    );
+    req
+}
+
+#[tokio::test]
+async fn test_bls_to_execution_change_fails_from_invalid_pk_hex() {
+    let port = common::read_secure_signer_port();
+    let req = bls_to_execution_change_request();
+    let bls_pk_hex = "0xdeadbeef".to_string();
+    let (_resp, status) = make_signing_route_request(req, &bls_pk_hex, port)
+        .await
+        .unwrap();
+    assert_eq!(status, 400);
+}
+
+#[tokio::test]
+async fn test_bls_to_execution_change_happy_path() {
+    let port = common::read_secure_signer_port();
+    let req = bls_to_execution_change_request();
+    let bls_pk_hex = register_new_bls_key(port).await.pk_hex;
+    let (_resp, status) = make_signing_route_request(req, &bls_pk_hex, port)
+        .await
+        .unwrap();
+    assert_eq!(status, 200);
+}
+
+#[tokio::test]
+async fn test_bls_to_execution_change_signature_verifies_under_genesis_domain() {
+    let port = None;
+    let req = bls_to_execution_change_request();
+    let bls_pk_hex = register_new_bls_key(port).await.pk_hex;
+    let (resp, status) = make_signing_route_request(req, &bls_pk_hex, port)
+        .await
+        .unwrap();
+    assert_eq!(status, 200);
+
+    // The mock route runs with GENESIS_FORK_VERSION
+    let root = bls_to_execution_change_request().to_signing_root(Some(GENESIS_FORK_VERSION));
+    let sig = resp.unwrap().to_ssz_bytes().unwrap();
+    let sig = blsttc::Signature::from_bytes(sig[..].try_into().unwrap()).unwrap();
+    let pk_hex: String = strip_0x_prefix!(&bls_pk_hex);
+    let pk = blsttc::PublicKey::from_hex(&pk_hex).unwrap();
+    assert!(pk.verify(&sig, root));
+}
+
+/// The mock change, moving the withdrawal credentials of `from_bls_pubkey`
+fn change_from(from_bls_pubkey: &str) -> serde_json::Value {
+    let mut change: serde_json::Value =
+        serde_json::from_str(&mock_bls_to_execution_change_request()).unwrap();
+    change["bls_to_execution_change"]["from_bls_pubkey"] = from_bls_pubkey.into();
+    change
+}
+
+fn batch_server(
+    state: puffersecuresigner::enclave::shared::handlers::AppState,
+) -> axum_test::TestServer {
+    axum_test::TestServer::new(
+        axum::Router::new()
+            .route(
+                "/api/v1/eth2/sign/bls-to-execution-change/batch",
+                axum::routing::post(
+                    puffersecuresigner::enclave::shared::handlers::sign_bls_to_execution_change_batch::handler,
+                ),
+            )
+            .with_state(state)
+            .into_make_service(),
+    )
+    .unwrap()
+}
+
+#[tokio::test]
+async fn test_batch_isolates_failing_items() {
+    let _data_dir = puffersecuresigner::io::config::Config::scoped_temp().unwrap();
+    let keys: Vec<String> = (0..2)
+        .map(|_| {
+            let sk = puffersecuresigner::crypto::bls_keys::new_bls_key(0);
+            puffersecuresigner::crypto::bls_keys::save_bls_key(&sk).unwrap();
+            format!("0x{}", sk.public_keys().public_key().to_hex())
+        })
+        .collect();
+    let unknown = format!(
+        "0x{}",
+        puffersecuresigner::crypto::bls_keys::new_bls_key(0)
+            .public_keys()
+            .public_key()
+            .to_hex()
+    );
+
+    let server = batch_server(crate::signing_tests::sign_state());
+    let resp = server
+        .post("/api/v1/eth2/sign/bls-to-execution-change/batch")
+        .json(&vec![
+            change_from(&keys[0]),
+            // Not a key of this signer
+            change_from(&unknown),
+            // Not a pubkey at all
+            change_from("0xdeadbeef"),
+            change_from(&keys[1]),
+        ])
+        .await;
+    assert_eq!(resp.status_code(), 200);
+    let resp: puffersecuresigner::enclave::types::BatchSignResponse = resp.json();
+    let statuses: Vec<u16> = resp.data.iter().map(|r| r.status).collect();
+    assert_eq!(statuses, vec![200, 404, 400, 200]);
+    assert!(resp.data[1].signature.is_none());
+    assert!(resp.data[1].error.is_some());
+    assert!(resp.data[2].signature.is_none());
+
+    // Each valid item is signed by its from_bls_pubkey's key
+    for (result, pk_hex) in [(&resp.data[0], &keys[0]), (&resp.data[3], &keys[1])] {
+        assert_eq!(&result.pubkey, pk_hex);
+        let req: BlsToExecutionChangeRequest = serde_json::from_value(change_from(pk_hex)).unwrap();
+        let root = BLSSignMsg::BLS_TO_EXECUTION_CHANGE(req).to_signing_root(None);
+        let sig: String = strip_0x_prefix!(result.signature.as_ref().unwrap());
+        let sig = blsttc::Signature::from_bytes(hex::decode(sig).unwrap()[..].try_into().unwrap())
+            .unwrap();
+        let pk_hex: String = strip_0x_prefix!(pk_hex);
+        assert!(blsttc::PublicKey::from_hex(&pk_hex)
+            .unwrap()
+            .verify(&sig, root));
+    }
+}
+
+#[tokio::test]
+async fn test_batch_is_refused_when_request_macs_are_required() {
+    let _data_dir = puffersecuresigner::io::config::Config::scoped_temp().unwrap();
+    let sk = puffersecuresigner::crypto::bls_keys::new_bls_key(0);
+    puffersecuresigner::crypto::bls_keys::save_bls_key(&sk).unwrap();
+    let bls_pk_hex = format!("0x{}", sk.public_keys().public_key().to_hex());
+
+    // Items carry no MAC, so a batch could sign for every key without one
+    let server = batch_server(puffersecuresigner::enclave::shared::handlers::AppState {
+        require_request_mac: true,
+        ..crate::signing_tests::sign_state()
+    });
+    let resp = server
+        .post("/api/v1/eth2/sign/bls-to-execution-change/batch")
+        .json(&vec![change_from(&bls_pk_hex)])
+        .await;
+    assert_eq!(resp.status_code(), 403);
+}
@@ -0,0 +1,97 @@
+use axum::http::{HeaderName, HeaderValue};
+use puffersecuresigner::enclave::shared::handlers::AppState;
+use puffersecuresigner::enclave::types::ConfigResponse;
+use puffersecuresigner::eth2::eth_types::{
+    AllowedDomains, ForkSchedule, GasLimitBounds, GENESIS_FORK_VERSION,
+};
+use puffersecuresigner::io::config::Config;
+use std::time::Duration;
+
+fn config_server(state: AppState) -> axum_test::TestServer {
+    let test_app = axum::Router::new()
+        .route(
+            "/eth/v1/config",
+            axum::routing::get(puffersecuresigner::enclave::shared::handlers::config::handler),
+        )
+        .with_state(state)
+        .into_make_service();
+    axum_test::TestServer::new(test_app).unwrap()
+}
+
+#[tokio::test]
+async fn test_config_reflects_the_running_signer() {
+    let _data_dir = Config::scoped_in_memory();
+    let server = config_server(AppState {
+        genesis_fork_version: GENESIS_FORK_VERSION,
+        fork_schedule: ForkSchedule::for_genesis_fork_version(GENESIS_FORK_VERSION).unwrap(),
+        allowed_domains: AllowedDomains::parse("0x00000000", "").unwrap(),
+        require_request_mac: true,
+        deny_unknown_fields: true,
+        import_only: true,
+        gas_limit_bounds: GasLimitBounds {
+            min: 30_000_000,
+            max: 36_000_000,
+        },
+        signing_queue: Some(
+            puffersecuresigner::enclave::shared::queue::SigningQueue::new(
+                4,
+                16,
+                Duration::from_millis(250),
+            ),
+        ),
+        sign_timeout: Some(Duration::from_millis(1500)),
+        key_limit: Some(puffersecuresigner::enclave::shared::key_limit::KeyLimit::new(100)),
+        config_token: Some("hunter2".to_string()),
+        ..Default::default()
+    });
+
+    let resp = server
+        .get("/eth/v1/config")
+        .add_header(
+            HeaderName::from_static("authorization"),
+            HeaderValue::from_static("Bearer hunter2"),
+        )
+        .await;
+    assert_eq!(resp.status_code(), 200);
+    assert!(!resp.text().contains("hunter2"));
+    let config: ConfigResponse = resp.json();
+
+    assert_eq!(config.storage, "memory");
+    assert_eq!(config.genesis_fork_version, "0x00000000");
+    assert_eq!(config.fork_schedule[0], ("0x00000000".to_string(), 0));
+    assert_eq!(config.allowed_fork_versions, vec!["0x00000000"]);
+    assert!(config.allowed_genesis_validators_roots.is_empty());
+    assert!(config.require_request_mac);
+    assert!(config.deny_unknown_fields);
+    assert!(config.import_only);
+    assert!(!config.allow_key_export);
+    assert!(!config.allow_raw_signing);
+    assert_eq!(
+        (config.min_gas_limit, config.max_gas_limit),
+        (30_000_000, 36_000_000)
+    );
+    assert_eq!(config.signing_concurrency, Some(4));
+    assert_eq!(config.signing_queue_max_depth, Some(16));
+    assert_eq!(config.signing_queue_max_wait_ms, Some(250));
+    assert_eq!(config.sign_timeout_ms, Some(1500));
+    assert_eq!(config.max_keys, Some(100));
+    assert_eq!(config.request_id_ttl_secs, None);
+    assert!(!config.sessions_enabled);
+    assert_eq!(config.version.version, env!("CARGO_PKG_VERSION"));
+}
+
+#[tokio::test]
+async fn test_config_is_refused_without_the_token() {
+    let server = config_server(AppState {
+        config_token: Some("hunter2".to_string()),
+        ..Default::default()
+    });
+    let resp = server
+        .get("/eth/v1/config")
+        .add_header(
+            HeaderName::from_static("authorization"),
+            HeaderValue::from_static("Bearer hunter3"),
+        )
+        .await;
+    assert_eq!(resp.status_code(), 401);
+}
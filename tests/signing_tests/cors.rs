@@ -0,0 +1,129 @@
+use axum::http::{HeaderName, HeaderValue};
+use puffersecuresigner::enclave::shared::cors::{self, Cors};
+use puffersecuresigner::enclave::shared::handlers::AppState;
+use puffersecuresigner::io::config::Config;
+
+const DASHBOARD: &str = "https://dashboard.example.com";
+
+fn cors_server(cors: Cors) -> axum_test::TestServer {
+    let test_app = axum::Router::new()
+        .route(
+            "/eth/v1/keystores",
+            axum::routing::get(
+                puffersecuresigner::enclave::shared::handlers::list_bls_keys::handler,
+            ),
+        )
+        .route(
+            "/api/v1/eth2/sign/:bls_pk_hex",
+            axum::routing::post(
+                puffersecuresigner::enclave::shared::handlers::secure_sign_bls::handler,
+            ),
+        )
+        .layer(axum::middleware::from_fn_with_state(cors, cors::middleware))
+        .with_state(AppState::default())
+        .into_make_service();
+    axum_test::TestServer::new(test_app).unwrap()
+}
+
+fn origin(origin: &'static str) -> (HeaderName, HeaderValue) {
+    (axum::http::header::ORIGIN, HeaderValue::from_static(origin))
+}
+
+#[tokio::test]
+async fn test_list_route_allows_configured_origin() {
+    let _data_dir = Config::scoped_temp().unwrap();
+    let server = cors_server(Cors::new(DASHBOARD).unwrap());
+
+    let (name, value) = origin(DASHBOARD);
+    let resp = server
+        .get("/eth/v1/keystores")
+        .add_header(name, value)
+        .await;
+    assert_eq!(resp.status_code(), 200);
+    assert_eq!(
+        resp.headers()
+            .get(axum::http::header::ACCESS_CONTROL_ALLOW_ORIGIN)
+            .unwrap(),
+        DASHBOARD
+    );
+
+    // Other origins get no CORS headers, so browsers keep blocking them
+    let (name, value) = origin("https://evil.example.com");
+    let resp = server
+        .get("/eth/v1/keystores")
+        .add_header(name, value)
+        .await;
+    assert_eq!(resp.status_code(), 200);
+    assert!(resp
+        .headers()
+        .get(axum::http::header::ACCESS_CONTROL_ALLOW_ORIGIN)
+        .is_none());
+}
+
+#[tokio::test]
+async fn test_preflight_on_list_route() {
+    let _data_dir = Config::scoped_temp().unwrap();
+    let server = cors_server(Cors::new(DASHBOARD).unwrap());
+
+    let preflight = |method: &'static str| {
+        let (name, value) = origin(DASHBOARD);
+        server
+            .method(axum::http::Method::OPTIONS, "/eth/v1/keystores")
+            .add_header(name, value)
+            .add_header(
+                axum::http::header::ACCESS_CONTROL_REQUEST_METHOD,
+                HeaderValue::from_static(method),
+            )
+    };
+    let resp = preflight("GET").await;
+    assert_eq!(resp.status_code(), 204);
+    assert_eq!(
+        resp.headers()
+            .get(axum::http::header::ACCESS_CONTROL_ALLOW_METHODS)
+            .unwrap(),
+        "GET, HEAD"
+    );
+
+    // Only read-only methods by default
+    assert_eq!(preflight("POST").await.status_code(), 403);
+}
+
+#[tokio::test]
+async fn test_sign_route_never_sends_cors_headers() {
+    let _data_dir = Config::scoped_temp().unwrap();
+    let server = cors_server(Cors::new("*").unwrap().with_methods("GET, POST").unwrap());
+
+    let bls_pk_hex = format!("0x{}", "aa".repeat(48));
+    let (name, value) = origin(DASHBOARD);
+    let resp = server
+        .post(&format!("/api/v1/eth2/sign/{bls_pk_hex}"))
+        .add_header(name, value)
+        .json(&serde_json::json!({}))
+        .await;
+    assert!(resp
+        .headers()
+        .get(axum::http::header::ACCESS_CONTROL_ALLOW_ORIGIN)
+        .is_none());
+}
+
+#[tokio::test]
+async fn test_list_route_stays_read_only_with_post_configured() {
+    let _data_dir = Config::scoped_temp().unwrap();
+    let server = cors_server(
+        Cors::new(DASHBOARD)
+            .unwrap()
+            .with_methods("GET, POST")
+            .unwrap(),
+    );
+
+    let (name, value) = origin(DASHBOARD);
+    let resp = server
+        .method(axum::http::Method::OPTIONS, "/eth/v1/keystores")
+        .add_header(name, value)
+        .add_header(
+            axum::http::header::ACCESS_CONTROL_REQUEST_METHOD,
+            HeaderValue::from_static("POST"),
+        )
+        .await;
+    assert_eq!(resp.status_code(), 403);
+}
@@ -0,0 +1,53 @@
+use crate::signing_tests::block::block_proposal_request;
+use crate::signing_tests::{sign_server, sign_state};
+use puffersecuresigner::crypto::bls_keys;
+use puffersecuresigner::eth2::eth_signing::BLSSignMsg;
+use puffersecuresigner::eth2::slash_protection::SlashingProtectionData;
+
+async fn sign(
+    server: &axum_test::TestServer,
+    pk_hex: &str,
+    req: BLSSignMsg,
+    dry_run: bool,
+) -> axum_test::TestResponse {
+    server
+        .post(&format!("/api/v1/eth2/sign/0x{pk_hex}?dryRun={dry_run}"))
+        .json(&req)
+        .await
+}
+
+fn snapshot(pk_hex: &str) -> serde_json::Value {
+    serde_json::to_value(SlashingProtectionData::read(pk_hex).unwrap()).unwrap()
+}
+
+#[tokio::test]
+async fn test_dry_run_checks_slashing_without_recording() {
+    let server = sign_server(sign_state());
+    let sk = bls_keys::new_bls_key(0);
+    bls_keys::save_bls_key(&sk).unwrap();
+    let pk_hex = sk.public_keys().public_key().to_hex();
+    SlashingProtectionData::from_pk_hex(&pk_hex)
+        .unwrap()
+        .write()
+        .unwrap();
+
+    let resp = sign(&server, &pk_hex, block_proposal_request(100), false).await;
+    assert_eq!(resp.status_code(), 200);
+    let before = snapshot(&pk_hex);
+
+    // Slashable, so the dry run is refused and nothing is recorded
+    let resp = sign(&server, &pk_hex, block_proposal_request(100), true).await;
+    assert_eq!(resp.status_code(), 412);
+    assert_eq!(snapshot(&pk_hex), before);
+
+    // Signable, but a dry run returns no signature and records nothing
+    let resp = sign(&server, &pk_hex, block_proposal_request(101), true).await;
+    assert_eq!(resp.status_code(), 200);
+    assert!(resp.as_bytes().is_empty());
+    assert_eq!(snapshot(&pk_hex), before);
+
+    // So the real request still goes through
+    let resp = sign(&server, &pk_hex, block_proposal_request(101), false).await;
+    assert_eq!(resp.status_code(), 200);
+    assert_ne!(snapshot(&pk_hex), before);
+}
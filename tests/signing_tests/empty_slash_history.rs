@@ -0,0 +1,89 @@
+use crate::common::signing_helper::mock_secure_sign_route_with_state;
+use crate::signing_tests::block::block_proposal_request;
+use crate::signing_tests::bls_import::{import_request, import_server_with, new_enclave_eth_key};
+use puffersecuresigner::enclave::shared::handlers::AppState;
+use puffersecuresigner::enclave::shared::metrics::EMPTY_SLASH_HISTORY_LOG_TARGET;
+use puffersecuresigner::eth2::eth_types::GENESIS_FORK_VERSION;
+use puffersecuresigner::io::config::Config;
+use std::sync::{Arc, Mutex};
+
+/// Collects everything the subscriber writes
+#[derive(Clone, Default)]
+struct Captured(Arc<Mutex<Vec<u8>>>);
+
+impl std::io::Write for Captured {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        self.0.lock().unwrap().extend_from_slice(buf);
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        Ok(())
+    }
+}
+
+impl Captured {
+    /// The empty slashing protection history warnings written so far
+    fn warnings(&self) -> Vec<String> {
+        String::from_utf8(self.0.lock().unwrap().clone())
+            .unwrap()
+            .lines()
+            .filter(|line| line.contains("WARN") && line.contains(EMPTY_SLASH_HISTORY_LOG_TARGET))
+            .map(str::to_string)
+            .collect()
+    }
+}
+
+#[tokio::test]
+async fn test_first_sign_without_slash_history_warns() {
+    // The handlers log through the `log` crate, forward it to the subscriber set below
+    tracing_log::LogTracer::init().ok();
+    let captured = Captured::default();
+    let writer = captured.clone();
+    let subscriber = tracing_subscriber::fmt()
+        .with_ansi(false)
+        .with_writer(move || writer.clone())
+        .finish();
+    // The test servers run on this thread, so their handlers log through this subscriber
+    let _guard = tracing::subscriber::set_default(subscriber);
+
+    // A key imported without an interchange
+    let _data_dir = Config::scoped_temp().unwrap();
+    let (eth_pk_hex, eth_pk) = new_enclave_eth_key();
+    let (bls_pk_hex, req) = import_request("hunter2", &eth_pk, eth_pk_hex);
+    let resp = import_server_with(false)
+        .post("/eth/v1/keystores")
+        .json(&req)
+        .await;
+    assert_eq!(resp.status_code(), 200);
+
+    let state = AppState {
+        genesis_fork_version: GENESIS_FORK_VERSION,
+        ..Default::default()
+    };
+    let bls_pk_hex = format!("0x{bls_pk_hex}");
+
+    // Signed, not refused, but warned about
+    let resp =
+        mock_secure_sign_route_with_state(&bls_pk_hex, block_proposal_request(1), state.clone())
+            .await
+            .unwrap();
+    assert_eq!(resp.status_code(), 200);
+    let warnings = captured.warnings();
+    assert_eq!(warnings.len(), 1, "{warnings:?}");
+    assert!(warnings[0].contains("no slashing protection history"));
+    assert_eq!(state.empty_slash_history.count(), 1);
+    assert!(state
+        .empty_slash_history
+        .to_prometheus()
+        .contains("secure_signer_empty_slash_history_total 1"));
+
+    // Once the key has history it signs quietly
+    let resp =
+        mock_secure_sign_route_with_state(&bls_pk_hex, block_proposal_request(2), state.clone())
+            .await
+            .unwrap();
+    assert_eq!(resp.status_code(), 200);
+    assert_eq!(captured.warnings().len(), 1);
+    assert_eq!(state.empty_slash_history.count(), 1);
+}
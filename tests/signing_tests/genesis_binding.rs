@@ -0,0 +1,100 @@
+use crate::signing_tests::block::block_proposal_request;
+use crate::signing_tests::randao_reveal::randao_reveal_request;
+use crate::signing_tests::{sign_server, sign_state};
+use puffersecuresigner::crypto::bls_keys;
+use puffersecuresigner::enclave::shared::handlers::AppState;
+use puffersecuresigner::eth2::eth_signing::BLSSignMsg;
+use puffersecuresigner::eth2::slash_protection::{GenesisBinding, SlashingProtectionData};
+
+fn new_key() -> String {
+    let sk = bls_keys::new_bls_key(0);
+    bls_keys::save_bls_key(&sk).unwrap();
+    let pk_hex = sk.public_keys().public_key().to_hex();
+    SlashingProtectionData::from_pk_hex(&pk_hex)
+        .unwrap()
+        .write()
+        .unwrap();
+    pk_hex
+}
+
+fn on_other_network(mut req: BLSSignMsg) -> BLSSignMsg {
+    req.fork_info_mut().unwrap().genesis_validators_root = [0xbb; 32];
+    req
+}
+
+#[tokio::test]
+async fn test_key_is_bound_to_its_first_genesis_root() {
+    let server = sign_server(AppState {
+        bind_genesis_root: true,
+        ..sign_state()
+    });
+    let pk_hex = new_key();
+    let uri = format!("/api/v1/eth2/sign/0x{pk_hex}");
+
+    let req = block_proposal_request(100);
+    let root = req.genesis_validators_root().unwrap();
+    let resp = server.post(&uri).json(&req).await;
+    assert_eq!(resp.status_code(), 200);
+    assert_eq!(
+        GenesisBinding::read(&pk_hex).unwrap(),
+        Some(GenesisBinding {
+            genesis_validators_root: root
+        })
+    );
+
+    // The same key under another network's genesis root is refused, and nothing is recorded
+    let resp = server
+        .post(&uri)
+        .json(&on_other_network(block_proposal_request(101)))
+        .await;
+    assert_eq!(resp.status_code(), 412);
+    assert!(resp.text().contains(&hex::encode(root)));
+    let data = SlashingProtectionData::read(&pk_hex).unwrap();
+    assert_eq!(data.signed_blocks.len(), 1);
+
+    // The original network still signs
+    let resp = server.post(&uri).json(&block_proposal_request(101)).await;
+    assert_eq!(resp.status_code(), 200);
+}
+
+#[tokio::test]
+async fn test_keys_are_not_bound_unless_enabled() {
+    let server = sign_server(AppState {
+        bind_genesis_root: false,
+        ..sign_state()
+    });
+    let pk_hex = new_key();
+    let uri = format!("/api/v1/eth2/sign/0x{pk_hex}");
+
+    let resp = server.post(&uri).json(&block_proposal_request(100)).await;
+    assert_eq!(resp.status_code(), 200);
+    assert_eq!(GenesisBinding::read(&pk_hex).unwrap(), None);
+}
+
+#[tokio::test]
+async fn test_first_binding_of_unslashable_request_takes_key_lock() {
+    let pk_hex = new_key();
+    let key_locks = puffersecuresigner::enclave::shared::key_lock::KeyLocks::new(
+        std::time::Duration::from_millis(50),
+    );
+    let server = sign_server(AppState {
+        bind_genesis_root: true,
+        key_locks: key_locks.clone(),
+        ..sign_state()
+    });
+    let uri = format!("/api/v1/eth2/sign/0x{pk_hex}");
+
+    // A RANDAO reveal can't race another request to bind the key while that one holds the lock
+    let held = key_locks.lock(&pk_hex).await.unwrap();
+    let resp = server
+        .post(&uri)
+        .json(&on_other_network(randao_reveal_request()))
+        .await;
+    assert_eq!(resp.status_code(), 503);
+    assert_eq!(GenesisBinding::read(&pk_hex).unwrap(), None);
+
+    drop(held);
+    let resp = server.post(&uri).json(&randao_reveal_request()).await;
+    assert_eq!(resp.status_code(), 200);
+    assert!(GenesisBinding::read(&pk_hex).unwrap().is_some());
+}
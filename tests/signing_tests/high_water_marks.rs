@@ -0,0 +1,81 @@
+use crate::common::signing_helper::mock_secure_sign_route;
+use crate::signing_tests::attestation::attestation_req;
+use crate::signing_tests::block::block_proposal_request;
+use puffersecuresigner::crypto::bls_keys;
+use puffersecuresigner::eth2::slash_protection::{HighWaterMarks, SlashingProtectionData};
+
+fn marks_server() -> axum_test::TestServer {
+    let test_app = axum::Router::new()
+        .route(
+            "/eth/v1/slashing/:bls_pk_hex",
+            axum::routing::get(
+                puffersecuresigner::enclave::shared::handlers::high_water_marks::handler,
+            ),
+        )
+        .into_make_service();
+    axum_test::TestServer::new(test_app).unwrap()
+}
+
+async fn marks(server: &axum_test::TestServer, pk_hex: &str) -> HighWaterMarks {
+    let resp = server.get(&format!("/eth/v1/slashing/{pk_hex}")).await;
+    assert_eq!(resp.status_code(), 200);
+    resp.json()
+}
+
+#[tokio::test]
+async fn test_high_water_marks_advance_with_signing() {
+    let server = marks_server();
+    let sk = bls_keys::new_bls_key(0);
+    bls_keys::save_bls_key(&sk).unwrap();
+    let pk_hex = sk.public_keys().public_key().to_hex();
+    SlashingProtectionData::from_pk_hex(&pk_hex)
+        .unwrap()
+        .write()
+        .unwrap();
+    let pk_hex = format!("0x{pk_hex}");
+    assert_eq!(marks(&server, &pk_hex).await, HighWaterMarks::default());
+
+    let resp = mock_secure_sign_route(&pk_hex, block_proposal_request(100))
+        .await
+        .unwrap();
+    assert_eq!(resp.status_code(), 200);
+    let resp = mock_secure_sign_route(&pk_hex, attestation_req(1, 2))
+        .await
+        .unwrap();
+    assert_eq!(resp.status_code(), 200);
+    assert_eq!(
+        marks(&server, &pk_hex).await,
+        HighWaterMarks {
+            last_signed_block_slot: 100,
+            last_source_epoch: 1,
+            last_target_epoch: 2,
+        }
+    );
+
+    let resp = mock_secure_sign_route(&pk_hex, block_proposal_request(101))
+        .await
+        .unwrap();
+    assert_eq!(resp.status_code(), 200);
+    let resp = mock_secure_sign_route(&pk_hex, attestation_req(2, 3))
+        .await
+        .unwrap();
+    assert_eq!(resp.status_code(), 200);
+    let resp = server.get(&format!("/eth/v1/slashing/{pk_hex}")).await;
+    assert_eq!(
+        resp.json::<serde_json::Value>(),
+        serde_json::json!({
+            "last_signed_block_slot": "101",
+            "last_source_epoch": "2",
+            "last_target_epoch": "3"
+        })
+    );
+}
+
+#[tokio::test]
+async fn test_high_water_marks_of_key_without_protection_data_is_404() {
+    let pk_hex = bls_keys::new_bls_key(0).public_keys().public_key().to_hex();
+    let resp = marks_server()
+        .get(&format!("/eth/v1/slashing/0x{pk_hex}"))
+        .await;
+    assert_eq!(resp.status_code(), 404);
+}
@@ -0,0 +1,53 @@
+use crate::common::bls_keygen_helper::register_new_bls_key;
+use crate::common::signing_helper::mock_secure_sign_route;
+use crate::signing_tests::attestation::attestation_req;
+use crate::signing_tests::block::block_proposal_request;
+use puffersecuresigner::eth2::slash_protection::SlashingProtectionData;
+use puffersecuresigner::io::config::Config;
+use puffersecuresigner::strip_0x_prefix;
+
+#[tokio::test]
+async fn test_keygen_and_sign_in_memory() {
+    let _storage = Config::scoped_in_memory();
+    let bls_pk_hex = register_new_bls_key(None).await.pk_hex;
+    let pk_hex: String = strip_0x_prefix!(&bls_pk_hex);
+
+    // Nothing was written under the data dir
+    let config = Config::current();
+    assert!(config.storage.exists(&config.bls_keys_dir().join(&pk_hex)));
+    assert!(!config.bls_keys_dir().join(&pk_hex).exists());
+    assert!(!config.slashing_protection_dir().join(&pk_hex).exists());
+
+    let resp = mock_secure_sign_route(&bls_pk_hex, block_proposal_request(5))
+        .await
+        .unwrap();
+    assert_eq!(resp.status_code(), 200);
+    let resp = mock_secure_sign_route(&bls_pk_hex, attestation_req(1, 2))
+        .await
+        .unwrap();
+    assert_eq!(resp.status_code(), 200);
+
+    // Slashing protection is enforced from memory
+    let resp = mock_secure_sign_route(&bls_pk_hex, block_proposal_request(5))
+        .await
+        .unwrap();
+    assert_eq!(resp.status_code(), 412);
+    let data = SlashingProtectionData::read(&pk_hex).unwrap();
+    assert_eq!(data.high_water_marks(), (5, 1, 2));
+    assert!(!config.slashing_protection_dir().join(&pk_hex).exists());
+}
+
+#[tokio::test]
+async fn test_in_memory_signers_are_isolated() {
+    let bls_pk_hex = {
+        let _storage = Config::scoped_in_memory();
+        register_new_bls_key(None).await.pk_hex
+    };
+
+    // A fresh in-memory signer knows nothing of the previous one's keys
+    let _storage = Config::scoped_in_memory();
+    let resp = mock_secure_sign_route(&bls_pk_hex, block_proposal_request(5))
+        .await
+        .unwrap();
+    assert_eq!(resp.status_code(), 404);
+}
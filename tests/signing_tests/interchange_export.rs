@@ -0,0 +1,172 @@
+use puffersecuresigner::crypto::bls_keys;
+use puffersecuresigner::enclave::types::SlashProtectionImportResponse;
+use puffersecuresigner::eth2::slash_protection::{
+    SignedAttestationEpochs, SignedBlockSlot, SlashingProtectionDB, SlashingProtectionData,
+    SlashingProtectionImportStatus,
+};
+use puffersecuresigner::io::config::Config;
+
+fn interchange_server() -> axum_test::TestServer {
+    interchange_server_with(Default::default())
+}
+
+fn interchange_server_with(
+    state: puffersecuresigner::enclave::shared::handlers::AppState,
+) -> axum_test::TestServer {
+    axum_test::TestServer::new(
+        axum::Router::new()
+            .route(
+                "/eth/v1/slashing-protection",
+                axum::routing::post(
+                    puffersecuresigner::enclave::shared::handlers::import_slash_protection::handler,
+                )
+                .get(
+                    puffersecuresigner::enclave::shared::handlers::export_slash_protection::handler,
+                ),
+            )
+            .with_state(state)
+            .into_make_service(),
+    )
+    .unwrap()
+}
+
+/// Saves a key that signed blocks at slots 1, 5 and 3 and attestations at (0, 1), (1, 2) and
+/// (2, 4), returning its hex pubkey
+fn key_with_history(sk: &blsttc::SecretKeySet) -> String {
+    bls_keys::save_bls_key(sk).unwrap();
+    let pk_hex = sk.public_keys().public_key().to_hex();
+    let mut data = SlashingProtectionData::from_pk_hex(&pk_hex).unwrap();
+    for slot in [1, 5, 3] {
+        data.signed_blocks.push(SignedBlockSlot {
+            slot,
+            signing_root: None,
+        });
+    }
+    for (source_epoch, target_epoch) in [(0, 1), (1, 2), (2, 4)] {
+        data.signed_attestations.push(SignedAttestationEpochs {
+            source_epoch,
+            target_epoch,
+            signing_root: None,
+        });
+    }
+    data.write().unwrap();
+    pk_hex
+}
+
+async fn export(format: &str) -> SlashingProtectionDB {
+    let resp = interchange_server()
+        .get(&format!("/eth/v1/slashing-protection?format={format}"))
+        .await;
+    assert_eq!(resp.status_code(), 200);
+    resp.json()
+}
+
+/// Imports `db` into a signer that holds the key but never signed with it
+async fn import_into_fresh_signer(sk: &blsttc::SecretKeySet, db: &SlashingProtectionDB) {
+    let _data_dir = Config::scoped_temp().unwrap();
+    bls_keys::save_bls_key(sk).unwrap();
+    let pk_hex = sk.public_keys().public_key().to_hex();
+
+    let resp = interchange_server()
+        .post("/eth/v1/slashing-protection")
+        .json(db)
+        .await;
+    assert_eq!(resp.status_code(), 200);
+    let resp: SlashProtectionImportResponse = resp.json();
+    assert_eq!(resp.data.len(), 1);
+    assert_eq!(
+        resp.data[0].status,
+        SlashingProtectionImportStatus::Imported
+    );
+
+    // The target signer refuses anything at or below the exported high-water marks
+    let imported = SlashingProtectionData::read(&pk_hex).unwrap();
+    assert_eq!(imported.high_water_marks(), (5, 2, 4));
+    assert!(imported.is_slashable_block_slot(5));
+    assert!(imported.is_slashable_attestation_epochs(2, 4));
+}
+
+#[tokio::test]
+async fn test_complete_export_round_trips() {
+    let _data_dir = Config::scoped_temp().unwrap();
+    let sk = bls_keys::new_bls_key(0);
+    key_with_history(&sk);
+
+    let db = export("complete").await;
+    assert_eq!(db.data.len(), 1);
+    assert_eq!(db.data[0].signed_blocks.len(), 3);
+    assert_eq!(db.data[0].signed_attestations.len(), 3);
+
+    // Complete is the default
+    let default: SlashingProtectionDB = interchange_server()
+        .get("/eth/v1/slashing-protection")
+        .await
+        .json();
+    assert_eq!(default.data[0].signed_blocks.len(), 3);
+
+    import_into_fresh_signer(&sk, &db).await;
+}
+
+#[tokio::test]
+async fn test_minimal_export_round_trips() {
+    let _data_dir = Config::scoped_temp().unwrap();
+    let sk = bls_keys::new_bls_key(0);
+    key_with_history(&sk);
+
+    let db = export("minimal").await;
+    assert_eq!(db.data.len(), 1);
+    assert_eq!(db.data[0].signed_blocks.len(), 1);
+    assert_eq!(db.data[0].signed_attestations.len(), 1);
+    assert_eq!(db.data[0].high_water_marks(), (5, 2, 4));
+
+    import_into_fresh_signer(&sk, &db).await;
+}
+
+#[tokio::test]
+async fn test_export_rejects_unknown_format() {
+    let _data_dir = Config::scoped_temp().unwrap();
+    let resp = interchange_server()
+        .get("/eth/v1/slashing-protection?format=partial")
+        .await;
+    assert_eq!(resp.status_code(), 400);
+}
+
+#[tokio::test]
+async fn test_import_waits_for_the_key_lock() {
+    let _data_dir = Config::scoped_temp().unwrap();
+    let sk = bls_keys::new_bls_key(0);
+    let pk_hex = key_with_history(&sk);
+    let db = export("complete").await;
+    SlashingProtectionData::from_pk_hex(&pk_hex)
+        .unwrap()
+        .write()
+        .unwrap();
+
+    // A sign request holding the key's lock keeps the import from interleaving with its write
+    let state = puffersecuresigner::enclave::shared::handlers::AppState {
+        key_locks: puffersecuresigner::enclave::shared::key_lock::KeyLocks::new(
+            std::time::Duration::from_millis(10),
+        ),
+        ..Default::default()
+    };
+    let held = state.key_locks.lock(&pk_hex).await.unwrap();
+    let server = interchange_server_with(state);
+    let resp: SlashProtectionImportResponse = server
+        .post("/eth/v1/slashing-protection")
+        .json(&db)
+        .await
+        .json();
+    assert_eq!(resp.data[0].status, SlashingProtectionImportStatus::Error);
+    assert!(SlashingProtectionData::read(&pk_hex).unwrap().is_empty());
+
+    drop(held);
+    let resp: SlashProtectionImportResponse = server
+        .post("/eth/v1/slashing-protection")
+        .json(&db)
+        .await
+        .json();
+    assert_eq!(
+        resp.data[0].status,
+        SlashingProtectionImportStatus::Imported
+    );
+}
@@ -0,0 +1,148 @@
+use puffersecuresigner::crypto::bls_keys;
+use puffersecuresigner::enclave::shared::handlers::AppState;
+use puffersecuresigner::enclave::types::SlashProtectionImportResponse;
+use puffersecuresigner::eth2::slash_protection::{
+    SignedAttestationEpochs, SignedBlockSlot, SlashingProtectionDB, SlashingProtectionData,
+    SlashingProtectionImportStatus,
+};
+use puffersecuresigner::io::config::Config;
+use std::path::PathBuf;
+
+fn file_import_server(import_dir: Option<PathBuf>) -> axum_test::TestServer {
+    file_import_server_with(AppState {
+        slash_protection_import_dir: import_dir,
+        ..Default::default()
+    })
+}
+
+fn file_import_server_with(state: AppState) -> axum_test::TestServer {
+    axum_test::TestServer::new(
+        axum::Router::new()
+            .route(
+                "/eth/v1/slashing-protection/file",
+                axum::routing::post(
+                    puffersecuresigner::enclave::shared::handlers::import_slash_protection_file::handler,
+                ),
+            )
+            .with_state(state)
+            .into_make_service(),
+    )
+    .unwrap()
+}
+
+/// Writes an interchange for the key `pk_hex` that signed a block at slot 7 and an attestation
+/// at (2, 3) to `dir/name`
+fn write_interchange(dir: &std::path::Path, name: &str, pk_hex: &str) {
+    let mut data = SlashingProtectionData::from_pk_hex(pk_hex).unwrap();
+    data.signed_blocks.push(SignedBlockSlot {
+        slot: 7,
+        signing_root: None,
+    });
+    data.signed_attestations.push(SignedAttestationEpochs {
+        source_epoch: 2,
+        target_epoch: 3,
+        signing_root: None,
+    });
+    let mut db = SlashingProtectionDB::new();
+    db.data.push(data);
+    std::fs::create_dir_all(dir).unwrap();
+    std::fs::write(dir.join(name), serde_json::to_string(&db).unwrap()).unwrap();
+}
+
+#[tokio::test]
+async fn test_import_from_file_path() {
+    let _data_dir = Config::scoped_temp().unwrap();
+    let sk = bls_keys::new_bls_key(0);
+    bls_keys::save_bls_key(&sk).unwrap();
+    let pk_hex = sk.public_keys().public_key().to_hex();
+    let import_dir = Config::current().data_dir.join("imports");
+    write_interchange(&import_dir, "interchange.json", &pk_hex);
+
+    let resp = file_import_server(Some(import_dir.clone()))
+        .post("/eth/v1/slashing-protection/file")
+        .json(&serde_json::json!({"path": "interchange.json"}))
+        .await;
+    assert_eq!(resp.status_code(), 200);
+    let resp: SlashProtectionImportResponse = resp.json();
+    assert_eq!(resp.data.len(), 1);
+    assert_eq!(
+        resp.data[0].status,
+        SlashingProtectionImportStatus::Imported
+    );
+
+    let imported = SlashingProtectionData::read(&pk_hex).unwrap();
+    assert!(imported.is_slashable_block_slot(7));
+    assert!(imported.is_slashable_attestation_epochs(2, 3));
+
+    // An absolute path under the import dir works too
+    let resp = file_import_server(Some(import_dir.clone()))
+        .post("/eth/v1/slashing-protection/file")
+        .json(&serde_json::json!({"path": import_dir.join("interchange.json")}))
+        .await;
+    assert_eq!(resp.status_code(), 200);
+}
+
+#[tokio::test]
+async fn test_import_from_file_outside_import_dir_is_rejected() {
+    let _data_dir = Config::scoped_temp().unwrap();
+    let sk = bls_keys::new_bls_key(0);
+    bls_keys::save_bls_key(&sk).unwrap();
+    let pk_hex = sk.public_keys().public_key().to_hex();
+    let data_dir = Config::current().data_dir;
+    let import_dir = data_dir.join("imports");
+    std::fs::create_dir_all(&import_dir).unwrap();
+    write_interchange(&data_dir, "outside.json", &pk_hex);
+
+    for path in [
+        "../outside.json".to_string(),
+        data_dir.join("outside.json").to_string_lossy().to_string(),
+        "missing.json".to_string(),
+    ] {
+        let resp = file_import_server(Some(import_dir.clone()))
+            .post("/eth/v1/slashing-protection/file")
+            .json(&serde_json::json!({ "path": path }))
+            .await;
+        assert_eq!(resp.status_code(), 400, "{path}");
+    }
+
+    // Nothing was imported
+    assert!(!SlashingProtectionData::try_read(&pk_hex)
+        .unwrap()
+        .map_or(false, |data| data.is_slashable_block_slot(7)));
+}
+
+#[tokio::test]
+async fn test_import_from_file_is_disabled_without_import_dir() {
+    let _data_dir = Config::scoped_temp().unwrap();
+    let resp = file_import_server(None)
+        .post("/eth/v1/slashing-protection/file")
+        .json(&serde_json::json!({"path": "interchange.json"}))
+        .await;
+    assert_eq!(resp.status_code(), 403);
+}
+
+#[tokio::test]
+async fn test_import_from_file_waits_for_the_key_lock() {
+    let _data_dir = Config::scoped_temp().unwrap();
+    let sk = bls_keys::new_bls_key(0);
+    bls_keys::save_bls_key(&sk).unwrap();
+    let pk_hex = sk.public_keys().public_key().to_hex();
+    let import_dir = Config::current().data_dir.join("imports");
+    write_interchange(&import_dir, "interchange.json", &pk_hex);
+
+    let state = AppState {
+        slash_protection_import_dir: Some(import_dir),
+        key_locks: puffersecuresigner::enclave::shared::key_lock::KeyLocks::new(
+            std::time::Duration::from_millis(10),
+        ),
+        ..Default::default()
+    };
+    let _held = state.key_locks.lock(&pk_hex).await.unwrap();
+    let resp: SlashProtectionImportResponse = file_import_server_with(state)
+        .post("/eth/v1/slashing-protection/file")
+        .json(&serde_json::json!({"path": "interchange.json"}))
+        .await
+        .json();
+    assert_eq!(resp.data[0].status, SlashingProtectionImportStatus::Error);
+    assert!(SlashingProtectionData::try_read(&pk_hex).unwrap().is_none());
+}
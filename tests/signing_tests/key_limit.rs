@@ -0,0 +1,68 @@
+use crate::signing_tests::bls_import::{import_request, new_enclave_eth_key};
+use puffersecuresigner::enclave::shared::handlers::AppState;
+use puffersecuresigner::enclave::shared::key_limit::KeyLimit;
+use puffersecuresigner::enclave::types::{KeyImportResponse, KeystoreImportStatus};
+use puffersecuresigner::io::config::Config;
+
+const MAX_KEYS: usize = 3;
+
+fn limited_server() -> axum_test::TestServer {
+    let test_app = axum::Router::new()
+        .route(
+            "/eth/v1/keygen/bls",
+            axum::routing::post(
+                puffersecuresigner::enclave::secure_signer::handlers::bls_keygen::handler,
+            ),
+        )
+        .route(
+            "/eth/v1/keystores",
+            axum::routing::post(
+                puffersecuresigner::enclave::secure_signer::handlers::bls_import::handler,
+            ),
+        )
+        .with_state(AppState {
+            key_limit: Some(KeyLimit::new(MAX_KEYS)),
+            ..Default::default()
+        })
+        .into_make_service();
+    axum_test::TestServer::new(test_app).unwrap()
+}
+
+#[tokio::test]
+async fn test_imports_beyond_max_keys_are_rejected() {
+    let _data_dir = Config::scoped_temp().unwrap();
+    let server = limited_server();
+    let (eth_pk_hex, eth_pk) = new_enclave_eth_key();
+
+    let resp = server.post("/eth/v1/keygen/bls").await;
+    assert_eq!(resp.status_code(), 201);
+
+    // A failed import gives its room back
+    let (_, mut bad) = import_request("hunter2", &eth_pk, eth_pk_hex.clone());
+    bad.keystore = "{}".to_string();
+    let resp = server.post("/eth/v1/keystores").json(&bad).await;
+    assert_eq!(resp.status_code(), 400);
+
+    for _ in 1..MAX_KEYS {
+        let (_, req) = import_request("hunter2", &eth_pk, eth_pk_hex.clone());
+        let resp = server.post("/eth/v1/keystores").json(&req).await;
+        assert_eq!(resp.status_code(), 200);
+        let resp: KeyImportResponse = resp.json();
+        assert_eq!(resp.data[0].status, KeystoreImportStatus::Imported);
+    }
+
+    let (bls_pk_hex, req) = import_request("hunter2", &eth_pk, eth_pk_hex.clone());
+    let resp = server.post("/eth/v1/keystores").json(&req).await;
+    assert_eq!(resp.status_code(), 507);
+    assert!(!puffersecuresigner::io::key_management::bls_key_exists(
+        &bls_pk_hex
+    ));
+    let resp = server.post("/eth/v1/keygen/bls").await;
+    assert_eq!(resp.status_code(), 507);
+    assert_eq!(
+        puffersecuresigner::io::key_management::list_bls_keys()
+            .unwrap()
+            .len(),
+        MAX_KEYS
+    );
+}
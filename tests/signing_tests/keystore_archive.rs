@@ -0,0 +1,341 @@
+use crate::signing_tests::bls_import::new_enclave_eth_key;
+use puffersecuresigner::crypto::{bls_keys, eth_keys};
+use puffersecuresigner::enclave::types::{KeystoreArchiveImportResponse, KeystoreImportStatus};
+use puffersecuresigner::eth2::slash_protection::{
+    SlashingProtectionData, SlashingProtectionImportStatus,
+};
+
+fn archive_server(allow_keystore_archive_import: bool) -> axum_test::TestServer {
+    archive_server_with(puffersecuresigner::enclave::shared::handlers::AppState {
+        allow_keystore_archive_import,
+        ..Default::default()
+    })
+}
+
+fn archive_server_with(
+    state: puffersecuresigner::enclave::shared::handlers::AppState,
+) -> axum_test::TestServer {
+    let test_app = axum::Router::new()
+        .route(
+            "/eth/v1/keystores/archive",
+            axum::routing::post(
+                puffersecuresigner::enclave::secure_signer::handlers::keystore_archive_import::handler,
+            ),
+        )
+        .with_state(state)
+        .into_make_service();
+    axum_test::TestServer::new(test_app).unwrap()
+}
+
+/// Encrypts a fresh BLS key into an EIP-2335 keystore, returning its pubkey and keystore JSON
+pub fn new_keystore(password: &str) -> (String, String) {
+    let sk = bls_keys::new_bls_key(0);
+    let dir = std::env::temp_dir().join(format!(
+        "keystores-{}",
+        hex::encode(rand::random::<[u8; 8]>())
+    ));
+    std::fs::create_dir_all(&dir).unwrap();
+    eth_keystore::encrypt_key(
+        &dir,
+        &mut rand::thread_rng(),
+        sk.secret_key().to_bytes(),
+        password,
+        Some("keystore"),
+    )
+    .unwrap();
+    let keystore = std::fs::read_to_string(dir.join("keystore")).unwrap();
+    std::fs::remove_dir_all(&dir).unwrap();
+    (sk.public_keys().public_key().to_hex(), keystore)
+}
+
+/// Encrypts an archive password or MAC secret to the enclave ETH key `pk`, in hex
+fn encrypt_to(pk: &ecies::PublicKey, secret: &[u8]) -> String {
+    hex::encode(eth_keys::envelope_encrypt(pk, secret).unwrap())
+}
+
+fn archive_url(eth_pk_hex: &str) -> String {
+    format!("/eth/v1/keystores/archive?encrypting_pk_hex={eth_pk_hex}")
+}
+
+fn tar_of(files: &[(&str, &str)]) -> Vec<u8> {
+    let mut builder = tar::Builder::new(Vec::new());
+    for (path, contents) in files {
+        let mut header = tar::Header::new_gnu();
+        header.set_size(contents.len() as u64);
+        header.set_mode(0o644);
+        header.set_cksum();
+        builder
+            .append_data(&mut header, path, contents.as_bytes())
+            .unwrap();
+    }
+    builder.into_inner().unwrap()
+}
+
+#[tokio::test]
+async fn test_import_archive_error_messages() {
+    let (eth_pk_hex, eth_pk) = new_enclave_eth_key();
+    let server = archive_server(true);
+    let (_, keystore) = new_keystore("password");
+    let mut unsupported_kdf: serde_json::Value = serde_json::from_str(&keystore).unwrap();
+    unsupported_kdf["crypto"]["kdf"]["function"] = "bcrypt".into();
+    let mut wrong_pubkey: serde_json::Value = serde_json::from_str(&keystore).unwrap();
+    let (other_pk, _) = new_keystore("password");
+    wrong_pubkey["pubkey"] = other_pk.clone().into();
+    let (_, other_eth_pk) = eth_keys::new_eth_key().unwrap();
+    let passwords = serde_json::json!({
+        "malformed.json": encrypt_to(&eth_pk, b"password"),
+        "unsupported-kdf.json": encrypt_to(&eth_pk, b"password"),
+        "wrong-password.json": encrypt_to(&eth_pk, b"not-the-password"),
+        "wrong-pubkey.json": encrypt_to(&eth_pk, b"password"),
+        "wrong-encrypting-key.json": encrypt_to(&other_eth_pk, b"password"),
+        "plaintext-password.json": "password",
+    })
+    .to_string();
+    let archive = tar_of(&[
+        ("malformed.json", "{\"crypto\": "),
+        ("no-password.json", &keystore),
+        ("unsupported-kdf.json", &unsupported_kdf.to_string()),
+        ("wrong-password.json", &keystore),
+        ("wrong-pubkey.json", &wrong_pubkey.to_string()),
+        ("wrong-encrypting-key.json", &keystore),
+        ("plaintext-password.json", &keystore),
+        ("passwords.json", &passwords),
+    ]);
+
+    let resp: KeystoreArchiveImportResponse = server
+        .post(&archive_url(&eth_pk_hex))
+        .bytes(archive.into())
+        .await
+        .json();
+    let message = |file: &str| {
+        let result = resp.data.iter().find(|r| r.file == file).unwrap();
+        assert_eq!(result.status, KeystoreImportStatus::Error);
+        result.message.clone().unwrap()
+    };
+    assert!(message("malformed.json").starts_with("Keystore is not EIP-2335 JSON: "));
+    assert_eq!(
+        message("no-password.json"),
+        "No password for this keystore in passwords.json"
+    );
+    assert_eq!(
+        message("unsupported-kdf.json"),
+        "Unsupported keystore kdf \"bcrypt\""
+    );
+    assert_eq!(
+        message("wrong-password.json"),
+        "Failed to decrypt keystore, bad password"
+    );
+    assert!(message("wrong-pubkey.json").starts_with(&format!(
+        "Keystore pubkey 0x{other_pk} does not match the decrypted key"
+    )));
+    assert_eq!(
+        message("wrong-encrypting-key.json"),
+        "Failed to decrypt keystore password"
+    );
+    assert_eq!(
+        message("plaintext-password.json"),
+        "Failed to decrypt keystore password"
+    );
+}
+
+pub fn interchange_entry(pk_hex: &str, slot: u64, src: u64, tgt: u64) -> String {
+    format!(
+        r#"{{
+            "pubkey": "0x{pk_hex}",
+            "signed_blocks": [{{ "slot": "{slot}" }}],
+            "signed_attestations": [{{ "source_epoch": "{src}", "target_epoch": "{tgt}" }}]
+        }}"#
+    )
+}
+
+#[tokio::test]
+async fn test_import_archive_of_two_keystores() {
+    let (eth_pk_hex, eth_pk) = new_enclave_eth_key();
+    let server = archive_server(true);
+    let (pk_0, keystore_0) = new_keystore("password-0");
+    let (pk_1, keystore_1) = new_keystore("password-1");
+
+    let passwords = serde_json::json!({
+        "keystore-0.json": encrypt_to(&eth_pk, b"password-0"),
+        "keystore-1.json": encrypt_to(&eth_pk, b"password-1"),
+    })
+    .to_string();
+    let interchange = format!(
+        r#"{{
+            "metadata": {{
+                "interchange_format_version": "5",
+                "genesis_validators_root": "0x04700007fabc8282644aed6d1c7c9e21d38a03a0c4ba193f3afe428824b3a673"
+            }},
+            "data": [{}, {}]
+        }}"#,
+        interchange_entry(&pk_0, 100, 10, 20),
+        interchange_entry(&pk_1, 7, 1, 2)
+    );
+    let archive = tar_of(&[
+        ("validator_keys/keystore-0.json", &keystore_0),
+        ("validator_keys/keystore-1.json", &keystore_1),
+        ("passwords.json", &passwords),
+        ("slashing_protection.json", &interchange),
+    ]);
+
+    let resp = server
+        .post(&archive_url(&eth_pk_hex))
+        .bytes(archive.into())
+        .await;
+    assert_eq!(resp.status_code(), 200);
+    let resp: KeystoreArchiveImportResponse = resp.json();
+
+    assert_eq!(resp.data.len(), 2);
+    assert_eq!(resp.data[0].file, "keystore-0.json");
+    assert_eq!(resp.data[0].pubkey, Some(format!("0x{pk_0}")));
+    assert_eq!(resp.data[0].status, KeystoreImportStatus::Imported);
+    assert_eq!(resp.data[1].file, "keystore-1.json");
+    assert_eq!(resp.data[1].pubkey, Some(format!("0x{pk_1}")));
+    assert_eq!(resp.data[1].status, KeystoreImportStatus::Imported);
+
+    assert_eq!(resp.slashing_protection.len(), 2);
+    assert!(resp
+        .slashing_protection
+        .iter()
+        .all(|r| r.status == SlashingProtectionImportStatus::Imported));
+
+    // Both keys can sign and carry the interchange's history
+    assert!(bls_keys::fetch_bls_sk(&pk_0).is_ok());
+    assert!(bls_keys::fetch_bls_sk(&pk_1).is_ok());
+    assert_eq!(
+        SlashingProtectionData::read(&pk_0)
+            .unwrap()
+            .high_water_marks(),
+        (100, 10, 20)
+    );
+    assert_eq!(
+        SlashingProtectionData::read(&pk_1)
+            .unwrap()
+            .high_water_marks(),
+        (7, 1, 2)
+    );
+}
+
+#[tokio::test]
+async fn test_import_archive_reports_per_file_errors() {
+    let (eth_pk_hex, eth_pk) = new_enclave_eth_key();
+    let server = archive_server(true);
+    let (pk, keystore) = new_keystore("password");
+    let passwords = serde_json::json!({
+        "keystore.json": encrypt_to(&eth_pk, b"password"),
+        "wrong-password.json": encrypt_to(&eth_pk, b"not-the-password"),
+    })
+    .to_string();
+    let archive = tar_of(&[
+        ("keystore.json", &keystore),
+        ("wrong-password.json", &keystore),
+        ("no-password.json", &keystore),
+        ("passwords.json", &passwords),
+    ]);
+
+    let resp = server
+        .post(&archive_url(&eth_pk_hex))
+        .bytes(archive.into())
+        .await;
+    assert_eq!(resp.status_code(), 200);
+    let resp: KeystoreArchiveImportResponse = resp.json();
+    assert_eq!(resp.data.len(), 3);
+    assert_eq!(resp.data[0].status, KeystoreImportStatus::Imported);
+    assert_eq!(resp.data[0].pubkey, Some(format!("0x{pk}")));
+    assert_eq!(resp.data[0].message, None);
+    assert_eq!(resp.data[1].status, KeystoreImportStatus::Error);
+    assert!(resp.data[1].message.is_some());
+    assert_eq!(resp.data[2].status, KeystoreImportStatus::Error);
+    assert!(resp.slashing_protection.is_empty());
+
+    // Importing the same keystore again leaves the saved key alone
+    let archive = tar_of(&[("keystore.json", &keystore), ("passwords.json", &passwords)]);
+    let resp: KeystoreArchiveImportResponse = server
+        .post(&archive_url(&eth_pk_hex))
+        .bytes(archive.into())
+        .await
+        .json();
+    assert_eq!(resp.data[0].status, KeystoreImportStatus::Duplicate);
+}
+
+#[tokio::test]
+async fn test_import_archive_is_gated() {
+    let (eth_pk_hex, _) = new_enclave_eth_key();
+    let server = archive_server(false);
+    let resp = server
+        .post(&archive_url(&eth_pk_hex))
+        .bytes(tar_of(&[]).into())
+        .await;
+    assert_eq!(resp.status_code(), 403);
+
+    let server = archive_server(true);
+    let resp = server
+        .post(&archive_url(&eth_pk_hex))
+        .bytes(b"not an archive".to_vec().into())
+        .await;
+    assert_eq!(resp.status_code(), 400);
+
+    // Secrets encrypted to a key the enclave doesn't hold can't be opened
+    let (_, unknown_pk) = eth_keys::new_eth_key().unwrap();
+    let resp = server
+        .post(&archive_url(&eth_keys::eth_pk_to_hex(&unknown_pk)))
+        .bytes(tar_of(&[]).into())
+        .await;
+    assert_eq!(resp.status_code(), 400);
+}
+
+#[tokio::test]
+async fn test_import_archive_requires_slashing_protection_when_strict() {
+    let server = archive_server_with(puffersecuresigner::enclave::shared::handlers::AppState {
+        allow_keystore_archive_import: true,
+        require_import_slashing_protection: true,
+        ..Default::default()
+    });
+    let (eth_pk_hex, eth_pk) = new_enclave_eth_key();
+    let (pk_0, keystore_0) = new_keystore("password");
+    let (pk_1, keystore_1) = new_keystore("password");
+    let passwords = serde_json::json!({
+        "keystore-0.json": encrypt_to(&eth_pk, b"password"),
+        "keystore-1.json": encrypt_to(&eth_pk, b"password"),
+    })
+    .to_string();
+    let interchange = format!(
+        r#"{{
+            "metadata": {{
+                "interchange_format_version": "5",
+                "genesis_validators_root": "0x04700007fabc8282644aed6d1c7c9e21d38a03a0c4ba193f3afe428824b3a673"
+            }},
+            "data": [{}]
+        }}"#,
+        interchange_entry(&pk_0, 100, 10, 20)
+    );
+    let archive = tar_of(&[
+        ("keystore-0.json", &keystore_0),
+        ("keystore-1.json", &keystore_1),
+        ("passwords.json", &passwords),
+        ("slashing_protection.json", &interchange),
+    ]);
+
+    let resp = server
+        .post(&archive_url(&eth_pk_hex))
+        .bytes(archive.into())
+        .await;
+    assert_eq!(resp.status_code(), 200);
+    let resp: KeystoreArchiveImportResponse = resp.json();
+    assert_eq!(resp.data[0].status, KeystoreImportStatus::Imported);
+    assert_eq!(resp.data[1].status, KeystoreImportStatus::Error);
+    assert_eq!(
+        resp.data[1].message,
+        Some(format!("no slashing protection history for 0x{pk_1}"))
+    );
+
+    // Only the key with history was saved
+    assert!(bls_keys::fetch_bls_sk(&pk_0).is_ok());
+    assert!(bls_keys::fetch_bls_sk(&pk_1).is_err());
+    assert_eq!(
+        SlashingProtectionData::read(&pk_0)
+            .unwrap()
+            .high_water_marks(),
+        (100, 10, 20)
+    );
+}
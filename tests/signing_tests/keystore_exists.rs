@@ -0,0 +1,43 @@
+use puffersecuresigner::crypto::bls_keys;
+use puffersecuresigner::io::config::Config;
+
+fn exists_server() -> axum_test::TestServer {
+    let test_app = axum::Router::new()
+        .route(
+            "/eth/v1/keystores/:bls_pk_hex",
+            axum::routing::get(
+                puffersecuresigner::enclave::shared::handlers::keystore_exists::handler,
+            ),
+        )
+        .into_make_service();
+    axum_test::TestServer::new(test_app).unwrap()
+}
+
+#[tokio::test]
+async fn test_saved_key_is_found() {
+    let _data_dir = Config::scoped_temp().unwrap();
+    let server = exists_server();
+    let sk = bls_keys::new_bls_key(0);
+    bls_keys::save_bls_key(&sk).unwrap();
+    let pk_hex = sk.public_keys().public_key().to_hex();
+
+    let resp = server.get(&format!("/eth/v1/keystores/0x{pk_hex}")).await;
+    assert_eq!(resp.status_code(), 200);
+    let resp = server.get(&format!("/eth/v1/keystores/{pk_hex}")).await;
+    assert_eq!(resp.status_code(), 200);
+}
+
+#[tokio::test]
+async fn test_unknown_key_is_not_found() {
+    let _data_dir = Config::scoped_temp().unwrap();
+    let server = exists_server();
+    bls_keys::save_bls_key(&bls_keys::new_bls_key(0)).unwrap();
+    let unknown = bls_keys::new_bls_key(0).public_keys().public_key().to_hex();
+
+    let resp = server.get(&format!("/eth/v1/keystores/0x{unknown}")).await;
+    assert_eq!(resp.status_code(), 404);
+
+    // Malformed pubkeys are refused before any file is looked up
+    let resp = server.get("/eth/v1/keystores/0x1234").await;
+    assert_eq!(resp.status_code(), 400);
+}
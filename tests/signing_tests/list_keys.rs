@@ -0,0 +1,237 @@
+use puffersecuresigner::crypto::bls_keys;
+use puffersecuresigner::enclave::types::ListKeystoresResponse;
+use puffersecuresigner::io::config::Config;
+
+fn list_server() -> axum_test::TestServer {
+    let test_app = axum::Router::new()
+        .route(
+            "/eth/v1/keystores",
+            axum::routing::get(
+                puffersecuresigner::enclave::shared::handlers::list_bls_keys::handler,
+            ),
+        )
+        .into_make_service();
+    axum_test::TestServer::new(test_app).unwrap()
+}
+
+async fn list(server: &axum_test::TestServer, query: &str) -> ListKeystoresResponse {
+    let resp = server.get(&format!("/eth/v1/keystores{query}")).await;
+    assert_eq!(resp.status_code(), 200);
+    serde_json::from_slice(resp.as_bytes()).unwrap()
+}
+
+fn pubkeys(resp: &ListKeystoresResponse) -> Vec<String> {
+    resp.data
+        .iter()
+        .map(|k| k.validating_pubkey.clone())
+        .collect()
+}
+
+#[tokio::test]
+async fn test_list_keys_pages_in_pubkey_order() {
+    let _data_dir = Config::scoped_temp().unwrap();
+    let server = list_server();
+    for _ in 0..5 {
+        bls_keys::save_bls_key(&bls_keys::new_bls_key(0)).unwrap();
+    }
+
+    let all = list(&server, "").await;
+    assert_eq!(all.data.len(), 5);
+    assert_eq!(all.total, None);
+    let mut sorted = pubkeys(&all);
+    sorted.sort();
+    assert_eq!(pubkeys(&all), sorted);
+
+    let first = list(&server, "?limit=2").await;
+    assert_eq!(pubkeys(&first), sorted[0..2].to_vec());
+    assert_eq!(first.total, Some(5));
+    assert_eq!(first.next_offset, Some(2));
+
+    let second = list(&server, "?limit=2&offset=2").await;
+    assert_eq!(pubkeys(&second), sorted[2..4].to_vec());
+    assert_eq!(second.next_offset, Some(4));
+
+    // The last page is short and has no next page
+    let last = list(&server, "?limit=2&offset=4").await;
+    assert_eq!(pubkeys(&last), sorted[4..5].to_vec());
+    assert_eq!(last.next_offset, None);
+
+    // A page that exactly ends the listing has no next page either
+    let exact = list(&server, "?limit=5").await;
+    assert_eq!(pubkeys(&exact), sorted);
+    assert_eq!(exact.next_offset, None);
+
+    // An offset alone returns the rest of the keys
+    let rest = list(&server, "?offset=3").await;
+    assert_eq!(pubkeys(&rest), sorted[3..5].to_vec());
+
+    // Pages past the end are empty
+    let past = list(&server, "?limit=2&offset=10").await;
+    assert!(past.data.is_empty());
+    assert_eq!(past.total, Some(5));
+    assert_eq!(past.next_offset, None);
+
+    let resp = server.get("/eth/v1/keystores?limit=-1").await;
+    assert_eq!(resp.status_code(), 400);
+}
+
+#[tokio::test]
+async fn test_list_keys_on_empty_data_dir() {
+    let _data_dir = Config::scoped_temp().unwrap();
+    let server = list_server();
+
+    let all = list(&server, "").await;
+    assert!(all.data.is_empty());
+
+    let page = list(&server, "?limit=10").await;
+    assert!(page.data.is_empty());
+    assert_eq!(page.total, Some(0));
+    assert_eq!(page.next_offset, None);
+}
+
+#[tokio::test]
+async fn test_list_keys_matches_keymanager_api() {
+    let _data_dir = Config::scoped_temp().unwrap();
+    let sk = bls_keys::new_bls_key(0);
+    bls_keys::save_bls_key(&sk).unwrap();
+    let pk_hex = sk.public_keys().public_key().to_hex();
+
+    // As the keymanager OpenAPI examples, every key in the enclave is read-only
+    let resp = list_server().get("/eth/v1/keystores").await;
+    let resp: serde_json::Value = resp.json();
+    assert_eq!(
+        resp,
+        serde_json::json!({
+            "data": [{ "validating_pubkey": format!("0x{pk_hex}"), "readonly": true }]
+        })
+    );
+
+    // All keys are held here, none remotely
+    let server = axum_test::TestServer::new(
+        axum::Router::new()
+            .route(
+                "/eth/v1/remotekeys",
+                axum::routing::get(
+                    puffersecuresigner::enclave::shared::handlers::list_remote_keys::handler,
+                ),
+            )
+            .into_make_service(),
+    )
+    .unwrap();
+    let resp = server.get("/eth/v1/remotekeys").await;
+    assert_eq!(resp.status_code(), 200);
+    let resp: serde_json::Value = resp.json();
+    assert_eq!(resp, serde_json::json!({ "data": [] }));
+}
+
+#[tokio::test]
+async fn test_public_keys_lists_generated_and_imported_keys() {
+    let _data_dir = Config::scoped_temp().unwrap();
+    let server = axum_test::TestServer::new(
+        axum::Router::new()
+            .route(
+                "/api/v1/eth2/publicKeys",
+                axum::routing::get(
+                    puffersecuresigner::enclave::shared::handlers::list_public_keys::handler,
+                ),
+            )
+            .into_make_service(),
+    )
+    .unwrap();
+
+    let sk = bls_keys::new_bls_key(0);
+    bls_keys::save_bls_key(&sk).unwrap();
+    let generated = sk.public_keys().public_key().to_hex();
+    let (imported, keystore) = crate::signing_tests::keystore_archive::new_keystore("hunter2");
+    let (eth_sk, eth_pk) = puffersecuresigner::crypto::eth_keys::new_eth_key().unwrap();
+    let ct_password =
+        puffersecuresigner::crypto::eth_keys::envelope_encrypt(&eth_pk, b"hunter2").unwrap();
+    let archive = puffersecuresigner::io::keystore_archive::KeystoreArchive {
+        keystores: vec![("keystore".to_string(), keystore)],
+        passwords: [("keystore".to_string(), hex::encode(ct_password))].into(),
+        ..Default::default()
+    };
+    puffersecuresigner::enclave::secure_signer::import_keystore_archive(
+        &archive, &eth_sk, None, None, false,
+    )
+    .unwrap();
+    // A stray file in the keys dir is not a key that can be signed with
+    std::fs::write(Config::current().bls_keys_dir().join("README"), "").unwrap();
+
+    let resp = server.get("/api/v1/eth2/publicKeys").await;
+    assert_eq!(resp.status_code(), 200);
+    let mut keys: Vec<String> = resp.json();
+    keys.sort();
+    let mut expected = vec![format!("0x{generated}"), format!("0x{imported}")];
+    expected.sort();
+    assert_eq!(keys, expected);
+}
+
+#[tokio::test]
+async fn test_list_keys_filtered_by_origin() {
+    let _data_dir = Config::scoped_temp().unwrap();
+    let server = axum_test::TestServer::new(
+        axum::Router::new()
+            .route(
+                "/eth/v1/keystores",
+                axum::routing::get(
+                    puffersecuresigner::enclave::shared::handlers::list_bls_keys::handler,
+                ),
+            )
+            .route(
+                "/eth/v1/keygen/bls",
+                axum::routing::post(
+                    puffersecuresigner::enclave::secure_signer::handlers::bls_keygen::handler,
+                ),
+            )
+            .with_state(puffersecuresigner::enclave::shared::handlers::AppState::default())
+            .into_make_service(),
+    )
+    .unwrap();
+
+    let resp = server.post("/eth/v1/keygen/bls").await;
+    assert_eq!(resp.status_code(), 201);
+    let generated: puffersecuresigner::enclave::types::KeyGenResponse = resp.json();
+    let (imported, keystore) = crate::signing_tests::keystore_archive::new_keystore("hunter2");
+    let (eth_sk, eth_pk) = puffersecuresigner::crypto::eth_keys::new_eth_key().unwrap();
+    let ct_password =
+        puffersecuresigner::crypto::eth_keys::envelope_encrypt(&eth_pk, b"hunter2").unwrap();
+    let archive = puffersecuresigner::io::keystore_archive::KeystoreArchive {
+        keystores: vec![("keystore".to_string(), keystore)],
+        passwords: [("keystore".to_string(), hex::encode(ct_password))].into(),
+        ..Default::default()
+    };
+    puffersecuresigner::enclave::secure_signer::import_keystore_archive(
+        &archive, &eth_sk, None, None, false,
+    )
+    .unwrap();
+    // Saved without recording where it came from, like keys saved by older versions
+    let sk = bls_keys::new_bls_key(0);
+    bls_keys::save_bls_key(&sk).unwrap();
+    let unknown = sk.public_keys().public_key().to_hex();
+
+    let mut expected = vec![
+        generated.pk_hex.clone(),
+        format!("0x{imported}"),
+        format!("0x{unknown}"),
+    ];
+    expected.sort();
+    assert_eq!(pubkeys(&list(&server, "").await), expected);
+
+    assert_eq!(
+        pubkeys(&list(&server, "?origin=generated").await),
+        vec![generated.pk_hex]
+    );
+    assert_eq!(
+        pubkeys(&list(&server, "?origin=imported").await),
+        vec![format!("0x{imported}")]
+    );
+
+    // Filtering applies before paging
+    let page = list(&server, "?origin=imported&limit=1").await;
+    assert_eq!(page.total, Some(1));
+    assert_eq!(page.next_offset, None);
+
+    let resp = server.get("/eth/v1/keystores?origin=stolen").await;
+    assert_eq!(resp.status_code(), 400);
+}
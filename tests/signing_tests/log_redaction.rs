@@ -0,0 +1,100 @@
+use crate::common;
+use crate::signing_tests::randao_reveal::randao_reveal_request;
+use puffersecuresigner::enclave::shared::logging::RedactPubkeys;
+use puffersecuresigner::eth2::eth_types::GENESIS_FORK_VERSION;
+use puffersecuresigner::strip_0x_prefix;
+use std::sync::{Arc, Mutex};
+
+/// Collects everything the subscriber writes
+#[derive(Clone, Default)]
+struct Captured(Arc<Mutex<Vec<u8>>>);
+
+impl std::io::Write for Captured {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        self.0.lock().unwrap().extend_from_slice(buf);
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        Ok(())
+    }
+}
+
+#[tokio::test]
+async fn test_pubkeys_are_redacted_from_handler_logs() {
+    // The handlers log through the `log` crate, forward it to the subscriber set below
+    tracing_log::LogTracer::init().ok();
+    let captured = Captured::default();
+    let writer = captured.clone();
+    let subscriber = tracing_subscriber::fmt()
+        .with_ansi(false)
+        .with_writer(RedactPubkeys(move || writer.clone()))
+        .finish();
+    // The test server runs on this thread, so its handlers log through this subscriber
+    let _guard = tracing::subscriber::set_default(subscriber);
+
+    let test_app = axum::Router::new()
+        .route(
+            "/api/v1/eth2/sign/:bls_pk_hex",
+            axum::routing::post(
+                puffersecuresigner::enclave::shared::handlers::secure_sign_bls::handler,
+            ),
+        )
+        .route(
+            "/api/v1/eth2/deposit",
+            axum::routing::post(
+                puffersecuresigner::enclave::secure_signer::handlers::validator_deposit::handler,
+            ),
+        )
+        .layer(axum::middleware::from_fn(
+            puffersecuresigner::enclave::shared::request_log::middleware,
+        ))
+        .with_state(puffersecuresigner::enclave::shared::handlers::AppState {
+            genesis_fork_version: GENESIS_FORK_VERSION,
+            ..Default::default()
+        })
+        .into_make_service();
+    let server = axum_test::TestServer::new(test_app).unwrap();
+
+    // A known key that signs, and an unknown one that is logged as such
+    let bls_pk_hex = common::setup_dummy_keypair();
+    let resp = server
+        .post(&format!("/api/v1/eth2/sign/{bls_pk_hex}"))
+        .json(&randao_reveal_request())
+        .await;
+    assert_eq!(resp.status_code(), 200);
+    let unknown_pk_hex = puffersecuresigner::crypto::bls_keys::new_bls_key(0)
+        .public_keys()
+        .public_key()
+        .to_hex();
+    let resp = server
+        .post(&format!("/api/v1/eth2/sign/0x{unknown_pk_hex}"))
+        .json(&randao_reveal_request())
+        .await;
+    assert_eq!(resp.status_code(), 404);
+
+    // The deposit route logs the whole request, pubkey included
+    let resp = server
+        .post("/api/v1/eth2/deposit")
+        .json(&serde_json::json!({
+            "genesis_fork_version": "0x00001020",
+            "deposit": {
+                "pubkey": bls_pk_hex,
+                "withdrawal_credentials": "0x75362a41a82133d71eee01e602ad564c73590557bb7c994cf9be5620d2023a58",
+                "amount": "32000000000"
+            }
+        }))
+        .await;
+    assert_eq!(resp.status_code(), 200);
+
+    let logs = String::from_utf8(captured.0.lock().unwrap().clone())
+        .unwrap()
+        .to_lowercase();
+    let bls_pk_hex: String = strip_0x_prefix!(bls_pk_hex);
+    for pk_hex in [bls_pk_hex, unknown_pk_hex] {
+        assert!(logs.contains(&format!("{}…", &pk_hex[..8])), "{logs}");
+        assert!(!logs.contains(&pk_hex), "{logs}");
+    }
+    // Nor does the pubkey slip through as a list of bytes
+    assert!(!logs.contains("fixedvector"), "{logs}");
+}
@@ -0,0 +1,82 @@
+use crate::common;
+use crate::signing_tests::randao_reveal::mock_randao_reveal_request;
+use puffersecuresigner::enclave::shared::metrics::MetricsLabels;
+use puffersecuresigner::eth2::eth_types::GENESIS_FORK_VERSION;
+
+fn labels_server(metrics_labels: MetricsLabels) -> axum_test::TestServer {
+    axum_test::TestServer::new(
+        axum::Router::new()
+            .route(
+                "/api/v1/eth2/sign/:bls_pk_hex",
+                axum::routing::post(
+                    puffersecuresigner::enclave::shared::handlers::secure_sign_bls::handler,
+                ),
+            )
+            .route(
+                "/eth/v1/keystores/:bls_pk_hex/metrics-label",
+                axum::routing::post(
+                    puffersecuresigner::enclave::shared::handlers::metrics_label::handler,
+                ),
+            )
+            .route(
+                "/metrics",
+                axum::routing::get(puffersecuresigner::enclave::shared::handlers::metrics::handler),
+            )
+            .with_state(puffersecuresigner::enclave::shared::handlers::AppState {
+                genesis_fork_version: GENESIS_FORK_VERSION,
+                metrics_labels,
+                ..Default::default()
+            })
+            .into_make_service(),
+    )
+    .unwrap()
+}
+
+#[tokio::test]
+async fn test_signing_metrics_carry_the_key_label() {
+    let _data_dir = puffersecuresigner::io::config::Config::scoped_temp().unwrap();
+    let bls_pk_hex = common::setup_dummy_keypair();
+    let server = labels_server(MetricsLabels::parse("pool-a,pool-b").unwrap());
+    let label_uri = format!("/eth/v1/keystores/{bls_pk_hex}/metrics-label");
+
+    // Only configured labels can be set
+    let resp = server
+        .post(&label_uri)
+        .json(&serde_json::json!({"label": "pool-c"}))
+        .await;
+    assert_eq!(resp.status_code(), 400);
+    let resp = server
+        .post(&label_uri)
+        .json(&serde_json::json!({"label": "pool-a"}))
+        .await;
+    assert_eq!(resp.status_code(), 200);
+
+    let randao = serde_json::from_str::<serde_json::Value>(&mock_randao_reveal_request()).unwrap();
+    let resp = server
+        .post(&format!("/api/v1/eth2/sign/{bls_pk_hex}"))
+        .json(&randao)
+        .await;
+    assert_eq!(resp.status_code(), 200);
+
+    let metrics = server.get("/metrics").await.text();
+    assert!(metrics.contains("secure_signer_requests_total{label=\"pool-a\"} 1\n"));
+    assert!(metrics.contains("secure_signer_requests_total{label=\"pool-b\"} 0\n"));
+}
+
+#[tokio::test]
+async fn test_metrics_labels_are_disabled_by_default() {
+    let _data_dir = puffersecuresigner::io::config::Config::scoped_temp().unwrap();
+    let bls_pk_hex = common::setup_dummy_keypair();
+    let server = labels_server(MetricsLabels::default());
+
+    let resp = server
+        .post(&format!("/eth/v1/keystores/{bls_pk_hex}/metrics-label"))
+        .json(&serde_json::json!({"label": "pool-a"}))
+        .await;
+    assert_eq!(resp.status_code(), 404);
+    assert!(!server
+        .get("/metrics")
+        .await
+        .text()
+        .contains("secure_signer_requests_total"));
+}
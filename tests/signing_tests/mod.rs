@@ -1,11 +1,81 @@
+//! Test servers run on the test's thread, so they see only the keys and state saved under the
+//! test's `Config::scoped_temp()` data dir.
+
 pub mod aggregate_and_proof;
+pub mod aggregate_signatures;
 pub mod aggregation_slot;
+pub mod allowed_domains;
 pub mod attestation;
 pub mod block;
 pub mod block_v2;
+pub mod bls_export;
+pub mod bls_import;
+pub mod bls_to_execution_change;
+pub mod config;
 pub mod contribution_and_proof;
+pub mod cors;
 pub mod deposit;
+pub mod dry_run;
+pub mod empty_slash_history;
+pub mod genesis_binding;
+pub mod high_water_marks;
+pub mod in_memory;
+pub mod interchange_export;
+pub mod interchange_file_import;
+pub mod key_limit;
+pub mod keystore_archive;
+pub mod keystore_exists;
+pub mod list_keys;
+pub mod log_redaction;
+pub mod metrics_label;
+pub mod proposer_index;
+pub mod prune_slash_protection;
+pub mod pubkey_validation;
 pub mod randao_reveal;
+pub mod request_id;
+pub mod request_mac;
+pub mod reseal;
+pub mod response_cache;
+pub mod session;
+pub mod sign_raw;
+pub mod sign_stats;
+pub mod sign_type_allowlist;
+pub mod signature_format;
+pub mod signing_floor;
+pub mod ssz_encoding;
+pub mod status_codes;
+pub mod strict_json;
 pub mod sync_committee_message;
 pub mod sync_committee_selection_proof;
+pub mod transient_sealing;
+pub mod unix_socket;
 pub mod validator_registration;
+pub mod verify;
+pub mod verify_batch;
+pub mod voluntary_exit;
+
+use puffersecuresigner::enclave::shared::handlers::AppState;
+
+/// A test server with only the signing route, answering with `state`
+pub fn sign_server(state: AppState) -> axum_test::TestServer {
+    axum_test::TestServer::new(
+        axum::Router::new()
+            .route(
+                "/api/v1/eth2/sign/:bls_pk_hex",
+                axum::routing::post(
+                    puffersecuresigner::enclave::shared::handlers::secure_sign_bls::handler,
+                ),
+            )
+            .with_state(state)
+            .into_make_service(),
+    )
+    .unwrap()
+}
+
+/// Signing state on the genesis fork version the test requests are built for
+pub fn sign_state() -> AppState {
+    AppState {
+        genesis_fork_version: puffersecuresigner::eth2::eth_types::GENESIS_FORK_VERSION,
+        ..Default::default()
+    }
+}
@@ -0,0 +1,41 @@
+use crate::signing_tests::block::block_proposal_request;
+use crate::signing_tests::bls_import::{import_request, import_server_with, new_enclave_eth_key};
+use crate::signing_tests::{sign_server, sign_state};
+use puffersecuresigner::io::config::Config;
+
+/// Imports a fresh key bound to `proposer_index`, returning its pubkey in hex
+async fn import_bound_key(proposer_index: u64) -> String {
+    let (eth_pk_hex, eth_pk) = new_enclave_eth_key();
+    let (bls_pk_hex, mut import) = import_request("hunter2", &eth_pk, eth_pk_hex);
+    import.proposer_index = Some(proposer_index);
+    let resp = import_server_with(false)
+        .post("/eth/v1/keystores")
+        .json(&import)
+        .await;
+    assert_eq!(resp.status_code(), 200);
+    bls_pk_hex
+}
+
+#[tokio::test]
+async fn test_block_with_wrong_proposer_index_is_rejected() {
+    let _data_dir = Config::scoped_temp().unwrap();
+    let server = sign_server(sign_state());
+
+    // The mock block is proposed by validator 5
+    let bls_pk_hex = import_bound_key(7).await;
+    let resp = server
+        .post(&format!("/api/v1/eth2/sign/0x{bls_pk_hex}"))
+        .json(&block_proposal_request(1))
+        .await;
+    assert_eq!(resp.status_code(), 403);
+    assert!(resp
+        .text()
+        .contains("proposer_index 5 is not the index bound to this key"));
+
+    let bls_pk_hex = import_bound_key(5).await;
+    let resp = server
+        .post(&format!("/api/v1/eth2/sign/0x{bls_pk_hex}"))
+        .json(&block_proposal_request(1))
+        .await;
+    assert_eq!(resp.status_code(), 200);
+}
@@ -0,0 +1,94 @@
+use crate::common::signing_helper::mock_secure_sign_route;
+use crate::signing_tests::attestation::attestation_req;
+use crate::signing_tests::block::block_proposal_request;
+use puffersecuresigner::crypto::bls_keys;
+use puffersecuresigner::enclave::types::SlashProtectionPruneResponse;
+use puffersecuresigner::eth2::slash_protection::{
+    SignedAttestationEpochs, SignedBlockSlot, SlashingProtectionData,
+};
+use puffersecuresigner::io::config::Config;
+
+fn prune_server() -> axum_test::TestServer {
+    let test_app = axum::Router::new()
+        .route(
+            "/eth/v1/slashing-protection/prune",
+            axum::routing::post(
+                puffersecuresigner::enclave::shared::handlers::prune_slash_protection::handler,
+            ),
+        )
+        .with_state(puffersecuresigner::enclave::shared::handlers::AppState::default())
+        .into_make_service();
+    axum_test::TestServer::new(test_app).unwrap()
+}
+
+#[tokio::test]
+async fn test_prune_large_history() {
+    // The test servers run on this thread, so they only see the keys saved here
+    let _data_dir = Config::scoped_temp().unwrap();
+    let sk = bls_keys::new_bls_key(0);
+    bls_keys::save_bls_key(&sk).unwrap();
+    let pk_hex = sk.public_keys().public_key().to_hex();
+
+    // A complete history of months of signing
+    let mut data = SlashingProtectionData::from_pk_hex(&pk_hex).unwrap();
+    for i in 1..=10_000 {
+        data.signed_blocks.push(SignedBlockSlot {
+            slot: i * 32,
+            signing_root: Some([1; 32]),
+        });
+        data.signed_attestations.push(SignedAttestationEpochs {
+            source_epoch: i,
+            target_epoch: i + 1,
+            signing_root: Some([2; 32]),
+        });
+    }
+    let marks = data.high_water_marks();
+    data.write().unwrap();
+
+    let resp = prune_server()
+        .post("/eth/v1/slashing-protection/prune")
+        .await;
+    assert_eq!(resp.status_code(), 200);
+    assert_eq!(
+        resp.json::<SlashProtectionPruneResponse>(),
+        SlashProtectionPruneResponse {
+            pruned: vec![format!("0x{pk_hex}")],
+            failed: vec![],
+            entries_removed: 19_998,
+        }
+    );
+
+    let pruned = SlashingProtectionData::read(&pk_hex).unwrap();
+    assert_eq!(pruned.signed_blocks.len(), 1);
+    assert_eq!(pruned.signed_attestations.len(), 1);
+    assert_eq!(pruned.high_water_marks(), marks);
+
+    // Signing below or at the marks is still refused
+    let pk_hex = format!("0x{pk_hex}");
+    for req in [
+        block_proposal_request(320_000),
+        block_proposal_request(100),
+        attestation_req(10_000, 10_001),
+        attestation_req(9_999, 10_002),
+    ] {
+        let resp = mock_secure_sign_route(&pk_hex, req).await.unwrap();
+        assert_eq!(resp.status_code(), 412);
+    }
+    let resp = mock_secure_sign_route(&pk_hex, block_proposal_request(320_001))
+        .await
+        .unwrap();
+    assert_eq!(resp.status_code(), 200);
+    let resp = mock_secure_sign_route(&pk_hex, attestation_req(10_000, 10_002))
+        .await
+        .unwrap();
+    assert_eq!(resp.status_code(), 200);
+
+    // Nothing left to prune
+    let resp = prune_server()
+        .post("/eth/v1/slashing-protection/prune")
+        .await;
+    assert_eq!(
+        resp.json::<SlashProtectionPruneResponse>(),
+        SlashProtectionPruneResponse::default()
+    );
+}
@@ -0,0 +1,83 @@
+use crate::common::signing_helper::mock_secure_sign_route;
+use crate::signing_tests::block_v2::mock_propose_block_v2_request;
+use puffersecuresigner::eth2::eth_signing::BLSSignMsg;
+
+fn block_proposal_request() -> BLSSignMsg {
+    let req = mock_propose_block_v2_request(100);
+    BLSSignMsg::BLOCK_V2(serde_json::from_str(&req).unwrap())
+}
+
+#[tokio::test]
+async fn test_path_traversal_pk_is_rejected() {
+    // Percent-encoded separators are decoded by the path extractor and reach the handler
+    let payloads = [
+        "..%2F..%2Fetc%2Fpasswd".to_string(),
+        "0x..%2F..%2F..%2Fetc%2Fpasswd".to_string(),
+        format!("0x..%2F{}", "a".repeat(92)),
+        "%2Fetc%2Fpasswd".to_string(),
+        "..".to_string(),
+    ];
+    for payload in payloads {
+        let resp = mock_secure_sign_route(&payload, block_proposal_request())
+            .await
+            .unwrap();
+        assert_eq!(resp.status_code(), 400, "payload {payload}");
+    }
+}
+
+#[tokio::test]
+async fn test_malformed_pk_hex_is_rejected() {
+    let pk_hex = crate::common::setup_dummy_keypair();
+    let payloads = [
+        // Too short and too long
+        pk_hex[..pk_hex.len() - 2].to_string(),
+        format!("{pk_hex}00"),
+        // Not hex
+        format!("0x{}", "z".repeat(96)),
+        // Right length but not a valid point
+        format!("0x{}", "ff".repeat(48)),
+    ];
+    for payload in payloads {
+        let resp = mock_secure_sign_route(&payload, block_proposal_request())
+            .await
+            .unwrap();
+        assert_eq!(resp.status_code(), 400, "payload {payload}");
+    }
+}
+
+#[tokio::test]
+async fn test_pk_hex_prefix_and_casing_resolve_to_the_same_key() {
+    let pk_hex = crate::common::setup_dummy_keypair();
+    let payloads = [
+        pk_hex.clone(),
+        pk_hex[2..].to_string(),
+        pk_hex.to_uppercase(),
+        pk_hex[2..].to_uppercase(),
+        // Mixed case
+        pk_hex
+            .chars()
+            .enumerate()
+            .map(|(i, c)| {
+                if i % 2 == 0 {
+                    c.to_ascii_uppercase()
+                } else {
+                    c
+                }
+            })
+            .collect(),
+    ];
+    let mut signatures = vec![];
+    for payload in payloads {
+        let resp = mock_secure_sign_route(
+            &payload,
+            crate::signing_tests::randao_reveal::randao_reveal_request(),
+        )
+        .await
+        .unwrap();
+        assert_eq!(resp.status_code(), 200, "payload {payload}");
+        let resp: puffersecuresigner::enclave::types::SignatureResponse = resp.json();
+        signatures.push(resp.signature);
+    }
+    signatures.dedup();
+    assert_eq!(signatures.len(), 1);
+}
@@ -5,7 +5,7 @@ use puffersecuresigner::eth2::eth_signing::*;
 use puffersecuresigner::eth2::eth_types::*;
 use puffersecuresigner::strip_0x_prefix;
 
-fn randao_reveal_request() -> BLSSignMsg {
+pub fn randao_reveal_request() -> BLSSignMsg {
     // Create a RandaoRevealRequest
     let req = mock_randao_reveal_request();
     let signing_data: RandaoRevealRequest = serde_json::from_str(&req).unwrap();
@@ -25,7 +25,7 @@ pub fn mock_randao_reveal_request() -> String {
               }},
               "genesis_validators_root":"0x2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a"
            }},
-           "signingRoot": "0xbf70dbbbc83299fb877334eaeaefb32df44242c1bf078cdc1836dcc3282d4fbd",
+           "signingRoot": "0x66ab8ee49a2b08e5603e2f94cfd7ff3db0f68877e2b57b99720d41b22c94be2e",
            "randao_reveal":{{
                 "epoch": "10"
            }}
@@ -69,3 +69,142 @@ pub async fn test_aggregate_randao_reveal_happy_path_test_vec() {
     let got_sig: String = strip_0x_prefix!(sig);
     assert_eq!(exp_sig.unwrap(), got_sig);
 }
+
+#[tokio::test]
+pub async fn test_signing_root_is_returned_on_request() {
+    let bls_pk_hex = common::setup_dummy_keypair();
+    let req: serde_json::Value = serde_json::from_str(&mock_randao_reveal_request()).unwrap();
+    let test_app = axum::Router::new()
+        .route(
+            "/api/v1/eth2/sign/:bls_pk_hex",
+            axum::routing::post(
+                puffersecuresigner::enclave::shared::handlers::secure_sign_bls::handler,
+            ),
+        )
+        .with_state(puffersecuresigner::enclave::shared::handlers::AppState {
+            genesis_fork_version: GENESIS_FORK_VERSION,
+            ..Default::default()
+        })
+        .into_make_service();
+    let server = axum_test::TestServer::new(test_app).unwrap();
+
+    // The root matches the one the request expects to be signed
+    let resp = server
+        .post(&format!(
+            "/api/v1/eth2/sign/{bls_pk_hex}?include_signing_root=true"
+        ))
+        .json(&req)
+        .await;
+    assert_eq!(resp.status_code(), 200);
+    let resp: puffersecuresigner::enclave::types::SignatureResponse = resp.json();
+    assert_eq!(resp.signing_root.as_deref(), req["signingRoot"].as_str());
+
+    // Existing clients keep getting the signature alone
+    let resp = server
+        .post(&format!("/api/v1/eth2/sign/{bls_pk_hex}"))
+        .json(&req)
+        .await;
+    assert_eq!(resp.status_code(), 200);
+    let resp: serde_json::Value = resp.json();
+    assert!(resp.get("signing_root").is_none());
+    assert!(resp.get("signature").is_some());
+}
+
+#[tokio::test]
+pub async fn test_tampered_signing_root_is_rejected() {
+    let bls_pk_hex = common::setup_dummy_keypair();
+    let mut req: serde_json::Value = serde_json::from_str(&mock_randao_reveal_request()).unwrap();
+    req["signingRoot"] = serde_json::json!(format!("0x{}", "ab".repeat(32)));
+    let test_app = axum::Router::new()
+        .route(
+            "/api/v1/eth2/sign/:bls_pk_hex",
+            axum::routing::post(
+                puffersecuresigner::enclave::shared::handlers::secure_sign_bls::handler,
+            ),
+        )
+        .with_state(puffersecuresigner::enclave::shared::handlers::AppState {
+            genesis_fork_version: GENESIS_FORK_VERSION,
+            ..Default::default()
+        })
+        .into_make_service();
+    let server = axum_test::TestServer::new(test_app).unwrap();
+
+    let resp = server
+        .post(&format!("/api/v1/eth2/sign/{bls_pk_hex}"))
+        .json(&req)
+        .await;
+    assert_eq!(resp.status_code(), 400);
+    assert!(resp.text().contains("signingRoot"));
+}
+
+#[tokio::test]
+pub async fn test_non_slashable_types_do_not_touch_slash_protection_db() {
+    let bls_pk_hex = register_new_bls_key(None).await.pk_hex;
+    let pk_hex: String = strip_0x_prefix!(bls_pk_hex);
+    let snapshot = || {
+        serde_json::to_value(
+            puffersecuresigner::eth2::slash_protection::SlashingProtectionData::read(&pk_hex)
+                .unwrap(),
+        )
+        .unwrap()
+    };
+    let before = snapshot();
+
+    let aggregate_and_proof: AggregateAndProofRequest = serde_json::from_str(
+        &crate::signing_tests::aggregate_and_proof::mock_aggregate_and_proof_request(),
+    )
+    .unwrap();
+    for req in [
+        randao_reveal_request(),
+        BLSSignMsg::AGGREGATE_AND_PROOF(aggregate_and_proof),
+    ] {
+        assert!(!req.is_slashable_type());
+        let (_resp, status) = make_signing_route_request(req, &bls_pk_hex, None)
+            .await
+            .unwrap();
+        assert_eq!(status, 200);
+    }
+
+    assert_eq!(snapshot(), before);
+}
+
+#[tokio::test]
+pub async fn test_key_added_after_startup_is_signable_without_reload() {
+    let _data_dir = puffersecuresigner::io::config::Config::scoped_temp().unwrap();
+    let server = axum_test::TestServer::new(
+        axum::Router::new()
+            .route(
+                "/api/v1/eth2/publicKeys",
+                axum::routing::get(
+                    puffersecuresigner::enclave::shared::handlers::list_public_keys::handler,
+                ),
+            )
+            .route(
+                "/api/v1/eth2/sign/:bls_pk_hex",
+                axum::routing::post(
+                    puffersecuresigner::enclave::shared::handlers::secure_sign_bls::handler,
+                ),
+            )
+            .with_state(puffersecuresigner::enclave::shared::handlers::AppState {
+                genesis_fork_version: GENESIS_FORK_VERSION,
+                ..Default::default()
+            })
+            .into_make_service(),
+    )
+    .unwrap();
+    let keys: Vec<String> = server.get("/api/v1/eth2/publicKeys").await.json();
+    assert!(keys.is_empty());
+
+    // Restore a key file into the running signer's data dir
+    let sk = puffersecuresigner::crypto::bls_keys::new_bls_key(0);
+    puffersecuresigner::crypto::bls_keys::save_bls_key(&sk).unwrap();
+    let bls_pk_hex = format!("0x{}", sk.public_keys().public_key().to_hex());
+
+    let keys: Vec<String> = server.get("/api/v1/eth2/publicKeys").await.json();
+    assert_eq!(keys, vec![bls_pk_hex.clone()]);
+    let resp = server
+        .post(&format!("/api/v1/eth2/sign/{bls_pk_hex}"))
+        .json(&randao_reveal_request())
+        .await;
+    assert_eq!(resp.status_code(), 200);
+}
@@ -0,0 +1,128 @@
+use crate::common::bls_keygen_helper::register_new_bls_key;
+use crate::signing_tests::block_v2::mock_propose_block_v2_request;
+use crate::signing_tests::{sign_server, sign_state};
+use axum::http::{HeaderName, HeaderValue};
+use puffersecuresigner::enclave::shared::handlers::AppState;
+use puffersecuresigner::enclave::shared::request_id::{RequestIdCache, REQUEST_ID_HEADER};
+use puffersecuresigner::eth2::eth_signing::BLSSignMsg;
+
+fn strict_sign_server() -> axum_test::TestServer {
+    sign_server(AppState {
+        request_ids: Some(RequestIdCache::default()),
+        ..sign_state()
+    })
+}
+
+fn block_proposal_request(slot: u64) -> BLSSignMsg {
+    let req = mock_propose_block_v2_request(slot);
+    BLSSignMsg::BLOCK_V2(serde_json::from_str(&req).unwrap())
+}
+
+#[tokio::test]
+async fn test_replayed_request_id_is_rejected_in_strict_mode() {
+    let bls_pk_hex = register_new_bls_key(None).await.pk_hex;
+    let server = strict_sign_server();
+    let uri = format!("/api/v1/eth2/sign/{}", bls_pk_hex);
+
+    let resp = server
+        .post(&uri)
+        .add_header(
+            HeaderName::from_static("x-request-id"),
+            HeaderValue::from_static("replayed-id"),
+        )
+        .json(&block_proposal_request(100))
+        .await;
+    assert_eq!(resp.status_code(), 200);
+
+    // Same request id on an otherwise valid request
+    let resp = server
+        .post(&uri)
+        .add_header(
+            HeaderName::from_static("x-request-id"),
+            HeaderValue::from_static("replayed-id"),
+        )
+        .json(&block_proposal_request(101))
+        .await;
+    assert_eq!(resp.status_code(), 409);
+}
+
+#[tokio::test]
+async fn test_missing_request_id_is_rejected_in_strict_mode() {
+    let bls_pk_hex = register_new_bls_key(None).await.pk_hex;
+    let server = strict_sign_server();
+    let uri = format!("/api/v1/eth2/sign/{}", bls_pk_hex);
+
+    let resp = server.post(&uri).json(&block_proposal_request(100)).await;
+    assert_eq!(resp.status_code(), 400);
+    assert!(resp.text().contains(REQUEST_ID_HEADER));
+}
+
+#[tokio::test]
+async fn test_request_id_is_echoed_in_response_headers() {
+    let bls_pk_hex = register_new_bls_key(None).await.pk_hex;
+    let test_app = axum::Router::new()
+        .route(
+            "/api/v1/eth2/sign/:bls_pk_hex",
+            axum::routing::post(
+                puffersecuresigner::enclave::shared::handlers::secure_sign_bls::handler,
+            ),
+        )
+        .layer(axum::middleware::from_fn(
+            puffersecuresigner::enclave::shared::request_log::middleware,
+        ))
+        .with_state(AppState {
+            request_ids: Some(RequestIdCache::default()),
+            ..sign_state()
+        })
+        .into_make_service();
+    let server = axum_test::TestServer::new(test_app).unwrap();
+    let uri = format!("/api/v1/eth2/sign/{}", bls_pk_hex);
+
+    let resp = server
+        .post(&uri)
+        .add_header(
+            HeaderName::from_static("x-request-id"),
+            HeaderValue::from_static("vc-duty-42"),
+        )
+        .json(&block_proposal_request(100))
+        .await;
+    assert_eq!(resp.status_code(), 200);
+    assert_eq!(resp.headers()[REQUEST_ID_HEADER], "vc-duty-42");
+
+    // The generated id of a request without one is returned, but doesn't count as the caller's
+    let resp = server.post(&uri).json(&block_proposal_request(101)).await;
+    assert_eq!(resp.status_code(), 400);
+    assert!(!resp.headers()[REQUEST_ID_HEADER].is_empty());
+}
+
+#[tokio::test]
+async fn test_request_id_turned_away_with_503_can_be_retried() {
+    let bls_pk_hex = register_new_bls_key(None).await.pk_hex;
+    let state = AppState {
+        request_ids: Some(RequestIdCache::default()),
+        key_locks: puffersecuresigner::enclave::shared::key_lock::KeyLocks::new(
+            std::time::Duration::from_millis(10),
+        ),
+        ..sign_state()
+    };
+    let pk = puffersecuresigner::crypto::bls_keys::validate_bls_pk_hex(&bls_pk_hex).unwrap();
+    let held = state.key_locks.lock(&pk).await.unwrap();
+    let server = sign_server(state);
+    let uri = format!("/api/v1/eth2/sign/{}", bls_pk_hex);
+    let send = || {
+        server
+            .post(&uri)
+            .add_header(
+                HeaderName::from_static("x-request-id"),
+                HeaderValue::from_static("busy-key"),
+            )
+            .json(&block_proposal_request(100))
+    };
+
+    // The key is busy, so the request never got to sign
+    assert_eq!(send().await.status_code(), 503);
+
+    drop(held);
+    assert_eq!(send().await.status_code(), 200);
+    assert_eq!(send().await.status_code(), 409);
+}
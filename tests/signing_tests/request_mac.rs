@@ -0,0 +1,154 @@
+use crate::signing_tests::bls_import::{import_request, import_server_with, new_enclave_eth_key};
+use crate::signing_tests::randao_reveal::mock_randao_reveal_request;
+use crate::signing_tests::{sign_server, sign_state};
+use axum::http::{HeaderName, HeaderValue};
+use puffersecuresigner::crypto::eth_keys;
+use puffersecuresigner::enclave::shared::handlers::AppState;
+use puffersecuresigner::enclave::shared::request_mac;
+use puffersecuresigner::enclave::types::KeyGenResponse;
+
+const SECRET: &[u8] = b"provisioned-at-import";
+
+fn mac_server() -> axum_test::TestServer {
+    sign_server(AppState {
+        require_request_mac: true,
+        ..sign_state()
+    })
+}
+
+/// Imports a fresh keystore through the import route with `SECRET` as its request MAC secret,
+/// returning its pubkey
+async fn import_with_mac_secret() -> String {
+    let (eth_pk_hex, eth_pk) = new_enclave_eth_key();
+    let (bls_pk_hex, mut req) = import_request("password", &eth_pk, eth_pk_hex);
+    req.ct_mac_secret_hex = Some(hex::encode(
+        eth_keys::envelope_encrypt(&eth_pk, SECRET).unwrap(),
+    ));
+    let resp = import_server_with(false)
+        .post("/eth/v1/keystores")
+        .json(&req)
+        .await;
+    assert_eq!(resp.status_code(), 200);
+    bls_pk_hex
+}
+
+async fn sign(
+    server: &axum_test::TestServer,
+    bls_pk_hex: &str,
+    body: &str,
+    mac: Option<String>,
+) -> axum_test::TestResponse {
+    let mut req = server
+        .post(&format!("/api/v1/eth2/sign/{bls_pk_hex}"))
+        .content_type("application/json")
+        .bytes(body.to_string().into());
+    if let Some(mac) = mac {
+        req = req.add_header(
+            HeaderName::from_static("x-request-mac"),
+            HeaderValue::from_str(&mac).unwrap(),
+        );
+    }
+    req.await
+}
+
+fn mac_of(secret: &[u8], body: &str) -> String {
+    format!(
+        "0x{}",
+        hex::encode(request_mac::compute(secret, body.as_bytes()).unwrap())
+    )
+}
+
+#[tokio::test]
+async fn test_valid_request_mac_signs() {
+    let bls_pk_hex = import_with_mac_secret().await;
+    let body = mock_randao_reveal_request();
+
+    let resp = sign(
+        &mac_server(),
+        &bls_pk_hex,
+        &body,
+        Some(mac_of(SECRET, &body)),
+    )
+    .await;
+    assert_eq!(resp.status_code(), 200);
+}
+
+#[tokio::test]
+async fn test_tampered_request_mac_returns_401() {
+    let bls_pk_hex = import_with_mac_secret().await;
+    let server = mac_server();
+    let body = mock_randao_reveal_request();
+    let mac = mac_of(SECRET, &body);
+
+    // The randao epoch was changed after the MAC was computed
+    let tampered = body.replace(r#""epoch": "10""#, r#""epoch": "11""#);
+    assert_ne!(tampered, body);
+    let resp = sign(&server, &bls_pk_hex, &tampered, Some(mac)).await;
+    assert_eq!(resp.status_code(), 401);
+
+    // MAC under the wrong secret
+    let resp = sign(&server, &bls_pk_hex, &body, Some(mac_of(b"guess", &body))).await;
+    assert_eq!(resp.status_code(), 401);
+
+    // No MAC at all
+    let resp = sign(&server, &bls_pk_hex, &body, None).await;
+    assert_eq!(resp.status_code(), 401);
+}
+
+#[tokio::test]
+async fn test_key_without_secret_returns_401() {
+    let sk = puffersecuresigner::crypto::bls_keys::new_bls_key(0);
+    puffersecuresigner::crypto::bls_keys::save_bls_key(&sk).unwrap();
+    let bls_pk_hex = format!("0x{}", sk.public_keys().public_key().to_hex());
+    let body = mock_randao_reveal_request();
+
+    let resp = sign(
+        &mac_server(),
+        &bls_pk_hex,
+        &body,
+        Some(mac_of(SECRET, &body)),
+    )
+    .await;
+    assert_eq!(resp.status_code(), 401);
+}
+
+#[tokio::test]
+async fn test_mac_secret_provisioned_at_keygen() {
+    let keygen_server = axum_test::TestServer::new(
+        axum::Router::new()
+            .route(
+                "/eth/v1/keygen/bls",
+                axum::routing::post(
+                    puffersecuresigner::enclave::secure_signer::handlers::bls_keygen::handler,
+                ),
+            )
+            .with_state(AppState::default())
+            .into_make_service(),
+    )
+    .unwrap();
+    let (eth_pk_hex, eth_pk) = new_enclave_eth_key();
+    let ct_mac_secret_hex = hex::encode(eth_keys::envelope_encrypt(&eth_pk, SECRET).unwrap());
+
+    // The secret must name the key it is encrypted to
+    let resp = keygen_server
+        .post(&format!(
+            "/eth/v1/keygen/bls?ct_mac_secret_hex={ct_mac_secret_hex}"
+        ))
+        .await;
+    assert_eq!(resp.status_code(), 400);
+
+    let resp = keygen_server
+        .post(&format!(
+            "/eth/v1/keygen/bls?ct_mac_secret_hex={ct_mac_secret_hex}&encrypting_pk_hex={eth_pk_hex}"
+        ))
+        .await;
+    assert_eq!(resp.status_code(), 201);
+    let bls_pk_hex = resp.json::<KeyGenResponse>().pk_hex;
+
+    let server = mac_server();
+    let body = mock_randao_reveal_request();
+    let resp = sign(&server, &bls_pk_hex, &body, Some(mac_of(SECRET, &body))).await;
+    assert_eq!(resp.status_code(), 200);
+    let resp = sign(&server, &bls_pk_hex, &body, Some(mac_of(b"guess", &body))).await;
+    assert_eq!(resp.status_code(), 401);
+}
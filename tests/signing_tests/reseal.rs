@@ -0,0 +1,139 @@
+use puffersecuresigner::crypto::bls_keys;
+use puffersecuresigner::enclave::types::ResealResponse;
+use puffersecuresigner::io::config::{Config, SealingPolicy};
+use puffersecuresigner::io::key_management;
+
+fn reseal_server() -> axum_test::TestServer {
+    let test_app = axum::Router::new()
+        .route(
+            "/eth/v1/reseal",
+            axum::routing::post(puffersecuresigner::enclave::shared::handlers::reseal::handler),
+        )
+        .into_make_service();
+    axum_test::TestServer::new(test_app).unwrap()
+}
+
+#[tokio::test]
+async fn test_reseal_moves_mrsigner_keys_to_the_current_policy() {
+    let _data_dir = Config::scoped_temp().unwrap();
+
+    // The previous build sealed its keys to MRSIGNER
+    let pk_hex = {
+        let _old_build = Config::current()
+            .with_sealing_policy(SealingPolicy::MrSigner)
+            .scoped();
+        let sk = bls_keys::new_bls_key(0);
+        bls_keys::save_bls_key(&sk).unwrap();
+        sk.public_keys().public_key().to_hex()
+    };
+
+    // The upgraded build seals to its own MRENCLAVE, and can't read them as they are
+    let _new_build = Config::current()
+        .with_sealing_policy(SealingPolicy::MrEnclave)
+        .scoped();
+    assert!(key_management::read_bls_key(&pk_hex).is_err());
+
+    let resp = reseal_server().post("/eth/v1/reseal").await;
+    assert_eq!(resp.status_code(), 200);
+    assert_eq!(
+        resp.json::<ResealResponse>(),
+        ResealResponse {
+            resealed: vec![format!("0x{pk_hex}")],
+            failed: vec![],
+        }
+    );
+    assert_eq!(
+        bls_keys::fetch_bls_sk(&pk_hex)
+            .unwrap()
+            .public_keys()
+            .public_key()
+            .to_hex(),
+        pk_hex
+    );
+
+    // Keys already sealed to the current policy are left alone
+    let resp = reseal_server().post("/eth/v1/reseal").await;
+    assert_eq!(resp.json::<ResealResponse>(), ResealResponse::default());
+}
+
+#[tokio::test]
+async fn test_reseal_reports_keys_it_cannot_unseal() {
+    let _data_dir = Config::scoped_temp().unwrap();
+    let bls_keys_dir = Config::current().bls_keys_dir();
+    key_management::ensure_dir(&bls_keys_dir).unwrap();
+    std::fs::write(bls_keys_dir.join("abcd"), hex::encode([7_u8; 40])).unwrap();
+
+    let resp = reseal_server().post("/eth/v1/reseal").await;
+    assert_eq!(resp.status_code(), 200);
+    assert_eq!(
+        resp.json::<ResealResponse>(),
+        ResealResponse {
+            resealed: vec![],
+            failed: vec!["0xabcd".to_string()],
+        }
+    );
+    // The unreadable key is left as it was
+    assert_eq!(
+        std::fs::read_to_string(bls_keys_dir.join("abcd")).unwrap(),
+        hex::encode([7_u8; 40])
+    );
+}
+
+/// Writes the key file of `sk` as versions before sealing did, the hex of the raw secret key.
+/// Returns its hex pubkey.
+fn write_baseline_key(sk: &blsttc::SecretKeySet) -> String {
+    let pk_hex = sk.public_keys().public_key().to_hex();
+    let bls_keys_dir = Config::current().bls_keys_dir();
+    key_management::ensure_dir(&bls_keys_dir).unwrap();
+    std::fs::write(bls_keys_dir.join(&pk_hex), hex::encode(sk.to_bytes())).unwrap();
+    pk_hex
+}
+
+#[tokio::test]
+async fn test_baseline_plaintext_key_is_sealed_and_signs() {
+    let _data_dir = Config::scoped_temp().unwrap();
+    let sk = bls_keys::new_bls_key(0);
+    let pk_hex = write_baseline_key(&sk);
+
+    let server = crate::signing_tests::sign_server(crate::signing_tests::sign_state());
+    let req = serde_json::from_str::<serde_json::Value>(
+        &crate::signing_tests::randao_reveal::mock_randao_reveal_request(),
+    )
+    .unwrap();
+    let resp = server
+        .post(&format!("/api/v1/eth2/sign/0x{pk_hex}"))
+        .json(&req)
+        .await;
+    assert_eq!(resp.status_code(), 200);
+
+    // The plaintext secret key is gone from disk
+    let stored = std::fs::read_to_string(Config::current().bls_keys_dir().join(&pk_hex)).unwrap();
+    assert_ne!(stored, hex::encode(sk.to_bytes()));
+    assert_eq!(
+        bls_keys::fetch_bls_sk(&pk_hex)
+            .unwrap()
+            .public_keys()
+            .public_key()
+            .to_hex(),
+        pk_hex
+    );
+}
+
+#[tokio::test]
+async fn test_reseal_seals_baseline_plaintext_keys() {
+    let _data_dir = Config::scoped_temp().unwrap();
+    let pk_hex = write_baseline_key(&bls_keys::new_bls_key(0));
+
+    let resp = reseal_server().post("/eth/v1/reseal").await;
+    assert_eq!(
+        resp.json::<ResealResponse>(),
+        ResealResponse {
+            resealed: vec![format!("0x{pk_hex}")],
+            failed: vec![],
+        }
+    );
+    assert!(key_management::read_bls_key(&pk_hex).is_ok());
+
+    let resp = reseal_server().post("/eth/v1/reseal").await;
+    assert_eq!(resp.json::<ResealResponse>(), ResealResponse::default());
+}
@@ -0,0 +1,88 @@
+use crate::signing_tests::randao_reveal::randao_reveal_request;
+use crate::signing_tests::{sign_server, sign_state};
+use puffersecuresigner::enclave::shared::handlers::AppState;
+use puffersecuresigner::enclave::shared::response_cache::ResponseCache;
+use puffersecuresigner::enclave::types::SignatureResponse;
+use puffersecuresigner::io::config::Config;
+use std::time::Duration;
+
+fn cache_state() -> AppState {
+    AppState {
+        response_cache: Some(ResponseCache::new(Duration::from_secs(60), 16)),
+        ..sign_state()
+    }
+}
+
+fn new_key() -> String {
+    let sk = puffersecuresigner::crypto::bls_keys::new_bls_key(0);
+    puffersecuresigner::crypto::bls_keys::save_bls_key(&sk).unwrap();
+    format!("0x{}", sk.public_keys().public_key().to_hex())
+}
+
+#[tokio::test]
+async fn test_identical_randao_request_is_answered_from_cache() {
+    let _data_dir = Config::scoped_temp().unwrap();
+    let server = sign_server(cache_state());
+    let bls_pk_hex = new_key();
+    let uri = format!("/api/v1/eth2/sign/{bls_pk_hex}");
+
+    let resp = server.post(&uri).json(&randao_reveal_request()).await;
+    assert_eq!(resp.status_code(), 200);
+    let first: SignatureResponse = resp.json();
+
+    // Without the key the retry can only be answered from the cache
+    puffersecuresigner::io::key_management::delete_bls_key(&bls_pk_hex).unwrap();
+    let resp = server.post(&uri).json(&randao_reveal_request()).await;
+    assert_eq!(resp.status_code(), 200);
+    let retried: SignatureResponse = resp.json();
+    assert_eq!(retried.signature, first.signature);
+}
+
+#[tokio::test]
+async fn test_cache_is_not_shared_across_keys() {
+    let _data_dir = Config::scoped_temp().unwrap();
+    let server = sign_server(cache_state());
+    let (a, b) = (new_key(), new_key());
+
+    let sign = |pk: &String| {
+        server
+            .post(&format!("/api/v1/eth2/sign/{pk}"))
+            .json(&randao_reveal_request())
+    };
+    let sig_a: SignatureResponse = sign(&a).await.json();
+    let sig_b: SignatureResponse = sign(&b).await.json();
+    assert_ne!(sig_a.signature, sig_b.signature);
+}
+
+#[tokio::test]
+async fn test_replayed_request_id_is_not_answered_from_cache() {
+    let _data_dir = Config::scoped_temp().unwrap();
+    let server = sign_server(AppState {
+        request_ids: Some(
+            puffersecuresigner::enclave::shared::request_id::RequestIdCache::default(),
+        ),
+        ..cache_state()
+    });
+    let uri = format!("/api/v1/eth2/sign/{}", new_key());
+    let sign = |request_id: &'static str| {
+        server
+            .post(&uri)
+            .add_header(
+                axum::http::HeaderName::from_static("x-request-id"),
+                axum::http::HeaderValue::from_static(request_id),
+            )
+            .json(&randao_reveal_request())
+    };
+
+    let first: SignatureResponse = sign("first").await.json();
+    assert_eq!(sign("first").await.status_code(), 409);
+
+    // A retry under a fresh id is still answered from the cache
+    let retried = sign("second").await;
+    assert_eq!(retried.status_code(), 200);
+    assert_eq!(
+        retried.json::<SignatureResponse>().signature,
+        first.signature
+    );
+    assert_eq!(sign("second").await.status_code(), 409);
+}
@@ -0,0 +1,154 @@
+use crate::signing_tests::randao_reveal::mock_randao_reveal_request;
+use axum::http::{HeaderName, HeaderValue};
+use puffersecuresigner::enclave::shared::session::SessionStore;
+use puffersecuresigner::enclave::types::{SessionResponse, SignatureResponse};
+use puffersecuresigner::eth2::eth_types::GENESIS_FORK_VERSION;
+
+const GENESIS_VALIDATORS_ROOT: &str =
+    "0x2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a";
+
+fn session_server() -> axum_test::TestServer {
+    let test_app = axum::Router::new()
+        .route(
+            "/api/v1/eth2/session",
+            axum::routing::post(puffersecuresigner::enclave::shared::handlers::session::handler),
+        )
+        .route(
+            "/api/v1/eth2/sign/:bls_pk_hex",
+            axum::routing::post(
+                puffersecuresigner::enclave::shared::handlers::secure_sign_bls::handler,
+            ),
+        )
+        .with_state(puffersecuresigner::enclave::shared::handlers::AppState {
+            genesis_fork_version: GENESIS_FORK_VERSION,
+            sessions: Some(SessionStore::new("token".to_string())),
+            ..Default::default()
+        })
+        .into_make_service();
+    axum_test::TestServer::new(test_app).unwrap()
+}
+
+async fn open_session(server: &axum_test::TestServer) -> String {
+    let resp = server
+        .post("/api/v1/eth2/session")
+        .add_header(
+            HeaderName::from_static("authorization"),
+            HeaderValue::from_static("Bearer token"),
+        )
+        .json(&serde_json::json!({ "genesis_validators_root": GENESIS_VALIDATORS_ROOT }))
+        .await;
+    assert_eq!(resp.status_code(), 200);
+    resp.json::<SessionResponse>().session_id
+}
+
+/// Randao reveal request with the genesis_validators_root replaced, or removed if None
+fn randao_reveal_request(genesis_validators_root: Option<&str>) -> serde_json::Value {
+    let mut req: serde_json::Value = serde_json::from_str(&mock_randao_reveal_request()).unwrap();
+    let fork_info = req["fork_info"].as_object_mut().unwrap();
+    match genesis_validators_root {
+        Some(root) => fork_info.insert("genesis_validators_root".into(), root.into()),
+        None => fork_info.remove("genesis_validators_root"),
+    };
+    req
+}
+
+#[tokio::test]
+async fn test_session_handshake_requires_token() {
+    let server = session_server();
+    let body = serde_json::json!({ "genesis_validators_root": GENESIS_VALIDATORS_ROOT });
+
+    let resp = server.post("/api/v1/eth2/session").json(&body).await;
+    assert_eq!(resp.status_code(), 401);
+
+    let resp = server
+        .post("/api/v1/eth2/session")
+        .add_header(
+            HeaderName::from_static("authorization"),
+            HeaderValue::from_static("Bearer wrong"),
+        )
+        .json(&body)
+        .await;
+    assert_eq!(resp.status_code(), 401);
+}
+
+#[tokio::test]
+async fn test_root_can_be_omitted_after_handshake() {
+    let bls_pk_hex = crate::common::setup_dummy_keypair();
+    let server = session_server();
+    let uri = format!("/api/v1/eth2/sign/{}", bls_pk_hex);
+
+    // Signature with the root supplied on the request itself
+    let resp = server
+        .post(&uri)
+        .json(&randao_reveal_request(Some(GENESIS_VALIDATORS_ROOT)))
+        .await;
+    assert_eq!(resp.status_code(), 200);
+    let expected = resp.json::<SignatureResponse>().signature;
+
+    let session_id = open_session(&server).await;
+    let resp = server
+        .post(&uri)
+        .add_header(
+            HeaderName::from_static("x-session-id"),
+            HeaderValue::from_str(&session_id).unwrap(),
+        )
+        .json(&randao_reveal_request(None))
+        .await;
+    assert_eq!(resp.status_code(), 200);
+    assert_eq!(resp.json::<SignatureResponse>().signature, expected);
+}
+
+#[tokio::test]
+async fn test_mismatching_root_is_rejected_in_session() {
+    let bls_pk_hex = crate::common::setup_dummy_keypair();
+    let server = session_server();
+    let uri = format!("/api/v1/eth2/sign/{}", bls_pk_hex);
+    let session_id = open_session(&server).await;
+
+    let resp = server
+        .post(&uri)
+        .add_header(
+            HeaderName::from_static("x-session-id"),
+            HeaderValue::from_str(&session_id).unwrap(),
+        )
+        .json(&randao_reveal_request(Some(
+            "0x2b2b2b2b2b2b2b2b2b2b2b2b2b2b2b2b2b2b2b2b2b2b2b2b2b2b2b2b2b2b2b2b",
+        )))
+        .await;
+    assert_eq!(resp.status_code(), 400);
+
+    // Matching root is fine
+    let resp = server
+        .post(&uri)
+        .add_header(
+            HeaderName::from_static("x-session-id"),
+            HeaderValue::from_str(&session_id).unwrap(),
+        )
+        .json(&randao_reveal_request(Some(GENESIS_VALIDATORS_ROOT)))
+        .await;
+    assert_eq!(resp.status_code(), 200);
+
+    // Unknown sessions are rejected
+    let resp = server
+        .post(&uri)
+        .add_header(
+            HeaderName::from_static("x-session-id"),
+            HeaderValue::from_static("unknown"),
+        )
+        .json(&randao_reveal_request(None))
+        .await;
+    assert_eq!(resp.status_code(), 401);
+}
+
+#[tokio::test]
+async fn test_omitted_root_without_session_is_rejected() {
+    let bls_pk_hex = crate::common::setup_dummy_keypair();
+    let server = session_server();
+
+    // Only a session can stand in for the root, it is never signed as zero
+    let resp = server
+        .post(&format!("/api/v1/eth2/sign/{}", bls_pk_hex))
+        .json(&randao_reveal_request(None))
+        .await;
+    assert_eq!(resp.status_code(), 400);
+}
@@ -0,0 +1,81 @@
+use crate::common;
+use crate::signing_tests::randao_reveal::mock_randao_reveal_request;
+use puffersecuresigner::enclave::types::SignatureResponse;
+
+fn raw_server(allow_raw_signing: bool) -> axum_test::TestServer {
+    let test_app = axum::Router::new()
+        .route(
+            "/api/v1/eth2/sign/:bls_pk_hex/raw",
+            axum::routing::post(
+                puffersecuresigner::enclave::secure_signer::handlers::sign_raw::handler,
+            ),
+        )
+        .with_state(puffersecuresigner::enclave::shared::handlers::AppState {
+            allow_raw_signing,
+            ..Default::default()
+        })
+        .into_make_service();
+    axum_test::TestServer::new(test_app).unwrap()
+}
+
+#[tokio::test]
+async fn test_raw_signing_matches_typed_signing() {
+    let bls_pk_hex = common::setup_dummy_keypair();
+    let server = raw_server(true);
+
+    // The root of the RANDAO reveal test vector signs to the same signature as the typed request
+    let req: serde_json::Value = serde_json::from_str(&mock_randao_reveal_request()).unwrap();
+    let resp = server
+        .post(&format!("/api/v1/eth2/sign/{bls_pk_hex}/raw"))
+        .json(&serde_json::json!({ "signingRoot": req["signingRoot"] }))
+        .await;
+    assert_eq!(resp.status_code(), 200);
+    let resp: SignatureResponse = resp.json();
+    assert_eq!(resp.signature, "0xa8c5eb481ef1c3ea345bc9cb9ce9918e18ef052d8287bacd3b1e1bbd34bc4e1e016602b778535d5b582bc35ea6d2ded106ea2cfec06f8b6c5bd049dbf0a544207ac3b21c634b8e78c2c0135a0000e961adae192203ef168de1edb83618d1a76d");
+    assert_eq!(resp.signing_root.as_deref(), req["signingRoot"].as_str());
+
+    // Roots must be exactly 32 bytes
+    let resp = server
+        .post(&format!("/api/v1/eth2/sign/{bls_pk_hex}/raw"))
+        .json(&serde_json::json!({ "signingRoot": "0xabcd" }))
+        .await;
+    assert_eq!(resp.status_code(), 400);
+}
+
+#[tokio::test]
+async fn test_raw_signing_is_rejected_when_disabled() {
+    let bls_pk_hex = common::setup_dummy_keypair();
+    let server = raw_server(false);
+    let resp = server
+        .post(&format!("/api/v1/eth2/sign/{bls_pk_hex}/raw"))
+        .json(&serde_json::json!({ "signingRoot": format!("0x{}", "ab".repeat(32)) }))
+        .await;
+    assert_eq!(resp.status_code(), 403);
+}
+
+#[tokio::test]
+async fn test_raw_signing_needs_raw_in_the_sign_type_allowlist() {
+    let _data_dir = puffersecuresigner::io::config::Config::scoped_temp().unwrap();
+    let bls_pk_hex = common::setup_dummy_keypair();
+    let server = raw_server(true);
+    let sign = || {
+        server
+            .post(&format!("/api/v1/eth2/sign/{bls_pk_hex}/raw"))
+            .json(&serde_json::json!({ "signingRoot": format!("0x{}", "ab".repeat(32)) }))
+    };
+
+    // A key provisioned for attestations only can't sign arbitrary roots
+    puffersecuresigner::io::key_management::write_sign_type_allowlist(
+        &bls_pk_hex,
+        &["attestation".to_string()],
+    )
+    .unwrap();
+    assert_eq!(sign().await.status_code(), 403);
+
+    puffersecuresigner::io::key_management::write_sign_type_allowlist(
+        &bls_pk_hex,
+        &["attestation".to_string(), "raw".to_string()],
+    )
+    .unwrap();
+    assert_eq!(sign().await.status_code(), 200);
+}
@@ -0,0 +1,72 @@
+use crate::common;
+use crate::signing_tests::attestation::attestation_req;
+use crate::signing_tests::block_v2::mock_propose_block_v2_request;
+use puffersecuresigner::enclave::shared::sign_stats::KeyStats;
+use puffersecuresigner::eth2::eth_types::GENESIS_FORK_VERSION;
+
+fn stats_server() -> axum_test::TestServer {
+    axum_test::TestServer::new(
+        axum::Router::new()
+            .route(
+                "/api/v1/eth2/sign/:bls_pk_hex",
+                axum::routing::post(
+                    puffersecuresigner::enclave::shared::handlers::secure_sign_bls::handler,
+                ),
+            )
+            .route(
+                "/eth/v1/stats/:bls_pk_hex",
+                axum::routing::get(
+                    puffersecuresigner::enclave::shared::handlers::sign_stats::handler,
+                ),
+            )
+            .with_state(puffersecuresigner::enclave::shared::handlers::AppState {
+                genesis_fork_version: GENESIS_FORK_VERSION,
+                ..Default::default()
+            })
+            .into_make_service(),
+    )
+    .unwrap()
+}
+
+#[tokio::test]
+async fn test_stats_count_signs_and_rejections() {
+    let _data_dir = puffersecuresigner::io::config::Config::scoped_temp().unwrap();
+    let bls_pk_hex = common::setup_dummy_keypair();
+    let server = stats_server();
+    let sign_uri = format!("/api/v1/eth2/sign/{bls_pk_hex}");
+    let stats_uri = format!("/eth/v1/stats/{bls_pk_hex}");
+
+    let resp = server.get(&stats_uri).await;
+    assert_eq!(resp.status_code(), 200);
+    assert_eq!(resp.json::<KeyStats>(), KeyStats::default());
+
+    let block =
+        serde_json::from_str::<serde_json::Value>(&mock_propose_block_v2_request(10)).unwrap();
+    assert_eq!(server.post(&sign_uri).json(&block).await.status_code(), 200);
+    assert_eq!(
+        server
+            .post(&sign_uri)
+            .json(&attestation_req(0, 1))
+            .await
+            .status_code(),
+        200
+    );
+    // Refused by slashing protection
+    assert_eq!(server.post(&sign_uri).json(&block).await.status_code(), 412);
+
+    let stats: KeyStats = server.get(&stats_uri).await.json();
+    assert_eq!(stats.blocks_signed, 1);
+    assert_eq!(stats.attestations_signed, 1);
+    assert_eq!(stats.rejections, 1);
+    assert!(stats.last_signed_at.is_some());
+}
+
+#[tokio::test]
+async fn test_stats_of_unknown_key() {
+    let _data_dir = puffersecuresigner::io::config::Config::scoped_temp().unwrap();
+    let server = stats_server();
+    let resp = server
+        .get(&format!("/eth/v1/stats/0x{}", "aa".repeat(48)))
+        .await;
+    assert_eq!(resp.status_code(), 404);
+}
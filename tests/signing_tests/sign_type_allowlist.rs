@@ -0,0 +1,47 @@
+use crate::signing_tests::attestation::attestation_req;
+use crate::signing_tests::block::block_proposal_request;
+use crate::signing_tests::bls_import::{import_request, import_server_with, new_enclave_eth_key};
+use crate::signing_tests::{sign_server, sign_state};
+use puffersecuresigner::io::config::Config;
+
+#[tokio::test]
+async fn test_attestation_only_key_refuses_blocks() {
+    let _data_dir = Config::scoped_temp().unwrap();
+
+    // Provision the allowlist when importing the key, in any case
+    let (eth_pk_hex, eth_pk) = new_enclave_eth_key();
+    let (bls_pk_hex, mut import) = import_request("hunter2", &eth_pk, eth_pk_hex);
+    import.allowed_types = Some(vec!["attestation".to_string()]);
+    let resp = import_server_with(false)
+        .post("/eth/v1/keystores")
+        .json(&import)
+        .await;
+    assert_eq!(resp.status_code(), 200);
+
+    let server = sign_server(sign_state());
+    let uri = format!("/api/v1/eth2/sign/0x{bls_pk_hex}");
+
+    let resp = server.post(&uri).json(&block_proposal_request(1)).await;
+    assert_eq!(resp.status_code(), 403);
+    assert!(resp.text().contains("BLOCK requests are not allowed"));
+
+    let resp = server.post(&uri).json(&attestation_req(0, 1)).await;
+    assert_eq!(resp.status_code(), 200);
+}
+
+#[tokio::test]
+async fn test_import_rejects_unknown_sign_types() {
+    let _data_dir = Config::scoped_temp().unwrap();
+    let (eth_pk_hex, eth_pk) = new_enclave_eth_key();
+    let (bls_pk_hex, mut import) = import_request("hunter2", &eth_pk, eth_pk_hex);
+    import.allowed_types = Some(vec!["ATTESTATION".to_string(), "BLOB".to_string()]);
+    let resp = import_server_with(false)
+        .post("/eth/v1/keystores")
+        .json(&import)
+        .await;
+    assert_eq!(resp.status_code(), 400);
+    assert!(resp.text().contains("Unknown signing type BLOB"));
+    assert!(!puffersecuresigner::io::key_management::bls_key_exists(
+        &bls_pk_hex
+    ));
+}
@@ -0,0 +1,43 @@
+use crate::signing_tests::randao_reveal::randao_reveal_request;
+use crate::signing_tests::{sign_server, sign_state};
+use puffersecuresigner::enclave::types::SignatureFormat;
+use puffersecuresigner::io::config::Config;
+
+#[tokio::test]
+async fn test_every_format_encodes_the_same_signature() {
+    let _data_dir = Config::scoped_temp().unwrap();
+    let server = sign_server(sign_state());
+    let sk = puffersecuresigner::crypto::bls_keys::new_bls_key(0);
+    puffersecuresigner::crypto::bls_keys::save_bls_key(&sk).unwrap();
+    let uri = format!(
+        "/api/v1/eth2/sign/0x{}",
+        sk.public_keys().public_key().to_hex()
+    );
+
+    // No format is the current hex form
+    let resp = server.post(&uri).json(&randao_reveal_request()).await;
+    assert_eq!(resp.status_code(), 200);
+    let json: serde_json::Value = resp.json();
+    let expected = SignatureFormat::Hex.decode(&json["signature"]).unwrap();
+    assert_eq!(expected.len(), 96);
+
+    for (name, format) in [
+        ("hex", SignatureFormat::Hex),
+        ("base64", SignatureFormat::Base64),
+        ("bytes", SignatureFormat::Bytes),
+    ] {
+        let resp = server
+            .post(&format!("{uri}?signatureFormat={name}"))
+            .json(&randao_reveal_request())
+            .await;
+        assert_eq!(resp.status_code(), 200);
+        let json: serde_json::Value = resp.json();
+        assert_eq!(format.decode(&json["signature"]).unwrap(), expected);
+    }
+
+    let resp = server
+        .post(&format!("{uri}?signatureFormat=ssz"))
+        .json(&randao_reveal_request())
+        .await;
+    assert_eq!(resp.status_code(), 400);
+}
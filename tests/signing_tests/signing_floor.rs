@@ -0,0 +1,99 @@
+use crate::common::signing_helper::mock_secure_sign_route;
+use crate::signing_tests::block::block_proposal_request;
+use puffersecuresigner::crypto::bls_keys;
+use puffersecuresigner::eth2::slash_protection::{SigningFloor, SlashingProtectionData};
+
+fn floor_server() -> axum_test::TestServer {
+    let test_app = axum::Router::new()
+        .route(
+            "/eth/v1/keystores/:bls_pk_hex/signing-floor",
+            axum::routing::post(
+                puffersecuresigner::enclave::shared::handlers::signing_floor::handler,
+            ),
+        )
+        .into_make_service();
+    axum_test::TestServer::new(test_app).unwrap()
+}
+
+/// Saves a fresh BLS key with an empty slashing protection database
+fn new_key() -> String {
+    let sk = bls_keys::new_bls_key(0);
+    bls_keys::save_bls_key(&sk).unwrap();
+    let pk_hex = sk.public_keys().public_key().to_hex();
+    SlashingProtectionData::from_pk_hex(&pk_hex)
+        .unwrap()
+        .write()
+        .unwrap();
+    format!("0x{pk_hex}")
+}
+
+#[tokio::test]
+async fn test_signing_floor_rejects_block_below_floor() {
+    let server = floor_server();
+    let pk_hex = new_key();
+
+    let resp = server
+        .post(&format!("/eth/v1/keystores/{pk_hex}/signing-floor"))
+        .json(&serde_json::json!({
+            "min_slot": "1000",
+            "min_source_epoch": "10",
+            "min_target_epoch": "20"
+        }))
+        .await;
+    assert_eq!(resp.status_code(), 200);
+
+    // The slash DB is empty, so only the floor stands in the way
+    let resp = mock_secure_sign_route(&pk_hex, block_proposal_request(999))
+        .await
+        .unwrap();
+    assert_eq!(resp.status_code(), 412);
+    assert!(resp.text().contains("below the signing floor"));
+
+    let resp = mock_secure_sign_route(&pk_hex, block_proposal_request(1000))
+        .await
+        .unwrap();
+    assert_eq!(resp.status_code(), 200);
+
+    // Monotonicity failures are still reported as such
+    let resp = mock_secure_sign_route(&pk_hex, block_proposal_request(1000))
+        .await
+        .unwrap();
+    assert_eq!(resp.status_code(), 412);
+    assert!(!resp.text().contains("below the signing floor"));
+}
+
+#[tokio::test]
+async fn test_signing_floor_is_never_lowered() {
+    let server = floor_server();
+    let pk_hex = new_key();
+
+    let set_floor = |slot: u64, src: u64, tgt: u64| {
+        server
+            .post(&format!("/eth/v1/keystores/{pk_hex}/signing-floor"))
+            .json(&SigningFloor {
+                min_slot: slot,
+                min_source_epoch: src,
+                min_target_epoch: tgt,
+            })
+    };
+
+    let resp = set_floor(100, 10, 20).await;
+    assert_eq!(resp.status_code(), 200);
+    let resp = set_floor(50, 15, 5).await;
+    assert_eq!(
+        resp.json::<SigningFloor>(),
+        SigningFloor {
+            min_slot: 100,
+            min_source_epoch: 15,
+            min_target_epoch: 20,
+        }
+    );
+
+    // Unknown keys have no floor to set
+    let unknown = bls_keys::new_bls_key(0).public_keys().public_key().to_hex();
+    let resp = server
+        .post(&format!("/eth/v1/keystores/0x{unknown}/signing-floor"))
+        .json(&SigningFloor::default())
+        .await;
+    assert_eq!(resp.status_code(), 404);
+}
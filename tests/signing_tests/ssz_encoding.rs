@@ -0,0 +1,99 @@
+use crate::signing_tests::block_v2::mock_propose_block_v2_request;
+use crate::signing_tests::randao_reveal::mock_randao_reveal_request;
+use crate::signing_tests::{sign_server, sign_state};
+use puffersecuresigner::crypto::bls_keys;
+use puffersecuresigner::enclave::types::SignatureResponse;
+use puffersecuresigner::eth2::eth_signing::BLSSignMsg;
+use puffersecuresigner::eth2::slash_protection::SlashingProtectionData;
+use puffersecuresigner::eth2::ssz_request::{self, SSZ_CONTENT_TYPE};
+
+/// Starts the key over with an empty slashing protection history
+fn reset_slashing_protection(pk_hex: &str) {
+    SlashingProtectionData::from_pk_hex(pk_hex)
+        .unwrap()
+        .write()
+        .unwrap();
+}
+
+async fn sign_both_ways(req: &str) -> (String, String) {
+    let server = sign_server(sign_state());
+    let sk = bls_keys::new_bls_key(0);
+    bls_keys::save_bls_key(&sk).unwrap();
+    let pk_hex = sk.public_keys().public_key().to_hex();
+    let uri = format!("/api/v1/eth2/sign/0x{pk_hex}");
+
+    reset_slashing_protection(&pk_hex);
+    let resp = server
+        .post(&uri)
+        .content_type("application/json")
+        .bytes(req.to_string().into())
+        .await;
+    assert_eq!(resp.status_code(), 200, "{}", resp.text());
+    let json_sig = resp.json::<SignatureResponse>().signature;
+
+    reset_slashing_protection(&pk_hex);
+    let msg: BLSSignMsg = serde_json::from_str(req).unwrap();
+    let resp = server
+        .post(&uri)
+        .content_type(SSZ_CONTENT_TYPE)
+        .bytes(ssz_request::to_ssz_bytes(&msg).unwrap().into())
+        .await;
+    assert_eq!(resp.status_code(), 200, "{}", resp.text());
+    let ssz_sig = resp.json::<SignatureResponse>().signature;
+
+    (json_sig, ssz_sig)
+}
+
+#[tokio::test]
+async fn test_block_v2_signs_the_same_as_json_and_ssz() {
+    let (json_sig, ssz_sig) = sign_both_ways(&mock_propose_block_v2_request(100)).await;
+    assert_eq!(json_sig, ssz_sig);
+}
+
+#[tokio::test]
+async fn test_randao_reveal_signs_the_same_as_json_and_ssz() {
+    let (json_sig, ssz_sig) = sign_both_ways(&mock_randao_reveal_request()).await;
+    assert_eq!(json_sig, ssz_sig);
+}
+
+#[tokio::test]
+async fn test_ssz_requests_are_slashing_protected() {
+    let server = sign_server(sign_state());
+    let sk = bls_keys::new_bls_key(0);
+    bls_keys::save_bls_key(&sk).unwrap();
+    let pk_hex = sk.public_keys().public_key().to_hex();
+    reset_slashing_protection(&pk_hex);
+    let uri = format!("/api/v1/eth2/sign/0x{pk_hex}");
+
+    let resp = server
+        .post(&uri)
+        .content_type("application/json")
+        .bytes(mock_propose_block_v2_request(100).into())
+        .await;
+    assert_eq!(resp.status_code(), 200);
+
+    // The same slot again, only encoded differently
+    let msg: BLSSignMsg = serde_json::from_str(&mock_propose_block_v2_request(100)).unwrap();
+    let resp = server
+        .post(&uri)
+        .content_type(SSZ_CONTENT_TYPE)
+        .bytes(ssz_request::to_ssz_bytes(&msg).unwrap().into())
+        .await;
+    assert_eq!(resp.status_code(), 412);
+}
+
+#[tokio::test]
+async fn test_malformed_ssz_is_a_bad_request() {
+    let server = sign_server(sign_state());
+    let sk = bls_keys::new_bls_key(0);
+    bls_keys::save_bls_key(&sk).unwrap();
+    let pk_hex = sk.public_keys().public_key().to_hex();
+    reset_slashing_protection(&pk_hex);
+
+    let resp = server
+        .post(&format!("/api/v1/eth2/sign/0x{pk_hex}"))
+        .content_type(SSZ_CONTENT_TYPE)
+        .bytes(vec![ssz_request::SSZ_BLOCK_V2, 1, 2, 3].into())
+        .await;
+    assert_eq!(resp.status_code(), 400);
+}
@@ -0,0 +1,207 @@
+use crate::common::signing_helper::{mock_secure_sign_route, mock_secure_sign_route_with_state};
+use crate::signing_tests::block_v2::mock_propose_block_v2_request;
+use crate::signing_tests::{sign_server, sign_state};
+use puffersecuresigner::eth2::eth_signing::BLSSignMsg;
+use puffersecuresigner::eth2::eth_types::GENESIS_FORK_VERSION;
+use puffersecuresigner::eth2::slash_protection::SlashingProtectionData;
+
+#[tokio::test]
+async fn test_unknown_key_returns_404() {
+    let pk = puffersecuresigner::crypto::bls_keys::new_bls_key(0)
+        .public_keys()
+        .public_key();
+    let req = mock_propose_block_v2_request(100);
+    let req = BLSSignMsg::BLOCK_V2(serde_json::from_str(&req).unwrap());
+    let resp = mock_secure_sign_route(&format!("0x{}", pk.to_hex()), req)
+        .await
+        .unwrap();
+    assert_eq!(resp.status_code(), 404);
+}
+
+#[tokio::test]
+async fn test_malformed_json_returns_400() {
+    let bls_pk_hex = crate::common::setup_dummy_keypair();
+    let resp = sign_server(sign_state())
+        .post(&format!("/api/v1/eth2/sign/{}", bls_pk_hex))
+        .content_type("application/json")
+        .bytes("{\"type\": \"BLOCK_V2\", ".into())
+        .await;
+    assert_eq!(resp.status_code(), 400);
+}
+
+#[tokio::test]
+async fn test_unsupported_type_returns_400() {
+    let bls_pk_hex = crate::common::setup_dummy_keypair();
+    let resp = sign_server(sign_state())
+        .post(&format!("/api/v1/eth2/sign/{}", bls_pk_hex))
+        .json(&serde_json::json!({
+            "type": "NOT_A_SIGNING_TYPE",
+            "signingRoot": "0x0000000000000000000000000000000000000000000000000000000000000000"
+        }))
+        .await;
+    assert_eq!(resp.status_code(), 400);
+}
+
+#[tokio::test]
+async fn test_malformed_fields_return_400() {
+    let bls_pk_hex = crate::common::setup_dummy_keypair();
+    let block = mock_propose_block_v2_request(100);
+    let root = format!("0x{}", "2a".repeat(32));
+    let registration = r#"{
+        "type": "VALIDATOR_REGISTRATION",
+        "validator_registration": {
+            "fee_recipient": "0x2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a",
+            "gas_limit": "30000000",
+            "timestamp": "100",
+            "pubkey": "0x8349434ad0700e79be65c0c7043945df426bd6d7e288c16671df69d822344f1b0ce8de80360a50550ad782b68035cb18"
+        }
+    }"#;
+    let malformed = [
+        // Truncated hex
+        block.replace(&root, &root[..root.len() - 1]),
+        registration.replace("cb18\"", "cb1\""),
+        // Wrong length roots and keys
+        block.replace(&root, &root[..root.len() - 2]),
+        block.replace(&root, &format!("{root}2a")),
+        registration.replace("cb18\"", "cb\""),
+        registration.replace("0x2a2a", "0x2a2a2a"),
+        // Out of range integers
+        block.replace("\"slot\": \"100\"", "\"slot\": \"18446744073709551616\""),
+        block.replace("\"slot\": \"100\"", "\"slot\": 18446744073709551616"),
+        block.replace("\"slot\": \"100\"", "\"slot\": -1"),
+        registration.replace("\"30000000\"", "\"1e400\""),
+    ];
+    for body in malformed {
+        assert!(body != block && body != registration);
+        let resp = sign_server(sign_state())
+            .post(&format!("/api/v1/eth2/sign/{}", bls_pk_hex))
+            .content_type("application/json")
+            .bytes(body.clone().into())
+            .await;
+        assert_eq!(resp.status_code(), 400, "{body}");
+    }
+}
+
+#[tokio::test]
+async fn test_busy_key_returns_503_with_retry_after() {
+    let bls_pk_hex = crate::common::bls_keygen_helper::register_new_bls_key(None)
+        .await
+        .pk_hex;
+    let key_locks = puffersecuresigner::enclave::shared::key_lock::KeyLocks::new(
+        std::time::Duration::from_millis(50),
+    );
+    let state = puffersecuresigner::enclave::shared::handlers::AppState {
+        key_locks: key_locks.clone(),
+        ..sign_state()
+    };
+    let req =
+        || BLSSignMsg::BLOCK_V2(serde_json::from_str(&mock_propose_block_v2_request(100)).unwrap());
+
+    // Another request on the key is stuck in its critical section
+    let held = key_locks
+        .lock(&bls_pk_hex.trim_start_matches("0x").to_lowercase())
+        .await
+        .unwrap();
+    let resp = mock_secure_sign_route_with_state(&bls_pk_hex, req(), state.clone())
+        .await
+        .unwrap();
+    assert_eq!(resp.status_code(), 503);
+    assert_eq!(resp.headers()["retry-after"], "1");
+
+    // The retry goes through once the key is free
+    drop(held);
+    let resp = mock_secure_sign_route_with_state(&bls_pk_hex, req(), state)
+        .await
+        .unwrap();
+    assert_eq!(resp.status_code(), 200);
+}
+
+#[tokio::test]
+async fn test_slow_signing_returns_504_before_recording() {
+    let _data_dir = puffersecuresigner::io::config::Config::scoped_temp().unwrap();
+    let bls_pk_hex = crate::common::bls_keygen_helper::register_new_bls_key(None)
+        .await
+        .pk_hex;
+    let pk_hex = bls_pk_hex.trim_start_matches("0x").to_lowercase();
+    let state = puffersecuresigner::enclave::shared::handlers::AppState {
+        sign_timeout: Some(std::time::Duration::from_millis(50)),
+        ..sign_state()
+    };
+    let req =
+        BLSSignMsg::BLOCK_V2(serde_json::from_str(&mock_propose_block_v2_request(100)).unwrap());
+
+    puffersecuresigner::enclave::shared::sign_deadline::inject_delay(
+        std::time::Duration::from_millis(500),
+    );
+    let resp = mock_secure_sign_route_with_state(&bls_pk_hex, req, state)
+        .await
+        .unwrap();
+    puffersecuresigner::enclave::shared::sign_deadline::inject_delay(std::time::Duration::ZERO);
+    assert_eq!(resp.status_code(), 504);
+    let data = SlashingProtectionData::read(&pk_hex).unwrap();
+    assert!(data.signed_blocks.is_empty());
+
+    // The late signing still finishes, against this test's data dir
+    tokio::time::sleep(std::time::Duration::from_millis(1000)).await;
+    let data = SlashingProtectionData::read(&pk_hex).unwrap();
+    assert_eq!(data.signed_blocks.len(), 1);
+}
+
+#[tokio::test]
+async fn test_oversized_sign_body_returns_413() {
+    let limit = puffersecuresigner::constants::DEFAULT_SIGN_BODY_LIMIT_BYTES;
+    let test_app = axum::Router::new()
+        .route(
+            "/api/v1/eth2/sign/:bls_pk_hex",
+            axum::routing::post(
+                puffersecuresigner::enclave::shared::handlers::secure_sign_bls::handler,
+            )
+            .layer(axum::extract::DefaultBodyLimit::max(limit)),
+        )
+        .with_state(puffersecuresigner::enclave::shared::handlers::AppState {
+            genesis_fork_version: GENESIS_FORK_VERSION,
+            ..Default::default()
+        })
+        .into_make_service();
+    let server = axum_test::TestServer::new(test_app).unwrap();
+    let uri = format!("/api/v1/eth2/sign/{}", crate::common::setup_dummy_keypair());
+
+    // A valid request padded with whitespace up to exactly the limit is still signed
+    let req = crate::signing_tests::randao_reveal::mock_randao_reveal_request();
+    let padded = format!("{req}{}", " ".repeat(limit - req.len()));
+    let resp = server
+        .post(&uri)
+        .content_type("application/json")
+        .bytes(padded.into())
+        .await;
+    assert_eq!(resp.status_code(), 200);
+
+    let oversized = format!("{req}{}", " ".repeat(limit + 1 - req.len()));
+    let resp = server
+        .post(&uri)
+        .content_type("application/json")
+        .bytes(oversized.into())
+        .await;
+    assert_eq!(resp.status_code(), 413);
+}
+
+#[tokio::test]
+async fn test_oversized_import_body_returns_413() {
+    let limit = 1024;
+    let test_app = axum::Router::new()
+        .route(
+            "/eth/v1/slashing-protection",
+            axum::routing::post(
+                puffersecuresigner::enclave::shared::handlers::import_slash_protection::handler,
+            )
+            .layer(axum::extract::DefaultBodyLimit::max(limit)),
+        )
+        .with_state(puffersecuresigner::enclave::shared::handlers::AppState::default())
+        .into_make_service();
+    let resp = axum_test::TestServer::new(test_app)
+        .unwrap()
+        .post("/eth/v1/slashing-protection")
+        .bytes(vec![b' '; limit + 1].into())
+        .await;
+    assert_eq!(resp.status_code(), 413);
+}
@@ -0,0 +1,58 @@
+use crate::common;
+use crate::signing_tests::block_v2::mock_propose_block_v2_request;
+use crate::signing_tests::randao_reveal::mock_randao_reveal_request;
+use crate::signing_tests::{sign_server, sign_state};
+use puffersecuresigner::enclave::shared::handlers::AppState;
+
+fn with_extra_fields(req: &str) -> serde_json::Value {
+    let mut req: serde_json::Value = serde_json::from_str(req).unwrap();
+    req["comment"] = "hello".into();
+    req["randao_reveal"]["nonce"] = 7.into();
+    req
+}
+
+#[tokio::test]
+async fn test_lenient_mode_ignores_unknown_fields() {
+    let bls_pk_hex = common::setup_dummy_keypair();
+    let resp = sign_server(AppState {
+        deny_unknown_fields: false,
+        ..sign_state()
+    })
+    .post(&format!("/api/v1/eth2/sign/{bls_pk_hex}"))
+    .json(&with_extra_fields(&mock_randao_reveal_request()))
+    .await;
+    assert_eq!(resp.status_code(), 200);
+}
+
+#[tokio::test]
+async fn test_strict_mode_rejects_unknown_fields() {
+    let bls_pk_hex = common::setup_dummy_keypair();
+    let server = sign_server(AppState {
+        deny_unknown_fields: true,
+        ..sign_state()
+    });
+    let uri = format!("/api/v1/eth2/sign/{bls_pk_hex}");
+
+    let resp = server
+        .post(&uri)
+        .json(&with_extra_fields(&mock_randao_reveal_request()))
+        .await;
+    assert_eq!(resp.status_code(), 400);
+    assert!(resp
+        .text()
+        .contains("Unknown fields .comment, .randao_reveal.nonce"));
+
+    // Well-formed requests still sign
+    let resp = server
+        .post(&uri)
+        .json(&serde_json::from_str::<serde_json::Value>(&mock_randao_reveal_request()).unwrap())
+        .await;
+    assert_eq!(resp.status_code(), 200);
+    let resp = server
+        .post(&uri)
+        .json(
+            &serde_json::from_str::<serde_json::Value>(&mock_propose_block_v2_request(1)).unwrap(),
+        )
+        .await;
+    assert_eq!(resp.status_code(), 200);
+}
@@ -29,7 +29,7 @@ pub fn mock_sync_committee_message_request() -> String {
                 }},
                 "genesis_validators_root":"0x2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a"
             }},
-            "signingRoot": "0x2ebfc2d70944cc2fbff6d67c6d9cbb043d7fbe0a660d248b6e666ce110af418a",
+            "signingRoot": "0x98bd7ac851ee91b3562a0ae3ab899d8bf9833900fea6d34c7dbed645dea80f0e",
             "sync_committee_message": {{
                 "slot": "123123",
                 "beacon_block_root": "0x2ebfc2d70944cc2fbff6d67c6d9cbb043d7fbe0a660d248b6e666ce110af418a"
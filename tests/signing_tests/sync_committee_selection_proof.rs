@@ -29,7 +29,7 @@ pub fn mock_sync_committee_selection_proof_request() -> String {
                 }},
                 "genesis_validators_root":"0x2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a"
             }},
-            "signingRoot": "0x2ebfc2d70944cc2fbff6d67c6d9cbb043d7fbe0a660d248b6e666ce110af418a",
+            "signingRoot": "0x52d4a281e039ef8ee7f649960356d2783632c3039f411681ba768518465b615f",
             "sync_aggregator_selection_data": {{
                 "slot": "123123",
                 "subcommittee_index": "12345"
@@ -39,6 +39,40 @@ pub fn mock_sync_committee_selection_proof_request() -> String {
     req
 }
 
+#[tokio::test]
+async fn test_subcommittee_index_bounds() {
+    let bls_pk_hex = common::setup_dummy_keypair();
+    let state = puffersecuresigner::enclave::shared::handlers::AppState {
+        genesis_fork_version: GENESIS_FORK_VERSION,
+        validate_subcommittee_index: true,
+        ..Default::default()
+    };
+    let req = |index: u64| {
+        let req = mock_sync_committee_selection_proof_request().replace(
+            r#""subcommittee_index": "12345""#,
+            &format!(r#""subcommittee_index": "{index}""#),
+        );
+        BLSSignMsg::SYNC_COMMITTEE_SELECTION_PROOF(serde_json::from_str(&req).unwrap())
+    };
+
+    // The last subcommittee
+    let resp = mock_secure_sign_route_with_state(&bls_pk_hex, req(3), state.clone())
+        .await
+        .unwrap();
+    assert_eq!(resp.status_code(), 200);
+
+    // Just past it
+    let resp = mock_secure_sign_route_with_state(&bls_pk_hex, req(4), state.clone())
+        .await
+        .unwrap();
+    assert_eq!(resp.status_code(), 400);
+    assert!(resp.text().contains("Bad subcommittee_index 4"));
+
+    // Not checked unless enabled
+    let resp = mock_secure_sign_route(&bls_pk_hex, req(4)).await.unwrap();
+    assert_eq!(resp.status_code(), 200);
+}
+
 #[tokio::test]
 pub async fn test_aggregate_route_fails_from_invalid_pk_hex() {
     let port = common::read_secure_signer_port();
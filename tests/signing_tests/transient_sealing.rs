@@ -0,0 +1,70 @@
+#![cfg(not(feature = "sgx"))]
+
+use crate::signing_tests::attestation::attestation_req;
+use puffersecuresigner::crypto::sealing::{inject_transient_failures, SEALING_RETRY_AFTER_SECS};
+use puffersecuresigner::eth2::eth_types::GENESIS_FORK_VERSION;
+use puffersecuresigner::io::config::Config;
+
+fn server() -> axum_test::TestServer {
+    axum_test::TestServer::new(
+        axum::Router::new()
+            .route(
+                "/api/v1/eth2/sign/:bls_pk_hex",
+                axum::routing::post(
+                    puffersecuresigner::enclave::shared::handlers::secure_sign_bls::handler,
+                ),
+            )
+            .route(
+                "/eth/v1/keygen/bls",
+                axum::routing::post(
+                    puffersecuresigner::enclave::secure_signer::handlers::bls_keygen::handler,
+                ),
+            )
+            .with_state(puffersecuresigner::enclave::shared::handlers::AppState {
+                genesis_fork_version: GENESIS_FORK_VERSION,
+                ..Default::default()
+            })
+            .into_make_service(),
+    )
+    .unwrap()
+}
+
+#[tokio::test]
+async fn test_transient_unseal_failure_is_retryable() {
+    let _data_dir = Config::scoped_temp().unwrap();
+    let server = server();
+    let sk = puffersecuresigner::crypto::bls_keys::new_bls_key(0);
+    puffersecuresigner::crypto::bls_keys::save_bls_key(&sk).unwrap();
+    let uri = format!(
+        "/api/v1/eth2/sign/0x{}",
+        sk.public_keys().public_key().to_hex()
+    );
+
+    inject_transient_failures(1);
+    let resp = server.post(&uri).json(&attestation_req(1, 2)).await;
+    assert_eq!(resp.status_code(), 503);
+    assert_eq!(
+        resp.headers()[axum::http::header::RETRY_AFTER],
+        SEALING_RETRY_AFTER_SECS.to_string()
+    );
+
+    // Nothing was recorded for the failed attempt, so the same attestation still signs
+    let resp = server.post(&uri).json(&attestation_req(1, 2)).await;
+    assert_eq!(resp.status_code(), 200);
+}
+
+#[tokio::test]
+async fn test_transient_seal_failure_during_keygen_is_retryable() {
+    let _data_dir = Config::scoped_temp().unwrap();
+    let server = server();
+
+    inject_transient_failures(1);
+    let resp = server.post("/eth/v1/keygen/bls").await;
+    assert_eq!(resp.status_code(), 503);
+    assert!(puffersecuresigner::io::key_management::list_bls_keys()
+        .unwrap()
+        .is_empty());
+
+    let resp = server.post("/eth/v1/keygen/bls").await;
+    assert_eq!(resp.status_code(), 201);
+}
@@ -0,0 +1,61 @@
+use crate::common;
+use crate::signing_tests::randao_reveal::mock_randao_reveal_request;
+use puffersecuresigner::enclave::shared::server::{ListenAddr, Server};
+use puffersecuresigner::eth2::eth_types::GENESIS_FORK_VERSION;
+use puffersecuresigner::strip_0x_prefix;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+/// Sends a raw HTTP/1.1 request over the unix socket at `path`, returning the status line and body
+async fn post_over_unix_socket(path: &std::path::Path, uri: &str, body: &str) -> (String, String) {
+    let mut stream = tokio::net::UnixStream::connect(path).await.unwrap();
+    let req = format!(
+        "POST {uri} HTTP/1.1\r\nHost: localhost\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{body}",
+        body.len()
+    );
+    stream.write_all(req.as_bytes()).await.unwrap();
+    let mut resp = String::new();
+    stream.read_to_string(&mut resp).await.unwrap();
+    let (head, body) = resp.split_once("\r\n\r\n").unwrap();
+    (head.lines().next().unwrap().to_string(), body.to_string())
+}
+
+#[tokio::test]
+pub async fn test_sign_over_unix_socket() {
+    let bls_pk_hex = common::setup_dummy_keypair();
+    let app = axum::Router::new()
+        .route(
+            "/api/v1/eth2/sign/:bls_pk_hex",
+            axum::routing::post(
+                puffersecuresigner::enclave::shared::handlers::secure_sign_bls::handler,
+            ),
+        )
+        .with_state(puffersecuresigner::enclave::shared::handlers::AppState {
+            genesis_fork_version: GENESIS_FORK_VERSION,
+            ..Default::default()
+        });
+
+    let path = std::env::temp_dir().join(format!("secure-signer-sign-{}.sock", std::process::id()));
+    let server = Server::bind(&ListenAddr::Unix(path.clone())).unwrap();
+    let (shutdown_tx, shutdown_rx) = tokio::sync::oneshot::channel::<()>();
+    let task = tokio::spawn(server.serve(app, Default::default(), async {
+        shutdown_rx.await.ok();
+    }));
+
+    let (status, body) = post_over_unix_socket(
+        &path,
+        &format!("/api/v1/eth2/sign/{bls_pk_hex}"),
+        &mock_randao_reveal_request(),
+    )
+    .await;
+    assert!(status.starts_with("HTTP/1.1 200"), "{status}: {body}");
+
+    // Same signature as over TCP
+    let resp: puffersecuresigner::enclave::types::SignatureResponse =
+        serde_json::from_str(&body).unwrap();
+    let got_sig: String = strip_0x_prefix!(resp.signature);
+    assert_eq!(got_sig, "a8c5eb481ef1c3ea345bc9cb9ce9918e18ef052d8287bacd3b1e1bbd34bc4e1e016602b778535d5b582bc35ea6d2ded106ea2cfec06f8b6c5bd049dbf0a544207ac3b21c634b8e78c2c0135a0000e961adae192203ef168de1edb83618d1a76d");
+
+    shutdown_tx.send(()).unwrap();
+    task.await.unwrap().unwrap();
+    std::fs::remove_file(path).unwrap();
+}
@@ -66,3 +66,147 @@ async fn test_aggregate_validator_registration_happy_path_test_vec() {
     let got_sig: String = strip_0x_prefix!(sig);
     assert_eq!(exp_sig.unwrap(), got_sig);
 }
+
+#[tokio::test]
+async fn test_validator_registration_fee_recipient_allowlist() {
+    use crate::signing_tests::bls_import::{
+        import_request, import_server_with, new_enclave_eth_key,
+    };
+    let _data_dir = puffersecuresigner::io::config::Config::scoped_temp().unwrap();
+
+    // Provision the allowlist when importing the key
+    let allowed = format!("0x{}", "2a".repeat(20));
+    let (eth_pk_hex, eth_pk) = new_enclave_eth_key();
+    let (bls_pk_hex, mut import) = import_request("hunter2", &eth_pk, eth_pk_hex);
+    import.fee_recipients = Some(vec![allowed.clone()]);
+    let resp = import_server_with(false)
+        .post("/eth/v1/keystores")
+        .json(&import)
+        .await;
+    assert_eq!(resp.status_code(), 200);
+
+    let sign_server = axum_test::TestServer::new(
+        axum::Router::new()
+            .route(
+                "/api/v1/eth2/sign/:bls_pk_hex",
+                axum::routing::post(
+                    puffersecuresigner::enclave::shared::handlers::secure_sign_bls::handler,
+                ),
+            )
+            .with_state(puffersecuresigner::enclave::shared::handlers::AppState {
+                genesis_fork_version: GENESIS_FORK_VERSION,
+                ..Default::default()
+            })
+            .into_make_service(),
+    )
+    .unwrap();
+    let uri = format!("/api/v1/eth2/sign/0x{bls_pk_hex}");
+
+    // The mock registers the allowed fee recipient
+    let req = mock_validator_registration_request();
+    assert!(req.contains(&allowed));
+    let resp = sign_server
+        .post(&uri)
+        .json(&serde_json::from_str::<serde_json::Value>(&req).unwrap())
+        .await;
+    assert_eq!(resp.status_code(), 200);
+
+    // Any other fee recipient is refused
+    let other = format!("0x{}", "bb".repeat(20));
+    let req = req.replace(&allowed, &other);
+    let resp = sign_server
+        .post(&uri)
+        .json(&serde_json::from_str::<serde_json::Value>(&req).unwrap())
+        .await;
+    assert_eq!(resp.status_code(), 403);
+    assert!(resp.text().contains(&other));
+}
+
+#[tokio::test]
+async fn test_validator_registration_gas_limit_bounds() {
+    let bls_pk_hex = common::setup_dummy_keypair();
+    let bounds = GasLimitBounds::default();
+    let sign_server = axum_test::TestServer::new(
+        axum::Router::new()
+            .route(
+                "/api/v1/eth2/sign/:bls_pk_hex",
+                axum::routing::post(
+                    puffersecuresigner::enclave::shared::handlers::secure_sign_bls::handler,
+                ),
+            )
+            .with_state(puffersecuresigner::enclave::shared::handlers::AppState {
+                genesis_fork_version: GENESIS_FORK_VERSION,
+                gas_limit_bounds: bounds,
+                ..Default::default()
+            })
+            .into_make_service(),
+    )
+    .unwrap();
+
+    for (gas_limit, status) in [
+        (bounds.min - 1, 400),
+        (bounds.min, 200),
+        (bounds.max, 200),
+        (bounds.max + 1, 400),
+        (278234191203, 400),
+    ] {
+        let mut req = validator_registration_request();
+        let BLSSignMsg::VALIDATOR_REGISTRATION(ref mut m) = req else {
+            unreachable!()
+        };
+        m.validator_registration.gas_limit = gas_limit;
+        // The mock's signingRoot is for a gas limit of 30M
+        m.signingRoot = None;
+        let resp = sign_server
+            .post(&format!("/api/v1/eth2/sign/{bls_pk_hex}"))
+            .json(&req)
+            .await;
+        assert_eq!(resp.status_code(), status, "gas_limit {gas_limit}");
+    }
+}
+
+#[tokio::test]
+async fn test_stale_validator_registration_is_rejected() {
+    let _data_dir = puffersecuresigner::io::config::Config::scoped_temp().unwrap();
+    let sk = puffersecuresigner::crypto::bls_keys::new_bls_key(0);
+    puffersecuresigner::crypto::bls_keys::save_bls_key(&sk).unwrap();
+    let bls_pk_hex = sk.public_keys().public_key().to_hex();
+    let sign_server = axum_test::TestServer::new(
+        axum::Router::new()
+            .route(
+                "/api/v1/eth2/sign/:bls_pk_hex",
+                axum::routing::post(
+                    puffersecuresigner::enclave::shared::handlers::secure_sign_bls::handler,
+                ),
+            )
+            .with_state(puffersecuresigner::enclave::shared::handlers::AppState {
+                genesis_fork_version: GENESIS_FORK_VERSION,
+                monotonic_registration_timestamps: true,
+                ..Default::default()
+            })
+            .into_make_service(),
+    )
+    .unwrap();
+    let registration_at = |timestamp: u64| {
+        let mut req = validator_registration_request();
+        let BLSSignMsg::VALIDATOR_REGISTRATION(ref mut m) = req else {
+            unreachable!()
+        };
+        m.validator_registration.timestamp = timestamp;
+        m.signingRoot = None;
+        req
+    };
+    let uri = format!("/api/v1/eth2/sign/0x{bls_pk_hex}");
+
+    let resp = sign_server.post(&uri).json(&registration_at(200)).await;
+    assert_eq!(resp.status_code(), 200);
+
+    // Older and replayed registrations are refused
+    for stale in [100, 200] {
+        let resp = sign_server.post(&uri).json(&registration_at(stale)).await;
+        assert_eq!(resp.status_code(), 412, "timestamp {stale}");
+    }
+
+    let resp = sign_server.post(&uri).json(&registration_at(201)).await;
+    assert_eq!(resp.status_code(), 200);
+}
@@ -0,0 +1,69 @@
+use puffersecuresigner::crypto::bls_keys::{bls_agg_sign, new_bls_key};
+use puffersecuresigner::enclave::types::{VerifyRequest, VerifyResponse};
+
+fn verify_server() -> axum_test::TestServer {
+    let test_app = axum::Router::new()
+        .route(
+            "/eth/v1/verify",
+            axum::routing::post(puffersecuresigner::enclave::shared::handlers::verify::handler),
+        )
+        .into_make_service();
+    axum_test::TestServer::new(test_app).unwrap()
+}
+
+fn signed_request(signing_root: [u8; 32]) -> VerifyRequest {
+    let sk_set = new_bls_key(0);
+    let sig = bls_agg_sign(&sk_set, &signing_root);
+    VerifyRequest {
+        pubkey: format!("0x{}", sk_set.public_keys().public_key().to_hex()),
+        signing_root: format!("0x{}", hex::encode(signing_root)),
+        signature: format!("0x{}", hex::encode(sig.to_bytes())),
+    }
+}
+
+async fn verify(req: &VerifyRequest) -> axum_test::TestResponse {
+    verify_server().post("/eth/v1/verify").json(req).await
+}
+
+#[tokio::test]
+async fn test_verify_valid_signature() {
+    let resp = verify(&signed_request([1; 32])).await;
+    assert_eq!(resp.status_code(), 200);
+    assert!(resp.json::<VerifyResponse>().valid);
+}
+
+#[tokio::test]
+async fn test_verify_invalid_signature() {
+    // Signed by another key
+    let mut req = signed_request([1; 32]);
+    req.signature = signed_request([1; 32]).signature;
+    let resp = verify(&req).await;
+    assert_eq!(resp.status_code(), 200);
+    assert!(!resp.json::<VerifyResponse>().valid);
+
+    // Over another message
+    let mut req = signed_request([1; 32]);
+    req.signing_root = format!("0x{}", hex::encode([2; 32]));
+    let resp = verify(&req).await;
+    assert_eq!(resp.status_code(), 200);
+    assert!(!resp.json::<VerifyResponse>().valid);
+}
+
+#[tokio::test]
+async fn test_verify_rejects_malformed_inputs() {
+    let bad_pubkey = VerifyRequest {
+        pubkey: "0xdeadbeef".into(),
+        ..signed_request([1; 32])
+    };
+    let bad_signature = VerifyRequest {
+        signature: format!("0x{}", hex::encode([0_u8; 96])),
+        ..signed_request([1; 32])
+    };
+    let bad_hex = VerifyRequest {
+        signing_root: "0xzz".into(),
+        ..signed_request([1; 32])
+    };
+    for req in [bad_pubkey, bad_signature, bad_hex] {
+        assert_eq!(verify(&req).await.status_code(), 400);
+    }
+}
@@ -0,0 +1,59 @@
+use puffersecuresigner::crypto::bls_keys::{bls_agg_sign, new_bls_key};
+use puffersecuresigner::enclave::types::{BatchVerifyItem, BatchVerifyResponse};
+
+fn verify_batch_server() -> axum_test::TestServer {
+    let test_app = axum::Router::new()
+        .route(
+            "/api/v1/eth2/verify/batch",
+            axum::routing::post(
+                puffersecuresigner::enclave::shared::handlers::verify_batch::handler,
+            ),
+        )
+        .into_make_service();
+    axum_test::TestServer::new(test_app).unwrap()
+}
+
+fn signed_item(signing_root: [u8; 32]) -> BatchVerifyItem {
+    let sk_set = new_bls_key(0);
+    let sig = bls_agg_sign(&sk_set, &signing_root);
+    BatchVerifyItem {
+        pubkey: format!("0x{}", sk_set.public_keys().public_key().to_hex()),
+        signing_root: format!("0x{}", hex::encode(signing_root)),
+        signature: format!("0x{}", hex::encode(sig.to_bytes())),
+    }
+}
+
+#[tokio::test]
+async fn test_verify_batch_all_valid() {
+    let items: Vec<BatchVerifyItem> = (0..3_u8).map(|i| signed_item([i; 32])).collect();
+    let resp = verify_batch_server()
+        .post("/api/v1/eth2/verify/batch")
+        .json(&items)
+        .await;
+    assert_eq!(resp.status_code(), 200);
+
+    let resp: BatchVerifyResponse = resp.json();
+    assert!(resp.batch_verified);
+    assert!(resp.data.iter().all(|r| r.valid));
+}
+
+#[tokio::test]
+async fn test_verify_batch_mixed_entries() {
+    let mut items: Vec<BatchVerifyItem> = (0..4_u8).map(|i| signed_item([i; 32])).collect();
+    // Signature over another root
+    items[1].signing_root = items[0].signing_root.clone();
+    // Malformed signature hex
+    items[3].signature = "0xnothex".to_string();
+
+    let resp = verify_batch_server()
+        .post("/api/v1/eth2/verify/batch")
+        .json(&items)
+        .await;
+    assert_eq!(resp.status_code(), 200);
+
+    let resp: BatchVerifyResponse = resp.json();
+    assert!(!resp.batch_verified);
+    let valid: Vec<bool> = resp.data.iter().map(|r| r.valid).collect();
+    assert_eq!(valid, vec![true, false, true, false]);
+    assert_eq!(resp.data[2].pubkey, items[2].pubkey);
+}
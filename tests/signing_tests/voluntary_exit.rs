@@ -0,0 +1,111 @@
+use crate::common::bls_keygen_helper::register_new_bls_key;
+use crate::common::signing_helper::*;
+use puffersecuresigner::enclave::shared::handlers::AppState;
+use puffersecuresigner::eth2::eth_signing::*;
+use puffersecuresigner::eth2::eth_types::*;
+
+const CURRENT_EPOCH: u64 = 1000;
+const MIN_EXIT_EPOCH_DISTANCE: u64 = 256;
+
+/// Voluntary exit on mainnet, under Deneb
+fn voluntary_exit_request(epoch: u64) -> BLSSignMsg {
+    voluntary_exit_request_on("0x04000000", epoch)
+}
+
+fn voluntary_exit_request_on(current_version: &str, epoch: u64) -> BLSSignMsg {
+    let req = format!(
+        r#"
+        {{
+            "fork_info":{{
+                "fork":{{
+                    "previous_version":"0x03000000",
+                    "current_version":"{current_version}",
+                    "epoch":"750"
+                }},
+                "genesis_validators_root":"0x2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a"
+            }},
+            "voluntary_exit": {{
+                "epoch": "{epoch}",
+                "validator_index": "0"
+            }}
+        }}"#
+    );
+    let signing_data: VoluntaryExitRequest = serde_json::from_str(&req).unwrap();
+    BLSSignMsg::VOLUNTARY_EXIT(signing_data)
+}
+
+/// State whose chain started CURRENT_EPOCH epochs ago (plus half an epoch of slack)
+fn state_with_min_exit_epoch_distance() -> AppState {
+    let now = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .unwrap()
+        .as_secs();
+    let epoch_secs = SLOTS_PER_EPOCH * SECONDS_PER_SLOT;
+    AppState {
+        genesis_fork_version: GENESIS_FORK_VERSION,
+        min_exit_epoch_distance: Some(MIN_EXIT_EPOCH_DISTANCE),
+        genesis_time: now - CURRENT_EPOCH * epoch_secs - epoch_secs / 2,
+        ..Default::default()
+    }
+}
+
+#[tokio::test]
+async fn test_exit_just_after_current_epoch_is_rejected() {
+    let bls_pk_hex = register_new_bls_key(None).await.pk_hex;
+    let req = voluntary_exit_request(CURRENT_EPOCH + 1);
+    let resp =
+        mock_secure_sign_route_with_state(&bls_pk_hex, req, state_with_min_exit_epoch_distance())
+            .await
+            .unwrap();
+    assert_eq!(resp.status_code(), 403);
+}
+
+#[tokio::test]
+async fn test_far_future_exit_is_allowed() {
+    let bls_pk_hex = register_new_bls_key(None).await.pk_hex;
+    let req = voluntary_exit_request(CURRENT_EPOCH + 10 * MIN_EXIT_EPOCH_DISTANCE);
+    let resp =
+        mock_secure_sign_route_with_state(&bls_pk_hex, req, state_with_min_exit_epoch_distance())
+            .await
+            .unwrap();
+    assert_eq!(resp.status_code(), 200);
+}
+
+#[tokio::test]
+async fn test_exit_distance_not_enforced_by_default() {
+    let bls_pk_hex = register_new_bls_key(None).await.pk_hex;
+    let req = voluntary_exit_request(CURRENT_EPOCH + 1);
+    let (_resp, status) = make_signing_route_request(req, &bls_pk_hex, None)
+        .await
+        .unwrap();
+    assert_eq!(status, 200);
+}
+
+#[tokio::test]
+async fn test_exit_under_fulu_is_signed() {
+    let bls_pk_hex = register_new_bls_key(None).await.pk_hex;
+    let req = voluntary_exit_request_on("0x06000000", CURRENT_EPOCH + 1);
+    let expected_root = voluntary_exit_request(CURRENT_EPOCH + 1)
+        .checked_signing_root(Some(GENESIS_FORK_VERSION))
+        .unwrap();
+    let (resp, status) = make_signing_route_request(req, &bls_pk_hex, None)
+        .await
+        .unwrap();
+    assert_eq!(status, 200);
+    // Signed under the Capella domain like every exit since Deneb
+    let sig = resp.unwrap().to_ssz_bytes().unwrap();
+    let sig = blsttc::Signature::from_bytes(sig[..].try_into().unwrap()).unwrap();
+    let pk = blsttc::PublicKey::from_hex(bls_pk_hex.trim_start_matches("0x")).unwrap();
+    assert!(pk.verify(&sig, expected_root));
+}
+
+#[tokio::test]
+async fn test_exit_under_unknown_fork_is_rejected() {
+    let bls_pk_hex = register_new_bls_key(None).await.pk_hex;
+    // Not a fork of the signer's network, so it can't be told whether EIP-7044 applies
+    let req = voluntary_exit_request_on("0x80000071", CURRENT_EPOCH + 1);
+    let (_resp, status) = make_signing_route_request(req, &bls_pk_hex, None)
+        .await
+        .unwrap();
+    assert_eq!(status, 400);
+}